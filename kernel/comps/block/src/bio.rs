@@ -92,7 +92,10 @@ impl Bio {
         );
         assert!(result.is_ok());
 
-        if let Err(e) = block_device.enqueue(SubmittedBio(self.0.clone())) {
+        if let Err(e) = block_device.enqueue(SubmittedBio {
+            inner: self.0.clone(),
+            sid_range: self.0.sid_range().clone(),
+        }) {
             // Fail to submit, revert the status.
             let result = self.0.status.compare_exchange(
                 BioStatus::Submit as u32,
@@ -243,27 +246,41 @@ impl Default for BioWaiter {
 ///
 /// The request queue of block device only accepts a `SubmittedBio` into the queue.
 #[derive(Debug)]
-pub struct SubmittedBio(Arc<BioInner>);
+pub struct SubmittedBio {
+    inner: Arc<BioInner>,
+    /// The range of the sector id on the device that the `Bio` is submitted to.
+    ///
+    /// It differs from the range of the `Bio` if the `Bio` is remapped (e.g., by a partition).
+    sid_range: Range<Sid>,
+}
 
 impl SubmittedBio {
     /// Returns the type.
     pub fn type_(&self) -> BioType {
-        self.0.type_()
+        self.inner.type_()
     }
 
     /// Returns the range of target sectors on the device.
     pub fn sid_range(&self) -> &Range<Sid> {
-        self.0.sid_range()
+        &self.sid_range
+    }
+
+    /// Remaps the target sectors by adding `offset` sectors.
+    ///
+    /// This is used by a block device that forwards the `SubmittedBio` to another block device,
+    /// e.g., a partition that forwards it to the whole disk.
+    pub fn remap_sid_range(&mut self, offset: u64) {
+        self.sid_range = self.sid_range.start + offset..self.sid_range.end + offset;
     }
 
     /// Returns the slice to the memory segments.
     pub fn segments(&self) -> &[BioSegment] {
-        self.0.segments()
+        self.inner.segments()
     }
 
     /// Returns the status.
     pub fn status(&self) -> BioStatus {
-        self.0.status()
+        self.inner.status()
     }
 
     /// Completes the `Bio` with the `status` and invokes the callback function.
@@ -273,7 +290,7 @@ impl SubmittedBio {
         assert!(status != BioStatus::Init && status != BioStatus::Submit);
 
         // Set the status.
-        let result = self.0.status.compare_exchange(
+        let result = self.inner.status.compare_exchange(
             BioStatus::Submit as u32,
             status as u32,
             Ordering::Release,
@@ -281,8 +298,8 @@ impl SubmittedBio {
         );
         assert!(result.is_ok());
 
-        self.0.wait_queue.wake_all();
-        if let Some(complete_fn) = self.0.complete_fn {
+        self.inner.wait_queue.wake_all();
+        if let Some(complete_fn) = self.inner.complete_fn {
             complete_fn(self);
         }
     }
@@ -37,6 +37,7 @@ extern crate alloc;
 pub mod bio;
 pub mod id;
 mod impl_block_device;
+pub mod partition;
 mod prelude;
 pub mod request_queue;
 
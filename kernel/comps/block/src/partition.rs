@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Partitions of block devices.
+//!
+//! This module discovers the partitions in the MBR (Master Boot Record) or the GPT (GUID
+//! Partition Table) of a block device, and exposes each partition as a separate block device.
+//!
+//! References:
+//!  - <https://en.wikipedia.org/wiki/Master_boot_record>
+//!  - <https://en.wikipedia.org/wiki/GUID_Partition_Table>
+
+use ostd::mm::VmIo;
+
+use crate::{
+    bio::{BioEnqueueError, SubmittedBio},
+    id::Sid,
+    prelude::*,
+    BlockDevice, BlockDeviceMeta, SECTOR_SIZE,
+};
+
+/// The information of a partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionInfo {
+    /// The partition number, which starts from one.
+    ///
+    /// Like Linux, MBR primary partitions are numbered from 1 to 4 according to their slots in
+    /// the partition table, and MBR logical partitions are numbered from 5.
+    pub number: usize,
+    /// The first sector of the partition.
+    pub start_sid: Sid,
+    /// The number of sectors of the partition.
+    pub nr_sectors: usize,
+}
+
+/// A partition of a block device.
+///
+/// I/O requests to the partition are forwarded to the whole disk.
+#[derive(Debug)]
+pub struct Partition {
+    disk: Arc<dyn BlockDevice>,
+    info: PartitionInfo,
+}
+
+impl Partition {
+    /// Creates a partition of the disk.
+    pub fn new(disk: Arc<dyn BlockDevice>, info: PartitionInfo) -> Self {
+        Self { disk, info }
+    }
+
+    /// Returns the whole disk.
+    pub fn disk(&self) -> &Arc<dyn BlockDevice> {
+        &self.disk
+    }
+
+    /// Returns the information of the partition.
+    pub fn info(&self) -> &PartitionInfo {
+        &self.info
+    }
+}
+
+impl BlockDevice for Partition {
+    fn enqueue(&self, mut bio: SubmittedBio) -> Result<(), BioEnqueueError> {
+        if bio.sid_range().end.to_raw() > self.info.nr_sectors as u64 {
+            return Err(BioEnqueueError::Refused);
+        }
+
+        bio.remap_sid_range(self.info.start_sid.to_raw());
+        self.disk.enqueue(bio)
+    }
+
+    fn metadata(&self) -> BlockDeviceMeta {
+        BlockDeviceMeta {
+            max_nr_segments_per_bio: self.disk.metadata().max_nr_segments_per_bio,
+            nr_sectors: self.info.nr_sectors,
+        }
+    }
+}
+
+/// Parses the partition table of the disk.
+///
+/// An empty vector is returned if the disk has no valid partition table.
+pub fn parse_partitions(disk: &dyn BlockDevice) -> Vec<PartitionInfo> {
+    let mut mbr = [0u8; SECTOR_SIZE];
+    if disk.read_bytes(0, &mut mbr).is_err() || mbr[MBR_SIGNATURE_OFFSET..] != MBR_SIGNATURE {
+        return Vec::new();
+    }
+
+    // Like Linux, the boot indicators are checked to tell an MBR from the boot sector of a
+    // filesystem (e.g., FAT), which has the same signature.
+    if (0..MBR_NR_PRIMARY)
+        .map(|index| mbr[MBR_ENTRIES_OFFSET + index * MBR_ENTRY_LEN])
+        .any(|boot_indicator| boot_indicator != 0x00 && boot_indicator != 0x80)
+    {
+        return Vec::new();
+    }
+
+    let entries = mbr_entries(&mbr);
+
+    // A protective MBR, which contains a single partition covering the whole disk, indicates the
+    // presence of a GPT.
+    let mut partitions = if entries
+        .iter()
+        .any(|entry| entry.type_ == MBR_TYPE_GPT_PROTECTIVE)
+    {
+        parse_gpt(disk).unwrap_or_default()
+    } else {
+        parse_mbr(disk, &entries)
+    };
+
+    let nr_disk_sectors = disk.metadata().nr_sectors as u64;
+    partitions.retain(|info| {
+        let is_valid = info.start_sid.to_raw() + info.nr_sectors as u64 <= nr_disk_sectors;
+        if !is_valid {
+            log::warn!("partition {} exceeds the disk", info.number);
+        }
+        is_valid
+    });
+
+    partitions
+}
+
+const MBR_ENTRIES_OFFSET: usize = 446;
+const MBR_ENTRY_LEN: usize = 16;
+const MBR_NR_PRIMARY: usize = 4;
+const MBR_SIGNATURE_OFFSET: usize = 510;
+const MBR_SIGNATURE: [u8; 2] = [0x55, 0xaa];
+
+const MBR_TYPE_EMPTY: u8 = 0x00;
+const MBR_TYPE_GPT_PROTECTIVE: u8 = 0xee;
+const MBR_TYPES_EXTENDED: [u8; 3] = [0x05, 0x0f, 0x85];
+
+/// The maximum number of logical partitions, which prevents looping on corrupted EBR chains.
+const MBR_MAX_LOGICAL: usize = 64;
+
+/// An entry in the partition table of an MBR or an EBR (Extended Boot Record).
+#[derive(Debug, Clone, Copy)]
+struct MbrEntry {
+    type_: u8,
+    /// The first sector, which is relative to the start of the table's container.
+    start: u32,
+    nr_sectors: u32,
+}
+
+impl MbrEntry {
+    fn is_extended(&self) -> bool {
+        MBR_TYPES_EXTENDED.contains(&self.type_)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.type_ == MBR_TYPE_EMPTY || self.nr_sectors == 0
+    }
+}
+
+fn mbr_entries(sector: &[u8; SECTOR_SIZE]) -> [MbrEntry; MBR_NR_PRIMARY] {
+    core::array::from_fn(|index| {
+        let entry = &sector[MBR_ENTRIES_OFFSET + index * MBR_ENTRY_LEN..][..MBR_ENTRY_LEN];
+        MbrEntry {
+            type_: entry[4],
+            start: u32::from_le_bytes(entry[8..12].try_into().unwrap()),
+            nr_sectors: u32::from_le_bytes(entry[12..16].try_into().unwrap()),
+        }
+    })
+}
+
+fn parse_mbr(disk: &dyn BlockDevice, entries: &[MbrEntry; MBR_NR_PRIMARY]) -> Vec<PartitionInfo> {
+    let mut partitions = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.is_empty() {
+            continue;
+        }
+
+        if entry.is_extended() {
+            parse_ebr_chain(disk, entry.start as u64, &mut partitions);
+            continue;
+        }
+
+        partitions.push(PartitionInfo {
+            number: index + 1,
+            start_sid: Sid::new(entry.start as u64),
+            nr_sectors: entry.nr_sectors as usize,
+        });
+    }
+
+    partitions.sort_by_key(|info| info.number);
+    partitions
+}
+
+/// Parses the logical partitions in the EBR chain of the extended partition.
+fn parse_ebr_chain(
+    disk: &dyn BlockDevice,
+    extended_start: u64,
+    partitions: &mut Vec<PartitionInfo>,
+) {
+    let mut ebr_start = extended_start;
+    let mut ebr = [0u8; SECTOR_SIZE];
+
+    for _ in 0..MBR_MAX_LOGICAL {
+        if disk
+            .read_bytes(ebr_start as usize * SECTOR_SIZE, &mut ebr)
+            .is_err()
+            || ebr[MBR_SIGNATURE_OFFSET..] != MBR_SIGNATURE
+        {
+            return;
+        }
+
+        // The first entry describes the logical partition relative to the EBR, and the second
+        // entry points to the next EBR relative to the extended partition.
+        let [logical, next, ..] = mbr_entries(&ebr);
+
+        if !logical.is_empty() {
+            let number = partitions
+                .iter()
+                .map(|info| info.number)
+                .max()
+                .unwrap_or(0)
+                .max(MBR_NR_PRIMARY)
+                + 1;
+            partitions.push(PartitionInfo {
+                number,
+                start_sid: Sid::new(ebr_start + logical.start as u64),
+                nr_sectors: logical.nr_sectors as usize,
+            });
+        }
+
+        if next.is_empty() || !next.is_extended() {
+            return;
+        }
+        ebr_start = extended_start + next.start as u64;
+    }
+}
+
+const GPT_HEADER_SID: usize = 1;
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+/// The maximum number of GPT entries, which is the number of entries of a typical GPT.
+const GPT_MAX_ENTRIES: usize = 128;
+const GPT_MIN_ENTRY_LEN: usize = 128;
+
+/// Parses the partitions in the GPT.
+///
+/// The CRC32 checksums and the backup GPT are not checked yet.
+fn parse_gpt(disk: &dyn BlockDevice) -> Option<Vec<PartitionInfo>> {
+    let mut header = [0u8; SECTOR_SIZE];
+    disk.read_bytes(GPT_HEADER_SID * SECTOR_SIZE, &mut header)
+        .ok()?;
+    if &header[0..8] != GPT_SIGNATURE {
+        return None;
+    }
+
+    let entries_sid = u64::from_le_bytes(header[72..80].try_into().unwrap()) as usize;
+    let nr_entries = u32::from_le_bytes(header[80..84].try_into().unwrap()) as usize;
+    let entry_len = u32::from_le_bytes(header[84..88].try_into().unwrap()) as usize;
+    if entry_len < GPT_MIN_ENTRY_LEN || !entry_len.is_power_of_two() {
+        return None;
+    }
+    let nr_entries = nr_entries.min(GPT_MAX_ENTRIES);
+
+    let mut entries = vec![0u8; (nr_entries * entry_len).next_multiple_of(SECTOR_SIZE)];
+    disk.read_bytes(entries_sid.checked_mul(SECTOR_SIZE)?, &mut entries)
+        .ok()?;
+
+    let partitions = entries
+        .chunks_exact(entry_len)
+        .take(nr_entries)
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            // An all-zero type GUID indicates an unused entry.
+            if entry[0..16].iter().all(|byte| *byte == 0) {
+                return None;
+            }
+
+            let first = u64::from_le_bytes(entry[32..40].try_into().unwrap());
+            let last = u64::from_le_bytes(entry[40..48].try_into().unwrap());
+            if first > last {
+                return None;
+            }
+
+            Some(PartitionInfo {
+                number: index + 1,
+                start_sid: Sid::new(first),
+                nr_sectors: (last - first + 1) as usize,
+            })
+        })
+        .collect();
+
+    Some(partitions)
+}
@@ -23,6 +23,9 @@ pub enum InputEvent {
 }
 
 pub trait InputDevice: Send + Sync + Any + Debug {
+    /// Returns the name of the device reported by the hardware (e.g., "QEMU Virtio Keyboard").
+    fn name(&self) -> &str;
+
     fn register_callbacks(&self, function: &'static (dyn Fn(InputEvent) + Send + Sync));
 }
 
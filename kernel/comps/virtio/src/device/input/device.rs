@@ -1,12 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{
-    boxed::Box,
-    string::{String, ToString},
-    sync::Arc,
-    vec::Vec,
+use alloc::{boxed::Box, format, string::String, sync::Arc, vec::Vec};
+use core::{
+    fmt::Debug,
+    iter, mem,
+    sync::atomic::{AtomicUsize, Ordering},
 };
-use core::{fmt::Debug, iter, mem};
 
 use aster_input::{
     key::{Key, KeyStatus},
@@ -74,6 +73,7 @@ pub struct InputDevice {
     event_queue: SpinLock<VirtQueue>,
     status_queue: VirtQueue,
     event_table: EventTable,
+    name: String,
     #[expect(clippy::type_complexity)]
     callbacks: RwLock<Vec<Arc<dyn Fn(InputEvent) + Send + Sync + 'static>>, LocalIrqDisabled>,
     transport: SpinLock<Box<dyn VirtioTransport>>,
@@ -102,17 +102,19 @@ impl InputDevice {
             }
         }
 
-        let device = Arc::new(Self {
+        let mut device = Self {
             config: VirtioInputConfig::new(transport.as_mut()),
             event_queue: SpinLock::new(event_queue),
             status_queue,
             event_table,
             transport: SpinLock::new(transport),
+            name: String::new(),
             callbacks: RwLock::new(Vec::new()),
-        });
+        };
 
-        let name = device.query_config_id_name();
-        info!("Virtio input device name:{}", name);
+        device.name = device.query_config_id_name();
+        info!("Virtio input device name:{}", device.name);
+        let device = Arc::new(device);
 
         let input_prop = device.query_config_prop_bits();
         if let Some(prop) = input_prop {
@@ -140,7 +142,11 @@ impl InputDevice {
         transport.finish_init();
         drop(transport);
 
-        aster_input::register_device(super::DEVICE_NAME.to_string(), device);
+        // Each device needs a unique name, or it will replace the previous one in the device
+        // table.
+        static DEVICE_INDEX_ALLOCATOR: AtomicUsize = AtomicUsize::new(0);
+        let index = DEVICE_INDEX_ALLOCATOR.fetch_add(1, Ordering::Relaxed);
+        aster_input::register_device(format!("{}{}", super::DEVICE_NAME, index), device);
 
         Ok(())
     }
@@ -215,21 +221,28 @@ impl InputDevice {
             let event: VirtioInputEvent = event.read().unwrap();
 
             match event.event_type {
-                0 => return false,
+                // Synchronization events are generated by the consumers of the input events.
+                0 => return true,
                 // Keyboard
                 1 => {}
                 // TODO: Support mouse device.
                 _ => return true,
             }
 
+            // Auto-repeated keys (whose value is 2) are tracked by the consumers of the input
+            // events, which know whether the keys are already pressed.
             let status = match event.value {
-                1 => KeyStatus::Pressed,
+                1 | 2 => KeyStatus::Pressed,
                 0 => KeyStatus::Released,
-                _ => return false,
+                _ => return true,
             };
 
-            let event = InputEvent::KeyBoard(Key::try_from(event.code).unwrap(), status);
-            info!("Input Event:{:?}", event);
+            let Ok(key) = Key::try_from(event.code) else {
+                debug!("unknown key code: {}", event.code);
+                return true;
+            };
+            let event = InputEvent::KeyBoard(key, status);
+            debug!("Input Event:{:?}", event);
 
             for callback in callbacks.iter() {
                 callback(event);
@@ -293,6 +306,10 @@ impl<T, M: HasDaddr> DmaBuf for SafePtr<T, M> {
 }
 
 impl aster_input::InputDevice for InputDevice {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
     fn register_callbacks(&self, function: &'static (dyn Fn(InputEvent) + Send + Sync)) {
         self.callbacks.write().push(Arc::new(function))
     }
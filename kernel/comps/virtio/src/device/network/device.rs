@@ -1,9 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{
-    boxed::Box, collections::linked_list::LinkedList, string::ToString, sync::Arc, vec::Vec,
+use alloc::{boxed::Box, collections::linked_list::LinkedList, format, sync::Arc, vec::Vec};
+use core::{
+    fmt::Debug,
+    mem::size_of,
+    sync::atomic::{AtomicUsize, Ordering},
 };
-use core::{fmt::Debug, mem::size_of};
 
 use aster_bigtcp::device::{Checksum, DeviceCapabilities, Medium};
 use aster_network::{
@@ -145,8 +147,12 @@ impl NetworkDevice {
 
         device.transport.finish_init();
 
+        // Each device needs a unique name, or it will replace the previous one in the device
+        // table.
+        static DEVICE_INDEX_ALLOCATOR: AtomicUsize = AtomicUsize::new(0);
+        let index = DEVICE_INDEX_ALLOCATOR.fetch_add(1, Ordering::Relaxed);
         aster_network::register_device(
-            super::DEVICE_NAME.to_string(),
+            format!("{}{}", super::DEVICE_NAME, index),
             Arc::new(SpinLock::new(device)),
         );
         Ok(())
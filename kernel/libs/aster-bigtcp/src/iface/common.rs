@@ -15,11 +15,11 @@ use ostd::sync::{SpinLock, SpinLockGuard};
 use smoltcp::{
     iface::{packet::Packet, Context},
     phy::Device,
-    wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv4Packet},
+    wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv4Cidr, Ipv6Address},
 };
 
 use super::{
-    poll::{FnHelper, PollContext, SocketTableAction},
+    poll::{FnHelper, IpPacket, PollContext, SocketTableAction},
     poll_iface::PollableIface,
     port::BindPortConfig,
     time::get_network_timestamp,
@@ -86,10 +86,34 @@ impl<E: Ext> IfaceCommon<E> {
         self.interface.lock().ipv4_addr()
     }
 
+    pub(super) fn ipv6_addr(&self) -> Option<Ipv6Address> {
+        self.interface.lock().ipv6_addr()
+    }
+
     pub(super) fn prefix_len(&self) -> Option<u8> {
         self.interface.lock().prefix_len()
     }
 
+    pub(super) fn has_ip_addr(&self, addr: IpAddress) -> bool {
+        self.interface.lock().has_ip_addr(addr)
+    }
+
+    pub(super) fn is_on_link(&self, addr: IpAddress) -> bool {
+        self.interface.lock().is_on_link(addr)
+    }
+
+    pub(super) fn set_ipv4_cidr(&self, cidr: Option<Ipv4Cidr>) {
+        self.interface.lock().set_ipv4_cidr(cidr)
+    }
+
+    pub(super) fn ipv4_gateway(&self) -> Option<Ipv4Address> {
+        self.interface.lock().ipv4_gateway()
+    }
+
+    pub(super) fn set_ipv4_gateway(&self, gateway: Option<Ipv4Address>) {
+        self.interface.lock().set_ipv4_gateway(gateway)
+    }
+
     pub(super) fn sched_poll(&self) -> &E::ScheduleNextPoll {
         &self.sched_poll
     }
@@ -120,10 +144,11 @@ impl<E: Ext> IfaceCommon<E> {
     pub(super) fn bind(
         &self,
         iface: Arc<dyn Iface<E>>,
+        addr: IpAddress,
         config: BindPortConfig,
     ) -> core::result::Result<BoundPort<E>, BindError> {
         let port = self.bind_port(config)?;
-        Ok(BoundPort { iface, port })
+        Ok(BoundPort { iface, addr, port })
     }
 
     /// Allocates an unused ephemeral port.
@@ -211,7 +236,7 @@ impl<E: Ext> IfaceCommon<E> {
             &'pkt [u8],
             &'cx mut Context,
             D::TxToken<'tx>,
-            Option<(Ipv4Packet<&'pkt [u8]>, D::TxToken<'tx>)>,
+            Option<(IpPacket<'pkt>, D::TxToken<'tx>)>,
         >,
         Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
     {
@@ -248,9 +273,11 @@ impl<E: Ext> IfaceCommon<E> {
 ///
 /// When dropped, the port is automatically released.
 //
-// FIXME: TCP and UDP ports are independent. Find a way to track the protocol here.
+// FIXME: TCP and UDP ports are independent, and so are IPv4 and IPv6 ports. Find a way to track
+// the protocol and the IP version here.
 pub struct BoundPort<E: Ext> {
     iface: Arc<dyn Iface<E>>,
+    addr: IpAddress,
     port: u16,
 }
 
@@ -266,12 +293,16 @@ impl<E: Ext> BoundPort<E> {
     }
 
     /// Returns the bound endpoint.
-    pub fn endpoint(&self) -> Option<IpEndpoint> {
-        let ip_addr = {
-            let ipv4_addr = self.iface().ipv4_addr()?;
-            IpAddress::Ipv4(ipv4_addr)
-        };
-        Some(IpEndpoint::new(ip_addr, self.port))
+    pub fn endpoint(&self) -> IpEndpoint {
+        IpEndpoint::new(self.addr, self.port)
+    }
+
+    /// Returns whether the bound address still belongs to the iface.
+    ///
+    /// Addresses can be removed from the iface at runtime, e.g., by netlink requests or when a
+    /// DHCP lease expires.
+    pub fn is_addr_available(&self) -> bool {
+        self.iface.common().has_ip_addr(self.addr)
     }
 }
 
@@ -2,7 +2,7 @@
 
 use alloc::sync::Arc;
 
-use smoltcp::wire::Ipv4Address;
+use smoltcp::wire::{IpAddress, Ipv4Address, Ipv4Cidr, Ipv6Address};
 
 use super::{port::BindPortConfig, BoundPort, InterfaceFlags, InterfaceType};
 use crate::{errors::BindError, ext::Ext};
@@ -25,7 +25,7 @@ impl<E: Ext> dyn Iface<E> {
     /// Binds a socket to the iface.
     ///
     /// After binding the socket to the iface, the iface will handle all packets to and from the
-    /// socket.
+    /// socket. `addr` is the local address of the socket, which should belong to the iface.
     ///
     /// If [`BindPortConfig::Ephemeral`] is specified, the iface will pick up an ephemeral port for
     /// the socket.
@@ -35,10 +35,11 @@ impl<E: Ext> dyn Iface<E> {
     /// <https://github.com/smoltcp-rs/smoltcp/issues/779>.
     pub fn bind(
         self: &Arc<Self>,
+        addr: IpAddress,
         config: BindPortConfig,
     ) -> core::result::Result<BoundPort<E>, BindError> {
         let common = self.common();
-        common.bind(self.clone(), config)
+        common.bind(self.clone(), addr, config)
     }
 
     /// Returns the interface index.
@@ -70,6 +71,18 @@ impl<E: Ext> dyn Iface<E> {
         self.common().ipv4_addr()
     }
 
+    /// Gets the IPv6 address of the iface, if any.
+    ///
+    /// FIXME: One iface may have multiple IPv6 addresses.
+    pub fn ipv6_addr(&self) -> Option<Ipv6Address> {
+        self.common().ipv6_addr()
+    }
+
+    /// Returns whether the IP address belongs to the iface.
+    pub fn has_ip_addr(&self, addr: IpAddress) -> bool {
+        self.common().has_ip_addr(addr)
+    }
+
     /// Retrieves the prefix length of the interface's IPv4 address.
     ///
     /// Both [`Self::ipv4_addr`] and this method will either return `Some(_)`
@@ -78,6 +91,32 @@ impl<E: Ext> dyn Iface<E> {
         self.common().prefix_len()
     }
 
+    /// Returns whether the IP address is on the link of the iface.
+    ///
+    /// An address is on the link if it is in the same subnet as one of the iface's addresses, so
+    /// packets to it can be sent directly without going through a router.
+    pub fn is_on_link(&self, addr: IpAddress) -> bool {
+        self.common().is_on_link(addr)
+    }
+
+    /// Sets the IPv4 address of the iface, replacing the old one (if any).
+    ///
+    /// If `cidr` is `None`, the IPv4 address is removed. Sockets bound to the old address are not
+    /// closed, but they will no longer be able to send or receive packets.
+    pub fn set_ipv4_cidr(&self, cidr: Option<Ipv4Cidr>) {
+        self.common().set_ipv4_cidr(cidr)
+    }
+
+    /// Gets the IPv4 default gateway of the iface, if any.
+    pub fn ipv4_gateway(&self) -> Option<Ipv4Address> {
+        self.common().ipv4_gateway()
+    }
+
+    /// Sets or removes the IPv4 default gateway of the iface.
+    pub fn set_ipv4_gateway(&self, gateway: Option<Ipv4Address>) {
+        self.common().set_ipv4_gateway(gateway)
+    }
+
     /// Returns a reference to the associated [`ScheduleNextPoll`].
     pub fn sched_poll(&self) -> &E::ScheduleNextPoll {
         self.common().sched_poll()
@@ -12,7 +12,7 @@ mod time;
 
 pub use common::{BoundPort, InterfaceFlags, InterfaceType};
 pub use iface::Iface;
-pub use phy::{EtherIface, IpIface};
+pub use phy::{EtherIface, IpIface, Ipv4Config};
 pub(crate) use poll_iface::{PollKey, PollableIfaceMut};
 pub use port::BindPortConfig;
 pub use sched::ScheduleNextPoll;
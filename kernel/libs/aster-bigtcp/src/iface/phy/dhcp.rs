@@ -0,0 +1,344 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A minimal DHCPv4 client.
+//!
+//! The client follows the state machine in
+//! <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4>, except that all messages are
+//! broadcast. This way, no address resolution is needed, and a renewal is always performed in the
+//! same way as a rebinding.
+
+use alloc::{vec, vec::Vec};
+
+use aster_softirq::BottomHalfDisabled;
+use jhash::jhash_3vals;
+use ostd::sync::SpinLock;
+use smoltcp::{
+    time::{Duration, Instant},
+    wire::{
+        DhcpMessageType, DhcpPacket, DhcpRepr, EthernetAddress, IpProtocol, Ipv4Address, Ipv4Cidr,
+        Ipv4Packet, UdpPacket, DHCP_CLIENT_PORT, DHCP_SERVER_PORT,
+    },
+};
+
+/// The options that we ask the server to provide.
+///
+/// They are the subnet mask (1), the router (3), and the DNS servers (6).
+const PARAMETER_REQUEST_LIST: &[u8] = &[1, 3, 6];
+
+/// The initial interval to retransmit a message.
+const INITIAL_RETRY_INTERVAL: Duration = Duration::from_secs(4);
+
+/// The maximum interval to retransmit a message.
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(64);
+
+/// The number of times to send a `DHCPREQUEST` before restarting from `DHCPDISCOVER`.
+const MAX_REQUEST_RETRIES: u32 = 3;
+
+/// The IPv4 configuration leased from a DHCP server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct DhcpLease {
+    pub(super) cidr: Ipv4Cidr,
+    pub(super) gateway: Option<Ipv4Address>,
+}
+
+/// A change of the IPv4 configuration that should be applied to the iface.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum DhcpEvent {
+    Configured(DhcpLease),
+    Deconfigured,
+}
+
+/// A message that should be broadcast to the DHCP servers.
+pub(super) struct DhcpMessage {
+    /// The source IP address, which is unspecified unless a lease is being renewed.
+    pub(super) src_addr: Ipv4Address,
+    /// The UDP payload.
+    pub(super) payload: Vec<u8>,
+}
+
+pub(super) struct DhcpClient {
+    ether_addr: EthernetAddress,
+    state: SpinLock<DhcpState, BottomHalfDisabled>,
+}
+
+struct DhcpState {
+    phase: DhcpPhase,
+    transaction_id: u32,
+    /// The time to (re)transmit the message of the current phase.
+    retry_at: Instant,
+    retry_count: u32,
+    /// The event that has not been taken by the iface.
+    event: Option<DhcpEvent>,
+}
+
+#[derive(Clone, Copy)]
+enum DhcpPhase {
+    Discovering,
+    Requesting {
+        server_id: Ipv4Address,
+        requested_ip: Ipv4Address,
+    },
+    Bound {
+        lease: DhcpLease,
+        renew_at: Instant,
+        expire_at: Instant,
+    },
+    Renewing {
+        lease: DhcpLease,
+        expire_at: Instant,
+    },
+}
+
+impl DhcpClient {
+    pub(super) fn new(ether_addr: EthernetAddress, now: Instant) -> Self {
+        let state = DhcpState {
+            phase: DhcpPhase::Discovering,
+            transaction_id: new_transaction_id(ether_addr, now),
+            retry_at: now,
+            retry_count: 0,
+            event: None,
+        };
+
+        Self {
+            ether_addr,
+            state: SpinLock::new(state),
+        }
+    }
+
+    /// Processes an incoming IPv4 packet.
+    ///
+    /// This method returns whether the packet is a DHCP reply sent to the client. If so, the
+    /// packet should not be processed further.
+    pub(super) fn process(&self, pkt: &Ipv4Packet<&[u8]>, now: Instant) -> bool {
+        if pkt.next_header() != IpProtocol::Udp {
+            return false;
+        }
+        let Ok(udp_pkt) = UdpPacket::new_checked(pkt.payload()) else {
+            return false;
+        };
+        if udp_pkt.src_port() != DHCP_SERVER_PORT || udp_pkt.dst_port() != DHCP_CLIENT_PORT {
+            return false;
+        }
+
+        // From now on, the packet is ours, so it is consumed even if it is ill-formed.
+        let Ok(dhcp_pkt) = DhcpPacket::new_checked(udp_pkt.payload()) else {
+            return true;
+        };
+        let Ok(repr) = DhcpRepr::parse(&dhcp_pkt) else {
+            return true;
+        };
+
+        let mut state = self.state.lock();
+        if repr.transaction_id != state.transaction_id
+            || repr.client_hardware_address != self.ether_addr
+        {
+            return true;
+        }
+
+        match (state.phase, repr.message_type) {
+            (DhcpPhase::Discovering, DhcpMessageType::Offer) => {
+                let Some(server_id) = repr.server_identifier else {
+                    return true;
+                };
+                state.phase = DhcpPhase::Requesting {
+                    server_id,
+                    requested_ip: repr.your_ip,
+                };
+                state.retry(now);
+            }
+            (DhcpPhase::Requesting { .. } | DhcpPhase::Renewing { .. }, DhcpMessageType::Ack) => {
+                let Some(lease) = Self::parse_lease(&repr) else {
+                    return true;
+                };
+                let is_renewed = matches!(state.phase, DhcpPhase::Renewing { lease: old_lease, .. } if old_lease == lease);
+                if !is_renewed {
+                    state.event = Some(DhcpEvent::Configured(lease));
+                }
+
+                // The renewal time (T1) defaults to half of the lease time. See
+                // <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4.5>.
+                let lease_secs = repr.lease_duration.unwrap_or(u32::MAX);
+                let renew_secs = repr.renew_duration.unwrap_or(lease_secs / 2);
+                state.phase = DhcpPhase::Bound {
+                    lease,
+                    renew_at: now + Duration::from_secs(renew_secs as u64),
+                    expire_at: now + Duration::from_secs(lease_secs as u64),
+                };
+            }
+            (DhcpPhase::Requesting { .. } | DhcpPhase::Renewing { .. }, DhcpMessageType::Nak) => {
+                if matches!(state.phase, DhcpPhase::Renewing { .. }) {
+                    state.event = Some(DhcpEvent::Deconfigured);
+                }
+                self.restart(&mut state, now);
+            }
+            _ => (),
+        }
+
+        true
+    }
+
+    /// Advances the timers of the client.
+    ///
+    /// This method returns the message that should be broadcast, if any.
+    pub(super) fn poll(&self, now: Instant) -> Option<DhcpMessage> {
+        let mut state = self.state.lock();
+
+        match state.phase {
+            DhcpPhase::Bound {
+                lease,
+                renew_at,
+                expire_at,
+            } => {
+                if now < renew_at {
+                    return None;
+                }
+                state.phase = DhcpPhase::Renewing { lease, expire_at };
+                state.retry(now);
+            }
+            DhcpPhase::Renewing { expire_at, .. } if now >= expire_at => {
+                state.event = Some(DhcpEvent::Deconfigured);
+                self.restart(&mut state, now);
+            }
+            DhcpPhase::Requesting { .. } if state.retry_count >= MAX_REQUEST_RETRIES => {
+                self.restart(&mut state, now);
+            }
+            _ => (),
+        }
+
+        if now < state.retry_at {
+            return None;
+        }
+
+        let message = match state.phase {
+            DhcpPhase::Discovering => DhcpMessage {
+                src_addr: Ipv4Address::UNSPECIFIED,
+                payload: self.build(
+                    &state,
+                    DhcpMessageType::Discover,
+                    Ipv4Address::UNSPECIFIED,
+                    None,
+                    None,
+                ),
+            },
+            DhcpPhase::Requesting {
+                server_id,
+                requested_ip,
+            } => DhcpMessage {
+                src_addr: Ipv4Address::UNSPECIFIED,
+                payload: self.build(
+                    &state,
+                    DhcpMessageType::Request,
+                    Ipv4Address::UNSPECIFIED,
+                    Some(requested_ip),
+                    Some(server_id),
+                ),
+            },
+            DhcpPhase::Renewing { lease, .. } => DhcpMessage {
+                src_addr: lease.cidr.address(),
+                payload: self.build(
+                    &state,
+                    DhcpMessageType::Request,
+                    lease.cidr.address(),
+                    None,
+                    None,
+                ),
+            },
+            DhcpPhase::Bound { .. } => unreachable!(),
+        };
+
+        let interval = INITIAL_RETRY_INTERVAL * (1 << state.retry_count.min(4));
+        state.retry_at = now + interval.min(MAX_RETRY_INTERVAL);
+        state.retry_count += 1;
+
+        Some(message)
+    }
+
+    /// Takes the configuration change that should be applied to the iface.
+    pub(super) fn take_event(&self) -> Option<DhcpEvent> {
+        self.state.lock().event.take()
+    }
+
+    /// Returns the time when [`Self::poll`] should be called next time.
+    pub(super) fn next_poll_at(&self) -> Instant {
+        let state = self.state.lock();
+        match state.phase {
+            DhcpPhase::Bound { renew_at, .. } => renew_at,
+            DhcpPhase::Renewing { expire_at, .. } => state.retry_at.min(expire_at),
+            DhcpPhase::Discovering | DhcpPhase::Requesting { .. } => state.retry_at,
+        }
+    }
+
+    fn restart(&self, state: &mut DhcpState, now: Instant) {
+        state.phase = DhcpPhase::Discovering;
+        state.transaction_id = new_transaction_id(self.ether_addr, now);
+        state.retry(now);
+    }
+
+    fn parse_lease(repr: &DhcpRepr) -> Option<DhcpLease> {
+        let cidr = Ipv4Cidr::from_netmask(repr.your_ip, repr.subnet_mask?).ok()?;
+        Some(DhcpLease {
+            cidr,
+            gateway: repr.router,
+        })
+    }
+
+    fn build(
+        &self,
+        state: &DhcpState,
+        message_type: DhcpMessageType,
+        client_ip: Ipv4Address,
+        requested_ip: Option<Ipv4Address>,
+        server_identifier: Option<Ipv4Address>,
+    ) -> Vec<u8> {
+        let repr = DhcpRepr {
+            message_type,
+            transaction_id: state.transaction_id,
+            secs: 0,
+            client_hardware_address: self.ether_addr,
+            client_ip,
+            your_ip: Ipv4Address::UNSPECIFIED,
+            server_ip: Ipv4Address::UNSPECIFIED,
+            router: None,
+            subnet_mask: None,
+            relay_agent_ip: Ipv4Address::UNSPECIFIED,
+            // Ask the server to broadcast the replies, since we cannot receive unicast packets
+            // before an address is configured.
+            broadcast: true,
+            requested_ip,
+            client_identifier: Some(self.ether_addr),
+            server_identifier,
+            parameter_request_list: Some(PARAMETER_REQUEST_LIST),
+            dns_servers: None,
+            max_size: None,
+            lease_duration: None,
+            renew_duration: None,
+            rebind_duration: None,
+            additional_options: &[],
+        };
+
+        let mut payload = vec![0; repr.buffer_len()];
+        repr.emit(&mut DhcpPacket::new_unchecked(&mut payload[..]))
+            .unwrap();
+        payload
+    }
+}
+
+/// Generates a transaction ID from the Ethernet address and the current time.
+///
+/// The transaction ID only needs to be unlikely to collide with other clients on the same link.
+fn new_transaction_id(ether_addr: EthernetAddress, now: Instant) -> u32 {
+    let mac = ether_addr.as_bytes();
+    jhash_3vals(
+        u32::from_be_bytes([mac[0], mac[1], mac[2], mac[3]]),
+        u32::from_be_bytes([0, 0, mac[4], mac[5]]),
+        now.total_millis() as u32,
+        0,
+    )
+}
+
+impl DhcpState {
+    fn retry(&mut self, now: Instant) {
+        self.retry_at = now;
+        self.retry_count = 0;
+    }
+}
@@ -5,20 +5,28 @@ use alloc::{collections::btree_map::BTreeMap, string::String, sync::Arc};
 use aster_softirq::BottomHalfDisabled;
 use ostd::sync::SpinLock;
 use smoltcp::{
-    iface::{packet::Packet, Config, Context},
+    iface::{
+        packet::{IpPayload, Packet},
+        Config, Context,
+    },
     phy::{Device, DeviceCapabilities, TxToken},
     wire::{
         self, ArpOperation, ArpPacket, ArpRepr, EthernetAddress, EthernetFrame, EthernetProtocol,
-        EthernetRepr, IpAddress, Ipv4Address, Ipv4AddressExt, Ipv4Cidr, Ipv4Packet,
+        EthernetRepr, Icmpv6Message, Icmpv6Packet, Icmpv6Repr, IpAddress, IpProtocol, Ipv4Address,
+        Ipv4AddressExt, Ipv4Cidr, Ipv4Packet, Ipv4Repr, Ipv6Address, Ipv6Cidr, Ipv6Packet,
+        Ipv6Repr, NdiscNeighborFlags, NdiscRepr, RawHardwareAddress, UdpRepr, DHCP_CLIENT_PORT,
+        DHCP_SERVER_PORT,
     },
 };
 
+use super::dhcp::{DhcpClient, DhcpEvent};
 use crate::{
     device::{NotifyDevice, WithDevice},
     ext::Ext,
     iface::{
         common::{IfaceCommon, InterfaceType},
         iface::internal::IfaceInternal,
+        poll::{ipv6_solicited_node, IpPacket, IPV6_ALL_NODES},
         time::get_network_timestamp,
         Iface, InterfaceFlags, ScheduleNextPoll,
     },
@@ -29,14 +37,46 @@ pub struct EtherIface<D, E: Ext> {
     common: IfaceCommon<E>,
     ether_addr: EthernetAddress,
     arp_table: SpinLock<BTreeMap<Ipv4Address, EthernetAddress>, BottomHalfDisabled>,
+    ndisc_table: SpinLock<BTreeMap<Ipv6Address, EthernetAddress>, BottomHalfDisabled>,
+    dhcp: Option<DhcpClient>,
+}
+
+/// The initial IPv4 configuration of an [`EtherIface`].
+#[derive(Debug, Clone, Copy)]
+pub enum Ipv4Config {
+    /// A static IPv4 address, with an optional default gateway.
+    Static {
+        cidr: Ipv4Cidr,
+        gateway: Option<Ipv4Address>,
+    },
+    /// An IPv4 address leased from a DHCP server.
+    ///
+    /// The iface has no IPv4 address until the lease is acknowledged. The leased address replaces
+    /// any address that is configured manually in the meantime.
+    Dhcp,
+    /// No IPv4 address.
+    ///
+    /// An address can be configured later with [`Iface::set_ipv4_cidr`].
+    None,
+}
+
+/// A packet of the neighbor discovery protocols (i.e., ARP for IPv4 and NDP for IPv6).
+enum NeighborPacket {
+    Arp(ArpRepr),
+    Ndisc(EthernetRepr, Packet<'static>),
 }
 
+/// The hop limit of NDP packets.
+///
+/// NDP packets with other hop limits must be ignored, since they may come from other links. See
+/// <https://datatracker.ietf.org/doc/html/rfc4861#section-7.1.1>.
+const NDISC_HOP_LIMIT: u8 = 255;
+
 impl<D: WithDevice, E: Ext> EtherIface<D, E> {
     pub fn new(
         driver: D,
         ether_addr: EthernetAddress,
-        ip_cidr: Ipv4Cidr,
-        gateway: Ipv4Address,
+        ipv4_config: Ipv4Config,
         name: String,
         sched_poll: E::ScheduleNextPoll,
         flags: InterfaceFlags,
@@ -48,15 +88,32 @@ impl<D: WithDevice, E: Ext> EtherIface<D, E> {
             let mut interface = smoltcp::iface::Interface::new(config, device, now);
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
-                ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                if let Ipv4Config::Static { cidr, .. } = ipv4_config {
+                    ip_addrs.push(wire::IpCidr::Ipv4(cidr)).unwrap();
+                }
+                // TODO: Perform Duplicate Address Detection before using the link-local address.
+                // See <https://datatracker.ietf.org/doc/html/rfc4862#section-5.4>.
+                let link_local = Ipv6Cidr::new(ipv6_link_local_addr(ether_addr), 64);
+                ip_addrs.push(wire::IpCidr::Ipv6(link_local)).unwrap();
             });
-            interface
-                .routes_mut()
-                .add_default_ipv4_route(gateway)
-                .unwrap();
+            if let Ipv4Config::Static {
+                gateway: Some(gateway),
+                ..
+            } = ipv4_config
+            {
+                interface
+                    .routes_mut()
+                    .add_default_ipv4_route(gateway)
+                    .unwrap();
+            }
             interface
         });
 
+        let dhcp = match ipv4_config {
+            Ipv4Config::Dhcp => Some(DhcpClient::new(ether_addr, get_network_timestamp())),
+            Ipv4Config::Static { .. } | Ipv4Config::None => None,
+        };
+
         let common = IfaceCommon::new(name, InterfaceType::ETHER, flags, interface, sched_poll);
 
         Arc::new(Self {
@@ -64,10 +121,38 @@ impl<D: WithDevice, E: Ext> EtherIface<D, E> {
             common,
             ether_addr,
             arp_table: SpinLock::new(BTreeMap::new()),
+            ndisc_table: SpinLock::new(BTreeMap::new()),
+            dhcp,
         })
     }
 }
 
+/// Generates the IPv6 link-local address from the Ethernet address, as SLAAC does.
+///
+/// The interface identifier is the modified EUI-64 identifier derived from the Ethernet address.
+/// See <https://datatracker.ietf.org/doc/html/rfc4291#appendix-A>.
+fn ipv6_link_local_addr(ether_addr: EthernetAddress) -> Ipv6Address {
+    let mac = ether_addr.as_bytes();
+    Ipv6Address::new(
+        0xfe80,
+        0,
+        0,
+        0,
+        u16::from_be_bytes([mac[0] ^ 0x02, mac[1]]),
+        u16::from_be_bytes([mac[2], 0xff]),
+        u16::from_be_bytes([0xfe, mac[3]]),
+        u16::from_be_bytes([mac[4], mac[5]]),
+    )
+}
+
+/// Returns the Ethernet address to which the IPv6 multicast address is mapped.
+///
+/// See <https://datatracker.ietf.org/doc/html/rfc2464#section-7>.
+fn ipv6_multicast_ether_addr(addr: Ipv6Address) -> EthernetAddress {
+    let octets = addr.octets();
+    EthernetAddress([0x33, 0x33, octets[12], octets[13], octets[14], octets[15]])
+}
+
 impl<D, E: Ext> IfaceInternal<E> for EtherIface<D, E> {
     fn common(&self) -> &IfaceCommon<E> {
         &self.common
@@ -85,6 +170,7 @@ where
                 |data, iface_cx, tx_token| self.process(data, iface_cx, tx_token),
                 |pkt, iface_cx, tx_token| self.dispatch(pkt, iface_cx, tx_token),
             );
+            let next_poll = self.poll_dhcp(&mut *device, next_poll);
             device.notify_poll_end();
             self.common.sched_poll().schedule_next_poll(next_poll);
         });
@@ -97,45 +183,134 @@ where
 }
 
 impl<D, E: Ext> EtherIface<D, E> {
+    /// Polls the DHCP client, if any.
+    ///
+    /// This method applies the changes of the leased configuration, broadcasts the pending DHCP
+    /// message, and returns the time to poll the iface next time, taking into account the
+    /// timers of the DHCP client.
+    fn poll_dhcp<T: Device + ?Sized>(&self, device: &mut T, next_poll: Option<u64>) -> Option<u64> {
+        let Some(dhcp) = self.dhcp.as_ref() else {
+            return next_poll;
+        };
+        let now = get_network_timestamp();
+
+        if let Some(message) = dhcp.poll(now) {
+            let caps = device.capabilities();
+            if let Some(tx_token) = device.transmit(now) {
+                self.emit_dhcp(message.src_addr, &message.payload, &caps, tx_token);
+            }
+        }
+
+        match dhcp.take_event() {
+            Some(DhcpEvent::Configured(lease)) => {
+                self.common.set_ipv4_cidr(Some(lease.cidr));
+                self.common.set_ipv4_gateway(lease.gateway);
+            }
+            Some(DhcpEvent::Deconfigured) => {
+                self.common.set_ipv4_gateway(None);
+                self.common.set_ipv4_cidr(None);
+            }
+            None => (),
+        }
+
+        let dhcp_poll_at = dhcp.next_poll_at().total_millis() as u64;
+        Some(next_poll.map_or(dhcp_poll_at, |next_poll| next_poll.min(dhcp_poll_at)))
+    }
+
+    /// Consumes the token and broadcasts a DHCP message.
+    fn emit_dhcp<T: TxToken>(
+        &self,
+        src_addr: Ipv4Address,
+        payload: &[u8],
+        caps: &DeviceCapabilities,
+        tx_token: T,
+    ) {
+        let udp_repr = UdpRepr {
+            src_port: DHCP_CLIENT_PORT,
+            dst_port: DHCP_SERVER_PORT,
+        };
+        let ip_repr = Ipv4Repr {
+            src_addr,
+            dst_addr: Ipv4Address::BROADCAST,
+            next_header: IpProtocol::Udp,
+            payload_len: udp_repr.header_len() + payload.len(),
+            hop_limit: 64,
+        };
+        let ether_repr = EthernetRepr {
+            src_addr: self.ether_addr,
+            dst_addr: EthernetAddress::BROADCAST,
+            ethertype: EthernetProtocol::Ipv4,
+        };
+
+        Self::emit_ip(
+            &ether_repr,
+            &Packet::new_ipv4(ip_repr, IpPayload::Udp(udp_repr, payload)),
+            caps,
+            tx_token,
+        );
+    }
+
     fn process<'pkt, T: TxToken>(
         &self,
         data: &'pkt [u8],
         iface_cx: &mut Context,
         tx_token: T,
-    ) -> Option<(Ipv4Packet<&'pkt [u8]>, T)> {
-        match self.parse_ip_or_process_arp(data, iface_cx) {
+    ) -> Option<(IpPacket<'pkt>, T)> {
+        match self.parse_ip_or_process_neighbor(data, iface_cx) {
             Ok(pkt) => Some((pkt, tx_token)),
-            Err(Some(arp)) => {
-                Self::emit_arp(&arp, tx_token);
+            Err(Some(neighbor)) => {
+                Self::emit_neighbor(&neighbor, &iface_cx.caps, tx_token);
                 None
             }
             Err(None) => None,
         }
     }
 
-    fn parse_ip_or_process_arp<'pkt>(
+    fn parse_ip_or_process_neighbor<'pkt>(
         &self,
         data: &'pkt [u8],
         iface_cx: &mut Context,
-    ) -> Result<Ipv4Packet<&'pkt [u8]>, Option<ArpRepr>> {
+    ) -> Result<IpPacket<'pkt>, Option<NeighborPacket>> {
         // Parse the Ethernet header. Ignore the packet if the header is ill-formed.
         let frame = EthernetFrame::new_checked(data).map_err(|_| None)?;
         let repr = EthernetRepr::parse(&frame).map_err(|_| None)?;
 
         // Ignore the Ethernet frame if it is not sent to us.
-        if !repr.dst_addr.is_broadcast() && repr.dst_addr != self.ether_addr {
+        //
+        // TODO: Filter multicast frames according to the multicast groups that we have joined.
+        if !repr.dst_addr.is_broadcast()
+            && !repr.dst_addr.is_multicast()
+            && repr.dst_addr != self.ether_addr
+        {
             return Err(None);
         }
 
         // Ignore the Ethernet frame if the protocol is not supported.
         match repr.ethertype {
             EthernetProtocol::Ipv4 => {
-                Ok(Ipv4Packet::new_checked(frame.payload()).map_err(|_| None)?)
+                let pkt = Ipv4Packet::new_checked(frame.payload()).map_err(|_| None)?;
+                // DHCP replies are handled by the DHCP client, since they may be sent to an
+                // address that we do not own yet.
+                if let Some(dhcp) = self.dhcp.as_ref() {
+                    if dhcp.process(&pkt, iface_cx.now()) {
+                        return Err(None);
+                    }
+                }
+                Ok(IpPacket::Ipv4(pkt))
+            }
+            EthernetProtocol::Ipv6 => {
+                let pkt = Ipv6Packet::new_checked(frame.payload()).map_err(|_| None)?;
+                if !Self::is_ndisc(&pkt) {
+                    return Ok(IpPacket::Ipv6(pkt));
+                }
+                Err(self
+                    .process_ndisc(&pkt, repr.src_addr, iface_cx)
+                    .map(|(ether_repr, pkt)| NeighborPacket::Ndisc(ether_repr, pkt)))
             }
             EthernetProtocol::Arp => {
                 let pkt = ArpPacket::new_checked(frame.payload()).map_err(|_| None)?;
                 let arp = ArpRepr::parse(&pkt).map_err(|_| None)?;
-                Err(self.process_arp(&arp, iface_cx))
+                Err(self.process_arp(&arp, iface_cx).map(NeighborPacket::Arp))
             }
             _ => Err(None),
         }
@@ -197,50 +372,247 @@ impl<D, E: Ext> EtherIface<D, E> {
         }
     }
 
+    /// Returns whether the IPv6 packet is an NDP packet.
+    fn is_ndisc(pkt: &Ipv6Packet<&[u8]>) -> bool {
+        if pkt.next_header() != IpProtocol::Icmpv6 {
+            return false;
+        }
+
+        let Ok(icmp_pkt) = Icmpv6Packet::new_checked(pkt.payload()) else {
+            return false;
+        };
+        matches!(
+            icmp_pkt.msg_type(),
+            Icmpv6Message::RouterSolicit
+                | Icmpv6Message::RouterAdvert
+                | Icmpv6Message::NeighborSolicit
+                | Icmpv6Message::NeighborAdvert
+                | Icmpv6Message::Redirect
+        )
+    }
+
+    fn process_ndisc(
+        &self,
+        pkt: &Ipv6Packet<&[u8]>,
+        source_ether_addr: EthernetAddress,
+        iface_cx: &mut Context,
+    ) -> Option<(EthernetRepr, Packet<'static>)> {
+        // Parse the NDP packet. Ignore the packet if it is ill-formed or comes from other links.
+        let ip_repr = Ipv6Repr::parse(pkt).ok()?;
+        if ip_repr.hop_limit != NDISC_HOP_LIMIT {
+            return None;
+        }
+        let icmp_pkt = Icmpv6Packet::new_checked(pkt.payload()).ok()?;
+        let Icmpv6Repr::Ndisc(ndisc_repr) = Icmpv6Repr::parse(
+            &ip_repr.src_addr,
+            &ip_repr.dst_addr,
+            &icmp_pkt,
+            &iface_cx.checksum_caps(),
+        )
+        .ok()?
+        else {
+            return None;
+        };
+
+        match ndisc_repr {
+            NdiscRepr::NeighborAdvert {
+                target_addr,
+                lladdr: Some(lladdr),
+                ..
+            } => {
+                // Ignore the NDP packet if the addresses are not unicast or not local.
+                let ether_addr = Self::parse_lladdr(&lladdr)?;
+                if !ether_addr.is_unicast()
+                    || !iface_cx.in_same_network(&IpAddress::Ipv6(target_addr))
+                {
+                    return None;
+                }
+
+                // Insert the mapping between the Ethernet address and the IP address.
+                //
+                // TODO: Remove the mapping if it expires.
+                self.ndisc_table.lock().insert(target_addr, ether_addr);
+
+                None
+            }
+            NdiscRepr::NeighborSolicit {
+                target_addr,
+                lladdr,
+            } => {
+                // Ignore the NDP packet if we do not own the target address.
+                if iface_cx.ipv6_addr().is_none_or(|addr| addr != target_addr) {
+                    return None;
+                }
+
+                // If the source address is unspecified, the sender is performing Duplicate Address
+                // Detection, so the reply is sent to all nodes. See
+                // <https://datatracker.ietf.org/doc/html/rfc4861#section-7.2.4>.
+                let (dst_addr, flags) = if ip_repr.src_addr.is_unspecified() {
+                    (IPV6_ALL_NODES, NdiscNeighborFlags::OVERRIDE)
+                } else {
+                    (
+                        ip_repr.src_addr,
+                        NdiscNeighborFlags::SOLICITED | NdiscNeighborFlags::OVERRIDE,
+                    )
+                };
+
+                let dst_ether_addr = if dst_addr.is_multicast() {
+                    ipv6_multicast_ether_addr(dst_addr)
+                } else {
+                    let ether_addr = lladdr
+                        .and_then(|lladdr| Self::parse_lladdr(&lladdr))
+                        .unwrap_or(source_ether_addr);
+                    if !ether_addr.is_unicast() {
+                        return None;
+                    }
+                    if iface_cx.in_same_network(&IpAddress::Ipv6(dst_addr)) {
+                        self.ndisc_table.lock().insert(dst_addr, ether_addr);
+                    }
+                    ether_addr
+                };
+
+                let icmp_repr = Icmpv6Repr::Ndisc(NdiscRepr::NeighborAdvert {
+                    flags,
+                    target_addr,
+                    lladdr: Some(RawHardwareAddress::from_bytes(self.ether_addr.as_bytes())),
+                });
+                let ip_repr = Ipv6Repr {
+                    src_addr: target_addr,
+                    dst_addr,
+                    next_header: IpProtocol::Icmpv6,
+                    payload_len: icmp_repr.buffer_len(),
+                    hop_limit: NDISC_HOP_LIMIT,
+                };
+
+                Some((
+                    EthernetRepr {
+                        src_addr: self.ether_addr,
+                        dst_addr: dst_ether_addr,
+                        ethertype: EthernetProtocol::Ipv6,
+                    },
+                    Packet::new_ipv6(ip_repr, IpPayload::Icmpv6(icmp_repr)),
+                ))
+            }
+            // TODO: Support router discovery and redirects.
+            _ => None,
+        }
+    }
+
+    /// Parses the link-layer address option in an NDP packet as an Ethernet address.
+    fn parse_lladdr(lladdr: &RawHardwareAddress) -> Option<EthernetAddress> {
+        if lladdr.len() != 6 {
+            return None;
+        }
+        Some(EthernetAddress::from_bytes(lladdr.as_bytes()))
+    }
+
     fn dispatch<T: TxToken>(&self, pkt: &Packet, iface_cx: &mut Context, tx_token: T) {
-        match self.resolve_ether_or_generate_arp(pkt, iface_cx) {
+        match self.resolve_ether_or_generate_neighbor(pkt, iface_cx) {
             Ok(ether) => Self::emit_ip(&ether, pkt, &iface_cx.caps, tx_token),
-            Err(Some(arp)) => Self::emit_arp(&arp, tx_token),
+            Err(Some(neighbor)) => Self::emit_neighbor(&neighbor, &iface_cx.caps, tx_token),
             Err(None) => (),
         }
     }
 
-    fn resolve_ether_or_generate_arp(
+    fn resolve_ether_or_generate_neighbor(
         &self,
         pkt: &Packet,
         iface_cx: &mut Context,
-    ) -> Result<EthernetRepr, Option<ArpRepr>> {
-        // Resolve the next-hop IP address.
-        let next_hop_ip = match iface_cx.route(&pkt.ip_repr().dst_addr(), iface_cx.now()) {
-            Some(IpAddress::Ipv4(next_hop_ip)) => next_hop_ip,
-            None => return Err(None),
+    ) -> Result<EthernetRepr, Option<NeighborPacket>> {
+        // Resolve the next-hop IP address. IPv6 multicast packets are sent directly.
+        let dst_addr = pkt.ip_repr().dst_addr();
+        let next_hop_ip = match dst_addr {
+            IpAddress::Ipv6(addr) if addr.is_multicast() => dst_addr,
+            _ => match iface_cx.route(&dst_addr, iface_cx.now()) {
+                Some(next_hop_ip) => next_hop_ip,
+                None => return Err(None),
+            },
         };
 
         // Resolve the next-hop Ethernet address.
-        let next_hop_ether = if next_hop_ip.is_broadcast() {
-            EthernetAddress::BROADCAST
-        } else if let Some(next_hop_ether) = self.arp_table.lock().get(&next_hop_ip) {
-            *next_hop_ether
-        } else {
-            // If the next-hop Ethernet address cannot be resolved, we drop the original packet and
-            // send an ARP packet instead. The upper layer should be responsible for detecting the
-            // packet loss and retrying later to see if the Ethernet address is ready.
-            return Err(Some(ArpRepr::EthernetIpv4 {
-                operation: ArpOperation::Request,
-                source_hardware_addr: self.ether_addr,
-                source_protocol_addr: iface_cx.ipv4_addr().unwrap_or(Ipv4Address::UNSPECIFIED),
-                target_hardware_addr: EthernetAddress::BROADCAST,
-                target_protocol_addr: next_hop_ip,
-            }));
+        let (next_hop_ether, ethertype) = match next_hop_ip {
+            IpAddress::Ipv4(next_hop_ip) => (
+                self.resolve_ipv4_ether(next_hop_ip, iface_cx)?,
+                EthernetProtocol::Ipv4,
+            ),
+            IpAddress::Ipv6(next_hop_ip) => (
+                self.resolve_ipv6_ether(next_hop_ip, iface_cx)?,
+                EthernetProtocol::Ipv6,
+            ),
         };
 
         Ok(EthernetRepr {
             src_addr: self.ether_addr,
             dst_addr: next_hop_ether,
-            ethertype: EthernetProtocol::Ipv4,
+            ethertype,
         })
     }
 
+    fn resolve_ipv4_ether(
+        &self,
+        next_hop_ip: Ipv4Address,
+        iface_cx: &Context,
+    ) -> Result<EthernetAddress, Option<NeighborPacket>> {
+        if next_hop_ip.is_broadcast() {
+            return Ok(EthernetAddress::BROADCAST);
+        }
+        if let Some(next_hop_ether) = self.arp_table.lock().get(&next_hop_ip) {
+            return Ok(*next_hop_ether);
+        }
+
+        // If the next-hop Ethernet address cannot be resolved, we drop the original packet and
+        // send an ARP packet instead. The upper layer should be responsible for detecting the
+        // packet loss and retrying later to see if the Ethernet address is ready.
+        Err(Some(NeighborPacket::Arp(ArpRepr::EthernetIpv4 {
+            operation: ArpOperation::Request,
+            source_hardware_addr: self.ether_addr,
+            source_protocol_addr: iface_cx.ipv4_addr().unwrap_or(Ipv4Address::UNSPECIFIED),
+            target_hardware_addr: EthernetAddress::BROADCAST,
+            target_protocol_addr: next_hop_ip,
+        })))
+    }
+
+    fn resolve_ipv6_ether(
+        &self,
+        next_hop_ip: Ipv6Address,
+        iface_cx: &Context,
+    ) -> Result<EthernetAddress, Option<NeighborPacket>> {
+        if next_hop_ip.is_multicast() {
+            return Ok(ipv6_multicast_ether_addr(next_hop_ip));
+        }
+        if let Some(next_hop_ether) = self.ndisc_table.lock().get(&next_hop_ip) {
+            return Ok(*next_hop_ether);
+        }
+
+        // As with ARP, we drop the original packet and send a Neighbor Solicitation message to
+        // the solicited-node multicast address instead.
+        let Some(src_addr) = iface_cx.ipv6_addr() else {
+            return Err(None);
+        };
+        let dst_addr = ipv6_solicited_node(next_hop_ip);
+
+        let icmp_repr = Icmpv6Repr::Ndisc(NdiscRepr::NeighborSolicit {
+            target_addr: next_hop_ip,
+            lladdr: Some(RawHardwareAddress::from_bytes(self.ether_addr.as_bytes())),
+        });
+        let ip_repr = Ipv6Repr {
+            src_addr,
+            dst_addr,
+            next_header: IpProtocol::Icmpv6,
+            payload_len: icmp_repr.buffer_len(),
+            hop_limit: NDISC_HOP_LIMIT,
+        };
+
+        Err(Some(NeighborPacket::Ndisc(
+            EthernetRepr {
+                src_addr: self.ether_addr,
+                dst_addr: ipv6_multicast_ether_addr(dst_addr),
+                ethertype: EthernetProtocol::Ipv6,
+            },
+            Packet::new_ipv6(ip_repr, IpPayload::Icmpv6(icmp_repr)),
+        )))
+    }
+
     /// Consumes the token and emits an IP packet.
     fn emit_ip<T: TxToken>(
         ether_repr: &EthernetRepr,
@@ -265,6 +637,20 @@ impl<D, E: Ext> EtherIface<D, E> {
         );
     }
 
+    /// Consumes the token and emits an ARP or NDP packet.
+    fn emit_neighbor<T: TxToken>(
+        neighbor: &NeighborPacket,
+        caps: &DeviceCapabilities,
+        tx_token: T,
+    ) {
+        match neighbor {
+            NeighborPacket::Arp(arp_repr) => Self::emit_arp(arp_repr, tx_token),
+            NeighborPacket::Ndisc(ether_repr, pkt) => {
+                Self::emit_ip(ether_repr, pkt, caps, tx_token)
+            }
+        }
+    }
+
     /// Consumes the token and emits an ARP packet.
     fn emit_arp<T: TxToken>(arp_repr: &ArpRepr, tx_token: T) {
         let ether_repr = match arp_repr {
@@ -5,7 +5,7 @@ use alloc::{string::String, sync::Arc};
 use smoltcp::{
     iface::Config,
     phy::{Device, TxToken},
-    wire::{self, Ipv4Cidr, Ipv4Packet},
+    wire::{self, Ipv4Cidr, Ipv6Cidr},
 };
 
 use crate::{
@@ -14,6 +14,7 @@ use crate::{
     iface::{
         common::{IfaceCommon, InterfaceFlags, InterfaceType},
         iface::internal::IfaceInternal,
+        poll::IpPacket,
         time::get_network_timestamp,
         Iface, ScheduleNextPoll,
     },
@@ -28,6 +29,7 @@ impl<D: WithDevice, E: Ext> IpIface<D, E> {
     pub fn new(
         driver: D,
         ip_cidr: Ipv4Cidr,
+        ipv6_cidr: Option<Ipv6Cidr>,
         name: String,
         sched_poll: E::ScheduleNextPoll,
         type_: InterfaceType,
@@ -41,6 +43,9 @@ impl<D: WithDevice, E: Ext> IpIface<D, E> {
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
                 ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                if let Some(ipv6_cidr) = ipv6_cidr {
+                    ip_addrs.push(wire::IpCidr::Ipv6(ipv6_cidr)).unwrap();
+                }
             });
             interface
         });
@@ -62,7 +67,7 @@ impl<D: WithDevice + 'static, E: Ext> Iface<E> for IpIface<D, E> {
         self.driver.with(|device| {
             let next_poll = self.common.poll(
                 device,
-                |data, _iface_cx, tx_token| Some((Ipv4Packet::new_checked(data).ok()?, tx_token)),
+                |data, _iface_cx, tx_token| Some((IpPacket::new_checked(data)?, tx_token)),
                 |pkt, iface_cx, tx_token| {
                     let ip_repr = pkt.ip_repr();
                     tx_token.consume(ip_repr.buffer_len(), |buffer| {
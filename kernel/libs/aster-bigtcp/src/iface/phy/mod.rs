@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
+mod dhcp;
 mod ether;
 mod ip;
 
-pub use ether::EtherIface;
+pub use ether::{EtherIface, Ipv4Config};
 pub use ip::IpIface;
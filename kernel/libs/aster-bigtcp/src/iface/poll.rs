@@ -9,9 +9,10 @@ use smoltcp::{
     },
     phy::{ChecksumCapabilities, Device, RxToken, TxToken},
     wire::{
-        Icmpv4DstUnreachable, Icmpv4Repr, IpAddress, IpProtocol, IpRepr, Ipv4Address, Ipv4Packet,
-        Ipv4Repr, TcpControl, TcpPacket, TcpRepr, UdpPacket, UdpRepr, IPV4_HEADER_LEN,
-        IPV4_MIN_MTU,
+        Icmpv4DstUnreachable, Icmpv4Repr, Icmpv6DstUnreachable, Icmpv6Packet, Icmpv6Repr,
+        IpAddress, IpProtocol, IpRepr, Ipv4Address, Ipv4Packet, Ipv4Repr, Ipv6Address, Ipv6Packet,
+        Ipv6Repr, TcpControl, TcpPacket, TcpRepr, UdpPacket, UdpRepr, IPV4_HEADER_LEN,
+        IPV4_MIN_MTU, IPV6_HEADER_LEN, IPV6_MIN_MTU,
     },
 };
 
@@ -51,6 +52,52 @@ impl<'a, E: Ext> PollContext<'a, E> {
     }
 }
 
+/// An IP packet received from the physical layer.
+pub(super) enum IpPacket<'pkt> {
+    Ipv4(Ipv4Packet<&'pkt [u8]>),
+    Ipv6(Ipv6Packet<&'pkt [u8]>),
+}
+
+impl<'pkt> IpPacket<'pkt> {
+    /// Parses the IP packet according to the version field.
+    ///
+    /// This method returns `None` if the version is unknown or the packet is ill-formed.
+    pub(super) fn new_checked(data: &'pkt [u8]) -> Option<Self> {
+        match data.first()? >> 4 {
+            4 => Ipv4Packet::new_checked(data).ok().map(Self::Ipv4),
+            6 => Ipv6Packet::new_checked(data).ok().map(Self::Ipv6),
+            _ => None,
+        }
+    }
+}
+
+/// The link-local all-nodes multicast address (`ff02::1`).
+pub(super) const IPV6_ALL_NODES: Ipv6Address = Ipv6Address::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+
+/// Returns the solicited-node multicast address (`ff02::1:ffXX:XXXX`) of the IPv6 address.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4291#section-2.7.1>
+pub(super) const fn ipv6_solicited_node(addr: Ipv6Address) -> Ipv6Address {
+    let octets = addr.octets();
+    Ipv6Address::new(
+        0xff02,
+        0,
+        0,
+        0,
+        0,
+        1,
+        0xff00 | octets[13] as u16,
+        u16::from_be_bytes([octets[14], octets[15]]),
+    )
+}
+
+/// The reason why a destination is unreachable.
+#[derive(Debug, Clone, Copy)]
+enum UnreachableReason {
+    Host,
+    Port,
+}
+
 // This works around <https://github.com/rust-lang/rust/issues/49601>.
 // See the issue above for details.
 pub(super) trait FnHelper<A, B, C, O>: FnMut(A, B, C) -> O {}
@@ -68,7 +115,7 @@ impl<E: Ext> PollContext<'_, E> {
             &'pkt [u8],
             &'cx mut Context,
             D::TxToken<'tx>,
-            Option<(Ipv4Packet<&'pkt [u8]>, D::TxToken<'tx>)>,
+            Option<(IpPacket<'pkt>, D::TxToken<'tx>)>,
         >,
         Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
     {
@@ -79,7 +126,11 @@ impl<E: Ext> PollContext<'_, E> {
                     return;
                 };
 
-                let Some(reply) = self.parse_and_process_ipv4(pkt) else {
+                let reply = match pkt {
+                    IpPacket::Ipv4(pkt) => self.parse_and_process_ipv4(pkt),
+                    IpPacket::Ipv6(pkt) => self.parse_and_process_ipv6(pkt),
+                };
+                let Some(reply) = reply else {
                     return;
                 };
 
@@ -99,7 +150,7 @@ impl<E: Ext> PollContext<'_, E> {
             return self.generate_icmp_unreachable(
                 &IpRepr::Ipv4(repr),
                 pkt.payload(),
-                Icmpv4DstUnreachable::HostUnreachable,
+                UnreachableReason::Host,
             );
         }
 
@@ -115,6 +166,96 @@ impl<E: Ext> PollContext<'_, E> {
         }
     }
 
+    fn parse_and_process_ipv6<'pkt>(
+        &mut self,
+        pkt: Ipv6Packet<&'pkt [u8]>,
+    ) -> Option<Packet<'pkt>> {
+        // Parse the IP header. Ignore the packet if the header is ill-formed.
+        let repr = Ipv6Repr::parse(&pkt).ok()?;
+
+        if !self.is_multicast_local_ipv6(repr.dst_addr)
+            && !self.is_unicast_local(IpAddress::Ipv6(repr.dst_addr))
+        {
+            return self.generate_icmp_unreachable(
+                &IpRepr::Ipv6(repr),
+                pkt.payload(),
+                UnreachableReason::Host,
+            );
+        }
+
+        // TODO: Support IPv6 extension headers. Packets with extension headers are ignored for
+        // now.
+        let checksum_caps = self.iface.context().checksum_caps();
+        match repr.next_header {
+            IpProtocol::Tcp => {
+                self.parse_and_process_tcp(&IpRepr::Ipv6(repr), pkt.payload(), &checksum_caps)
+            }
+            IpProtocol::Udp => {
+                self.parse_and_process_udp(&IpRepr::Ipv6(repr), pkt.payload(), &checksum_caps)
+            }
+            IpProtocol::Icmpv6 => {
+                self.parse_and_process_icmpv6(&repr, pkt.payload(), &checksum_caps)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_and_process_icmpv6<'pkt>(
+        &self,
+        ip_repr: &Ipv6Repr,
+        ip_payload: &'pkt [u8],
+        checksum_caps: &ChecksumCapabilities,
+    ) -> Option<Packet<'pkt>> {
+        // Parse the ICMPv6 header. Ignore the packet if the header is ill-formed.
+        let icmp_pkt = Icmpv6Packet::new_checked(ip_payload).ok()?;
+        let icmp_repr = Icmpv6Repr::parse(
+            &ip_repr.src_addr,
+            &ip_repr.dst_addr,
+            &icmp_pkt,
+            checksum_caps,
+        )
+        .ok()?;
+
+        // Neighbor Discovery messages are handled by the physical layer, so we only need to reply
+        // to echo requests here. Other messages are ignored for now.
+        //
+        // TODO: Deliver ICMPv6 error messages to the sockets.
+        let Icmpv6Repr::EchoRequest {
+            ident,
+            seq_no,
+            data,
+        } = icmp_repr
+        else {
+            return None;
+        };
+
+        if !IpAddress::Ipv6(ip_repr.src_addr).is_unicast() {
+            return None;
+        }
+
+        let src_addr = if ip_repr.dst_addr.is_multicast() {
+            self.iface.context().ipv6_addr()?
+        } else {
+            ip_repr.dst_addr
+        };
+        let icmp_repr = Icmpv6Repr::EchoReply {
+            ident,
+            seq_no,
+            data,
+        };
+
+        Some(Packet::new_ipv6(
+            Ipv6Repr {
+                src_addr,
+                dst_addr: ip_repr.src_addr,
+                next_header: IpProtocol::Icmpv6,
+                payload_len: icmp_repr.buffer_len(),
+                hop_limit: 64,
+            },
+            IpPayload::Icmpv6(icmp_repr),
+        ))
+    }
+
     fn parse_and_process_tcp<'pkt>(
         &mut self,
         ip_repr: &IpRepr,
@@ -265,11 +406,7 @@ impl<E: Ext> PollContext<'_, E> {
         .ok()?;
 
         if !self.process_udp(ip_repr, &udp_repr, udp_pkt.payload()) {
-            return self.generate_icmp_unreachable(
-                ip_repr,
-                ip_payload,
-                Icmpv4DstUnreachable::PortUnreachable,
-            );
+            return self.generate_icmp_unreachable(ip_repr, ip_payload, UnreachableReason::Port);
         }
 
         None
@@ -296,7 +433,7 @@ impl<E: Ext> PollContext<'_, E> {
         &self,
         ip_repr: &IpRepr,
         ip_payload: &'pkt [u8],
-        reason: Icmpv4DstUnreachable,
+        reason: UnreachableReason,
     ) -> Option<Packet<'pkt>> {
         if !ip_repr.src_addr().is_unicast() || !ip_repr.dst_addr().is_unicast() {
             return None;
@@ -312,29 +449,64 @@ impl<E: Ext> PollContext<'_, E> {
             return None;
         }
 
-        let IpRepr::Ipv4(ipv4_repr) = ip_repr;
+        match ip_repr {
+            IpRepr::Ipv4(ipv4_repr) => {
+                let reason = match reason {
+                    UnreachableReason::Host => Icmpv4DstUnreachable::HostUnreachable,
+                    UnreachableReason::Port => Icmpv4DstUnreachable::PortUnreachable,
+                };
+                let reply_len =
+                    icmp_reply_payload_len(ip_payload.len(), IPV4_MIN_MTU, IPV4_HEADER_LEN);
+                let icmp_repr = Icmpv4Repr::DstUnreachable {
+                    reason,
+                    header: *ipv4_repr,
+                    data: &ip_payload[..reply_len],
+                };
 
-        let reply_len = icmp_reply_payload_len(ip_payload.len(), IPV4_MIN_MTU, IPV4_HEADER_LEN);
-        let icmp_repr = Icmpv4Repr::DstUnreachable {
-            reason,
-            header: *ipv4_repr,
-            data: &ip_payload[..reply_len],
-        };
+                Some(Packet::new_ipv4(
+                    Ipv4Repr {
+                        src_addr: self
+                            .iface
+                            .context()
+                            .ipv4_addr()
+                            .unwrap_or(Ipv4Address::UNSPECIFIED),
+                        dst_addr: ipv4_repr.src_addr,
+                        next_header: IpProtocol::Icmp,
+                        payload_len: icmp_repr.buffer_len(),
+                        hop_limit: 64,
+                    },
+                    IpPayload::Icmpv4(icmp_repr),
+                ))
+            }
+            IpRepr::Ipv6(ipv6_repr) => {
+                let reason = match reason {
+                    UnreachableReason::Host => Icmpv6DstUnreachable::AddrUnreachable,
+                    UnreachableReason::Port => Icmpv6DstUnreachable::PortUnreachable,
+                };
+                let reply_len =
+                    icmp_reply_payload_len(ip_payload.len(), IPV6_MIN_MTU, IPV6_HEADER_LEN);
+                let icmp_repr = Icmpv6Repr::DstUnreachable {
+                    reason,
+                    header: *ipv6_repr,
+                    data: &ip_payload[..reply_len],
+                };
 
-        Some(Packet::new_ipv4(
-            Ipv4Repr {
-                src_addr: self
-                    .iface
-                    .context()
-                    .ipv4_addr()
-                    .unwrap_or(Ipv4Address::UNSPECIFIED),
-                dst_addr: ipv4_repr.src_addr,
-                next_header: IpProtocol::Icmp,
-                payload_len: icmp_repr.buffer_len(),
-                hop_limit: 64,
-            },
-            IpPayload::Icmpv4(icmp_repr),
-        ))
+                Some(Packet::new_ipv6(
+                    Ipv6Repr {
+                        src_addr: self
+                            .iface
+                            .context()
+                            .ipv6_addr()
+                            .unwrap_or(Ipv6Address::UNSPECIFIED),
+                        dst_addr: ipv6_repr.src_addr,
+                        next_header: IpProtocol::Icmpv6,
+                        payload_len: icmp_repr.buffer_len(),
+                        hop_limit: 64,
+                    },
+                    IpPayload::Icmpv6(icmp_repr),
+                ))
+            }
+        }
     }
 
     /// Returns whether the destination address is the unicast address of a local interface.
@@ -348,8 +520,26 @@ impl<E: Ext> PollContext<'_, E> {
                 .context()
                 .ipv4_addr()
                 .is_some_and(|addr| addr == dst_addr),
+            IpAddress::Ipv6(dst_addr) => self
+                .iface
+                .context()
+                .ipv6_addr()
+                .is_some_and(|addr| addr == dst_addr),
         }
     }
+
+    /// Returns whether the destination address is an IPv6 multicast address that a local
+    /// interface listens to.
+    ///
+    /// Only the all-nodes address and the solicited-node address are supported for now.
+    fn is_multicast_local_ipv6(&self, dst_addr: Ipv6Address) -> bool {
+        dst_addr == IPV6_ALL_NODES
+            || self
+                .iface
+                .context()
+                .ipv6_addr()
+                .is_some_and(|addr| ipv6_solicited_node(addr) == dst_addr)
+    }
 }
 
 impl<E: Ext> PollContext<'_, E> {
@@ -39,13 +39,72 @@ impl<E: Ext> PollableIface<E> {
         self.interface.ipv4_addr()
     }
 
+    pub(super) fn ipv6_addr(&self) -> Option<smoltcp::wire::Ipv6Address> {
+        self.interface.ipv6_addr()
+    }
+
     pub(super) fn prefix_len(&self) -> Option<u8> {
         self.interface
             .ip_addrs()
-            .first()
+            .iter()
+            .find(|ip_addr| matches!(ip_addr, smoltcp::wire::IpCidr::Ipv4(_)))
             .map(|ip_addr| ip_addr.prefix_len())
     }
 
+    pub(super) fn has_ip_addr(&self, addr: smoltcp::wire::IpAddress) -> bool {
+        self.interface
+            .ip_addrs()
+            .iter()
+            .any(|ip_addr| ip_addr.address() == addr)
+    }
+
+    pub(super) fn is_on_link(&self, addr: smoltcp::wire::IpAddress) -> bool {
+        self.interface
+            .ip_addrs()
+            .iter()
+            .any(|ip_addr| ip_addr.contains_addr(&addr))
+    }
+
+    pub(super) fn set_ipv4_cidr(&mut self, cidr: Option<smoltcp::wire::Ipv4Cidr>) {
+        use smoltcp::wire::IpCidr;
+
+        self.interface.update_ip_addrs(|ip_addrs| {
+            ip_addrs.retain(|ip_addr| !matches!(ip_addr, IpCidr::Ipv4(_)));
+            if let Some(cidr) = cidr {
+                ip_addrs.push(IpCidr::Ipv4(cidr)).unwrap();
+            }
+        });
+    }
+
+    pub(super) fn ipv4_gateway(&mut self) -> Option<smoltcp::wire::Ipv4Address> {
+        use smoltcp::wire::{IpAddress, IpCidr};
+
+        let mut gateway = None;
+        self.interface.routes_mut().update(|routes| {
+            gateway = routes
+                .iter()
+                .find_map(|route| match (route.cidr, route.via_router) {
+                    (IpCidr::Ipv4(cidr), IpAddress::Ipv4(router)) if cidr.prefix_len() == 0 => {
+                        Some(router)
+                    }
+                    _ => None,
+                });
+        });
+        gateway
+    }
+
+    pub(super) fn set_ipv4_gateway(&mut self, gateway: Option<smoltcp::wire::Ipv4Address>) {
+        let routes = self.interface.routes_mut();
+        match gateway {
+            Some(gateway) => {
+                routes.add_default_ipv4_route(gateway).unwrap();
+            }
+            None => {
+                routes.remove_default_ipv4_route();
+            }
+        }
+    }
+
     /// Returns the next poll time.
     pub(super) fn next_poll_at_ms(&self) -> Option<u64> {
         self.pending_conns.next_poll_at_ms()
@@ -91,7 +91,7 @@ impl<T: Inner<E>, E: Ext> Socket<T, E> {
         self.0.observer.call_once(|| new_observer);
     }
 
-    pub fn local_endpoint(&self) -> Option<IpEndpoint> {
+    pub fn local_endpoint(&self) -> IpEndpoint {
         self.0.bound.endpoint()
     }
 
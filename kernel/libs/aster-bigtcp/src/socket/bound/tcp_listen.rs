@@ -73,9 +73,10 @@ impl<E: Ext> TcpListener<E> {
         option: &RawTcpOption,
         observer: E::TcpEventObserver,
     ) -> Result<Self, (BoundPort<E>, ListenError)> {
-        let Some(local_endpoint) = bound.endpoint() else {
+        if !bound.is_addr_available() {
             return Err((bound, ListenError::Unaddressable));
-        };
+        }
+        let local_endpoint = bound.endpoint();
 
         let iface = bound.iface().clone();
         let mut sockets = iface.common().sockets();
@@ -236,7 +237,10 @@ impl<E: Ext> TcpListenerBg<E> {
         let conn = TcpConnection::new_cyclic(
             self.bound
                 .iface()
-                .bind(BindPortConfig::CanReuse(self.bound.port()))
+                .bind(
+                    self.bound.endpoint().addr,
+                    BindPortConfig::CanReuse(self.bound.port()),
+                )
                 .unwrap(),
             |weak| {
                 TcpConnectionInner::new(
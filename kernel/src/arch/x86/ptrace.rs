@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The architecture-specific parts of `ptrace`.
+
+use ostd::{cpu::context::UserContext, mm::MAX_USERSPACE_VADDR, Pod};
+
+use crate::prelude::*;
+
+/// The user-mode registers exposed to tracers.
+///
+/// This is the same as `struct user_regs_struct` in Linux.
+#[derive(Debug, Clone, Copy, Pod, Default)]
+#[repr(C)]
+pub struct PtraceRegs {
+    pub r15: usize,
+    pub r14: usize,
+    pub r13: usize,
+    pub r12: usize,
+    pub rbp: usize,
+    pub rbx: usize,
+    pub r11: usize,
+    pub r10: usize,
+    pub r9: usize,
+    pub r8: usize,
+    pub rax: usize,
+    pub rcx: usize,
+    pub rdx: usize,
+    pub rsi: usize,
+    pub rdi: usize,
+    pub orig_rax: usize,
+    pub rip: usize,
+    pub cs: usize,
+    pub rflags: usize,
+    pub rsp: usize,
+    pub ss: usize,
+    pub fs_base: usize,
+    pub gs_base: usize,
+    pub ds: usize,
+    pub es: usize,
+    pub fs: usize,
+    pub gs: usize,
+}
+
+/// The user-mode code segment selector in Linux.
+const USER_CS: usize = 0x33;
+/// The user-mode data segment selector in Linux.
+const USER_DS: usize = 0x2b;
+
+/// The trap flag in `RFLAGS`, which enables single-stepping.
+const RFLAGS_TF: usize = 1 << 8;
+
+/// The `RFLAGS` bits that can be changed by tracers.
+///
+/// This includes CF, PF, AF, ZF, SF, TF, DF, OF, and AC.
+const RFLAGS_USER_MASK: usize = 0x40dd5;
+
+impl PtraceRegs {
+    /// Creates the registers from the user context.
+    ///
+    /// `orig_syscall_num` is the number of the system call if the tracee is
+    /// stopped at a system call, which is reported as `orig_rax`.
+    pub fn from_user_ctx(user_ctx: &UserContext, orig_syscall_num: Option<usize>) -> Self {
+        let regs = user_ctx.general_regs();
+        Self {
+            r15: regs.r15,
+            r14: regs.r14,
+            r13: regs.r13,
+            r12: regs.r12,
+            rbp: regs.rbp,
+            rbx: regs.rbx,
+            r11: regs.r11,
+            r10: regs.r10,
+            r9: regs.r9,
+            r8: regs.r8,
+            rax: regs.rax,
+            rcx: regs.rcx,
+            rdx: regs.rdx,
+            rsi: regs.rsi,
+            rdi: regs.rdi,
+            orig_rax: orig_syscall_num.unwrap_or(usize::MAX),
+            rip: regs.rip,
+            cs: USER_CS,
+            rflags: regs.rflags,
+            rsp: regs.rsp,
+            ss: USER_DS,
+            fs_base: regs.fsbase,
+            gs_base: regs.gsbase,
+            ds: 0,
+            es: 0,
+            fs: 0,
+            gs: 0,
+        }
+    }
+
+    /// Checks whether tracers can replace the `current` registers with these
+    /// registers.
+    ///
+    /// Like Linux, the FS base must be a user-space address. The GS base
+    /// cannot be changed, since it is never loaded for the user space.
+    pub fn check_writable(&self, current: &Self) -> Result<()> {
+        if self.fs_base >= MAX_USERSPACE_VADDR {
+            return_errno_with_message!(Errno::EIO, "the FS base is not a user-space address");
+        }
+        if self.gs_base != current.gs_base {
+            return_errno_with_message!(Errno::EIO, "the GS base cannot be changed");
+        }
+        Ok(())
+    }
+
+    /// Writes the registers back to the user context of the current thread.
+    ///
+    /// If the tracee is stopped at a system call, `orig_syscall_num` is
+    /// updated with `orig_rax`, so that tracers can change or skip the system
+    /// call.
+    pub fn write_to(
+        &self,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+        orig_syscall_num: &mut Option<usize>,
+    ) {
+        let regs = user_ctx.general_regs_mut();
+        regs.r15 = self.r15;
+        regs.r14 = self.r14;
+        regs.r13 = self.r13;
+        regs.r12 = self.r12;
+        regs.rbp = self.rbp;
+        regs.rbx = self.rbx;
+        regs.r11 = self.r11;
+        regs.r10 = self.r10;
+        regs.r9 = self.r9;
+        regs.r8 = self.r8;
+        regs.rax = self.rax;
+        regs.rcx = self.rcx;
+        regs.rdx = self.rdx;
+        regs.rsi = self.rsi;
+        regs.rdi = self.rdi;
+        regs.rip = self.rip;
+        regs.rflags = (regs.rflags & !RFLAGS_USER_MASK) | (self.rflags & RFLAGS_USER_MASK);
+        regs.rsp = self.rsp;
+
+        // The FS base is changed in the same way as `ARCH_SET_FS`.
+        if self.fs_base != user_ctx.tls_pointer() {
+            ctx.task.set_tls_pointer(self.fs_base);
+            user_ctx.set_tls_pointer(self.fs_base);
+            user_ctx.activate_tls_pointer();
+        }
+
+        if let Some(syscall_num) = orig_syscall_num {
+            *syscall_num = self.orig_rax;
+        }
+    }
+}
+
+/// Enables or disables single-stepping in the user context.
+pub fn set_single_step(user_ctx: &mut UserContext, is_enabled: bool) -> Result<()> {
+    let regs = user_ctx.general_regs_mut();
+    if is_enabled {
+        regs.rflags |= RFLAGS_TF;
+    } else {
+        regs.rflags &= !RFLAGS_TF;
+    }
+    Ok(())
+}
+
+/// Saves the floating-point registers of the current CPU and returns them.
+///
+/// The format is the same as `struct user_fpregs_struct` in Linux.
+pub fn save_fp_regs(user_ctx: &UserContext) -> Result<Vec<u8>> {
+    user_ctx.fpu_state().save();
+    Ok(user_ctx.fpu_state().as_fxsave_bytes().to_vec())
+}
+
+/// Prepares the user context to be inspected at a syscall-enter-stop.
+///
+/// Like Linux, `rax` is set to `-ENOSYS`, while the system call number is
+/// reported in `orig_rax`.
+pub fn prepare_syscall_enter_stop(user_ctx: &mut UserContext) {
+    user_ctx.set_rax(-(Errno::ENOSYS as i32) as usize);
+}
@@ -24,6 +24,9 @@ impl From<&CpuExceptionInfo> for FaultSignal {
             CpuException::BOUND_RANGE_EXCEEDED => (SIGSEGV, SEGV_BNDERR, None),
             CpuException::ALIGNMENT_CHECK => (SIGBUS, BUS_ADRALN, None),
             CpuException::INVALID_OPCODE => (SIGILL, ILL_ILLOPC, None),
+            CpuException::DEBUG => (SIGTRAP, TRAP_TRACE, None),
+            // Like Linux, `int3` generates a `SIGTRAP` with `SI_KERNEL`.
+            CpuException::BREAKPOINT => (SIGTRAP, SI_KERNEL, None),
             CpuException::GENERAL_PROTECTION_FAULT => (SIGBUS, BUS_ADRERR, None),
             CpuException::PAGE_FAULT => {
                 const PF_ERR_FLAG_PRESENT: usize = 1usize << 0;
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Block device nodes.
+//!
+//! Every block device registered in `aster_block` is exposed as a node in `/dev` (e.g.,
+//! `/dev/vda`), and every partition found in its partition table is exposed as a separate node
+//! (e.g., `/dev/vda1`). Like udev in Linux, the VirtIO block devices are also linked from
+//! `/dev/disk/by-id/virtio-<serial>`.
+
+use core::ops::Range;
+
+use align_ext::AlignExt;
+use aster_block::{
+    bio::BioStatus,
+    partition::{parse_partitions, Partition},
+    BlockDevice, SECTOR_SIZE,
+};
+use aster_virtio::device::block::device::BlockDevice as VirtIoBlockDevice;
+use ostd::mm::VmIo;
+
+use super::*;
+use crate::{
+    events::IoEvents,
+    fs::{device::add_symlink, inode_handle::FileIo, utils::IoctlCmd},
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        posix_thread::AsPosixThread,
+        signal::{PollHandle, Pollable},
+    },
+    ThreadOptions,
+};
+
+/// The number of minor device numbers per disk.
+///
+/// Like Linux VirtIO block devices, one is for the whole disk and the rest are for the
+/// partitions.
+const MINORS_PER_DISK: u32 = 16;
+
+/// The maximum number of bytes that are read or written with a single request.
+const MAX_IO_LEN: usize = 128 * 1024;
+
+static BLOCK_DEVICES: RwLock<BTreeMap<DeviceId, Arc<dyn BlockDevice>>> =
+    RwLock::new(BTreeMap::new());
+
+/// Initializes the block device nodes.
+///
+/// This must be called in a kernel thread, because the partition tables are read from the disks.
+pub(super) fn init() -> Result<()> {
+    // Like Linux VirtIO block devices, the major device number is allocated dynamically.
+    let major = register_major(DeviceType::BlockDevice, None, "virtblk")?;
+    let mut nr_virtio_disks = 0;
+
+    for (index, (name, disk)) in aster_block::all_devices().into_iter().enumerate() {
+        let Some(major_offset) = u32::try_from(index)
+            .ok()
+            .filter(|index| *index < (1 << 20) / MINORS_PER_DISK)
+        else {
+            warn!("too many block devices, `{}` is not added", name);
+            continue;
+        };
+        let first_minor = major_offset * MINORS_PER_DISK;
+
+        let (node_name, alias) = if disk.downcast_ref::<VirtIoBlockDevice>().is_some() {
+            start_virtio_requests(&disk);
+            let node_name = virtio_disk_name(nr_virtio_disks);
+            nr_virtio_disks += 1;
+            (node_name, Some(format!("disk/by-id/virtio-{}", name)))
+        } else {
+            (name.clone(), None)
+        };
+
+        add_block_file(&node_name, DeviceId::new(major, first_minor), disk.clone())?;
+        if let Some(alias) = alias.as_ref() {
+            add_symlink(&format!("../../{}", node_name), alias)?;
+        }
+
+        for info in parse_partitions(disk.as_ref()) {
+            if info.number >= MINORS_PER_DISK as usize {
+                warn!(
+                    "partition {} of `{}` exceeds the maximum number of partitions",
+                    info.number, node_name
+                );
+                continue;
+            }
+
+            let partition_name = partition_name(&node_name, info.number);
+            let partition = Arc::new(Partition::new(disk.clone(), info));
+            add_block_file(
+                &partition_name,
+                DeviceId::new(major, first_minor + info.number as u32),
+                partition,
+            )?;
+            if let Some(alias) = alias.as_ref() {
+                add_symlink(
+                    &format!("../../{}", partition_name),
+                    &format!("{}-part{}", alias, info.number),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the block device with the device ID.
+pub fn get_block_device(id: DeviceId) -> Option<Arc<dyn BlockDevice>> {
+    BLOCK_DEVICES.read().get(&id).cloned()
+}
+
+fn add_block_file(name: &str, id: DeviceId, device: Arc<dyn BlockDevice>) -> Result<()> {
+    let block_file = BlockFile {
+        id,
+        device: device.clone(),
+    };
+    add_device(Arc::new(block_file), name)?;
+    BLOCK_DEVICES.write().insert(id, device);
+
+    info!("add block device /dev/{} ({:?})", name, id);
+    Ok(())
+}
+
+/// Spawns a kernel thread to process the requests of the VirtIO block device.
+fn start_virtio_requests(disk: &Arc<dyn BlockDevice>) {
+    let disk = disk.clone();
+    let task_fn = move || {
+        info!("spawn the virt-io-block thread");
+        let virtio_block_device = disk.downcast_ref::<VirtIoBlockDevice>().unwrap();
+        loop {
+            virtio_block_device.handle_requests();
+        }
+    };
+    ThreadOptions::new(task_fn).spawn();
+}
+
+/// Returns the name of the VirtIO disk with the index (e.g., `vda`, `vdz`, and `vdaa`).
+fn virtio_disk_name(mut index: usize) -> String {
+    let mut suffix = Vec::new();
+    loop {
+        suffix.push(b'a' + (index % 26) as u8);
+        index /= 26;
+        if index == 0 {
+            break;
+        }
+        index -= 1;
+    }
+    suffix.reverse();
+
+    format!("vd{}", String::from_utf8(suffix).unwrap())
+}
+
+/// Returns the name of the partition of the disk (e.g., `vda1` and `nvme0n1p1`).
+fn partition_name(disk_name: &str, number: usize) -> String {
+    if disk_name.ends_with(|c: char| c.is_ascii_digit()) {
+        format!("{}p{}", disk_name, number)
+    } else {
+        format!("{}{}", disk_name, number)
+    }
+}
+
+/// A block device file, which represents a whole disk or a partition.
+#[derive(Clone)]
+struct BlockFile {
+    id: DeviceId,
+    device: Arc<dyn BlockDevice>,
+}
+
+impl BlockFile {
+    /// Runs `f` on each sector-aligned chunk of the byte range.
+    ///
+    /// The arguments of `f` are the aligned offset, the buffer of the aligned chunk, and the
+    /// range of the requested bytes in the buffer. `f` returns the number of bytes processed, and
+    /// the iteration stops if fewer bytes are processed than requested.
+    fn for_each_chunk(
+        &self,
+        offset: usize,
+        len: usize,
+        mut f: impl FnMut(usize, &mut [u8], Range<usize>) -> Result<usize>,
+    ) -> Result<usize> {
+        let mut done_len = 0;
+
+        while done_len < len {
+            let chunk_offset = offset + done_len;
+            let aligned_offset = chunk_offset.align_down(SECTOR_SIZE);
+            let aligned_end = (offset + len)
+                .min(aligned_offset + MAX_IO_LEN)
+                .align_up(SECTOR_SIZE);
+
+            let mut buffer = vec![0u8; aligned_end - aligned_offset];
+            let start = chunk_offset - aligned_offset;
+            let end = (offset + len - aligned_offset).min(buffer.len());
+
+            let chunk_len = match f(aligned_offset, &mut buffer, start..end) {
+                Ok(chunk_len) => chunk_len,
+                Err(err) if done_len == 0 => return Err(err),
+                Err(_) => break,
+            };
+            done_len += chunk_len;
+            if chunk_len < end - start {
+                break;
+            }
+        }
+
+        Ok(done_len)
+    }
+}
+
+impl Device for BlockFile {
+    fn type_(&self) -> DeviceType {
+        DeviceType::BlockDevice
+    }
+
+    fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
+        Ok(Some(Arc::new(self.clone())))
+    }
+}
+
+impl Pollable for BlockFile {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        let events = IoEvents::IN | IoEvents::OUT;
+        events & mask
+    }
+}
+
+impl FileIo for BlockFile {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::ESPIPE, "the block device must be read at an offset");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(
+            Errno::ESPIPE,
+            "the block device must be written at an offset"
+        );
+    }
+
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let size = self.size();
+        if offset >= size {
+            return Ok(0);
+        }
+        let len = writer.avail().min(size - offset);
+
+        self.for_each_chunk(offset, len, |aligned_offset, buffer, range| {
+            self.device.read_bytes(aligned_offset, buffer)?;
+            Ok(writer.write_fallible(&mut VmReader::from(&buffer[range]))?)
+        })
+    }
+
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let size = self.size();
+        if reader.remain() == 0 {
+            return Ok(0);
+        }
+        if offset >= size {
+            return_errno_with_message!(Errno::ENOSPC, "the offset exceeds the block device");
+        }
+        let len = reader.remain().min(size - offset);
+
+        self.for_each_chunk(offset, len, |aligned_offset, buffer, range| {
+            // Partially written sectors must be read first.
+            if range.start != 0 || range.end != buffer.len() {
+                self.device.read_bytes(aligned_offset, buffer)?;
+            }
+
+            let copied_len = reader.read_fallible(&mut VmWriter::from(&mut buffer[range]))?;
+            self.device.write_bytes(aligned_offset, buffer)?;
+            Ok(copied_len)
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.device.metadata().nr_sectors * SECTOR_SIZE
+    }
+
+    fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
+        match cmd {
+            IoctlCmd::BLKGETSIZE => {
+                let nr_sectors = self.device.metadata().nr_sectors as u64;
+                current_userspace!().write_val(arg, &nr_sectors)?;
+            }
+            IoctlCmd::BLKGETSIZE64 => {
+                let size = self.size() as u64;
+                current_userspace!().write_val(arg, &size)?;
+            }
+            IoctlCmd::BLKSSZGET => {
+                current_userspace!().write_val(arg, &(SECTOR_SIZE as i32))?;
+            }
+            IoctlCmd::BLKFLSBUF => {
+                let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+                if !credentials.effective_capset().contains(CapSet::SYS_ADMIN) {
+                    return_errno_with_message!(
+                        Errno::EACCES,
+                        "flushing the block device requires CAP_SYS_ADMIN"
+                    );
+                }
+
+                // There are no buffers in the kernel, so the disk cache is flushed instead.
+                let status = self.device.sync()?;
+                if status != BioStatus::Complete {
+                    return Err(status.into());
+                }
+            }
+            _ => return_errno_with_message!(
+                Errno::ENOTTY,
+                "the ioctl command is not supported by block devices"
+            ),
+        }
+
+        Ok(0)
+    }
+}
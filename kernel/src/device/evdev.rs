@@ -0,0 +1,508 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The event devices of input devices (`/dev/input/event*`).
+//!
+//! Like the evdev interface of Linux, every input device registered in `aster_input` is exposed
+//! as a character device, which produces `struct input_event`s. Every opened file is a client
+//! with its own queue of events, unless a client grabs the device with `EVIOCGRAB`, in which case
+//! only that client receives the events.
+//!
+//! Reference: <https://www.kernel.org/doc/html/latest/input/input.html#evdev>
+
+use alloc::collections::VecDeque;
+use core::{
+    sync::atomic::{AtomicI32, Ordering},
+    time::Duration,
+};
+
+use aster_input::{
+    key::{Key, KeyStatus},
+    InputEvent,
+};
+use ostd::sync::LocalIrqDisabled;
+
+use super::*;
+use crate::{
+    events::IoEvents,
+    fs::{inode_handle::FileIo, utils::RawIoctlCmd},
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    syscall::ClockId,
+    time::{
+        clocks::{BootTimeClock, MonotonicClock, RealTimeClock},
+        timeval_t, Clock,
+    },
+};
+
+/// The major device number of input devices, which is the same as Linux.
+const INPUT_MAJOR: u32 = 13;
+/// The first minor device number of event devices, which is the same as Linux.
+const EVDEV_MINOR_BASE: u32 = 64;
+/// The maximum number of event devices, which is the same as Linux.
+const EVDEV_MINORS: usize = 32;
+
+/// The maximum number of events queued for a client.
+const CLIENT_BUFFER_LEN: usize = 64;
+
+/// The version of the evdev protocol, which is the same as Linux.
+const EV_VERSION: i32 = 0x010001;
+/// The bus type of virtual devices.
+const BUS_VIRTUAL: u16 = 0x06;
+
+// Event types and codes.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/input-event-codes.h>
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const EV_MSC: u16 = 0x04;
+const EV_SW: u16 = 0x05;
+const EV_LED: u16 = 0x11;
+const EV_SND: u16 = 0x12;
+const EV_FF: u16 = 0x15;
+const EV_CNT: usize = 0x20;
+const SYN_REPORT: u16 = 0;
+const SYN_DROPPED: u16 = 3;
+const KEY_CNT: usize = 0x300;
+const REL_CNT: usize = 0x10;
+const ABS_CNT: usize = 0x40;
+const MSC_CNT: usize = 0x08;
+const SW_CNT: usize = 0x11;
+const LED_CNT: usize = 0x10;
+const SND_CNT: usize = 0x08;
+const FF_CNT: usize = 0x80;
+const INPUT_PROP_CNT: usize = 0x20;
+
+// The commands of evdev ioctls, which are identified by their numbers in the `E` type.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/input.h>
+const EVDEV_IOCTL_TYPE: u8 = b'E';
+const EVIOCGVERSION_NR: u8 = 0x01;
+const EVIOCGID_NR: u8 = 0x02;
+const EVIOCGNAME_NR: u8 = 0x06;
+const EVIOCGPHYS_NR: u8 = 0x07;
+const EVIOCGUNIQ_NR: u8 = 0x08;
+const EVIOCGPROP_NR: u8 = 0x09;
+const EVIOCGKEY_NR: u8 = 0x18;
+const EVIOCGLED_NR: u8 = 0x19;
+const EVIOCGSND_NR: u8 = 0x1a;
+const EVIOCGSW_NR: u8 = 0x1b;
+const EVIOCGBIT_NR_BASE: u8 = 0x20;
+const EVIOCGRAB_NR: u8 = 0x90;
+const EVIOCSCLOCKID_NR: u8 = 0xa0;
+
+pub(super) fn init() -> Result<()> {
+    register_major(DeviceType::CharDevice, Some(INPUT_MAJOR), "input")?;
+
+    for (index, (name, device)) in aster_input::all_devices().into_iter().enumerate() {
+        if index >= EVDEV_MINORS {
+            warn!("too many input devices, `{}` is not added", name);
+            break;
+        }
+
+        let evdev = Evdev::new(index as u32, device.name().to_string());
+        add_device(evdev.clone(), &format!("input/event{}", index))?;
+
+        // The event devices are never removed, so leaking the callback is fine.
+        let callback = Box::leak(Box::new(move |event: InputEvent| evdev.handle_event(event)));
+        device.register_callbacks(callback);
+    }
+
+    Ok(())
+}
+
+/// The `struct input_event` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct input_event_t {
+    time: timeval_t,
+    type_: u16,
+    code: u16,
+    value: i32,
+}
+
+/// The `struct input_id` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct input_id_t {
+    bustype: u16,
+    vendor: u16,
+    product: u16,
+    version: u16,
+}
+
+/// The event device of an input device.
+struct Evdev {
+    id: DeviceId,
+    name: String,
+    inner: SpinLock<EvdevInner, LocalIrqDisabled>,
+    weak_self: Weak<Self>,
+}
+
+struct EvdevInner {
+    /// The bitmap of the pressed keys.
+    key_state: [u8; KEY_CNT / 8],
+    clients: Vec<Weak<EvdevClient>>,
+    /// The client that grabs the device, which receives all the events exclusively.
+    grab: Option<Weak<EvdevClient>>,
+}
+
+impl Evdev {
+    fn new(index: u32, name: String) -> Arc<Self> {
+        Arc::new_cyclic(|weak_self| Self {
+            id: DeviceId::new(INPUT_MAJOR, EVDEV_MINOR_BASE + index),
+            name,
+            inner: SpinLock::new(EvdevInner {
+                key_state: [0; KEY_CNT / 8],
+                clients: Vec::new(),
+                grab: None,
+            }),
+            weak_self: weak_self.clone(),
+        })
+    }
+
+    /// Handles an event from the input device.
+    ///
+    /// This is called in the interrupt context.
+    fn handle_event(&self, event: InputEvent) {
+        let InputEvent::KeyBoard(key, status) = event;
+
+        let mut inner = self.inner.lock();
+
+        let code = key as u16;
+        let is_pressed = test_bit(&inner.key_state, code as usize);
+        let value = match (status, is_pressed) {
+            (KeyStatus::Pressed, false) => 1,
+            // Like Linux, pressing a pressed key means that the key is auto-repeated.
+            (KeyStatus::Pressed, true) => 2,
+            (KeyStatus::Released, true) => 0,
+            // Releasing a released key is meaningless, so the event is dropped.
+            (KeyStatus::Released, false) => return,
+        };
+        set_bit(&mut inner.key_state, code as usize, value != 0);
+
+        let clients: Vec<_> = if let Some(grab) = inner.grab.as_ref().and_then(Weak::upgrade) {
+            vec![grab]
+        } else {
+            inner.clients.iter().filter_map(Weak::upgrade).collect()
+        };
+        // The clients may be dropped here, which requires the lock.
+        drop(inner);
+
+        let events = [(EV_KEY, code, value), (EV_SYN, SYN_REPORT, 0)];
+        for client in clients.iter() {
+            client.push_events(&events);
+        }
+    }
+
+    fn set_grab(&self, client: &EvdevClient, is_grabbed: bool) -> Result<()> {
+        let mut inner = self.inner.lock();
+
+        let holder = inner.grab.as_ref().filter(|grab| grab.strong_count() > 0);
+        let is_held = holder.is_some();
+        let is_holder = holder.is_some_and(|grab| core::ptr::eq(grab.as_ptr(), client));
+
+        if is_grabbed {
+            // Like Linux, grabbing the device twice fails even if it is the same file.
+            if is_held {
+                return_errno_with_message!(Errno::EBUSY, "the device is grabbed");
+            }
+            inner.grab = Some(client.weak_self.clone());
+        } else {
+            if !is_holder {
+                return_errno_with_message!(Errno::EINVAL, "the device is not grabbed by the file");
+            }
+            inner.grab = None;
+        }
+
+        Ok(())
+    }
+
+    fn key_state(&self) -> [u8; KEY_CNT / 8] {
+        self.inner.lock().key_state
+    }
+}
+
+impl Device for Evdev {
+    fn type_(&self) -> DeviceType {
+        DeviceType::CharDevice
+    }
+
+    fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
+        let evdev = self.weak_self.upgrade().unwrap();
+        let client = Arc::new_cyclic(|weak_self| EvdevClient {
+            evdev,
+            queue: SpinLock::new(VecDeque::new()),
+            clock_id: AtomicI32::new(ClockId::CLOCK_REALTIME as i32),
+            pollee: Pollee::new(),
+            weak_self: weak_self.clone(),
+        });
+
+        let mut inner = self.inner.lock();
+        inner.clients.retain(|client| client.strong_count() > 0);
+        inner.clients.push(Arc::downgrade(&client));
+
+        Ok(Some(client))
+    }
+}
+
+/// An opened event device, which has its own queue of events.
+struct EvdevClient {
+    evdev: Arc<Evdev>,
+    queue: SpinLock<VecDeque<input_event_t>, LocalIrqDisabled>,
+    /// The clock of the timestamps of the events.
+    clock_id: AtomicI32,
+    pollee: Pollee,
+    weak_self: Weak<Self>,
+}
+
+impl EvdevClient {
+    fn push_events(&self, events: &[(u16, u16, i32)]) {
+        let time = timeval_t::from(self.now());
+        let mut queue = self.queue.lock();
+
+        for (type_, code, value) in events.iter().copied() {
+            if queue.len() >= CLIENT_BUFFER_LEN {
+                // Like Linux, the queued events are dropped, so that the client knows that it
+                // should resynchronize the state of the device.
+                queue.clear();
+                queue.push_back(input_event_t {
+                    time,
+                    type_: EV_SYN,
+                    code: SYN_DROPPED,
+                    value: 0,
+                });
+            }
+            queue.push_back(input_event_t {
+                time,
+                type_,
+                code,
+                value,
+            });
+        }
+
+        drop(queue);
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn now(&self) -> Duration {
+        match ClockId::try_from(self.clock_id.load(Ordering::Relaxed)) {
+            Ok(ClockId::CLOCK_MONOTONIC) => MonotonicClock::get().read_time(),
+            Ok(ClockId::CLOCK_BOOTTIME) => BootTimeClock::get().read_time(),
+            _ => RealTimeClock::get().read_time(),
+        }
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        // The events are copied to the user space without the lock held, since page faults may
+        // occur.
+        let events: Vec<_> = {
+            let mut queue = self.queue.lock();
+            if queue.is_empty() {
+                return_errno_with_message!(Errno::EAGAIN, "no events are available");
+            }
+            let nr_events = queue.len().min(writer.avail() / size_of::<input_event_t>());
+            queue.drain(..nr_events).collect()
+        };
+        self.pollee.invalidate();
+
+        for event in events.iter() {
+            writer.write_val(event)?;
+        }
+        Ok(events.len() * size_of::<input_event_t>())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.queue.lock().is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+
+    fn check_read_len(writer: &VmWriter) -> Result<()> {
+        if writer.avail() < size_of::<input_event_t>() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the buffer is too small to contain an input event"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for EvdevClient {
+    fn drop(&mut self) {
+        let mut inner = self.evdev.inner.lock();
+        inner.clients.retain(|client| client.strong_count() > 0);
+        if inner
+            .grab
+            .as_ref()
+            .is_some_and(|grab| grab.strong_count() == 0)
+        {
+            inner.grab = None;
+        }
+    }
+}
+
+impl Pollable for EvdevClient {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileIo for EvdevClient {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        Self::check_read_len(writer)?;
+        self.wait_events(IoEvents::IN, None, || self.try_read(writer))
+    }
+
+    fn read_nonblocking(&self, writer: &mut VmWriter) -> Result<usize> {
+        Self::check_read_len(writer)?;
+        self.try_read(writer)
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "injecting events into the input device is not supported"
+        );
+    }
+
+    fn ioctl_raw(&self, cmd: RawIoctlCmd, arg: usize) -> Result<i32> {
+        if cmd.type_() != EVDEV_IOCTL_TYPE {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the ioctl command is not supported by event devices"
+            );
+        }
+
+        let size = cmd.size();
+        let user_space = current_userspace!();
+
+        match cmd.nr() {
+            EVIOCGVERSION_NR => {
+                user_space.write_val(arg, &EV_VERSION)?;
+                Ok(0)
+            }
+            EVIOCGID_NR => {
+                let id = input_id_t {
+                    bustype: BUS_VIRTUAL,
+                    vendor: 0,
+                    product: 0,
+                    version: 0,
+                };
+                user_space.write_val(arg, &id)?;
+                Ok(0)
+            }
+            EVIOCGNAME_NR => {
+                let mut name = self.evdev.name.as_bytes().to_vec();
+                name.push(0);
+                write_bytes_to_user(arg, size, &name)
+            }
+            EVIOCGPHYS_NR | EVIOCGUNIQ_NR => {
+                return_errno_with_message!(Errno::ENOENT, "the device has no such information")
+            }
+            EVIOCGKEY_NR => write_bytes_to_user(arg, size, &self.evdev.key_state()),
+            // The device has no properties, LEDs, sounds, or switches.
+            EVIOCGPROP_NR => write_bytes_to_user(arg, size, &new_bitmap(INPUT_PROP_CNT)),
+            EVIOCGLED_NR => write_bytes_to_user(arg, size, &new_bitmap(LED_CNT)),
+            EVIOCGSND_NR => write_bytes_to_user(arg, size, &new_bitmap(SND_CNT)),
+            EVIOCGSW_NR => write_bytes_to_user(arg, size, &new_bitmap(SW_CNT)),
+            nr if (EVIOCGBIT_NR_BASE..EVIOCGBIT_NR_BASE + EV_CNT as u8).contains(&nr) => {
+                let bits = event_bits((nr - EVIOCGBIT_NR_BASE) as u16)?;
+                write_bytes_to_user(arg, size, &bits)
+            }
+            EVIOCGRAB_NR => {
+                self.evdev.set_grab(self, arg != 0)?;
+                Ok(0)
+            }
+            EVIOCSCLOCKID_NR => {
+                let clock_id = user_space.read_val::<i32>(arg)?;
+                match ClockId::try_from(clock_id) {
+                    Ok(
+                        ClockId::CLOCK_REALTIME
+                        | ClockId::CLOCK_MONOTONIC
+                        | ClockId::CLOCK_BOOTTIME,
+                    ) => {}
+                    _ => return_errno_with_message!(Errno::EINVAL, "the clock is not supported"),
+                }
+                self.clock_id.store(clock_id, Ordering::Relaxed);
+                Ok(0)
+            }
+            _ => return_errno_with_message!(
+                Errno::EINVAL,
+                "the ioctl command is not supported by event devices"
+            ),
+        }
+    }
+}
+
+/// Returns the bitmap of the supported event codes of the event type.
+///
+/// Only key events are supported, so the bitmaps of other event types are empty.
+fn event_bits(type_: u16) -> Result<Vec<u8>> {
+    let bits = match type_ {
+        EV_SYN => {
+            let mut bits = new_bitmap(EV_CNT);
+            set_bit(&mut bits, EV_SYN as usize, true);
+            set_bit(&mut bits, EV_KEY as usize, true);
+            bits
+        }
+        EV_KEY => {
+            let mut bits = new_bitmap(KEY_CNT);
+            // Like Linux, `KEY_RESERVED` (i.e., zero) is never reported.
+            for code in 1..KEY_CNT {
+                if Key::try_from(code as u16).is_ok() {
+                    set_bit(&mut bits, code, true);
+                }
+            }
+            bits
+        }
+        EV_REL => new_bitmap(REL_CNT),
+        EV_ABS => new_bitmap(ABS_CNT),
+        EV_MSC => new_bitmap(MSC_CNT),
+        EV_SW => new_bitmap(SW_CNT),
+        EV_LED => new_bitmap(LED_CNT),
+        EV_SND => new_bitmap(SND_CNT),
+        EV_FF => new_bitmap(FF_CNT),
+        _ => return_errno_with_message!(Errno::EINVAL, "the event type is invalid"),
+    };
+
+    Ok(bits)
+}
+
+/// Writes the bytes to the user buffer of `size` bytes, and returns the number of written bytes.
+///
+/// Like Linux, the bytes are truncated if the buffer is too small, and the rest of the buffer is
+/// left untouched if the buffer is too large.
+fn write_bytes_to_user(addr: Vaddr, size: usize, bytes: &[u8]) -> Result<i32> {
+    let len = size.min(bytes.len());
+    current_userspace!().write_bytes(addr, &mut VmReader::from(&bytes[..len]))?;
+    Ok(len as i32)
+}
+
+/// Creates a bitmap of `nr_bits` bits.
+///
+/// Like Linux, the bitmap consists of `u64`s, so its length is a multiple of 8 bytes.
+fn new_bitmap(nr_bits: usize) -> Vec<u8> {
+    vec![0; nr_bits.div_ceil(u64::BITS as usize) * size_of::<u64>()]
+}
+
+fn test_bit(bits: &[u8], index: usize) -> bool {
+    bits[index / 8] & (1 << (index % 8)) != 0
+}
+
+fn set_bit(bits: &mut [u8], index: usize, value: bool) {
+    if value {
+        bits[index / 8] |= 1 << (index % 8);
+    } else {
+        bits[index / 8] &= !(1 << (index % 8));
+    }
+}
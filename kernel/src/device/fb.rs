@@ -0,0 +1,448 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The framebuffer device (`/dev/fb0`).
+//!
+//! User page tables can only map tracked frames, so the I/O memory of the framebuffer cannot be
+//! mapped into the user space directly. Instead, like the deferred I/O of Linux framebuffers, the
+//! device is backed by a shadow buffer in RAM, which is what `read`, `write`, and `mmap` access.
+//! Writes are copied to the framebuffer immediately. While the shadow buffer is mapped, a kernel
+//! thread periodically copies the modified pages to the framebuffer, and `FBIOPAN_DISPLAY` copies
+//! them at once. The thread exits once the shadow buffer is no longer mapped.
+//!
+//! Reference: <https://www.kernel.org/doc/html/latest/fb/deferred_io.html>
+
+use core::time::Duration;
+
+use align_ext::AlignExt;
+use aster_framebuffer::{FrameBuffer, PixelFormat, FRAMEBUFFER};
+use aster_rights::Rights;
+use ostd::{
+    mm::{VmIo, PAGE_SIZE},
+    sync::WaitQueue,
+};
+
+use super::*;
+use crate::{
+    events::IoEvents,
+    fs::{inode_handle::FileIo, utils::IoctlCmd},
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+    vm::vmo::{Vmo, VmoOptions},
+    ThreadOptions, WaitTimeout,
+};
+
+/// The major device number of framebuffer devices, which is the same as Linux.
+const FB_MAJOR: u32 = 29;
+
+/// The interval between two copies of the mapped shadow buffer to the framebuffer.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(20);
+
+pub(super) fn init() -> Result<()> {
+    register_major(DeviceType::CharDevice, Some(FB_MAJOR), "fb")?;
+
+    let Some(framebuffer) = FRAMEBUFFER.get() else {
+        return Ok(());
+    };
+    add_device(FbDevice::new(framebuffer.clone())?, "fb0")
+}
+
+struct FbDevice {
+    framebuffer: Arc<FrameBuffer>,
+    /// The shadow buffer, which is read, written, and mapped by the user space.
+    shadow: Vmo<Rights>,
+    state: Mutex<FbState>,
+    weak_self: Weak<Self>,
+}
+
+struct FbState {
+    /// The content of the framebuffer, which tells the modified pages of the shadow buffer.
+    ///
+    /// This is `Some` if and only if the flush thread is running. Otherwise, the shadow buffer is
+    /// not mapped, and all writes to the shadow buffer are copied to the framebuffer immediately,
+    /// so the framebuffer is always up to date.
+    front: Option<Vec<u8>>,
+    is_blanked: bool,
+}
+
+impl FbDevice {
+    fn new(framebuffer: Arc<FrameBuffer>) -> Result<Arc<Self>> {
+        // The framebuffer is cleared when it is initialized, and so is the shadow buffer.
+        let shadow = VmoOptions::<Rights>::new(framebuffer.size().align_up(PAGE_SIZE)).alloc()?;
+        // The shadow buffer stands for the I/O memory, so it is never swapped out.
+        shadow.set_unevictable(true);
+
+        let device = Arc::new_cyclic(|weak_self| Self {
+            framebuffer,
+            shadow,
+            state: Mutex::new(FbState {
+                front: None,
+                is_blanked: false,
+            }),
+            weak_self: weak_self.clone(),
+        });
+
+        let weak_device = device.weak_self.clone();
+        device.shadow.set_mapping_hook(Box::new(move || {
+            if let Some(device) = weak_device.upgrade()
+                && let Err(err) = device.start_flushing_if_mapped()
+            {
+                warn!("failed to start flushing the framebuffer: {:?}", err);
+            }
+        }));
+
+        Ok(device)
+    }
+
+    /// Copies the modified pages of the shadow buffer to the framebuffer.
+    fn flush(&self) -> Result<()> {
+        self.flush_locked(&mut self.state.lock())
+    }
+
+    fn flush_locked(&self, state: &mut FbState) -> Result<()> {
+        let FbState {
+            front: Some(front),
+            is_blanked: false,
+        } = state
+        else {
+            return Ok(());
+        };
+
+        let mut buffer = [0u8; PAGE_SIZE];
+        for (index, front_page) in front.chunks_mut(PAGE_SIZE).enumerate() {
+            let offset = index * PAGE_SIZE;
+            let page = &mut buffer[..front_page.len()];
+
+            self.shadow.read_bytes(offset, page)?;
+            if page != front_page {
+                self.framebuffer.write_bytes_at(offset, page)?;
+                front_page.copy_from_slice(page);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blanks or unblanks the display.
+    ///
+    /// The display is blanked by filling the framebuffer with black. The shadow buffer is kept,
+    /// and it is copied back when the display is unblanked.
+    fn set_blanked(&self, is_blanked: bool) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.is_blanked == is_blanked {
+            return Ok(());
+        }
+
+        if is_blanked {
+            self.framebuffer.clear();
+        } else {
+            let mut content = vec![0u8; self.framebuffer.size()];
+            self.shadow.read_bytes(0, &mut content)?;
+            self.framebuffer.write_bytes_at(0, &content)?;
+            if state.front.is_some() {
+                state.front = Some(content);
+            }
+        }
+        state.is_blanked = is_blanked;
+
+        Ok(())
+    }
+
+    /// Starts the flush thread if the shadow buffer is mapped and the thread is not running.
+    fn start_flushing_if_mapped(&self) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.front.is_some() || self.shadow.num_mappings() == 0 {
+            return Ok(());
+        }
+
+        let mut front = vec![0u8; self.framebuffer.size()];
+        self.shadow.read_bytes(0, &mut front)?;
+        state.front = Some(front);
+
+        let device = self.weak_self.upgrade().unwrap();
+        let task_fn = move || {
+            let wait_queue = WaitQueue::new();
+            loop {
+                let _ = wait_queue.wait_until_or_timeout(|| None::<()>, &FLUSH_INTERVAL);
+
+                let mut state = device.state.lock();
+                if let Err(err) = device.flush_locked(&mut state) {
+                    warn!("failed to flush the framebuffer: {:?}", err);
+                }
+                // The thread is started again by the mapping hook if the shadow buffer is mapped
+                // later, which checks the state with the same lock held.
+                if device.shadow.num_mappings() == 0 {
+                    state.front = None;
+                    return;
+                }
+            }
+        };
+        ThreadOptions::new(task_fn).spawn();
+
+        Ok(())
+    }
+
+    fn var_screeninfo(&self) -> FbVarScreeninfo {
+        let width = self.framebuffer.width() as u32;
+        let height = self.framebuffer.height() as u32;
+        let bitfield = |offset, length| FbBitfield {
+            offset,
+            length,
+            msb_right: 0,
+        };
+
+        let (bits_per_pixel, grayscale, red, green, blue) = match self.framebuffer.pixel_format() {
+            PixelFormat::Grayscale8 => (8, 1, bitfield(0, 8), bitfield(0, 8), bitfield(0, 8)),
+            PixelFormat::Rgb565 => (16, 0, bitfield(11, 5), bitfield(5, 6), bitfield(0, 5)),
+            PixelFormat::Rgb888 => (24, 0, bitfield(0, 8), bitfield(8, 8), bitfield(16, 8)),
+            PixelFormat::BgrReserved => (32, 0, bitfield(16, 8), bitfield(8, 8), bitfield(0, 8)),
+        };
+
+        FbVarScreeninfo {
+            xres: width,
+            yres: height,
+            xres_virtual: width,
+            yres_virtual: height,
+            bits_per_pixel,
+            grayscale,
+            red,
+            green,
+            blue,
+            // The physical size of the display is unknown.
+            height: u32::MAX,
+            width: u32::MAX,
+            ..FbVarScreeninfo::new_zeroed()
+        }
+    }
+
+    fn fix_screeninfo(&self) -> FbFixScreeninfo {
+        let mut id = [0u8; 16];
+        id[..FB_ID.len()].copy_from_slice(FB_ID);
+
+        FbFixScreeninfo {
+            id,
+            // The shadow buffer, rather than the I/O memory, is mapped by `mmap`, so the physical
+            // address of the I/O memory is not reported.
+            smem_start: 0,
+            smem_len: self.framebuffer.size() as u32,
+            type_: FB_TYPE_PACKED_PIXELS,
+            visual: FB_VISUAL_TRUECOLOR,
+            line_length: (self.framebuffer.width() * self.framebuffer.pixel_format().nbytes())
+                as u32,
+            ..FbFixScreeninfo::new_zeroed()
+        }
+    }
+}
+
+impl Device for FbDevice {
+    fn type_(&self) -> DeviceType {
+        DeviceType::CharDevice
+    }
+
+    fn id(&self) -> DeviceId {
+        DeviceId::new(FB_MAJOR, 0)
+    }
+
+    fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
+        Ok(Some(self.weak_self.upgrade().unwrap()))
+    }
+}
+
+impl Pollable for FbDevice {
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        let events = IoEvents::IN | IoEvents::OUT;
+        events & mask
+    }
+}
+
+impl FileIo for FbDevice {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::ESPIPE, "the framebuffer must be read at an offset");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(
+            Errno::ESPIPE,
+            "the framebuffer must be written at an offset"
+        );
+    }
+
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let size = self.size();
+        if offset >= size {
+            return Ok(0);
+        }
+
+        let mut buffer = vec![0u8; writer.avail().min(size - offset)];
+        self.shadow.read_bytes(offset, &mut buffer)?;
+        Ok(writer.write_fallible(&mut VmReader::from(buffer.as_slice()))?)
+    }
+
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let size = self.size();
+        if offset > size {
+            return_errno_with_message!(Errno::EFBIG, "the offset exceeds the framebuffer");
+        }
+        if reader.remain() == 0 {
+            return Ok(0);
+        }
+        if offset == size {
+            return_errno_with_message!(Errno::ENOSPC, "the framebuffer is full");
+        }
+
+        let mut buffer = vec![0u8; reader.remain().min(size - offset)];
+        let len = reader.read_fallible(&mut VmWriter::from(buffer.as_mut_slice()))?;
+        let buffer = &buffer[..len];
+
+        let mut state = self.state.lock();
+        self.shadow.write_bytes(offset, buffer)?;
+        if !state.is_blanked {
+            self.framebuffer.write_bytes_at(offset, buffer)?;
+            if let Some(front) = state.front.as_mut() {
+                front[offset..offset + len].copy_from_slice(buffer);
+            }
+        }
+
+        Ok(len)
+    }
+
+    fn size(&self) -> usize {
+        self.framebuffer.size()
+    }
+
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        // The flush thread is started by the mapping hook once the shadow buffer is mapped.
+        Ok((self.shadow.dup()?, offset))
+    }
+
+    fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
+        match cmd {
+            IoctlCmd::FBIOGET_VSCREENINFO => {
+                current_userspace!().write_val(arg, &self.var_screeninfo())?;
+            }
+            IoctlCmd::FBIOPUT_VSCREENINFO => {
+                let var: FbVarScreeninfo = current_userspace!().read_val(arg)?;
+                let current = self.var_screeninfo();
+                if var.xres != current.xres
+                    || var.yres != current.yres
+                    || var.xres_virtual != current.xres_virtual
+                    || var.yres_virtual != current.yres_virtual
+                    || var.bits_per_pixel != current.bits_per_pixel
+                {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the video mode of the framebuffer cannot be changed"
+                    );
+                }
+                current_userspace!().write_val(arg, &current)?;
+            }
+            IoctlCmd::FBIOGET_FSCREENINFO => {
+                current_userspace!().write_val(arg, &self.fix_screeninfo())?;
+            }
+            IoctlCmd::FBIOPAN_DISPLAY => {
+                let var: FbVarScreeninfo = current_userspace!().read_val(arg)?;
+                // The virtual resolution is the same as the visible resolution.
+                if var.xoffset != 0 || var.yoffset != 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the display is panned beyond the virtual resolution"
+                    );
+                }
+                self.flush()?;
+            }
+            IoctlCmd::FBIOBLANK => match arg {
+                FB_BLANK_UNBLANK => self.set_blanked(false)?,
+                FB_BLANK_NORMAL..=FB_BLANK_POWERDOWN => self.set_blanked(true)?,
+                _ => return_errno_with_message!(Errno::EINVAL, "the blank level is invalid"),
+            },
+            _ => return_errno_with_message!(
+                Errno::ENOTTY,
+                "the ioctl command is not supported by framebuffers"
+            ),
+        }
+
+        Ok(0)
+    }
+}
+
+const FB_ID: &[u8] = b"Asterinas FB";
+
+const FB_TYPE_PACKED_PIXELS: u32 = 0;
+const FB_VISUAL_TRUECOLOR: u32 = 2;
+
+const FB_BLANK_UNBLANK: usize = 0;
+const FB_BLANK_NORMAL: usize = 1;
+const FB_BLANK_POWERDOWN: usize = 4;
+
+/// The variable information of the framebuffer, which is `struct fb_var_screeninfo` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct FbVarScreeninfo {
+    xres: u32,
+    yres: u32,
+    xres_virtual: u32,
+    yres_virtual: u32,
+    xoffset: u32,
+    yoffset: u32,
+    bits_per_pixel: u32,
+    grayscale: u32,
+    red: FbBitfield,
+    green: FbBitfield,
+    blue: FbBitfield,
+    transp: FbBitfield,
+    nonstd: u32,
+    activate: u32,
+    /// The height of the picture in millimeters.
+    height: u32,
+    /// The width of the picture in millimeters.
+    width: u32,
+    accel_flags: u32,
+    pixclock: u32,
+    left_margin: u32,
+    right_margin: u32,
+    upper_margin: u32,
+    lower_margin: u32,
+    hsync_len: u32,
+    vsync_len: u32,
+    sync: u32,
+    vmode: u32,
+    rotate: u32,
+    colorspace: u32,
+    reserved: [u32; 4],
+}
+
+/// The position of a color in a pixel, which is `struct fb_bitfield` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct FbBitfield {
+    offset: u32,
+    length: u32,
+    msb_right: u32,
+}
+
+/// The fixed information of the framebuffer, which is `struct fb_fix_screeninfo` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct FbFixScreeninfo {
+    id: [u8; 16],
+    smem_start: u64,
+    smem_len: u32,
+    type_: u32,
+    type_aux: u32,
+    visual: u32,
+    xpanstep: u16,
+    ypanstep: u16,
+    ywrapstep: u16,
+    _pad0: u16,
+    line_length: u32,
+    _pad1: u32,
+    mmio_start: u64,
+    mmio_len: u32,
+    accel: u32,
+    capabilities: u16,
+    reserved: [u16; 2],
+    _pad2: u16,
+}
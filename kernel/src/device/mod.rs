@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
+mod block;
+mod evdev;
+mod fb;
 mod null;
 mod pty;
 mod random;
+mod registry;
 mod shm;
 pub mod tty;
 mod urandom;
@@ -11,59 +15,63 @@ mod zero;
 #[cfg(all(target_arch = "x86_64", feature = "cvm_guest"))]
 mod tdxguest;
 
+pub use block::get_block_device;
 pub use pty::{new_pty_pair, PtyMaster, PtySlave};
 pub use random::Random;
+pub use registry::{
+    add_device, lookup_device, register_device, register_driver, register_major, registered_majors,
+    DeviceDriver, MISC_MAJOR,
+};
 pub use urandom::Urandom;
 
 use self::tty::get_n_tty;
 use crate::{
-    fs::device::{add_node, Device, DeviceId, DeviceType},
+    fs::device::{Device, DeviceId, DeviceType},
     prelude::*,
 };
 
 /// Init the device node in fs, must be called after mounting rootfs.
 pub fn init() -> Result<()> {
+    register_major(DeviceType::CharDevice, Some(1), "mem")?;
+    register_major(DeviceType::CharDevice, Some(5), "/dev/tty")?;
+    register_major(DeviceType::CharDevice, Some(MISC_MAJOR), "misc")?;
+
     let null = Arc::new(null::Null);
-    add_node(null, "null")?;
+    add_device(null, "null")?;
     let zero = Arc::new(zero::Zero);
-    add_node(zero, "zero")?;
+    add_device(zero, "zero")?;
     tty::init();
     let console = get_n_tty().clone();
-    add_node(console, "console")?;
+    add_device(console, "console")?;
     let tty = Arc::new(tty::TtyDevice);
-    add_node(tty, "tty")?;
+    add_device(tty, "tty")?;
     #[cfg(target_arch = "x86_64")]
     ostd::if_tdx_enabled!({
-        add_node(Arc::new(tdxguest::TdxGuest), "tdx_guest")?;
+        add_device(Arc::new(tdxguest::TdxGuest), "tdx_guest")?;
     });
     let random = Arc::new(random::Random);
-    add_node(random, "random")?;
+    add_device(random, "random")?;
     let urandom = Arc::new(urandom::Urandom);
-    add_node(urandom, "urandom")?;
+    add_device(urandom, "urandom")?;
     pty::init()?;
     shm::init()?;
+    fb::init()?;
+    evdev::init()?;
     Ok(())
 }
 
-// TODO: Implement a more scalable solution for ID-to-device mapping.
-// Instead of hardcoding every device numbers in this function,
-// a registration mechanism should be used to allow each driver to
-// allocate device IDs either statically or dynamically.
-pub fn get_device(dev: usize) -> Result<Arc<dyn Device>> {
+/// Init the device nodes that require kernel threads, must be called in the init thread.
+pub fn lazy_init() -> Result<()> {
+    block::init()
+}
+
+/// Returns the device with the device number, which is used to create device nodes.
+pub fn get_device(type_: DeviceType, dev: usize) -> Result<Arc<dyn Device>> {
     if dev == 0 {
         return_errno_with_message!(Errno::EPERM, "whiteout device")
     }
 
     let devid = DeviceId::from(dev as u64);
-    let major = devid.major();
-    let minor = devid.minor();
-
-    match (major, minor) {
-        (1, 3) => Ok(Arc::new(null::Null)),
-        (1, 5) => Ok(Arc::new(zero::Zero)),
-        (5, 0) => Ok(Arc::new(tty::TtyDevice)),
-        (1, 8) => Ok(Arc::new(random::Random)),
-        (1, 9) => Ok(Arc::new(urandom::Urandom)),
-        _ => return_errno_with_message!(Errno::EINVAL, "unsupported device"),
-    }
+    lookup_device(type_, devid)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unsupported device"))
 }
@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use super::{register_device, register_driver, DeviceDriver};
 use crate::{
     fs::{
+        device::{Device, DeviceType},
         devpts::DevPts,
         fs_resolver::{FsPath, FsResolver},
         path::Dentry,
@@ -16,6 +18,11 @@ mod pty;
 pub use pty::{PtyMaster, PtySlave};
 use spin::Once;
 
+/// The major device number of pseudo-terminal slaves, which is the same as Linux.
+///
+/// The minor device number is the index of the slave.
+const SLAVE_MAJOR: u32 = 136;
+
 static DEV_PTS: Once<Dentry> = Once::new();
 
 pub fn init() -> Result<()> {
@@ -30,6 +37,15 @@ pub fn init() -> Result<()> {
 
     DEV_PTS.call_once(|| devpts);
 
+    let ptmx_device = DEV_PTS.get().unwrap().inode().lookup("ptmx")?.as_device();
+    register_device(ptmx_device.unwrap())?;
+    register_driver(
+        DeviceType::CharDevice,
+        Some(SLAVE_MAJOR),
+        "pts",
+        Arc::new(PtySlaveDriver),
+    )?;
+
     // Create the "ptmx" symlink.
     let ptmx = dev.new_fs_child(
         "ptmx",
@@ -46,3 +62,13 @@ pub fn new_pty_pair(index: u32, ptmx: Arc<dyn Inode>) -> Result<(Arc<PtyMaster>,
     let slave = PtySlave::new(&master);
     Ok((master, slave))
 }
+
+/// The driver of pseudo-terminal slaves, which are looked up in devpts.
+struct PtySlaveDriver;
+
+impl DeviceDriver for PtySlaveDriver {
+    fn device(&self, minor: u32) -> Option<Arc<dyn Device>> {
+        let devpts = DEV_PTS.get()?;
+        devpts.inode().lookup(&minor.to_string()).ok()?.as_device()
+    }
+}
@@ -325,7 +325,7 @@ impl Device for PtySlave {
     }
 
     fn id(&self) -> crate::fs::device::DeviceId {
-        DeviceId::new(88, self.index())
+        DeviceId::new(super::SLAVE_MAJOR, self.index())
     }
 }
 
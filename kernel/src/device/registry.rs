@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The registry of device numbers.
+//!
+//! Like Linux, each major device number belongs to a driver. It is either assigned statically
+//! (e.g., 1 for memory devices like `/dev/null`) or allocated dynamically (e.g., for VirtIO block
+//! devices). The registry maps device IDs to devices, so that device nodes created by `mknod` can
+//! find their devices.
+//!
+//! Character devices and block devices have separate namespaces of major device numbers. Misc
+//! devices are character devices whose major device number is [`MISC_MAJOR`].
+
+use crate::{
+    fs::device::{add_node, Device, DeviceId, DeviceType},
+    prelude::*,
+};
+
+/// The major device number of misc devices.
+pub const MISC_MAJOR: u32 = 10;
+
+/// The range of dynamically allocated major device numbers.
+///
+/// Like Linux, the major device numbers are allocated from the end of the range.
+const DYNAMIC_MAJORS: core::ops::RangeInclusive<u32> = 234..=254;
+
+/// A driver that provides the devices of a major device number.
+///
+/// This is for devices that come and go without nodes in `/dev` (e.g., pseudo-terminal slaves).
+/// Other devices are added one by one with [`add_device`].
+pub trait DeviceDriver: Send + Sync {
+    /// Returns the device with the minor device number, if it exists.
+    fn device(&self, minor: u32) -> Option<Arc<dyn Device>>;
+}
+
+/// Registers a major device number whose devices are added with [`add_device`].
+///
+/// If `major` is `None`, a major device number is allocated dynamically. The registered major
+/// device number is returned.
+pub fn register_major(type_: DeviceType, major: Option<u32>, name: &'static str) -> Result<u32> {
+    REGISTRY
+        .write()
+        .register(type_, major, name, Minors::Table(BTreeMap::new()))
+}
+
+/// Registers a major device number whose devices are provided by the driver.
+///
+/// If `major` is `None`, a major device number is allocated dynamically. The registered major
+/// device number is returned.
+pub fn register_driver(
+    type_: DeviceType,
+    major: Option<u32>,
+    name: &'static str,
+    driver: Arc<dyn DeviceDriver>,
+) -> Result<u32> {
+    REGISTRY
+        .write()
+        .register(type_, major, name, Minors::Driver(driver))
+}
+
+/// Adds the device to the registry without creating a node in `/dev`.
+///
+/// The major device number of the device must have been registered with [`register_major`].
+pub fn register_device(device: Arc<dyn Device>) -> Result<()> {
+    REGISTRY.write().insert(device)
+}
+
+/// Adds the device to the registry and creates its node in `/dev`.
+///
+/// The major device number of the device must have been registered with [`register_major`].
+pub fn add_device(device: Arc<dyn Device>, path: &str) -> Result<()> {
+    register_device(device.clone())?;
+
+    if let Err(err) = add_node(device.clone(), path) {
+        REGISTRY.write().remove(device.as_ref());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Returns the device with the device type and the device ID.
+pub fn lookup_device(type_: DeviceType, id: DeviceId) -> Option<Arc<dyn Device>> {
+    let driver = {
+        let registry = REGISTRY.read();
+        match &registry.majors(type_).get(&id.major())?.minors {
+            Minors::Table(devices) => return devices.get(&id.minor()).cloned(),
+            Minors::Driver(driver) => driver.clone(),
+        }
+    };
+
+    // The driver is called without the registry locked, since it may take its own locks.
+    driver.device(id.minor())
+}
+
+/// Returns the registered major device numbers and their names, in ascending order.
+pub fn registered_majors(type_: DeviceType) -> Vec<(u32, &'static str)> {
+    REGISTRY
+        .read()
+        .majors(type_)
+        .iter()
+        .map(|(major, entry)| (*major, entry.name))
+        .collect()
+}
+
+static REGISTRY: RwLock<Registry> = RwLock::new(Registry {
+    char_majors: BTreeMap::new(),
+    block_majors: BTreeMap::new(),
+});
+
+struct Registry {
+    char_majors: BTreeMap<u32, Major>,
+    block_majors: BTreeMap<u32, Major>,
+}
+
+struct Major {
+    name: &'static str,
+    minors: Minors,
+}
+
+enum Minors {
+    Table(BTreeMap<u32, Arc<dyn Device>>),
+    Driver(Arc<dyn DeviceDriver>),
+}
+
+impl Registry {
+    fn majors(&self, type_: DeviceType) -> &BTreeMap<u32, Major> {
+        match type_ {
+            DeviceType::CharDevice | DeviceType::MiscDevice => &self.char_majors,
+            DeviceType::BlockDevice => &self.block_majors,
+        }
+    }
+
+    fn majors_mut(&mut self, type_: DeviceType) -> &mut BTreeMap<u32, Major> {
+        match type_ {
+            DeviceType::CharDevice | DeviceType::MiscDevice => &mut self.char_majors,
+            DeviceType::BlockDevice => &mut self.block_majors,
+        }
+    }
+
+    fn register(
+        &mut self,
+        type_: DeviceType,
+        major: Option<u32>,
+        name: &'static str,
+        minors: Minors,
+    ) -> Result<u32> {
+        let majors = self.majors_mut(type_);
+
+        let major = match major {
+            Some(major) if majors.contains_key(&major) => {
+                return_errno_with_message!(Errno::EBUSY, "the major device number is in use")
+            }
+            Some(major) if major >= 1 << 12 => {
+                return_errno_with_message!(Errno::EINVAL, "the major device number is too large")
+            }
+            Some(major) => major,
+            None => DYNAMIC_MAJORS
+                .rev()
+                .find(|major| !majors.contains_key(major))
+                .ok_or_else(|| {
+                    Error::with_message(Errno::EBUSY, "no major device numbers are available")
+                })?,
+        };
+
+        majors.insert(major, Major { name, minors });
+        Ok(major)
+    }
+
+    fn insert(&mut self, device: Arc<dyn Device>) -> Result<()> {
+        let id = device.id();
+
+        let Some(major) = self.majors_mut(device.type_()).get_mut(&id.major()) else {
+            return_errno_with_message!(Errno::ENXIO, "the major device number is not registered");
+        };
+        let Minors::Table(devices) = &mut major.minors else {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the major device number is managed by a driver"
+            );
+        };
+        if devices.contains_key(&id.minor()) {
+            return_errno_with_message!(Errno::EEXIST, "the device ID is in use");
+        }
+
+        devices.insert(id.minor(), device);
+        Ok(())
+    }
+
+    fn remove(&mut self, device: &dyn Device) {
+        let id = device.id();
+
+        if let Some(major) = self.majors_mut(device.type_()).get_mut(&id.major())
+            && let Minors::Table(devices) = &mut major.minors
+        {
+            devices.remove(&id.minor());
+        }
+    }
+}
@@ -29,7 +29,7 @@ impl Device for TdxGuest {
     }
 
     fn id(&self) -> DeviceId {
-        DeviceId::new(0xa, 0x7b)
+        DeviceId::new(MISC_MAJOR, 0x7b)
     }
 }
 
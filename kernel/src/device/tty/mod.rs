@@ -185,7 +185,7 @@ impl Device for Tty {
 
     fn id(&self) -> DeviceId {
         // The same value as /dev/console in linux.
-        DeviceId::new(88, 0)
+        DeviceId::new(5, 1)
     }
 }
 
@@ -3,14 +3,8 @@
 use alloc::string::ToString;
 
 use aster_framebuffer::{CONSOLE_NAME, FRAMEBUFFER_CONSOLE};
-use log::info;
 
 pub fn init() {
-    // print all the input device to make sure input crate will compile
-    for (name, _) in aster_input::all_devices() {
-        info!("Found Input device, name:{}", name);
-    }
-
     if let Some(console) = FRAMEBUFFER_CONSOLE.get() {
         aster_console::register_device(CONSOLE_NAME.to_string(), console.clone());
     }
@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::{
+    file::{CgroupFile, ControlFile},
+    CgroupFs, BLOCK_SIZE, ROOT_INO,
+};
+use crate::{
+    fs::utils::{DirentVisitor, FileSystem, Inode, InodeMode, InodeType, Metadata, NAME_MAX},
+    prelude::*,
+    process::{cgroup::Cgroup, Gid, Uid},
+    time::clocks::RealTimeCoarseClock,
+};
+
+/// The inode of a cgroup directory.
+pub(super) struct CgroupDir {
+    cgroup: Arc<Cgroup>,
+    /// The parent directory, which is `None` for the root directory.
+    parent: Option<Weak<CgroupDir>>,
+    this: Weak<CgroupDir>,
+    children: RwMutex<BTreeMap<String, Arc<CgroupDir>>>,
+    /// The interface files, including the invisible ones.
+    files: Vec<Arc<CgroupFile>>,
+    metadata: RwLock<Metadata>,
+    fs: Weak<CgroupFs>,
+}
+
+impl CgroupDir {
+    pub(super) fn new_root(cgroup: Arc<Cgroup>, fs: Weak<CgroupFs>) -> Arc<Self> {
+        // The file system is being constructed, so the inode numbers of the
+        // interface files cannot be allocated from it. They are the ones
+        // following the root inode number instead.
+        Self::new(ROOT_INO, cgroup, None, ROOT_INO + 1.., fs)
+    }
+
+    fn new_child(&self, cgroup: Arc<Cgroup>) -> Arc<Self> {
+        let fs = self.fs.upgrade().unwrap();
+        let file_inos = core::iter::repeat_with(|| fs.alloc_ino());
+        Self::new(
+            fs.alloc_ino(),
+            cgroup,
+            Some(self.this.clone()),
+            file_inos,
+            self.fs.clone(),
+        )
+    }
+
+    fn new(
+        ino: u64,
+        cgroup: Arc<Cgroup>,
+        parent: Option<Weak<CgroupDir>>,
+        file_inos: impl Iterator<Item = u64>,
+        fs: Weak<CgroupFs>,
+    ) -> Arc<Self> {
+        let files = ControlFile::ALL
+            .iter()
+            .zip(file_inos)
+            .map(|(kind, ino)| CgroupFile::new(ino, *kind, cgroup.clone(), fs.clone()))
+            .collect();
+
+        Arc::new_cyclic(|this| Self {
+            cgroup,
+            parent,
+            this: this.clone(),
+            children: RwMutex::new(BTreeMap::new()),
+            files,
+            metadata: RwLock::new(Metadata::new_dir(
+                ino,
+                InodeMode::from_bits_truncate(0o755),
+                BLOCK_SIZE,
+            )),
+            fs,
+        })
+    }
+
+    pub(super) fn cgroup(&self) -> &Arc<Cgroup> {
+        &self.cgroup
+    }
+
+    fn visible_files(&self) -> impl Iterator<Item = &Arc<CgroupFile>> {
+        self.files
+            .iter()
+            .filter(|file| file.kind().is_visible_in(&self.cgroup))
+    }
+
+    fn touch(&self) {
+        let now = RealTimeCoarseClock::get().read_time();
+        let mut metadata = self.metadata.write();
+        metadata.mtime = now;
+        metadata.ctime = now;
+    }
+}
+
+impl Inode for CgroupDir {
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn type_(&self) -> InodeType {
+        InodeType::Dir
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().atime
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().atime = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().mtime
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().mtime = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().ctime
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().ctime = time;
+    }
+
+    fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        if type_ != InodeType::Dir {
+            return_errno_with_message!(Errno::EPERM, "only cgroups can be created");
+        }
+        if name.len() > NAME_MAX {
+            return_errno!(Errno::ENAMETOOLONG);
+        }
+        if self.visible_files().any(|file| file.kind().name() == name) {
+            return_errno_with_message!(Errno::EEXIST, "the name is used by an interface file");
+        }
+
+        let mut children = self.children.write();
+        let cgroup = self.cgroup.create_child(name)?;
+        let child = self.new_child(cgroup);
+        child.set_mode(mode)?;
+        children.insert(name.to_string(), child.clone());
+        drop(children);
+
+        self.touch();
+        Ok(child)
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the 2 special entries.
+            if *offset == 0 {
+                visitor.visit(".", self.ino(), InodeType::Dir, *offset)?;
+                *offset += 1;
+            }
+            if *offset == 1 {
+                let parent_ino = match &self.parent {
+                    Some(parent) => parent.upgrade().unwrap().ino(),
+                    None => self.ino(),
+                };
+                visitor.visit("..", parent_ino, InodeType::Dir, *offset)?;
+                *offset += 1;
+            }
+
+            // Read the child cgroups and the interface files.
+            let children = self.children.read();
+            let child_entries = children
+                .iter()
+                .map(|(name, child)| (name.as_str(), child.ino(), InodeType::Dir));
+            let file_entries = self
+                .visible_files()
+                .map(|file| (file.kind().name(), file.ino(), InodeType::File));
+            for (idx, (name, ino, type_)) in child_entries
+                .chain(file_entries)
+                .enumerate()
+                .skip(*offset - 2)
+            {
+                visitor.visit(name, ino, type_, idx + 2)?;
+                *offset = idx + 3;
+            }
+            Ok(())
+        };
+
+        let mut iterate_offset = offset;
+        match try_readdir(&mut iterate_offset, visitor) {
+            Err(e) if offset == iterate_offset => Err(e),
+            _ => Ok(iterate_offset - offset),
+        }
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        if self.children.read().contains_key(name) {
+            return_errno!(Errno::EISDIR);
+        }
+        return_errno_with_message!(Errno::EPERM, "interface files cannot be removed");
+    }
+
+    fn rmdir(&self, name: &str) -> Result<()> {
+        let mut children = self.children.write();
+        if !children.contains_key(name) {
+            if self.visible_files().any(|file| file.kind().name() == name) {
+                return_errno!(Errno::ENOTDIR);
+            }
+            return_errno_with_message!(Errno::ENOENT, "the cgroup does not exist");
+        }
+
+        self.cgroup.remove_child(name)?;
+        children.remove(name);
+        drop(children);
+
+        self.touch();
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode: Arc<dyn Inode> = match name {
+            "." => self.this.upgrade().unwrap(),
+            ".." => match &self.parent {
+                Some(parent) => parent.upgrade().unwrap(),
+                None => self.this.upgrade().unwrap(),
+            },
+            name => {
+                if let Some(child) = self.children.read().get(name) {
+                    return Ok(child.clone());
+                }
+                self.visible_files()
+                    .find(|file| file.kind().name() == name)
+                    .cloned()
+                    .ok_or(Error::new(Errno::ENOENT))?
+            }
+        };
+        Ok(inode)
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+
+    fn is_dentry_cacheable(&self) -> bool {
+        false
+    }
+}
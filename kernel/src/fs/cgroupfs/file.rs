@@ -0,0 +1,409 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::time::Duration;
+
+use super::{CgroupFs, BLOCK_SIZE};
+use crate::{
+    fs::utils::{FileSystem, Inode, InodeMode, InodeType, Metadata},
+    prelude::*,
+    process::{
+        cgroup::{Cgroup, Controllers},
+        posix_thread::{thread_table, AsPosixThread},
+        process_table, Gid, Pid, Process, Uid,
+    },
+    sched::FairGroup,
+};
+
+/// The kind of an interface file of a cgroup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ControlFile {
+    Procs,
+    Controllers,
+    SubtreeControl,
+    Events,
+    CpuWeight,
+    CpuMax,
+    MemoryCurrent,
+    MemoryMax,
+    PidsCurrent,
+    PidsMax,
+}
+
+impl ControlFile {
+    pub(super) const ALL: [ControlFile; 10] = [
+        ControlFile::Procs,
+        ControlFile::Controllers,
+        ControlFile::SubtreeControl,
+        ControlFile::Events,
+        ControlFile::CpuWeight,
+        ControlFile::CpuMax,
+        ControlFile::MemoryCurrent,
+        ControlFile::MemoryMax,
+        ControlFile::PidsCurrent,
+        ControlFile::PidsMax,
+    ];
+
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            ControlFile::Procs => "cgroup.procs",
+            ControlFile::Controllers => "cgroup.controllers",
+            ControlFile::SubtreeControl => "cgroup.subtree_control",
+            ControlFile::Events => "cgroup.events",
+            ControlFile::CpuWeight => "cpu.weight",
+            ControlFile::CpuMax => "cpu.max",
+            ControlFile::MemoryCurrent => "memory.current",
+            ControlFile::MemoryMax => "memory.max",
+            ControlFile::PidsCurrent => "pids.current",
+            ControlFile::PidsMax => "pids.max",
+        }
+    }
+
+    fn is_writable(self) -> bool {
+        !matches!(
+            self,
+            ControlFile::Controllers
+                | ControlFile::Events
+                | ControlFile::MemoryCurrent
+                | ControlFile::PidsCurrent
+        )
+    }
+
+    /// Returns the controller that the file belongs to, or `None` for the
+    /// core files.
+    fn controller(self) -> Option<Controllers> {
+        match self {
+            ControlFile::Procs
+            | ControlFile::Controllers
+            | ControlFile::SubtreeControl
+            | ControlFile::Events => None,
+            ControlFile::CpuWeight | ControlFile::CpuMax => Some(Controllers::CPU),
+            ControlFile::MemoryCurrent | ControlFile::MemoryMax => Some(Controllers::MEMORY),
+            ControlFile::PidsCurrent | ControlFile::PidsMax => Some(Controllers::PIDS),
+        }
+    }
+
+    /// Returns whether the file is present in the directory of the cgroup.
+    ///
+    /// Like Linux, the root cgroup has no files of the controllers, since it
+    /// cannot be limited.
+    pub(super) fn is_visible_in(self, cgroup: &Cgroup) -> bool {
+        match self.controller() {
+            None => self != ControlFile::Events || !cgroup.is_root(),
+            Some(controller) => !cgroup.is_root() && cgroup.controllers().contains(controller),
+        }
+    }
+
+    fn read(self, cgroup: &Cgroup) -> String {
+        match self {
+            ControlFile::Procs => {
+                let pid_ns = current!().pid_ns().clone();
+                let mut pids: Vec<_> = cgroup
+                    .processes()
+                    .iter()
+                    .filter_map(|process| pid_ns.tid_in_ns(process.pid()))
+                    .collect();
+                pids.sort_unstable();
+                pids.iter().map(|pid| format!("{}\n", pid)).collect()
+            }
+            ControlFile::Controllers => format!("{}\n", cgroup.controllers().to_names()),
+            ControlFile::SubtreeControl => format!("{}\n", cgroup.subtree_control().to_names()),
+            ControlFile::Events => format!("populated {}\n", cgroup.is_populated() as u8),
+            ControlFile::CpuWeight => {
+                format!("{}\n", cgroup.fair_group().unwrap().weight())
+            }
+            ControlFile::CpuMax => {
+                let (quota_us, period_us) = cgroup.fair_group().unwrap().bandwidth();
+                format!("{} {}\n", format_max(quota_us), period_us)
+            }
+            ControlFile::MemoryCurrent => format!("{}\n", cgroup.memory_current()),
+            ControlFile::MemoryMax => format!("{}\n", format_max(cgroup.memory_max())),
+            ControlFile::PidsCurrent => format!("{}\n", cgroup.pids_current()),
+            ControlFile::PidsMax => format!("{}\n", format_max(cgroup.pids_max())),
+        }
+    }
+
+    fn write(self, cgroup: &Arc<Cgroup>, input: &str) -> Result<()> {
+        match self {
+            ControlFile::Procs => {
+                let pid = input
+                    .parse::<Pid>()
+                    .map_err(|_| Error::with_message(Errno::EINVAL, "the PID is invalid"))?;
+                cgroup.attach(&process_of(pid)?)
+            }
+            ControlFile::SubtreeControl => {
+                let (enable, disable) = parse_subtree_control(input)?;
+                cgroup.update_subtree_control(enable, disable)
+            }
+            ControlFile::CpuWeight => {
+                let weight = parse_number(input)?;
+                if !(FairGroup::MIN_WEIGHT..=FairGroup::MAX_WEIGHT).contains(&weight) {
+                    return_errno_with_message!(Errno::ERANGE, "the weight is out of range");
+                }
+                cgroup.fair_group().unwrap().set_weight(weight);
+                Ok(())
+            }
+            ControlFile::CpuMax => {
+                let fair_group = cgroup.fair_group().unwrap();
+                let (quota_us, period_us) = parse_cpu_max(input, fair_group.bandwidth().1)?;
+                fair_group.set_bandwidth(quota_us, period_us);
+                Ok(())
+            }
+            ControlFile::MemoryMax => {
+                let max = parse_max(input, parse_memory_size)?;
+                cgroup.set_memory_max(max);
+                Ok(())
+            }
+            ControlFile::PidsMax => {
+                let max = parse_max(input, |input| parse_number(input).map(|max| max as usize))?;
+                cgroup.set_pids_max(max);
+                Ok(())
+            }
+            ControlFile::Controllers
+            | ControlFile::Events
+            | ControlFile::MemoryCurrent
+            | ControlFile::PidsCurrent => {
+                return_errno_with_message!(Errno::EPERM, "the file is read-only")
+            }
+        }
+    }
+}
+
+/// Finds the process to be moved by writing to `cgroup.procs`.
+///
+/// The PID is in the PID namespace of the current process. Like Linux, the
+/// PID can also be the TID of any thread in the process, and zero means the
+/// current process.
+fn process_of(pid: Pid) -> Result<Arc<Process>> {
+    let current = current!();
+    if pid == 0 {
+        return Ok(current);
+    }
+
+    let global_pid = current
+        .pid_ns()
+        .global_tid(pid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?;
+    if let Some(process) = process_table::get_process(global_pid) {
+        return Ok(process);
+    }
+    thread_table::get_thread(global_pid)
+        .map(|thread| thread.as_posix_thread().unwrap().process())
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))
+}
+
+fn format_max<T: core::fmt::Display>(value: Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "max".to_string(),
+    }
+}
+
+fn parse_number(input: &str) -> Result<u64> {
+    input
+        .parse()
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the value is not a number"))
+}
+
+/// Parses a limit, where "max" means that the resource is unlimited.
+fn parse_max<T>(input: &str, parse: impl FnOnce(&str) -> Result<T>) -> Result<Option<T>> {
+    match input {
+        "max" => Ok(None),
+        input => parse(input).map(Some),
+    }
+}
+
+/// Parses a memory size in bytes, which may have a suffix of "K", "M", "G",
+/// or "T".
+fn parse_memory_size(input: &str) -> Result<usize> {
+    let (digits, shift) = match input.as_bytes().last() {
+        Some(b'k' | b'K') => (&input[..input.len() - 1], 10),
+        Some(b'm' | b'M') => (&input[..input.len() - 1], 20),
+        Some(b'g' | b'G') => (&input[..input.len() - 1], 30),
+        Some(b't' | b'T') => (&input[..input.len() - 1], 40),
+        _ => (input, 0),
+    };
+    let size = parse_number(digits)?;
+    size.checked_mul(1 << shift)
+        .and_then(|size| usize::try_from(size).ok())
+        .ok_or_else(|| Error::with_message(Errno::ERANGE, "the size is too large"))
+}
+
+/// Parses the content of `cpu.max`, which is "$MAX $PERIOD" or "$MAX".
+///
+/// Returns the quota and the period in microseconds. If the period is
+/// omitted, `old_period_us` is kept.
+fn parse_cpu_max(input: &str, old_period_us: u64) -> Result<(Option<u64>, u64)> {
+    let mut fields = input.split_whitespace();
+    let quota_us = parse_max(fields.next().unwrap_or_default(), parse_number)?;
+    let period_us = match fields.next() {
+        Some(period) => parse_number(period)?,
+        None => old_period_us,
+    };
+    if fields.next().is_some() {
+        return_errno_with_message!(Errno::EINVAL, "too many fields");
+    }
+
+    if !(FairGroup::MIN_BANDWIDTH_US..=FairGroup::MAX_PERIOD_US).contains(&period_us)
+        || quota_us.is_some_and(|quota_us| quota_us < FairGroup::MIN_BANDWIDTH_US)
+    {
+        return_errno_with_message!(Errno::EINVAL, "the quota or the period is out of range");
+    }
+
+    Ok((quota_us, period_us))
+}
+
+/// Parses the content of `cgroup.subtree_control`, which is a list of
+/// controller names prefixed with "+" (to enable) or "-" (to disable).
+fn parse_subtree_control(input: &str) -> Result<(Controllers, Controllers)> {
+    let mut enable = Controllers::empty();
+    let mut disable = Controllers::empty();
+
+    for token in input.split_whitespace() {
+        let (controllers, name) = if let Some(name) = token.strip_prefix('+') {
+            (&mut enable, name)
+        } else if let Some(name) = token.strip_prefix('-') {
+            (&mut disable, name)
+        } else {
+            return_errno_with_message!(Errno::EINVAL, "the controller has no '+' or '-' prefix");
+        };
+        let controller = Controllers::from_name(name)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the controller is unknown"))?;
+        *controllers |= controller;
+    }
+
+    if enable.intersects(disable) {
+        return_errno_with_message!(Errno::EINVAL, "the controller is enabled and disabled");
+    }
+    Ok((enable, disable))
+}
+
+/// The inode of an interface file of a cgroup.
+pub(super) struct CgroupFile {
+    kind: ControlFile,
+    cgroup: Arc<Cgroup>,
+    metadata: RwLock<Metadata>,
+    fs: Weak<CgroupFs>,
+}
+
+impl CgroupFile {
+    pub(super) fn new(
+        ino: u64,
+        kind: ControlFile,
+        cgroup: Arc<Cgroup>,
+        fs: Weak<CgroupFs>,
+    ) -> Arc<Self> {
+        let mode = if kind.is_writable() { 0o644 } else { 0o444 };
+        let metadata = Metadata::new_file(ino, InodeMode::from_bits_truncate(mode), BLOCK_SIZE);
+
+        Arc::new(Self {
+            kind,
+            cgroup,
+            metadata: RwLock::new(metadata),
+            fs,
+        })
+    }
+
+    pub(super) fn kind(&self) -> ControlFile {
+        self.kind
+    }
+}
+
+impl Inode for CgroupFile {
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        // Opening the file with `O_TRUNC` resizes it to zero.
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn type_(&self) -> InodeType {
+        InodeType::File
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().atime
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().atime = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().mtime
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().mtime = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().ctime
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().ctime = time;
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let content = self.kind.read(&self.cgroup);
+        let Some(bytes) = content.as_bytes().get(offset..) else {
+            return Ok(0);
+        };
+        let len = writer.write_fallible(&mut bytes.into())?;
+        Ok(len)
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let buf = reader.collect()?;
+        let input = core::str::from_utf8(&buf)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the input is not UTF-8"))?;
+        self.kind.write(&self.cgroup, input.trim())?;
+        Ok(buf.len())
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+
+    fn is_dentry_cacheable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The cgroup file system (version 2), which manages the cgroup hierarchy.
+//!
+//! Each directory in the file system is a cgroup. Creating or removing a
+//! directory creates or removes a child cgroup. The files in a directory are
+//! the interface files of the cgroup, which show and change its state. For
+//! example, writing a PID to `cgroup.procs` moves the process to the cgroup,
+//! and writing to `memory.max` sets its memory limit. The interface files of
+//! a controller are present only if the controller is enabled in the cgroup.
+//!
+//! The file system has a single instance, which is normally mounted at
+//! "/sys/fs/cgroup".
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/cgroup-v2.html>
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::Once;
+
+use self::{dir::CgroupDir, file::ControlFile};
+use crate::{
+    fs::utils::{FileSystem, FsFlags, Inode, SuperBlock, NAME_MAX},
+    prelude::*,
+    process::cgroup::{self, Cgroup},
+};
+
+mod dir;
+mod file;
+
+const CGROUP2_SUPER_MAGIC: u64 = 0x6367_7270;
+const BLOCK_SIZE: usize = 4096;
+
+const ROOT_INO: u64 = 1;
+
+/// The cgroup file system.
+pub struct CgroupFs {
+    sb: SuperBlock,
+    root: Arc<CgroupDir>,
+    next_ino: AtomicU64,
+}
+
+impl CgroupFs {
+    fn new() -> Arc<Self> {
+        Arc::new_cyclic(|weak_self| Self {
+            sb: SuperBlock::new(CGROUP2_SUPER_MAGIC, BLOCK_SIZE, NAME_MAX),
+            root: CgroupDir::new_root(cgroup::root().clone(), weak_self.clone()),
+            // See `CgroupDir::new_root` for the inode numbers of the
+            // interface files in the root directory.
+            next_ino: AtomicU64::new(ROOT_INO + 1 + ControlFile::ALL.len() as u64),
+        })
+    }
+
+    fn alloc_ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl FileSystem for CgroupFs {
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn flags(&self) -> FsFlags {
+        FsFlags::empty()
+    }
+}
+
+static CGROUP_FS: Once<Arc<CgroupFs>> = Once::new();
+
+/// Returns the cgroup file system.
+pub fn singleton() -> &'static Arc<CgroupFs> {
+    CGROUP_FS.call_once(CgroupFs::new)
+}
+
+/// Returns the cgroup of the inode if the inode is a directory in the cgroup
+/// file system.
+pub fn cgroup_of_inode(inode: &dyn Inode) -> Option<Arc<Cgroup>> {
+    inode
+        .downcast_ref::<CgroupDir>()
+        .map(|dir| dir.cgroup().clone())
+}
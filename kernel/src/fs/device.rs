@@ -42,7 +42,7 @@ pub enum DeviceType {
 }
 
 /// Device Id
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DeviceId(u64);
 
 impl DeviceId {
@@ -92,6 +92,32 @@ impl From<u64> for DeviceId {
 /// If the parent path is not existing, `mkdir -p` the parent path.
 /// This function is used in registering device.
 pub fn add_node(device: Arc<dyn Device>, path: &str) -> Result<Dentry> {
+    add_entry(path, |parent, name| {
+        parent.mknod(
+            name,
+            InodeMode::from_bits_truncate(0o666),
+            device.clone().into(),
+        )
+    })
+}
+
+/// Add a symbolic link to FS that points to `target`.
+///
+/// If the parent path is not existing, `mkdir -p` the parent path.
+/// This function is used in registering device aliases (e.g., `/dev/disk/by-id/*`).
+pub fn add_symlink(target: &str, path: &str) -> Result<Dentry> {
+    add_entry(path, |parent, name| {
+        let dentry = parent.new_fs_child(
+            name,
+            InodeType::SymLink,
+            InodeMode::from_bits_truncate(0o777),
+        )?;
+        dentry.inode().write_link(target)?;
+        Ok(dentry)
+    })
+}
+
+fn add_entry(path: &str, create: impl FnOnce(&Dentry, &str) -> Result<Dentry>) -> Result<Dentry> {
     let mut dentry = {
         let fs_resolver = FsResolver::new();
         fs_resolver.lookup(&FsPath::try_from("/dev").unwrap())?
@@ -104,6 +130,7 @@ pub fn add_node(device: Arc<dyn Device>, path: &str) -> Result<Dentry> {
         relative_path
     };
 
+    let mut create = Some(create);
     while !relative_path.is_empty() {
         let (next_name, path_remain) = if let Some((prefix, suffix)) = relative_path.split_once('/')
         {
@@ -122,11 +149,7 @@ pub fn add_node(device: Arc<dyn Device>, path: &str) -> Result<Dentry> {
             Err(_) => {
                 if path_remain.is_empty() {
                     // Create the device node
-                    dentry = dentry.mknod(
-                        next_name,
-                        InodeMode::from_bits_truncate(0o666),
-                        device.clone().into(),
-                    )?;
+                    dentry = (create.take().unwrap())(&dentry, next_name)?;
                 } else {
                     // Mkdir parent path
                     dentry = dentry.new_fs_child(
@@ -2,17 +2,24 @@
 
 #![expect(dead_code)]
 
+use ostd::sync::WaitQueue;
+
 use super::{
     block_group::{BlockGroup, RawGroupDescriptor},
     block_ptr::Ext2Bid,
     inode::{FilePerm, Inode, InodeDesc, RawInode},
+    journal::{Journal, JournalHandle},
     prelude::*,
-    super_block::{RawSuperBlock, SuperBlock, SUPER_BLOCK_OFFSET},
+    super_block::{FeatureInCompatSet, RawSuperBlock, SuperBlock, SUPER_BLOCK_OFFSET},
 };
+use crate::{ThreadOptions, WaitTimeout};
 
 /// The root inode number.
 const ROOT_INO: u32 = 2;
 
+/// The interval between the periodic commits of the journal, which is the same as Linux.
+const JOURNAL_COMMIT_INTERVAL: Duration = Duration::from_secs(5);
+
 /// The Ext2 filesystem.
 #[derive(Debug)]
 pub struct Ext2 {
@@ -24,24 +31,50 @@ pub struct Ext2 {
     inode_size: usize,
     block_size: usize,
     group_descriptors_segment: USegment,
+    journal: Option<Arc<Journal>>,
     self_ref: Weak<Self>,
 }
 
 impl Ext2 {
     /// Opens and loads an Ext2 from the `block_device`.
+    ///
+    /// If the filesystem has a journal (e.g., it is created by `mkfs.ext3`), the committed
+    /// transactions in the journal are replayed first.
     pub fn open(block_device: Arc<dyn BlockDevice>) -> Result<Arc<Self>> {
-        // Load the superblock
-        // TODO: if the main superblock is corrupted, should we load the backup?
-        let super_block = {
-            let raw_super_block = block_device.read_val::<RawSuperBlock>(SUPER_BLOCK_OFFSET)?;
-            SuperBlock::try_from(raw_super_block)?
+        let super_block = load_super_block(block_device.as_ref())?;
+        let Some(journal_ino) = super_block.journal_ino() else {
+            return Self::load(block_device, super_block, None);
         };
-        assert_eq!(
-            super_block.block_size(),
-            BLOCK_SIZE,
-            "currently only support 4096-byte block size"
-        );
+        if super_block
+            .feature_incompat()
+            .contains(FeatureInCompatSet::JOURNAL_DEV)
+        {
+            return_errno_with_message!(Errno::EINVAL, "external journals are not supported");
+        }
+
+        // Like Linux, the journal is located with the metadata before the recovery. This is
+        // fine because the journal inode is never logged.
+        let journal = {
+            let ext2 = Self::load(block_device.clone(), super_block, None)?;
+            let journal_bids = ext2.lookup_inode(journal_ino)?.device_bids()?;
+            Arc::new(Journal::load(block_device.clone(), journal_bids)?)
+        };
+        journal.recover()?;
+        set_needs_recovery(block_device.as_ref(), false)?;
+
+        // Reloads the metadata, which may have been updated by the recovery.
+        let super_block = load_super_block(block_device.as_ref())?;
+        let ext2 = Self::load(block_device, super_block, Some(journal))?;
+        spawn_journal_commit_thread(Arc::downgrade(&ext2));
+        Ok(ext2)
+    }
 
+    /// Loads an Ext2 with the `super_block` from the `block_device`.
+    fn load(
+        block_device: Arc<dyn BlockDevice>,
+        super_block: SuperBlock,
+        journal: Option<Arc<Journal>>,
+    ) -> Result<Arc<Self>> {
         let group_descriptors_segment: USegment = {
             let npages = ((super_block.block_groups_count() as usize)
                 * core::mem::size_of::<RawGroupDescriptor>())
@@ -94,6 +127,7 @@ impl Ext2 {
             block_device,
             super_block: RwMutex::new(Dirty::new(super_block)),
             group_descriptors_segment,
+            journal,
             self_ref: weak_ref.clone(),
         });
         Ok(ext2)
@@ -124,6 +158,11 @@ impl Ext2 {
         self.blocks_per_group
     }
 
+    /// Returns whether the filesystem has a journal.
+    pub fn has_journal(&self) -> bool {
+        self.journal.is_some()
+    }
+
     /// Returns the super block.
     pub fn super_block(&self) -> RwMutexReadGuard<Dirty<SuperBlock>> {
         self.super_block.read()
@@ -277,7 +316,19 @@ impl Ext2 {
     }
 
     /// Frees a range of blocks.
+    ///
+    /// If the filesystem has a journal, the blocks are freed when the running transaction is
+    /// committed.
     pub(super) fn free_blocks(&self, range: Range<Ext2Bid>) -> Result<()> {
+        if let Some(journal) = self.journal.as_ref() {
+            journal.free_blocks(range);
+            return Ok(());
+        }
+        self.release_blocks(range)
+    }
+
+    /// Frees a range of blocks in the block groups immediately.
+    fn release_blocks(&self, range: Range<Ext2Bid>) -> Result<()> {
         let mut current_range = range.clone();
         while !current_range.is_empty() {
             let (_, block_group) = self.block_group_of_bid(current_range.start)?;
@@ -302,11 +353,16 @@ impl Ext2 {
     pub(super) fn read_blocks(&self, bid: Ext2Bid, bio_segment: BioSegment) -> Result<()> {
         let status = self
             .block_device
-            .read_blocks(Bid::new(bid as u64), bio_segment)?;
-        match status {
-            BioStatus::Complete => Ok(()),
-            err_status => Err(Error::from(err_status)),
+            .read_blocks(Bid::new(bid as u64), bio_segment.clone())?;
+        if status != BioStatus::Complete {
+            return Err(Error::from(status));
         }
+
+        // The logged blocks are newer than the ones on the device.
+        if let Some(journal) = self.journal.as_ref() {
+            journal.copy_logged_blocks(bid, &bio_segment)?;
+        }
+        Ok(())
     }
 
     /// Reads contiguous blocks starting from the `bid` asynchronously.
@@ -315,6 +371,14 @@ impl Ext2 {
         bid: Ext2Bid,
         bio_segment: BioSegment,
     ) -> Result<BioWaiter> {
+        if let Some(journal) = self.journal.as_ref()
+            && journal.is_logged(bid..bid + bio_segment.nblocks() as Ext2Bid)
+        {
+            // The logged blocks can only be copied after the read completes.
+            self.read_blocks(bid, bio_segment)?;
+            return Ok(BioWaiter::new());
+        }
+
         let waiter = self
             .block_device
             .read_blocks_async(Bid::new(bid as u64), bio_segment)?;
@@ -344,7 +408,99 @@ impl Ext2 {
         Ok(waiter)
     }
 
+    /// Writes contiguous metadata blocks starting from the `bid` synchronously.
+    pub(super) fn write_metadata_blocks(
+        &self,
+        bid: Ext2Bid,
+        bio_segment: BioSegment,
+    ) -> Result<()> {
+        match self.write_metadata_blocks_async(bid, bio_segment)?.wait() {
+            Some(BioStatus::Complete) => Ok(()),
+            _ => return_errno!(Errno::EIO),
+        }
+    }
+
+    /// Writes contiguous metadata blocks starting from the `bid` asynchronously.
+    ///
+    /// If the filesystem has a journal, the blocks are logged in the running transaction
+    /// instead of being written to the block device.
+    pub(super) fn write_metadata_blocks_async(
+        &self,
+        bid: Ext2Bid,
+        bio_segment: BioSegment,
+    ) -> Result<BioWaiter> {
+        let Some(journal) = self.journal.as_ref() else {
+            return self.write_blocks_async(bid, bio_segment);
+        };
+        journal.log_blocks(bid, &bio_segment)?;
+        Ok(BioWaiter::new())
+    }
+
+    /// Writes the metadata bytes starting from the byte `offset` asynchronously.
+    ///
+    /// If the filesystem has a journal, the bytes are logged in the running transaction
+    /// instead of being written to the block device.
+    pub(super) fn write_metadata_bytes_async(
+        &self,
+        offset: usize,
+        buf: &[u8],
+    ) -> Result<BioWaiter> {
+        let Some(journal) = self.journal.as_ref() else {
+            let waiter = self.block_device.write_bytes_async(offset, buf)?;
+            return Ok(waiter);
+        };
+        journal.log_bytes(offset, buf)?;
+        Ok(BioWaiter::new())
+    }
+
+    /// Starts a journal handle for an operation that updates the metadata.
+    ///
+    /// The handle must be held until the operation finishes, so that the operation is
+    /// committed atomically. Returns `None` if the filesystem does not have a journal.
+    ///
+    /// If the running transaction is full, it is committed before the handle is started.
+    pub(super) fn start_handle(&self) -> Result<Option<JournalHandle>> {
+        let Some(journal) = self.journal.as_ref() else {
+            return Ok(None);
+        };
+        loop {
+            if let Some(handle) = journal.try_start_handle() {
+                return Ok(Some(handle));
+            }
+            self.commit_journal()?;
+        }
+    }
+
+    /// Commits the metadata updates to the journal and flushes the block device.
+    ///
+    /// If the filesystem does not have a journal, this method does nothing.
+    pub fn commit_journal(&self) -> Result<()> {
+        let Some(journal) = self.journal.as_ref() else {
+            return Ok(());
+        };
+        let _updates_guard = journal.lock_updates();
+
+        // In the ordered mode, the file data is written before the metadata is committed.
+        self.sync_all_inodes()?;
+        for range in journal.take_freed_blocks() {
+            self.release_blocks(range)?;
+        }
+        self.sync_metadata()?;
+
+        if journal.has_logged_blocks() {
+            // The journal must be replayed if the checkpoint is interrupted.
+            set_needs_recovery(self.block_device(), true)?;
+            journal.commit()?;
+            set_needs_recovery(self.block_device(), false)?;
+        } else {
+            self.block_device.sync()?;
+        }
+        Ok(())
+    }
+
     /// Writes back the metadata to the block device.
+    ///
+    /// If the filesystem has a journal, the metadata is logged in the running transaction.
     pub fn sync_metadata(&self) -> Result<()> {
         // If the superblock is clean, the block groups must be clean.
         if !self.super_block.read().is_dirty() {
@@ -360,16 +516,21 @@ impl Ext2 {
         // Writes back the main superblock and group descriptor table.
         let mut bio_waiter = BioWaiter::new();
         let raw_super_block = RawSuperBlock::from((*super_block).deref());
+        let mut raw_main_super_block = raw_super_block;
+        if self.journal.is_some() {
+            // The logged superblock reaches its home location during the checkpoint, when the
+            // journal may still need to be replayed.
+            raw_main_super_block.feature_incompat |= FeatureInCompatSet::RECOVER.bits();
+        }
         bio_waiter.concat(
-            self.block_device
-                .write_bytes_async(SUPER_BLOCK_OFFSET, raw_super_block.as_bytes())?,
+            self.write_metadata_bytes_async(SUPER_BLOCK_OFFSET, raw_main_super_block.as_bytes())?,
         );
         let group_descriptors_bio_segment = BioSegment::new_from_segment(
             self.group_descriptors_segment.clone(),
             BioDirection::ToDevice,
         );
-        bio_waiter.concat(self.block_device.write_blocks_async(
-            super_block.group_descriptors_bid(0),
+        bio_waiter.concat(self.write_metadata_blocks_async(
+            super_block.group_descriptors_bid(0).to_raw() as Ext2Bid,
             group_descriptors_bio_segment.clone(),
         )?);
         bio_waiter
@@ -383,12 +544,12 @@ impl Ext2 {
             if super_block.is_backup_group(idx as usize) {
                 let mut bio_waiter = BioWaiter::new();
                 raw_super_block_backup.block_group_idx = idx as u16;
-                bio_waiter.concat(self.block_device.write_bytes_async(
+                bio_waiter.concat(self.write_metadata_bytes_async(
                     super_block.bid(idx as usize).to_offset(),
                     raw_super_block_backup.as_bytes(),
                 )?);
-                bio_waiter.concat(self.block_device.write_blocks_async(
-                    super_block.group_descriptors_bid(idx as usize),
+                bio_waiter.concat(self.write_metadata_blocks_async(
+                    super_block.group_descriptors_bid(idx as usize).to_raw() as Ext2Bid,
                     group_descriptors_bio_segment.clone(),
                 )?);
                 bio_waiter.wait().ok_or_else(|| {
@@ -438,3 +599,58 @@ impl Ext2 {
         bid % self.blocks_per_group
     }
 }
+
+/// Loads the main superblock from the `block_device`.
+fn load_super_block(block_device: &dyn BlockDevice) -> Result<SuperBlock> {
+    // TODO: if the main superblock is corrupted, should we load the backup?
+    let super_block = {
+        let raw_super_block = block_device.read_val::<RawSuperBlock>(SUPER_BLOCK_OFFSET)?;
+        SuperBlock::try_from(raw_super_block)?
+    };
+    assert_eq!(
+        super_block.block_size(),
+        BLOCK_SIZE,
+        "currently only support 4096-byte block size"
+    );
+    Ok(super_block)
+}
+
+/// Sets or clears the `RECOVER` feature of the main superblock on the `block_device`.
+///
+/// The feature tells whether the journal may contain transactions that are not checkpointed.
+fn set_needs_recovery(block_device: &dyn BlockDevice, needs_recovery: bool) -> Result<()> {
+    let mut raw_super_block = block_device.read_val::<RawSuperBlock>(SUPER_BLOCK_OFFSET)?;
+    let recover = FeatureInCompatSet::RECOVER.bits();
+    if (raw_super_block.feature_incompat & recover != 0) == needs_recovery {
+        return Ok(());
+    }
+
+    raw_super_block.feature_incompat ^= recover;
+    block_device.write_val(SUPER_BLOCK_OFFSET, &raw_super_block)?;
+    match block_device.sync()? {
+        BioStatus::Complete => Ok(()),
+        err_status => Err(Error::from(err_status)),
+    }
+}
+
+/// Spawns a kernel thread to commit the journal periodically.
+///
+/// The thread exits after the filesystem is dropped.
+fn spawn_journal_commit_thread(fs: Weak<Ext2>) {
+    let task_fn = move || {
+        let wait_queue = WaitQueue::new();
+        loop {
+            let _ = wait_queue.wait_until_or_timeout(|| None::<()>, &JOURNAL_COMMIT_INTERVAL);
+
+            let Some(fs) = fs.upgrade() else {
+                break;
+            };
+            if fs.journal.as_ref().unwrap().has_updates()
+                && let Err(err) = fs.commit_journal()
+            {
+                warn!("failed to commit the ext2 journal: {:?}", err);
+            }
+        }
+    };
+    ThreadOptions::new(task_fn).spawn();
+}
@@ -12,6 +12,10 @@ use crate::{
 
 impl FileSystem for Ext2 {
     fn sync(&self) -> Result<()> {
+        if self.has_journal() {
+            return self.commit_journal();
+        }
+
         self.sync_all_inodes()?;
         self.sync_metadata()?;
 
@@ -25,6 +25,7 @@ impl Inode for Ext2Inode {
     }
 
     fn resize(&self, new_size: usize) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.resize(new_size)
     }
 
@@ -69,6 +70,7 @@ impl Inode for Ext2Inode {
     }
 
     fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.set_file_perm(mode.into());
         Ok(())
     }
@@ -78,6 +80,7 @@ impl Inode for Ext2Inode {
     }
 
     fn set_owner(&self, uid: Uid) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.set_uid(uid.into());
         Ok(())
     }
@@ -87,6 +90,7 @@ impl Inode for Ext2Inode {
     }
 
     fn set_group(&self, gid: Gid) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.set_gid(gid.into());
         Ok(())
     }
@@ -104,18 +108,36 @@ impl Inode for Ext2Inode {
     }
 
     fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let _handle = self.fs().start_handle()?;
         self.write_at(offset, reader)
     }
 
     fn write_direct_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let _handle = self.fs().start_handle()?;
         self.write_direct_at(offset, reader)
     }
 
+    fn copy_range_from(
+        &self,
+        src: &Arc<dyn Inode>,
+        src_offset: usize,
+        offset: usize,
+        len: usize,
+    ) -> Result<usize> {
+        let src = src
+            .downcast_ref::<Ext2Inode>()
+            .ok_or_else(|| Error::with_message(Errno::EOPNOTSUPP, "not same fs"))?;
+        let _handle = self.fs().start_handle()?;
+        self.copy_range_from(src, src_offset, offset, len)
+    }
+
     fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        let _handle = self.fs().start_handle()?;
         Ok(self.create(name, type_, mode.into())?)
     }
 
     fn mknod(&self, name: &str, mode: InodeMode, type_: MknodType) -> Result<Arc<dyn Inode>> {
+        let _handle = self.fs().start_handle()?;
         let inode_type = type_.inode_type();
         let inode = match type_ {
             MknodType::CharDeviceNode(dev) | MknodType::BlockDeviceNode(dev) => {
@@ -141,14 +163,17 @@ impl Inode for Ext2Inode {
         let old = old
             .downcast_ref::<Ext2Inode>()
             .ok_or_else(|| Error::with_message(Errno::EXDEV, "not same fs"))?;
+        let _handle = self.fs().start_handle()?;
         self.link(old, name)
     }
 
     fn unlink(&self, name: &str) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.unlink(name)
     }
 
     fn rmdir(&self, name: &str) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.rmdir(name)
     }
 
@@ -156,6 +181,7 @@ impl Inode for Ext2Inode {
         let target = target
             .downcast_ref::<Ext2Inode>()
             .ok_or_else(|| Error::with_message(Errno::EXDEV, "not same fs"))?;
+        let _handle = self.fs().start_handle()?;
         self.rename(old_name, target, new_name)
     }
 
@@ -164,10 +190,12 @@ impl Inode for Ext2Inode {
     }
 
     fn write_link(&self, target: &str) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.write_link(target)
     }
 
     fn fallocate(&self, mode: FallocMode, offset: usize, len: usize) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.fallocate(mode, offset, len)
     }
 
@@ -176,14 +204,25 @@ impl Inode for Ext2Inode {
     }
 
     fn sync_all(&self) -> Result<()> {
+        let fs = self.fs();
+        if fs.has_journal() {
+            // The metadata reaches the block device only after the journal is committed.
+            return fs.commit_journal();
+        }
+
         self.sync_all()?;
-        self.fs().block_device().sync()?;
+        fs.block_device().sync()?;
         Ok(())
     }
 
     fn sync_data(&self) -> Result<()> {
+        let fs = self.fs();
+        if fs.has_journal() {
+            return fs.commit_journal();
+        }
+
         self.sync_data()?;
-        self.fs().block_device().sync()?;
+        fs.block_device().sync()?;
         Ok(())
     }
 
@@ -201,6 +240,7 @@ impl Inode for Ext2Inode {
         value_reader: &mut VmReader,
         flags: XattrSetFlags,
     ) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.set_xattr(name, value_reader, flags)
     }
 
@@ -213,6 +253,7 @@ impl Inode for Ext2Inode {
     }
 
     fn remove_xattr(&self, name: XattrName) -> Result<()> {
+        let _handle = self.fs().start_handle()?;
         self.remove_xattr(name)
     }
 }
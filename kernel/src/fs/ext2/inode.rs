@@ -27,6 +27,7 @@ use crate::{
         },
     },
     process::{posix_thread::AsPosixThread, Gid, Uid},
+    vm::vmo::CommitFlags,
 };
 
 /// Max length of file name.
@@ -749,6 +750,60 @@ impl Inode {
         Ok(bytes_written)
     }
 
+    /// Copies at most `len` bytes of `src` at `src_offset` to this inode at
+    /// `offset`.
+    ///
+    /// The bytes are copied from the page cache of `src` to that of this
+    /// inode directly, without any intermediate buffer.
+    pub fn copy_range_from(
+        &self,
+        src: &Inode,
+        src_offset: usize,
+        offset: usize,
+        len: usize,
+    ) -> Result<usize> {
+        if self.type_ != InodeType::File || src.type_ != InodeType::File {
+            return_errno!(Errno::EISDIR);
+        }
+
+        let len = len.min(src.file_size().saturating_sub(src_offset));
+        let src_pages = src.page_cache();
+
+        let mut copied_len = 0;
+        while copied_len < len {
+            let src_pos = src_offset + copied_len;
+            let page_offset = src_pos % BLOCK_SIZE;
+            let copy_len = (BLOCK_SIZE - page_offset).min(len - copied_len);
+
+            let result = src_pages
+                .commit_on(src_pos / BLOCK_SIZE, CommitFlags::empty())
+                .and_then(|frame| {
+                    let mut reader = frame.reader();
+                    reader.skip(page_offset).limit(copy_len);
+                    self.write_at(offset + copied_len, &mut reader.to_fallible())
+                });
+            match result {
+                Ok(written_len) => {
+                    copied_len += written_len;
+                    if written_len < copy_len {
+                        break;
+                    }
+                }
+                Err(err) if copied_len == 0 => return Err(err),
+                Err(_) => break,
+            }
+        }
+
+        Ok(copied_len)
+    }
+
+    /// Returns the device block IDs of all the blocks of the file, in order.
+    ///
+    /// This is used to locate the journal, which is accessed without the page cache.
+    pub(super) fn device_bids(&self) -> Result<Vec<Ext2Bid>> {
+        self.inner.read().inode_impl.block_manager.device_bids()
+    }
+
     pub fn sync_all(&self) -> Result<()> {
         let mut inner = self.inner.write();
         inner.sync_data()?;
@@ -1203,6 +1258,7 @@ impl InodeImpl {
             nblocks: AtomicUsize::new(desc.blocks_count() as _),
             block_ptrs: RwMutex::new(desc.block_ptrs),
             indirect_blocks: RwMutex::new(IndirectBlockCache::new(fs.clone())),
+            is_metadata: matches!(desc.type_, InodeType::Dir | InodeType::SymLink),
             fs,
         };
         Self {
@@ -1832,6 +1888,9 @@ struct InodeBlockManager {
     /// frequent reads access the `InodeDesc` copy without locking.
     block_ptrs: RwMutex<BlockPtrs>,
     indirect_blocks: RwMutex<IndirectBlockCache>,
+    /// Whether the blocks are metadata (i.e., directory entries or symlink targets), which
+    /// are logged in the journal instead of being written in place.
+    is_metadata: bool,
     fs: Weak<Ext2>,
 }
 
@@ -1902,7 +1961,7 @@ impl InodeBlockManager {
             let bio_segment = BioSegment::alloc(range_nblocks, BioDirection::ToDevice);
             bio_segment.writer().unwrap().write_fallible(reader)?;
 
-            let waiter = self.fs().write_blocks_async(start_bid, bio_segment)?;
+            let waiter = self.write_device_blocks_async(start_bid, bio_segment)?;
             bio_waiter.concat(waiter);
         }
 
@@ -1927,13 +1986,40 @@ impl InodeBlockManager {
                 .writer()
                 .unwrap()
                 .write_fallible(&mut frame.reader().to_fallible())?;
-            let waiter = self.fs().write_blocks_async(start_bid, bio_segment)?;
+            let waiter = self.write_device_blocks_async(start_bid, bio_segment)?;
             bio_waiter.concat(waiter);
         }
 
         Ok(bio_waiter)
     }
 
+    /// Writes contiguous blocks starting from the device block `bid` asynchronously.
+    fn write_device_blocks_async(
+        &self,
+        bid: Ext2Bid,
+        bio_segment: BioSegment,
+    ) -> Result<BioWaiter> {
+        if self.is_metadata {
+            self.fs().write_metadata_blocks_async(bid, bio_segment)
+        } else {
+            self.fs().write_blocks_async(bid, bio_segment)
+        }
+    }
+
+    /// Returns the device block IDs of all the blocks, in order.
+    pub fn device_bids(&self) -> Result<Vec<Ext2Bid>> {
+        let nblocks = self.nblocks() as Ext2Bid;
+        let mut device_bids = Vec::with_capacity(nblocks as usize);
+        if nblocks == 0 {
+            return Ok(device_bids);
+        }
+
+        for dev_range in DeviceRangeReader::new(self, 0..nblocks)? {
+            device_bids.extend(dev_range);
+        }
+        Ok(device_bids)
+    }
+
     pub fn nblocks(&self) -> usize {
         self.nblocks.load(Ordering::Acquire)
     }
@@ -0,0 +1,854 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The journal of Ext3.
+//!
+//! The on-disk format of the journal is compatible with JBD2, which is used by Ext3 and Ext4
+//! in Linux. The journal is stored in a reserved inode and works in the ordered mode: only the
+//! metadata (e.g., bitmaps, inode tables, indirect blocks, and directory blocks) is logged, and
+//! the file data is written in place before the metadata referring to it is committed.
+//!
+//! Instead of being written to the device, the updated metadata blocks are logged in the
+//! running transaction. Committing a transaction writes the logged blocks to the journal,
+//! followed by a commit block. Then the transaction is checkpointed by writing the blocks to
+//! their home locations, after which the journal is empty again. If the system crashes before
+//! the checkpoint finishes, the committed transaction is replayed at the next mount.
+//!
+//! The operations that update the metadata run in handles. A transaction is committed only
+//! when no handles are running, so it never contains a half-finished operation. Each handle
+//! reserves room for the blocks that it may log, and no handles can be started if the running
+//! transaction is full, so that a transaction always fits in the log.
+//!
+//! Since every transaction is checkpointed right after it is committed, no revoke records are
+//! written. They are still honored when replaying a journal written by Linux.
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use aster_block::SECTOR_SIZE;
+use ostd::sync::WaitQueue;
+
+use super::{block_ptr::Ext2Bid, prelude::*};
+use crate::time::{clocks::RealTimeClock, Clock};
+
+/// The magic number of the journal blocks.
+const JOURNAL_MAGIC: u32 = 0xc03b3998;
+
+/// The minimum number of journal blocks, which is the same as Linux.
+const MIN_JOURNAL_BLOCKS: u32 = 1024;
+
+/// The size of the UUID that follows a block tag without `TagFlags::SAME_UUID`.
+const UUID_SIZE: usize = 16;
+
+/// The number of blocks reserved in the running transaction for each handle.
+///
+/// This is the maximum number of metadata blocks that an operation is expected to update. Most
+/// of them are logged only when the transaction is committed, so the reserved blocks are not
+/// released until then.
+const HANDLE_CREDITS: usize = 16;
+
+/// The type of a journal block.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, TryFromInt)]
+enum BlockType {
+    Descriptor = 1,
+    Commit = 2,
+    SuperBlockV1 = 3,
+    SuperBlockV2 = 4,
+    Revoke = 5,
+}
+
+bitflags! {
+    /// Incompatible feature set of the journal.
+    struct FeatureInCompatSet: u32 {
+        /// The journal has revoke blocks
+        const REVOKE = 1 << 0;
+        /// The journal uses 64-bit block numbers
+        const BIT64 = 1 << 1;
+        /// Commit blocks are written without waiting for the logged blocks
+        const ASYNC_COMMIT = 1 << 2;
+        /// The journal blocks have version 2 checksums
+        const CSUM_V2 = 1 << 3;
+        /// The journal blocks have version 3 checksums
+        const CSUM_V3 = 1 << 4;
+        /// The journal has a fast commit area
+        const FAST_COMMIT = 1 << 5;
+    }
+}
+
+bitflags! {
+    /// The flags of a block tag in descriptor blocks.
+    struct TagFlags: u16 {
+        /// The block started with the journal magic number, which has been zeroed
+        const ESCAPE = 1 << 0;
+        /// The tag has the same UUID as the previous one, so no UUID follows it
+        const SAME_UUID = 1 << 1;
+        /// The block was deleted by the transaction
+        const DELETED = 1 << 2;
+        /// The tag is the last one in the descriptor block
+        const LAST_TAG = 1 << 3;
+    }
+}
+
+/// The journal.
+pub(super) struct Journal {
+    block_device: Arc<dyn BlockDevice>,
+    /// The device block IDs of the journal blocks, indexed by the journal block numbers.
+    bids: Vec<Ext2Bid>,
+    /// The first journal block of the log.
+    first: u32,
+    /// The total number of journal blocks.
+    max_len: u32,
+    feature_incompat: FeatureInCompatSet,
+    uuid: [u8; UUID_SIZE],
+    /// The journal superblock, which is also locked while committing transactions.
+    super_block: Mutex<RawJournalSuperBlock>,
+    running: Mutex<Transaction>,
+    /// The number of blocks logged in the running transaction.
+    nr_logged_blocks: AtomicUsize,
+    /// The blocks of the transaction being committed.
+    ///
+    /// They are still the latest version of the blocks until the checkpoint finishes.
+    committing: Mutex<Option<Arc<BTreeMap<Ext2Bid, Box<[u8]>>>>>,
+    /// Whether any handles have been started or any blocks have been logged since the last
+    /// commit.
+    has_updates: AtomicBool,
+    updates: SpinLock<Updates>,
+    updates_wait_queue: WaitQueue,
+}
+
+impl Journal {
+    /// Loads the journal whose blocks are located at `bids` on the `block_device`.
+    pub fn load(block_device: Arc<dyn BlockDevice>, bids: Vec<Ext2Bid>) -> Result<Self> {
+        let Some(&super_block_bid) = bids.first() else {
+            return_errno_with_message!(Errno::EINVAL, "the journal is empty");
+        };
+        let super_block = {
+            let mut sector = [0u8; SECTOR_SIZE];
+            block_device.read_bytes(super_block_bid as usize * BLOCK_SIZE, &mut sector)?;
+            RawJournalSuperBlock::from_bytes(&sector)
+        };
+
+        match super_block.header.block_type() {
+            Some(BlockType::SuperBlockV1 | BlockType::SuperBlockV2) => (),
+            _ => return_errno_with_message!(Errno::EINVAL, "bad journal superblock"),
+        }
+        if u32::from_be(super_block.block_size) as usize != BLOCK_SIZE {
+            return_errno_with_message!(Errno::EINVAL, "the journal block size is not supported");
+        }
+        let max_len = u32::from_be(super_block.max_len);
+        let first = u32::from_be(super_block.first);
+        if max_len as usize > bids.len() || first == 0 || first >= max_len {
+            return_errno_with_message!(Errno::EINVAL, "invalid journal length");
+        }
+        if max_len < MIN_JOURNAL_BLOCKS {
+            return_errno_with_message!(Errno::EINVAL, "the journal is too small");
+        }
+
+        // Version 1 superblocks have no features.
+        let feature_incompat = if super_block.header.block_type() == Some(BlockType::SuperBlockV2) {
+            FeatureInCompatSet::from_bits(u32::from_be(super_block.feature_incompat)).ok_or(
+                Error::with_message(Errno::EINVAL, "invalid journal feature incompat set"),
+            )?
+        } else {
+            FeatureInCompatSet::empty()
+        };
+        if feature_incompat.intersects(
+            FeatureInCompatSet::CSUM_V2
+                | FeatureInCompatSet::CSUM_V3
+                | FeatureInCompatSet::FAST_COMMIT,
+        ) {
+            return_errno_with_message!(Errno::EINVAL, "the journal features are not supported");
+        }
+
+        Ok(Self {
+            block_device,
+            bids,
+            first,
+            max_len,
+            feature_incompat,
+            uuid: super_block.uuid,
+            super_block: Mutex::new(super_block),
+            running: Mutex::new(Transaction::default()),
+            nr_logged_blocks: AtomicUsize::new(0),
+            committing: Mutex::new(None),
+            has_updates: AtomicBool::new(false),
+            updates: SpinLock::new(Updates {
+                nr_handles: 0,
+                nr_reserved_blocks: 0,
+                is_locked: false,
+            }),
+            updates_wait_queue: WaitQueue::new(),
+        })
+    }
+
+    /// Replays the committed transactions that have not been checkpointed.
+    ///
+    /// This must be called before the metadata is loaded from the device.
+    pub fn recover(&self) -> Result<()> {
+        let mut super_block = self.super_block.lock();
+        let start = u32::from_be(super_block.start);
+        if start == 0 {
+            return Ok(());
+        }
+        if start < self.first || start >= self.max_len {
+            return_errno_with_message!(Errno::EINVAL, "invalid journal start");
+        }
+
+        // Scans the log for the committed transactions.
+        let mut transactions = Vec::new();
+        let mut sequence = u32::from_be(super_block.sequence);
+        let mut pos = start;
+        let mut block = vec![0u8; BLOCK_SIZE];
+        let mut logged_blocks = Vec::new();
+        let mut revoked_bids = Vec::new();
+        loop {
+            self.read_log_block(pos, &mut block)?;
+            let header = RawHeader::from_bytes(&block);
+            if header.sequence() != sequence {
+                break;
+            }
+
+            match header.block_type() {
+                Some(BlockType::Descriptor) => {
+                    for (bid, flags) in self.parse_tags(&block)? {
+                        pos = self.next_pos(pos);
+                        logged_blocks.push(LoggedBlock { bid, pos, flags });
+                    }
+                }
+                Some(BlockType::Revoke) => revoked_bids.extend(self.parse_revoke_records(&block)?),
+                Some(BlockType::Commit) => {
+                    transactions.push(CommittedTransaction {
+                        sequence,
+                        logged_blocks: core::mem::take(&mut logged_blocks),
+                        revoked_bids: core::mem::take(&mut revoked_bids),
+                    });
+                    sequence = sequence.wrapping_add(1);
+                }
+                _ => break,
+            }
+            pos = self.next_pos(pos);
+        }
+
+        // A block is not replayed if it is revoked by the same or a later transaction.
+        let mut revoked = BTreeMap::new();
+        for transaction in transactions.iter() {
+            for bid in transaction.revoked_bids.iter() {
+                revoked.insert(*bid, transaction.sequence);
+            }
+        }
+
+        let mut nr_replayed = 0;
+        for transaction in transactions.iter() {
+            for logged_block in transaction.logged_blocks.iter() {
+                if let Some(revoked_sequence) = revoked.get(&logged_block.bid)
+                    && !is_sequence_after(transaction.sequence, *revoked_sequence)
+                {
+                    continue;
+                }
+
+                self.read_log_block(logged_block.pos, &mut block)?;
+                if logged_block.flags.contains(TagFlags::ESCAPE) {
+                    block[..4].copy_from_slice(&JOURNAL_MAGIC.to_be_bytes());
+                }
+                self.block_device
+                    .write_bytes(logged_block.bid as usize * BLOCK_SIZE, &block)?;
+                nr_replayed += 1;
+            }
+        }
+        self.flush()?;
+
+        // The log is empty now.
+        super_block.sequence = sequence.to_be();
+        super_block.start = 0;
+        self.write_super_block(&super_block)?;
+        self.flush()?;
+
+        info!(
+            "ext2: replayed {} transactions ({} blocks) from the journal",
+            transactions.len(),
+            nr_replayed
+        );
+        Ok(())
+    }
+
+    /// Starts a handle, which makes the metadata updates of an operation belong to one
+    /// transaction.
+    ///
+    /// The running transaction is not committed until the handle is dropped. Handles must not
+    /// be nested, otherwise a pending commit will deadlock.
+    ///
+    /// Returns `None` if the running transaction has no room for the blocks of the handle. The
+    /// caller should commit the running transaction and try again.
+    pub fn try_start_handle(self: &Arc<Self>) -> Option<JournalHandle> {
+        let max_blocks = self.max_transaction_blocks();
+        let is_full = self.updates_wait_queue.wait_until(|| {
+            let mut updates = self.updates.lock();
+            if updates.is_locked {
+                return None;
+            }
+            let nr_logged_blocks = self.nr_logged_blocks.load(Ordering::Relaxed);
+            if nr_logged_blocks.max(updates.nr_reserved_blocks) + HANDLE_CREDITS > max_blocks {
+                return Some(true);
+            }
+            updates.nr_handles += 1;
+            updates.nr_reserved_blocks += HANDLE_CREDITS;
+            Some(false)
+        });
+        if is_full {
+            return None;
+        }
+        self.has_updates.store(true, Ordering::Relaxed);
+
+        Some(JournalHandle {
+            journal: self.clone(),
+        })
+    }
+
+    /// Waits for the running handles to finish and prevents new handles from being started.
+    ///
+    /// Only one guard can exist at a time, so the commits are serialized. The blocks reserved
+    /// by the handles are released, since the holder of the guard is going to commit the
+    /// running transaction.
+    pub fn lock_updates(&self) -> UpdatesGuard<'_> {
+        self.updates_wait_queue.wait_until(|| {
+            let mut updates = self.updates.lock();
+            if updates.is_locked {
+                return None;
+            }
+            updates.is_locked = true;
+            updates.nr_reserved_blocks = 0;
+            Some(())
+        });
+        self.updates_wait_queue
+            .wait_until(|| (self.updates.lock().nr_handles == 0).then_some(()));
+        self.has_updates.store(false, Ordering::Relaxed);
+
+        UpdatesGuard { journal: self }
+    }
+
+    /// Returns whether any handles have been started or any blocks have been logged since the
+    /// last commit.
+    pub fn has_updates(&self) -> bool {
+        self.has_updates.load(Ordering::Relaxed)
+    }
+
+    /// Logs the blocks starting from `bid` in the running transaction.
+    pub fn log_blocks(&self, bid: Ext2Bid, bio_segment: &BioSegment) -> Result<()> {
+        let mut running = self.running.lock();
+        for i in 0..bio_segment.nblocks() {
+            let mut block = vec![0u8; BLOCK_SIZE].into_boxed_slice();
+            bio_segment.read_bytes(i * BLOCK_SIZE, &mut block)?;
+            running.blocks.insert(bid + i as Ext2Bid, block);
+        }
+        self.nr_logged_blocks
+            .store(running.blocks.len(), Ordering::Relaxed);
+        self.has_updates.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Logs the bytes starting from the byte `offset` of the device in the running transaction.
+    ///
+    /// The rest of the partially updated blocks is kept as it is.
+    pub fn log_bytes(&self, offset: usize, bytes: &[u8]) -> Result<()> {
+        let mut running = self.running.lock();
+        let mut done_len = 0;
+        while done_len < bytes.len() {
+            let bid = ((offset + done_len) / BLOCK_SIZE) as Ext2Bid;
+            let offset_in_block = (offset + done_len) % BLOCK_SIZE;
+            let len = (BLOCK_SIZE - offset_in_block).min(bytes.len() - done_len);
+
+            if !running.blocks.contains_key(&bid) {
+                let mut block = vec![0u8; BLOCK_SIZE].into_boxed_slice();
+                if !self.copy_committing_block(bid, &mut block) {
+                    self.block_device
+                        .read_bytes(bid as usize * BLOCK_SIZE, &mut block)?;
+                }
+                running.blocks.insert(bid, block);
+            }
+            let block = running.blocks.get_mut(&bid).unwrap();
+            block[offset_in_block..offset_in_block + len]
+                .copy_from_slice(&bytes[done_len..done_len + len]);
+
+            done_len += len;
+        }
+        self.nr_logged_blocks
+            .store(running.blocks.len(), Ordering::Relaxed);
+        self.has_updates.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Returns whether any of the blocks in the range is logged and not yet checkpointed.
+    pub fn is_logged(&self, range: Range<Ext2Bid>) -> bool {
+        if self
+            .running
+            .lock()
+            .blocks
+            .range(range.clone())
+            .next()
+            .is_some()
+        {
+            return true;
+        }
+        self.committing
+            .lock()
+            .as_ref()
+            .is_some_and(|blocks| blocks.range(range).next().is_some())
+    }
+
+    /// Copies the logged blocks that are not yet checkpointed to the segment, which has been
+    /// read from the device starting from `bid`.
+    pub fn copy_logged_blocks(&self, bid: Ext2Bid, bio_segment: &BioSegment) -> Result<()> {
+        let running = self.running.lock();
+        let mut block = vec![0u8; BLOCK_SIZE];
+        for i in 0..bio_segment.nblocks() {
+            let bid = bid + i as Ext2Bid;
+            if let Some(logged_block) = running.blocks.get(&bid) {
+                bio_segment.write_bytes(i * BLOCK_SIZE, logged_block)?;
+            } else if self.copy_committing_block(bid, &mut block) {
+                bio_segment.write_bytes(i * BLOCK_SIZE, &block)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that the blocks are freed in the running transaction.
+    ///
+    /// The blocks are not freed until the transaction is committed. Otherwise, they could be
+    /// reused for file data, which is written in place, while the metadata on the device still
+    /// refers to them.
+    pub fn free_blocks(&self, range: Range<Ext2Bid>) {
+        self.running.lock().freed_blocks.push(range);
+    }
+
+    /// Takes the blocks that are freed in the running transaction, which must be freed before
+    /// the transaction is committed.
+    pub fn take_freed_blocks(&self) -> Vec<Range<Ext2Bid>> {
+        core::mem::take(&mut self.running.lock().freed_blocks)
+    }
+
+    /// Returns whether the running transaction has logged blocks.
+    pub fn has_logged_blocks(&self) -> bool {
+        !self.running.lock().blocks.is_empty()
+    }
+
+    /// Commits the running transaction and checkpoints it.
+    ///
+    /// The caller must hold the [`UpdatesGuard`] and must have logged all the metadata.
+    ///
+    /// If the transaction does not fit in the log, it is kept running and an error is returned,
+    /// since committing only part of it would break the atomicity of the operations.
+    pub fn commit(&self) -> Result<()> {
+        let mut super_block = self.super_block.lock();
+
+        let blocks = {
+            let mut running = self.running.lock();
+            if running.blocks.len() > self.max_transaction_blocks() {
+                return_errno_with_message!(
+                    Errno::ENOSPC,
+                    "the transaction exceeds the journal capacity"
+                );
+            }
+            self.nr_logged_blocks.store(0, Ordering::Relaxed);
+            Arc::new(core::mem::take(&mut running.blocks))
+        };
+        *self.committing.lock() = Some(blocks.clone());
+
+        let blocks: Vec<(Ext2Bid, &[u8])> = blocks
+            .iter()
+            .map(|(bid, block)| (*bid, block.as_ref()))
+            .collect();
+        self.write_transaction(&blocks, &mut super_block)?;
+        self.checkpoint(&blocks, &mut super_block)?;
+
+        *self.committing.lock() = None;
+        Ok(())
+    }
+
+    /// Writes the blocks to the log as a transaction.
+    ///
+    /// The transaction is committed once this method succeeds.
+    fn write_transaction(
+        &self,
+        blocks: &[(Ext2Bid, &[u8])],
+        super_block: &mut RawJournalSuperBlock,
+    ) -> Result<()> {
+        let sequence = u32::from_be(super_block.sequence);
+
+        // The log starts from the beginning, since it is always empty before a commit. The
+        // transaction is ignored by the recovery until its commit block is written.
+        super_block.start = self.first.to_be();
+        self.write_super_block(super_block)?;
+
+        let mut pos = self.first;
+        let mut bio_waiter = BioWaiter::new();
+        let tag_size = self.tag_size();
+        for blocks in blocks.chunks(self.tags_per_descriptor()) {
+            let mut descriptor = vec![0u8; BLOCK_SIZE];
+            descriptor[..size_of::<RawHeader>()]
+                .copy_from_slice(RawHeader::new(BlockType::Descriptor, sequence).as_bytes());
+
+            let mut offset = size_of::<RawHeader>();
+            for (i, (bid, block)) in blocks.iter().enumerate() {
+                let mut flags = TagFlags::empty();
+                if i != 0 {
+                    flags |= TagFlags::SAME_UUID;
+                }
+                if i == blocks.len() - 1 {
+                    flags |= TagFlags::LAST_TAG;
+                }
+                if block[..4] == JOURNAL_MAGIC.to_be_bytes() {
+                    flags |= TagFlags::ESCAPE;
+                }
+
+                let tag = &mut descriptor[offset..offset + tag_size];
+                tag[0..4].copy_from_slice(&bid.to_be_bytes());
+                tag[6..8].copy_from_slice(&flags.bits().to_be_bytes());
+                offset += tag_size;
+
+                if i == 0 {
+                    descriptor[offset..offset + UUID_SIZE].copy_from_slice(&self.uuid);
+                    offset += UUID_SIZE;
+                }
+            }
+            bio_waiter.concat(self.write_log_block_async(pos, &descriptor)?);
+            pos = self.next_pos(pos);
+
+            for (_, block) in blocks.iter() {
+                if block[..4] == JOURNAL_MAGIC.to_be_bytes() {
+                    let mut escaped_block = block.to_vec();
+                    escaped_block[..4].fill(0);
+                    bio_waiter.concat(self.write_log_block_async(pos, &escaped_block)?);
+                } else {
+                    bio_waiter.concat(self.write_log_block_async(pos, block)?);
+                }
+                pos = self.next_pos(pos);
+            }
+        }
+        bio_waiter
+            .wait()
+            .ok_or_else(|| Error::with_message(Errno::EIO, "failed to write the journal"))?;
+        self.flush()?;
+
+        // The commit block must not reach the device before the logged blocks.
+        let mut commit_block = vec![0u8; BLOCK_SIZE];
+        let commit_time = RealTimeClock::get().read_time();
+        let raw_commit_block = RawCommitBlock {
+            header: RawHeader::new(BlockType::Commit, sequence),
+            commit_sec: commit_time.as_secs().to_be(),
+            commit_nsec: commit_time.subsec_nanos().to_be(),
+            ..RawCommitBlock::new_zeroed()
+        };
+        commit_block[..size_of::<RawCommitBlock>()].copy_from_slice(raw_commit_block.as_bytes());
+        self.write_log_block_async(pos, &commit_block)?
+            .wait()
+            .ok_or_else(|| Error::with_message(Errno::EIO, "failed to write the commit block"))?;
+        self.flush()
+    }
+
+    /// Writes the committed blocks to their home locations and empties the log.
+    fn checkpoint(
+        &self,
+        blocks: &[(Ext2Bid, &[u8])],
+        super_block: &mut RawJournalSuperBlock,
+    ) -> Result<()> {
+        let mut bio_waiter = BioWaiter::new();
+        for (bid, block) in blocks.iter() {
+            bio_waiter.concat(
+                self.block_device
+                    .write_bytes_async(*bid as usize * BLOCK_SIZE, block)?,
+            );
+        }
+        bio_waiter
+            .wait()
+            .ok_or_else(|| Error::with_message(Errno::EIO, "failed to checkpoint the journal"))?;
+        self.flush()?;
+
+        let sequence = u32::from_be(super_block.sequence).wrapping_add(1);
+        super_block.sequence = sequence.to_be();
+        super_block.start = 0;
+        self.write_super_block(super_block)?;
+        self.flush()
+    }
+
+    /// Copies the block in the transaction being committed, if it exists.
+    fn copy_committing_block(&self, bid: Ext2Bid, block: &mut [u8]) -> bool {
+        let committing = self.committing.lock();
+        let Some(committing_block) = committing.as_ref().and_then(|blocks| blocks.get(&bid)) else {
+            return false;
+        };
+        block.copy_from_slice(committing_block);
+        true
+    }
+
+    /// Parses the block tags in the descriptor block.
+    fn parse_tags(&self, block: &[u8]) -> Result<Vec<(Ext2Bid, TagFlags)>> {
+        let tag_size = self.tag_size();
+        let mut tags = Vec::new();
+
+        let mut offset = size_of::<RawHeader>();
+        while offset + tag_size <= BLOCK_SIZE {
+            let tag = &block[offset..offset + tag_size];
+            let flags = TagFlags::from_bits_truncate(u16::from_be_bytes([tag[6], tag[7]]));
+            if self.feature_incompat.contains(FeatureInCompatSet::BIT64) && tag[8..12] != [0; 4] {
+                return_errno_with_message!(Errno::EINVAL, "the logged block is out of range");
+            }
+            let bid = u32::from_be_bytes([tag[0], tag[1], tag[2], tag[3]]);
+            tags.push((bid, flags));
+
+            offset += tag_size;
+            if !flags.contains(TagFlags::SAME_UUID) {
+                offset += UUID_SIZE;
+            }
+            if flags.contains(TagFlags::LAST_TAG) {
+                break;
+            }
+        }
+
+        Ok(tags)
+    }
+
+    /// Parses the revoked block IDs in the revoke block.
+    fn parse_revoke_records(&self, block: &[u8]) -> Result<Vec<Ext2Bid>> {
+        let offset = size_of::<RawHeader>();
+        let count = u32::from_be_bytes(block[offset..offset + 4].try_into().unwrap()) as usize;
+        if count > BLOCK_SIZE {
+            return_errno_with_message!(Errno::EINVAL, "invalid revoke block");
+        }
+
+        let record_size = if self.feature_incompat.contains(FeatureInCompatSet::BIT64) {
+            8
+        } else {
+            4
+        };
+        let records = block[offset + 4..count.max(offset + 4)]
+            .chunks_exact(record_size)
+            .filter_map(|record| {
+                let (high, low) = record.split_at(record_size - 4);
+                if high.iter().any(|byte| *byte != 0) {
+                    // Such blocks are out of range, so they will never be replayed anyway.
+                    return None;
+                }
+                Some(u32::from_be_bytes(low.try_into().unwrap()))
+            })
+            .collect();
+
+        Ok(records)
+    }
+
+    /// Returns the size of a block tag in descriptor blocks.
+    fn tag_size(&self) -> usize {
+        if self.feature_incompat.contains(FeatureInCompatSet::BIT64) {
+            12
+        } else {
+            8
+        }
+    }
+
+    /// Returns the maximum number of tags in a descriptor block.
+    fn tags_per_descriptor(&self) -> usize {
+        (BLOCK_SIZE - size_of::<RawHeader>() - UUID_SIZE) / self.tag_size()
+    }
+
+    /// Returns the maximum number of logged blocks in a transaction.
+    ///
+    /// The descriptor blocks and the commit block must fit in the log as well.
+    fn max_transaction_blocks(&self) -> usize {
+        let tags_per_descriptor = self.tags_per_descriptor();
+        let log_len = (self.max_len - self.first) as usize;
+        (log_len - 2) * tags_per_descriptor / (tags_per_descriptor + 1)
+    }
+
+    /// Returns the journal block number following `pos` in the log.
+    fn next_pos(&self, pos: u32) -> u32 {
+        if pos + 1 == self.max_len {
+            self.first
+        } else {
+            pos + 1
+        }
+    }
+
+    fn read_log_block(&self, pos: u32, block: &mut [u8]) -> Result<()> {
+        let bid = self.bids[pos as usize];
+        self.block_device
+            .read_bytes(bid as usize * BLOCK_SIZE, block)?;
+        Ok(())
+    }
+
+    fn write_log_block_async(&self, pos: u32, block: &[u8]) -> Result<BioWaiter> {
+        let bid = self.bids[pos as usize];
+        let waiter = self
+            .block_device
+            .write_bytes_async(bid as usize * BLOCK_SIZE, block)?;
+        Ok(waiter)
+    }
+
+    fn write_super_block(&self, super_block: &RawJournalSuperBlock) -> Result<()> {
+        // The rest of the superblock is not interpreted, so it is kept as it is.
+        let offset = self.bids[0] as usize * BLOCK_SIZE;
+        let mut sector = [0u8; SECTOR_SIZE];
+        self.block_device.read_bytes(offset, &mut sector)?;
+        sector[..size_of::<RawJournalSuperBlock>()].copy_from_slice(super_block.as_bytes());
+        self.block_device.write_bytes(offset, &sector)?;
+        Ok(())
+    }
+
+    /// Flushes the volatile write cache of the device.
+    fn flush(&self) -> Result<()> {
+        match self.block_device.sync()? {
+            BioStatus::Complete => Ok(()),
+            err_status => Err(Error::from(err_status)),
+        }
+    }
+}
+
+impl Debug for Journal {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Journal")
+            .field("first", &self.first)
+            .field("max_len", &self.max_len)
+            .field("feature_incompat", &self.feature_incompat)
+            .field("super_block", &*self.super_block.lock())
+            .finish()
+    }
+}
+
+/// A handle of the journal.
+///
+/// See [`Journal::try_start_handle`] for details.
+pub(super) struct JournalHandle {
+    journal: Arc<Journal>,
+}
+
+impl Drop for JournalHandle {
+    fn drop(&mut self) {
+        let mut updates = self.journal.updates.lock();
+        updates.nr_handles -= 1;
+        if updates.nr_handles == 0 {
+            drop(updates);
+            self.journal.updates_wait_queue.wake_all();
+        }
+    }
+}
+
+/// A guard that prevents new handles from being started.
+///
+/// See [`Journal::lock_updates`] for details.
+pub(super) struct UpdatesGuard<'a> {
+    journal: &'a Journal,
+}
+
+impl Drop for UpdatesGuard<'_> {
+    fn drop(&mut self) {
+        self.journal.updates.lock().is_locked = false;
+        self.journal.updates_wait_queue.wake_all();
+    }
+}
+
+struct Updates {
+    /// The number of running handles.
+    nr_handles: usize,
+    /// The number of blocks reserved by the handles started since the last commit.
+    nr_reserved_blocks: usize,
+    /// Whether new handles are prevented from being started.
+    is_locked: bool,
+}
+
+/// The running transaction.
+#[derive(Default)]
+struct Transaction {
+    /// The logged blocks, indexed by their device block IDs.
+    blocks: BTreeMap<Ext2Bid, Box<[u8]>>,
+    /// The blocks that are freed in the transaction.
+    freed_blocks: Vec<Range<Ext2Bid>>,
+}
+
+/// A transaction that is found in the log by the recovery.
+struct CommittedTransaction {
+    sequence: u32,
+    logged_blocks: Vec<LoggedBlock>,
+    revoked_bids: Vec<Ext2Bid>,
+}
+
+struct LoggedBlock {
+    /// The device block ID of the home location.
+    bid: Ext2Bid,
+    /// The journal block number in the log.
+    pos: u32,
+    flags: TagFlags,
+}
+
+/// Returns whether the transaction sequence number `a` is after `b`.
+///
+/// The sequence numbers may wrap around.
+fn is_sequence_after(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) > 0
+}
+
+/// The header of the journal blocks except the logged blocks.
+///
+/// Like the other journal structures, all fields are big-endian.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawHeader {
+    magic: u32,
+    block_type: u32,
+    sequence: u32,
+}
+
+impl RawHeader {
+    fn new(block_type: BlockType, sequence: u32) -> Self {
+        Self {
+            magic: JOURNAL_MAGIC.to_be(),
+            block_type: (block_type as u32).to_be(),
+            sequence: sequence.to_be(),
+        }
+    }
+
+    /// Returns the block type, or `None` if the block is not a valid journal block.
+    fn block_type(&self) -> Option<BlockType> {
+        if u32::from_be(self.magic) != JOURNAL_MAGIC {
+            return None;
+        }
+        BlockType::try_from(u32::from_be(self.block_type)).ok()
+    }
+
+    fn sequence(&self) -> u32 {
+        u32::from_be(self.sequence)
+    }
+}
+
+/// The journal superblock.
+///
+/// Only the fields used by this implementation are defined.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawJournalSuperBlock {
+    header: RawHeader,
+    block_size: u32,
+    /// The total number of journal blocks.
+    max_len: u32,
+    /// The first journal block of the log.
+    first: u32,
+    /// The sequence number of the first transaction in the log.
+    sequence: u32,
+    /// The journal block number of the first transaction, or 0 if the log is empty.
+    start: u32,
+    errno: i32,
+    feature_compat: u32,
+    feature_incompat: u32,
+    feature_ro_compat: u32,
+    uuid: [u8; UUID_SIZE],
+}
+
+/// The commit block.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct RawCommitBlock {
+    header: RawHeader,
+    checksum_type: u8,
+    checksum_size: u8,
+    padding: [u8; 2],
+    checksum: [u32; 8],
+    commit_sec: u64,
+    commit_nsec: u32,
+    padding2: u32,
+}
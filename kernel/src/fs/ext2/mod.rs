@@ -14,6 +14,8 @@
 //!    stored in PageCache, which accelerates the performance of data access.
 //! 3. Compatible with queue-based block device. The filesystem can submits multiple
 //!    BIO requests to be block device at once, thereby enhancing I/O performance.
+//! 4. Compatible with the Ext3 journal. If the filesystem has a JBD2 journal, metadata
+//!    updates are logged in ordered mode, committed periodically and replayed at mount.
 //!
 //! # Example
 //!
@@ -47,6 +49,7 @@ mod fs;
 mod impl_for_vfs;
 mod indirect_block_cache;
 mod inode;
+mod journal;
 mod prelude;
 mod super_block;
 mod utils;
@@ -93,6 +93,16 @@ pub struct SuperBlock {
     prealloc_file_blocks: u8,
     /// Number of blocks to preallocate for directories.
     prealloc_dir_blocks: u8,
+    ///
+    /// This fields are valid if the FeatureCompatSet::HAS_JOURNAL is set.
+    ///
+    /// Inode number of journal file.
+    journal_ino: u32,
+    /// The raw superblock loaded from the device.
+    ///
+    /// The fields that are not interpreted above (e.g., the backup of the journal
+    /// inode's blocks) are written back as they are.
+    raw: RawSuperBlock,
 }
 
 impl TryFrom<RawSuperBlock> for SuperBlock {
@@ -174,6 +184,8 @@ impl TryFrom<RawSuperBlock> for SuperBlock {
             last_mounted_dir: sb.last_mounted_dir,
             prealloc_file_blocks: sb.prealloc_file_blocks,
             prealloc_dir_blocks: sb.prealloc_dir_blocks,
+            journal_ino: sb.journal_ino,
+            raw: sb,
         })
     }
 }
@@ -244,6 +256,13 @@ impl SuperBlock {
         self.feature_ro_compat
     }
 
+    /// Returns the inode number of the journal file, if the filesystem has a journal.
+    pub fn journal_ino(&self) -> Option<u32> {
+        self.feature_compat
+            .contains(FeatureCompatSet::HAS_JOURNAL)
+            .then_some(self.journal_ino)
+    }
+
     /// Returns the number of free blocks.
     pub fn free_blocks_count(&self) -> u32 {
         self.free_blocks_count
@@ -528,7 +547,8 @@ impl From<&SuperBlock> for RawSuperBlock {
             last_mounted_dir: sb.last_mounted_dir,
             prealloc_file_blocks: sb.prealloc_file_blocks,
             prealloc_dir_blocks: sb.prealloc_dir_blocks,
-            ..Default::default()
+            journal_ino: sb.journal_ino,
+            ..sb.raw
         }
     }
 }
@@ -132,8 +132,8 @@ impl Xattr {
             self.inode().set_acl(new_bid);
         // Need to load the xattr block from device
         } else if cache.header.is_none() {
-            fs.block_device().read_blocks(
-                cache.bid,
+            fs.read_blocks(
+                cache.bid.to_raw() as Ext2Bid,
                 BioSegment::new_from_segment(self.blocks_buf.clone(), BioDirection::FromDevice),
             )?;
 
@@ -330,8 +330,8 @@ impl Xattr {
     pub fn flush(&self) -> Result<()> {
         let cache = self.cache.upread();
         if cache.is_dirty() {
-            self.fs().block_device().write_blocks(
-                cache.bid,
+            self.fs().write_metadata_blocks(
+                cache.bid.to_raw() as Ext2Bid,
                 BioSegment::new_from_segment(self.blocks_buf.clone(), BioDirection::ToDevice),
             )?;
             cache.upgrade().clear_dirty();
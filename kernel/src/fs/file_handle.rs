@@ -4,12 +4,17 @@
 
 //! Opened File Handle
 
+use aster_rights::Rights;
+
 use super::inode_handle::InodeHandle;
 use crate::{
-    fs::utils::{AccessMode, FallocMode, InodeMode, IoctlCmd, Metadata, SeekFrom, StatusFlags},
+    fs::utils::{
+        AccessMode, FallocMode, InodeMode, IoctlCmd, Metadata, RawIoctlCmd, SeekFrom, StatusFlags,
+    },
     net::socket::Socket,
     prelude::*,
     process::{signal::Pollable, Gid, Uid},
+    vm::vmo::Vmo,
 };
 
 /// The basic operations defined on a file
@@ -47,6 +52,18 @@ pub trait FileLike: Pollable + Send + Sync + Any {
         return_errno_with_message!(Errno::EINVAL, "ioctl is not supported");
     }
 
+    /// Performs an ioctl command that is not listed in [`IoctlCmd`].
+    fn ioctl_raw(&self, cmd: RawIoctlCmd, arg: usize) -> Result<i32> {
+        return_errno_with_message!(Errno::EINVAL, "the ioctl command is unknown");
+    }
+
+    /// Returns the VMO to be mapped at the given file offset and the offset within the VMO.
+    ///
+    /// This is used to memory-map files that are not backed by inodes.
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        return_errno_with_message!(Errno::ENODEV, "mmap is not supported");
+    }
+
     fn resize(&self, new_size: usize) -> Result<()> {
         return_errno_with_message!(Errno::EINVAL, "resize is not supported");
     }
@@ -29,6 +29,8 @@ impl InodeHandle<Rights> {
             None
         };
 
+        dentry.publish_event(FsEvents::OPEN);
+
         let inner = Arc::new(InodeHandle_ {
             dentry,
             file_io,
@@ -69,6 +71,8 @@ impl Pollable for InodeHandle<Rights> {
 #[inherit_methods(from = "self.0")]
 impl FileLike for InodeHandle<Rights> {
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32>;
+    fn ioctl_raw(&self, cmd: RawIoctlCmd, arg: usize) -> Result<i32>;
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)>;
     fn status_flags(&self) -> StatusFlags;
     fn access_mode(&self) -> AccessMode;
     fn metadata(&self) -> Metadata;
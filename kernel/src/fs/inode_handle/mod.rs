@@ -16,11 +16,12 @@ use crate::{
     events::IoEvents,
     fs::{
         file_handle::FileLike,
+        notify::FsEvents,
         path::Dentry,
         utils::{
             AccessMode, DirentVisitor, FallocMode, FileRange, FlockItem, FlockList, InodeMode,
             InodeType, IoctlCmd, Metadata, RangeLockItem, RangeLockItemBuilder, RangeLockList,
-            RangeLockType, SeekFrom, StatusFlags, OFFSET_MAX,
+            RangeLockType, RawIoctlCmd, SeekFrom, StatusFlags, OFFSET_MAX,
         },
     },
     prelude::*,
@@ -28,6 +29,7 @@ use crate::{
         signal::{PollHandle, Pollable},
         Gid, Uid,
     },
+    vm::vmo::Vmo,
 };
 
 #[derive(Debug)]
@@ -46,11 +48,21 @@ struct InodeHandle_ {
 
 impl InodeHandle_ {
     pub fn read(&self, writer: &mut VmWriter) -> Result<usize> {
-        if let Some(ref file_io) = self.file_io {
-            return file_io.read(writer);
+        if let Some(ref file_io) = self.file_io
+            && !file_io.is_seekable()
+        {
+            let len = if self.status_flags().contains(StatusFlags::O_NONBLOCK) {
+                file_io.read_nonblocking(writer)?
+            } else {
+                file_io.read(writer)?
+            };
+            if len > 0 {
+                self.dentry.publish_event(FsEvents::ACCESS);
+            }
+            return Ok(len);
         }
 
-        if !self.dentry.inode().is_seekable() {
+        if self.file_io.is_none() && !self.dentry.inode().is_seekable() {
             return self.read_at(0, writer);
         }
 
@@ -63,18 +75,20 @@ impl InodeHandle_ {
     }
 
     pub fn write(&self, reader: &mut VmReader) -> Result<usize> {
-        if let Some(ref file_io) = self.file_io {
+        if let Some(ref file_io) = self.file_io
+            && !file_io.is_seekable()
+        {
             return file_io.write(reader);
         }
 
-        if !self.dentry.inode().is_seekable() {
+        if self.file_io.is_none() && !self.dentry.inode().is_seekable() {
             return self.write_at(0, reader);
         }
 
         let mut offset = self.offset.lock();
 
         if self.status_flags().contains(StatusFlags::O_APPEND) {
-            *offset = self.dentry.size();
+            *offset = self.size();
         }
 
         let len = self.write_at(*offset, reader)?;
@@ -84,20 +98,23 @@ impl InodeHandle_ {
     }
 
     pub fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
-        if let Some(ref file_io) = self.file_io {
-            todo!("support read_at for FileIo");
-        }
-
-        if self.status_flags().contains(StatusFlags::O_DIRECT) {
-            self.dentry.inode().read_direct_at(offset, writer)
+        let len = if let Some(ref file_io) = self.file_io {
+            file_io.read_at(offset, writer)?
+        } else if self.status_flags().contains(StatusFlags::O_DIRECT) {
+            self.dentry.inode().read_direct_at(offset, writer)?
         } else {
-            self.dentry.inode().read_at(offset, writer)
+            self.dentry.inode().read_at(offset, writer)?
+        };
+
+        if len > 0 {
+            self.dentry.publish_event(FsEvents::ACCESS);
         }
+        Ok(len)
     }
 
     pub fn write_at(&self, mut offset: usize, reader: &mut VmReader) -> Result<usize> {
         if let Some(ref file_io) = self.file_io {
-            todo!("support write_at for FileIo");
+            return file_io.write_at(offset, reader);
         }
 
         let status_flags = self.status_flags();
@@ -106,11 +123,16 @@ impl InodeHandle_ {
             offset = self.dentry.size();
         }
 
-        if status_flags.contains(StatusFlags::O_DIRECT) {
-            self.dentry.inode().write_direct_at(offset, reader)
+        let len = if status_flags.contains(StatusFlags::O_DIRECT) {
+            self.dentry.inode().write_direct_at(offset, reader)?
         } else {
-            self.dentry.inode().write_at(offset, reader)
+            self.dentry.inode().write_at(offset, reader)?
+        };
+
+        if len > 0 {
+            self.dentry.publish_event(FsEvents::MODIFY);
         }
+        Ok(len)
     }
 
     pub fn seek(&self, pos: SeekFrom) -> Result<usize> {
@@ -123,7 +145,7 @@ impl InodeHandle_ {
                 off as isize
             }
             SeekFrom::End(off /* as isize */) => {
-                let file_size = self.dentry.size() as isize;
+                let file_size = self.size() as isize;
                 assert!(file_size >= 0);
                 file_size
                     .checked_add(off)
@@ -147,6 +169,16 @@ impl InodeHandle_ {
         *offset
     }
 
+    pub fn size(&self) -> usize {
+        if let Some(ref file_io) = self.file_io
+            && file_io.is_seekable()
+        {
+            return file_io.size();
+        }
+
+        self.dentry.size()
+    }
+
     pub fn resize(&self, new_size: usize) -> Result<()> {
         if self.status_flags().contains(StatusFlags::O_APPEND) {
             return_errno_with_message!(Errno::EPERM, "can not resize append-only file");
@@ -204,7 +236,9 @@ impl InodeHandle_ {
             );
         }
 
-        self.dentry.inode().fallocate(mode, offset, len)
+        self.dentry.inode().fallocate(mode, offset, len)?;
+        self.dentry.publish_event(FsEvents::MODIFY);
+        Ok(())
     }
 
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
@@ -215,6 +249,22 @@ impl InodeHandle_ {
         self.dentry.inode().ioctl(cmd, arg)
     }
 
+    fn ioctl_raw(&self, cmd: RawIoctlCmd, arg: usize) -> Result<i32> {
+        if let Some(ref file_io) = self.file_io {
+            return file_io.ioctl_raw(cmd, arg);
+        }
+
+        return_errno_with_message!(Errno::EINVAL, "the ioctl command is unknown");
+    }
+
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        if let Some(ref file_io) = self.file_io {
+            return file_io.mmap_vmo(offset);
+        }
+
+        return_errno_with_message!(Errno::EBADF, "File does not have page cache");
+    }
+
     fn test_range_lock(&self, lock: RangeLockItem) -> Result<RangeLockItem> {
         let mut req_lock = lock.clone();
         if let Some(extension) = self.dentry.inode().extension() {
@@ -320,7 +370,6 @@ impl InodeHandle_ {
 
 #[inherit_methods(from = "self.dentry")]
 impl InodeHandle_ {
-    pub fn size(&self) -> usize;
     pub fn metadata(&self) -> Metadata;
     pub fn mode(&self) -> Result<InodeMode>;
     pub fn set_mode(&self, mode: InodeMode) -> Result<()>;
@@ -330,6 +379,17 @@ impl InodeHandle_ {
     pub fn set_group(&self, gid: Gid) -> Result<()>;
 }
 
+impl Drop for InodeHandle_ {
+    fn drop(&mut self) {
+        let events = if self.access_mode.is_writable() {
+            FsEvents::CLOSE_WRITE
+        } else {
+            FsEvents::CLOSE_NOWRITE
+        };
+        self.dentry.publish_event(events);
+    }
+}
+
 impl Debug for InodeHandle_ {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.debug_struct("InodeHandle_")
@@ -382,9 +442,49 @@ impl<R> Drop for InodeHandle<R> {
 pub trait FileIo: Pollable + Send + Sync + 'static {
     fn read(&self, writer: &mut VmWriter) -> Result<usize>;
 
+    /// Reads without blocking, which is used if the file is opened with `O_NONBLOCK`.
+    ///
+    /// A file I/O that may block in `read` should return `EAGAIN` instead of blocking.
+    fn read_nonblocking(&self, writer: &mut VmWriter) -> Result<usize> {
+        self.read(writer)
+    }
+
     fn write(&self, reader: &mut VmReader) -> Result<usize>;
 
+    /// Returns whether the file I/O is seekable.
+    ///
+    /// A seekable file I/O (e.g., a block device) is read and written at the file offset with
+    /// `read_at` and `write_at`, instead of `read` and `write`.
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::ESPIPE, "read_at is not supported");
+    }
+
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::ESPIPE, "write_at is not supported");
+    }
+
+    /// Returns the size of a seekable file I/O, which is used to seek from the end.
+    fn size(&self) -> usize {
+        0
+    }
+
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
         return_errno_with_message!(Errno::EINVAL, "ioctl is not supported");
     }
+
+    /// Performs an ioctl command that is not listed in [`IoctlCmd`].
+    fn ioctl_raw(&self, cmd: RawIoctlCmd, arg: usize) -> Result<i32> {
+        return_errno_with_message!(Errno::EINVAL, "the ioctl command is unknown");
+    }
+
+    /// Returns the VMO to be mapped at the given file offset and the offset within the VMO.
+    ///
+    /// This is used to memory-map devices (e.g., framebuffers), which have no page cache.
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        return_errno_with_message!(Errno::ENODEV, "mmap is not supported");
+    }
 }
@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_rights::Rights;
+
+use super::{
+    request::IoUringOp,
+    ring::{IoUring, UserBuffer},
+    IoUringEnterFlags, IoUringFeatures, IoUringParams, IoUringRegisterOp, IoUringSetupFlags,
+    IORING_MAX_CQ_ENTRIES, IORING_MAX_ENTRIES,
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        file_table::FileDesc,
+        utils::{InodeMode, InodeType, Metadata},
+    },
+    prelude::*,
+    process::{
+        signal::{PollHandle, Pollable},
+        Gid, Uid,
+    },
+    time::clocks::RealTimeClock,
+    vm::vmo::Vmo,
+};
+
+/// A file that represents an io_uring instance.
+pub struct IoUringFile {
+    io_uring: Arc<IoUring>,
+}
+
+impl IoUringFile {
+    /// Creates a new io_uring instance.
+    ///
+    /// The actual numbers of entries and the offsets of the fields in the rings are written back
+    /// to `params`.
+    pub fn new(entries: u32, params: &mut IoUringParams) -> Result<Self> {
+        let Some(flags) = IoUringSetupFlags::from_bits(params.flags) else {
+            return_errno_with_message!(Errno::EINVAL, "the setup flags are invalid");
+        };
+        // TODO: Support polling the SQ with a kernel thread and other setup flags.
+        if flags.intersects(!(IoUringSetupFlags::CQSIZE | IoUringSetupFlags::CLAMP)) {
+            return_errno_with_message!(Errno::EINVAL, "the setup flags are not supported");
+        }
+
+        let is_clamped = flags.contains(IoUringSetupFlags::CLAMP);
+        let sq_entries = clamp_entries(entries, IORING_MAX_ENTRIES, is_clamped)?;
+        let cq_entries = if flags.contains(IoUringSetupFlags::CQSIZE) {
+            let cq_entries = clamp_entries(params.cq_entries, IORING_MAX_CQ_ENTRIES, is_clamped)?;
+            if cq_entries < sq_entries {
+                return_errno_with_message!(Errno::EINVAL, "the CQ cannot be smaller than the SQ");
+            }
+            cq_entries
+        } else {
+            sq_entries * 2
+        };
+
+        let io_uring = IoUring::new(sq_entries, cq_entries)?;
+
+        params.sq_entries = io_uring.sq_entries();
+        params.cq_entries = io_uring.cq_entries();
+        params.features = (IoUringFeatures::SINGLE_MMAP
+            | IoUringFeatures::SUBMIT_STABLE
+            | IoUringFeatures::RW_CUR_POS
+            | IoUringFeatures::FAST_POLL)
+            .bits();
+        params.sq_off = io_uring.sq_offsets();
+        params.cq_off = io_uring.cq_offsets();
+
+        Ok(Self { io_uring })
+    }
+
+    /// Submits requests and waits for completions.
+    ///
+    /// Returns the number of the consumed SQEs.
+    pub fn enter(
+        &self,
+        to_submit: u32,
+        min_complete: u32,
+        flags: IoUringEnterFlags,
+        ctx: &Context,
+    ) -> Result<u32> {
+        let submitted = self.io_uring.submit(to_submit, ctx);
+
+        if flags.contains(IoUringEnterFlags::GETEVENTS) && min_complete > 0 {
+            let res = self.io_uring.wait_cqes(min_complete);
+            // Like Linux, the errors are ignored if some SQEs have been consumed.
+            if submitted == 0 {
+                res?;
+            }
+        }
+
+        Ok(submitted)
+    }
+
+    /// Registers resources to the io_uring instance or unregisters them.
+    pub fn register(
+        &self,
+        op: IoUringRegisterOp,
+        arg: Vaddr,
+        nr_args: u32,
+        ctx: &Context,
+    ) -> Result<i32> {
+        let user_space = ctx.user_space();
+
+        let read_files = |addr: Vaddr, nr_files: usize| -> Result<Vec<Option<Arc<dyn FileLike>>>> {
+            let mut fds = Vec::with_capacity(nr_files);
+            for i in 0..nr_files {
+                fds.push(user_space.read_val::<FileDesc>(addr + i * size_of::<FileDesc>())?);
+            }
+
+            let file_table = ctx.thread_local.borrow_file_table();
+            let file_table_locked = file_table.unwrap().read();
+            fds.into_iter()
+                .map(|fd| {
+                    // Like Linux, `-1` represents an empty slot.
+                    if fd == -1 {
+                        return Ok(None);
+                    }
+                    let file = file_table_locked.get_file(fd)?;
+                    if file.downcast_ref::<IoUringFile>().is_some() {
+                        return_errno_with_message!(
+                            Errno::EBADF,
+                            "io_uring files cannot be registered"
+                        );
+                    }
+                    Ok(Some(file.clone()))
+                })
+                .collect()
+        };
+
+        match op {
+            IoUringRegisterOp::RegisterBuffers => {
+                let mut buffers = Vec::new();
+                for i in 0..nr_args as usize {
+                    let io_vec: [usize; 2] =
+                        user_space.read_val(arg + i * size_of::<[usize; 2]>())?;
+                    let buffer = UserBuffer {
+                        addr: io_vec[0],
+                        len: io_vec[1],
+                    };
+                    if buffer.addr == 0 || buffer.len == 0 {
+                        return_errno_with_message!(Errno::EFAULT, "the buffer is invalid");
+                    }
+                    buffers.push(buffer);
+                }
+                self.io_uring.register_buffers(buffers)?;
+                Ok(0)
+            }
+            IoUringRegisterOp::UnregisterBuffers => {
+                check_no_args(arg, nr_args)?;
+                self.io_uring.unregister_buffers()?;
+                Ok(0)
+            }
+            IoUringRegisterOp::RegisterFiles => {
+                let files = read_files(arg, nr_args as usize)?;
+                self.io_uring.register_files(files)?;
+                Ok(0)
+            }
+            IoUringRegisterOp::UnregisterFiles => {
+                check_no_args(arg, nr_args)?;
+                self.io_uring.unregister_files()?;
+                Ok(0)
+            }
+            IoUringRegisterOp::RegisterFilesUpdate => {
+                let update: IoUringFilesUpdate = user_space.read_val(arg)?;
+                if update.resv != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the reserved field is not zero");
+                }
+
+                let files = read_files(update.fds as Vaddr, nr_args as usize)?;
+                let num_files = self.io_uring.update_files(update.offset as usize, files)?;
+                Ok(num_files as i32)
+            }
+            IoUringRegisterOp::RegisterProbe => {
+                let num_ops = (nr_args as usize).min(IoUringOp::LAST as usize + 1);
+
+                let mut probe: IoUringProbe = user_space.read_val(arg)?;
+                if probe.as_bytes().iter().any(|byte| *byte != 0) {
+                    return_errno_with_message!(Errno::EINVAL, "the probe is not zeroed");
+                }
+                probe.last_op = IoUringOp::LAST;
+                probe.ops_len = num_ops as u8;
+                user_space.write_val(arg, &probe)?;
+
+                for op in 0..num_ops {
+                    let probe_op = IoUringProbeOp {
+                        op: op as u8,
+                        resv: 0,
+                        flags: if IoUringOp::try_from(op as u8).is_ok() {
+                            IO_URING_OP_SUPPORTED
+                        } else {
+                            0
+                        },
+                        resv2: 0,
+                    };
+                    user_space.write_val(
+                        arg + size_of::<IoUringProbe>() + op * size_of::<IoUringProbeOp>(),
+                        &probe_op,
+                    )?;
+                }
+                Ok(0)
+            }
+        }
+    }
+}
+
+impl Drop for IoUringFile {
+    fn drop(&mut self) {
+        self.io_uring.cancel_all();
+    }
+}
+
+impl Pollable for IoUringFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.io_uring.poll(mask, poller)
+    }
+}
+
+impl FileLike for IoUringFile {
+    fn mmap_vmo(&self, offset: usize) -> Result<(Vmo<Rights>, usize)> {
+        let vmo = self.io_uring.vmo_at(offset)?;
+        Ok((vmo.dup()?, 0))
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `IoUringFile` to it.
+        let now = RealTimeClock::get().read_time();
+        Metadata {
+            dev: 0,
+            ino: 0,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: now,
+            mtime: now,
+            ctime: now,
+            type_: InodeType::NamedPipe,
+            mode: InodeMode::from_bits_truncate(0o600),
+            nlinks: 1,
+            uid: Uid::new_root(),
+            gid: Gid::new_root(),
+            rdev: 0,
+        }
+    }
+}
+
+/// Checks the number of entries and rounds it up to a power of two.
+///
+/// If `is_clamped` is true, a too large number is clamped to `max_entries` instead of being
+/// rejected.
+fn clamp_entries(entries: u32, max_entries: u32, is_clamped: bool) -> Result<u32> {
+    if entries == 0 {
+        return_errno_with_message!(Errno::EINVAL, "the number of entries cannot be zero");
+    }
+    if entries > max_entries && !is_clamped {
+        return_errno_with_message!(Errno::EINVAL, "the number of entries is too large");
+    }
+
+    Ok(entries.min(max_entries).next_power_of_two())
+}
+
+fn check_no_args(arg: Vaddr, nr_args: u32) -> Result<()> {
+    if arg != 0 || nr_args != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the arguments must be empty");
+    }
+    Ok(())
+}
+
+/// The argument of `IORING_REGISTER_FILES_UPDATE`.
+///
+/// This is the same as `struct io_uring_files_update` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct IoUringFilesUpdate {
+    offset: u32,
+    resv: u32,
+    fds: u64,
+}
+
+const IO_URING_OP_SUPPORTED: u16 = 1 << 0;
+
+/// The header of the argument of `IORING_REGISTER_PROBE`.
+///
+/// This is the same as `struct io_uring_probe` in Linux, except that the flexible array member is
+/// omitted.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct IoUringProbe {
+    last_op: u8,
+    ops_len: u8,
+    resv: u16,
+    resv2: [u32; 3],
+}
+
+/// An operation in the argument of `IORING_REGISTER_PROBE`.
+///
+/// This is the same as `struct io_uring_probe_op` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct IoUringProbeOp {
+    op: u8,
+    resv: u8,
+    flags: u16,
+    resv2: u32,
+}
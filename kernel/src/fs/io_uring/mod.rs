@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Asynchronous I/O with io_uring.
+//!
+//! An io_uring instance consists of a submission queue (SQ) and a completion queue (CQ), which
+//! are rings shared between the kernel and the user space. The user space submits requests by
+//! filling submission queue entries (SQEs) and advancing the SQ tail, and then calls
+//! `io_uring_enter` to notify the kernel. The kernel performs the requests asynchronously and
+//! reports the results by appending completion queue entries (CQEs) to the CQ.
+//!
+//! Requests that may block are performed by the kernel work queue. If the target file is not
+//! ready for the request, the request will wait for the I/O events of the file before it is
+//! handed to the work queue, so that the workers are not blocked by slow files such as sockets.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/io_uring.7.html>
+
+mod file;
+mod request;
+mod ring;
+
+pub use file::IoUringFile;
+pub use ring::SubmittedIoUrings;
+
+/// The maximum number of entries in the submission queue.
+pub const IORING_MAX_ENTRIES: u32 = 32768;
+/// The maximum number of entries in the completion queue.
+pub const IORING_MAX_CQ_ENTRIES: u32 = 2 * IORING_MAX_ENTRIES;
+
+/// The `mmap` offset of the submission queue ring.
+pub const IORING_OFF_SQ_RING: usize = 0;
+/// The `mmap` offset of the completion queue ring.
+pub const IORING_OFF_CQ_RING: usize = 0x8000000;
+/// The `mmap` offset of the submission queue entries.
+pub const IORING_OFF_SQES: usize = 0x10000000;
+
+bitflags! {
+    /// The flags of `io_uring_setup`.
+    pub struct IoUringSetupFlags: u32 {
+        const IOPOLL     = 1 << 0;
+        const SQPOLL     = 1 << 1;
+        const SQ_AFF     = 1 << 2;
+        const CQSIZE     = 1 << 3;
+        const CLAMP      = 1 << 4;
+        const ATTACH_WQ  = 1 << 5;
+        const R_DISABLED = 1 << 6;
+        const SUBMIT_ALL = 1 << 7;
+    }
+}
+
+bitflags! {
+    /// The features that are reported to the user space by `io_uring_setup`.
+    pub struct IoUringFeatures: u32 {
+        const SINGLE_MMAP     = 1 << 0;
+        const NODROP          = 1 << 1;
+        const SUBMIT_STABLE   = 1 << 2;
+        const RW_CUR_POS      = 1 << 3;
+        const CUR_PERSONALITY = 1 << 4;
+        const FAST_POLL       = 1 << 5;
+    }
+}
+
+bitflags! {
+    /// The flags of `io_uring_enter`.
+    pub struct IoUringEnterFlags: u32 {
+        const GETEVENTS = 1 << 0;
+        const SQ_WAKEUP = 1 << 1;
+        const SQ_WAIT   = 1 << 2;
+        const EXT_ARG   = 1 << 3;
+    }
+}
+
+/// The opcodes of `io_uring_register`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+#[repr(u32)]
+pub enum IoUringRegisterOp {
+    RegisterBuffers = 0,
+    UnregisterBuffers = 1,
+    RegisterFiles = 2,
+    UnregisterFiles = 3,
+    RegisterFilesUpdate = 6,
+    RegisterProbe = 8,
+}
+
+/// The parameters of `io_uring_setup`.
+///
+/// This is the same as `struct io_uring_params` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct IoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: IoSqringOffsets,
+    pub cq_off: IoCqringOffsets,
+}
+
+/// The offsets of the fields in the submission queue ring.
+///
+/// This is the same as `struct io_sqring_offsets` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct IoSqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// The offsets of the fields in the completion queue ring.
+///
+/// This is the same as `struct io_cqring_offsets` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct IoCqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// A submission queue entry.
+///
+/// This is the same as `struct io_uring_sqe` in Linux, except that the unions are replaced by
+/// the names of their most common members.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct IoUringSqe {
+    pub opcode: u8,
+    pub flags: u8,
+    pub ioprio: u16,
+    pub fd: i32,
+    pub off: u64,
+    pub addr: u64,
+    pub len: u32,
+    pub op_flags: u32,
+    pub user_data: u64,
+    pub buf_index: u16,
+    pub personality: u16,
+    pub splice_fd_in: i32,
+    pub addr3: u64,
+    pub pad: u64,
+}
+
+/// A completion queue entry.
+///
+/// This is the same as `struct io_uring_cqe` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct IoUringCqe {
+    pub user_data: u64,
+    pub res: i32,
+    pub flags: u32,
+}
@@ -0,0 +1,959 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{slice, time::Duration};
+
+use aster_rights::Full;
+use ostd::sync::RwArc;
+
+use super::{
+    ring::{IoUring, UserBuffer},
+    IoUringSqe,
+};
+use crate::{
+    events::{IoEvents, Observer},
+    fs::{
+        file_handle::FileLike,
+        file_table::{get_file_fast, FdFlags, FileTable},
+        utils::{InodeType, StatusFlags},
+    },
+    net::socket::{MessageHeader, SendRecvFlags, SocketAddr},
+    prelude::*,
+    process::signal::PollHandle,
+    thread::{
+        work_queue::{submit_work_item, work_item::WorkItem, WorkPriority},
+        Tid,
+    },
+    time::{clocks::MonotonicClock, timer::Timeout, timespec_t, Timer},
+    util::net::{read_socket_addr_from_user, socket_addr_to_c_bytes, SockFlags},
+    vm::vmar::Vmar,
+};
+
+/// The opcodes of the requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+#[repr(u8)]
+pub(super) enum IoUringOp {
+    Nop = 0,
+    Readv = 1,
+    Writev = 2,
+    Fsync = 3,
+    ReadFixed = 4,
+    WriteFixed = 5,
+    PollAdd = 6,
+    PollRemove = 7,
+    Timeout = 11,
+    TimeoutRemove = 12,
+    Accept = 13,
+    AsyncCancel = 14,
+    Connect = 16,
+    Read = 22,
+    Write = 23,
+    Send = 26,
+    Recv = 27,
+}
+
+impl IoUringOp {
+    /// The largest opcode that is supported.
+    pub(super) const LAST: u8 = Self::Recv as u8;
+}
+
+bitflags! {
+    /// The flags of an SQE.
+    struct SqeFlags: u8 {
+        const FIXED_FILE       = 1 << 0;
+        const IO_DRAIN         = 1 << 1;
+        const IO_LINK          = 1 << 2;
+        const IO_HARDLINK      = 1 << 3;
+        const ASYNC            = 1 << 4;
+        const BUFFER_SELECT    = 1 << 5;
+        const CQE_SKIP_SUCCESS = 1 << 6;
+    }
+}
+
+const IORING_FSYNC_DATASYNC: u32 = 1 << 0;
+const IORING_POLL_ADD_MULTI: u32 = 1 << 0;
+const IORING_TIMEOUT_ABS: u32 = 1 << 0;
+
+/// The maximum number of I/O vectors in a request.
+const UIO_MAXIOV: usize = 1024;
+/// The maximum number of bytes that can be transferred by a request.
+const MAX_RW_COUNT: usize = (i32::MAX as usize) & !(PAGE_SIZE - 1);
+/// The size of the kernel buffer that is used to transfer data from or to the user buffers.
+const BOUNCE_BUFFER_LEN: usize = 64 * 1024;
+
+/// An in-flight request of an io_uring instance.
+pub(super) struct Request {
+    io_uring: Weak<IoUring>,
+    id: u64,
+    user_data: u64,
+    op: Op,
+    /// The thread that has submitted the request.
+    submitter: Tid,
+    /// The VMAR of the submitter, which is used to access the user buffers.
+    vmar: Vmar<Full>,
+    inner: Mutex<RequestInner>,
+    work_item: Arc<WorkItem>,
+    this: Weak<Request>,
+}
+
+struct RequestInner {
+    state: RequestState,
+    poll_handle: Option<PollHandle>,
+    timer: Option<Arc<Timer>>,
+    /// The file table of the submitter, where the accepted sockets are installed.
+    ///
+    /// It is dropped as soon as the request completes or is canceled, so it does not keep the
+    /// files of the submitter open after the submitter exits.
+    file_table: Option<RwArc<FileTable>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestState {
+    /// The request is waiting to be performed by the work queue.
+    Queued,
+    /// The request is waiting for the I/O events of the file or the timer.
+    Waiting,
+    /// The request is being performed.
+    Running,
+    /// The request has completed or has been canceled.
+    Completed,
+}
+
+enum Op {
+    Nop,
+    Read {
+        file: Arc<dyn FileLike>,
+        buffers: Vec<UserBuffer>,
+        offset: Option<usize>,
+    },
+    Write {
+        file: Arc<dyn FileLike>,
+        buffers: Vec<UserBuffer>,
+        offset: Option<usize>,
+    },
+    Fsync {
+        file: Arc<dyn FileLike>,
+        is_datasync: bool,
+    },
+    PollAdd {
+        file: Arc<dyn FileLike>,
+        events: IoEvents,
+    },
+    PollRemove {
+        target: u64,
+    },
+    Timeout {
+        duration: Duration,
+        is_absolute: bool,
+        count: u32,
+    },
+    TimeoutRemove {
+        target: u64,
+    },
+    Accept {
+        file: Arc<dyn FileLike>,
+        addr: Vaddr,
+        addrlen: Vaddr,
+        flags: SockFlags,
+    },
+    AsyncCancel {
+        target: u64,
+    },
+    Connect {
+        file: Arc<dyn FileLike>,
+        addr: SocketAddr,
+    },
+    Send {
+        file: Arc<dyn FileLike>,
+        buffer: UserBuffer,
+        flags: SendRecvFlags,
+    },
+    Recv {
+        file: Arc<dyn FileLike>,
+        buffer: UserBuffer,
+        flags: SendRecvFlags,
+    },
+}
+
+impl Request {
+    /// Creates a request from an SQE.
+    pub(super) fn new(
+        io_uring: &Arc<IoUring>,
+        id: u64,
+        sqe: &IoUringSqe,
+        ctx: &Context,
+    ) -> Result<Arc<Self>> {
+        let op = Op::parse(io_uring, sqe, ctx)?;
+        let vmar = ctx.user_space().root_vmar().dup()?;
+        let file_table = if matches!(op, Op::Accept { .. }) {
+            Some(ctx.thread_local.borrow_file_table().unwrap().clone())
+        } else {
+            None
+        };
+
+        Ok(Arc::new_cyclic(|this: &Weak<Request>| {
+            let weak_request = this.clone();
+            let work_item = WorkItem::new(Box::new(move || {
+                if let Some(request) = weak_request.upgrade() {
+                    request.run();
+                }
+            }));
+
+            Self {
+                io_uring: Arc::downgrade(io_uring),
+                id,
+                user_data: sqe.user_data,
+                op,
+                submitter: ctx.posix_thread.tid(),
+                vmar,
+                inner: Mutex::new(RequestInner {
+                    state: RequestState::Queued,
+                    poll_handle: None,
+                    timer: None,
+                    file_table,
+                }),
+                work_item,
+                this: this.clone(),
+            }
+        }))
+    }
+
+    /// Starts performing the request.
+    pub(super) fn submit(&self) {
+        match &self.op {
+            Op::Timeout {
+                duration,
+                is_absolute,
+                count,
+            } => self.arm_timer(*duration, *is_absolute, *count),
+            // Syncing a file may block, so it is always performed by the work queue.
+            Op::Fsync { .. } => self.queue(),
+            Op::PollRemove { target } => {
+                self.cancel_target(*target, |op| matches!(op, Op::PollAdd { .. }))
+            }
+            Op::TimeoutRemove { target } => {
+                self.cancel_target(*target, |op| matches!(op, Op::Timeout { .. }))
+            }
+            Op::AsyncCancel { target } => self.cancel_target(*target, |_| true),
+            op => match op.poll_target() {
+                Some((file, mask)) => self.arm_poll(file, mask),
+                // The other requests never block, so they are performed by the submitter. This
+                // includes `connect`, which needs the context of the submitter (e.g., to look up
+                // the network namespace or the path of a UNIX socket) and is therefore only
+                // supported for non-blocking sockets.
+                None => self.run(),
+            },
+        }
+    }
+
+    /// Returns the thread that has submitted the request.
+    pub(super) fn submitter(&self) -> Tid {
+        self.submitter
+    }
+
+    /// Completes the request with the result.
+    ///
+    /// This method does nothing if the request has completed.
+    pub(super) fn complete(&self, res: i32) {
+        let inner = self.inner.lock();
+        if inner.state == RequestState::Completed {
+            return;
+        }
+        self.complete_locked(inner, res);
+    }
+
+    /// Cancels the request.
+    ///
+    /// The request will complete with [`ECANCELED`] if it has not been performed.
+    ///
+    /// [`ECANCELED`]: Errno::ECANCELED
+    pub(super) fn cancel(&self) -> Result<()> {
+        self.cancel_without_cqe()?;
+        self.post_cqe(-(Errno::ECANCELED as i32));
+        Ok(())
+    }
+
+    /// Cancels the request, but leaves posting the CQE to the caller.
+    fn cancel_without_cqe(&self) -> Result<()> {
+        let inner = self.inner.lock();
+        match inner.state {
+            RequestState::Queued | RequestState::Waiting => {
+                self.finish_locked(inner);
+                Ok(())
+            }
+            RequestState::Running => {
+                return_errno_with_message!(Errno::EALREADY, "the request is being performed")
+            }
+            RequestState::Completed => {
+                return_errno_with_message!(Errno::ENOENT, "the request has completed")
+            }
+        }
+    }
+
+    fn complete_locked(&self, inner: MutexGuard<RequestInner>, res: i32) {
+        self.finish_locked(inner);
+        self.post_cqe(res);
+    }
+
+    fn finish_locked(&self, mut inner: MutexGuard<RequestInner>) {
+        inner.state = RequestState::Completed;
+        inner.poll_handle = None;
+        if let Some(timer) = inner.timer.take() {
+            timer.cancel();
+        }
+        // Dropping the file table may close files, so it is done without holding the lock.
+        let file_table = inner.file_table.take();
+        drop(inner);
+        drop(file_table);
+
+        if let Some(io_uring) = self.io_uring.upgrade() {
+            io_uring.remove_request(self.id);
+        }
+    }
+
+    /// Posts the CQE of the request.
+    ///
+    /// The CQE is dropped if the io_uring instance has been closed.
+    fn post_cqe(&self, res: i32) {
+        if let Some(io_uring) = self.io_uring.upgrade() {
+            io_uring.post_cqe(self.user_data, res, matches!(self.op, Op::Timeout { .. }));
+        }
+    }
+
+    fn queue(&self) {
+        submit_work_item(self.work_item.clone(), WorkPriority::Normal);
+    }
+
+    fn run(&self) {
+        {
+            let mut inner = self.inner.lock();
+            if matches!(inner.state, RequestState::Running | RequestState::Completed) {
+                return;
+            }
+            inner.state = RequestState::Running;
+            inner.poll_handle = None;
+        }
+
+        let res = match self.perform() {
+            Ok(res) => res,
+            Err(err) => {
+                // The file is not ready. Wait for the I/O events again.
+                if err.error() == Errno::EAGAIN
+                    && let Some((file, mask)) = self.op.poll_target()
+                {
+                    self.arm_poll(file, mask);
+                    return;
+                }
+                -(err.error() as i32)
+            }
+        };
+        self.complete(res);
+    }
+
+    /// Waits for the I/O events of the file before performing the request.
+    fn arm_poll(&self, file: &Arc<dyn FileLike>, mask: IoEvents) {
+        let mut inner = self.inner.lock();
+        if inner.state == RequestState::Completed {
+            return;
+        }
+        inner.state = RequestState::Waiting;
+
+        let mut poll_handle = PollHandle::new(self.this.clone() as Weak<dyn Observer<IoEvents>>);
+        let events = file.poll(mask, Some(&mut poll_handle));
+        inner.poll_handle = Some(poll_handle);
+        drop(inner);
+
+        if !events.is_empty() {
+            self.queue();
+        }
+    }
+
+    /// Waits for the timer before completing the request.
+    fn arm_timer(&self, duration: Duration, is_absolute: bool, count: u32) {
+        let weak_request = self.this.clone();
+        let timer = MonotonicClock::timer_manager().create_timer(move || {
+            if let Some(request) = weak_request.upgrade() {
+                request.queue();
+            }
+        });
+
+        let mut inner = self.inner.lock();
+        if inner.state == RequestState::Completed {
+            return;
+        }
+        inner.state = RequestState::Waiting;
+
+        if is_absolute {
+            timer.set_timeout(Timeout::When(duration));
+        } else {
+            timer.set_timeout(Timeout::After(duration));
+        }
+        inner.timer = Some(timer);
+        drop(inner);
+
+        if count > 0
+            && let Some(io_uring) = self.io_uring.upgrade()
+        {
+            io_uring.add_count_timeout(count, self.this.clone());
+        }
+    }
+
+    fn perform(&self) -> Result<i32> {
+        match &self.op {
+            Op::Nop => Ok(0),
+            Op::Read {
+                file,
+                buffers,
+                offset,
+            } => {
+                // Reading more data from the files other than regular files may block.
+                let read_once = !is_regular_file(file.as_ref());
+                self.read_to_user(buffers, read_once, |buf, pos| match offset {
+                    Some(offset) => file.read_bytes_at(offset + pos, buf),
+                    None => file.read_bytes(buf),
+                })
+            }
+            Op::Write {
+                file,
+                buffers,
+                offset,
+            } => {
+                // Writing more data to the files other than regular files may block.
+                let write_once = !is_regular_file(file.as_ref());
+                self.write_from_user(buffers, write_once, |buf, pos| match offset {
+                    Some(offset) => file.write_bytes_at(offset + pos, buf),
+                    None => file.write_bytes(buf),
+                })
+            }
+            Op::Fsync { file, is_datasync } => {
+                let dentry = file.as_inode_or_err()?.dentry();
+                if *is_datasync {
+                    dentry.sync_data()?;
+                } else {
+                    dentry.sync_all()?;
+                }
+                Ok(0)
+            }
+            Op::PollAdd { file, events } => {
+                let events = file.poll(*events, None);
+                if events.is_empty() {
+                    return_errno_with_message!(Errno::EAGAIN, "the file is not ready");
+                }
+                Ok(events.bits() as i32)
+            }
+            Op::Timeout { .. } => {
+                return_errno_with_message!(Errno::ETIME, "the timer has expired")
+            }
+            Op::Accept {
+                file,
+                addr,
+                addrlen,
+                flags,
+            } => {
+                // The request is being performed, so it cannot have been canceled.
+                let file_table = self.inner.lock().file_table.clone().unwrap();
+                let (connected_socket, socket_addr) = file.as_socket_or_err()?.accept()?;
+
+                if flags.contains(SockFlags::SOCK_NONBLOCK) {
+                    connected_socket.set_status_flags(StatusFlags::O_NONBLOCK)?;
+                }
+                if *addr != 0 {
+                    self.write_socket_addr(&socket_addr, *addr, *addrlen)?;
+                }
+
+                let fd_flags = if flags.contains(SockFlags::SOCK_CLOEXEC) {
+                    FdFlags::CLOEXEC
+                } else {
+                    FdFlags::empty()
+                };
+                let fd = file_table.write().insert(connected_socket, fd_flags);
+                Ok(fd)
+            }
+            Op::PollRemove { .. } | Op::TimeoutRemove { .. } | Op::AsyncCancel { .. } => {
+                unreachable!("the cancel requests are performed when they are submitted")
+            }
+            Op::Connect { file, addr } => {
+                // TODO: Support blocking sockets by performing the request with the work queue,
+                // which requires capturing the context of the submitter in advance.
+                if !file.status_flags().contains(StatusFlags::O_NONBLOCK) {
+                    return_errno_with_message!(
+                        Errno::EAGAIN,
+                        "connecting blocking sockets is not supported"
+                    );
+                }
+                file.as_socket_or_err()?.connect(addr.clone())?;
+                Ok(0)
+            }
+            Op::Send {
+                file,
+                buffer,
+                flags,
+            } => {
+                let socket = file.as_socket_or_err()?;
+                let flags = *flags | SendRecvFlags::MSG_DONTWAIT;
+                self.write_from_user(slice::from_ref(buffer), true, |buf, _| {
+                    let mut reader = VmReader::from(buf).to_fallible();
+                    socket.sendmsg(&mut reader, MessageHeader::new(None, Vec::new()), flags)
+                })
+            }
+            Op::Recv {
+                file,
+                buffer,
+                flags,
+            } => {
+                let socket = file.as_socket_or_err()?;
+                let flags = *flags | SendRecvFlags::MSG_DONTWAIT;
+                self.read_to_user(slice::from_ref(buffer), true, |buf, _| {
+                    let mut writer = VmWriter::from(buf).to_fallible();
+                    socket.recvmsg(&mut writer, flags).map(|(len, _)| len)
+                })
+            }
+        }
+    }
+
+    /// Cancels the target request and completes this request with the result.
+    fn cancel_target(&self, target: u64, is_target_op: impl Fn(&Op) -> bool) {
+        let Some(io_uring) = self.io_uring.upgrade() else {
+            return;
+        };
+
+        let canceled_request = io_uring
+            .find_request(|request| {
+                request.user_data == target && request.id != self.id && is_target_op(&request.op)
+            })
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the request is not found"))
+            .and_then(|request| request.cancel_without_cqe().map(|_| request));
+
+        // Like Linux, the CQE of this request precedes that of the canceled request.
+        match canceled_request {
+            Ok(request) => {
+                self.complete(0);
+                request.post_cqe(-(Errno::ECANCELED as i32));
+            }
+            Err(err) => self.complete(-(err.error() as i32)),
+        }
+    }
+
+    /// Reads data with `read` and copies the data to the user buffers.
+    ///
+    /// The data is read in chunks. `read` is called with the kernel buffer of each chunk and the
+    /// number of bytes that have been read. The reading stops if `read` returns fewer bytes than
+    /// requested, or after the first chunk if `read_once` is true.
+    fn read_to_user<F>(&self, buffers: &[UserBuffer], read_once: bool, mut read: F) -> Result<i32>
+    where
+        F: FnMut(&mut [u8], usize) -> Result<usize>,
+    {
+        let total_len = total_len(buffers);
+
+        let mut read_len = 0;
+        while read_len < total_len {
+            let mut buf = vec![0; (total_len - read_len).min(BOUNCE_BUFFER_LEN)];
+            let len = match read(&mut buf, read_len) {
+                Ok(len) => len,
+                Err(_) if read_len > 0 => break,
+                Err(err) => return Err(err),
+            };
+            self.copy_to_user(buffers, read_len, &buf[..len])?;
+            read_len += len;
+
+            if len < buf.len() || read_once {
+                break;
+            }
+        }
+
+        Ok(read_len as i32)
+    }
+
+    /// Copies data from the user buffers and writes the data with `write`.
+    ///
+    /// This is the counterpart of [`Self::read_to_user`].
+    fn write_from_user<F>(
+        &self,
+        buffers: &[UserBuffer],
+        write_once: bool,
+        mut write: F,
+    ) -> Result<i32>
+    where
+        F: FnMut(&[u8], usize) -> Result<usize>,
+    {
+        let total_len = total_len(buffers);
+
+        let mut written_len = 0;
+        while written_len < total_len {
+            let mut buf = vec![0; (total_len - written_len).min(BOUNCE_BUFFER_LEN)];
+            self.copy_from_user(buffers, written_len, &mut buf)?;
+            let len = match write(&buf, written_len) {
+                Ok(len) => len,
+                Err(_) if written_len > 0 => break,
+                Err(err) => return Err(err),
+            };
+            written_len += len;
+
+            if len < buf.len() || write_once {
+                break;
+            }
+        }
+
+        Ok(written_len as i32)
+    }
+
+    /// Copies `data` to the user buffers, starting from the position `pos` in the buffers.
+    fn copy_to_user(&self, buffers: &[UserBuffer], mut pos: usize, mut data: &[u8]) -> Result<()> {
+        for buffer in buffers {
+            if data.is_empty() {
+                break;
+            }
+            if pos >= buffer.len {
+                pos -= buffer.len;
+                continue;
+            }
+
+            let len = (buffer.len - pos).min(data.len());
+            self.vmar
+                .write_remote(buffer.addr + pos, &data[..len])
+                .map_err(|_| Error::with_message(Errno::EFAULT, "the user buffer is invalid"))?;
+            data = &data[len..];
+            pos = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Copies data from the user buffers to `buf`, starting from the position `pos` in the
+    /// buffers.
+    fn copy_from_user(
+        &self,
+        buffers: &[UserBuffer],
+        mut pos: usize,
+        mut buf: &mut [u8],
+    ) -> Result<()> {
+        for buffer in buffers {
+            if buf.is_empty() {
+                break;
+            }
+            if pos >= buffer.len {
+                pos -= buffer.len;
+                continue;
+            }
+
+            let len = (buffer.len - pos).min(buf.len());
+            let (dst, rest) = buf.split_at_mut(len);
+            self.vmar
+                .read_remote(buffer.addr + pos, dst)
+                .map_err(|_| Error::with_message(Errno::EFAULT, "the user buffer is invalid"))?;
+            buf = rest;
+            pos = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the socket address and its length to the user space.
+    ///
+    /// This is similar to [`write_socket_addr_to_user`], but works for the VMAR of the submitter.
+    ///
+    /// [`write_socket_addr_to_user`]: crate::util::net::write_socket_addr_to_user
+    fn write_socket_addr(
+        &self,
+        socket_addr: &SocketAddr,
+        dest: Vaddr,
+        max_len_ptr: Vaddr,
+    ) -> Result<()> {
+        let mut max_len = [0u8; size_of::<i32>()];
+        self.vmar
+            .read_remote(max_len_ptr, &mut max_len)
+            .map_err(|_| Error::with_message(Errno::EFAULT, "the address length is invalid"))?;
+        let max_len = i32::from_ne_bytes(max_len);
+        if max_len < 0 {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the socket address length cannot be negative"
+            );
+        }
+
+        let bytes = socket_addr_to_c_bytes(socket_addr);
+        let written_len = bytes.len().min(max_len as usize);
+        self.vmar
+            .write_remote(dest, &bytes[..written_len])
+            .and_then(|_| {
+                self.vmar
+                    .write_remote(max_len_ptr, &(bytes.len() as i32).to_ne_bytes())
+            })
+            .map_err(|_| Error::with_message(Errno::EFAULT, "the socket address is invalid"))
+    }
+}
+
+impl Observer<IoEvents> for Request {
+    fn on_events(&self, _events: &IoEvents) {
+        // This may be called in the interrupt context, so we let the work queue perform the
+        // request.
+        self.queue();
+    }
+}
+
+impl Op {
+    fn parse(io_uring: &IoUring, sqe: &IoUringSqe, ctx: &Context) -> Result<Self> {
+        let Some(sqe_flags) = SqeFlags::from_bits(sqe.flags) else {
+            return_errno_with_message!(Errno::EINVAL, "the SQE flags are invalid");
+        };
+        // TODO: Support linked requests, draining, buffer selection, and skipping CQEs.
+        if sqe_flags.intersects(
+            SqeFlags::IO_DRAIN
+                | SqeFlags::IO_LINK
+                | SqeFlags::IO_HARDLINK
+                | SqeFlags::BUFFER_SELECT
+                | SqeFlags::CQE_SKIP_SUCCESS,
+        ) {
+            return_errno_with_message!(Errno::EINVAL, "the SQE flags are not supported");
+        }
+
+        let opcode = IoUringOp::try_from(sqe.opcode)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the opcode is not supported"))?;
+
+        let get_file = || -> Result<Arc<dyn FileLike>> {
+            if sqe_flags.contains(SqeFlags::FIXED_FILE) {
+                let Ok(index) = usize::try_from(sqe.fd) else {
+                    return_errno_with_message!(Errno::EBADF, "the file index is negative");
+                };
+                io_uring.registered_file(index)
+            } else {
+                let mut file_table = ctx.thread_local.borrow_file_table_mut();
+                Ok(get_file_fast!(&mut file_table, sqe.fd).into_owned())
+            }
+        };
+
+        let op = match opcode {
+            IoUringOp::Nop => Op::Nop,
+            IoUringOp::Read | IoUringOp::Readv | IoUringOp::ReadFixed => {
+                let file = get_file()?;
+                let buffers = parse_buffers(io_uring, opcode, sqe, ctx)?;
+                let offset = parse_offset(file.as_ref(), sqe.off);
+                Op::Read {
+                    file,
+                    buffers,
+                    offset,
+                }
+            }
+            IoUringOp::Write | IoUringOp::Writev | IoUringOp::WriteFixed => {
+                let file = get_file()?;
+                let buffers = parse_buffers(io_uring, opcode, sqe, ctx)?;
+                let offset = parse_offset(file.as_ref(), sqe.off);
+                Op::Write {
+                    file,
+                    buffers,
+                    offset,
+                }
+            }
+            IoUringOp::Fsync => {
+                if sqe.op_flags & !IORING_FSYNC_DATASYNC != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the fsync flags are invalid");
+                }
+                Op::Fsync {
+                    file: get_file()?,
+                    is_datasync: sqe.op_flags & IORING_FSYNC_DATASYNC != 0,
+                }
+            }
+            IoUringOp::PollAdd => {
+                // TODO: Support multishot polling.
+                if sqe.len & IORING_POLL_ADD_MULTI != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "multishot polling is not supported");
+                }
+                Op::PollAdd {
+                    file: get_file()?,
+                    events: IoEvents::from_bits_truncate(sqe.op_flags),
+                }
+            }
+            IoUringOp::PollRemove => Op::PollRemove { target: sqe.addr },
+            IoUringOp::Timeout => {
+                if sqe.len != 1 {
+                    return_errno_with_message!(Errno::EINVAL, "only one timespec is allowed");
+                }
+                // TODO: Support the flags that select other clocks.
+                if sqe.op_flags & !IORING_TIMEOUT_ABS != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the timeout flags are invalid");
+                }
+                let timespec: timespec_t = ctx.user_space().read_val(sqe.addr as Vaddr)?;
+                Op::Timeout {
+                    duration: Duration::try_from(timespec)?,
+                    is_absolute: sqe.op_flags & IORING_TIMEOUT_ABS != 0,
+                    count: sqe.off as u32,
+                }
+            }
+            IoUringOp::TimeoutRemove => {
+                if sqe.op_flags != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the timeout flags are invalid");
+                }
+                Op::TimeoutRemove { target: sqe.addr }
+            }
+            IoUringOp::Accept => {
+                let Some(flags) = SockFlags::from_bits(sqe.op_flags as i32) else {
+                    return_errno_with_message!(Errno::EINVAL, "the accept flags are invalid");
+                };
+                let file = get_file()?;
+                file.as_socket_or_err()?;
+                Op::Accept {
+                    file,
+                    addr: sqe.addr as Vaddr,
+                    addrlen: sqe.off as Vaddr,
+                    flags,
+                }
+            }
+            IoUringOp::AsyncCancel => {
+                // TODO: Support canceling requests by file descriptors or canceling all
+                // matching requests.
+                if sqe.op_flags != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the cancel flags are not supported");
+                }
+                Op::AsyncCancel { target: sqe.addr }
+            }
+            IoUringOp::Connect => {
+                let file = get_file()?;
+                let addr = read_socket_addr_from_user(sqe.addr as Vaddr, sqe.off as usize)?;
+                Op::Connect { file, addr }
+            }
+            IoUringOp::Send | IoUringOp::Recv => {
+                let file = get_file()?;
+                file.as_socket_or_err()?;
+                let buffer = UserBuffer {
+                    addr: sqe.addr as Vaddr,
+                    len: sqe.len as usize,
+                };
+                check_buffer(&buffer)?;
+                let flags = SendRecvFlags::from_bits_truncate(sqe.op_flags as i32);
+                if opcode == IoUringOp::Send {
+                    Op::Send {
+                        file,
+                        buffer,
+                        flags,
+                    }
+                } else {
+                    Op::Recv {
+                        file,
+                        buffer,
+                        flags,
+                    }
+                }
+            }
+        };
+
+        Ok(op)
+    }
+
+    /// Returns the file and the I/O events that the request should wait for before it is
+    /// performed.
+    fn poll_target(&self) -> Option<(&Arc<dyn FileLike>, IoEvents)> {
+        match self {
+            Op::Read { file, .. } | Op::Recv { file, .. } | Op::Accept { file, .. } => {
+                Some((file, IoEvents::IN))
+            }
+            Op::Write { file, .. } | Op::Send { file, .. } => Some((file, IoEvents::OUT)),
+            Op::PollAdd { file, events } => Some((file, *events)),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the user buffers of the read or write requests.
+fn parse_buffers(
+    io_uring: &IoUring,
+    opcode: IoUringOp,
+    sqe: &IoUringSqe,
+    ctx: &Context,
+) -> Result<Vec<UserBuffer>> {
+    let buffers = match opcode {
+        IoUringOp::Readv | IoUringOp::Writev => {
+            let count = sqe.len as usize;
+            if count > UIO_MAXIOV {
+                return_errno_with_message!(Errno::EINVAL, "too many I/O vectors");
+            }
+
+            let user_space = ctx.user_space();
+            let mut buffers = Vec::with_capacity(count);
+            for i in 0..count {
+                let io_vec: UserIoVec =
+                    user_space.read_val(sqe.addr as Vaddr + i * size_of::<UserIoVec>())?;
+                let Ok(len) = usize::try_from(io_vec.len) else {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the length of I/O vector cannot be negative"
+                    );
+                };
+                buffers.push(UserBuffer {
+                    addr: io_vec.base,
+                    len,
+                });
+            }
+            buffers
+        }
+        IoUringOp::ReadFixed | IoUringOp::WriteFixed => {
+            let buffer = UserBuffer {
+                addr: sqe.addr as Vaddr,
+                len: sqe.len as usize,
+            };
+            let registered_buffer = io_uring.registered_buffer(sqe.buf_index as usize)?;
+            if !registered_buffer.contains(&buffer) {
+                return_errno_with_message!(
+                    Errno::EFAULT,
+                    "the buffer is out of the registered buffer"
+                );
+            }
+            vec![buffer]
+        }
+        _ => vec![UserBuffer {
+            addr: sqe.addr as Vaddr,
+            len: sqe.len as usize,
+        }],
+    };
+
+    for buffer in buffers.iter() {
+        check_buffer(buffer)?;
+    }
+
+    Ok(buffers)
+}
+
+/// Parses the file offset of the read or write requests.
+///
+/// Like Linux, an offset of `-1` means the current file offset. The offset is also ignored for
+/// the files that are not seekable.
+fn parse_offset(file: &dyn FileLike, offset: u64) -> Option<usize> {
+    if offset == u64::MAX {
+        return None;
+    }
+
+    match file.metadata().type_ {
+        InodeType::File | InodeType::BlockDevice => Some(offset as usize),
+        _ => None,
+    }
+}
+
+fn check_buffer(buffer: &UserBuffer) -> Result<()> {
+    if buffer
+        .addr
+        .checked_add(buffer.len)
+        .is_none_or(|end| end > isize::MAX as usize)
+    {
+        return_errno_with_message!(Errno::EFAULT, "the user buffer is invalid");
+    }
+    Ok(())
+}
+
+fn total_len(buffers: &[UserBuffer]) -> usize {
+    buffers
+        .iter()
+        .fold(0usize, |sum, buffer| sum.saturating_add(buffer.len))
+        .min(MAX_RW_COUNT)
+}
+
+fn is_regular_file(file: &dyn FileLike) -> bool {
+    file.metadata().type_ == InodeType::File
+}
+
+/// An I/O vector in the user space.
+///
+/// This is the same as `struct iovec` in Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct UserIoVec {
+    base: Vaddr,
+    len: isize,
+}
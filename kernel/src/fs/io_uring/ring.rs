@@ -0,0 +1,515 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use align_ext::AlignExt;
+use aster_rights::Rights;
+use ostd::{mm::VmIo, sync::WaitQueue};
+
+use super::{
+    request::Request, IoCqringOffsets, IoSqringOffsets, IoUringCqe, IoUringSqe, IORING_OFF_CQ_RING,
+    IORING_OFF_SQES, IORING_OFF_SQ_RING,
+};
+use crate::{
+    events::IoEvents,
+    fs::file_handle::FileLike,
+    prelude::*,
+    process::signal::{PollHandle, Pollee},
+    thread::Tid,
+    vm::vmo::{Vmo, VmoFlags, VmoOptions},
+};
+
+// The layout of the fields in the rings, which are shared by the SQ and the CQ.
+const SQ_HEAD: usize = 0;
+const SQ_TAIL: usize = 4;
+const CQ_HEAD: usize = 8;
+const CQ_TAIL: usize = 12;
+const SQ_RING_MASK: usize = 16;
+const CQ_RING_MASK: usize = 20;
+const SQ_RING_ENTRIES: usize = 24;
+const CQ_RING_ENTRIES: usize = 28;
+const SQ_DROPPED: usize = 32;
+const SQ_FLAGS: usize = 36;
+const CQ_FLAGS: usize = 40;
+const CQ_OVERFLOW: usize = 44;
+const CQES: usize = 64;
+
+/// The maximum number of registered files.
+const IORING_MAX_FIXED_FILES: usize = 1 << 20;
+/// The maximum number of registered buffers.
+const IORING_MAX_FIXED_BUFFERS: usize = 1 << 10;
+
+/// The state of an io_uring instance.
+///
+/// It is owned by the file. The in-flight requests only refer to it weakly, so that the requests
+/// that never complete do not keep it alive after the file is closed.
+pub(super) struct IoUring {
+    /// The VMO of the SQ and the CQ rings.
+    rings: Vmo<Rights>,
+    /// The VMO of the SQEs.
+    sqes: Vmo<Rights>,
+    sq_entries: u32,
+    cq_entries: u32,
+    /// The offset of the SQ index array in `rings`.
+    sq_array: usize,
+    /// The head of the SQ.
+    ///
+    /// The head is only updated by the kernel, so the value in the rings is only a copy of it.
+    /// The lock also serializes the consumption of the SQ.
+    sq_head: Mutex<u32>,
+    cq: Mutex<CqState>,
+    /// The in-flight requests, indexed by their IDs.
+    requests: Mutex<BTreeMap<u64, Arc<Request>>>,
+    next_request_id: AtomicU64,
+    registered_files: Mutex<Option<Vec<Option<Arc<dyn FileLike>>>>>,
+    registered_buffers: Mutex<Option<Vec<UserBuffer>>>,
+    pollee: Pollee,
+    cq_wait_queue: WaitQueue,
+}
+
+struct CqState {
+    /// The tail of the CQ.
+    ///
+    /// The tail is only updated by the kernel, so the value in the rings is only a copy of it.
+    tail: u32,
+    /// The number of posted CQEs, excluding those of the timeout requests.
+    num_completions: u64,
+    /// The timeout requests that wait for a number of completions.
+    count_timeouts: Vec<(u64, Weak<Request>)>,
+}
+
+/// The io_uring instances that a thread has submitted requests to.
+///
+/// The requests hold resources of the submitter, such as its address space and its file table,
+/// so they must be canceled when the submitter exits.
+#[derive(Default)]
+pub struct SubmittedIoUrings {
+    io_urings: Vec<Weak<IoUring>>,
+}
+
+impl SubmittedIoUrings {
+    /// Records that the thread has submitted requests to the io_uring instance.
+    fn add(&mut self, io_uring: &Arc<IoUring>) {
+        let weak_io_uring = Arc::downgrade(io_uring);
+        if self
+            .io_urings
+            .iter()
+            .any(|submitted| submitted.ptr_eq(&weak_io_uring))
+        {
+            return;
+        }
+
+        self.io_urings
+            .retain(|submitted| submitted.strong_count() > 0);
+        self.io_urings.push(weak_io_uring);
+    }
+
+    /// Cancels the in-flight requests that have been submitted by the thread.
+    ///
+    /// The requests that are being performed cannot be canceled. They will complete normally and
+    /// release the resources of the thread afterwards.
+    pub fn cancel_all(&mut self, tid: Tid) {
+        for io_uring in self.io_urings.drain(..) {
+            if let Some(io_uring) = io_uring.upgrade() {
+                io_uring.cancel_if(|request| request.submitter() == tid);
+            }
+        }
+    }
+}
+
+/// A buffer in the user space.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct UserBuffer {
+    pub(super) addr: Vaddr,
+    pub(super) len: usize,
+}
+
+impl UserBuffer {
+    /// Returns whether the buffer contains `other`.
+    pub(super) fn contains(&self, other: &UserBuffer) -> bool {
+        other.addr >= self.addr
+            && other
+                .addr
+                .checked_add(other.len)
+                .is_some_and(|end| end <= self.addr + self.len)
+    }
+}
+
+impl IoUring {
+    /// Creates an io_uring instance with the given numbers of entries.
+    ///
+    /// The numbers of entries must be powers of two.
+    pub(super) fn new(sq_entries: u32, cq_entries: u32) -> Result<Arc<Self>> {
+        debug_assert!(sq_entries.is_power_of_two() && cq_entries.is_power_of_two());
+
+        let sq_array = CQES + cq_entries as usize * size_of::<IoUringCqe>();
+        let rings_size = sq_array + sq_entries as usize * size_of::<u32>();
+        let sqes_size = sq_entries as usize * size_of::<IoUringSqe>();
+
+        // The VMOs are contiguous, so their pages are committed and accessing them never fails.
+        let rings = VmoOptions::<Rights>::new(rings_size.align_up(PAGE_SIZE))
+            .flags(VmoFlags::CONTIGUOUS)
+            .alloc()?;
+        let sqes = VmoOptions::<Rights>::new(sqes_size.align_up(PAGE_SIZE))
+            .flags(VmoFlags::CONTIGUOUS)
+            .alloc()?;
+
+        let io_uring = Self {
+            rings,
+            sqes,
+            sq_entries,
+            cq_entries,
+            sq_array,
+            sq_head: Mutex::new(0),
+            cq: Mutex::new(CqState {
+                tail: 0,
+                num_completions: 0,
+                count_timeouts: Vec::new(),
+            }),
+            requests: Mutex::new(BTreeMap::new()),
+            next_request_id: AtomicU64::new(0),
+            registered_files: Mutex::new(None),
+            registered_buffers: Mutex::new(None),
+            pollee: Pollee::new(),
+            cq_wait_queue: WaitQueue::new(),
+        };
+
+        io_uring.write_u32(SQ_RING_MASK, sq_entries - 1);
+        io_uring.write_u32(CQ_RING_MASK, cq_entries - 1);
+        io_uring.write_u32(SQ_RING_ENTRIES, sq_entries);
+        io_uring.write_u32(CQ_RING_ENTRIES, cq_entries);
+
+        Ok(Arc::new(io_uring))
+    }
+
+    pub(super) fn sq_entries(&self) -> u32 {
+        self.sq_entries
+    }
+
+    pub(super) fn cq_entries(&self) -> u32 {
+        self.cq_entries
+    }
+
+    /// Returns the offsets of the fields in the SQ ring.
+    pub(super) fn sq_offsets(&self) -> IoSqringOffsets {
+        IoSqringOffsets {
+            head: SQ_HEAD as u32,
+            tail: SQ_TAIL as u32,
+            ring_mask: SQ_RING_MASK as u32,
+            ring_entries: SQ_RING_ENTRIES as u32,
+            flags: SQ_FLAGS as u32,
+            dropped: SQ_DROPPED as u32,
+            array: self.sq_array as u32,
+            resv1: 0,
+            user_addr: 0,
+        }
+    }
+
+    /// Returns the offsets of the fields in the CQ ring.
+    pub(super) fn cq_offsets(&self) -> IoCqringOffsets {
+        IoCqringOffsets {
+            head: CQ_HEAD as u32,
+            tail: CQ_TAIL as u32,
+            ring_mask: CQ_RING_MASK as u32,
+            ring_entries: CQ_RING_ENTRIES as u32,
+            overflow: CQ_OVERFLOW as u32,
+            cqes: CQES as u32,
+            flags: CQ_FLAGS as u32,
+            resv1: 0,
+            user_addr: 0,
+        }
+    }
+
+    /// Returns the VMO to map at the `mmap` offset.
+    ///
+    /// Since the SQ ring and the CQ ring are in the same VMO, the offsets of both rings map the
+    /// same VMO.
+    pub(super) fn vmo_at(&self, offset: usize) -> Result<&Vmo<Rights>> {
+        match offset {
+            IORING_OFF_SQ_RING | IORING_OFF_CQ_RING => Ok(&self.rings),
+            IORING_OFF_SQES => Ok(&self.sqes),
+            _ => return_errno_with_message!(Errno::EINVAL, "the mmap offset is invalid"),
+        }
+    }
+
+    /// Submits at most `to_submit` requests in the SQ.
+    ///
+    /// Returns the number of the consumed SQEs.
+    pub(super) fn submit(self: &Arc<Self>, to_submit: u32, ctx: &Context) -> u32 {
+        let mut sq_head = self.sq_head.lock();
+
+        let sq_tail = self.read_u32(SQ_TAIL);
+        let to_submit = to_submit.min(sq_tail.wrapping_sub(*sq_head).min(self.sq_entries));
+
+        let mut submitted = 0;
+        while submitted < to_submit {
+            let array_offset = self.sq_array + (*sq_head & (self.sq_entries - 1)) as usize * 4;
+            let index = self.read_u32(array_offset);
+            *sq_head = sq_head.wrapping_add(1);
+
+            // Like Linux, an invalid index is consumed and counted as a dropped entry, but the
+            // submission stops at it.
+            if index >= self.sq_entries {
+                let dropped = self.read_u32(SQ_DROPPED);
+                self.write_u32(SQ_DROPPED, dropped.wrapping_add(1));
+                break;
+            }
+            submitted += 1;
+
+            let sqe: IoUringSqe = self
+                .sqes
+                .read_val(index as usize * size_of::<IoUringSqe>())
+                .unwrap();
+            let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+            match Request::new(self, id, &sqe, ctx) {
+                Ok(request) => {
+                    self.requests.lock().insert(id, request.clone());
+                    request.submit();
+                }
+                Err(err) => {
+                    // Like Linux, the submission stops at the first invalid SQE.
+                    self.post_cqe(sqe.user_data, -(err.error() as i32), false);
+                    break;
+                }
+            }
+        }
+
+        self.write_u32(SQ_HEAD, *sq_head);
+        drop(sq_head);
+
+        if submitted > 0 {
+            ctx.thread_local.io_urings().borrow_mut().add(self);
+        }
+
+        self.pollee.notify(IoEvents::OUT);
+        submitted
+    }
+
+    /// Waits until there are at least `min_complete` CQEs in the CQ.
+    pub(super) fn wait_cqes(&self, min_complete: u32) -> Result<()> {
+        self.cq_wait_queue
+            .pause_until(|| (self.num_cqes() >= min_complete).then_some(()))
+    }
+
+    /// Posts a CQE to the CQ.
+    ///
+    /// If the CQ is full, the CQE is dropped and the overflow counter is increased.
+    pub(super) fn post_cqe(&self, user_data: u64, res: i32, is_timeout: bool) {
+        let mut cq = self.cq.lock();
+
+        let cq_head = self.read_u32(CQ_HEAD);
+        if cq.tail.wrapping_sub(cq_head) >= self.cq_entries {
+            let overflow = self.read_u32(CQ_OVERFLOW);
+            self.write_u32(CQ_OVERFLOW, overflow.wrapping_add(1));
+        } else {
+            let cqe = IoUringCqe {
+                user_data,
+                res,
+                flags: 0,
+            };
+            let offset =
+                CQES + (cq.tail & (self.cq_entries - 1)) as usize * size_of::<IoUringCqe>();
+            self.rings.write_val(offset, &cqe).unwrap();
+            cq.tail = cq.tail.wrapping_add(1);
+            self.write_u32(CQ_TAIL, cq.tail);
+        }
+
+        let mut expired_timeouts = Vec::new();
+        if !is_timeout {
+            cq.num_completions += 1;
+            let num_completions = cq.num_completions;
+            cq.count_timeouts.retain(|(target, request)| {
+                if *target > num_completions {
+                    return true;
+                }
+                expired_timeouts.push(request.clone());
+                false
+            });
+        }
+        drop(cq);
+
+        self.pollee.notify(IoEvents::IN);
+        self.cq_wait_queue.wake_all();
+
+        for request in expired_timeouts {
+            if let Some(request) = request.upgrade() {
+                request.complete(0);
+            }
+        }
+    }
+
+    /// Makes the timeout request complete after `count` more requests complete.
+    pub(super) fn add_count_timeout(&self, count: u32, request: Weak<Request>) {
+        let mut cq = self.cq.lock();
+        let target = cq.num_completions + count as u64;
+        cq.count_timeouts.push((target, request));
+    }
+
+    /// Removes a completed request from the in-flight requests.
+    pub(super) fn remove_request(&self, id: u64) {
+        self.requests.lock().remove(&id);
+    }
+
+    /// Finds an in-flight request that satisfies the predicate.
+    pub(super) fn find_request<P>(&self, predicate: P) -> Option<Arc<Request>>
+    where
+        P: Fn(&Request) -> bool,
+    {
+        self.requests
+            .lock()
+            .values()
+            .find(|request| predicate(request))
+            .cloned()
+    }
+
+    /// Cancels all the in-flight requests.
+    ///
+    /// The requests that are being performed cannot be canceled and will complete normally.
+    pub(super) fn cancel_all(&self) {
+        self.cancel_if(|_| true);
+    }
+
+    /// Cancels the in-flight requests that satisfy the predicate.
+    ///
+    /// The requests that are being performed cannot be canceled and will complete normally.
+    fn cancel_if<P>(&self, predicate: P)
+    where
+        P: Fn(&Request) -> bool,
+    {
+        let requests: Vec<_> = self
+            .requests
+            .lock()
+            .values()
+            .filter(|request| predicate(request))
+            .cloned()
+            .collect();
+        for request in requests {
+            let _ = request.cancel();
+        }
+    }
+
+    pub(super) fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+
+        if self.num_cqes() > 0 {
+            events |= IoEvents::IN;
+        }
+
+        let sq_tail = self.read_u32(SQ_TAIL);
+        if sq_tail.wrapping_sub(self.read_u32(SQ_HEAD)) < self.sq_entries {
+            events |= IoEvents::OUT;
+        }
+
+        events
+    }
+
+    fn num_cqes(&self) -> u32 {
+        self.read_u32(CQ_TAIL).wrapping_sub(self.read_u32(CQ_HEAD))
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        self.rings.read_val(offset).unwrap()
+    }
+
+    fn write_u32(&self, offset: usize, val: u32) {
+        self.rings.write_val(offset, &val).unwrap();
+    }
+}
+
+// Registered files and buffers.
+impl IoUring {
+    pub(super) fn register_files(&self, files: Vec<Option<Arc<dyn FileLike>>>) -> Result<()> {
+        if files.is_empty() || files.len() > IORING_MAX_FIXED_FILES {
+            return_errno_with_message!(Errno::EINVAL, "the number of files is invalid");
+        }
+
+        let mut registered_files = self.registered_files.lock();
+        if registered_files.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "the files have been registered");
+        }
+        *registered_files = Some(files);
+
+        Ok(())
+    }
+
+    pub(super) fn unregister_files(&self) -> Result<()> {
+        self.registered_files
+            .lock()
+            .take()
+            .map(|_| ())
+            .ok_or_else(|| Error::with_message(Errno::ENXIO, "no files have been registered"))
+    }
+
+    /// Replaces the registered files starting from `offset`.
+    ///
+    /// Returns the number of the replaced files.
+    pub(super) fn update_files(
+        &self,
+        offset: usize,
+        files: Vec<Option<Arc<dyn FileLike>>>,
+    ) -> Result<usize> {
+        let mut registered_files = self.registered_files.lock();
+        let Some(registered_files) = registered_files.as_mut() else {
+            return_errno_with_message!(Errno::ENXIO, "no files have been registered");
+        };
+
+        if offset
+            .checked_add(files.len())
+            .is_none_or(|end| end > registered_files.len())
+        {
+            return_errno_with_message!(Errno::EINVAL, "the files are out of range");
+        }
+
+        let num_files = files.len();
+        for (slot, file) in registered_files[offset..].iter_mut().zip(files) {
+            *slot = file;
+        }
+
+        Ok(num_files)
+    }
+
+    pub(super) fn registered_file(&self, index: usize) -> Result<Arc<dyn FileLike>> {
+        self.registered_files
+            .lock()
+            .as_ref()
+            .and_then(|files| files.get(index).cloned().flatten())
+            .ok_or_else(|| Error::with_message(Errno::EBADF, "the registered file does not exist"))
+    }
+
+    pub(super) fn register_buffers(&self, buffers: Vec<UserBuffer>) -> Result<()> {
+        if buffers.is_empty() || buffers.len() > IORING_MAX_FIXED_BUFFERS {
+            return_errno_with_message!(Errno::EINVAL, "the number of buffers is invalid");
+        }
+
+        let mut registered_buffers = self.registered_buffers.lock();
+        if registered_buffers.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "the buffers have been registered");
+        }
+        *registered_buffers = Some(buffers);
+
+        Ok(())
+    }
+
+    pub(super) fn unregister_buffers(&self) -> Result<()> {
+        self.registered_buffers
+            .lock()
+            .take()
+            .map(|_| ())
+            .ok_or_else(|| Error::with_message(Errno::ENXIO, "no buffers have been registered"))
+    }
+
+    pub(super) fn registered_buffer(&self, index: usize) -> Result<UserBuffer> {
+        self.registered_buffers
+            .lock()
+            .as_ref()
+            .and_then(|buffers| buffers.get(index).copied())
+            .ok_or_else(|| {
+                Error::with_message(Errno::EFAULT, "the registered buffer does not exist")
+            })
+    }
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::time::Duration;
+
+use super::{MessageQueue, MqueueFs, BLOCK_SIZE};
+use crate::{
+    events::IoEvents,
+    fs::utils::{FileSystem, Inode, InodeMode, InodeType, Metadata},
+    prelude::*,
+    process::{
+        signal::{PollHandle, Pollable},
+        Gid, Uid,
+    },
+};
+
+/// The inode of a message queue.
+pub struct MqueueInode {
+    queue: MessageQueue,
+    metadata: RwLock<Metadata>,
+    fs: Weak<MqueueFs>,
+}
+
+impl MqueueInode {
+    pub(super) fn new(
+        ino: u64,
+        mode: InodeMode,
+        uid: Uid,
+        gid: Gid,
+        queue: MessageQueue,
+        fs: Weak<MqueueFs>,
+    ) -> Arc<Self> {
+        let mut metadata = Metadata::new_file(ino, mode, BLOCK_SIZE);
+        metadata.uid = uid;
+        metadata.gid = gid;
+
+        Arc::new(Self {
+            queue,
+            metadata: RwLock::new(metadata),
+            fs,
+        })
+    }
+
+    pub fn queue(&self) -> &MessageQueue {
+        &self.queue
+    }
+
+    /// Returns the status of the queue, which is the content of the file.
+    fn status(&self) -> String {
+        // The values of `SIGEV_SIGNAL` and `SIGEV_NONE`.
+        const SIGEV_SIGNAL: i32 = 0;
+        const SIGEV_NONE: i32 = 1;
+
+        let (notify, signo, notify_pid) = match self.queue.notification() {
+            Some(notification) => match notification.signal {
+                Some(signal) => (SIGEV_SIGNAL, signal.as_u8() as i32, notification.pid),
+                None => (SIGEV_NONE, 0, notification.pid),
+            },
+            None => (0, 0, 0),
+        };
+
+        format!(
+            "QSIZE:{:<10} NOTIFY:{:<5} SIGNO:{:<5} NOTIFY_PID:{:<6}\n",
+            self.queue.num_bytes(),
+            notify,
+            signo,
+            notify_pid
+        )
+    }
+}
+
+impl Inode for MqueueInode {
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        return_errno_with_message!(Errno::EINVAL, "message queues cannot be resized");
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn type_(&self) -> InodeType {
+        InodeType::File
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().atime
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().atime = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().mtime
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().mtime = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().ctime
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().ctime = time;
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let status = self.status();
+        let Some(bytes) = status.as_bytes().get(offset..) else {
+            return Ok(0);
+        };
+        let len = writer.write_fallible(&mut bytes.into())?;
+        Ok(len)
+    }
+
+    fn write_at(&self, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "message queues cannot be written as files");
+    }
+
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.queue.poll(mask, poller)
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+
+    fn is_dentry_cacheable(&self) -> bool {
+        false
+    }
+}
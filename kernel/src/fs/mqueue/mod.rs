@@ -0,0 +1,325 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The mqueue file system, which contains POSIX message queues.
+//!
+//! Each file in the file system is a message queue. The file system has a
+//! single instance, which is used internally by `mq_open` and `mq_unlink`
+//! and can also be mounted (normally at "/dev/mqueue") to inspect and remove
+//! the queues with ordinary file operations.
+//!
+//! The files of message queues are pollable. A queue is readable if it has
+//! messages and is writable if it is not full.
+
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use spin::Once;
+
+pub use self::{
+    inode::MqueueInode,
+    queue::{
+        MessageQueue, Notification, DFLT_MSGMAX, DFLT_MSGSIZEMAX, HARD_MSGMAX, HARD_MSGSIZEMAX,
+        MQ_PRIO_MAX,
+    },
+};
+use crate::{
+    fs::{
+        path::{Dentry, MountNode},
+        utils::{
+            DirentVisitor, FileSystem, FsFlags, Inode, InodeMode, InodeType, Metadata, SuperBlock,
+            NAME_MAX,
+        },
+    },
+    prelude::*,
+    process::{Gid, Uid},
+    time::clocks::RealTimeCoarseClock,
+};
+
+mod inode;
+mod queue;
+
+const MQUEUE_MAGIC: u64 = 0x1980_2002;
+const BLOCK_SIZE: usize = 4096;
+
+const ROOT_INO: u64 = 1;
+
+/// The mqueue file system.
+pub struct MqueueFs {
+    sb: SuperBlock,
+    root: Arc<RootInode>,
+    next_ino: AtomicU64,
+}
+
+impl MqueueFs {
+    fn new() -> Arc<Self> {
+        Arc::new_cyclic(|weak_self| Self {
+            sb: SuperBlock::new(MQUEUE_MAGIC, BLOCK_SIZE, NAME_MAX),
+            root: RootInode::new(weak_self.clone()),
+            next_ino: AtomicU64::new(ROOT_INO + 1),
+        })
+    }
+
+    /// Creates a message queue with the name.
+    ///
+    /// The queue holds at most `max_messages` messages, each of which is at
+    /// most `max_msg_size` bytes long.
+    pub fn create_queue(
+        &self,
+        name: &str,
+        mode: InodeMode,
+        uid: Uid,
+        gid: Gid,
+        max_messages: usize,
+        max_msg_size: usize,
+    ) -> Result<Arc<MqueueInode>> {
+        self.root
+            .create_queue(name, mode, uid, gid, max_messages, max_msg_size)
+    }
+
+    fn alloc_ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl FileSystem for MqueueFs {
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn flags(&self) -> FsFlags {
+        FsFlags::empty()
+    }
+}
+
+static MQUEUE_FS: Once<Arc<MqueueFs>> = Once::new();
+
+/// Returns the mqueue file system.
+pub fn singleton() -> &'static Arc<MqueueFs> {
+    MQUEUE_FS.call_once(MqueueFs::new)
+}
+
+/// Returns the root directory of the internal mount of the mqueue file
+/// system, where `mq_open` and `mq_unlink` look up the queues.
+pub fn root_dentry() -> &'static Dentry {
+    static ROOT_DENTRY: Once<Dentry> = Once::new();
+
+    ROOT_DENTRY.call_once(|| Dentry::new_fs_root(MountNode::new_root(singleton().clone())))
+}
+
+struct RootInode {
+    queues: RwLock<BTreeMap<String, Arc<MqueueInode>>>,
+    metadata: RwLock<Metadata>,
+    fs: Weak<MqueueFs>,
+}
+
+impl RootInode {
+    fn new(fs: Weak<MqueueFs>) -> Arc<Self> {
+        Arc::new(Self {
+            queues: RwLock::new(BTreeMap::new()),
+            metadata: RwLock::new(Metadata::new_dir(
+                ROOT_INO,
+                InodeMode::from_bits_truncate(0o1777),
+                BLOCK_SIZE,
+            )),
+            fs,
+        })
+    }
+
+    fn create_queue(
+        &self,
+        name: &str,
+        mode: InodeMode,
+        uid: Uid,
+        gid: Gid,
+        max_messages: usize,
+        max_msg_size: usize,
+    ) -> Result<Arc<MqueueInode>> {
+        if name.len() > NAME_MAX {
+            return_errno!(Errno::ENAMETOOLONG);
+        }
+
+        let mut queues = self.queues.write();
+        if queues.contains_key(name) {
+            return_errno_with_message!(Errno::EEXIST, "the message queue already exists");
+        }
+
+        let fs = self.fs.upgrade().unwrap();
+        let queue = MqueueInode::new(
+            fs.alloc_ino(),
+            mode,
+            uid,
+            gid,
+            MessageQueue::new(max_messages, max_msg_size),
+            self.fs.clone(),
+        );
+        queues.insert(name.to_string(), queue.clone());
+        drop(queues);
+
+        let now = RealTimeCoarseClock::get().read_time();
+        let mut metadata = self.metadata.write();
+        metadata.mtime = now;
+        metadata.ctime = now;
+
+        Ok(queue)
+    }
+}
+
+impl Inode for RootInode {
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn ino(&self) -> u64 {
+        ROOT_INO
+    }
+
+    fn type_(&self) -> InodeType {
+        InodeType::Dir
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().atime
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().atime = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().mtime
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().mtime = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().ctime
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().ctime = time;
+    }
+
+    fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        if type_ != InodeType::File {
+            return_errno_with_message!(Errno::EPERM, "only message queues can be created");
+        }
+
+        let queue = self.create_queue(
+            name,
+            mode,
+            Uid::new_root(),
+            Gid::new_root(),
+            DFLT_MSGMAX,
+            DFLT_MSGSIZEMAX,
+        )?;
+        Ok(queue)
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the 2 special entries.
+            if *offset == 0 {
+                visitor.visit(".", ROOT_INO, InodeType::Dir, *offset)?;
+                *offset += 1;
+            }
+            if *offset == 1 {
+                visitor.visit("..", ROOT_INO, InodeType::Dir, *offset)?;
+                *offset += 1;
+            }
+
+            // Read the queues.
+            let queues = self.queues.read();
+            for (idx, (name, queue)) in queues.iter().enumerate().skip(*offset - 2) {
+                visitor.visit(name, queue.ino(), InodeType::File, idx + 2)?;
+                *offset = idx + 3;
+            }
+            Ok(())
+        };
+
+        let mut iterate_offset = offset;
+        match try_readdir(&mut iterate_offset, visitor) {
+            Err(e) if offset == iterate_offset => Err(e),
+            _ => Ok(iterate_offset - offset),
+        }
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        self.queues.write().remove(name).ok_or_else(|| {
+            Error::with_message(Errno::ENOENT, "the message queue does not exist")
+        })?;
+
+        let now = RealTimeCoarseClock::get().read_time();
+        let mut metadata = self.metadata.write();
+        metadata.mtime = now;
+        metadata.ctime = now;
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "." | ".." => self.fs().root_inode(),
+            name => self
+                .queues
+                .read()
+                .get(name)
+                .cloned()
+                .ok_or(Error::new(Errno::ENOENT))?,
+        };
+        Ok(inode)
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+
+    fn is_dentry_cacheable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::{
+        process_table,
+        signal::{sig_num::SigNum, signals::kernel::KernelSignal, PollHandle, Pollable, Pollee},
+        Pid,
+    },
+};
+
+// The following constant values are derived from the default values in Linux.
+
+/// Default maximum number of messages in a queue.
+pub const DFLT_MSGMAX: usize = 10;
+/// Default maximum size of a message in bytes.
+pub const DFLT_MSGSIZEMAX: usize = 8192;
+/// Upper bound of the maximum number of messages in a queue.
+pub const HARD_MSGMAX: usize = 65536;
+/// Upper bound of the maximum size of a message in bytes.
+pub const HARD_MSGSIZEMAX: usize = 16 * 1024 * 1024;
+/// Priorities of messages must be less than this value.
+pub const MQ_PRIO_MAX: u32 = 32768;
+
+/// A POSIX message queue.
+///
+/// Messages are received in the order of descending priority, and messages
+/// of the same priority are received in the order they were sent.
+pub struct MessageQueue {
+    /// Maximum number of messages
+    max_messages: usize,
+    /// Maximum size of a message in bytes
+    max_msg_size: usize,
+    /// Inner
+    inner: SpinLock<MessageQueueInner>,
+    pollee: Pollee,
+}
+
+struct MessageQueueInner {
+    /// Messages grouped by their priorities
+    messages: BTreeMap<u32, VecDeque<Vec<u8>>>,
+    /// Number of messages
+    num_messages: usize,
+    /// Number of bytes of all the messages
+    num_bytes: usize,
+    /// Number of receivers that are waiting for messages
+    num_waiting_receivers: usize,
+    /// The process that is notified of new messages
+    notification: Option<Notification>,
+}
+
+/// A registration for the notification of new messages, as made by
+/// `mq_notify`.
+#[derive(Debug, Clone, Copy)]
+pub struct Notification {
+    /// The process that registers the notification
+    pub pid: Pid,
+    /// The signal to send, or `None` if no signal should be sent
+    pub signal: Option<SigNum>,
+}
+
+impl MessageQueue {
+    pub(super) fn new(max_messages: usize, max_msg_size: usize) -> Self {
+        Self {
+            max_messages,
+            max_msg_size,
+            inner: SpinLock::new(MessageQueueInner {
+                messages: BTreeMap::new(),
+                num_messages: 0,
+                num_bytes: 0,
+                num_waiting_receivers: 0,
+                notification: None,
+            }),
+            pollee: Pollee::new(),
+        }
+    }
+
+    pub fn max_messages(&self) -> usize {
+        self.max_messages
+    }
+
+    pub fn max_msg_size(&self) -> usize {
+        self.max_msg_size
+    }
+
+    /// Returns the number of messages in the queue.
+    pub fn num_messages(&self) -> usize {
+        self.inner.lock().num_messages
+    }
+
+    /// Returns the number of bytes of all the messages in the queue.
+    pub fn num_bytes(&self) -> usize {
+        self.inner.lock().num_bytes
+    }
+
+    /// Sends a message with the priority.
+    ///
+    /// If the queue is full, this method waits until there is space or
+    /// `timeout` expires, unless `is_nonblocking` is true.
+    pub fn send(
+        &self,
+        data: Vec<u8>,
+        priority: u32,
+        is_nonblocking: bool,
+        timeout: Option<&Duration>,
+    ) -> Result<()> {
+        if data.len() > self.max_msg_size {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+        }
+        if priority >= MQ_PRIO_MAX {
+            return_errno_with_message!(Errno::EINVAL, "the priority is too large");
+        }
+
+        let mut data = Some(data);
+        let mut try_send = || self.try_send(&mut data, priority);
+        if is_nonblocking {
+            return try_send();
+        }
+        self.wait_events(IoEvents::OUT, timeout, try_send)
+            .map_err(convert_timeout_error)
+    }
+
+    fn try_send(&self, data: &mut Option<Vec<u8>>, priority: u32) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.num_messages >= self.max_messages {
+            return_errno_with_message!(Errno::EAGAIN, "the message queue is full");
+        }
+
+        let data = data.take().unwrap();
+        let was_empty = inner.num_messages == 0;
+        inner.num_messages += 1;
+        inner.num_bytes += data.len();
+        inner.messages.entry(priority).or_default().push_back(data);
+
+        // Like Linux, the notification is only sent if the queue was empty
+        // and no receivers are waiting for the message.
+        let notification = if was_empty && inner.num_waiting_receivers == 0 {
+            inner.notification.take()
+        } else {
+            None
+        };
+        drop(inner);
+
+        self.pollee.notify(IoEvents::IN);
+        if let Some(notification) = notification {
+            notification.deliver();
+        }
+        Ok(())
+    }
+
+    /// Receives the message with the highest priority.
+    ///
+    /// Returns the message and its priority.
+    ///
+    /// If the queue is empty, this method waits until a message arrives or
+    /// `timeout` expires, unless `is_nonblocking` is true.
+    pub fn receive(
+        &self,
+        is_nonblocking: bool,
+        timeout: Option<&Duration>,
+    ) -> Result<(Vec<u8>, u32)> {
+        match self.try_receive() {
+            Err(err) if err.error() == Errno::EAGAIN && !is_nonblocking => (),
+            result => return result,
+        }
+
+        self.inner.lock().num_waiting_receivers += 1;
+        let result = self
+            .wait_events(IoEvents::IN, timeout, || self.try_receive())
+            .map_err(convert_timeout_error);
+        self.inner.lock().num_waiting_receivers -= 1;
+
+        result
+    }
+
+    fn try_receive(&self) -> Result<(Vec<u8>, u32)> {
+        let mut inner = self.inner.lock();
+        let Some(mut entry) = inner.messages.last_entry() else {
+            return_errno_with_message!(Errno::EAGAIN, "the message queue is empty");
+        };
+
+        let priority = *entry.key();
+        let data = entry.get_mut().pop_front().unwrap();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+        inner.num_messages -= 1;
+        inner.num_bytes -= data.len();
+        drop(inner);
+
+        self.pollee.notify(IoEvents::OUT);
+        Ok((data, priority))
+    }
+
+    /// Returns the current notification registration.
+    ///
+    /// A registration whose process has exited is discarded.
+    pub fn notification(&self) -> Option<Notification> {
+        let notification = self.inner.lock().notification?;
+        if process_table::get_process(notification.pid).is_some() {
+            return Some(notification);
+        }
+
+        let mut inner = self.inner.lock();
+        if inner
+            .notification
+            .is_some_and(|current| current.pid == notification.pid)
+        {
+            inner.notification = None;
+        }
+        None
+    }
+
+    /// Registers `notification`, or unregisters the current one if
+    /// `notification` is `None`.
+    ///
+    /// Only one process can register for notification at a time. A process
+    /// can only unregister its own registration.
+    pub fn set_notification(&self, pid: Pid, notification: Option<Notification>) -> Result<()> {
+        let current = self.notification();
+        let mut inner = self.inner.lock();
+
+        match notification {
+            Some(notification) => {
+                if current.is_some() {
+                    return_errno_with_message!(
+                        Errno::EBUSY,
+                        "another process has registered for notification"
+                    );
+                }
+                inner.notification = Some(notification);
+            }
+            None => {
+                if current.is_some_and(|current| current.pid == pid) {
+                    inner.notification = None;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let inner = self.inner.lock();
+
+        let mut events = IoEvents::empty();
+        if inner.num_messages > 0 {
+            events |= IoEvents::IN;
+        }
+        if inner.num_messages < self.max_messages {
+            events |= IoEvents::OUT;
+        }
+
+        events
+    }
+}
+
+impl Pollable for MessageQueue {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl Notification {
+    fn deliver(&self) {
+        let Some(signal) = self.signal else {
+            return;
+        };
+        if let Some(process) = process_table::get_process(self.pid) {
+            process.enqueue_signal(KernelSignal::new(signal));
+        }
+    }
+}
+
+/// Converts the error of an expired timeout to the one that the message
+/// queue syscalls should return.
+fn convert_timeout_error(err: Error) -> Error {
+    if err.error() == Errno::ETIME {
+        Error::with_message(Errno::ETIMEDOUT, "the timeout expired")
+    } else {
+        err
+    }
+}
@@ -0,0 +1,449 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The inotify API for monitoring file system events.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use core::{
+    mem::size_of,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
+
+use align_ext::AlignExt;
+
+use super::{FsEventSubscriber, FsEvents};
+use crate::{
+    current_userspace,
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        utils::{Inode, InodeMode, InodeType, IoctlCmd, Metadata, StatusFlags},
+    },
+    prelude::*,
+    process::{
+        signal::{PollHandle, Pollable, Pollee},
+        Gid, Uid,
+    },
+    time::clocks::RealTimeClock,
+};
+
+/// The maximum number of queued events of an inotify instance.
+///
+/// This is the default value of `/proc/sys/fs/inotify/max_queued_events` in
+/// Linux.
+const MAX_QUEUED_EVENTS: usize = 16384;
+
+/// The maximum number of watches of an inotify instance.
+///
+/// This is the default value of `/proc/sys/fs/inotify/max_user_watches` in
+/// Linux.
+const MAX_WATCHES: usize = 8192;
+
+bitflags! {
+    /// The flags that control how an inotify watch is added.
+    pub struct InotifyControls: u32 {
+        /// Only watches the path if it is a directory.
+        const IN_ONLYDIR = 0x0100_0000;
+        /// Does not follow the path if it is a symbolic link.
+        const IN_DONT_FOLLOW = 0x0200_0000;
+        /// Stops watching the children after they are unlinked.
+        const IN_EXCL_UNLINK = 0x0400_0000;
+        /// Only creates a watch and fails if the path is already watched.
+        const IN_MASK_CREATE = 0x1000_0000;
+        /// Adds the events to the mask of the existing watch.
+        const IN_MASK_ADD = 0x2000_0000;
+        /// Removes the watch after delivering one event.
+        const IN_ONESHOT = 0x8000_0000;
+    }
+}
+
+/// An inotify instance, which is exposed to the user space as a file.
+pub struct InotifyFile {
+    /// The watches, indexed by watch descriptors.
+    watches: Mutex<InotifyWatches>,
+    /// The queued events that have not been read.
+    event_queue: Mutex<VecDeque<InotifyEvent>>,
+    pollee: Pollee,
+    is_nonblocking: AtomicBool,
+    this: Weak<InotifyFile>,
+}
+
+struct InotifyWatches {
+    watches: BTreeMap<u32, Arc<InotifyWatch>>,
+    next_wd: u32,
+}
+
+impl InotifyFile {
+    /// Creates a new inotify instance.
+    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+        Arc::new_cyclic(|weak_self| Self {
+            watches: Mutex::new(InotifyWatches {
+                watches: BTreeMap::new(),
+                next_wd: 1,
+            }),
+            event_queue: Mutex::new(VecDeque::new()),
+            pollee: Pollee::new(),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            this: weak_self.clone(),
+        })
+    }
+
+    /// Adds a watch on `inode`, or modifies the existing watch on `inode`.
+    ///
+    /// Returns the watch descriptor.
+    pub fn add_watch(
+        &self,
+        inode: &Arc<dyn Inode>,
+        events: FsEvents,
+        controls: InotifyControls,
+    ) -> Result<u32> {
+        let events = events & FsEvents::ALL_EVENTS;
+        if events.is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "no events are specified");
+        }
+        if controls.contains(InotifyControls::IN_MASK_ADD | InotifyControls::IN_MASK_CREATE) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IN_MASK_ADD and IN_MASK_CREATE are mutually exclusive"
+            );
+        }
+        if controls.contains(InotifyControls::IN_ONLYDIR) && inode.type_() != InodeType::Dir {
+            return_errno_with_message!(Errno::ENOTDIR, "the inode is not a directory");
+        }
+
+        let mut watches = self.watches.lock();
+
+        if let Some(watch) = watches
+            .watches
+            .values()
+            .find(|watch| Arc::ptr_eq(&watch.inode, inode))
+        {
+            if controls.contains(InotifyControls::IN_MASK_CREATE) {
+                return_errno_with_message!(Errno::EEXIST, "the inode is already watched");
+            }
+            let events = if controls.contains(InotifyControls::IN_MASK_ADD) {
+                events | watch.events()
+            } else {
+                events
+            };
+            watch.set_mask(events, controls);
+            return Ok(watch.wd);
+        }
+
+        if watches.watches.len() >= MAX_WATCHES {
+            return_errno_with_message!(Errno::ENOSPC, "too many watches");
+        }
+        let Some(publisher) = inode.fs_event_publisher_or_init() else {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the inode cannot be watched");
+        };
+
+        let wd = watches.next_wd;
+        watches.next_wd = wd
+            .checked_add(1)
+            .filter(|next_wd| *next_wd <= i32::MAX as u32)
+            .ok_or_else(|| Error::with_message(Errno::ENOSPC, "watch descriptors exhausted"))?;
+
+        let watch = Arc::new(InotifyWatch {
+            wd,
+            mask: AtomicU32::new(0),
+            inode: inode.clone(),
+            owner: self.this.clone(),
+        });
+        watch.set_mask(events, controls);
+        publisher.add_subscriber(watch.clone());
+        watches.watches.insert(wd, watch);
+
+        Ok(wd)
+    }
+
+    /// Removes the watch specified by the watch descriptor.
+    pub fn remove_watch(&self, wd: u32) -> Result<()> {
+        let watch = self
+            .watches
+            .lock()
+            .watches
+            .remove(&wd)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the watch does not exist"))?;
+
+        watch.unsubscribe();
+        self.push_event(InotifyEvent::new(wd as i32, FsEvents::IGNORED, 0, None));
+        Ok(())
+    }
+
+    /// Removes the watch from the watch table, if the watch still exists.
+    fn forget_watch(&self, watch: &InotifyWatch) -> Option<Arc<InotifyWatch>> {
+        let mut watches = self.watches.lock();
+        match watches.watches.get(&watch.wd) {
+            Some(found) if core::ptr::eq(Arc::as_ptr(found), watch) => {
+                watches.watches.remove(&watch.wd)
+            }
+            _ => None,
+        }
+    }
+
+    fn push_event(&self, event: InotifyEvent) {
+        let mut event_queue = self.event_queue.lock();
+
+        // Coalesce the event with the last one if they are identical.
+        if event_queue.back() == Some(&event) {
+            return;
+        }
+
+        if event_queue.len() >= MAX_QUEUED_EVENTS {
+            let overflow_event = InotifyEvent::new(-1, FsEvents::Q_OVERFLOW, 0, None);
+            if event_queue.back() != Some(&overflow_event) {
+                event_queue.push_back(overflow_event);
+            }
+        } else {
+            event_queue.push_back(event);
+        }
+        drop(event_queue);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let mut event_queue = self.event_queue.lock();
+        if event_queue.is_empty() {
+            return_errno_with_message!(Errno::EAGAIN, "no events are available");
+        }
+
+        let mut read_len = 0;
+        while let Some(event) = event_queue.front() {
+            let event_len = event.len();
+            if event_len > writer.avail() {
+                if read_len == 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the buffer is too small");
+                }
+                break;
+            }
+
+            event.write_to(writer)?;
+            read_len += event_len;
+            event_queue.pop_front();
+        }
+
+        self.pollee.invalidate();
+        Ok(read_len)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.event_queue.lock().is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+}
+
+impl Drop for InotifyFile {
+    fn drop(&mut self) {
+        let watches = core::mem::take(&mut self.watches.get_mut().watches);
+        for watch in watches.into_values() {
+            watch.unsubscribe();
+        }
+    }
+}
+
+impl Pollable for InotifyFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for InotifyFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            self.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_read(writer))
+        }
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "inotify files cannot be written");
+    }
+
+    fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
+        match cmd {
+            IoctlCmd::FIONREAD => {
+                let len: usize = self.event_queue.lock().iter().map(InotifyEvent::len).sum();
+                current_userspace!().write_val(arg, &(len as i32))?;
+                Ok(0)
+            }
+            _ => return_errno_with_message!(Errno::EINVAL, "ioctl is not supported"),
+        }
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `InotifyFile` to it.
+        let now = RealTimeClock::get().read_time();
+        Metadata {
+            dev: 0,
+            ino: 0,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: now,
+            mtime: now,
+            ctime: now,
+            type_: InodeType::NamedPipe,
+            mode: InodeMode::from_bits_truncate(0o600),
+            nlinks: 1,
+            uid: Uid::new_root(),
+            gid: Gid::new_root(),
+            rdev: 0,
+        }
+    }
+}
+
+/// A watch of an inotify instance on an inode.
+struct InotifyWatch {
+    /// The watch descriptor.
+    wd: u32,
+    /// The watched events and the control flags.
+    mask: AtomicU32,
+    /// The watched inode.
+    ///
+    /// Like Linux, the watch keeps the inode alive.
+    inode: Arc<dyn Inode>,
+    owner: Weak<InotifyFile>,
+}
+
+impl InotifyWatch {
+    fn events(&self) -> FsEvents {
+        FsEvents::from_bits_truncate(self.mask.load(Ordering::Relaxed))
+    }
+
+    fn controls(&self) -> InotifyControls {
+        InotifyControls::from_bits_truncate(self.mask.load(Ordering::Relaxed))
+    }
+
+    fn set_mask(&self, events: FsEvents, controls: InotifyControls) {
+        self.mask
+            .store(events.bits() | controls.bits(), Ordering::Relaxed);
+    }
+
+    /// Stops receiving events from the inode.
+    fn unsubscribe(self: Arc<Self>) {
+        if let Some(publisher) = self.inode.fs_event_publisher() {
+            let subscriber: Arc<dyn FsEventSubscriber> = self;
+            publisher.remove_subscriber(&subscriber);
+        }
+    }
+}
+
+impl FsEventSubscriber for InotifyWatch {
+    fn deliver_event(&self, events: FsEvents, name: Option<&str>, cookie: u32) {
+        let Some(owner) = self.owner.upgrade() else {
+            return;
+        };
+
+        let interesting_events = events & self.events();
+        if !interesting_events.is_empty() {
+            let mask = interesting_events | (events & FsEvents::ISDIR);
+            owner.push_event(InotifyEvent::new(self.wd as i32, mask, cookie, name));
+        }
+
+        let is_removed = if events.contains(FsEvents::DELETE_SELF) {
+            // The publisher has removed all its subscribers.
+            owner.forget_watch(self).is_some()
+        } else if !interesting_events.is_empty()
+            && self.controls().contains(InotifyControls::IN_ONESHOT)
+        {
+            owner
+                .forget_watch(self)
+                .map(|watch| watch.unsubscribe())
+                .is_some()
+        } else {
+            false
+        };
+
+        if is_removed {
+            owner.push_event(InotifyEvent::new(
+                self.wd as i32,
+                FsEvents::IGNORED,
+                0,
+                None,
+            ));
+        }
+    }
+}
+
+/// An event queued in an inotify instance.
+#[derive(Debug, PartialEq, Eq)]
+struct InotifyEvent {
+    wd: i32,
+    mask: FsEvents,
+    cookie: u32,
+    name: Option<String>,
+}
+
+impl InotifyEvent {
+    fn new(wd: i32, mask: FsEvents, cookie: u32, name: Option<&str>) -> Self {
+        Self {
+            wd,
+            mask,
+            cookie,
+            name: name.map(String::from),
+        }
+    }
+
+    /// Returns the length of the name field, including the padding.
+    fn name_len(&self) -> usize {
+        // The name is terminated by a null byte and padded to the size of
+        // `c_inotify_event` (see `round_event_name_len` in Linux).
+        self.name.as_ref().map_or(0, |name| {
+            (name.len() + 1).align_up(size_of::<c_inotify_event>())
+        })
+    }
+
+    /// Returns the length of the event when it is read.
+    fn len(&self) -> usize {
+        size_of::<c_inotify_event>() + self.name_len()
+    }
+
+    fn write_to(&self, writer: &mut VmWriter) -> Result<()> {
+        let name_len = self.name_len();
+        let header = c_inotify_event {
+            wd: self.wd,
+            mask: self.mask.bits(),
+            cookie: self.cookie,
+            len: name_len as u32,
+        };
+        writer.write_val(&header)?;
+
+        if let Some(name) = self.name.as_ref() {
+            writer.write_fallible(&mut name.as_bytes().into())?;
+            writer.fill_zeros(name_len - name.len())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct c_inotify_event {
+    wd: i32,
+    mask: u32,
+    cookie: u32,
+    len: u32,
+}
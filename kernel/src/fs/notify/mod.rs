@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! File system event notification.
+//!
+//! The VFS publishes file system events (e.g., creating, deleting or
+//! modifying files) to the [`FsEventPublisher`] attached to the affected
+//! inode. Notification mechanisms like inotify subscribe to the publishers
+//! of the inodes that they watch.
+//!
+//! Since the events are generated in the VFS layer, all file systems support
+//! event notification without any extra effort.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{fs::utils::Inode, prelude::*};
+
+pub mod inotify;
+
+bitflags! {
+    /// File system events.
+    ///
+    /// The values are the same as those of the inotify events in Linux.
+    pub struct FsEvents: u32 {
+        /// The file was accessed.
+        const ACCESS = 0x0000_0001;
+        /// The file was modified.
+        const MODIFY = 0x0000_0002;
+        /// The metadata of the file was changed.
+        const ATTRIB = 0x0000_0004;
+        /// A file opened for writing was closed.
+        const CLOSE_WRITE = 0x0000_0008;
+        /// A file not opened for writing was closed.
+        const CLOSE_NOWRITE = 0x0000_0010;
+        /// The file was opened.
+        const OPEN = 0x0000_0020;
+        /// A file was moved out of the watched directory.
+        const MOVED_FROM = 0x0000_0040;
+        /// A file was moved into the watched directory.
+        const MOVED_TO = 0x0000_0080;
+        /// A file was created in the watched directory.
+        const CREATE = 0x0000_0100;
+        /// A file was deleted from the watched directory.
+        const DELETE = 0x0000_0200;
+        /// The watched file was deleted.
+        const DELETE_SELF = 0x0000_0400;
+        /// The watched file was moved.
+        const MOVE_SELF = 0x0000_0800;
+        /// The file system containing the watched file was unmounted.
+        const UNMOUNT = 0x0000_2000;
+        /// The event queue overflowed.
+        const Q_OVERFLOW = 0x0000_4000;
+        /// The watch was removed.
+        const IGNORED = 0x0000_8000;
+        /// The subject of the event is a directory.
+        const ISDIR = 0x4000_0000;
+
+        const CLOSE = Self::CLOSE_WRITE.bits | Self::CLOSE_NOWRITE.bits;
+        const MOVE = Self::MOVED_FROM.bits | Self::MOVED_TO.bits;
+        const ALL_EVENTS = Self::ACCESS.bits | Self::MODIFY.bits | Self::ATTRIB.bits
+            | Self::CLOSE.bits | Self::OPEN.bits | Self::MOVE.bits | Self::CREATE.bits
+            | Self::DELETE.bits | Self::DELETE_SELF.bits | Self::MOVE_SELF.bits;
+    }
+}
+
+/// A subscriber of file system events.
+pub trait FsEventSubscriber: Send + Sync {
+    /// Delivers the events that happened on the watched inode.
+    ///
+    /// If the events happened on a child of the watched directory, `name` is
+    /// the name of the child. The events of the same move operation share the
+    /// same non-zero `cookie`.
+    fn deliver_event(&self, events: FsEvents, name: Option<&str>, cookie: u32);
+}
+
+/// A publisher of file system events, which is attached to an inode.
+#[derive(Default)]
+pub struct FsEventPublisher {
+    subscribers: RwLock<Vec<Arc<dyn FsEventSubscriber>>>,
+}
+
+impl FsEventPublisher {
+    /// Adds a subscriber.
+    pub fn add_subscriber(&self, subscriber: Arc<dyn FsEventSubscriber>) {
+        self.subscribers.write().push(subscriber);
+    }
+
+    /// Removes a subscriber.
+    ///
+    /// Returns whether the subscriber was found.
+    pub fn remove_subscriber(&self, subscriber: &Arc<dyn FsEventSubscriber>) -> bool {
+        let mut subscribers = self.subscribers.write();
+        let Some(pos) = subscribers.iter().position(|s| Arc::ptr_eq(s, subscriber)) else {
+            return false;
+        };
+        subscribers.swap_remove(pos);
+        true
+    }
+
+    /// Returns whether there are any subscribers.
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.read().is_empty()
+    }
+
+    /// Publishes events to all subscribers.
+    ///
+    /// If the events contain [`FsEvents::DELETE_SELF`], the inode has gone
+    /// and all subscribers are removed after the events are delivered.
+    pub fn publish(&self, events: FsEvents, name: Option<&str>, cookie: u32) {
+        let subscribers = if events.contains(FsEvents::DELETE_SELF) {
+            core::mem::take(&mut *self.subscribers.write())
+        } else {
+            self.subscribers.read().clone()
+        };
+
+        // Deliver the events without holding the lock, since the subscribers
+        // may remove themselves from the publisher.
+        for subscriber in subscribers {
+            subscriber.deliver_event(events, name, cookie);
+        }
+    }
+}
+
+impl Debug for FsEventPublisher {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("FsEventPublisher")
+            .field("num_subscribers", &self.subscribers.read().len())
+            .finish()
+    }
+}
+
+/// Publishes events that happened on `inode` to its watchers.
+pub fn publish_event(inode: &dyn Inode, events: FsEvents, name: Option<&str>, cookie: u32) {
+    if let Some(publisher) = inode.fs_event_publisher() {
+        publisher.publish(events, name, cookie);
+    }
+}
+
+/// Returns the publisher of `inode` if it has any subscribers.
+pub fn watched_publisher(inode: &dyn Inode) -> Option<Arc<FsEventPublisher>> {
+    inode
+        .fs_event_publisher()
+        .filter(|publisher| publisher.has_subscribers())
+}
+
+/// Allocates a new cookie to connect the events of a move operation.
+pub fn alloc_move_cookie() -> u32 {
+    static NEXT_COOKIE: AtomicU32 = AtomicU32::new(1);
+
+    loop {
+        let cookie = NEXT_COOKIE.fetch_add(1, Ordering::Relaxed);
+        if cookie != 0 {
+            return cookie;
+        }
+    }
+}
@@ -20,9 +20,9 @@ use crate::{
         device::Device,
         path::Dentry,
         utils::{
-            DirentVisitor, FallocMode, FileSystem, FsFlags, Inode, InodeMode, InodeType, IoctlCmd,
-            Metadata, MknodType, SuperBlock, XattrName, XattrNamespace, XattrSetFlags, NAME_MAX,
-            XATTR_VALUE_MAX_LEN,
+            DirentVisitor, Extension, FallocMode, FileSystem, FsFlags, Inode, InodeMode, InodeType,
+            IoctlCmd, Metadata, MknodType, SuperBlock, XattrName, XattrNamespace, XattrSetFlags,
+            NAME_MAX, XATTR_VALUE_MAX_LEN,
         },
     },
     prelude::*,
@@ -93,6 +93,8 @@ struct OverlayInode {
     lowers: Vec<Arc<dyn Inode>>,
     /// Weak fs reference.
     fs: Weak<OverlayFS>,
+    /// Extensions.
+    extension: Extension,
     /// Weak self reference.
     self_: Weak<OverlayInode>,
 }
@@ -132,6 +134,7 @@ impl FileSystem for OverlayFS {
                 .cloned()
                 .collect(),
             fs: self.self_.clone(),
+            extension: Extension::new(),
             self_: weak.clone(),
         })
     }
@@ -231,6 +234,7 @@ impl OverlayInode {
             upper_is_opaque,
             lowers: Vec::new(),
             fs: self.fs.clone(),
+            extension: Extension::new(),
             self_: weak.clone(),
         });
         Ok(new_child)
@@ -659,6 +663,7 @@ impl OverlayInode {
             upper_is_opaque,
             lowers: lower_children,
             fs: self.fs.clone(),
+            extension: Extension::new(),
             self_: weak.clone(),
         });
 
@@ -923,6 +928,10 @@ impl Inode for OverlayInode {
     fn get_xattr(&self, name: XattrName, value_writer: &mut VmWriter) -> Result<usize>;
     fn list_xattr(&self, namespace: XattrNamespace, list_writer: &mut VmWriter) -> Result<usize>;
     fn remove_xattr(&self, name: XattrName) -> Result<()>;
+
+    fn extension(&self) -> Option<&Extension> {
+        Some(&self.extension)
+    }
 }
 
 /// The index of the layer of an `OverlayFS`.
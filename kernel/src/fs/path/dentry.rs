@@ -15,6 +15,7 @@ use ostd::sync::RwMutexWriteGuard;
 use super::{is_dot, is_dot_or_dotdot, is_dotdot};
 use crate::{
     fs::{
+        notify::{alloc_move_cookie, publish_event, watched_publisher, FsEvents},
         path::mount::MountNode,
         utils::{
             FileSystem, Inode, InodeMode, InodeType, Metadata, MknodType, Permission, XattrName,
@@ -39,7 +40,11 @@ pub struct Dentry_ {
     type_: InodeType,
     name_and_parent: RwLock<Option<(String, Arc<Dentry_>)>>,
     children: RwMutex<DentryChildren>,
-    flags: AtomicU32,
+    /// The number of mounts whose mountpoint is this dentry.
+    ///
+    /// A dentry can be the mountpoint in multiple mount trees (e.g., in
+    /// different mount namespaces), so a counter is used instead of a flag.
+    mount_count: AtomicU32,
     this: Weak<Dentry_>,
 }
 
@@ -61,7 +66,7 @@ impl Dentry_ {
                 _ => RwLock::new(None),
             },
             children: RwMutex::new(DentryChildren::new()),
-            flags: AtomicU32::new(DentryFlags::empty().bits()),
+            mount_count: AtomicU32::new(0),
             this: weak_self.clone(),
         })
     }
@@ -110,11 +115,6 @@ impl Dentry_ {
         &self.inode
     }
 
-    fn flags(&self) -> DentryFlags {
-        let flags = self.flags.load(Ordering::Relaxed);
-        DentryFlags::from_bits(flags).unwrap()
-    }
-
     /// Checks if this dentry is a descendant (child, grandchild, or
     /// great-grandchild, etc.) of another dentry.
     pub fn is_descendant_of(&self, ancestor: &Arc<Self>) -> bool {
@@ -129,17 +129,16 @@ impl Dentry_ {
     }
 
     pub fn is_mountpoint(&self) -> bool {
-        self.flags().contains(DentryFlags::MOUNTED)
+        self.mount_count.load(Ordering::Acquire) > 0
     }
 
     pub fn set_mountpoint_dentry(&self) {
-        self.flags
-            .fetch_or(DentryFlags::MOUNTED.bits(), Ordering::Release);
+        self.mount_count.fetch_add(1, Ordering::Release);
     }
 
     pub fn clear_mountpoint(&self) {
-        self.flags
-            .fetch_and(!(DentryFlags::MOUNTED.bits()), Ordering::Release);
+        let old_count = self.mount_count.fetch_sub(1, Ordering::Release);
+        debug_assert!(old_count > 0);
     }
 
     /// Currently, the root `Dentry_` of a fs is the root of a mount.
@@ -166,6 +165,7 @@ impl Dentry_ {
             children.upgrade().insert(name, new_child.clone());
         }
 
+        new_child.publish_event(FsEvents::CREATE);
         Ok(new_child)
     }
 
@@ -217,6 +217,7 @@ impl Dentry_ {
             children.upgrade().insert(name, new_child.clone());
         }
 
+        new_child.publish_event(FsEvents::CREATE);
         Ok(new_child)
     }
 
@@ -242,6 +243,9 @@ impl Dentry_ {
         if dentry.is_dentry_cacheable() {
             children.upgrade().insert(name, dentry.clone());
         }
+
+        publish_event(old_inode.as_ref(), FsEvents::ATTRIB, None, 0);
+        dentry.publish_event(FsEvents::CREATE);
         Ok(())
     }
 
@@ -253,11 +257,17 @@ impl Dentry_ {
 
         let children = self.children.upread();
         children.check_mountpoint(name)?;
+        let child_inode = self.find_child_inode(&children, name);
 
         self.inode.unlink(name)?;
 
         let mut children = children.upgrade();
         children.delete(name);
+        drop(children);
+
+        if let Some(child_inode) = child_inode {
+            self.publish_child_removal(&child_inode, name);
+        }
         Ok(())
     }
 
@@ -269,11 +279,17 @@ impl Dentry_ {
 
         let children = self.children.upread();
         children.check_mountpoint(name)?;
+        let child_inode = self.find_child_inode(&children, name);
 
         self.inode.rmdir(name)?;
 
         let mut children = children.upgrade();
         children.delete(name);
+        drop(children);
+
+        if let Some(child_inode) = child_inode {
+            self.publish_child_removal(&child_inode, name);
+        }
         Ok(())
     }
 
@@ -295,6 +311,7 @@ impl Dentry_ {
             let children = self.children.upread();
             let old_dentry = children.check_mountpoint_then_find(old_name)?;
             children.check_mountpoint(new_name)?;
+            let child_inode = self.find_child_inode(&children, old_name);
 
             self.inode.rename(old_name, &self.inode, new_name)?;
 
@@ -311,12 +328,18 @@ impl Dentry_ {
                     children.delete(new_name);
                 }
             }
+            drop(children);
+
+            if let Some(child_inode) = child_inode {
+                self.publish_move(&child_inode, old_name, self, new_name);
+            }
         } else {
             // The two are different dentries
             let (mut self_children, mut new_dir_children) =
                 write_lock_children_on_two_dentries(self, new_dir);
             let old_dentry = self_children.check_mountpoint_then_find(old_name)?;
             new_dir_children.check_mountpoint(new_name)?;
+            let child_inode = self.find_child_inode(&self_children, old_name);
 
             self.inode.rename(old_name, &new_dir.inode, new_name)?;
             match old_dentry.as_ref() {
@@ -331,9 +354,132 @@ impl Dentry_ {
                     new_dir_children.delete(new_name);
                 }
             }
+            drop(self_children);
+            drop(new_dir_children);
+
+            if let Some(child_inode) = child_inode {
+                self.publish_move(&child_inode, old_name, new_dir, new_name);
+            }
         }
         Ok(())
     }
+
+    /// Sets the mode of the inode.
+    pub fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.inode.set_mode(mode)?;
+        self.publish_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Sets the owner of the inode.
+    pub fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.inode.set_owner(uid)?;
+        self.publish_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Sets the group of the inode.
+    pub fn set_group(&self, gid: Gid) -> Result<()> {
+        self.inode.set_group(gid)?;
+        self.publish_event(FsEvents::ATTRIB);
+        Ok(())
+    }
+
+    /// Resizes the inode.
+    pub fn resize(&self, size: usize) -> Result<()> {
+        self.inode.resize(size)?;
+        self.publish_event(FsEvents::MODIFY);
+        Ok(())
+    }
+
+    /// Publishes file system events that happened on this `Dentry_`.
+    ///
+    /// The events are published to the watchers of the inode and the
+    /// watchers of the parent directory.
+    pub fn publish_event(&self, events: FsEvents) {
+        let events = if self.type_ == InodeType::Dir {
+            events | FsEvents::ISDIR
+        } else {
+            events
+        };
+
+        publish_event(self.inode.as_ref(), events, None, 0);
+
+        // Avoid copying the name unless the parent is watched, since this is
+        // done on every read and write.
+        let name_and_publisher = {
+            let name_and_parent = self.name_and_parent.read();
+            name_and_parent.as_ref().and_then(|(name, parent)| {
+                watched_publisher(parent.inode.as_ref()).map(|publisher| (name.clone(), publisher))
+            })
+        };
+        if let Some((name, publisher)) = name_and_publisher {
+            publisher.publish(events, Some(&name), 0);
+        }
+    }
+
+    /// Finds the inode of a child, which is used to publish events after the
+    /// child is removed or moved.
+    fn find_child_inode(&self, children: &DentryChildren, name: &str) -> Option<Arc<dyn Inode>> {
+        match children.find(name) {
+            Ok(Some(dentry)) => Some(dentry.inode.clone()),
+            Ok(None) => self.inode.lookup(name).ok(),
+            Err(_) => None,
+        }
+    }
+
+    /// Publishes events after the child `name` with `child_inode` is removed.
+    fn publish_child_removal(&self, child_inode: &Arc<dyn Inode>, name: &str) {
+        let is_dir = child_inode.type_() == InodeType::Dir;
+        let isdir_flag = if is_dir {
+            FsEvents::ISDIR
+        } else {
+            FsEvents::empty()
+        };
+
+        if is_dir || child_inode.metadata().nlinks == 0 {
+            publish_event(child_inode.as_ref(), FsEvents::DELETE_SELF, None, 0);
+        } else {
+            publish_event(child_inode.as_ref(), FsEvents::ATTRIB, None, 0);
+        }
+        publish_event(
+            self.inode.as_ref(),
+            FsEvents::DELETE | isdir_flag,
+            Some(name),
+            0,
+        );
+    }
+
+    /// Publishes events after the child `old_name` with `child_inode` is moved
+    /// to `new_dir` as `new_name`.
+    fn publish_move(
+        &self,
+        child_inode: &Arc<dyn Inode>,
+        old_name: &str,
+        new_dir: &Dentry_,
+        new_name: &str,
+    ) {
+        let isdir_flag = if child_inode.type_() == InodeType::Dir {
+            FsEvents::ISDIR
+        } else {
+            FsEvents::empty()
+        };
+        let cookie = alloc_move_cookie();
+
+        publish_event(
+            self.inode.as_ref(),
+            FsEvents::MOVED_FROM | isdir_flag,
+            Some(old_name),
+            cookie,
+        );
+        publish_event(
+            new_dir.inode.as_ref(),
+            FsEvents::MOVED_TO | isdir_flag,
+            Some(new_name),
+            cookie,
+        );
+        publish_event(child_inode.as_ref(), FsEvents::MOVE_SELF, None, 0);
+    }
 }
 
 #[inherit_methods(from = "self.inode")]
@@ -343,13 +489,9 @@ impl Dentry_ {
     pub fn sync_data(&self) -> Result<()>;
     pub fn metadata(&self) -> Metadata;
     pub fn mode(&self) -> Result<InodeMode>;
-    pub fn set_mode(&self, mode: InodeMode) -> Result<()>;
     pub fn size(&self) -> usize;
-    pub fn resize(&self, size: usize) -> Result<()>;
     pub fn owner(&self) -> Result<Uid>;
-    pub fn set_owner(&self, uid: Uid) -> Result<()>;
     pub fn group(&self) -> Result<Gid>;
-    pub fn set_group(&self, gid: Gid) -> Result<()>;
     pub fn atime(&self) -> Duration;
     pub fn set_atime(&self, time: Duration);
     pub fn mtime(&self) -> Duration;
@@ -405,12 +547,6 @@ impl DentryKey {
     }
 }
 
-bitflags! {
-    struct DentryFlags: u32 {
-        const MOUNTED = 1 << 0;
-    }
-}
-
 enum DentryOptions {
     Root,
     Leaf((String, Arc<Dentry_>)),
@@ -748,6 +884,20 @@ impl Dentry {
         Ok(())
     }
 
+    /// Gets the corresponding `Dentry` in a copy of the mount tree.
+    ///
+    /// For more details, see [`MountNode::find_corresponding_mount`].
+    pub(super) fn find_corresponding(
+        &self,
+        old_root: &MountNode,
+        new_root: &Arc<MountNode>,
+    ) -> Option<Self> {
+        let mount_node = self
+            .mount_node
+            .find_corresponding_mount(old_root, new_root)?;
+        Some(Self::new(mount_node, self.inner.clone()))
+    }
+
     fn this(&self) -> Self {
         self.clone()
     }
@@ -781,6 +931,7 @@ impl Dentry {
     pub fn set_ctime(&self, time: Duration);
     pub fn key(&self) -> DentryKey;
     pub fn inode(&self) -> &Arc<dyn Inode>;
+    pub fn publish_event(&self, events: FsEvents);
     pub fn is_root_of_mount(&self) -> bool;
     pub fn is_mountpoint(&self) -> bool;
     pub fn set_xattr(
@@ -4,9 +4,11 @@
 
 pub use dentry::{Dentry, DentryKey};
 pub use mount::MountNode;
+pub use mount_namespace::MountNamespace;
 
 mod dentry;
 mod mount;
+mod mount_namespace;
 
 /// Checks if the file name is ".", indicating it's the current directory.
 pub const fn is_dot(filename: &str) -> bool {
@@ -149,6 +149,7 @@ impl MountNode {
                     .children
                     .write()
                     .insert(key, new_child_mount.clone());
+                mountpoint_dentry.set_mountpoint_dentry();
                 new_child_mount.set_parent(&new_parent_mount);
                 new_child_mount
                     .set_mountpoint_dentry(&old_child_mount.mountpoint_dentry().unwrap());
@@ -164,10 +165,9 @@ impl MountNode {
     fn detach_mount_node(&self) {
         if let Some(parent) = self.parent() {
             let parent = parent.upgrade().unwrap();
-            parent
-                .children
-                .write()
-                .remove(&self.mountpoint_dentry().unwrap().key());
+            let mountpoint_dentry = self.mountpoint_dentry().unwrap();
+            parent.children.write().remove(&mountpoint_dentry.key());
+            mountpoint_dentry.clear_mountpoint();
         }
     }
 
@@ -248,6 +248,33 @@ impl MountNode {
         *parent = Some(Arc::downgrade(mount_node));
     }
 
+    /// Finds the mount node in the mount tree of `new_root` that corresponds
+    /// to this mount node in the mount tree of `old_root`.
+    ///
+    /// The mount tree of `new_root` should be a copy of that of `old_root`.
+    /// Returns `None` if this mount node is not in the mount tree of `old_root`
+    /// or the corresponding mount node has been unmounted.
+    pub(super) fn find_corresponding_mount(
+        &self,
+        old_root: &MountNode,
+        new_root: &Arc<MountNode>,
+    ) -> Option<Arc<MountNode>> {
+        let mut mountpoint_keys = Vec::new();
+        let mut mount = self.this();
+        while !core::ptr::eq(mount.as_ref(), old_root) {
+            let parent = mount.parent()?.upgrade()?;
+            mountpoint_keys.push(mount.mountpoint_dentry()?.key());
+            mount = parent;
+        }
+
+        let mut new_mount = new_root.clone();
+        for key in mountpoint_keys.iter().rev() {
+            let child = new_mount.children.read().get(key)?.clone();
+            new_mount = child;
+        }
+        Some(new_mount)
+    }
+
     fn this(&self) -> Arc<Self> {
         self.this.upgrade().unwrap()
     }
@@ -258,6 +285,16 @@ impl MountNode {
     }
 }
 
+impl Drop for MountNode {
+    fn drop(&mut self) {
+        for child in self.children.get_mut().values() {
+            if let Some(mountpoint_dentry) = child.mountpoint_dentry() {
+                mountpoint_dentry.clear_mountpoint();
+            }
+        }
+    }
+}
+
 impl Debug for MountNode {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.debug_struct("MountNode")
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::{Dentry, MountNode};
+use crate::{fs::rootfs::root_mount, prelude::*, process::namespace::alloc_ns_id};
+
+/// A mount namespace.
+///
+/// A mount namespace owns a mount tree. The processes in different mount
+/// namespaces see different mount trees, so a mount or unmount operation in
+/// one namespace is invisible to the others.
+pub struct MountNamespace {
+    id: u64,
+    root: Arc<MountNode>,
+}
+
+impl MountNamespace {
+    /// Returns the initial mount namespace, which owns the mount tree of the root file system.
+    pub fn get_init_singleton() -> &'static Arc<MountNamespace> {
+        static INIT: Once<Arc<MountNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            Arc::new(Self {
+                id: alloc_ns_id(),
+                root: root_mount().clone(),
+            })
+        })
+    }
+
+    /// Creates a new mount namespace with a copy of the mount tree of this namespace.
+    pub fn new_copy(&self) -> Arc<MountNamespace> {
+        let root = self
+            .root
+            .clone_mount_node_tree(self.root.root_dentry(), true);
+
+        Arc::new(Self {
+            id: alloc_ns_id(),
+            root,
+        })
+    }
+
+    /// Returns the ID of the namespace.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the root mount node of the namespace.
+    pub fn root(&self) -> &Arc<MountNode> {
+        &self.root
+    }
+
+    /// Returns the root directory of the namespace.
+    pub fn root_dentry(&self) -> Dentry {
+        Dentry::new_fs_root(self.root.clone())
+    }
+
+    /// Translates a `Dentry` in this namespace to the corresponding one in `new_ns`.
+    ///
+    /// `new_ns` should be created by [`Self::new_copy`] from this namespace. If the `Dentry`
+    /// cannot be found in `new_ns`, the root directory of `new_ns` is returned.
+    pub fn translate(&self, dentry: &Dentry, new_ns: &MountNamespace) -> Dentry {
+        dentry
+            .find_corresponding(&self.root, &new_ns.root)
+            .unwrap_or_else(|| new_ns.root_dentry())
+    }
+}
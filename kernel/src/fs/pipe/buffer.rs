@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use ostd::mm::{FrameAllocOptions, Infallible, UFrame, UntypedMem};
+
+use crate::{prelude::*, util::MultiRead};
+
+/// A buffer of a pipe, which refers to a range of bytes in a frame.
+///
+/// The frame is either allocated by the pipe to hold the bytes written to it,
+/// or shared with its owner (e.g., a page in the page cache that is spliced
+/// into the pipe). In the latter case, the bytes are not copied, so later
+/// changes to the frame are visible to the readers of the pipe, like Linux.
+#[derive(Clone)]
+pub struct PipeBuffer {
+    frame: UFrame,
+    offset: usize,
+    len: usize,
+    /// Whether the frame is exclusively owned by the buffer, so that more
+    /// bytes can be appended to it.
+    can_merge: bool,
+}
+
+impl PipeBuffer {
+    /// Creates a buffer that refers to the range of a shared frame.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the range is out of the frame.
+    pub fn from_frame(frame: UFrame, range: Range<usize>) -> Self {
+        assert!(range.start <= range.end && range.end <= frame.size());
+
+        Self {
+            frame,
+            offset: range.start,
+            len: range.len(),
+            can_merge: false,
+        }
+    }
+
+    /// Allocates a buffer and fills it with `fill`.
+    ///
+    /// The writer passed to `fill` has at most `max_len` bytes of space,
+    /// which is no more than [`PAGE_SIZE`]. `fill` returns the number of
+    /// bytes that it writes.
+    pub fn alloc_with<F>(max_len: usize, fill: F) -> Result<Self>
+    where
+        F: FnOnce(VmWriter<'_, Infallible>) -> Result<usize>,
+    {
+        let frame: UFrame = FrameAllocOptions::new().zeroed(false).alloc_frame()?.into();
+
+        let mut writer = frame.writer();
+        writer.limit(max_len);
+        let len = fill(writer)?;
+
+        Ok(Self {
+            frame,
+            offset: 0,
+            len,
+            can_merge: true,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reader of the bytes in the buffer.
+    pub(super) fn reader(&self) -> VmReader<'_, Infallible> {
+        let mut reader = self.frame.reader();
+        reader.skip(self.offset).limit(self.len);
+        reader
+    }
+
+    /// Appends at most `max_len` bytes from `reader` to the buffer.
+    ///
+    /// Nothing is appended if the frame is shared or full.
+    pub(super) fn append(&mut self, reader: &mut dyn MultiRead, max_len: usize) -> Result<usize> {
+        if !self.can_merge {
+            return Ok(0);
+        }
+
+        let mut writer = self.frame.writer();
+        writer.skip(self.offset + self.len).limit(max_len);
+        let len = reader.read(&mut writer)?;
+
+        self.len += len;
+        Ok(len)
+    }
+
+    /// Consumes the first `nbytes` bytes of the buffer.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `nbytes` is greater than the length.
+    pub(super) fn advance(&mut self, nbytes: usize) {
+        assert!(nbytes <= self.len);
+
+        self.offset += nbytes;
+        self.len -= nbytes;
+    }
+
+    /// Returns a buffer that refers to the first `nbytes` bytes of this
+    /// buffer, without copying the bytes.
+    ///
+    /// Since the frame becomes shared, no more bytes can be appended to
+    /// either of the buffers.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `nbytes` is greater than the length.
+    pub(super) fn share(&mut self, nbytes: usize) -> Self {
+        assert!(nbytes <= self.len);
+
+        self.can_merge = false;
+        Self {
+            frame: self.frame.clone(),
+            offset: self.offset,
+            len: nbytes,
+            can_merge: false,
+        }
+    }
+}
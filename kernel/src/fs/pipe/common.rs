@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use ostd::mm::Infallible;
+
+use super::PipeBuffer;
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::signal::{PollHandle, Pollee},
+    util::{MultiRead, MultiWrite},
+};
+
+/// Maximum number of bytes guaranteed to be written to a pipe atomically.
+///
+/// If the number of bytes to be written is less than the threshold, the write must be atomic.
+/// A non-blocking atomic write may fail with `EAGAIN`, even if there is room for a partial write.
+/// In other words, a partial write is not allowed for an atomic write.
+///
+/// For more details, see the description of `PIPE_BUF` in
+/// <https://man7.org/linux/man-pages/man7/pipe.7.html>.
+#[cfg(not(ktest))]
+const PIPE_BUF: usize = 4096;
+#[cfg(ktest)]
+const PIPE_BUF: usize = 2;
+
+/// The states shared by the two ends of a pipe.
+///
+/// The bytes in the pipe are kept in a queue of [`PipeBuffer`]s, so the
+/// frames can be moved between pipes or shared with the page cache without
+/// copying the bytes.
+pub(super) struct Common {
+    buffers: Mutex<Buffers>,
+    capacity: usize,
+    reader_pollee: Pollee,
+    writer_pollee: Pollee,
+    is_shutdown: AtomicBool,
+}
+
+struct Buffers {
+    queue: VecDeque<PipeBuffer>,
+    /// Total number of bytes in the queue
+    len: usize,
+}
+
+impl Common {
+    /// Creates the states of a pipe with the given capacity.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the given capacity is zero.
+    pub(super) fn new(capacity: usize) -> Arc<Self> {
+        assert!(capacity > 0);
+
+        Arc::new(Self {
+            buffers: Mutex::new(Buffers {
+                queue: VecDeque::new(),
+                len: 0,
+            }),
+            capacity,
+            reader_pollee: Pollee::new(),
+            writer_pollee: Pollee::new(),
+            is_shutdown: AtomicBool::new(false),
+        })
+    }
+
+    pub(super) fn is_shutdown(&self) -> bool {
+        self.is_shutdown.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn shutdown(&self) {
+        if self.is_shutdown.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        // The POLLHUP event indicates that the write end is shut down.
+        self.reader_pollee.notify(IoEvents::HUP);
+
+        // The POLLERR event indicates that the read end is shut down (so any subsequent writes
+        // will fail with an `EPIPE` error).
+        self.writer_pollee.notify(IoEvents::ERR | IoEvents::OUT);
+    }
+
+    pub(super) fn poll_reader(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.reader_pollee
+            .poll_with(mask, poller, || self.check_reader_events())
+    }
+
+    pub(super) fn poll_writer(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.writer_pollee
+            .poll_with(mask, poller, || self.check_writer_events())
+    }
+
+    fn check_reader_events(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+        if self.is_shutdown() {
+            events |= IoEvents::HUP;
+        }
+        if self.buffers.lock().len > 0 {
+            events |= IoEvents::IN;
+        }
+        events
+    }
+
+    fn check_writer_events(&self) -> IoEvents {
+        if self.is_shutdown() {
+            IoEvents::ERR | IoEvents::OUT
+        } else if self.capacity - self.buffers.lock().len > PIPE_BUF {
+            IoEvents::OUT
+        } else {
+            IoEvents::empty()
+        }
+    }
+
+    /// Tries to read the pipe to `writer`.
+    ///
+    /// - Returns `Ok(_)` with the number of bytes read if successful.
+    /// - Returns `Ok(0)` if the pipe is shut down and there is no data left.
+    /// - Returns `Err(EAGAIN)` if the pipe is empty.
+    pub(super) fn try_read(&self, writer: &mut dyn MultiWrite) -> Result<usize> {
+        if writer.is_empty() {
+            return Ok(0);
+        }
+
+        self.try_consume(writer.sum_lens(), |reader| writer.write(reader))
+    }
+
+    /// Tries to consume at most `max_len` bytes of the pipe with `consume`.
+    ///
+    /// `consume` is called with the readers of the buffers in order, and
+    /// returns the number of bytes that it consumes. Consuming stops at the
+    /// first reader that is not fully consumed.
+    ///
+    /// The return values are the same as those of [`Self::try_read`].
+    pub(super) fn try_consume<F>(&self, max_len: usize, mut consume: F) -> Result<usize>
+    where
+        F: FnMut(&mut VmReader<'_, Infallible>) -> Result<usize>,
+    {
+        // This must be recorded before the actual operation to avoid race conditions.
+        let is_shutdown = self.is_shutdown();
+
+        let mut buffers = self.buffers.lock();
+        let mut read_len = 0;
+        let result = loop {
+            if read_len == max_len {
+                break Ok(());
+            }
+            let Some(buffer) = buffers.queue.front_mut() else {
+                break Ok(());
+            };
+
+            let mut reader = buffer.reader();
+            reader.limit(max_len - read_len);
+            let reader_len = reader.remain();
+            let len = match consume(&mut reader) {
+                Ok(len) => len.min(reader_len),
+                Err(err) => break Err(err),
+            };
+
+            buffer.advance(len);
+            if buffer.is_empty() {
+                buffers.queue.pop_front();
+            }
+            buffers.len -= len;
+            read_len += len;
+
+            if len < reader_len {
+                break Ok(());
+            }
+        };
+        drop(buffers);
+
+        if read_len > 0 {
+            self.writer_pollee.notify(IoEvents::OUT);
+            self.reader_pollee.invalidate();
+        }
+
+        match result {
+            Err(err) if read_len == 0 => Err(err),
+            _ if read_len > 0 => Ok(read_len),
+            _ if is_shutdown => Ok(0),
+            _ => return_errno_with_message!(Errno::EAGAIN, "the pipe is empty"),
+        }
+    }
+
+    /// Tries to write `reader` to the pipe.
+    ///
+    /// - Returns `Ok(_)` with the number of bytes written if successful.
+    /// - Returns `Err(EPIPE)` if the pipe is shut down.
+    /// - Returns `Err(EAGAIN)` if the pipe is full.
+    pub(super) fn try_write(&self, reader: &mut dyn MultiRead) -> Result<usize> {
+        if reader.is_empty() {
+            // Even after shutdown, writing an empty buffer is still fine.
+            return Ok(0);
+        }
+
+        if self.is_shutdown() {
+            return_errno_with_message!(Errno::EPIPE, "the pipe is shut down");
+        }
+
+        let mut buffers = self.buffers.lock();
+        let free_len = self.capacity - buffers.len;
+        let sum_lens = reader.sum_lens();
+        if free_len < sum_lens && sum_lens <= PIPE_BUF {
+            // No sufficient space for an atomic write
+            return_errno_with_message!(Errno::EAGAIN, "the pipe is full");
+        }
+        let write_len = sum_lens.min(free_len);
+
+        let mut written_len = 0;
+        let result = (|| -> Result<()> {
+            // Append the bytes to the last buffer first to avoid wasting frames
+            // on small writes.
+            if let Some(last) = buffers.queue.back_mut() {
+                let len = last.append(reader, write_len)?;
+                written_len += len;
+                buffers.len += len;
+            }
+
+            while written_len < write_len {
+                let max_len = (write_len - written_len).min(PAGE_SIZE);
+                let buffer =
+                    PipeBuffer::alloc_with(max_len, |mut writer| reader.read(&mut writer))?;
+                if buffer.is_empty() {
+                    break;
+                }
+
+                written_len += buffer.len();
+                buffers.len += buffer.len();
+                buffers.queue.push_back(buffer);
+            }
+
+            Ok(())
+        })();
+        drop(buffers);
+
+        if written_len > 0 {
+            self.reader_pollee.notify(IoEvents::IN);
+        }
+
+        match result {
+            Err(err) if written_len == 0 => Err(err),
+            _ if written_len > 0 => Ok(written_len),
+            _ => return_errno_with_message!(Errno::EAGAIN, "the pipe is full"),
+        }
+    }
+
+    /// Tries to push the buffers returned by `next_buffer` to the pipe, until
+    /// `max_len` bytes are pushed, the pipe is full, or `next_buffer` returns
+    /// `None`.
+    ///
+    /// `next_buffer` is given the maximum number of bytes that the next
+    /// buffer can hold.
+    ///
+    /// The return values are the same as those of [`Self::try_write`].
+    pub(super) fn try_push_with<F>(&self, max_len: usize, mut next_buffer: F) -> Result<usize>
+    where
+        F: FnMut(usize) -> Result<Option<PipeBuffer>>,
+    {
+        if max_len == 0 {
+            return Ok(0);
+        }
+
+        if self.is_shutdown() {
+            return_errno_with_message!(Errno::EPIPE, "the pipe is shut down");
+        }
+
+        let mut buffers = self.buffers.lock();
+        if buffers.len == self.capacity {
+            return_errno_with_message!(Errno::EAGAIN, "the pipe is full");
+        }
+
+        let mut pushed_len = 0;
+        let result = loop {
+            let free_len = (self.capacity - buffers.len).min(max_len - pushed_len);
+            if free_len == 0 {
+                break Ok(());
+            }
+
+            let mut buffer = match next_buffer(free_len) {
+                Ok(Some(buffer)) => buffer,
+                Ok(None) => break Ok(()),
+                Err(err) => break Err(err),
+            };
+            if buffer.len() > free_len {
+                buffer = buffer.share(free_len);
+            }
+            if buffer.is_empty() {
+                break Ok(());
+            }
+
+            pushed_len += buffer.len();
+            buffers.len += buffer.len();
+            buffers.queue.push_back(buffer);
+        };
+        drop(buffers);
+
+        if pushed_len > 0 {
+            self.reader_pollee.notify(IoEvents::IN);
+        }
+
+        match result {
+            Err(err) if pushed_len == 0 => Err(err),
+            _ => Ok(pushed_len),
+        }
+    }
+
+    /// Tries to move at most `max_len` bytes from this pipe to `other`.
+    ///
+    /// If `is_tee` is true, the bytes are duplicated without being consumed
+    /// from this pipe. In either case, the frames are shared instead of
+    /// copied.
+    ///
+    /// - Returns `Ok(_)` with the number of bytes moved if successful.
+    /// - Returns `Ok(0)` if this pipe is shut down and there is no data left.
+    /// - Returns `Err(EPIPE)` if `other` is shut down.
+    /// - Returns `Err(EAGAIN)` if this pipe is empty or `other` is full.
+    pub(super) fn try_move_to(
+        &self,
+        other: &Common,
+        max_len: usize,
+        is_tee: bool,
+    ) -> Result<usize> {
+        debug_assert!(!core::ptr::eq(self, other));
+
+        if max_len == 0 {
+            return Ok(0);
+        }
+
+        // This must be recorded before the actual operation to avoid race conditions.
+        let is_shutdown = self.is_shutdown();
+
+        if other.is_shutdown() {
+            return_errno_with_message!(Errno::EPIPE, "the pipe is shut down");
+        }
+
+        // Lock the two pipes in the order of their addresses to avoid deadlocks.
+        let (mut src, mut dst) = if (self as *const Self) < (other as *const Self) {
+            let src = self.buffers.lock();
+            (src, other.buffers.lock())
+        } else {
+            let dst = other.buffers.lock();
+            (self.buffers.lock(), dst)
+        };
+
+        if src.len == 0 {
+            if is_shutdown {
+                return Ok(0);
+            }
+            return_errno_with_message!(Errno::EAGAIN, "the pipe is empty");
+        }
+        if dst.len == other.capacity {
+            return_errno_with_message!(Errno::EAGAIN, "the pipe is full");
+        }
+
+        let max_len = max_len.min(src.len).min(other.capacity - dst.len);
+        let mut moved_len = 0;
+        let mut index = 0;
+        while moved_len < max_len {
+            let src_buffer = if is_tee {
+                &mut src.queue[index]
+            } else {
+                src.queue.front_mut().unwrap()
+            };
+
+            let len = src_buffer.len().min(max_len - moved_len);
+            let buffer = if is_tee || len < src_buffer.len() {
+                let buffer = src_buffer.share(len);
+                if !is_tee {
+                    src_buffer.advance(len);
+                }
+                buffer
+            } else {
+                src.queue.pop_front().unwrap()
+            };
+            index += 1;
+
+            moved_len += len;
+            dst.len += len;
+            dst.queue.push_back(buffer);
+        }
+        if !is_tee {
+            src.len -= moved_len;
+        }
+        drop(src);
+        drop(dst);
+
+        if !is_tee {
+            self.writer_pollee.notify(IoEvents::OUT);
+            self.reader_pollee.invalidate();
+        }
+        other.reader_pollee.notify(IoEvents::IN);
+
+        Ok(moved_len)
+    }
+
+    /// Checks whether there is data to read, or the pipe is shut down.
+    ///
+    /// Returns `Err(EAGAIN)` if neither is the case.
+    pub(super) fn check_readable(&self) -> Result<()> {
+        if self.buffers.lock().len == 0 && !self.is_shutdown() {
+            return_errno_with_message!(Errno::EAGAIN, "the pipe is empty");
+        }
+        Ok(())
+    }
+
+    /// Checks whether there is space to write, or the pipe is shut down.
+    ///
+    /// Returns `Err(EAGAIN)` if neither is the case.
+    pub(super) fn check_writable(&self) -> Result<()> {
+        if self.buffers.lock().len == self.capacity && !self.is_shutdown() {
+            return_errno_with_message!(Errno::EAGAIN, "the pipe is full");
+        }
+        Ok(())
+    }
+}
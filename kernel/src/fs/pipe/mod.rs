@@ -1,10 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
 
+//! Pipes.
+//!
+//! The bytes in a pipe are kept in [`PipeBuffer`]s, each of which refers to a
+//! range of a frame. Besides the usual reads and writes, this allows `splice`
+//! to move pages of the page cache into a pipe, and `splice` and `tee` to
+//! move or duplicate the bytes between pipes, without copying the bytes.
+
 use core::sync::atomic::{AtomicU32, Ordering};
 
+use ostd::mm::Infallible;
+
+pub use self::buffer::PipeBuffer;
+use self::common::Common;
 use super::{
     file_handle::FileLike,
-    utils::{AccessMode, Channel, Consumer, InodeMode, InodeType, Metadata, Producer, StatusFlags},
+    utils::{AccessMode, InodeMode, InodeType, Metadata, StatusFlags},
 };
 use crate::{
     events::IoEvents,
@@ -14,58 +25,149 @@ use crate::{
         Gid, Uid,
     },
     time::clocks::RealTimeCoarseClock,
+    util::{MultiRead, MultiWrite},
 };
 
+mod buffer;
+mod common;
+
 const DEFAULT_PIPE_BUF_SIZE: usize = 65536;
 
 pub fn new_pair() -> Result<(Arc<PipeReader>, Arc<PipeWriter>)> {
-    let (producer, consumer) = Channel::with_capacity(DEFAULT_PIPE_BUF_SIZE).split();
-
-    Ok((
-        PipeReader::new(consumer, StatusFlags::empty())?,
-        PipeWriter::new(producer, StatusFlags::empty())?,
-    ))
+    new_pair_with_capacity(DEFAULT_PIPE_BUF_SIZE)
 }
 
 pub fn new_pair_with_capacity(capacity: usize) -> Result<(Arc<PipeReader>, Arc<PipeWriter>)> {
-    let (producer, consumer) = Channel::with_capacity(capacity).split();
+    let common = Common::new(capacity);
 
     Ok((
-        PipeReader::new(consumer, StatusFlags::empty())?,
-        PipeWriter::new(producer, StatusFlags::empty())?,
+        PipeReader::new(common.clone(), StatusFlags::empty())?,
+        PipeWriter::new(common, StatusFlags::empty())?,
     ))
 }
 
 pub struct PipeReader {
-    consumer: Consumer<u8>,
+    common: Arc<Common>,
     status_flags: AtomicU32,
 }
 
 impl PipeReader {
-    pub fn new(consumer: Consumer<u8>, status_flags: StatusFlags) -> Result<Arc<Self>> {
+    fn new(common: Arc<Common>, status_flags: StatusFlags) -> Result<Arc<Self>> {
         check_status_flags(status_flags)?;
 
         Ok(Arc::new(Self {
-            consumer,
+            common,
             status_flags: AtomicU32::new(status_flags.bits()),
         }))
     }
+
+    /// Reads the pipe to `writer`, which may consist of multiple buffers.
+    ///
+    /// If the pipe is empty, this method waits for data unless
+    /// `is_nonblocking` is true.
+    pub fn read_vectored(
+        &self,
+        writer: &mut dyn MultiWrite,
+        is_nonblocking: bool,
+    ) -> Result<usize> {
+        if is_nonblocking {
+            self.common.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.common.try_read(writer))
+        }
+    }
+
+    /// Consumes at most `max_len` bytes of the pipe with `consume`, without
+    /// copying the bytes to an intermediate buffer.
+    ///
+    /// `consume` is called with the readers of the bytes in order, and
+    /// returns the number of bytes that it consumes. The bytes that are not
+    /// consumed are left in the pipe.
+    ///
+    /// If the pipe is empty, this method waits for data unless
+    /// `is_nonblocking` is true.
+    pub fn consume_with<F>(
+        &self,
+        max_len: usize,
+        is_nonblocking: bool,
+        mut consume: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&mut VmReader<'_, Infallible>) -> Result<usize>,
+    {
+        if is_nonblocking {
+            self.common.try_consume(max_len, &mut consume)
+        } else {
+            self.wait_events(IoEvents::IN, None, || {
+                self.common.try_consume(max_len, &mut consume)
+            })
+        }
+    }
+
+    /// Moves at most `max_len` bytes from this pipe to the pipe of `writer`.
+    ///
+    /// The bytes are moved by sharing the frames instead of copying them.
+    pub fn splice_to(
+        &self,
+        writer: &PipeWriter,
+        max_len: usize,
+        is_nonblocking: bool,
+    ) -> Result<usize> {
+        self.move_to(writer, max_len, is_nonblocking, false)
+    }
+
+    /// Duplicates at most `max_len` bytes from this pipe to the pipe of
+    /// `writer`, without consuming them from this pipe.
+    ///
+    /// The bytes are duplicated by sharing the frames instead of copying them.
+    pub fn tee_to(
+        &self,
+        writer: &PipeWriter,
+        max_len: usize,
+        is_nonblocking: bool,
+    ) -> Result<usize> {
+        self.move_to(writer, max_len, is_nonblocking, true)
+    }
+
+    fn move_to(
+        &self,
+        writer: &PipeWriter,
+        max_len: usize,
+        is_nonblocking: bool,
+        is_tee: bool,
+    ) -> Result<usize> {
+        if Arc::ptr_eq(&self.common, &writer.common) {
+            return_errno_with_message!(Errno::EINVAL, "the two ends belong to the same pipe");
+        }
+
+        let try_move = || self.common.try_move_to(&writer.common, max_len, is_tee);
+        if is_nonblocking {
+            return try_move();
+        }
+
+        loop {
+            self.wait_events(IoEvents::IN, None, || self.common.check_readable())?;
+            writer.wait_events(IoEvents::OUT, None, || writer.common.check_writable())?;
+
+            match try_move() {
+                // Another thread may have raced with us.
+                Err(err) if err.error() == Errno::EAGAIN => continue,
+                result => return result,
+            }
+        }
+    }
 }
 
 impl Pollable for PipeReader {
     fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
-        self.consumer.poll(mask, poller)
+        self.common.poll_reader(mask, poller)
     }
 }
 
 impl FileLike for PipeReader {
     fn read(&self, writer: &mut VmWriter) -> Result<usize> {
-        let read_len = if self.status_flags().contains(StatusFlags::O_NONBLOCK) {
-            self.consumer.try_read(writer)?
-        } else {
-            self.wait_events(IoEvents::IN, None, || self.consumer.try_read(writer))?
-        };
-        Ok(read_len)
+        let is_nonblocking = self.status_flags().contains(StatusFlags::O_NONBLOCK);
+        self.read_vectored(writer, is_nonblocking)
     }
 
     fn status_flags(&self) -> StatusFlags {
@@ -106,35 +208,82 @@ impl FileLike for PipeReader {
     }
 }
 
+impl Drop for PipeReader {
+    fn drop(&mut self) {
+        self.common.shutdown();
+    }
+}
+
 pub struct PipeWriter {
-    producer: Producer<u8>,
+    common: Arc<Common>,
     status_flags: AtomicU32,
 }
 
 impl PipeWriter {
-    pub fn new(producer: Producer<u8>, status_flags: StatusFlags) -> Result<Arc<Self>> {
+    fn new(common: Arc<Common>, status_flags: StatusFlags) -> Result<Arc<Self>> {
         check_status_flags(status_flags)?;
 
         Ok(Arc::new(Self {
-            producer,
+            common,
             status_flags: AtomicU32::new(status_flags.bits()),
         }))
     }
+
+    /// Writes `reader`, which may consist of multiple buffers, to the pipe.
+    ///
+    /// If the pipe is full, this method waits for space unless
+    /// `is_nonblocking` is true.
+    pub fn write_vectored(
+        &self,
+        reader: &mut dyn MultiRead,
+        is_nonblocking: bool,
+    ) -> Result<usize> {
+        if is_nonblocking {
+            self.common.try_write(reader)
+        } else {
+            self.wait_events(IoEvents::OUT, None, || self.common.try_write(reader))
+        }
+    }
+
+    /// Pushes the buffers returned by `next_buffer` to the pipe, until
+    /// `max_len` bytes are pushed, the pipe is full, or `next_buffer` returns
+    /// `None`.
+    ///
+    /// `next_buffer` is given the maximum number of bytes that the next
+    /// buffer can hold. The buffers are pushed as they are, so they can refer
+    /// to frames shared with others (e.g., pages in the page cache).
+    ///
+    /// If the pipe is full, this method waits for space unless
+    /// `is_nonblocking` is true.
+    pub fn push_with<F>(
+        &self,
+        max_len: usize,
+        is_nonblocking: bool,
+        mut next_buffer: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(usize) -> Result<Option<PipeBuffer>>,
+    {
+        if is_nonblocking {
+            self.common.try_push_with(max_len, &mut next_buffer)
+        } else {
+            self.wait_events(IoEvents::OUT, None, || {
+                self.common.try_push_with(max_len, &mut next_buffer)
+            })
+        }
+    }
 }
 
 impl Pollable for PipeWriter {
     fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
-        self.producer.poll(mask, poller)
+        self.common.poll_writer(mask, poller)
     }
 }
 
 impl FileLike for PipeWriter {
     fn write(&self, reader: &mut VmReader) -> Result<usize> {
-        if self.status_flags().contains(StatusFlags::O_NONBLOCK) {
-            self.producer.try_write(reader)
-        } else {
-            self.wait_events(IoEvents::OUT, None, || self.producer.try_write(reader))
-        }
+        let is_nonblocking = self.status_flags().contains(StatusFlags::O_NONBLOCK);
+        self.write_vectored(reader, is_nonblocking)
     }
 
     fn status_flags(&self) -> StatusFlags {
@@ -175,6 +324,12 @@ impl FileLike for PipeWriter {
     }
 }
 
+impl Drop for PipeWriter {
+    fn drop(&mut self) {
+        self.common.shutdown();
+    }
+}
+
 fn check_status_flags(status_flags: StatusFlags) -> Result<()> {
     if status_flags.contains(StatusFlags::O_DIRECT) {
         // "O_DIRECT .. Older kernels that do not support this flag will indicate this via an
@@ -198,10 +353,7 @@ mod test {
     use ostd::prelude::*;
 
     use super::*;
-    use crate::{
-        fs::utils::Channel,
-        thread::{kernel_thread::ThreadOptions, Thread},
-    };
+    use crate::thread::{kernel_thread::ThreadOptions, Thread};
 
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     enum Ordering {
@@ -214,11 +366,7 @@ mod test {
         W: FnOnce(Arc<PipeWriter>) + Send + 'static,
         R: FnOnce(Arc<PipeReader>) + Send + 'static,
     {
-        let channel = Channel::with_capacity(2);
-        let (writer, readr) = channel.split();
-
-        let writer = PipeWriter::new(writer, StatusFlags::empty()).unwrap();
-        let reader = PipeReader::new(readr, StatusFlags::empty()).unwrap();
+        let (reader, writer) = new_pair_with_capacity(2).unwrap();
 
         let signal_writer = Arc::new(AtomicBool::new(false));
         let signal_reader = signal_writer.clone();
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/devices` file support, which tells the user space
+//! about the registered major device numbers of character and block devices.
+//!
+//! Reference: <https://www.man7.org/linux/man-pages/man5/proc_devices.5.html>
+
+use alloc::format;
+
+use crate::{
+    device::registered_majors,
+    fs::{
+        device::DeviceType,
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/devices`.
+pub struct DevicesFileOps;
+
+impl DevicesFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for DevicesFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut result = String::from("Character devices:\n");
+        for (major, name) in registered_majors(DeviceType::CharDevice) {
+            result.push_str(&format!("{:3} {}\n", major, name));
+        }
+
+        result.push_str("\nBlock devices:\n");
+        for (major, name) in registered_majors(DeviceType::BlockDevice) {
+            result.push_str(&format!("{:3} {}\n", major, name));
+        }
+
+        Ok(result.into_bytes())
+    }
+}
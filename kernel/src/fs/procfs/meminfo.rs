@@ -14,6 +14,7 @@ use crate::{
         utils::Inode,
     },
     prelude::*,
+    vm::swap,
 };
 
 /// Represents the inode at `/proc/meminfo`.
@@ -33,13 +34,19 @@ impl FileOps for MemInfoFileOps {
         // applications, without disk operations.
         let available = osdk_frame_allocator::load_total_free_size();
 
+        // The total amount of swap space and the amount of unused swap space.
+        let swap_total = swap::total_swap_size();
+        let swap_free = swap::free_swap_size();
+
         // Convert the values to KiB.
         let total = total / 1024;
         let available = available / 1024;
         let free = total - available;
+        let swap_total = swap_total / 1024;
+        let swap_free = swap_free / 1024;
         let output = format!(
-            "MemTotal:\t{} kB\nMemFree:\t{} kB\nMemAvailable:\t{} kB\n",
-            total, free, available
+            "MemTotal:\t{} kB\nMemFree:\t{} kB\nMemAvailable:\t{} kB\nSwapTotal:\t{} kB\nSwapFree:\t{} kB\n",
+            total, free, available, swap_total, swap_free
         );
         Ok(output.into_bytes())
     }
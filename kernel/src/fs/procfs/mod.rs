@@ -6,13 +6,16 @@ use filesystems::{FileSystemType, FILESYSTEM_TYPES};
 
 use self::{
     cpuinfo::CpuInfoFileOps,
+    devices::DevicesFileOps,
     loadavg::LoadAvgFileOps,
     meminfo::MemInfoFileOps,
     pid::PidDirOps,
     self_::SelfSymOps,
     sys::SysDirOps,
+    sysvipc::SysVIpcDirOps,
     template::{DirOps, ProcDir, ProcDirBuilder, ProcSymBuilder, SymOps},
     thread_self::ThreadSelfSymOps,
+    vmstat::VmStatFileOps,
 };
 use crate::{
     events::Observer,
@@ -28,14 +31,19 @@ use crate::{
 };
 
 mod cpuinfo;
+mod devices;
 mod filesystems;
 mod loadavg;
 mod meminfo;
 mod pid;
 mod self_;
 mod sys;
+mod sysvipc;
 mod template;
 mod thread_self;
+mod vmstat;
+
+pub use pid::namespace_of_inode;
 
 pub(super) fn init() {
     FILESYSTEM_TYPES.call_once(|| {
@@ -43,7 +51,10 @@ pub(super) fn init() {
             FileSystemType::new("proc", true),
             FileSystemType::new("ramfs", true),
             FileSystemType::new("devpts", true),
+            FileSystemType::new("mqueue", true),
+            FileSystemType::new("cgroup2", true),
             FileSystemType::new("ext2", false),
+            FileSystemType::new("ext3", false),
             FileSystemType::new("exfat", false),
         ]
     });
@@ -124,8 +135,12 @@ impl DirOps for RootDirOps {
             SelfSymOps::new_inode(this_ptr.clone())
         } else if name == "sys" {
             SysDirOps::new_inode(this_ptr.clone())
+        } else if name == "sysvipc" {
+            SysVIpcDirOps::new_inode(this_ptr.clone())
         } else if name == "thread-self" {
             ThreadSelfSymOps::new_inode(this_ptr.clone())
+        } else if name == "devices" {
+            DevicesFileOps::new_inode(this_ptr.clone())
         } else if name == "filesystems" {
             FileSystemsFileOps::new_inode(this_ptr.clone())
         } else if name == "meminfo" {
@@ -134,6 +149,8 @@ impl DirOps for RootDirOps {
             LoadAvgFileOps::new_inode(this_ptr.clone())
         } else if name == "cpuinfo" {
             CpuInfoFileOps::new_inode(this_ptr.clone())
+        } else if name == "vmstat" {
+            VmStatFileOps::new_inode(this_ptr.clone())
         } else if let Ok(pid) = name.parse::<Pid>() {
             let process_ref =
                 process_table::get_process(pid).ok_or_else(|| Error::new(Errno::ENOENT))?;
@@ -155,6 +172,10 @@ impl DirOps for RootDirOps {
             ThreadSelfSymOps::new_inode(this_ptr.clone())
         });
         cached_children.put_entry_if_not_found("sys", || SysDirOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("sysvipc", || SysVIpcDirOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("devices", || DevicesFileOps::new_inode(this_ptr.clone()));
         cached_children.put_entry_if_not_found("filesystems", || {
             FileSystemsFileOps::new_inode(this_ptr.clone())
         });
@@ -164,6 +185,8 @@ impl DirOps for RootDirOps {
             .put_entry_if_not_found("loadavg", || LoadAvgFileOps::new_inode(this_ptr.clone()));
         cached_children
             .put_entry_if_not_found("cpuinfo", || CpuInfoFileOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("vmstat", || VmStatFileOps::new_inode(this_ptr.clone()));
         for process in process_table::process_table_mut().iter() {
             let pid = process.pid().to_string();
             cached_children.put_entry_if_not_found(&pid, || {
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/cgroup`.
+///
+/// The file shows the cgroup of the process. Since only the unified (version
+/// 2) hierarchy is supported, the file always has a single line, whose
+/// hierarchy ID is 0 and whose controller list is empty.
+///
+/// Reference: <https://man7.org/linux/man-pages/man7/cgroups.7.html>
+pub struct CgroupFileOps(Arc<Process>);
+
+impl CgroupFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CgroupFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("0::{}\n", self.0.cgroup().path()).into_bytes())
+    }
+}
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
+pub use self::ns::namespace_of_inode;
 use self::{
-    cmdline::CmdlineFileOps, comm::CommFileOps, exe::ExeSymOps, fd::FdDirOps, task::TaskDirOps,
+    cgroup::CgroupFileOps, cmdline::CmdlineFileOps, comm::CommFileOps, exe::ExeSymOps,
+    fd::FdDirOps, ns::NsDirOps, oom_score::OomScoreFileOps, oom_score_adj::OomScoreAdjFileOps,
+    task::TaskDirOps,
 };
 use super::template::{DirOps, ProcDir, ProcDirBuilder};
 use crate::{
@@ -14,10 +17,14 @@ use crate::{
     process::{posix_thread::AsPosixThread, Process},
 };
 
+mod cgroup;
 mod cmdline;
 mod comm;
 mod exe;
 mod fd;
+mod ns;
+mod oom_score;
+mod oom_score_adj;
 mod stat;
 mod status;
 mod task;
@@ -60,9 +67,13 @@ impl DirOps for PidDirOps {
     fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
         let inode = match name {
             "exe" => ExeSymOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "cgroup" => CgroupFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "comm" => CommFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "fd" => FdDirOps::new_inode(self.0.clone(), this_ptr.clone()),
             "cmdline" => CmdlineFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "ns" => NsDirOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "oom_score" => OomScoreFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "oom_score_adj" => OomScoreAdjFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "status" => status::StatusFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "stat" => stat::StatFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "task" => TaskDirOps::new_inode(self.0.clone(), this_ptr.clone()),
@@ -80,6 +91,9 @@ impl DirOps for PidDirOps {
         cached_children.put_entry_if_not_found("exe", || {
             ExeSymOps::new_inode(self.0.clone(), this_ptr.clone())
         });
+        cached_children.put_entry_if_not_found("cgroup", || {
+            CgroupFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
         cached_children.put_entry_if_not_found("comm", || {
             CommFileOps::new_inode(self.0.clone(), this_ptr.clone())
         });
@@ -89,6 +103,15 @@ impl DirOps for PidDirOps {
         cached_children.put_entry_if_not_found("cmdline", || {
             CmdlineFileOps::new_inode(self.0.clone(), this_ptr.clone())
         });
+        cached_children.put_entry_if_not_found("ns", || {
+            NsDirOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("oom_score", || {
+            OomScoreFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("oom_score_adj", || {
+            OomScoreAdjFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
         cached_children.put_entry_if_not_found("status", || {
             status::StatusFileOps::new_inode(self.0.clone(), this_ptr.clone())
         });
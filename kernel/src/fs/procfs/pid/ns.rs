@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/[pid]/ns` support, which contains a file for each namespace of
+//! the process. A file can be passed to `setns` to enter the namespace.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/namespaces.7.html>
+
+use crate::{
+    fs::{
+        procfs::template::{DirOps, FileOps, ProcDir, ProcDirBuilder, ProcFile, ProcFileBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+    process::{
+        namespace::{Namespace, NsType},
+        posix_thread::AsPosixThread,
+        Process,
+    },
+};
+
+/// Represents the inode at `/proc/[pid]/ns`.
+pub struct NsDirOps(Arc<Process>);
+
+impl NsDirOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+
+    fn namespace(&self, name: &str) -> Option<Namespace> {
+        let ns_proxy = {
+            let main_thread = self.0.main_thread();
+            main_thread.as_posix_thread().unwrap().ns_proxy()
+        };
+
+        let ns = match name {
+            "ipc" => ns_proxy.get(NsType::Ipc),
+            "mnt" => ns_proxy.get(NsType::Mnt),
+            "net" => ns_proxy.get(NsType::Net),
+            "pid" => Namespace::Pid(self.0.pid_ns().clone()),
+            "pid_for_children" => ns_proxy.get(NsType::Pid),
+            "uts" => ns_proxy.get(NsType::Uts),
+            _ => return None,
+        };
+        Some(ns)
+    }
+}
+
+const NS_FILE_NAMES: [&str; 6] = ["ipc", "mnt", "net", "pid", "pid_for_children", "uts"];
+
+impl DirOps for NsDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let Some(ns) = self.namespace(name) else {
+            return_errno!(Errno::ENOENT);
+        };
+        Ok(NsFileOps::new_inode(ns, this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<NsDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for name in NS_FILE_NAMES {
+            cached_children.put_entry_if_not_found(name, || {
+                NsFileOps::new_inode(self.namespace(name).unwrap(), this_ptr.clone())
+            });
+        }
+    }
+}
+
+/// Represents the inode at `/proc/[pid]/ns/[type]`.
+///
+/// FIXME: In Linux, the namespace files are symbolic links to the files of the `nsfs` file
+/// system, which show the types and the IDs of the namespaces when being read by `readlink`.
+pub struct NsFileOps(Namespace);
+
+impl NsFileOps {
+    pub fn new_inode(ns: Namespace, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let ino = ns.id();
+        ProcFileBuilder::new(Self(ns))
+            .parent(parent)
+            .ino(ino)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for NsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        return_errno_with_message!(Errno::EINVAL, "namespace files cannot be read");
+    }
+}
+
+/// Returns the namespace of the inode if the inode is a namespace file in `/proc/[pid]/ns`.
+pub fn namespace_of_inode(inode: &dyn Inode) -> Option<Namespace> {
+    let ns_file = inode.downcast_ref::<ProcFile<NsFileOps>>()?;
+    Some(ns_file.inner().0.clone())
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet, posix_thread::AsPosixThread, OOM_SCORE_ADJ_MAX,
+        OOM_SCORE_ADJ_MIN,
+    },
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/oom_score_adj`.
+///
+/// Reading the file shows the OOM score adjustment of the process, and writing
+/// to the file sets it. Like Linux, lowering the adjustment requires
+/// `CAP_SYS_RESOURCE`.
+///
+/// Reference: <https://man7.org/linux/man-pages/man5/proc_pid_oom_score_adj.5.html>
+pub struct OomScoreAdjFileOps(Arc<Process>);
+
+impl OomScoreAdjFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for OomScoreAdjFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.0.oom_score_adj()).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let buf = reader.collect()?;
+        let oom_score_adj = core::str::from_utf8(&buf)
+            .ok()
+            .and_then(|str| str.trim().parse::<i16>().ok())
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the value is not a number"))?;
+        if !(OOM_SCORE_ADJ_MIN..=OOM_SCORE_ADJ_MAX).contains(&oom_score_adj) {
+            return_errno_with_message!(Errno::EINVAL, "the value is out of range");
+        }
+
+        if oom_score_adj < self.0.oom_score_adj() {
+            let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+            if !credentials
+                .effective_capset()
+                .contains(CapSet::SYS_RESOURCE)
+            {
+                return_errno_with_message!(
+                    Errno::EACCES,
+                    "lowering the OOM score adjustment requires CAP_SYS_RESOURCE"
+                );
+            }
+        }
+
+        self.0.set_oom_score_adj(oom_score_adj);
+        Ok(buf.len())
+    }
+}
@@ -51,6 +51,7 @@ use crate::{
 /// - CapEff: Effective capabilities.
 /// - CapBnd: Bounding set.
 /// - CapAmb: Ambient capabilities.
+/// - NoNewPrivs: Whether the `no_new_privs` attribute is set.
 /// - Seccomp: Seccomp mode.
 /// - Cpus_allowed: CPUs allowed for this process.
 /// - Cpus_allowed_list: List of CPUs allowed for this process.
@@ -73,7 +74,8 @@ impl FileOps for StatusFileOps {
     fn data(&self) -> Result<Vec<u8>> {
         let process = &self.0;
         let main_thread = process.main_thread();
-        let file_table = main_thread.as_posix_thread().unwrap().file_table();
+        let posix_thread = main_thread.as_posix_thread().unwrap();
+        let file_table = posix_thread.file_table();
 
         let mut status_output = String::new();
         writeln!(status_output, "Name:\t{}", process.executable_path()).unwrap();
@@ -97,6 +99,18 @@ impl FileOps for StatusFileOps {
             process.tasks().lock().as_slice().len()
         )
         .unwrap();
+        writeln!(
+            status_output,
+            "NoNewPrivs:\t{}",
+            posix_thread.no_new_privs() as u8
+        )
+        .unwrap();
+        writeln!(
+            status_output,
+            "Seccomp:\t{}",
+            posix_thread.seccomp().mode() as u8
+        )
+        .unwrap();
         Ok(status_output.into_bytes())
     }
 }
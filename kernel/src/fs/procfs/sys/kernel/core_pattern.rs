@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::{
+        coredump::{core_pattern, set_core_pattern},
+        credentials::capabilities::CapSet,
+        posix_thread::AsPosixThread,
+    },
+};
+
+/// Represents the inode at `/proc/sys/kernel/core_pattern`.
+///
+/// Reading the file shows the pattern to name core files, and writing to the
+/// file sets it, which requires `CAP_SYS_ADMIN`.
+///
+/// Reference: <https://man7.org/linux/man-pages/man5/core.5.html>
+pub struct CorePatternFileOps;
+
+impl CorePatternFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self)
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CorePatternFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", core_pattern()).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+        if !credentials.effective_capset().contains(CapSet::SYS_ADMIN) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "setting the core pattern requires CAP_SYS_ADMIN"
+            );
+        }
+
+        let buf = reader.collect()?;
+        let pattern = core::str::from_utf8(&buf)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the pattern is not valid UTF-8"))?;
+        set_core_pattern(pattern)?;
+        Ok(buf.len())
+    }
+}
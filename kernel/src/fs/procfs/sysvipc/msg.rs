@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/sysvipc/msg` file support, which lists the
+//! System V message queues in the system.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_sysvipc.5.html>
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    ipc::msg::system_v::msg_queue::msg_queues,
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+};
+
+/// Represents the inode at `/proc/sysvipc/msg`.
+pub struct MsgFileOps;
+
+impl MsgFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for MsgFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut output = String::from(
+            "       key      msqid perms      cbytes       qnum lspid lrpid   uid   gid  cuid  cgid      stime      rtime      ctime\n",
+        );
+
+        let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+        for queue in msg_queues(ns_proxy.ipc_ns()) {
+            let stat = queue.stat();
+            output.push_str(&format!(
+                "{:>10} {:>10}  {:>4o}  {:>10} {:>10} {:>5} {:>5} {:>5} {:>5} {:>5} {:>5} {:>10} {:>10} {:>10}\n",
+                stat.key,
+                queue.id(),
+                stat.mode,
+                stat.num_bytes,
+                stat.num_messages,
+                stat.lspid,
+                stat.lrpid,
+                u32::from(stat.uid),
+                u32::from(stat.gid),
+                u32::from(stat.cuid),
+                u32::from(stat.cgid),
+                stat.stime,
+                stat.rtime,
+                stat.ctime,
+            ));
+        }
+
+        Ok(output.into_bytes())
+    }
+}
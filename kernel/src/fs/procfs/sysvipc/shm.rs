@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/sysvipc/shm` file support, which lists the
+//! System V shared memory segments in the system.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_sysvipc.5.html>
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    ipc::shm::system_v::shm_segment::shm_segments,
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+};
+
+/// Represents the inode at `/proc/sysvipc/shm`.
+pub struct ShmFileOps;
+
+impl ShmFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for ShmFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut output = String::from(
+            "       key      shmid perms                  size  cpid  lpid nattch   uid   gid  cuid  cgid      atime      dtime      ctime                   rss                  swap\n",
+        );
+
+        let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+        for segment in shm_segments(ns_proxy.ipc_ns()) {
+            let stat = segment.stat();
+            // The pages of segments are always resident in memory.
+            let rss = stat.size.div_ceil(PAGE_SIZE) * PAGE_SIZE;
+            output.push_str(&format!(
+                "{:>10} {:>10}  {:>4o} {:>21} {:>5} {:>5}  {:>5} {:>5} {:>5} {:>5} {:>5} {:>10} {:>10} {:>10} {:>21} {:>21}\n",
+                stat.key,
+                segment.id(),
+                stat.mode,
+                stat.size,
+                stat.cpid,
+                stat.lpid,
+                stat.nattch,
+                u32::from(stat.uid),
+                u32::from(stat.gid),
+                u32::from(stat.cuid),
+                u32::from(stat.cgid),
+                stat.atime,
+                stat.dtime,
+                stat.ctime,
+                rss,
+                0,
+            ));
+        }
+
+        Ok(output.into_bytes())
+    }
+}
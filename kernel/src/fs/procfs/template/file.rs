@@ -17,15 +17,21 @@ pub struct ProcFile<F: FileOps> {
 }
 
 impl<F: FileOps> ProcFile<F> {
-    pub fn new(file: F, fs: Weak<dyn FileSystem>, is_volatile: bool) -> Arc<Self> {
+    pub fn new(
+        file: F,
+        fs: Weak<dyn FileSystem>,
+        ino: Option<u64>,
+        mode: InodeMode,
+        is_volatile: bool,
+    ) -> Arc<Self> {
         let common = {
-            let arc_fs = fs.upgrade().unwrap();
-            let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
-            let metadata = Metadata::new_file(
-                procfs.alloc_id(),
-                InodeMode::from_bits_truncate(0o444),
-                super::BLOCK_SIZE,
-            );
+            let ino = ino.unwrap_or_else(|| {
+                let arc_fs = fs.upgrade().unwrap();
+                let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
+                procfs.alloc_id()
+            });
+
+            let metadata = Metadata::new_file(ino, mode, super::BLOCK_SIZE);
             Common::new(metadata, fs, is_volatile)
         };
         Arc::new(Self {
@@ -33,6 +39,10 @@ impl<F: FileOps> ProcFile<F> {
             common,
         })
     }
+
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
 }
 
 #[inherit_methods(from = "self.common")]
@@ -55,6 +65,11 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
     fn fs(&self) -> Arc<dyn FileSystem>;
 
     fn resize(&self, _new_size: usize) -> Result<()> {
+        // Like Linux, truncating a writable file (e.g., when it is opened
+        // with `O_TRUNC`) does nothing.
+        if self.common.mode()?.is_owner_writable() {
+            return Ok(());
+        }
         Err(Error::new(Errno::EPERM))
     }
 
@@ -75,12 +90,12 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
         self.read_at(offset, writer)
     }
 
-    fn write_at(&self, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
-        Err(Error::new(Errno::EPERM))
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        self.inner.write_at(offset, reader)
     }
 
-    fn write_direct_at(&self, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
-        Err(Error::new(Errno::EPERM))
+    fn write_direct_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        self.write_at(offset, reader)
     }
 
     fn read_link(&self) -> Result<String> {
@@ -102,4 +117,14 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
 
 pub trait FileOps: Sync + Send {
     fn data(&self) -> Result<Vec<u8>>;
+
+    /// Writes the data from `reader` at `offset`.
+    ///
+    /// Only the files that are built with write permissions (see
+    /// [`ProcFileBuilder::mode`]) need to implement this method.
+    ///
+    /// [`ProcFileBuilder::mode`]: super::ProcFileBuilder::mode
+    fn write_at(&self, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
+        Err(Error::new(Errno::EPERM))
+    }
 }
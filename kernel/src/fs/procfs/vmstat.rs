@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/vmstat` file support, which tells the user space
+//! about the virtual memory statistics. Only a few of the fields in Linux are
+//! supported.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/mm/vmstat.c#L1168>
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    process::nr_oom_kills,
+};
+
+/// Represents the inode at `/proc/vmstat`.
+pub struct VmStatFileOps;
+
+impl VmStatFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for VmStatFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        // The number of processes that are killed by the OOM killer.
+        let oom_kill = nr_oom_kills();
+
+        let output = format!("oom_kill {}\n", oom_kill);
+        Ok(output.into_bytes())
+    }
+}
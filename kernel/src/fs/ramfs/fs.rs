@@ -15,7 +15,7 @@ use ostd::{
     sync::{PreemptDisabled, RwLockWriteGuard},
 };
 
-use super::{xattr::RamXattr, *};
+use super::{memfd::MemfdSeals, xattr::RamXattr, *};
 use crate::{
     events::IoEvents,
     fs::{
@@ -563,6 +563,13 @@ impl Inode for RamInode {
                 let write_len = reader.remain();
                 let new_size = offset + write_len;
                 let should_expand_size = new_size > file_size;
+
+                let seals = MemfdSeals::of(self);
+                let _seals_guard = seals
+                    .as_ref()
+                    .map(|seals| seals.lock_for_write(file_size, file_size.max(new_size)))
+                    .transpose()?;
+
                 let new_size_aligned = new_size.align_up(BLOCK_SIZE);
                 if should_expand_size {
                     page_cache.resize(new_size_aligned)?;
@@ -612,6 +619,12 @@ impl Inode for RamInode {
             return Ok(());
         }
 
+        let seals = MemfdSeals::of(self);
+        let _seals_guard = seals
+            .as_ref()
+            .map(|seals| seals.lock_for_resize(file_size, new_size))
+            .transpose()?;
+
         let page_cache = self.inner.as_file().unwrap();
         page_cache.resize(new_size)?;
 
@@ -1170,6 +1183,13 @@ impl Inode for RamInode {
                     return Ok(());
                 }
                 let range = offset..file_size.min(offset + len);
+
+                let seals = MemfdSeals::of(self);
+                let _seals_guard = seals
+                    .as_ref()
+                    .map(|seals| seals.lock_for_write(file_size, file_size))
+                    .transpose()?;
+
                 // TODO: Think of a more light-weight approach
                 self.inner.as_file().unwrap().fill_zeros(range)
             }
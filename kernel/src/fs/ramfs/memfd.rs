@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Anonymous memory-backed files created by `memfd_create`.
+//!
+//! A memfd file is a regular file of an internal RamFS that is unlinked right
+//! after creation, so it lives as long as there are references to it. Unlike
+//! other files, a memfd file supports file seals, which restrict the
+//! operations that can be performed on the file.
+
+use spin::Once;
+
+use super::RamFS;
+use crate::{
+    fs::{
+        inode_handle::InodeHandle,
+        path::{Dentry, MountNode},
+        utils::{AccessMode, Inode, InodeMode, InodeType, StatusFlags},
+    },
+    prelude::*,
+};
+
+/// The prefix of the names of memfd files.
+pub const MEMFD_NAME_PREFIX: &str = "memfd:";
+
+/// The maximum length of the name of a memfd file, excluding the prefix.
+pub const MAX_MEMFD_NAME_LEN: usize = super::NAME_MAX - MEMFD_NAME_PREFIX.len();
+
+/// The root directory of the internal RamFS that contains memfd files.
+static MEMFD_ROOT: Once<Mutex<Dentry>> = Once::new();
+
+/// Creates a new memfd file with the given name.
+///
+/// The returned file is opened for reading and writing. If `allow_sealing` is
+/// false, the file will be sealed with [`FileSeals::F_SEAL_SEAL`], so no more
+/// seals can be added to it.
+pub fn create_memfd(name: &str, allow_sealing: bool) -> Result<InodeHandle> {
+    if name.len() > MAX_MEMFD_NAME_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the memfd name is too long");
+    }
+
+    let root = MEMFD_ROOT.call_once(|| {
+        let root = Dentry::new_fs_root(MountNode::new_root(RamFS::new()));
+        root.inode()
+            .set_mode(InodeMode::from_bits_truncate(0o1777))
+            .unwrap();
+        Mutex::new(root)
+    });
+
+    let dentry = {
+        let root = root.lock();
+        let file_name = format!("{}{}", MEMFD_NAME_PREFIX, name);
+        let dentry = root.new_fs_child(
+            &file_name,
+            InodeType::File,
+            InodeMode::from_bits_truncate(0o777),
+        )?;
+        root.unlink(&file_name)?;
+        dentry
+    };
+
+    let initial_seals = if allow_sealing {
+        FileSeals::empty()
+    } else {
+        FileSeals::F_SEAL_SEAL
+    };
+    dentry
+        .inode()
+        .extension()
+        .unwrap()
+        .put(Arc::new(MemfdSeals::new(initial_seals)));
+
+    InodeHandle::new_unchecked_access(dentry, AccessMode::O_RDWR, StatusFlags::empty())
+}
+
+bitflags! {
+    /// The seals of a file.
+    pub struct FileSeals: u32 {
+        /// Prevents further seals from being added.
+        const F_SEAL_SEAL = 0x0001;
+        /// Prevents the file from shrinking.
+        const F_SEAL_SHRINK = 0x0002;
+        /// Prevents the file from growing.
+        const F_SEAL_GROW = 0x0004;
+        /// Prevents writes to the file.
+        const F_SEAL_WRITE = 0x0008;
+    }
+}
+
+/// The seals of a memfd file, stored in the extension of its inode.
+pub struct MemfdSeals(Mutex<FileSeals>);
+
+impl MemfdSeals {
+    fn new(seals: FileSeals) -> Self {
+        Self(Mutex::new(seals))
+    }
+
+    /// Returns the seals of the file if the inode is a memfd file.
+    pub fn of(inode: &dyn Inode) -> Option<Arc<Self>> {
+        inode.extension()?.get::<Self>()
+    }
+
+    /// Returns the current seals.
+    pub fn get(&self) -> FileSeals {
+        *self.0.lock()
+    }
+
+    /// Adds new seals to the file.
+    pub fn add(&self, inode: &dyn Inode, new_seals: FileSeals) -> Result<()> {
+        let mut seals = self.0.lock();
+
+        if seals.contains(FileSeals::F_SEAL_SEAL) {
+            return_errno_with_message!(Errno::EPERM, "the file is sealed against new seals");
+        }
+
+        if new_seals.contains(FileSeals::F_SEAL_WRITE) && !seals.contains(FileSeals::F_SEAL_WRITE) {
+            // Writable shared mappings would bypass the write seal, so deny
+            // them here.
+            inode
+                .page_cache()
+                .unwrap()
+                .writable_mapping_status()
+                .deny()?;
+        }
+
+        *seals |= new_seals;
+        Ok(())
+    }
+
+    /// Locks the seals for writing to the file, which resizes the file from
+    /// `old_size` to `new_size`.
+    ///
+    /// Holding the returned guard ensures that no seals are added during the
+    /// write.
+    pub(super) fn lock_for_write(
+        &self,
+        old_size: usize,
+        new_size: usize,
+    ) -> Result<MutexGuard<FileSeals>> {
+        let seals = self.0.lock();
+        if seals.contains(FileSeals::F_SEAL_WRITE) {
+            return_errno_with_message!(Errno::EPERM, "the file is sealed against writes");
+        }
+        check_resize(*seals, old_size, new_size)?;
+        Ok(seals)
+    }
+
+    /// Locks the seals for resizing the file from `old_size` to `new_size`.
+    pub(super) fn lock_for_resize(
+        &self,
+        old_size: usize,
+        new_size: usize,
+    ) -> Result<MutexGuard<FileSeals>> {
+        let seals = self.0.lock();
+        check_resize(*seals, old_size, new_size)?;
+        Ok(seals)
+    }
+}
+
+fn check_resize(seals: FileSeals, old_size: usize, new_size: usize) -> Result<()> {
+    if new_size < old_size && seals.contains(FileSeals::F_SEAL_SHRINK) {
+        return_errno_with_message!(Errno::EPERM, "the file is sealed against shrinking");
+    }
+    if new_size > old_size && seals.contains(FileSeals::F_SEAL_GROW) {
+        return_errno_with_message!(Errno::EPERM, "the file is sealed against growing");
+    }
+    Ok(())
+}
@@ -15,8 +15,8 @@ use crate::{
     },
 };
 
-/// A unidirectional communication channel, intended to implement IPC, e.g., pipe,
-/// unix domain sockets, etc.
+/// A unidirectional communication channel, intended to implement IPC, e.g., unix domain
+/// sockets, etc.
 pub struct Channel<T> {
     producer: Producer<T>,
     consumer: Consumer<T>,
@@ -125,6 +125,11 @@ impl<T> Producer<T> {
         }
     }
 
+    /// Returns the number of items that can be written to the channel.
+    pub fn free_len(&self) -> usize {
+        self.this_end().rb().free_len()
+    }
+
     impl_common_methods_for_channel!();
 }
 
@@ -14,7 +14,10 @@ use super::{
 };
 use crate::{
     events::IoEvents,
-    fs::device::{Device, DeviceType},
+    fs::{
+        device::{Device, DeviceType},
+        notify::FsEventPublisher,
+    },
     prelude::*,
     process::{posix_thread::AsPosixThread, signal::PollHandle, Gid, Uid},
     time::clocks::RealTimeCoarseClock,
@@ -401,6 +404,22 @@ pub trait Inode: Any + Sync + Send {
         Err(Error::new(Errno::EISDIR))
     }
 
+    /// Copies at most `len` bytes of `src` at `src_offset` to this inode at
+    /// `offset` inside the kernel, e.g., for `copy_file_range`.
+    ///
+    /// File systems may implement this method to copy the bytes in a faster
+    /// way. `EOPNOTSUPP` means that the caller should fall back to copying
+    /// the bytes with `read_at` and `write_at`.
+    fn copy_range_from(
+        &self,
+        src: &Arc<dyn Inode>,
+        src_offset: usize,
+        offset: usize,
+        len: usize,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EOPNOTSUPP))
+    }
+
     fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
         Err(Error::new(Errno::ENOTDIR))
     }
@@ -595,6 +614,25 @@ impl dyn Inode {
         let mut reader = VmReader::from(buf).to_fallible();
         self.write_direct_at(offset, &mut reader)
     }
+
+    /// Returns the publisher of file system events on this inode.
+    ///
+    /// Returns `None` if nobody has ever watched this inode.
+    pub fn fs_event_publisher(&self) -> Option<Arc<FsEventPublisher>> {
+        self.extension()?.get::<FsEventPublisher>()
+    }
+
+    /// Returns the publisher of file system events on this inode, creating
+    /// one if there is none.
+    ///
+    /// Returns `None` if the inode does not support extensions.
+    pub fn fs_event_publisher_or_init(&self) -> Option<Arc<FsEventPublisher>> {
+        let extension = self.extension()?;
+        Some(match extension.get::<FsEventPublisher>() {
+            Some(publisher) => publisher,
+            None => extension.get_or_put_default::<FsEventPublisher>(),
+        })
+    }
 }
 
 pub struct InodeWriter<'a> {
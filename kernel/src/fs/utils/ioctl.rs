@@ -39,6 +39,62 @@ pub enum IoctlCmd {
     TIOCSPTLCK = 0x40045431,
     /// Safely open the slave
     TIOCGPTPEER = 0x40045441,
+    /// Get the size of the block device in 512-byte sectors
+    BLKGETSIZE = 0x1260,
+    /// Flush the buffers of the block device
+    BLKFLSBUF = 0x1261,
+    /// Get the logical sector size of the block device
+    BLKSSZGET = 0x1268,
+    /// Get the size of the block device in bytes
+    BLKGETSIZE64 = 0x80081272,
+    /// Get the variable information of the framebuffer
+    FBIOGET_VSCREENINFO = 0x4600,
+    /// Set the variable information of the framebuffer
+    FBIOPUT_VSCREENINFO = 0x4601,
+    /// Get the fixed information of the framebuffer
+    FBIOGET_FSCREENINFO = 0x4602,
+    /// Pan the display of the framebuffer
+    FBIOPAN_DISPLAY = 0x4606,
+    /// Blank or unblank the display of the framebuffer
+    FBIOBLANK = 0x4611,
     /// Get tdx report using TDCALL
     TDXGETREPORT = 0xc4405401,
 }
+
+/// A raw ioctl command number that is not listed in [`IoctlCmd`].
+///
+/// Some ioctl commands encode the sizes of their arguments in the command numbers (e.g.,
+/// `EVIOCGNAME(len)`), so they cannot be listed in [`IoctlCmd`]. Such commands are passed to
+/// files as raw command numbers, which are decoded with the methods of this type.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/asm-generic/ioctl.h>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawIoctlCmd(u32);
+
+impl RawIoctlCmd {
+    const NR_BITS: u32 = 8;
+    const TYPE_BITS: u32 = 8;
+    const SIZE_BITS: u32 = 14;
+
+    const TYPE_SHIFT: u32 = Self::NR_BITS;
+    const SIZE_SHIFT: u32 = Self::TYPE_SHIFT + Self::TYPE_BITS;
+
+    pub const fn new(cmd: u32) -> Self {
+        Self(cmd)
+    }
+
+    /// Returns the command number within the type.
+    pub const fn nr(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Returns the type (i.e., the magic number) of the command.
+    pub const fn type_(self) -> u8 {
+        (self.0 >> Self::TYPE_SHIFT) as u8
+    }
+
+    /// Returns the size of the argument.
+    pub const fn size(self) -> usize {
+        ((self.0 >> Self::SIZE_SHIFT) & ((1 << Self::SIZE_BITS) - 1)) as usize
+    }
+}
@@ -12,7 +12,7 @@ pub use file_creation_mask::FileCreationMask;
 pub use flock::{FlockItem, FlockList, FlockType};
 pub use fs::{FileSystem, FsFlags, SuperBlock};
 pub use inode::{Extension, Inode, InodeMode, InodeType, Metadata, MknodType, Permission};
-pub use ioctl::IoctlCmd;
+pub use ioctl::{IoctlCmd, RawIoctlCmd};
 pub use page_cache::{CachePage, PageCache, PageCacheBackend};
 pub use random_test::{generate_random_operation, new_fs_in_memory};
 pub use range_lock::{
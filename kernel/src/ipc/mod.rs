@@ -1,15 +1,25 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_rights::ReadOp;
+
 use crate::{
     prelude::*,
-    process::{Gid, Uid},
+    process::{credentials::capabilities::CapSet, Credentials, Gid, Uid},
 };
 
+pub mod msg;
+mod namespace;
 pub mod semaphore;
+pub mod shm;
+
+pub use namespace::IpcNamespace;
 
 #[expect(non_camel_case_types)]
 pub type key_t = i32;
 
+/// The key that always creates a new IPC object.
+pub const IPC_PRIVATE: key_t = 0;
+
 bitflags! {
     pub struct IpcFlags: u32{
         /// Create key if key does not exist
@@ -23,6 +33,14 @@ bitflags! {
     }
 }
 
+bitflags! {
+    pub struct PermissionMode: u16{
+        const ALTER  = 0o002;
+        const WRITE  = 0o002;
+        const READ   = 0o004;
+    }
+}
+
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, TryFromInt)]
 #[expect(non_camel_case_types)]
@@ -85,7 +103,61 @@ impl IpcPermission {
         self.mode
     }
 
-    pub(self) fn new_sem_perm(key: key_t, uid: Uid, gid: Gid, mode: u16) -> Self {
+    /// Checks whether `credentials` are granted `required_perm` by the
+    /// permission mode.
+    pub fn check(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        required_perm: PermissionMode,
+    ) -> Result<()> {
+        let euid = credentials.euid();
+        let egid = credentials.egid();
+        let granted_mode = if euid == self.uid || euid == self.cuid {
+            self.mode >> 6
+        } else if egid == self.gid
+            || egid == self.cguid
+            || credentials.groups().contains(&self.gid)
+            || credentials.groups().contains(&self.cguid)
+        {
+            self.mode >> 3
+        } else {
+            self.mode
+        };
+
+        if PermissionMode::from_bits_truncate(granted_mode & 0o7).contains(required_perm)
+            || credentials.effective_capset().contains(CapSet::IPC_OWNER)
+        {
+            return Ok(());
+        }
+        return_errno_with_message!(Errno::EACCES, "the IPC permission check failed");
+    }
+
+    /// Checks whether `credentials` are allowed to change the permission or
+    /// to remove the IPC object.
+    pub fn check_owner(&self, credentials: &Credentials<ReadOp>) -> Result<()> {
+        let euid = credentials.euid();
+        if euid == self.uid
+            || euid == self.cuid
+            || credentials.effective_capset().contains(CapSet::SYS_ADMIN)
+        {
+            return Ok(());
+        }
+        return_errno_with_message!(Errno::EPERM, "not the owner of the IPC object");
+    }
+
+    /// Makes the IPC object no longer reachable by its key.
+    pub(self) fn clear_key(&mut self) {
+        self.key = IPC_PRIVATE;
+    }
+
+    /// Sets the owner and the permission mode, as `IPC_SET` does.
+    pub fn set_owner_and_mode(&mut self, uid: Uid, gid: Gid, mode: u16) {
+        self.uid = uid;
+        self.gid = gid;
+        self.mode = (self.mode & !0o777) | (mode & 0o777);
+    }
+
+    pub(self) fn new(key: key_t, uid: Uid, gid: Gid, mode: u16) -> Self {
         Self {
             key,
             uid,
@@ -97,6 +169,51 @@ impl IpcPermission {
     }
 }
 
-pub(super) fn init() {
-    semaphore::init();
+/// The permission of an IPC object, as reported to the user space by the
+/// `IPC_STAT` commands.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+pub struct c_ipc64_perm {
+    key: key_t,
+    uid: u32,
+    gid: u32,
+    cuid: u32,
+    cgid: u32,
+    mode: u32,
+    seq: u16,
+    __pad2: u16,
+    __pad3: u32,
+    __unused1: u64,
+    __unused2: u64,
+}
+
+impl c_ipc64_perm {
+    pub fn new(key: key_t, uid: Uid, gid: Gid, cuid: Uid, cgid: Gid, mode: u16) -> Self {
+        Self {
+            key,
+            uid: uid.into(),
+            gid: gid.into(),
+            cuid: cuid.into(),
+            cgid: cgid.into(),
+            mode: mode as u32,
+            seq: 0,
+            __pad2: 0,
+            __pad3: 0,
+            __unused1: 0,
+            __unused2: 0,
+        }
+    }
+
+    pub fn uid(&self) -> Uid {
+        Uid::new(self.uid)
+    }
+
+    pub fn gid(&self) -> Gid {
+        Gid::new(self.gid)
+    }
+
+    pub fn mode(&self) -> u16 {
+        self.mode as u16
+    }
 }
@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Message queues for the system, i.e., System V message queues.
+//!
+//! POSIX message queues are files of the mqueue file system, which is
+//! implemented in `crate::fs::mqueue`.
+
+pub mod system_v;
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V message queues.
+
+use bitflags::bitflags;
+
+use crate::ipc::IpcFlags;
+
+pub mod msg_queue;
+
+bitflags! {
+    pub struct MsgRcvFlags: u32 {
+        /// Return an error instead of waiting if there is no message
+        const IPC_NOWAIT = IpcFlags::IPC_NOWAIT.bits;
+        /// Truncate the message if it is too long
+        const MSG_NOERROR = 0o10000;
+        /// Receive the first message whose type differs from the requested one
+        const MSG_EXCEPT = 0o20000;
+        /// Copy the message at the requested position without removing it
+        const MSG_COPY = 0o40000;
+    }
+}
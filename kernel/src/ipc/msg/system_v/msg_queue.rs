@@ -0,0 +1,468 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_rights::ReadOp;
+use id_alloc::IdAlloc;
+use ostd::sync::WaitQueue;
+
+use super::MsgRcvFlags;
+use crate::{
+    ipc::{key_t, IpcFlags, IpcNamespace, IpcPermission, PermissionMode, IPC_PRIVATE},
+    prelude::*,
+    process::{credentials::capabilities::CapSet, Credentials, Gid, Pid, Uid},
+    time::clocks::RealTimeCoarseClock,
+};
+
+// The following constant values are derived from the default values in Linux.
+
+/// Maximum size of a message in bytes.
+pub const MSGMAX: usize = 8192;
+/// Default maximum number of bytes in a message queue.
+pub const MSGMNB: usize = 16384;
+/// Maximum number of message queues.
+pub const MSGMNI: usize = 32000;
+
+/// A System V message queue.
+#[derive(Debug)]
+pub struct MsgQueue {
+    /// The queue ID
+    id: key_t,
+    /// Inner
+    inner: SpinLock<MsgQueueInner>,
+    /// The wait queue of both the senders and the receivers
+    wait_queue: WaitQueue,
+}
+
+#[derive(Debug)]
+struct MsgQueueInner {
+    /// Queue permission
+    permission: IpcPermission,
+    /// Whether `IPC_RMID` has been performed on the queue
+    is_removed: bool,
+    /// Messages in the order they were sent
+    messages: VecDeque<Message>,
+    /// Number of bytes of all the messages
+    num_bytes: usize,
+    /// Maximum number of bytes allowed in the queue
+    max_bytes: usize,
+    /// PID of the last `msgsnd`
+    lspid: Pid,
+    /// PID of the last `msgrcv`
+    lrpid: Pid,
+    /// Last `msgsnd` time
+    stime: u64,
+    /// Last `msgrcv` time
+    rtime: u64,
+    /// Creation time or last modification via `msgctl`
+    ctime: u64,
+}
+
+/// A message in a System V message queue.
+#[derive(Debug, Clone)]
+pub struct Message {
+    type_: i64,
+    data: Vec<u8>,
+}
+
+impl Message {
+    /// Creates a new message.
+    ///
+    /// The type of a message must be positive.
+    pub fn new(type_: i64, data: Vec<u8>) -> Result<Self> {
+        if type_ <= 0 {
+            return_errno_with_message!(Errno::EINVAL, "the message type must be positive");
+        }
+        if data.len() > MSGMAX {
+            return_errno_with_message!(Errno::EINVAL, "the message is too long");
+        }
+        Ok(Self { type_, data })
+    }
+
+    pub fn type_(&self) -> i64 {
+        self.type_
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// The status of a message queue, as reported by `IPC_STAT`.
+#[derive(Debug, Clone, Copy)]
+pub struct MsgQueueStat {
+    pub key: key_t,
+    pub uid: Uid,
+    pub gid: Gid,
+    pub cuid: Uid,
+    pub cgid: Gid,
+    pub mode: u16,
+    pub stime: u64,
+    pub rtime: u64,
+    pub ctime: u64,
+    /// Number of bytes of all the messages
+    pub num_bytes: usize,
+    /// Number of messages
+    pub num_messages: usize,
+    /// Maximum number of bytes allowed in the queue
+    pub max_bytes: usize,
+    pub lspid: Pid,
+    pub lrpid: Pid,
+}
+
+impl MsgQueue {
+    fn new(id: key_t, key: key_t, mode: u16, credentials: &Credentials<ReadOp>) -> Self {
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Self {
+            id,
+            inner: SpinLock::new(MsgQueueInner {
+                permission,
+                is_removed: false,
+                messages: VecDeque::new(),
+                num_bytes: 0,
+                max_bytes: MSGMNB,
+                lspid: 0,
+                lrpid: 0,
+                stime: 0,
+                rtime: 0,
+                ctime: now(),
+            }),
+            wait_queue: WaitQueue::new(),
+        }
+    }
+
+    pub fn id(&self) -> key_t {
+        self.id
+    }
+
+    /// Checks whether `credentials` are granted `required_perm`.
+    pub fn check_permission(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        required_perm: PermissionMode,
+    ) -> Result<()> {
+        self.inner
+            .lock()
+            .permission
+            .check(credentials, required_perm)
+    }
+
+    pub fn stat(&self) -> MsgQueueStat {
+        let inner = self.inner.lock();
+        let permission = &inner.permission;
+
+        MsgQueueStat {
+            key: permission.key(),
+            uid: permission.uid(),
+            gid: permission.gid(),
+            cuid: permission.cuid(),
+            cgid: permission.cguid(),
+            mode: permission.mode(),
+            stime: inner.stime,
+            rtime: inner.rtime,
+            ctime: inner.ctime,
+            num_bytes: inner.num_bytes,
+            num_messages: inner.messages.len(),
+            max_bytes: inner.max_bytes,
+            lspid: inner.lspid,
+            lrpid: inner.lrpid,
+        }
+    }
+
+    /// Sets the owner, the permission mode and the maximum number of bytes
+    /// of the queue, as `IPC_SET` does.
+    pub fn set_owner_and_mode(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        uid: Uid,
+        gid: Gid,
+        mode: u16,
+        max_bytes: usize,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.permission.check_owner(credentials)?;
+        if max_bytes > MSGMNB
+            && !credentials
+                .effective_capset()
+                .contains(CapSet::SYS_RESOURCE)
+        {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "raising the queue size above the limit requires CAP_SYS_RESOURCE"
+            );
+        }
+
+        inner.permission.set_owner_and_mode(uid, gid, mode);
+        inner.max_bytes = max_bytes;
+        inner.ctime = now();
+        drop(inner);
+
+        // Senders may be able to proceed with a larger queue.
+        self.wait_queue.wake_all();
+        Ok(())
+    }
+
+    /// Sends a message to the queue.
+    ///
+    /// If the queue is full, this method waits until there is enough space
+    /// unless `is_nonblocking` is true.
+    pub fn send(&self, message: Message, is_nonblocking: bool, pid: Pid) -> Result<()> {
+        let mut message = Some(message);
+        self.wait_until(is_nonblocking, Errno::EAGAIN, |inner| {
+            let len = message.as_ref().unwrap().data.len();
+            if inner.num_bytes + len > inner.max_bytes || inner.messages.len() >= inner.max_bytes {
+                return Ok(None);
+            }
+
+            inner.num_bytes += len;
+            inner.messages.push_back(message.take().unwrap());
+            inner.lspid = pid;
+            inner.stime = now();
+            Ok(Some(()))
+        })?;
+
+        self.wait_queue.wake_all();
+        Ok(())
+    }
+
+    /// Receives a message from the queue.
+    ///
+    /// The message is selected by `msgtyp` as `msgrcv` does. If the message
+    /// is longer than `max_len`, it is truncated if `MSG_NOERROR` is
+    /// specified, or an error is returned otherwise.
+    ///
+    /// If there is no such message, this method waits until one arrives
+    /// unless `IPC_NOWAIT` is specified.
+    pub fn receive(
+        &self,
+        msgtyp: i64,
+        max_len: usize,
+        flags: MsgRcvFlags,
+        pid: Pid,
+    ) -> Result<Message> {
+        if flags.contains(MsgRcvFlags::MSG_COPY) {
+            return self.copy(msgtyp, max_len, flags);
+        }
+
+        let is_nonblocking = flags.contains(MsgRcvFlags::IPC_NOWAIT);
+        let mut message = self.wait_until(is_nonblocking, Errno::ENOMSG, |inner| {
+            let Some(index) = inner.find_message(msgtyp, flags) else {
+                return Ok(None);
+            };
+            if inner.messages[index].data.len() > max_len
+                && !flags.contains(MsgRcvFlags::MSG_NOERROR)
+            {
+                return_errno_with_message!(Errno::E2BIG, "the message is too long");
+            }
+
+            let message = inner.messages.remove(index).unwrap();
+            inner.num_bytes -= message.data.len();
+            inner.lrpid = pid;
+            inner.rtime = now();
+            Ok(Some(message))
+        })?;
+
+        self.wait_queue.wake_all();
+
+        message.data.truncate(max_len);
+        Ok(message)
+    }
+
+    /// Copies the message at position `index` without removing it, as
+    /// `MSG_COPY` does.
+    fn copy(&self, index: i64, max_len: usize, flags: MsgRcvFlags) -> Result<Message> {
+        if !flags.contains(MsgRcvFlags::IPC_NOWAIT) || flags.contains(MsgRcvFlags::MSG_EXCEPT) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "MSG_COPY requires IPC_NOWAIT and conflicts with MSG_EXCEPT"
+            );
+        }
+
+        let inner = self.inner.lock();
+        if inner.is_removed {
+            return_errno_with_message!(Errno::EIDRM, "the message queue is removed");
+        }
+        let message = usize::try_from(index)
+            .ok()
+            .and_then(|index| inner.messages.get(index))
+            .ok_or_else(|| Error::with_message(Errno::ENOMSG, "no message at the position"))?;
+        if message.data.len() > max_len && !flags.contains(MsgRcvFlags::MSG_NOERROR) {
+            return_errno_with_message!(Errno::E2BIG, "the message is too long");
+        }
+
+        let mut message = message.clone();
+        message.data.truncate(max_len);
+        Ok(message)
+    }
+
+    /// Performs `op` on the queue, waiting until it succeeds.
+    ///
+    /// The operation succeeds if `op` returns `Ok(Some(_))`. If the operation
+    /// cannot succeed right now and `is_nonblocking` is true, an error with
+    /// `errno` is returned.
+    fn wait_until<R>(
+        &self,
+        is_nonblocking: bool,
+        errno: Errno,
+        mut op: impl FnMut(&mut MsgQueueInner) -> Result<Option<R>>,
+    ) -> Result<R> {
+        let mut try_op = || {
+            let mut inner = self.inner.lock();
+            if inner.is_removed {
+                return_errno_with_message!(Errno::EIDRM, "the message queue is removed");
+            }
+            op(&mut inner)
+        };
+
+        if let Some(res) = try_op()? {
+            return Ok(res);
+        }
+        if is_nonblocking {
+            return_errno_with_message!(errno, "the operation would block");
+        }
+
+        self.wait_queue.pause_until(|| try_op().transpose())?
+    }
+}
+
+impl MsgQueueInner {
+    /// Finds the position of the message selected by `msgtyp`.
+    ///
+    /// If `msgtyp` is zero, the first message is selected. If `msgtyp` is
+    /// positive, the first message of the type is selected, or the first
+    /// message of another type if `MSG_EXCEPT` is specified. If `msgtyp` is
+    /// negative, the first message of the lowest type that is less than or
+    /// equal to the absolute value of `msgtyp` is selected.
+    fn find_message(&self, msgtyp: i64, flags: MsgRcvFlags) -> Option<usize> {
+        let mut messages = self.messages.iter().enumerate();
+
+        let selected = match msgtyp {
+            0 => messages.next(),
+            1.. if flags.contains(MsgRcvFlags::MSG_EXCEPT) => {
+                messages.find(|(_, message)| message.type_ != msgtyp)
+            }
+            1.. => messages.find(|(_, message)| message.type_ == msgtyp),
+            _ => messages
+                .filter(|(_, message)| message.type_.unsigned_abs() <= msgtyp.unsigned_abs())
+                .min_by_key(|(_, message)| message.type_),
+        };
+        selected.map(|(index, _)| index)
+    }
+}
+
+/// Gets the queue with the key, and creates one if necessary.
+///
+/// Returns the ID of the queue.
+pub fn get_or_create_queue(
+    ipc_ns: &IpcNamespace,
+    key: key_t,
+    flags: IpcFlags,
+    mode: u16,
+    credentials: &Credentials<ReadOp>,
+) -> Result<key_t> {
+    let mut table = ipc_ns.msg_table().lock();
+
+    if key != IPC_PRIVATE
+        && let Some(queue) = table.find_by_key(key)
+    {
+        if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the message queue already exists");
+        }
+
+        // Check the permissions requested by the mode, as Linux does.
+        let requested_mode = (mode >> 6) | (mode >> 3) | mode;
+        queue.check_permission(
+            credentials,
+            PermissionMode::from_bits_truncate(requested_mode & 0o7),
+        )?;
+        return Ok(queue.id());
+    }
+
+    if key != IPC_PRIVATE && !flags.contains(IpcFlags::IPC_CREAT) {
+        return_errno_with_message!(Errno::ENOENT, "the message queue does not exist");
+    }
+
+    let id = table
+        .id_alloc
+        .alloc()
+        .ok_or_else(|| Error::with_message(Errno::ENOSPC, "too many message queues"))?;
+    let queue = MsgQueue::new(id as key_t, key, mode, credentials);
+    table.queues.insert(id as key_t, Arc::new(queue));
+
+    Ok(id as key_t)
+}
+
+/// Gets the queue with the ID.
+pub fn get_queue(ipc_ns: &IpcNamespace, id: key_t) -> Result<Arc<MsgQueue>> {
+    ipc_ns
+        .msg_table()
+        .lock()
+        .queues
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the message queue does not exist"))
+}
+
+/// Removes the queue with the ID.
+///
+/// The queue is destroyed immediately. The messages in the queue are
+/// discarded and the waiting senders and receivers fail with `EIDRM`.
+pub fn remove_queue(
+    ipc_ns: &IpcNamespace,
+    id: key_t,
+    credentials: &Credentials<ReadOp>,
+) -> Result<()> {
+    let mut table = ipc_ns.msg_table().lock();
+    let queue = table
+        .queues
+        .get(&id)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the message queue does not exist"))?;
+
+    let mut inner = queue.inner.lock();
+    inner.permission.check_owner(credentials)?;
+    inner.permission.clear_key();
+    inner.is_removed = true;
+    inner.messages.clear();
+    inner.num_bytes = 0;
+    drop(inner);
+
+    let queue = table.queues.remove(&id).unwrap();
+    table.id_alloc.free(id as usize);
+    drop(table);
+
+    queue.wait_queue.wake_all();
+    Ok(())
+}
+
+/// Returns all the queues, in the order of their IDs.
+pub fn msg_queues(ipc_ns: &IpcNamespace) -> Vec<Arc<MsgQueue>> {
+    ipc_ns.msg_table().lock().queues.values().cloned().collect()
+}
+
+/// The message queues in an IPC namespace.
+pub(in crate::ipc) struct MsgTable {
+    queues: BTreeMap<key_t, Arc<MsgQueue>>,
+    id_alloc: IdAlloc,
+}
+
+impl MsgTable {
+    pub(in crate::ipc) fn new() -> Self {
+        let mut id_alloc = IdAlloc::with_capacity(MSGMNI + 1);
+        // Remove the first index 0
+        id_alloc.alloc();
+
+        Self {
+            queues: BTreeMap::new(),
+            id_alloc,
+        }
+    }
+
+    fn find_by_key(&self, key: key_t) -> Option<&Arc<MsgQueue>> {
+        self.queues
+            .values()
+            .find(|queue| queue.inner.lock().permission.key() == key)
+    }
+}
+
+fn now() -> u64 {
+    RealTimeCoarseClock::get().read_time().as_secs()
+}
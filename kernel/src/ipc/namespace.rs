@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::{
+    msg::system_v::msg_queue::MsgTable, semaphore::system_v::sem_set::SemTable,
+    shm::system_v::shm_segment::ShmTable,
+};
+use crate::{prelude::*, process::namespace::alloc_ns_id};
+
+/// An IPC namespace.
+///
+/// An IPC namespace isolates the System V IPC objects, i.e., the message
+/// queues, the semaphore sets and the shared memory segments. Each IPC
+/// namespace has its own ID spaces of these objects.
+///
+/// POSIX message queues are not isolated yet.
+pub struct IpcNamespace {
+    id: u64,
+    msg_table: Mutex<MsgTable>,
+    sem_table: SemTable,
+    shm_table: Mutex<ShmTable>,
+}
+
+impl IpcNamespace {
+    /// Returns the initial IPC namespace.
+    pub fn get_init_singleton() -> &'static Arc<IpcNamespace> {
+        static INIT: Once<Arc<IpcNamespace>> = Once::new();
+
+        INIT.call_once(Self::new)
+    }
+
+    /// Creates a new IPC namespace without any IPC objects.
+    pub fn new() -> Arc<IpcNamespace> {
+        Arc::new(Self {
+            id: alloc_ns_id(),
+            msg_table: Mutex::new(MsgTable::new()),
+            sem_table: SemTable::new(),
+            shm_table: Mutex::new(ShmTable::new()),
+        })
+    }
+
+    /// Returns the ID of the namespace.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub(super) fn msg_table(&self) -> &Mutex<MsgTable> {
+        &self.msg_table
+    }
+
+    pub(super) fn sem_table(&self) -> &SemTable {
+        &self.sem_table
+    }
+
+    pub(super) fn shm_table(&self) -> &Mutex<ShmTable> {
+        &self.shm_table
+    }
+}
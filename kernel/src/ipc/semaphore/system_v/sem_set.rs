@@ -8,17 +8,15 @@ use core::{
 
 use aster_rights::ReadOp;
 use id_alloc::IdAlloc;
-use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
-use spin::Once;
+use ostd::sync::{PreemptDisabled, RwLockReadGuard};
 
-use super::{
-    sem::{update_pending_alter, wake_const_ops, PendingOp, Status},
-    PermissionMode,
-};
+use super::sem::{update_pending_alter, wake_const_ops, PendingOp, Status};
 use crate::{
-    ipc::{key_t, semaphore::system_v::sem::Semaphore, IpcPermission},
+    ipc::{
+        key_t, semaphore::system_v::sem::Semaphore, IpcNamespace, IpcPermission, PermissionMode,
+    },
     prelude::*,
-    process::{Credentials, Pid},
+    process::{Credentials, Pid, Uid},
     time::clocks::RealTimeCoarseClock,
 };
 
@@ -181,8 +179,7 @@ impl SemaphoreSet {
             sems.push(Semaphore::new(0));
         }
 
-        let permission =
-            IpcPermission::new_sem_perm(key, credentials.euid(), credentials.egid(), mode);
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
 
         Ok(Self {
             nsems,
@@ -218,16 +215,11 @@ impl Drop for SemaphoreSet {
             }
         }
         pending_const.clear();
-
-        ID_ALLOCATOR
-            .get()
-            .unwrap()
-            .lock()
-            .free(self.permission.key() as usize);
     }
 }
 
 pub fn create_sem_set_with_id(
+    ipc_ns: &IpcNamespace,
     id: key_t,
     nsems: usize,
     mode: u16,
@@ -239,24 +231,29 @@ pub fn create_sem_set_with_id(
         return_errno_with_message!(Errno::ENOENT, "id larger than SEMMNI");
     }
 
-    ID_ALLOCATOR
-        .get()
-        .unwrap()
+    let sem_table = ipc_ns.sem_table();
+    sem_table
+        .id_alloc
         .lock()
         .alloc_specific(id as usize)
         .ok_or(Error::new(Errno::EEXIST))?;
 
-    let mut sem_sets = SEMAPHORE_SETS.write();
+    let mut sem_sets = sem_table.sets.write();
     sem_sets.insert(id, SemaphoreSet::new(id, nsems, mode, credentials)?);
 
     Ok(())
 }
 
 /// Checks the semaphore. Return Ok if the semaphore exists and pass the check.
-pub fn check_sem(id: key_t, nsems: Option<usize>, required_perm: PermissionMode) -> Result<()> {
+pub fn check_sem(
+    ipc_ns: &IpcNamespace,
+    id: key_t,
+    nsems: Option<usize>,
+    required_perm: PermissionMode,
+) -> Result<()> {
     debug_assert!(id > 0);
 
-    let sem_sets = SEMAPHORE_SETS.read();
+    let sem_sets = sem_sets(ipc_ns);
     let sem_set = sem_sets.get(&id).ok_or(Error::new(Errno::ENOENT))?;
 
     if let Some(nsems) = nsems {
@@ -274,41 +271,68 @@ pub fn check_sem(id: key_t, nsems: Option<usize>, required_perm: PermissionMode)
     Ok(())
 }
 
-pub fn create_sem_set(nsems: usize, mode: u16, credentials: Credentials<ReadOp>) -> Result<key_t> {
+pub fn create_sem_set(
+    ipc_ns: &IpcNamespace,
+    nsems: usize,
+    mode: u16,
+    credentials: Credentials<ReadOp>,
+) -> Result<key_t> {
     debug_assert!(nsems <= SEMMSL);
 
-    let id = ID_ALLOCATOR
-        .get()
-        .unwrap()
+    let sem_table = ipc_ns.sem_table();
+    let id = sem_table
+        .id_alloc
         .lock()
         .alloc()
         .ok_or(Error::new(Errno::ENOSPC))? as i32;
 
-    let mut sem_sets = SEMAPHORE_SETS.write();
+    let mut sem_sets = sem_table.sets.write();
     sem_sets.insert(id, SemaphoreSet::new(id, nsems, mode, credentials)?);
 
     Ok(id)
 }
 
-pub fn sem_sets<'a>() -> RwLockReadGuard<'a, BTreeMap<key_t, SemaphoreSet>, PreemptDisabled> {
-    SEMAPHORE_SETS.read()
-}
+/// Removes the semaphore set with the ID.
+///
+/// Only the creator and the owner of the semaphore set can remove it.
+pub fn remove_sem_set(ipc_ns: &IpcNamespace, id: key_t, euid: Uid) -> Result<()> {
+    let sem_table = ipc_ns.sem_table();
+    let mut sem_sets = sem_table.sets.write();
+    let sem_set = sem_sets.get(&id).ok_or(Error::new(Errno::EINVAL))?;
+
+    let permission = sem_set.permission();
+    let can_removed = (euid == permission.uid()) || (euid == permission.cuid());
+    if !can_removed {
+        return_errno!(Errno::EPERM);
+    }
+
+    sem_sets.remove(&id);
+    sem_table.id_alloc.lock().free(id as usize);
 
-pub fn sem_sets_mut<'a>() -> RwLockWriteGuard<'a, BTreeMap<key_t, SemaphoreSet>, PreemptDisabled> {
-    SEMAPHORE_SETS.write()
+    Ok(())
 }
 
-static ID_ALLOCATOR: Once<SpinLock<IdAlloc>> = Once::new();
+pub fn sem_sets(
+    ipc_ns: &IpcNamespace,
+) -> RwLockReadGuard<'_, BTreeMap<key_t, SemaphoreSet>, PreemptDisabled> {
+    ipc_ns.sem_table().sets.read()
+}
 
-/// Semaphore sets in system
-static SEMAPHORE_SETS: RwLock<BTreeMap<key_t, SemaphoreSet>> = RwLock::new(BTreeMap::new());
+/// The semaphore sets in an IPC namespace.
+pub(in crate::ipc) struct SemTable {
+    id_alloc: SpinLock<IdAlloc>,
+    sets: RwLock<BTreeMap<key_t, SemaphoreSet>>,
+}
 
-pub(super) fn init() {
-    ID_ALLOCATOR.call_once(|| {
+impl SemTable {
+    pub(in crate::ipc) fn new() -> Self {
         let mut id_alloc = IdAlloc::with_capacity(SEMMNI + 1);
         // Remove the first index 0
         id_alloc.alloc();
 
-        SpinLock::new(id_alloc)
-    });
+        Self {
+            id_alloc: SpinLock::new(id_alloc),
+            sets: RwLock::new(BTreeMap::new()),
+        }
+    }
 }
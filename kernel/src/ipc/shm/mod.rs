@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Shared memory for the system, i.e., System V shared memory.
+//!
+//! POSIX shared memory is implemented with the files in `/dev/shm` and needs
+//! no extra support here.
+
+pub mod system_v;
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V shared memory.
+
+use bitflags::bitflags;
+
+pub mod shm_segment;
+
+bitflags! {
+    pub struct ShmAtFlags: u32 {
+        /// Attach the segment for read-only access
+        const SHM_RDONLY = 0o10000;
+        /// Round the attach address down to `SHMLBA`
+        const SHM_RND = 0o20000;
+        /// Take over the existing mappings in the attach range
+        const SHM_REMAP = 0o40000;
+        /// Allow the contents of the segment to be executed
+        const SHM_EXEC = 0o100000;
+    }
+}
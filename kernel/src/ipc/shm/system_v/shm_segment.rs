@@ -0,0 +1,453 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::collections::btree_map::BTreeMap;
+
+use aster_rights::{Full, ReadOp, Rights};
+use id_alloc::IdAlloc;
+
+use super::ShmAtFlags;
+use crate::{
+    ipc::{key_t, IpcFlags, IpcNamespace, IpcPermission, PermissionMode, IPC_PRIVATE},
+    prelude::*,
+    process::{Credentials, Gid, Pid, Uid},
+    time::clocks::RealTimeCoarseClock,
+    vm::{
+        perms::VmPerms,
+        vmar::Vmar,
+        vmo::{Vmo, VmoOptions},
+    },
+};
+
+// The following constant values are derived from the default values in Linux.
+
+/// Minimum size of a shared memory segment in bytes.
+pub const SHMMIN: usize = 1;
+/// Maximum size of a shared memory segment in bytes.
+pub const SHMMAX: usize = usize::MAX - (1 << 24);
+/// Maximum number of shared memory segments.
+pub const SHMMNI: usize = 4096;
+/// Maximum number of pages of all shared memory segments.
+pub const SHMALL: usize = usize::MAX - (1 << 24);
+/// Maximum number of shared memory segments attached by a process.
+pub const SHMSEG: usize = SHMMNI;
+/// Alignment of the attach addresses.
+pub const SHMLBA: usize = PAGE_SIZE;
+
+/// The mode bit indicating that the segment will be destroyed on last detach.
+const SHM_DEST: u16 = 0o1000;
+/// The mode bit indicating that the segment is locked in memory.
+const SHM_LOCKED: u16 = 0o2000;
+
+/// A System V shared memory segment.
+#[derive(Debug)]
+pub struct ShmSegment {
+    /// The segment ID
+    id: key_t,
+    /// Size of the segment in bytes, as requested by `shmget`
+    size: usize,
+    /// The VMO that holds the contents of the segment
+    vmo: Vmo<Rights>,
+    /// PID of the creator
+    cpid: Pid,
+    /// Inner
+    inner: SpinLock<ShmSegmentInner>,
+}
+
+#[derive(Debug)]
+struct ShmSegmentInner {
+    /// Segment permission
+    permission: IpcPermission,
+    /// Whether `IPC_RMID` has been performed on the segment
+    is_removed: bool,
+    /// Whether the segment is locked by `SHM_LOCK`
+    is_locked: bool,
+    /// PID of the last `shmat` or `shmdt`
+    lpid: Pid,
+    /// Last `shmat` time
+    atime: u64,
+    /// Last `shmdt` time
+    dtime: u64,
+    /// Creation time or last modification via `shmctl`
+    ctime: u64,
+}
+
+/// The status of a shared memory segment, as reported by `IPC_STAT`.
+#[derive(Debug, Clone, Copy)]
+pub struct ShmSegmentStat {
+    pub key: key_t,
+    pub uid: Uid,
+    pub gid: Gid,
+    pub cuid: Uid,
+    pub cgid: Gid,
+    /// The permission mode, with `SHM_DEST` and `SHM_LOCKED` if applicable
+    pub mode: u16,
+    pub size: usize,
+    pub atime: u64,
+    pub dtime: u64,
+    pub ctime: u64,
+    pub cpid: Pid,
+    pub lpid: Pid,
+    /// Number of current attaches
+    pub nattch: usize,
+}
+
+impl ShmSegment {
+    fn new(
+        id: key_t,
+        key: key_t,
+        size: usize,
+        mode: u16,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+    ) -> Result<Self> {
+        let vmo = VmoOptions::<Rights>::new(size).alloc()?;
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Ok(Self {
+            id,
+            size,
+            vmo,
+            cpid: pid,
+            inner: SpinLock::new(ShmSegmentInner {
+                permission,
+                is_removed: false,
+                is_locked: false,
+                lpid: 0,
+                atime: 0,
+                dtime: 0,
+                ctime: now(),
+            }),
+        })
+    }
+
+    pub fn id(&self) -> key_t {
+        self.id
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of current attaches.
+    ///
+    /// Like Linux, each mapping of the segment is counted. So the count also
+    /// increases on `fork` and decreases on `munmap` or process exit.
+    pub fn num_attaches(&self) -> usize {
+        self.vmo.num_mappings()
+    }
+
+    /// Checks whether `credentials` are granted `required_perm`.
+    pub fn check_permission(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        required_perm: PermissionMode,
+    ) -> Result<()> {
+        self.inner
+            .lock()
+            .permission
+            .check(credentials, required_perm)
+    }
+
+    pub fn stat(&self) -> ShmSegmentStat {
+        let inner = self.inner.lock();
+        let permission = &inner.permission;
+
+        let mut mode = permission.mode();
+        if inner.is_removed {
+            mode |= SHM_DEST;
+        }
+        if inner.is_locked {
+            mode |= SHM_LOCKED;
+        }
+
+        ShmSegmentStat {
+            key: permission.key(),
+            uid: permission.uid(),
+            gid: permission.gid(),
+            cuid: permission.cuid(),
+            cgid: permission.cguid(),
+            mode,
+            size: self.size,
+            atime: inner.atime,
+            dtime: inner.dtime,
+            ctime: inner.ctime,
+            cpid: self.cpid,
+            lpid: inner.lpid,
+            nattch: self.num_attaches(),
+        }
+    }
+
+    /// Sets the owner and the permission mode of the segment.
+    pub fn set_owner_and_mode(
+        &self,
+        credentials: &Credentials<ReadOp>,
+        uid: Uid,
+        gid: Gid,
+        mode: u16,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.permission.check_owner(credentials)?;
+        inner.permission.set_owner_and_mode(uid, gid, mode);
+        inner.ctime = now();
+        Ok(())
+    }
+
+    /// Locks or unlocks the segment in memory.
+    ///
+    /// The pages of a locked segment are not swapped out. Like Linux, the
+    /// pages that have already been swapped out are not swapped in by
+    /// locking the segment.
+    pub fn set_locked(&self, credentials: &Credentials<ReadOp>, is_locked: bool) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.permission.check_owner(credentials)?;
+        inner.is_locked = is_locked;
+        self.vmo.set_unevictable(is_locked);
+        inner.ctime = now();
+        Ok(())
+    }
+
+    /// Attaches the segment to `root_vmar`.
+    ///
+    /// If `addr` is `None`, the attach address is chosen by the system.
+    /// Otherwise, `addr` must be page-aligned.
+    pub fn attach(
+        &self,
+        root_vmar: &Vmar<Full>,
+        addr: Option<Vaddr>,
+        flags: ShmAtFlags,
+        credentials: &Credentials<ReadOp>,
+        pid: Pid,
+    ) -> Result<Vaddr> {
+        let (perms, required_perm) = if flags.contains(ShmAtFlags::SHM_RDONLY) {
+            (VmPerms::READ, PermissionMode::READ)
+        } else {
+            (
+                VmPerms::READ | VmPerms::WRITE,
+                PermissionMode::READ | PermissionMode::WRITE,
+            )
+        };
+        let perms = if flags.contains(ShmAtFlags::SHM_EXEC) {
+            perms | VmPerms::EXEC
+        } else {
+            perms
+        };
+        self.check_permission(credentials, required_perm)?;
+
+        let mut options = root_vmar
+            .new_map(self.vmo.size(), perms)?
+            .vmo(self.vmo.dup()?)
+            .is_shared(true);
+        if let Some(addr) = addr {
+            options = options
+                .offset(addr)
+                .can_overwrite(flags.contains(ShmAtFlags::SHM_REMAP));
+        }
+        let map_addr = options.build()?;
+
+        let mut inner = self.inner.lock();
+        inner.atime = now();
+        inner.lpid = pid;
+
+        Ok(map_addr)
+    }
+}
+
+/// Detaches the segment attached at `addr` from `root_vmar`.
+pub fn detach_segment(
+    ipc_ns: &IpcNamespace,
+    root_vmar: &Vmar<Full>,
+    addr: Vaddr,
+    pid: Pid,
+) -> Result<()> {
+    let segments = shm_segments(ipc_ns);
+    let segment = segments
+        .iter()
+        .find(|segment| root_vmar.vmo_offset_at(addr, &segment.vmo) == Some(0))
+        .ok_or_else(|| {
+            Error::with_message(Errno::EINVAL, "no segment is attached at the address")
+        })?;
+
+    root_vmar.remove_vmo_mappings(addr, &segment.vmo)?;
+
+    let mut inner = segment.inner.lock();
+    inner.dtime = now();
+    inner.lpid = pid;
+
+    Ok(())
+}
+
+/// Gets the segment with the key, and creates one if necessary.
+///
+/// Returns the ID of the segment.
+pub fn get_or_create_segment(
+    ipc_ns: &Arc<IpcNamespace>,
+    key: key_t,
+    size: usize,
+    flags: IpcFlags,
+    mode: u16,
+    credentials: &Credentials<ReadOp>,
+    pid: Pid,
+) -> Result<key_t> {
+    let mut table = ipc_ns.shm_table().lock();
+
+    if key != IPC_PRIVATE
+        && let Some(segment) = table.find_by_key(key)
+    {
+        if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the segment already exists");
+        }
+
+        // Check the permissions requested by the mode, as Linux does.
+        let requested_mode = (mode >> 6) | (mode >> 3) | mode;
+        segment.check_permission(
+            credentials,
+            PermissionMode::from_bits_truncate(requested_mode & 0o7),
+        )?;
+
+        if size > segment.size() {
+            return_errno_with_message!(Errno::EINVAL, "the segment is smaller than the size");
+        }
+        return Ok(segment.id());
+    }
+
+    if key != IPC_PRIVATE && !flags.contains(IpcFlags::IPC_CREAT) {
+        return_errno_with_message!(Errno::ENOENT, "the segment does not exist");
+    }
+    if !(SHMMIN..=SHMMAX).contains(&size) {
+        return_errno_with_message!(Errno::EINVAL, "the segment size is invalid");
+    }
+
+    let total_pages: usize = table
+        .segments
+        .values()
+        .map(|segment| segment.vmo.size() / PAGE_SIZE)
+        .sum();
+    if total_pages.saturating_add(size.div_ceil(PAGE_SIZE)) > SHMALL {
+        return_errno_with_message!(Errno::ENOSPC, "too much shared memory is allocated");
+    }
+
+    let id = table
+        .id_alloc
+        .alloc()
+        .ok_or_else(|| Error::with_message(Errno::ENOSPC, "too many segments"))?;
+    let segment = match ShmSegment::new(id as key_t, key, size, mode, credentials, pid) {
+        Ok(segment) => segment,
+        Err(err) => {
+            table.id_alloc.free(id);
+            return Err(err);
+        }
+    };
+    let segment = Arc::new(segment);
+
+    // Destroy the segment on its last detach if it is removed. A segment can
+    // also be detached implicitly (e.g., by `munmap` or process exit), so
+    // this is done when its last mapping is removed.
+    let weak_ns = Arc::downgrade(ipc_ns);
+    let weak_segment = Arc::downgrade(&segment);
+    segment.vmo.set_mapping_hook(Box::new(move || {
+        if let Some(ipc_ns) = weak_ns.upgrade()
+            && let Some(segment) = weak_segment.upgrade()
+        {
+            ipc_ns.shm_table().lock().destroy_if_unused(&segment);
+        }
+    }));
+
+    table.segments.insert(id as key_t, segment);
+
+    Ok(id as key_t)
+}
+
+/// Gets the segment with the ID.
+pub fn get_segment(ipc_ns: &IpcNamespace, id: key_t) -> Result<Arc<ShmSegment>> {
+    ipc_ns
+        .shm_table()
+        .lock()
+        .segments
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the segment does not exist"))
+}
+
+/// Removes the segment with the ID.
+///
+/// The segment is destroyed after its last detach. Before that, the segment
+/// can no longer be found by its key.
+pub fn remove_segment(
+    ipc_ns: &IpcNamespace,
+    id: key_t,
+    credentials: &Credentials<ReadOp>,
+) -> Result<()> {
+    let mut table = ipc_ns.shm_table().lock();
+    let segment = table
+        .segments
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the segment does not exist"))?;
+
+    let mut inner = segment.inner.lock();
+    inner.permission.check_owner(credentials)?;
+    inner.permission.clear_key();
+    inner.is_removed = true;
+    inner.ctime = now();
+    drop(inner);
+
+    table.destroy_if_unused(&segment);
+    Ok(())
+}
+
+/// Returns all the segments, in the order of their IDs.
+pub fn shm_segments(ipc_ns: &IpcNamespace) -> Vec<Arc<ShmSegment>> {
+    ipc_ns
+        .shm_table()
+        .lock()
+        .segments
+        .values()
+        .cloned()
+        .collect()
+}
+
+/// The shared memory segments in an IPC namespace.
+pub(in crate::ipc) struct ShmTable {
+    segments: BTreeMap<key_t, Arc<ShmSegment>>,
+    id_alloc: IdAlloc,
+}
+
+impl ShmTable {
+    pub(in crate::ipc) fn new() -> Self {
+        let mut id_alloc = IdAlloc::with_capacity(SHMMNI + 1);
+        // Remove the first index 0
+        id_alloc.alloc();
+
+        Self {
+            segments: BTreeMap::new(),
+            id_alloc,
+        }
+    }
+
+    fn find_by_key(&self, key: key_t) -> Option<&Arc<ShmSegment>> {
+        self.segments
+            .values()
+            .find(|segment| segment.inner.lock().permission.key() == key)
+    }
+
+    /// Destroys the segment if it is removed and has no attaches.
+    fn destroy_if_unused(&mut self, segment: &Arc<ShmSegment>) {
+        if !segment.inner.lock().is_removed || segment.num_attaches() > 0 {
+            return;
+        }
+
+        // The segment may have been destroyed, and its ID may be reused.
+        if self
+            .segments
+            .get(&segment.id)
+            .is_some_and(|current| Arc::ptr_eq(current, segment))
+        {
+            self.segments.remove(&segment.id);
+            self.id_alloc.free(segment.id as usize);
+        }
+    }
+}
+
+fn now() -> u64 {
+    RealTimeCoarseClock::get().read_time().as_secs()
+}
@@ -96,7 +96,6 @@ pub fn init() {
     sched::init();
     fs::rootfs::init(boot_info().initramfs.expect("No initramfs found!")).unwrap();
     device::init().unwrap();
-    syscall::init();
     vdso::init();
     process::init();
 }
@@ -130,8 +129,8 @@ fn init_thread() {
     thread::work_queue::init();
     #[cfg(target_arch = "x86_64")]
     net::lazy_init();
+    device::lazy_init().unwrap();
     fs::lazy_init();
-    ipc::init();
     // driver::pci::virtio::block::block_device_test();
     let thread = ThreadOptions::new(|| {
         println!("[kernel] Hello world from kernel!");
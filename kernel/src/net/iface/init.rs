@@ -1,17 +1,20 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{borrow::ToOwned, sync::Arc};
+use alloc::{borrow::ToOwned, format, sync::Arc};
 use core::slice::Iter;
 
 use aster_bigtcp::{
     device::WithDevice,
-    iface::{InterfaceFlags, InterfaceType},
+    iface::{InterfaceFlags, InterfaceType, Ipv4Config},
+    wire::{Ipv4Address, Ipv4Cidr},
 };
+use aster_network::AnyNetworkDevice;
 use aster_softirq::BottomHalfDisabled;
+use ostd::boot::boot_info;
 use spin::Once;
 
-use super::{poll::poll_ifaces, Iface};
-use crate::{net::iface::sched::PollScheduler, prelude::*};
+use super::{ip_config::parse_ip_configs, poll::poll_ifaces, Iface};
+use crate::{kcmdline::KCmdlineArg, net::iface::sched::PollScheduler, prelude::*};
 
 static IFACES: Once<Vec<Arc<Iface>>> = Once::new();
 
@@ -19,56 +22,87 @@ pub fn loopback_iface() -> &'static Arc<Iface> {
     &IFACES.get().unwrap()[0]
 }
 
-pub fn virtio_iface() -> Option<&'static Arc<Iface>> {
-    IFACES.get().unwrap().get(1)
-}
-
 pub fn iter_all_ifaces() -> Iter<'static, Arc<Iface>> {
     IFACES.get().unwrap().iter()
 }
 
+/// Returns the interfaces of the initial network namespace.
+///
+/// There are no interfaces if the network is not initialized.
+pub(in crate::net) fn init_ifaces() -> Vec<Arc<Iface>> {
+    IFACES.get().cloned().unwrap_or_default()
+}
+
 pub fn init() {
+    let karg: KCmdlineArg = boot_info().kernel_cmdline.as_str().into();
+    let mut ip_configs = parse_ip_configs(karg.get_ip_configs());
+
     IFACES.call_once(|| {
-        let mut ifaces = Vec::with_capacity(2);
+        let devices = aster_network::all_devices();
+        let mut ifaces = Vec::with_capacity(devices.len() + 1);
 
-        // Initialize loopback before virtio
-        // to ensure the loopback interface index is ahead of virtio.
+        // Initialize loopback before Ethernet interfaces
+        // to ensure the loopback interface index is ahead of them.
         ifaces.push(new_loopback());
 
-        if let Some(iface_virtio) = new_virtio() {
-            ifaces.push(iface_virtio);
+        for (index, (device_name, device)) in devices.into_iter().enumerate() {
+            let name = format!("eth{}", index);
+            let ipv4_config = ip_configs
+                .remove(&name)
+                .unwrap_or_else(|| default_ipv4_config(index));
+            let iface = new_ether(device, name, ipv4_config);
+
+            let iface_weak = Arc::downgrade(&iface);
+            let callback = move || {
+                if let Some(iface) = iface_weak.upgrade() {
+                    iface.poll();
+                }
+            };
+            aster_network::register_recv_callback(&device_name, callback.clone());
+            aster_network::register_send_callback(&device_name, callback);
+
+            ifaces.push(iface);
         }
 
         ifaces
     });
 
-    if let Some(iface_virtio) = virtio_iface() {
-        for (name, _) in aster_network::all_devices() {
-            // TODO: further check that the irq num is the same as iface's irq num
-            let callback = || iface_virtio.poll();
-            aster_network::register_recv_callback(&name, callback);
-            aster_network::register_send_callback(&name, callback);
-        }
+    for name in ip_configs.keys() {
+        warn!("[IpConfig] no such interface: {}", name);
     }
 
     poll_ifaces();
 }
 
-fn new_virtio() -> Option<Arc<Iface>> {
-    use aster_bigtcp::{
-        iface::EtherIface,
-        wire::{EthernetAddress, Ipv4Address, Ipv4Cidr},
-    };
-    use aster_network::AnyNetworkDevice;
-    use aster_virtio::device::network::DEVICE_NAME;
-
+/// Returns the IPv4 configuration of the Ethernet interface if it is not
+/// specified on the kernel command line.
+///
+/// For compatibility with the user-mode network of QEMU, the first
+/// interface uses the static address assigned by QEMU by default. Other
+/// interfaces are left unconfigured.
+fn default_ipv4_config(index: usize) -> Ipv4Config {
     const VIRTIO_ADDRESS: Ipv4Address = Ipv4Address::new(10, 0, 2, 15);
     const VIRTIO_ADDRESS_PREFIX_LEN: u8 = 24; // mask: 255.255.255.0
     const VIRTIO_GATEWAY: Ipv4Address = Ipv4Address::new(10, 0, 2, 2);
 
-    let virtio_net = aster_network::get_device(DEVICE_NAME)?;
+    if index != 0 {
+        return Ipv4Config::None;
+    }
 
-    let ether_addr = virtio_net.lock().mac_addr().0;
+    Ipv4Config::Static {
+        cidr: Ipv4Cidr::new(VIRTIO_ADDRESS, VIRTIO_ADDRESS_PREFIX_LEN),
+        gateway: Some(VIRTIO_GATEWAY),
+    }
+}
+
+fn new_ether(
+    device: Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>,
+    name: String,
+    ipv4_config: Ipv4Config,
+) -> Arc<Iface> {
+    use aster_bigtcp::{iface::EtherIface, wire::EthernetAddress};
+
+    let ether_addr = device.lock().mac_addr().0;
 
     struct Wrapper(Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>);
 
@@ -92,26 +126,27 @@ fn new_virtio() -> Option<Arc<Iface>> {
         | InterfaceFlags::MULTICAST
         | InterfaceFlags::LOWER_UP;
 
-    Some(EtherIface::new(
-        Wrapper(virtio_net),
+    EtherIface::new(
+        Wrapper(device),
         EthernetAddress(ether_addr),
-        Ipv4Cidr::new(VIRTIO_ADDRESS, VIRTIO_ADDRESS_PREFIX_LEN),
-        VIRTIO_GATEWAY,
-        "eth0".to_owned(),
+        ipv4_config,
+        name,
         PollScheduler::new(),
         flags,
-    ))
+    ) as Arc<Iface>
 }
 
-fn new_loopback() -> Arc<Iface> {
+pub(in crate::net) fn new_loopback() -> Arc<Iface> {
     use aster_bigtcp::{
         device::{Loopback, Medium},
         iface::IpIface,
-        wire::{Ipv4Address, Ipv4Cidr},
+        wire::{Ipv6Address, Ipv6Cidr},
     };
 
     const LOOPBACK_ADDRESS: Ipv4Address = Ipv4Address::new(127, 0, 0, 1);
     const LOOPBACK_ADDRESS_PREFIX_LEN: u8 = 8; // mask: 255.0.0.0
+    const LOOPBACK_IPV6_ADDRESS: Ipv6Address = Ipv6Address::LOCALHOST;
+    const LOOPBACK_IPV6_ADDRESS_PREFIX_LEN: u8 = 128;
 
     struct Wrapper(Mutex<Loopback>);
 
@@ -137,6 +172,10 @@ fn new_loopback() -> Arc<Iface> {
     IpIface::new(
         Wrapper(Mutex::new(Loopback::new(Medium::Ip))),
         Ipv4Cidr::new(LOOPBACK_ADDRESS, LOOPBACK_ADDRESS_PREFIX_LEN),
+        Some(Ipv6Cidr::new(
+            LOOPBACK_IPV6_ADDRESS,
+            LOOPBACK_IPV6_ADDRESS_PREFIX_LEN,
+        )),
         "lo".to_owned(),
         PollScheduler::new(),
         InterfaceType::LOOPBACK,
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The IPv4 configurations of Ethernet interfaces from the kernel command line.
+//!
+//! Each `ip=` option configures one interface, in the same format as Linux:
+//!
+//! ```text
+//! ip=<client-ip>:<server-ip>:<gw-ip>:<netmask>:<hostname>:<device>:<autoconf>
+//! ```
+//!
+//! Trailing fields can be omitted. If `<device>` is empty, the option configures `eth0`. If
+//! `<autoconf>` is `dhcp`, `on` or `any`, the address is obtained via DHCP. Otherwise, the static
+//! address in `<client-ip>` is used, if any. The whole option can also be `dhcp` or `off` as a
+//! shorthand.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/nfs/nfsroot.html>
+
+use aster_bigtcp::{
+    iface::Ipv4Config,
+    wire::{Ipv4Address, Ipv4Cidr},
+};
+
+use crate::prelude::*;
+
+/// The name of the interface that is configured if no device is specified.
+const DEFAULT_DEVICE: &str = "eth0";
+
+/// Parses the values of the `ip=` options.
+///
+/// This method returns the configurations indexed by the interface names. Ill-formed options are
+/// ignored with warnings.
+pub(super) fn parse_ip_configs(values: &[String]) -> BTreeMap<String, Ipv4Config> {
+    let mut configs = BTreeMap::new();
+
+    for value in values {
+        match parse_ip_config(value) {
+            Some((device, config)) => {
+                configs.insert(device, config);
+            }
+            None => warn!("[IpConfig] unable to parse `ip={}`, skip for now", value),
+        }
+    }
+
+    configs
+}
+
+fn parse_ip_config(value: &str) -> Option<(String, Ipv4Config)> {
+    match value {
+        "dhcp" | "on" | "any" => return Some((DEFAULT_DEVICE.to_string(), Ipv4Config::Dhcp)),
+        "off" | "none" => return Some((DEFAULT_DEVICE.to_string(), Ipv4Config::None)),
+        _ => (),
+    }
+
+    let mut fields = value.split(':');
+    let mut next_field = || fields.next().unwrap_or("");
+
+    let client_ip = parse_optional_addr(next_field())?;
+    let _server_ip = parse_optional_addr(next_field())?;
+    let gateway = parse_optional_addr(next_field())?;
+    let netmask = parse_optional_addr(next_field())?;
+    // TODO: Set the host name if it is specified.
+    let _hostname = next_field();
+    let device = match next_field() {
+        "" => DEFAULT_DEVICE,
+        device => device,
+    };
+    let autoconf = next_field();
+
+    let config = match (autoconf, client_ip) {
+        ("dhcp" | "on" | "any", _) => Ipv4Config::Dhcp,
+        ("" | "off" | "none" | "static", Some(client_ip)) => {
+            let cidr = match netmask {
+                Some(netmask) => Ipv4Cidr::from_netmask(client_ip, netmask).ok()?,
+                None => Ipv4Cidr::new(client_ip, classful_prefix_len(client_ip)),
+            };
+            Ipv4Config::Static { cidr, gateway }
+        }
+        ("" | "off" | "none" | "static", None) => Ipv4Config::None,
+        _ => return None,
+    };
+
+    Some((device.to_string(), config))
+}
+
+fn parse_optional_addr(field: &str) -> Option<Option<Ipv4Address>> {
+    if field.is_empty() {
+        return Some(None);
+    }
+    field.parse().ok().map(Some)
+}
+
+/// Returns the prefix length of the classful network to which the address belongs.
+///
+/// Like Linux, this is used as the default netmask.
+fn classful_prefix_len(addr: Ipv4Address) -> u8 {
+    match addr.octets()[0] {
+        0..=127 => 8,
+        128..=191 => 16,
+        _ => 24,
+    }
+}
@@ -2,11 +2,14 @@
 
 mod ext;
 mod init;
+mod ip_config;
 mod poll;
 mod sched;
 
-pub use init::{init, iter_all_ifaces, loopback_iface, virtio_iface};
+pub use init::{init, iter_all_ifaces, loopback_iface};
+pub(super) use init::{init_ifaces, new_loopback};
 pub use poll::lazy_init;
+pub(super) use poll::spawn_background_poll_thread;
 
 pub type Iface = dyn aster_bigtcp::iface::Iface<ext::BigtcpExt>;
 pub type BoundPort = aster_bigtcp::iface::BoundPort<ext::BigtcpExt>;
@@ -25,7 +25,7 @@ pub(super) fn poll_ifaces() {
     }
 }
 
-fn spawn_background_poll_thread(iface: Arc<Iface>) {
+pub(in crate::net) fn spawn_background_poll_thread(iface: Arc<Iface>) {
     let task_fn = move || {
         trace!("spawn background poll thread for {}", iface.name());
 
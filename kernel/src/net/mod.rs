@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub mod iface;
+mod namespace;
 pub mod socket;
 
+pub use namespace::NetNamespace;
+
 pub fn init() {
     iface::init();
     socket::netlink::init();
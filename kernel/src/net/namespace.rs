@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::iface::{init_ifaces, new_loopback, spawn_background_poll_thread, Iface};
+use crate::{prelude::*, process::namespace::alloc_ns_id};
+
+/// A network namespace.
+///
+/// A network namespace isolates the network interfaces. A new network
+/// namespace only has its own loopback interface.
+///
+/// FIXME: A socket should stay in the network namespace in which it is
+/// created. Currently, sockets look up the interfaces in the network
+/// namespace of the calling thread instead.
+pub struct NetNamespace {
+    id: u64,
+    ifaces: Vec<Arc<Iface>>,
+}
+
+impl NetNamespace {
+    /// Returns the initial network namespace, which owns the interfaces of the devices.
+    pub fn get_init_singleton() -> &'static Arc<NetNamespace> {
+        static INIT: Once<Arc<NetNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            Arc::new(Self {
+                id: alloc_ns_id(),
+                ifaces: init_ifaces(),
+            })
+        })
+    }
+
+    /// Creates a new network namespace with a loopback interface.
+    pub fn new() -> Arc<NetNamespace> {
+        let loopback = new_loopback();
+        // FIXME: The polling thread should exit after the namespace is dropped.
+        spawn_background_poll_thread(loopback.clone());
+
+        Arc::new(Self {
+            id: alloc_ns_id(),
+            ifaces: vec![loopback],
+        })
+    }
+
+    /// Returns the ID of the namespace.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the interfaces in the namespace.
+    ///
+    /// The first interface is always the loopback interface.
+    pub fn ifaces(&self) -> &[Arc<Iface>] {
+        &self.ifaces
+    }
+
+    /// Returns the default interface to send packets to other hosts.
+    ///
+    /// This is the first interface with an IPv4 default gateway. If there
+    /// is no such interface, the first interface other than the loopback
+    /// interface is used.
+    pub fn default_iface(&self) -> &Arc<Iface> {
+        // FIXME: Instead of hardcoding the rules here, we should choose the
+        // default interface according to a full routing table.
+        self.ifaces
+            .iter()
+            .find(|iface| iface.ipv4_gateway().is_some())
+            .or(self.ifaces.get(1))
+            .unwrap_or(&self.ifaces[0])
+    }
+}
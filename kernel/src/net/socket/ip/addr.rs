@@ -1,37 +1,114 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use aster_bigtcp::wire::{IpAddress, IpEndpoint, Ipv4Address};
+use aster_bigtcp::wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv6Address};
 
-use crate::{net::socket::SocketAddr, prelude::*, return_errno_with_message};
+use crate::{net::socket::SocketAddr, prelude::*, util::net::CSocketAddrFamily};
 
-impl TryFrom<SocketAddr> for IpEndpoint {
+/// The address family of an IP socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFamily {
+    /// `AF_INET` sockets, which only speak IPv4.
+    Ipv4,
+    /// `AF_INET6` sockets, which speak IPv6 and, unless `IPV6_V6ONLY` is set, IPv4 via
+    /// IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`).
+    Ipv6,
+}
+
+impl TryFrom<CSocketAddrFamily> for IpFamily {
     type Error = Error;
 
-    fn try_from(value: SocketAddr) -> Result<Self> {
+    fn try_from(value: CSocketAddrFamily) -> Result<Self> {
         match value {
-            SocketAddr::IPv4(addr, port) => Ok(IpEndpoint::new(addr.into(), port)),
+            CSocketAddrFamily::AF_INET => Ok(Self::Ipv4),
+            CSocketAddrFamily::AF_INET6 => Ok(Self::Ipv6),
+            _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "the family is not an IP family"),
+        }
+    }
+}
+
+impl IpFamily {
+    /// Converts the socket address to a local endpoint to bind.
+    pub(super) fn local_endpoint_from(
+        self,
+        socket_addr: SocketAddr,
+        is_v6only: bool,
+    ) -> Result<IpEndpoint> {
+        let endpoint = self.endpoint_from(socket_addr)?;
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.10.2/source/net/ipv6/af_inet6.c#L336>
+        if self == Self::Ipv6 && is_v6only && matches!(endpoint.addr, IpAddress::Ipv4(_)) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IPv4-mapped addresses cannot be bound with IPV6_V6ONLY"
+            );
+        }
+
+        Ok(endpoint)
+    }
+
+    /// Converts the socket address to a remote endpoint to connect or send to.
+    pub(super) fn remote_endpoint_from(
+        self,
+        socket_addr: SocketAddr,
+        is_v6only: bool,
+    ) -> Result<IpEndpoint> {
+        let endpoint = self.endpoint_from(socket_addr)?;
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.10.2/source/net/ipv6/tcp_ipv6.c#L211>
+        if self == Self::Ipv6 && is_v6only && matches!(endpoint.addr, IpAddress::Ipv4(_)) {
+            return_errno_with_message!(
+                Errno::ENETUNREACH,
+                "IPv4-mapped addresses cannot be reached with IPV6_V6ONLY"
+            );
+        }
+
+        Ok(endpoint)
+    }
+
+    /// Converts the socket address to an endpoint.
+    ///
+    /// For `AF_INET6` sockets, IPv4-mapped IPv6 addresses are converted to IPv4 endpoints.
+    fn endpoint_from(self, socket_addr: SocketAddr) -> Result<IpEndpoint> {
+        match (self, socket_addr) {
+            (Self::Ipv4, SocketAddr::IPv4(addr, port)) => Ok(IpEndpoint::new(addr.into(), port)),
+            (Self::Ipv6, SocketAddr::IPv6(addr, port)) => {
+                let addr = match addr.to_ipv4_mapped() {
+                    Some(ipv4_addr) => IpAddress::Ipv4(ipv4_addr),
+                    None => IpAddress::Ipv6(addr),
+                };
+                Ok(IpEndpoint::new(addr, port))
+            }
             _ => return_errno_with_message!(
                 Errno::EAFNOSUPPORT,
                 "the address is in an unsupported address family"
             ),
         }
     }
-}
 
-impl From<IpEndpoint> for SocketAddr {
-    fn from(endpoint: IpEndpoint) -> Self {
+    /// Converts the endpoint to a socket address.
+    ///
+    /// For `AF_INET6` sockets, IPv4 endpoints are converted to IPv4-mapped IPv6 addresses.
+    pub(super) fn socket_addr_from(self, endpoint: IpEndpoint) -> SocketAddr {
         let port = endpoint.port;
-        match endpoint.addr {
-            IpAddress::Ipv4(addr) => SocketAddr::IPv4(addr, port),
-            // TODO: support IPv6
+        match (self, endpoint.addr) {
+            (Self::Ipv4, IpAddress::Ipv4(addr)) => SocketAddr::IPv4(addr, port),
+            (Self::Ipv6, IpAddress::Ipv4(addr)) => SocketAddr::IPv6(addr.to_ipv6_mapped(), port),
+            (Self::Ipv6, IpAddress::Ipv6(addr)) => SocketAddr::IPv6(addr, port),
+            (Self::Ipv4, IpAddress::Ipv6(_)) => {
+                unreachable!("`AF_INET` sockets cannot have IPv6 endpoints")
+            }
         }
     }
-}
 
-/// A local endpoint, which indicates that the local endpoint is unspecified.
-///
-/// According to the Linux man pages and the Linux implementation, `getsockname()` will _not_ fail
-/// even if the socket is unbound. Instead, it will return an unspecified socket address. This
-/// unspecified endpoint helps with that.
-pub(super) const UNSPECIFIED_LOCAL_ENDPOINT: IpEndpoint =
-    IpEndpoint::new(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED), 0);
+    /// Returns the unspecified local endpoint.
+    ///
+    /// According to the Linux man pages and the Linux implementation, `getsockname()` will _not_
+    /// fail even if the socket is unbound. Instead, it will return an unspecified socket address.
+    /// This unspecified endpoint helps with that.
+    pub(super) const fn unspecified_endpoint(self) -> IpEndpoint {
+        match self {
+            Self::Ipv4 => IpEndpoint::new(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED), 0),
+            Self::Ipv6 => IpEndpoint::new(IpAddress::Ipv6(Ipv6Address::UNSPECIFIED), 0),
+        }
+    }
+}
@@ -7,45 +7,42 @@ use aster_bigtcp::{
 };
 
 use crate::{
-    net::iface::{iter_all_ifaces, loopback_iface, virtio_iface, BoundPort, Iface},
+    net::iface::{BoundPort, Iface},
     prelude::*,
+    process::posix_thread::AsPosixThread,
 };
 
 pub(super) fn get_iface_to_bind(ip_addr: &IpAddress) -> Option<Arc<Iface>> {
-    let IpAddress::Ipv4(ipv4_addr) = ip_addr;
-    iter_all_ifaces()
-        .find(|iface| {
-            if let Some(iface_ipv4_addr) = iface.ipv4_addr() {
-                iface_ipv4_addr == *ipv4_addr
-            } else {
-                false
-            }
-        })
+    let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+    ns_proxy
+        .net_ns()
+        .ifaces()
+        .iter()
+        .find(|iface| iface.has_ip_addr(*ip_addr))
         .map(Clone::clone)
 }
 
 /// Get a suitable iface to deal with sendto/connect request if the socket is not bound to an iface.
 /// If the remote address is the same as that of some iface, we will use the iface.
+/// If the remote address is on the link of some iface, we will use the iface.
 /// Otherwise, we will use a default interface.
 fn get_ephemeral_iface(remote_ip_addr: &IpAddress) -> Arc<Iface> {
-    let IpAddress::Ipv4(remote_ipv4_addr) = remote_ip_addr;
-    if let Some(iface) = iter_all_ifaces().find(|iface| {
-        if let Some(iface_ipv4_addr) = iface.ipv4_addr() {
-            iface_ipv4_addr == *remote_ipv4_addr
-        } else {
-            false
-        }
-    }) {
+    let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+    let net_ns = ns_proxy.net_ns();
+    let ifaces = net_ns.ifaces();
+    if let Some(iface) = ifaces
+        .iter()
+        .find(|iface| iface.has_ip_addr(*remote_ip_addr))
+        .or_else(|| {
+            ifaces
+                .iter()
+                .find(|iface| iface.is_on_link(*remote_ip_addr))
+        })
+    {
         return iface.clone();
     }
 
-    // FIXME: Instead of hardcoding the rules here, we should choose the
-    // default interface according to the routing table.
-    if let Some(virtio_iface) = virtio_iface() {
-        virtio_iface.clone()
-    } else {
-        loopback_iface().clone()
-    }
+    net_ns.default_iface().clone()
 }
 
 pub(super) fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundPort> {
@@ -61,7 +58,7 @@ pub(super) fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundP
 
     let bind_port_config = BindPortConfig::new(endpoint.port, can_reuse);
 
-    Ok(iface.bind(bind_port_config)?)
+    Ok(iface.bind(endpoint.addr, bind_port_config)?)
 }
 
 impl From<BindError> for Error {
@@ -77,8 +74,17 @@ impl From<BindError> for Error {
     }
 }
 
-pub(super) fn get_ephemeral_endpoint(remote_endpoint: &IpEndpoint) -> IpEndpoint {
+pub(super) fn get_ephemeral_endpoint(remote_endpoint: &IpEndpoint) -> Result<IpEndpoint> {
     let iface = get_ephemeral_iface(&remote_endpoint.addr);
-    let ip_addr = iface.ipv4_addr().unwrap();
-    IpEndpoint::new(IpAddress::Ipv4(ip_addr), 0)
+    let ip_addr = match remote_endpoint.addr {
+        IpAddress::Ipv4(_) => iface.ipv4_addr().map(IpAddress::Ipv4),
+        IpAddress::Ipv6(_) => iface.ipv6_addr().map(IpAddress::Ipv6),
+    };
+    let Some(ip_addr) = ip_addr else {
+        return_errno_with_message!(
+            Errno::ENETUNREACH,
+            "the interface has no address of the same version as the remote address"
+        );
+    };
+    Ok(IpEndpoint::new(ip_addr, 0))
 }
@@ -6,7 +6,10 @@ use aster_bigtcp::wire::IpEndpoint;
 use unbound::BindOptions;
 
 use self::{bound::BoundDatagram, unbound::UnboundDatagram};
-use super::UNSPECIFIED_LOCAL_ENDPOINT;
+use super::{
+    options::{Ipv6OptionSet, SetIpv6LevelOption},
+    IpFamily,
+};
 use crate::{
     events::IoEvents,
     match_sock_option_mut,
@@ -36,13 +39,15 @@ pub(in crate::net) use self::observer::DatagramObserver;
 #[derive(Debug, Clone)]
 struct OptionSet {
     socket: SocketOptionSet,
+    ipv6: Ipv6OptionSet,
     // TODO: UDP option set
 }
 
 impl OptionSet {
     fn new() -> Self {
         let socket = SocketOptionSet::new_udp();
-        OptionSet { socket }
+        let ipv6 = Ipv6OptionSet::new();
+        OptionSet { socket, ipv6 }
     }
 }
 
@@ -51,16 +56,18 @@ pub struct DatagramSocket {
     inner: RwMutex<Inner<UnboundDatagram, BoundDatagram>>,
     options: RwLock<OptionSet>,
 
+    family: IpFamily,
     is_nonblocking: AtomicBool,
     pollee: Pollee,
 }
 
 impl DatagramSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+    pub fn new(family: IpFamily, is_nonblocking: bool) -> Arc<Self> {
         let unbound_datagram = UnboundDatagram::new();
         Arc::new(Self {
             inner: RwMutex::new(Inner::Unbound(unbound_datagram)),
             options: RwLock::new(OptionSet::new()),
+            family,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
         })
@@ -71,14 +78,10 @@ impl DatagramSocket {
         writer: &mut dyn MultiWrite,
         flags: SendRecvFlags,
     ) -> Result<(usize, SocketAddr)> {
-        let recv_bytes = self
-            .inner
-            .read()
-            .try_recv(writer, flags)
-            .map(|(recv_bytes, remote_endpoint)| (recv_bytes, remote_endpoint.into()))?;
+        let (recv_bytes, remote_endpoint) = self.inner.read().try_recv(writer, flags)?;
         self.pollee.invalidate();
 
-        Ok(recv_bytes)
+        Ok((recv_bytes, self.family.socket_addr_from(remote_endpoint)))
     }
 
     fn try_send(
@@ -134,8 +137,12 @@ impl SocketPrivate for DatagramSocket {
 
 impl Socket for DatagramSocket {
     fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
-        let can_reuse = self.options.read().socket.reuse_addr();
+        let options = self.options.read();
+        let endpoint = self
+            .family
+            .local_endpoint_from(socket_addr, options.ipv6.v6only())?;
+        let can_reuse = options.socket.reuse_addr();
+        drop(options);
 
         self.inner
             .write()
@@ -143,7 +150,8 @@ impl Socket for DatagramSocket {
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
+        let is_v6only = self.options.read().ipv6.v6only();
+        let endpoint = self.family.remote_endpoint_from(socket_addr, is_v6only)?;
 
         self.inner.write().connect(&endpoint, &self.pollee)
     }
@@ -153,9 +161,9 @@ impl Socket for DatagramSocket {
             .inner
             .read()
             .addr()
-            .unwrap_or(UNSPECIFIED_LOCAL_ENDPOINT);
+            .unwrap_or(self.family.unspecified_endpoint());
 
-        Ok(endpoint.into())
+        Ok(self.family.socket_addr_from(endpoint))
     }
 
     fn peer_addr(&self) -> Result<SocketAddr> {
@@ -164,7 +172,7 @@ impl Socket for DatagramSocket {
                 Error::with_message(Errno::ENOTCONN, "the socket is not connected")
             })?;
 
-        Ok(endpoint.into())
+        Ok(self.family.socket_addr_from(endpoint))
     }
 
     fn sendmsg(
@@ -180,15 +188,18 @@ impl Socket for DatagramSocket {
 
         let MessageHeader {
             addr,
-            control_message,
+            control_messages,
         } = message_header;
 
         let endpoint = match addr {
-            Some(addr) => Some(addr.try_into()?),
+            Some(addr) => {
+                let is_v6only = self.options.read().ipv6.v6only();
+                Some(self.family.remote_endpoint_from(addr, is_v6only)?)
+            }
             None => None,
         };
 
-        if control_message.is_some() {
+        if !control_messages.is_empty() {
             // TODO: Support sending control message
             warn!("sending control message is not supported");
         }
@@ -212,7 +223,7 @@ impl Socket for DatagramSocket {
 
         // TODO: Receive control message
 
-        let message_header = MessageHeader::new(Some(peer_addr), None);
+        let message_header = MessageHeader::new(Some(peer_addr), Vec::new());
 
         Ok((received_bytes, message_header))
     }
@@ -227,14 +238,34 @@ impl Socket for DatagramSocket {
             _ => ()
         });
 
-        self.options.read().socket.get_option(option)
+        let options = self.options.read();
+
+        // Deal with socket-level options
+        match options.socket.get_option(option) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res,
+        }
+
+        // Deal with IPv6-level options
+        if self.family == IpFamily::Ipv6 {
+            return options.ipv6.get_option(option);
+        }
+
+        return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to get is unknown")
     }
 
     fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
         let inner = self.inner.read();
         let mut options = self.options.write();
 
-        match options.socket.set_option(option, &*inner) {
+        let result = match options.socket.set_option(option, &*inner) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT && self.family == IpFamily::Ipv6 => {
+                options.ipv6.set_option(option, &*inner)
+            }
+            result => result,
+        };
+
+        match result {
             Err(e) => Err(e),
             Ok(need_iface_poll) => {
                 let iface_to_poll = need_iface_poll
@@ -258,3 +289,9 @@ impl Socket for DatagramSocket {
 }
 
 impl SetSocketLevelOption for Inner<UnboundDatagram, BoundDatagram> {}
+
+impl SetIpv6LevelOption for Inner<UnboundDatagram, BoundDatagram> {
+    fn is_bound(&self) -> bool {
+        matches!(self, Inner::Bound(_))
+    }
+}
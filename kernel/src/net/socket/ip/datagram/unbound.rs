@@ -57,7 +57,7 @@ impl datagram_common::Unbound for UnboundDatagram {
         remote_endpoint: &Self::Endpoint,
         pollee: &Pollee,
     ) -> Result<Self::Bound> {
-        let endpoint = get_ephemeral_endpoint(remote_endpoint);
+        let endpoint = get_ephemeral_endpoint(remote_endpoint)?;
         self.bind(&endpoint, pollee, BindOptions { can_reuse: false })
     }
 
@@ -6,4 +6,4 @@ pub mod datagram;
 pub mod options;
 pub mod stream;
 
-use addr::UNSPECIFIED_LOCAL_ENDPOINT;
+pub use addr::IpFamily;
@@ -84,8 +84,57 @@ impl_socket_options!(
     pub struct Tos(i32);
     pub struct Ttl(IpTtl);
     pub struct Hdrincl(bool);
+    pub struct V6Only(bool);
 );
 
+/// IPv6-level socket options.
+#[derive(Debug, Clone, Copy, CopyGetters, Setters)]
+#[get_copy = "pub"]
+#[set = "pub"]
+pub(super) struct Ipv6OptionSet {
+    v6only: bool,
+}
+
+impl Ipv6OptionSet {
+    pub(super) const fn new() -> Self {
+        Self { v6only: false }
+    }
+
+    pub(super) fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            ipv6_v6only: V6Only => {
+                let v6only = self.v6only();
+                ipv6_v6only.set(v6only);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown")
+        });
+
+        Ok(())
+    }
+
+    pub(super) fn set_option(
+        &mut self,
+        option: &dyn SocketOption,
+        socket: &dyn SetIpv6LevelOption,
+    ) -> Result<NeedIfacePoll> {
+        match_sock_option_ref!(option, {
+            ipv6_v6only: V6Only => {
+                if socket.is_bound() {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "IPV6_V6ONLY cannot be changed after the socket is bound"
+                    );
+                }
+                let v6only = ipv6_v6only.get().unwrap();
+                self.set_v6only(*v6only);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to be set is unknown")
+        });
+
+        Ok(NeedIfacePoll::FALSE)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct IpTtl(Option<NonZeroU8>);
 
@@ -106,3 +155,7 @@ impl IpTtl {
 pub trait SetIpLevelOption {
     fn set_hdrincl(&self, _hdrincl: bool) -> Result<()>;
 }
+
+pub trait SetIpv6LevelOption {
+    fn is_bound(&self) -> bool;
+}
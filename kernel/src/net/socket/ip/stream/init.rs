@@ -93,13 +93,26 @@ impl InitStream {
         let bound_port = if let Some(bound_port) = self.bound_port {
             bound_port
         } else {
-            let endpoint = get_ephemeral_endpoint(remote_endpoint);
+            let endpoint = match get_ephemeral_endpoint(remote_endpoint) {
+                Ok(endpoint) => endpoint,
+                Err(err) => return Err((err, self)),
+            };
             match bind_port(&endpoint, false) {
                 Ok(bound_port) => bound_port,
                 Err(err) => return Err((err, self)),
             }
         };
 
+        if bound_port.endpoint().addr.version() != remote_endpoint.addr.version() {
+            return Err((
+                Error::with_message(
+                    Errno::ENETUNREACH,
+                    "the local and remote addresses are of different IP versions",
+                ),
+                InitStream::new_bound(bound_port),
+            ));
+        }
+
         ConnectingStream::new(bound_port, *remote_endpoint, option, observer).map_err(
             |(err, bound_port)| {
                 if err.error() == Errno::ECONNREFUSED {
@@ -194,7 +207,7 @@ impl InitStream {
     pub fn local_endpoint(&self) -> Option<IpEndpoint> {
         self.bound_port
             .as_ref()
-            .map(|bound_port| bound_port.endpoint().unwrap())
+            .map(|bound_port| bound_port.endpoint())
     }
 
     pub(super) fn check_io_events(&self) -> IoEvents {
@@ -3,7 +3,7 @@
 use self::options::SocketOption;
 pub use self::util::{
     options::LingerOption, send_recv_flags::SendRecvFlags, shutdown_cmd::SockShutdownCmd,
-    socket_addr::SocketAddr, MessageHeader,
+    socket_addr::SocketAddr, CUserCred, ControlMessage, MessageHeader,
 };
 use crate::{
     fs::{
@@ -138,7 +138,7 @@ impl<T: Socket + 'static> FileLike for T {
             reader,
             MessageHeader {
                 addr: None,
-                control_message: None,
+                control_messages: Vec::new(),
             },
             SendRecvFlags::empty(),
         )
@@ -54,6 +54,11 @@ impl CAttrHeader {
     pub fn type_(&self) -> u16 {
         self.type_ & ATTRIBUTE_TYPE_MASK
     }
+
+    /// Returns the payload length (excluding padding).
+    pub fn payload_len(&self) -> usize {
+        (self.len as usize).saturating_sub(size_of::<Self>())
+    }
 }
 
 const IS_NESTED_MASK: u16 = 1u16 << 15;
@@ -4,18 +4,23 @@
 
 use core::num::NonZeroU32;
 
-use super::util::finish_response;
+use aster_bigtcp::wire::{Ipv4Address, Ipv4Cidr};
+
+use super::util::{check_net_admin, finish_response, get_iface_by_index};
 use crate::{
     net::{
-        iface::{iter_all_ifaces, Iface},
+        iface::Iface,
         socket::netlink::{
-            message::{CMsgSegHdr, CSegmentType, GetRequestFlags, SegHdrCommonFlags},
+            message::{
+                CMsgSegHdr, CSegmentType, GetRequestFlags, NewRequestFlags, SegHdrCommonFlags,
+            },
             route::message::{
                 AddrAttr, AddrMessageFlags, AddrSegment, AddrSegmentBody, RtScope, RtnlSegment,
             },
         },
     },
     prelude::*,
+    process::posix_thread::AsPosixThread,
     util::net::CSocketAddrFamily,
 };
 
@@ -28,7 +33,11 @@ pub(super) fn do_get_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegme
         return_errno_with_message!(Errno::EOPNOTSUPP, "GETADDR only supports dump requests");
     }
 
-    let mut response_segments: Vec<RtnlSegment> = iter_all_ifaces()
+    let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+    let mut response_segments: Vec<RtnlSegment> = ns_proxy
+        .net_ns()
+        .ifaces()
+        .iter()
         // GETADDR only supports dump mode, so we're going to report all addresses.
         .filter_map(|iface| iface_to_new_addr(request_segment.header(), iface))
         .map(RtnlSegment::NewAddr)
@@ -39,6 +48,90 @@ pub(super) fn do_get_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegme
     Ok(response_segments)
 }
 
+pub(super) fn do_new_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegment>> {
+    check_net_admin()?;
+
+    let body = request_segment.body();
+    if body.family != CSocketAddrFamily::AF_INET as i32 {
+        return_errno_with_message!(Errno::EAFNOSUPPORT, "only IPv4 addresses are supported");
+    }
+    if body.prefix_len > 32 {
+        return_errno_with_message!(Errno::EINVAL, "the prefix length is invalid");
+    }
+    let iface = get_iface_by_index(body.index)?;
+    let Some(addr) = addr_from_attrs(request_segment) else {
+        return_errno_with_message!(Errno::EINVAL, "the address is not specified");
+    };
+    let cidr = Ipv4Cidr::new(addr, body.prefix_len);
+
+    // TODO: Support multiple IPv4 addresses on an interface.
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    match iface.ipv4_addr() {
+        Some(old_addr) if old_addr == addr => {
+            if flags.contains(NewRequestFlags::EXCL) {
+                return_errno_with_message!(Errno::EEXIST, "the address already exists");
+            }
+        }
+        Some(_) => {
+            if !flags.contains(NewRequestFlags::REPLACE) {
+                return_errno_with_message!(
+                    Errno::EEXIST,
+                    "only one IPv4 address is supported on an interface"
+                );
+            }
+        }
+        None => {
+            if !flags.contains(NewRequestFlags::CREATE) {
+                return_errno_with_message!(Errno::ENOENT, "the address does not exist");
+            }
+        }
+    }
+
+    iface.set_ipv4_cidr(Some(cidr));
+
+    Ok(Vec::new())
+}
+
+pub(super) fn do_del_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegment>> {
+    check_net_admin()?;
+
+    let body = request_segment.body();
+    if body.family != CSocketAddrFamily::AF_INET as i32 {
+        return_errno_with_message!(Errno::EAFNOSUPPORT, "only IPv4 addresses are supported");
+    }
+    let iface = get_iface_by_index(body.index)?;
+
+    let Some(old_addr) = iface.ipv4_addr() else {
+        return_errno_with_message!(Errno::EADDRNOTAVAIL, "the interface has no IPv4 address");
+    };
+    if addr_from_attrs(request_segment).is_some_and(|addr| addr != old_addr) {
+        return_errno_with_message!(Errno::EADDRNOTAVAIL, "the address does not exist");
+    }
+
+    // Like Linux, the routes via the address are removed together with the address.
+    iface.set_ipv4_gateway(None);
+    iface.set_ipv4_cidr(None);
+
+    Ok(Vec::new())
+}
+
+/// Gets the address from the `IFA_LOCAL` or `IFA_ADDRESS` attribute.
+///
+/// Like Linux, `IFA_LOCAL` takes precedence over `IFA_ADDRESS`.
+fn addr_from_attrs(request_segment: &AddrSegment) -> Option<Ipv4Address> {
+    let attrs = request_segment.attrs();
+    let local = attrs.iter().find_map(|attr| match attr {
+        AddrAttr::Local(local) => Some(*local),
+        _ => None,
+    });
+    let address = attrs.iter().find_map(|attr| match attr {
+        AddrAttr::Address(address) => Some(*address),
+        _ => None,
+    });
+
+    local.or(address).map(Ipv4Address::from)
+}
+
 fn iface_to_new_addr(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> Option<AddrSegment> {
     let ipv4_addr = iface.ipv4_addr()?;
 
@@ -52,7 +145,7 @@ fn iface_to_new_addr(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> Option<
 
     let addr_message = AddrSegmentBody {
         family: CSocketAddrFamily::AF_INET as _,
-        prefix_len: iface.prefix_len().unwrap(),
+        prefix_len: iface.prefix_len()?,
         flags: AddrMessageFlags::PERMANENT,
         scope: RtScope::HOST,
         index: NonZeroU32::new(iface.index()),
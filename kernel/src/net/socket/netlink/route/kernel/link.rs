@@ -9,20 +9,25 @@ use aster_bigtcp::iface::InterfaceType;
 use super::util::finish_response;
 use crate::{
     net::{
-        iface::{iter_all_ifaces, Iface},
+        iface::Iface,
         socket::netlink::{
             message::{CMsgSegHdr, CSegmentType, GetRequestFlags, SegHdrCommonFlags},
             route::message::{LinkAttr, LinkSegment, LinkSegmentBody, RtnlSegment},
         },
     },
     prelude::*,
+    process::posix_thread::AsPosixThread,
     util::net::CSocketAddrFamily,
 };
 
 pub(super) fn do_get_link(request_segment: &LinkSegment) -> Result<Vec<RtnlSegment>> {
     let filter_by = FilterBy::from_request(request_segment)?;
 
-    let mut response_segments: Vec<RtnlSegment> = iter_all_ifaces()
+    let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+    let mut response_segments: Vec<RtnlSegment> = ns_proxy
+        .net_ns()
+        .ifaces()
+        .iter()
         // Filter to include only requested links.
         .filter(|iface| match &filter_by {
             FilterBy::Index(index) => *index == iface.index(),
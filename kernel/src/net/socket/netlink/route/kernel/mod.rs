@@ -7,12 +7,15 @@ use core::marker::PhantomData;
 
 use super::message::{RtnlMessage, RtnlSegment};
 use crate::{
-    net::socket::netlink::message::{CSegmentType, ErrorSegment, ProtocolSegment},
+    net::socket::netlink::message::{
+        CSegmentType, ErrorSegment, ProtocolSegment, SegHdrCommonFlags,
+    },
     prelude::*,
 };
 
 mod addr;
 mod link;
+mod route;
 mod util;
 
 pub(super) struct NetlinkRouteKernelSocket {
@@ -40,7 +43,11 @@ impl NetlinkRouteKernelSocket {
 
             let response_segments = match segment {
                 RtnlSegment::GetLink(request_segment) => link::do_get_link(request_segment),
+                RtnlSegment::NewAddr(request_segment) => addr::do_new_addr(request_segment),
+                RtnlSegment::DelAddr(request_segment) => addr::do_del_addr(request_segment),
                 RtnlSegment::GetAddr(request_segment) => addr::do_get_addr(request_segment),
+                RtnlSegment::NewRoute(request_segment) => route::do_new_route(request_segment),
+                RtnlSegment::DelRoute(request_segment) => route::do_del_route(request_segment),
                 _ => {
                     // FIXME: The error is currently silently ignored.
                     warn!("unsupported request type: {:?}", segment_type);
@@ -49,7 +56,17 @@ impl NetlinkRouteKernelSocket {
             };
 
             let response = match response_segments {
-                Ok(segments) => RtnlMessage::new(segments),
+                Ok(segments) if !segments.is_empty() => RtnlMessage::new(segments),
+                Ok(_) => {
+                    // Requests that modify the configuration have no responses. An
+                    // acknowledgment is sent only if the `ACK` flag is set.
+                    let flags = SegHdrCommonFlags::from_bits_truncate(request_header.flags);
+                    if !flags.contains(SegHdrCommonFlags::ACK) {
+                        continue;
+                    }
+                    let ack_segment = ErrorSegment::new_from_request(request_header, None);
+                    RtnlMessage::new(vec![RtnlSegment::Error(ack_segment)])
+                }
                 Err(error) => {
                     // TODO: Deal with the `NetlinkMessageCommonFlags::ACK` flag.
                     // Should we return `ErrorSegment` if ACK flag does not exist?
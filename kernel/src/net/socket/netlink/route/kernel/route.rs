@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle route-related requests.
+
+use core::num::NonZeroU32;
+
+use aster_bigtcp::wire::{IpAddress, Ipv4Address};
+
+use super::util::{check_net_admin, get_iface_by_index};
+use crate::{
+    net::{
+        iface::Iface,
+        socket::netlink::{
+            message::NewRequestFlags,
+            route::message::{RouteAttr, RouteSegment, RtnlSegment, RTN_UNICAST, RT_TABLE_MAIN},
+        },
+    },
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+    util::net::CSocketAddrFamily,
+};
+
+pub(super) fn do_new_route(request_segment: &RouteSegment) -> Result<Vec<RtnlSegment>> {
+    check_net_admin()?;
+
+    let request = DefaultRouteRequest::from_segment(request_segment)?;
+    if request_segment.body().type_ != RTN_UNICAST {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "only unicast routes are supported");
+    }
+    let Some(gateway) = request.gateway else {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "only default routes via gateways are supported"
+        );
+    };
+
+    let ifaces = current_ifaces();
+    let iface = match request.oif {
+        Some(_) => get_iface_by_index(request.oif)?,
+        None => ifaces
+            .iter()
+            .find(|iface| iface.is_on_link(IpAddress::Ipv4(gateway)))
+            .cloned()
+            .ok_or_else(|| {
+                Error::with_message(Errno::ENETUNREACH, "the gateway is not reachable")
+            })?,
+    };
+    if !iface.is_on_link(IpAddress::Ipv4(gateway)) {
+        return_errno_with_message!(Errno::ENETUNREACH, "the gateway is not reachable");
+    }
+
+    // TODO: Support multiple default routes with different priorities.
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    let old_ifaces: Vec<_> = ifaces
+        .iter()
+        .filter(|iface| iface.ipv4_gateway().is_some())
+        .collect();
+    if !old_ifaces.is_empty() {
+        if flags.contains(NewRequestFlags::EXCL) || !flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EEXIST, "the default route already exists");
+        }
+        for old_iface in old_ifaces {
+            old_iface.set_ipv4_gateway(None);
+        }
+    } else if !flags.contains(NewRequestFlags::CREATE) {
+        return_errno_with_message!(Errno::ENOENT, "the default route does not exist");
+    }
+
+    iface.set_ipv4_gateway(Some(gateway));
+
+    Ok(Vec::new())
+}
+
+pub(super) fn do_del_route(request_segment: &RouteSegment) -> Result<Vec<RtnlSegment>> {
+    check_net_admin()?;
+
+    let request = DefaultRouteRequest::from_segment(request_segment)?;
+
+    let ifaces = current_ifaces();
+    let Some(iface) = ifaces.iter().find(|iface| {
+        let Some(gateway) = iface.ipv4_gateway() else {
+            return false;
+        };
+        request.oif.is_none_or(|oif| oif.get() == iface.index())
+            && request.gateway.is_none_or(|addr| addr == gateway)
+    }) else {
+        return_errno_with_message!(Errno::ESRCH, "the route does not exist");
+    };
+
+    iface.set_ipv4_gateway(None);
+
+    Ok(Vec::new())
+}
+
+/// A request to add or delete the IPv4 default route.
+struct DefaultRouteRequest {
+    gateway: Option<Ipv4Address>,
+    oif: Option<NonZeroU32>,
+}
+
+impl DefaultRouteRequest {
+    fn from_segment(request_segment: &RouteSegment) -> Result<Self> {
+        let body = request_segment.body();
+        if body.family != CSocketAddrFamily::AF_INET as i32 {
+            return_errno_with_message!(Errno::EAFNOSUPPORT, "only IPv4 routes are supported");
+        }
+
+        let mut table = body.table as u32;
+        let mut gateway = None;
+        let mut oif = None;
+        for attr in request_segment.attrs() {
+            match attr {
+                RouteAttr::Dst(dst) if *dst != [0; 4] => {
+                    return_errno_with_message!(
+                        Errno::EOPNOTSUPP,
+                        "only default routes are supported"
+                    );
+                }
+                RouteAttr::Gateway(addr) => gateway = Some(Ipv4Address::from(*addr)),
+                RouteAttr::Oif(index) => oif = NonZeroU32::new(*index),
+                RouteAttr::Table(id) => table = *id,
+                // The priority and the preferred source address are ignored since there can be
+                // only one default route.
+                RouteAttr::Dst(_) | RouteAttr::Priority(_) | RouteAttr::PrefSrc(_) => (),
+            }
+        }
+
+        if body.dst_len != 0 {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "only default routes are supported");
+        }
+        // TODO: Support other routing tables.
+        if table != 0 && table != RT_TABLE_MAIN as u32 {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "only the main table is supported");
+        }
+
+        Ok(Self { gateway, oif })
+    }
+}
+
+fn current_ifaces() -> Vec<Arc<Iface>> {
+    let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+    ns_proxy.net_ns().ifaces().to_vec()
+}
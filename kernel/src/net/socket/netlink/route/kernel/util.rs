@@ -1,11 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::num::NonZeroU32;
+
 use crate::{
-    net::socket::netlink::{
-        message::{CMsgSegHdr, DoneSegment, ProtocolSegment, SegHdrCommonFlags},
-        route::message::RtnlSegment,
+    net::{
+        iface::Iface,
+        socket::netlink::{
+            message::{CMsgSegHdr, DoneSegment, ProtocolSegment, SegHdrCommonFlags},
+            route::message::RtnlSegment,
+        },
     },
     prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::AsPosixThread},
 };
 
 /// Finishes a response message.
@@ -37,3 +43,28 @@ fn add_multi_flag(response_segments: &mut Vec<RtnlSegment>) {
         header.flags = flags.bits();
     }
 }
+
+/// Checks whether the current thread can modify the network configuration.
+pub fn check_net_admin() -> Result<()> {
+    let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+    if !credentials.effective_capset().contains(CapSet::NET_ADMIN) {
+        return_errno_with_message!(Errno::EPERM, "CAP_NET_ADMIN is required");
+    }
+    Ok(())
+}
+
+/// Gets the interface in the current network namespace by its index.
+pub fn get_iface_by_index(index: Option<NonZeroU32>) -> Result<Arc<Iface>> {
+    let Some(index) = index else {
+        return_errno_with_message!(Errno::ENODEV, "the interface is not specified");
+    };
+
+    let ns_proxy = current_thread!().as_posix_thread().unwrap().ns_proxy();
+    let iface = ns_proxy
+        .net_ns()
+        .ifaces()
+        .iter()
+        .find(|iface| iface.index() == index.get())
+        .cloned();
+    iface.ok_or_else(|| Error::with_message(Errno::ENODEV, "the interface does not exist"))
+}
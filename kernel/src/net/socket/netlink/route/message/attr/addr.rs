@@ -32,6 +32,7 @@ pub enum AddrAttr {
     Address([u8; 4]),
     Local([u8; 4]),
     Label(CString),
+    Broadcast([u8; 4]),
 }
 
 impl AddrAttr {
@@ -40,6 +41,7 @@ impl AddrAttr {
             AddrAttr::Address(_) => AddrAttrClass::ADDRESS,
             AddrAttr::Local(_) => AddrAttrClass::LOCAL,
             AddrAttr::Label(_) => AddrAttrClass::LABEL,
+            AddrAttr::Broadcast(_) => AddrAttrClass::BROADCAST,
         }
     }
 }
@@ -54,6 +56,7 @@ impl Attribute for AddrAttr {
             AddrAttr::Address(address) => address,
             AddrAttr::Local(local) => local,
             AddrAttr::Label(label) => label.as_bytes_with_nul(),
+            AddrAttr::Broadcast(broadcast) => broadcast,
         }
     }
 
@@ -63,10 +66,21 @@ impl Attribute for AddrAttr {
     {
         let header = reader.read_val::<CAttrHeader>()?;
         // TODO: Currently, `IS_NET_BYTEORDER_MASK` and `IS_NESTED_MASK` are ignored.
-        let res = match AddrAttrClass::try_from(header.type_())? {
+        let class = AddrAttrClass::try_from(header.type_())?;
+        // TODO: Support IPv6 addresses.
+        if matches!(
+            class,
+            AddrAttrClass::ADDRESS | AddrAttrClass::LOCAL | AddrAttrClass::BROADCAST
+        ) && header.payload_len() != size_of::<[u8; 4]>()
+        {
+            return_errno_with_message!(Errno::EAFNOSUPPORT, "only IPv4 addresses are supported");
+        }
+
+        let res = match class {
             AddrAttrClass::ADDRESS => Self::Address(reader.read_val()?),
             AddrAttrClass::LOCAL => Self::Local(reader.read_val()?),
             AddrAttrClass::LABEL => Self::Label(reader.read_cstring_with_max_len(IFNAME_SIZE)?),
+            AddrAttrClass::BROADCAST => Self::Broadcast(reader.read_val()?),
             class => {
                 // FIXME: Netlink should ignore all unknown attributes.
                 // See the reference in `LinkAttr::read_from`.
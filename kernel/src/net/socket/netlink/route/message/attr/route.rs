@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    net::socket::netlink::message::{Attribute, CAttrHeader},
+    prelude::*,
+    util::MultiRead,
+};
+
+/// Route-related attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h#L370>.
+#[derive(Debug, Clone, Copy, TryFromInt)]
+#[repr(u16)]
+#[expect(non_camel_case_types)]
+enum RouteAttrClass {
+    UNSPEC = 0,
+    DST = 1,
+    SRC = 2,
+    IIF = 3,
+    OIF = 4,
+    GATEWAY = 5,
+    PRIORITY = 6,
+    PREFSRC = 7,
+    METRICS = 8,
+    MULTIPATH = 9,
+    PROTOINFO = 10,
+    FLOW = 11,
+    CACHEINFO = 12,
+    SESSION = 13,
+    MP_ALGO = 14,
+    TABLE = 15,
+}
+
+#[derive(Debug)]
+pub enum RouteAttr {
+    Dst([u8; 4]),
+    Oif(u32),
+    Gateway([u8; 4]),
+    Priority(u32),
+    PrefSrc([u8; 4]),
+    Table(u32),
+}
+
+impl RouteAttr {
+    fn class(&self) -> RouteAttrClass {
+        match self {
+            RouteAttr::Dst(_) => RouteAttrClass::DST,
+            RouteAttr::Oif(_) => RouteAttrClass::OIF,
+            RouteAttr::Gateway(_) => RouteAttrClass::GATEWAY,
+            RouteAttr::Priority(_) => RouteAttrClass::PRIORITY,
+            RouteAttr::PrefSrc(_) => RouteAttrClass::PREFSRC,
+            RouteAttr::Table(_) => RouteAttrClass::TABLE,
+        }
+    }
+}
+
+impl Attribute for RouteAttr {
+    fn type_(&self) -> u16 {
+        self.class() as u16
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            RouteAttr::Dst(dst) => dst,
+            RouteAttr::Oif(oif) => oif.as_bytes(),
+            RouteAttr::Gateway(gateway) => gateway,
+            RouteAttr::Priority(priority) => priority.as_bytes(),
+            RouteAttr::PrefSrc(pref_src) => pref_src,
+            RouteAttr::Table(table) => table.as_bytes(),
+        }
+    }
+
+    fn read_from(reader: &mut dyn MultiRead) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let header = reader.read_val::<CAttrHeader>()?;
+        let class = RouteAttrClass::try_from(header.type_())?;
+        // TODO: Support IPv6 addresses.
+        if matches!(
+            class,
+            RouteAttrClass::DST | RouteAttrClass::GATEWAY | RouteAttrClass::PREFSRC
+        ) && header.payload_len() != size_of::<[u8; 4]>()
+        {
+            return_errno_with_message!(Errno::EAFNOSUPPORT, "only IPv4 addresses are supported");
+        }
+
+        // TODO: Currently, `IS_NET_BYTEORDER_MASK` and `IS_NESTED_MASK` are ignored.
+        let res = match class {
+            RouteAttrClass::DST => Self::Dst(reader.read_val()?),
+            RouteAttrClass::OIF => Self::Oif(reader.read_val()?),
+            RouteAttrClass::GATEWAY => Self::Gateway(reader.read_val()?),
+            RouteAttrClass::PRIORITY => Self::Priority(reader.read_val()?),
+            RouteAttrClass::PREFSRC => Self::PrefSrc(reader.read_val()?),
+            RouteAttrClass::TABLE => Self::Table(reader.read_val()?),
+            class => {
+                // FIXME: Netlink should ignore all unknown attributes.
+                // See the reference in `LinkAttr::read_from`.
+                warn!("route attribute `{:?}` is not supported", class);
+                return_errno_with_message!(Errno::EINVAL, "unsupported route attribute");
+            }
+        };
+
+        Ok(res)
+    }
+}
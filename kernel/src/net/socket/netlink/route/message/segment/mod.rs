@@ -36,6 +36,7 @@ pub mod route;
 
 use addr::AddrSegment;
 use link::LinkSegment;
+use route::RouteSegment;
 
 use crate::{
     net::socket::netlink::message::{
@@ -51,7 +52,10 @@ pub enum RtnlSegment {
     NewLink(LinkSegment),
     GetLink(LinkSegment),
     NewAddr(AddrSegment),
+    DelAddr(AddrSegment),
     GetAddr(AddrSegment),
+    NewRoute(RouteSegment),
+    DelRoute(RouteSegment),
     Done(DoneSegment),
     Error(ErrorSegment),
 }
@@ -62,8 +66,11 @@ impl ProtocolSegment for RtnlSegment {
             RtnlSegment::NewLink(link_segment) | RtnlSegment::GetLink(link_segment) => {
                 link_segment.header()
             }
-            RtnlSegment::NewAddr(addr_segment) | RtnlSegment::GetAddr(addr_segment) => {
-                addr_segment.header()
+            RtnlSegment::NewAddr(addr_segment)
+            | RtnlSegment::DelAddr(addr_segment)
+            | RtnlSegment::GetAddr(addr_segment) => addr_segment.header(),
+            RtnlSegment::NewRoute(route_segment) | RtnlSegment::DelRoute(route_segment) => {
+                route_segment.header()
             }
             RtnlSegment::Done(done_segment) => done_segment.header(),
             RtnlSegment::Error(error_segment) => error_segment.header(),
@@ -75,8 +82,11 @@ impl ProtocolSegment for RtnlSegment {
             RtnlSegment::NewLink(link_segment) | RtnlSegment::GetLink(link_segment) => {
                 link_segment.header_mut()
             }
-            RtnlSegment::NewAddr(addr_segment) | RtnlSegment::GetAddr(addr_segment) => {
-                addr_segment.header_mut()
+            RtnlSegment::NewAddr(addr_segment)
+            | RtnlSegment::DelAddr(addr_segment)
+            | RtnlSegment::GetAddr(addr_segment) => addr_segment.header_mut(),
+            RtnlSegment::NewRoute(route_segment) | RtnlSegment::DelRoute(route_segment) => {
+                route_segment.header_mut()
             }
             RtnlSegment::Done(done_segment) => done_segment.header_mut(),
             RtnlSegment::Error(error_segment) => error_segment.header_mut(),
@@ -88,7 +98,15 @@ impl ProtocolSegment for RtnlSegment {
 
         let segment = match CSegmentType::try_from(header.type_)? {
             CSegmentType::GETLINK => RtnlSegment::GetLink(LinkSegment::read_from(header, reader)?),
+            CSegmentType::NEWADDR => RtnlSegment::NewAddr(AddrSegment::read_from(header, reader)?),
+            CSegmentType::DELADDR => RtnlSegment::DelAddr(AddrSegment::read_from(header, reader)?),
             CSegmentType::GETADDR => RtnlSegment::GetAddr(AddrSegment::read_from(header, reader)?),
+            CSegmentType::NEWROUTE => {
+                RtnlSegment::NewRoute(RouteSegment::read_from(header, reader)?)
+            }
+            CSegmentType::DELROUTE => {
+                RtnlSegment::DelRoute(RouteSegment::read_from(header, reader)?)
+            }
             _ => return_errno_with_message!(Errno::EINVAL, "unsupported segment type"),
         };
 
@@ -101,8 +119,12 @@ impl ProtocolSegment for RtnlSegment {
             RtnlSegment::NewAddr(addr_segment) => addr_segment.write_to(writer)?,
             RtnlSegment::Done(done_segment) => done_segment.write_to(writer)?,
             RtnlSegment::Error(error_segment) => error_segment.write_to(writer)?,
-            RtnlSegment::GetAddr(_) | RtnlSegment::GetLink(_) => {
-                unreachable!("kernel should not write get requests to user space");
+            RtnlSegment::GetAddr(_)
+            | RtnlSegment::GetLink(_)
+            | RtnlSegment::DelAddr(_)
+            | RtnlSegment::NewRoute(_)
+            | RtnlSegment::DelRoute(_) => {
+                unreachable!("kernel should not write requests to user space");
             }
         }
         Ok(())
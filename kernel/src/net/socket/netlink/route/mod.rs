@@ -125,7 +125,7 @@ impl Socket for NetlinkRouteSocket {
     ) -> Result<usize> {
         let MessageHeader {
             addr,
-            control_message,
+            control_messages,
         } = message_header;
 
         let remote = match addr {
@@ -133,7 +133,7 @@ impl Socket for NetlinkRouteSocket {
             Some(addr) => Some(addr.try_into()?),
         };
 
-        if control_message.is_some() {
+        if !control_messages.is_empty() {
             // TODO: Support sending control message
             warn!("sending control message is not supported");
         }
@@ -151,7 +151,7 @@ impl Socket for NetlinkRouteSocket {
 
         // TODO: Receive control message
 
-        let message_header = MessageHeader::new(Some(addr), None);
+        let message_header = MessageHeader::new(Some(addr), Vec::new());
 
         Ok((received_len, message_header))
     }
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::gc::InflightFiles;
+use crate::{
+    fs::file_handle::FileLike,
+    net::socket::{CUserCred, ControlMessage},
+    prelude::*,
+};
+
+/// The ancillary data that is sent along with the data over UNIX sockets.
+pub(super) struct AncillaryData {
+    /// The files passed with `SCM_RIGHTS`.
+    pub(super) files: Option<InflightFiles>,
+    /// The credentials of the sender.
+    pub(super) cred: CUserCred,
+}
+
+impl AncillaryData {
+    /// Creates the ancillary data from the control messages to send.
+    ///
+    /// If the control messages do not specify the credentials, the credentials of the current
+    /// process are used.
+    pub(super) fn from_control_messages(control_messages: Vec<ControlMessage>) -> Self {
+        let mut files = Vec::new();
+        let mut cred = None;
+
+        for control_message in control_messages {
+            match control_message {
+                ControlMessage::Rights(rights) => files.extend(rights),
+                ControlMessage::Credentials(credentials) => cred = Some(credentials),
+            }
+        }
+
+        Self {
+            files: (!files.is_empty()).then(|| InflightFiles::new(files)),
+            cred: cred.unwrap_or_else(CUserCred::new_current),
+        }
+    }
+
+    /// Converts the received ancillary data into control messages.
+    ///
+    /// The credentials are included only if `is_pass_cred` is true (i.e., `SO_PASSCRED` is set).
+    pub(super) fn into_control_messages(self, is_pass_cred: bool) -> Vec<ControlMessage> {
+        let files = self.files.map(InflightFiles::into_files);
+        Self::build_control_messages(files, self.cred, is_pass_cred)
+    }
+
+    /// Converts the received ancillary data into control messages, without consuming it.
+    ///
+    /// This is used by `MSG_PEEK`.
+    pub(super) fn clone_control_messages(&self, is_pass_cred: bool) -> Vec<ControlMessage> {
+        let files = self.files.as_ref().map(InflightFiles::clone_files);
+        Self::build_control_messages(files, self.cred, is_pass_cred)
+    }
+
+    fn build_control_messages(
+        files: Option<Vec<Arc<dyn FileLike>>>,
+        cred: CUserCred,
+        is_pass_cred: bool,
+    ) -> Vec<ControlMessage> {
+        let mut control_messages = Vec::new();
+
+        // Like Linux, the credentials come before the files.
+        if is_pass_cred {
+            control_messages.push(ControlMessage::Credentials(cred));
+        }
+        if let Some(files) = files {
+            control_messages.push(ControlMessage::Rights(files));
+        }
+
+        control_messages
+    }
+}
@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::sync::WaitQueue;
+
+use crate::{
+    events::IoEvents,
+    fs::file_handle::FileLike,
+    net::socket::{
+        unix::{
+            addr::UnixSocketAddrKey, ancillary::AncillaryData, gc::InflightFiles, UnixSocketAddr,
+        },
+        ControlMessage,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollee},
+    util::MultiWrite,
+};
+
+/// A datagram sent to a UNIX domain socket.
+pub(super) struct Message {
+    data: Vec<u8>,
+    /// The address of the sending socket.
+    src_addr: UnixSocketAddr,
+    ancillary_data: AncillaryData,
+}
+
+impl Message {
+    pub(super) fn new(
+        data: Vec<u8>,
+        src_addr: UnixSocketAddr,
+        ancillary_data: AncillaryData,
+    ) -> Self {
+        Self {
+            data,
+            src_addr,
+            ancillary_data,
+        }
+    }
+}
+
+/// The receive queue of a UNIX datagram socket.
+pub(super) struct MessageQueue {
+    inner: Mutex<QueueInner>,
+    pollee: Pollee,
+    /// The wait queue of the senders that are waiting for free space.
+    wait_queue: WaitQueue,
+}
+
+struct QueueInner {
+    messages: VecDeque<Message>,
+    /// The total number of bytes of the queued messages.
+    total_len: usize,
+    is_shutdown: bool,
+}
+
+impl MessageQueue {
+    pub(super) fn new() -> Self {
+        let inner = QueueInner {
+            messages: VecDeque::new(),
+            total_len: 0,
+            is_shutdown: false,
+        };
+
+        Self {
+            inner: Mutex::new(inner),
+            pollee: Pollee::new(),
+            wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Tries to push a message to the queue.
+    ///
+    /// - Returns `Err(EPIPE)` if the queue is shut down.
+    /// - Returns `Err(EAGAIN)` if the queue is full.
+    pub(super) fn try_push(&self, message: Message) -> core::result::Result<(), (Error, Message)> {
+        let mut inner = self.inner.lock();
+
+        if inner.is_shutdown {
+            let err = Error::with_message(Errno::EPIPE, "the remote socket is shut down");
+            return Err((err, message));
+        }
+
+        // Like Linux, a message is always accepted if the queue is empty, so messages that fit in
+        // `MAX_MESSAGE_LEN` never block forever.
+        if !inner.messages.is_empty() && inner.total_len + message.data.len() > QUEUE_CAPACITY {
+            let err = Error::with_message(Errno::EAGAIN, "the receive queue is full");
+            return Err((err, message));
+        }
+
+        inner.total_len += message.data.len();
+        inner.messages.push_back(message);
+        drop(inner);
+
+        self.pollee.notify(IoEvents::IN);
+
+        Ok(())
+    }
+
+    /// Pushes a message to the queue, waiting for free space if the queue is full.
+    pub(super) fn push(&self, message: Message) -> Result<()> {
+        let mut message = Some(message);
+
+        self.wait_queue
+            .pause_until(|| match self.try_push(message.take().unwrap()) {
+                Ok(()) => Some(Ok(())),
+                Err((err, returned)) if err.error() == Errno::EAGAIN => {
+                    message = Some(returned);
+                    None
+                }
+                Err((err, _)) => Some(Err(err)),
+            })?
+    }
+
+    /// Tries to receive a message from the queue.
+    ///
+    /// If `is_peek` is true, the message is left in the queue.
+    ///
+    /// This method returns the number of bytes copied to `writer`, the address of the sending
+    /// socket, and the control messages. If `writer` is too small, the rest of the message is
+    /// discarded. The credentials are included in the control messages only if `is_pass_cred` is
+    /// true.
+    pub(super) fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        is_peek: bool,
+        is_pass_cred: bool,
+    ) -> Result<(usize, UnixSocketAddr, Vec<ControlMessage>)> {
+        let mut inner = self.inner.lock();
+
+        let Some(message) = inner.messages.front() else {
+            if inner.is_shutdown {
+                return Ok((0, UnixSocketAddr::Unnamed, Vec::new()));
+            }
+            return_errno_with_message!(Errno::EAGAIN, "the receive queue is empty");
+        };
+
+        let copied_len = writer.write(&mut VmReader::from(message.data.as_slice()))?;
+        let src_addr = message.src_addr.clone();
+
+        if is_peek {
+            let control_messages = message.ancillary_data.clone_control_messages(is_pass_cred);
+            return Ok((copied_len, src_addr, control_messages));
+        }
+
+        let message = inner.messages.pop_front().unwrap();
+        inner.total_len -= message.data.len();
+        drop(inner);
+
+        self.pollee.invalidate();
+        self.wait_queue.wake_all();
+
+        let control_messages = message.ancillary_data.into_control_messages(is_pass_cred);
+        Ok((copied_len, src_addr, control_messages))
+    }
+
+    /// Shuts down the queue so that no more messages can be received.
+    pub(super) fn shutdown(&self) {
+        self.inner.lock().is_shutdown = true;
+
+        self.pollee.notify(IoEvents::HUP);
+        self.wait_queue.wake_all();
+    }
+
+    pub(super) fn try_for_each_inflight(&self, f: &mut dyn FnMut(&Arc<dyn FileLike>)) -> bool {
+        let Some(inner) = self.inner.try_lock() else {
+            return false;
+        };
+
+        inner
+            .messages
+            .iter()
+            .filter_map(|message| message.ancillary_data.files.as_ref())
+            .flat_map(|files| files.files())
+            .for_each(f);
+
+        true
+    }
+
+    pub(super) fn try_purge_inflight(&self, purged: &mut Vec<InflightFiles>) -> bool {
+        let Some(mut inner) = self.inner.try_lock() else {
+            return false;
+        };
+
+        purged.extend(
+            inner
+                .messages
+                .iter_mut()
+                .filter_map(|message| message.ancillary_data.files.take()),
+        );
+
+        true
+    }
+
+    pub(super) fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let inner = self.inner.lock();
+
+        let mut events = IoEvents::empty();
+        if inner.is_shutdown {
+            events |= IoEvents::HUP;
+        }
+        if !inner.messages.is_empty() {
+            events |= IoEvents::IN;
+        }
+        events
+    }
+}
+
+/// The maximum number of bytes in a single message.
+pub(super) const MAX_MESSAGE_LEN: usize = QUEUE_CAPACITY;
+
+/// The maximum number of bytes that can be queued for a receiving socket.
+const QUEUE_CAPACITY: usize = 65536;
+
+static QUEUE_TABLE: QueueTable = QueueTable::new();
+
+/// The table of the receive queues of the bound datagram sockets.
+struct QueueTable {
+    queues: RwLock<BTreeMap<UnixSocketAddrKey, Weak<MessageQueue>>>,
+}
+
+impl QueueTable {
+    const fn new() -> Self {
+        Self {
+            queues: RwLock::new(BTreeMap::new()),
+        }
+    }
+}
+
+/// Registers the receive queue of a datagram socket that is bound to `addr`.
+pub(super) fn register_queue(addr: UnixSocketAddrKey, queue: &Arc<MessageQueue>) {
+    QUEUE_TABLE
+        .queues
+        .write()
+        .insert(addr, Arc::downgrade(queue));
+}
+
+/// Unregisters the receive queue of a datagram socket that was bound to `addr`.
+pub(super) fn unregister_queue(addr: &UnixSocketAddrKey) {
+    QUEUE_TABLE.queues.write().remove(addr);
+}
+
+/// Looks up the receive queue of the datagram socket that is bound to `addr`.
+pub(super) fn lookup_queue(addr: &UnixSocketAddrKey) -> Result<Arc<MessageQueue>> {
+    QUEUE_TABLE
+        .queues
+        .read()
+        .get(addr)
+        .and_then(Weak::upgrade)
+        .ok_or_else(|| {
+            Error::with_message(
+                Errno::ECONNREFUSED,
+                "no datagram socket is bound to the remote address",
+            )
+        })
+}
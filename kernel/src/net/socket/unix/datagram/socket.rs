@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{
+    ops::Sub,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use super::message::{
+    lookup_queue, register_queue, unregister_queue, Message, MessageQueue, MAX_MESSAGE_LEN,
+};
+use crate::{
+    events::IoEvents,
+    fs::file_handle::FileLike,
+    match_sock_option_mut, match_sock_option_ref,
+    net::socket::{
+        options::{PassCred, PeerCred, SocketOption},
+        private::SocketPrivate,
+        unix::{
+            addr::UnixSocketAddrBound,
+            ancillary::AncillaryData,
+            gc::{self, InflightFiles, InflightQueue},
+            UnixSocketAddr,
+        },
+        util::{send_recv_flags::SendRecvFlags, socket_addr::SocketAddr, MessageHeader},
+        CUserCred, SockShutdownCmd, Socket,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable},
+    util::{MultiRead, MultiWrite},
+};
+
+/// A UNIX domain socket of the `SOCK_DGRAM` type.
+pub struct UnixDatagramSocket {
+    receive_queue: Arc<MessageQueue>,
+    state: Mutex<State>,
+    is_nonblocking: AtomicBool,
+    is_write_shutdown: AtomicBool,
+    /// Whether the credentials are received as control messages (i.e., `SO_PASSCRED`).
+    is_pass_cred: AtomicBool,
+    /// The credentials of the peer socket, which are only known for socket pairs.
+    peer_cred: CUserCred,
+}
+
+struct State {
+    addr: Option<UnixSocketAddrBound>,
+    peer: Option<Peer>,
+}
+
+/// The socket to which the datagrams are sent by default.
+struct Peer {
+    addr: UnixSocketAddr,
+    queue: Weak<MessageQueue>,
+}
+
+impl UnixDatagramSocket {
+    pub fn new(is_nonblocking: bool) -> Arc<Self> {
+        Arc::new(Self::new_unconnected(
+            is_nonblocking,
+            CUserCred::new_unknown(),
+        ))
+    }
+
+    pub fn new_pair(is_nonblocking: bool) -> (Arc<Self>, Arc<Self>) {
+        let cred = CUserCred::new_current_peer();
+        let socket_a = Self::new_unconnected(is_nonblocking, cred);
+        let socket_b = Self::new_unconnected(is_nonblocking, cred);
+
+        socket_a.state.lock().peer = Some(Peer {
+            addr: UnixSocketAddr::Unnamed,
+            queue: Arc::downgrade(&socket_b.receive_queue),
+        });
+        socket_b.state.lock().peer = Some(Peer {
+            addr: UnixSocketAddr::Unnamed,
+            queue: Arc::downgrade(&socket_a.receive_queue),
+        });
+
+        (Arc::new(socket_a), Arc::new(socket_b))
+    }
+
+    fn new_unconnected(is_nonblocking: bool, peer_cred: CUserCred) -> Self {
+        Self {
+            receive_queue: Arc::new(MessageQueue::new()),
+            state: Mutex::new(State {
+                addr: None,
+                peer: None,
+            }),
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_write_shutdown: AtomicBool::new(false),
+            is_pass_cred: AtomicBool::new(false),
+            peer_cred,
+        }
+    }
+
+    /// Selects the receive queue of the remote socket.
+    ///
+    /// The address specified in the system call (e.g., `sendto`) is preferred, otherwise the
+    /// connected peer is used.
+    fn select_remote(&self, remote: Option<SocketAddr>) -> Result<Arc<MessageQueue>> {
+        if let Some(remote) = remote {
+            let remote_key = UnixSocketAddr::try_from(remote)?.connect()?;
+            return lookup_queue(&remote_key);
+        }
+
+        let state = self.state.lock();
+        let Some(peer) = state.peer.as_ref() else {
+            return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected");
+        };
+
+        peer.queue.upgrade().ok_or_else(|| {
+            Error::with_message(Errno::ECONNREFUSED, "the peer socket has been closed")
+        })
+    }
+}
+
+impl Pollable for UnixDatagramSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        let reader_events = self.receive_queue.poll(mask, poller);
+
+        let mut events = reader_events & IoEvents::IN;
+        if reader_events.contains(IoEvents::HUP) {
+            events |= IoEvents::RDHUP | IoEvents::IN;
+
+            if self.is_write_shutdown.load(Ordering::Relaxed) {
+                events |= IoEvents::HUP;
+            }
+        }
+
+        // TODO: Report whether the receive queue of the peer socket is full.
+        events |= IoEvents::OUT;
+
+        events & (mask | IoEvents::ALWAYS_POLL)
+    }
+}
+
+impl SocketPrivate for UnixDatagramSocket {
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) {
+        self.is_nonblocking.store(nonblocking, Ordering::Relaxed);
+    }
+}
+
+impl Socket for UnixDatagramSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let addr_to_bind = UnixSocketAddr::try_from(socket_addr)?;
+
+        let mut state = self.state.lock();
+
+        if state.addr.is_some() {
+            return addr_to_bind.bind_unnamed();
+        }
+
+        let bound_addr = addr_to_bind.bind()?;
+        register_queue(bound_addr.to_key(), &self.receive_queue);
+        state.addr = Some(bound_addr);
+
+        Ok(())
+    }
+
+    fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        let remote_addr = UnixSocketAddr::try_from(socket_addr)?;
+        let remote_queue = lookup_queue(&remote_addr.connect()?)?;
+
+        // TODO: Linux only allows a socket to receive datagrams from its peer once connected.
+        // We do not filter the incoming datagrams for now.
+        self.state.lock().peer = Some(Peer {
+            addr: remote_addr,
+            queue: Arc::downgrade(&remote_queue),
+        });
+
+        Ok(())
+    }
+
+    fn shutdown(&self, cmd: SockShutdownCmd) -> Result<()> {
+        if cmd.shut_write() {
+            self.is_write_shutdown.store(true, Ordering::Relaxed);
+        }
+
+        if cmd.shut_read() {
+            self.receive_queue.shutdown();
+        }
+
+        Ok(())
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let addr = self.state.lock().addr.clone();
+
+        Ok(addr.into())
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        let state = self.state.lock();
+        let Some(peer) = state.peer.as_ref() else {
+            return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected");
+        };
+
+        Ok(peer.addr.clone().into())
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        if self.is_write_shutdown.load(Ordering::Relaxed) {
+            return_errno_with_message!(Errno::EPIPE, "the socket is shut down for writing");
+        }
+
+        let remote_queue = self.select_remote(addr)?;
+
+        let len = reader.sum_lens();
+        if len > MAX_MESSAGE_LEN {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+        }
+
+        let mut data = vec![0; len];
+        reader.read(&mut VmWriter::from(data.as_mut_slice()))?;
+
+        let ancillary_data = AncillaryData::from_control_messages(control_messages);
+        if ancillary_data.files.is_some() {
+            gc::collect_garbage();
+        }
+
+        let src_addr = self.state.lock().addr.clone().into();
+        let message = Message::new(data, src_addr, ancillary_data);
+
+        if self.is_nonblocking() {
+            remote_queue.try_push(message).map_err(|(err, _)| err)?;
+        } else {
+            remote_queue.push(message)?;
+        }
+
+        Ok(len)
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with other flags. Only MSG_PEEK is handled here.
+        if !flags.sub(SendRecvFlags::MSG_PEEK).is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let is_peek = flags.contains(SendRecvFlags::MSG_PEEK);
+        let is_pass_cred = self.is_pass_cred.load(Ordering::Relaxed);
+        let (received_bytes, src_addr, control_messages) = self.block_on(IoEvents::IN, || {
+            self.receive_queue.try_recv(writer, is_peek, is_pass_cred)
+        })?;
+
+        let message_header = MessageHeader::new(Some(src_addr.into()), control_messages);
+
+        Ok((received_bytes, message_header))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            pass_cred: PassCred => {
+                pass_cred.set(self.is_pass_cred.load(Ordering::Relaxed));
+            },
+            peer_cred: PeerCred => {
+                peer_cred.set(self.peer_cred);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to get is unknown")
+        });
+
+        Ok(())
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        match_sock_option_ref!(option, {
+            pass_cred: PassCred => {
+                self.is_pass_cred.store(*pass_cred.get().unwrap(), Ordering::Relaxed);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to set is unknown")
+        });
+
+        Ok(())
+    }
+}
+
+impl InflightQueue for UnixDatagramSocket {
+    fn try_for_each_inflight(&self, f: &mut dyn FnMut(&Arc<dyn FileLike>)) -> bool {
+        self.receive_queue.try_for_each_inflight(f)
+    }
+
+    fn try_purge_inflight(&self, purged: &mut Vec<InflightFiles>) -> bool {
+        self.receive_queue.try_purge_inflight(purged)
+    }
+}
+
+impl Drop for UnixDatagramSocket {
+    fn drop(&mut self) {
+        if let Some(addr) = self.state.get_mut().addr.as_ref() {
+            unregister_queue(&addr.to_key());
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Garbage collection of in-flight UNIX sockets.
+//!
+//! A UNIX socket can be sent over another UNIX socket, or even over itself, with `SCM_RIGHTS`.
+//! While the socket is in flight, it is kept alive by the receive queue that contains it. So
+//! sockets can form reference cycles that are no longer reachable from the user space. For
+//! example, a socket is sent to its own receive queue and then closed.
+//!
+//! Like Linux, the collector finds such sockets by comparing the number of references to a socket
+//! with the number of its in-flight references:
+//!  1. The candidates are the in-flight sockets that are _only_ referenced by receive queues.
+//!  2. A candidate is reachable if some of its in-flight references are not in the receive queues
+//!     of the candidates, or if it is in the receive queue of a reachable candidate.
+//!  3. The files in the receive queues of unreachable candidates are dropped to break the cycles.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/unix/garbage.c>.
+
+use super::{UnixDatagramSocket, UnixStreamSocket};
+use crate::{fs::file_handle::FileLike, prelude::*};
+
+/// Files that are in flight, i.e., in the receive queue of a UNIX socket.
+///
+/// UNIX sockets in the files are tracked so that the garbage collector can find unreachable ones.
+pub(super) struct InflightFiles(Vec<Arc<dyn FileLike>>);
+
+impl InflightFiles {
+    /// Creates the in-flight files and registers UNIX sockets in them.
+    pub(super) fn new(files: Vec<Arc<dyn FileLike>>) -> Self {
+        let mut table = INFLIGHT_TABLE.lock();
+
+        for file in files
+            .iter()
+            .filter(|file| as_inflight_queue(file.as_ref()).is_some())
+        {
+            table
+                .entry(table_key(file))
+                .or_insert_with(|| InflightEntry {
+                    socket: Arc::downgrade(file),
+                    count: 0,
+                })
+                .count += 1;
+        }
+
+        Self(files)
+    }
+
+    /// Returns the in-flight files.
+    pub(super) fn files(&self) -> &[Arc<dyn FileLike>] {
+        &self.0
+    }
+
+    /// Clones the in-flight files so that they can be received without being dequeued.
+    ///
+    /// This is used by `MSG_PEEK`. The cloned files are no longer in flight.
+    pub(super) fn clone_files(&self) -> Vec<Arc<dyn FileLike>> {
+        // New references must not be created while the garbage collector is counting them.
+        let _table = INFLIGHT_TABLE.lock();
+
+        self.0.clone()
+    }
+
+    /// Takes the files out of flight.
+    pub(super) fn into_files(mut self) -> Vec<Arc<dyn FileLike>> {
+        let files = core::mem::take(&mut self.0);
+        unregister_files(&files);
+        files
+    }
+}
+
+impl Drop for InflightFiles {
+    fn drop(&mut self) {
+        // The files are dropped after the table is unlocked, so it is fine for them to drop other
+        // in-flight files.
+        unregister_files(&self.0);
+    }
+}
+
+fn unregister_files(files: &[Arc<dyn FileLike>]) {
+    let mut table = INFLIGHT_TABLE.lock();
+
+    for file in files
+        .iter()
+        .filter(|file| as_inflight_queue(file.as_ref()).is_some())
+    {
+        let key = table_key(file);
+        let entry = table.get_mut(&key).unwrap();
+        entry.count -= 1;
+        if entry.count == 0 {
+            table.remove(&key);
+        }
+    }
+}
+
+/// A UNIX socket whose receive queue can contain in-flight files.
+///
+/// The methods must not block, because they are called with the in-flight table locked. They
+/// return `false` if the receive queue cannot be locked immediately.
+pub(super) trait InflightQueue {
+    /// Visits the in-flight files in the receive queue.
+    fn try_for_each_inflight(&self, f: &mut dyn FnMut(&Arc<dyn FileLike>)) -> bool;
+
+    /// Takes all in-flight files out of the receive queue.
+    fn try_purge_inflight(&self, purged: &mut Vec<InflightFiles>) -> bool;
+}
+
+fn as_inflight_queue(file: &dyn FileLike) -> Option<&dyn InflightQueue> {
+    if let Some(socket) = file.downcast_ref::<UnixStreamSocket>() {
+        return Some(socket);
+    }
+    if let Some(socket) = file.downcast_ref::<UnixDatagramSocket>() {
+        return Some(socket);
+    }
+    None
+}
+
+/// Collects the unreachable in-flight UNIX sockets.
+///
+/// If some receive queues are busy, the collection is given up. It will be retried the next time
+/// files are sent.
+pub(super) fn collect_garbage() {
+    let mut garbage = Garbage {
+        sockets: Vec::new(),
+        purged: Vec::new(),
+    };
+
+    let table = INFLIGHT_TABLE.lock();
+    if !table.is_empty() {
+        garbage.collect(&table);
+    }
+    drop(table);
+
+    // The files and the sockets must be dropped after the table is unlocked.
+    drop(garbage);
+}
+
+struct Garbage {
+    /// The in-flight sockets, which are held to be dropped after the table is unlocked.
+    sockets: Vec<Arc<dyn FileLike>>,
+    purged: Vec<InflightFiles>,
+}
+
+impl Garbage {
+    fn collect(&mut self, table: &BTreeMap<usize, InflightEntry>) {
+        // Step 1: Find the candidates.
+        let mut candidates = BTreeMap::new();
+        for (key, entry) in table.iter() {
+            let Some(socket) = entry.socket.upgrade() else {
+                continue;
+            };
+            // One reference is held by ourselves.
+            if Arc::strong_count(&socket) - 1 == entry.count {
+                candidates.insert(
+                    *key,
+                    Candidate {
+                        index: self.sockets.len(),
+                        num_inflight: entry.count,
+                        num_internal: 0,
+                        is_reachable: false,
+                    },
+                );
+            }
+            self.sockets.push(socket);
+        }
+
+        // Step 2: Count the in-flight references in the receive queues of the candidates.
+        let keys = candidates.keys().copied().collect::<Vec<_>>();
+        for key in keys.iter() {
+            let socket = as_inflight_queue(self.sockets[candidates[key].index].as_ref()).unwrap();
+            let is_locked = socket.try_for_each_inflight(&mut |file| {
+                if let Some(candidate) = candidates.get_mut(&table_key(file)) {
+                    candidate.num_internal += 1;
+                }
+            });
+            if !is_locked {
+                return;
+            }
+        }
+
+        // Step 3: Mark the reachable candidates.
+        let mut stack = Vec::new();
+        for (key, candidate) in candidates.iter_mut() {
+            if candidate.num_inflight > candidate.num_internal {
+                candidate.is_reachable = true;
+                stack.push(*key);
+            }
+        }
+        while let Some(key) = stack.pop() {
+            let socket = as_inflight_queue(self.sockets[candidates[&key].index].as_ref()).unwrap();
+            let is_locked = socket.try_for_each_inflight(&mut |file| {
+                let key = table_key(file);
+                if let Some(candidate) = candidates.get_mut(&key)
+                    && !candidate.is_reachable
+                {
+                    candidate.is_reachable = true;
+                    stack.push(key);
+                }
+            });
+            if !is_locked {
+                return;
+            }
+        }
+
+        // Step 4: Purge the unreachable candidates.
+        for candidate in candidates
+            .values()
+            .filter(|candidate| !candidate.is_reachable)
+        {
+            let socket = as_inflight_queue(self.sockets[candidate.index].as_ref()).unwrap();
+            // If the receive queue is busy, the socket is purged next time.
+            socket.try_purge_inflight(&mut self.purged);
+        }
+    }
+}
+
+struct Candidate {
+    /// The index in `Garbage::sockets`.
+    index: usize,
+    num_inflight: usize,
+    num_internal: usize,
+    is_reachable: bool,
+}
+
+static INFLIGHT_TABLE: Mutex<BTreeMap<usize, InflightEntry>> = Mutex::new(BTreeMap::new());
+
+struct InflightEntry {
+    socket: Weak<dyn FileLike>,
+    /// The number of in-flight references.
+    count: usize,
+}
+
+fn table_key(file: &Arc<dyn FileLike>) -> usize {
+    Arc::as_ptr(file) as *const () as usize
+}
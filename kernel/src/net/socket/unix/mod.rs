@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod addr;
+mod ancillary;
+mod datagram;
+mod gc;
 mod ns;
 mod stream;
 
 pub use addr::UnixSocketAddr;
+pub use datagram::UnixDatagramSocket;
 pub use stream::UnixStreamSocket;
@@ -6,10 +6,15 @@ use ostd::sync::PreemptDisabled;
 
 use crate::{
     events::IoEvents,
-    fs::utils::{Channel, Consumer, Producer},
+    fs::{
+        file_handle::FileLike,
+        utils::{Channel, Consumer, Producer},
+    },
     net::socket::{
-        unix::{addr::UnixSocketAddrBound, UnixSocketAddr},
-        SockShutdownCmd,
+        unix::{
+            addr::UnixSocketAddrBound, ancillary::AncillaryData, gc::InflightFiles, UnixSocketAddr,
+        },
+        CUserCred, SockShutdownCmd,
     },
     prelude::*,
     process::signal::{PollHandle, Pollee},
@@ -20,31 +25,65 @@ pub(super) struct Connected {
     addr: AddrView,
     reader: Consumer<u8>,
     writer: Producer<u8>,
+    /// The frames in the receive buffer.
+    reader_frames: Frames,
+    /// The frames in the send buffer.
+    writer_frames: Frames,
+    /// The credentials of the peer socket when the connection was established.
+    peer_cred: CUserCred,
+    is_seqpacket: bool,
+}
+
+/// Consecutive bytes in a buffer that were sent with the same ancillary data.
+///
+/// For `SOCK_SEQPACKET` sockets, each frame is a record whose boundaries are preserved. For
+/// `SOCK_STREAM` sockets, adjacent frames are merged if they have the same credentials and no
+/// files.
+struct Frame {
+    len: usize,
+    cred: CUserCred,
+    files: Option<InflightFiles>,
 }
 
+type Frames = Arc<Mutex<VecDeque<Frame>>>;
+
 impl Connected {
     pub(super) fn new_pair(
         addr: Option<UnixSocketAddrBound>,
         peer_addr: Option<UnixSocketAddrBound>,
         reader_pollee: Option<Pollee>,
         writer_pollee: Option<Pollee>,
+        cred: CUserCred,
+        peer_cred: CUserCred,
+        is_seqpacket: bool,
     ) -> (Connected, Connected) {
         let (writer_peer, reader_this) =
             Channel::with_capacity_and_pollees(DEFAULT_BUF_SIZE, None, reader_pollee).split();
         let (writer_this, reader_peer) =
             Channel::with_capacity_and_pollees(DEFAULT_BUF_SIZE, writer_pollee, None).split();
 
+        let frames_this = Arc::new(Mutex::new(VecDeque::new()));
+        let frames_peer = Arc::new(Mutex::new(VecDeque::new()));
+
         let (addr_this, addr_peer) = AddrView::new_pair(addr, peer_addr);
 
         let this = Connected {
             addr: addr_this,
             reader: reader_this,
             writer: writer_this,
+            reader_frames: frames_this.clone(),
+            writer_frames: frames_peer.clone(),
+            peer_cred,
+            is_seqpacket,
         };
         let peer = Connected {
             addr: addr_peer,
             reader: reader_peer,
             writer: writer_peer,
+            reader_frames: frames_peer,
+            writer_frames: frames_this,
+            peer_cred: cred,
+            is_seqpacket,
         };
 
         (this, peer)
@@ -71,12 +110,163 @@ impl Connected {
         Ok(())
     }
 
-    pub(super) fn try_read(&self, writer: &mut dyn MultiWrite) -> Result<usize> {
-        self.reader.try_read(writer)
+    pub(super) fn peer_cred(&self) -> CUserCred {
+        self.peer_cred
+    }
+
+    /// Tries to read bytes and the ancillary data sent with them.
+    ///
+    /// The ancillary data is `None` if the peer has shut down and no bytes remain.
+    pub(super) fn try_read(
+        &self,
+        writer: &mut dyn MultiWrite,
+        is_pass_cred: bool,
+    ) -> Result<(usize, Option<AncillaryData>)> {
+        let mut frames = self.reader_frames.lock();
+
+        let Some(front) = frames.front() else {
+            if self.reader.is_shutdown() {
+                return Ok((0, None));
+            }
+            return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty");
+        };
+        let cred = front.cred;
+
+        if self.is_seqpacket {
+            // The whole record is consumed, even if it cannot fit in the buffer. The rest of the
+            // record is discarded, like Linux does.
+            let mut record = vec![0; front.len];
+            let read_len = self
+                .reader
+                .try_read(&mut VmWriter::from(record.as_mut_slice()).to_fallible())?;
+            debug_assert_eq!(read_len, record.len());
+            let frame = frames.pop_front().unwrap();
+            drop(frames);
+
+            let copied_len = writer.write(&mut VmReader::from(record.as_slice()))?;
+            let ancillary_data = AncillaryData {
+                files: frame.files,
+                cred,
+            };
+            return Ok((copied_len, Some(ancillary_data)));
+        }
+
+        // Like Linux, a read stops at a frame with different credentials (if they are passed to
+        // the user space) or after a frame with files.
+        let mut readable_len = 0;
+        let mut num_readable_frames = 0;
+        for frame in frames.iter() {
+            if num_readable_frames > 0 && is_pass_cred && frame.cred != cred {
+                break;
+            }
+            readable_len += frame.len;
+            num_readable_frames += 1;
+            if frame.files.is_some() {
+                break;
+            }
+        }
+
+        let read_len = if num_readable_frames == frames.len() {
+            self.reader.try_read(writer)?
+        } else {
+            let mut buffer = vec![0; readable_len.min(writer.sum_lens())];
+            let read_len = self
+                .reader
+                .try_read(&mut VmWriter::from(buffer.as_mut_slice()).to_fallible())?;
+            debug_assert_eq!(read_len, buffer.len());
+            writer.write(&mut VmReader::from(buffer.as_slice()))?
+        };
+
+        // Files are received along with the first byte of the frame.
+        let mut files = None;
+        let mut remaining_len = read_len;
+        while remaining_len > 0 {
+            let frame = frames.front_mut().unwrap();
+            if frame.files.is_some() {
+                files = frame.files.take();
+            }
+            if frame.len > remaining_len {
+                frame.len -= remaining_len;
+                break;
+            }
+            remaining_len -= frame.len;
+            frames.pop_front();
+        }
+
+        Ok((read_len, Some(AncillaryData { files, cred })))
     }
 
-    pub(super) fn try_write(&self, reader: &mut dyn MultiRead) -> Result<usize> {
-        self.writer.try_write(reader)
+    /// Tries to write bytes and the ancillary data.
+    ///
+    /// If some bytes are written, the files in the ancillary data are taken and sent along with
+    /// them.
+    pub(super) fn try_write(
+        &self,
+        reader: &mut dyn MultiRead,
+        ancillary_data: &mut AncillaryData,
+    ) -> Result<usize> {
+        let record_len = reader.sum_lens();
+        if self.is_seqpacket && record_len > DEFAULT_BUF_SIZE {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+        }
+
+        let mut frames = self.writer_frames.lock();
+
+        // A record must be written as a whole. Since the writers are serialized by the lock and
+        // the readers can only increase the free space, the write below cannot be partial unless
+        // the user buffer is inaccessible.
+        if self.is_seqpacket && !self.writer.is_shutdown() && self.writer.free_len() < record_len {
+            return_errno_with_message!(Errno::EAGAIN, "the send buffer is full");
+        }
+
+        let written_len = self.writer.try_write(reader)?;
+        if written_len == 0 {
+            return Ok(0);
+        }
+
+        let files = ancillary_data.files.take();
+        let cred = ancillary_data.cred;
+        match frames.back_mut() {
+            Some(last)
+                if !self.is_seqpacket
+                    && files.is_none()
+                    && last.files.is_none()
+                    && last.cred == cred =>
+            {
+                last.len += written_len;
+            }
+            _ => frames.push_back(Frame {
+                len: written_len,
+                cred,
+                files,
+            }),
+        }
+
+        Ok(written_len)
+    }
+
+    pub(super) fn try_for_each_inflight(&self, f: &mut dyn FnMut(&Arc<dyn FileLike>)) -> bool {
+        let Some(frames) = self.reader_frames.try_lock() else {
+            return false;
+        };
+
+        frames
+            .iter()
+            .filter_map(|frame| frame.files.as_ref())
+            .flat_map(|files| files.files())
+            .for_each(f);
+
+        true
+    }
+
+    pub(super) fn try_purge_inflight(&self, purged: &mut Vec<InflightFiles>) -> bool {
+        let Some(mut frames) = self.reader_frames.try_lock() else {
+            return false;
+        };
+
+        purged.extend(frames.iter_mut().filter_map(|frame| frame.files.take()));
+
+        true
     }
 
     pub(super) fn shutdown(&self, cmd: SockShutdownCmd) {
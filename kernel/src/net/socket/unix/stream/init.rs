@@ -10,7 +10,7 @@ use crate::{
     events::IoEvents,
     net::socket::{
         unix::addr::{UnixSocketAddr, UnixSocketAddrBound},
-        SockShutdownCmd,
+        CUserCred, SockShutdownCmd,
     },
     prelude::*,
     process::signal::{PollHandle, Pollee},
@@ -46,7 +46,13 @@ impl Init {
         Ok(())
     }
 
-    pub(super) fn into_connected(self, peer_addr: UnixSocketAddrBound) -> (Connected, Connected) {
+    pub(super) fn into_connected(
+        self,
+        peer_addr: UnixSocketAddrBound,
+        cred: CUserCred,
+        peer_cred: CUserCred,
+        is_seqpacket: bool,
+    ) -> (Connected, Connected) {
         let Init {
             addr,
             reader_pollee,
@@ -60,6 +66,9 @@ impl Init {
             Some(peer_addr),
             Some(reader_pollee),
             Some(writer_pollee),
+            cred,
+            peer_cred,
+            is_seqpacket,
         );
 
         if is_read_shutdown.into_inner() {
@@ -73,7 +82,11 @@ impl Init {
         (this_conn, peer_conn)
     }
 
-    pub(super) fn listen(self, backlog: usize) -> core::result::Result<Listener, (Error, Self)> {
+    pub(super) fn listen(
+        self,
+        backlog: usize,
+        is_seqpacket: bool,
+    ) -> core::result::Result<Listener, (Error, Self)> {
         let Some(addr) = self.addr else {
             return Err((
                 Error::with_message(Errno::EINVAL, "the socket is not bound"),
@@ -88,6 +101,7 @@ impl Init {
             backlog,
             self.is_read_shutdown.into_inner(),
             self.is_write_shutdown.into_inner(),
+            is_seqpacket,
         ))
     }
 
@@ -14,7 +14,7 @@ use crate::{
     fs::file_handle::FileLike,
     net::socket::{
         unix::addr::{UnixSocketAddrBound, UnixSocketAddrKey},
-        SockShutdownCmd, SocketAddr,
+        CUserCred, SockShutdownCmd, SocketAddr,
     },
     prelude::*,
     process::signal::{PollHandle, Pollee},
@@ -34,9 +34,20 @@ impl Listener {
         backlog: usize,
         is_read_shutdown: bool,
         is_write_shutdown: bool,
+        is_seqpacket: bool,
     ) -> Self {
+        // Like Linux, the credentials of the listening socket are those at the time of `listen`.
+        let cred = CUserCred::new_current_peer();
+
         let backlog = BACKLOG_TABLE
-            .add_backlog(addr, reader_pollee, backlog, is_read_shutdown)
+            .add_backlog(
+                addr,
+                reader_pollee,
+                backlog,
+                is_read_shutdown,
+                cred,
+                is_seqpacket,
+            )
             .unwrap();
         writer_pollee.invalidate();
 
@@ -51,11 +62,20 @@ impl Listener {
         self.backlog.addr()
     }
 
-    pub(super) fn try_accept(&self) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
+    pub(super) fn cred(&self) -> CUserCred {
+        self.backlog.cred
+    }
+
+    pub(super) fn try_accept(&self, is_pass_cred: bool) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
         let connected = self.backlog.pop_incoming()?;
         let peer_addr = connected.peer_addr().into();
 
-        let socket = UnixStreamSocket::new_connected(connected, false);
+        let socket = UnixStreamSocket::new_connected(
+            connected,
+            false,
+            self.backlog.is_seqpacket(),
+            is_pass_cred,
+        );
         Ok((socket, peer_addr))
     }
 
@@ -122,6 +142,8 @@ impl BacklogTable {
         pollee: Pollee,
         backlog: usize,
         is_shutdown: bool,
+        cred: CUserCred,
+        is_seqpacket: bool,
     ) -> Option<Arc<Backlog>> {
         let addr_key = addr.to_key();
 
@@ -133,7 +155,14 @@ impl BacklogTable {
 
         // Note that the cached events can be correctly inherited from `Init`, so there is no need
         // to explicitly call `Pollee::invalidate`.
-        let new_backlog = Arc::new(Backlog::new(addr, pollee, backlog, is_shutdown));
+        let new_backlog = Arc::new(Backlog::new(
+            addr,
+            pollee,
+            backlog,
+            is_shutdown,
+            cred,
+            is_seqpacket,
+        ));
         backlog_sockets.insert(addr_key, new_backlog.clone());
 
         Some(new_backlog)
@@ -154,10 +183,20 @@ pub(super) struct Backlog {
     backlog: AtomicUsize,
     incoming_conns: SpinLock<Option<VecDeque<Connected>>>,
     wait_queue: WaitQueue,
+    /// The credentials of the listening socket.
+    cred: CUserCred,
+    is_seqpacket: bool,
 }
 
 impl Backlog {
-    fn new(addr: UnixSocketAddrBound, pollee: Pollee, backlog: usize, is_shutdown: bool) -> Self {
+    fn new(
+        addr: UnixSocketAddrBound,
+        pollee: Pollee,
+        backlog: usize,
+        is_shutdown: bool,
+        cred: CUserCred,
+        is_seqpacket: bool,
+    ) -> Self {
         let incoming_sockets = if is_shutdown {
             None
         } else {
@@ -170,6 +209,8 @@ impl Backlog {
             backlog: AtomicUsize::new(backlog),
             incoming_conns: SpinLock::new(incoming_sockets),
             wait_queue: WaitQueue::new(),
+            cred,
+            is_seqpacket,
         }
     }
 
@@ -177,6 +218,11 @@ impl Backlog {
         &self.addr
     }
 
+    /// Returns whether the listening socket is a `SOCK_SEQPACKET` socket.
+    pub(super) fn is_seqpacket(&self) -> bool {
+        self.is_seqpacket
+    }
+
     fn pop_incoming(&self) -> Result<Connected> {
         let mut locked_incoming_conns = self.incoming_conns.lock();
 
@@ -205,11 +251,15 @@ impl Backlog {
     fn shutdown(&self) {
         let mut incoming_conns = self.incoming_conns.lock();
 
-        *incoming_conns = None;
+        let conns = incoming_conns.take();
         self.pollee.notify(IoEvents::HUP);
 
         drop(incoming_conns);
 
+        // The pending connections may hold in-flight files, which cannot be dropped with the spin
+        // lock held.
+        drop(conns);
+
         self.wait_queue.wake_all();
     }
 
@@ -238,6 +288,8 @@ impl Backlog {
         &self,
         init: Init,
     ) -> core::result::Result<Connected, (Error, Init)> {
+        let cred = CUserCred::new_current_peer();
+
         let mut locked_incoming_conns = self.incoming_conns.lock();
 
         let Some(incoming_conns) = &mut *locked_incoming_conns else {
@@ -260,7 +312,8 @@ impl Backlog {
             ));
         }
 
-        let (client_conn, server_conn) = init.into_connected(self.addr.clone());
+        let (client_conn, server_conn) =
+            init.into_connected(self.addr.clone(), cred, self.cred, self.is_seqpacket);
 
         incoming_conns.push_back(server_conn);
         self.pollee.notify(IoEvents::IN);
@@ -12,34 +12,56 @@ use super::{
 use crate::{
     events::IoEvents,
     fs::file_handle::FileLike,
+    match_sock_option_mut, match_sock_option_ref,
     net::socket::{
+        options::{PassCred, PeerCred, SocketOption},
         private::SocketPrivate,
-        unix::UnixSocketAddr,
+        unix::{
+            ancillary::AncillaryData,
+            gc::{self, InflightFiles, InflightQueue},
+            UnixSocketAddr,
+        },
         util::{send_recv_flags::SendRecvFlags, socket_addr::SocketAddr, MessageHeader},
-        SockShutdownCmd, Socket,
+        CUserCred, SockShutdownCmd, Socket,
     },
     prelude::*,
     process::signal::{PollHandle, Pollable},
     util::{MultiRead, MultiWrite},
 };
 
+/// A UNIX domain socket of the `SOCK_STREAM` or `SOCK_SEQPACKET` type.
+///
+/// The two types share the same connection-oriented implementation, except that the
+/// `SOCK_SEQPACKET` sockets preserve the message boundaries.
 pub struct UnixStreamSocket {
     state: RwMutex<Takeable<State>>,
     is_nonblocking: AtomicBool,
+    is_seqpacket: bool,
+    /// Whether the credentials are received as control messages (i.e., `SO_PASSCRED`).
+    is_pass_cred: AtomicBool,
 }
 
 impl UnixStreamSocket {
-    pub(super) fn new_init(init: Init, is_nonblocking: bool) -> Arc<Self> {
+    pub(super) fn new_init(init: Init, is_nonblocking: bool, is_seqpacket: bool) -> Arc<Self> {
         Arc::new(Self {
             state: RwMutex::new(Takeable::new(State::Init(init))),
             is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_seqpacket,
+            is_pass_cred: AtomicBool::new(false),
         })
     }
 
-    pub(super) fn new_connected(connected: Connected, is_nonblocking: bool) -> Arc<Self> {
+    pub(super) fn new_connected(
+        connected: Connected,
+        is_nonblocking: bool,
+        is_seqpacket: bool,
+        is_pass_cred: bool,
+    ) -> Arc<Self> {
         Arc::new(Self {
             state: RwMutex::new(Takeable::new(State::Connected(connected))),
             is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_seqpacket,
+            is_pass_cred: AtomicBool::new(is_pass_cred),
         })
     }
 }
@@ -51,30 +73,43 @@ enum State {
 }
 
 impl UnixStreamSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        Self::new_init(Init::new(), is_nonblocking)
+    pub fn new(is_nonblocking: bool, is_seqpacket: bool) -> Arc<Self> {
+        Self::new_init(Init::new(), is_nonblocking, is_seqpacket)
     }
 
-    pub fn new_pair(is_nonblocking: bool) -> (Arc<Self>, Arc<Self>) {
-        let (conn_a, conn_b) = Connected::new_pair(None, None, None, None);
+    pub fn new_pair(is_nonblocking: bool, is_seqpacket: bool) -> (Arc<Self>, Arc<Self>) {
+        let cred = CUserCred::new_current_peer();
+        let (conn_a, conn_b) =
+            Connected::new_pair(None, None, None, None, cred, cred, is_seqpacket);
         (
-            Self::new_connected(conn_a, is_nonblocking),
-            Self::new_connected(conn_b, is_nonblocking),
+            Self::new_connected(conn_a, is_nonblocking, is_seqpacket, false),
+            Self::new_connected(conn_b, is_nonblocking, is_seqpacket, false),
         )
     }
 
-    fn try_send(&self, buf: &mut dyn MultiRead, _flags: SendRecvFlags) -> Result<usize> {
+    fn try_send(
+        &self,
+        buf: &mut dyn MultiRead,
+        ancillary_data: &mut AncillaryData,
+        _flags: SendRecvFlags,
+    ) -> Result<usize> {
         match self.state.read().as_ref() {
-            State::Connected(connected) => connected.try_write(buf),
+            State::Connected(connected) => connected.try_write(buf, ancillary_data),
             State::Init(_) | State::Listen(_) => {
                 return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected")
             }
         }
     }
 
-    fn try_recv(&self, buf: &mut dyn MultiWrite, _flags: SendRecvFlags) -> Result<usize> {
+    fn try_recv(
+        &self,
+        buf: &mut dyn MultiWrite,
+        _flags: SendRecvFlags,
+    ) -> Result<(usize, Option<AncillaryData>)> {
+        let is_pass_cred = self.is_pass_cred.load(Ordering::Relaxed);
+
         match self.state.read().as_ref() {
-            State::Connected(connected) => connected.try_read(buf),
+            State::Connected(connected) => connected.try_read(buf, is_pass_cred),
             State::Init(_) | State::Listen(_) => {
                 return_errno_with_message!(Errno::EINVAL, "the socket is not connected")
             }
@@ -118,7 +153,7 @@ impl UnixStreamSocket {
 
     fn try_accept(&self) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
         match self.state.read().as_ref() {
-            State::Listen(listen) => listen.try_accept() as _,
+            State::Listen(listen) => listen.try_accept(self.is_pass_cred.load(Ordering::Relaxed)),
             State::Init(_) | State::Connected(_) => {
                 return_errno_with_message!(Errno::EINVAL, "the socket is not listening")
             }
@@ -165,6 +200,13 @@ impl Socket for UnixStreamSocket {
         let remote_addr = UnixSocketAddr::try_from(socket_addr)?.connect()?;
         let backlog = get_backlog(&remote_addr)?;
 
+        if backlog.is_seqpacket() != self.is_seqpacket {
+            return_errno_with_message!(
+                Errno::EPROTOTYPE,
+                "the remote socket is of a different type"
+            );
+        }
+
         if self.is_nonblocking() {
             self.try_connect(&backlog)
         } else {
@@ -200,7 +242,7 @@ impl Socket for UnixStreamSocket {
                 }
             };
 
-            let listener = match init.listen(backlog) {
+            let listener = match init.listen(backlog, self.is_seqpacket) {
                 Ok(listener) => listener,
                 Err((err, init)) => {
                     return (State::Init(init), Err(err));
@@ -258,15 +300,17 @@ impl Socket for UnixStreamSocket {
         }
 
         let MessageHeader {
-            control_message, ..
+            control_messages, ..
         } = message_header;
 
-        if control_message.is_some() {
-            // TODO: Support sending control message
-            warn!("sending control message is not supported");
+        let mut ancillary_data = AncillaryData::from_control_messages(control_messages);
+        if ancillary_data.files.is_some() {
+            gc::collect_garbage();
         }
 
-        self.block_on(IoEvents::OUT, || self.try_send(reader, flags))
+        self.block_on(IoEvents::OUT, || {
+            self.try_send(reader, &mut ancillary_data, flags)
+        })
     }
 
     fn recvmsg(
@@ -279,12 +323,74 @@ impl Socket for UnixStreamSocket {
             warn!("unsupported flags: {:?}", flags);
         }
 
-        let received_bytes = self.block_on(IoEvents::IN, || self.try_recv(writer, flags))?;
+        let (received_bytes, ancillary_data) =
+            self.block_on(IoEvents::IN, || self.try_recv(writer, flags))?;
 
-        // TODO: Receive control message
+        let control_messages = match ancillary_data {
+            Some(ancillary_data) => {
+                ancillary_data.into_control_messages(self.is_pass_cred.load(Ordering::Relaxed))
+            }
+            None => Vec::new(),
+        };
 
-        let message_header = MessageHeader::new(None, None);
+        let message_header = MessageHeader::new(None, control_messages);
 
         Ok((received_bytes, message_header))
     }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        match_sock_option_mut!(option, {
+            pass_cred: PassCred => {
+                pass_cred.set(self.is_pass_cred.load(Ordering::Relaxed));
+            },
+            peer_cred: PeerCred => {
+                let cred = match self.state.read().as_ref() {
+                    State::Init(_) => CUserCred::new_unknown(),
+                    State::Listen(listen) => listen.cred(),
+                    State::Connected(connected) => connected.peer_cred(),
+                };
+                peer_cred.set(cred);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to get is unknown")
+        });
+
+        Ok(())
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        match_sock_option_ref!(option, {
+            pass_cred: PassCred => {
+                self.is_pass_cred.store(*pass_cred.get().unwrap(), Ordering::Relaxed);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to set is unknown")
+        });
+
+        Ok(())
+    }
+}
+
+impl InflightQueue for UnixStreamSocket {
+    fn try_for_each_inflight(&self, f: &mut dyn FnMut(&Arc<dyn FileLike>)) -> bool {
+        let Some(state) = self.state.try_read() else {
+            return false;
+        };
+
+        // TODO: Count the in-flight files in the pending connections of listening sockets. For
+        // now, they are considered to be referenced from the outside, which is conservative.
+        match state.as_ref() {
+            State::Connected(connected) => connected.try_for_each_inflight(f),
+            State::Init(_) | State::Listen(_) => true,
+        }
+    }
+
+    fn try_purge_inflight(&self, purged: &mut Vec<InflightFiles>) -> bool {
+        let Some(state) = self.state.try_read() else {
+            return false;
+        };
+
+        match state.as_ref() {
+            State::Connected(connected) => connected.try_purge_inflight(purged),
+            State::Init(_) | State::Listen(_) => true,
+        }
+    }
 }
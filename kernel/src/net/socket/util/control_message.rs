@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Control messages (i.e., ancillary data) of `sendmsg` and `recvmsg`.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man3/cmsg.3.html>.
+
+use core::mem::size_of;
+
+use ostd::sync::RwArc;
+
+use crate::{
+    fs::{
+        file_handle::FileLike,
+        file_table::{FdFlags, FileDesc, FileTable},
+    },
+    prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::AsPosixThread, Gid, Uid},
+    util::net::CSocketOptionLevel,
+};
+
+/// A control message that is sent or received with a message.
+#[derive(Debug)]
+pub enum ControlMessage {
+    /// Open files passed with `SCM_RIGHTS`.
+    Rights(Vec<Arc<dyn FileLike>>),
+    /// Process credentials passed with `SCM_CREDENTIALS`.
+    Credentials(CUserCred),
+}
+
+/// The credentials of a process (`struct ucred` in Linux).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod)]
+pub struct CUserCred {
+    pub pid: i32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl CUserCred {
+    /// Returns the credentials that are sent by the current process by default.
+    ///
+    /// Like Linux, the real user and group IDs are used.
+    pub fn new_current() -> Self {
+        let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+
+        Self {
+            pid: current!().pid() as i32,
+            uid: credentials.ruid().into(),
+            gid: credentials.rgid().into(),
+        }
+    }
+
+    /// Returns the credentials that identify the current process as a peer socket.
+    ///
+    /// Like Linux, the effective user and group IDs are used.
+    pub fn new_current_peer() -> Self {
+        let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+
+        Self {
+            pid: current!().pid() as i32,
+            uid: credentials.euid().into(),
+            gid: credentials.egid().into(),
+        }
+    }
+
+    /// Returns the credentials that are reported if the peer credentials are unknown.
+    pub const fn new_unknown() -> Self {
+        Self {
+            pid: 0,
+            uid: u32::MAX,
+            gid: u32::MAX,
+        }
+    }
+
+    /// Checks whether the current process is allowed to send the credentials.
+    ///
+    /// See <https://man7.org/linux/man-pages/man7/unix.7.html> for the rules.
+    fn check_current(&self) -> Result<()> {
+        let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+        let capset = credentials.effective_capset();
+
+        if self.pid != current!().pid() as i32 && !capset.contains(CapSet::SYS_ADMIN) {
+            return_errno_with_message!(Errno::EPERM, "the PID in the credentials is not ours");
+        }
+
+        let uid = Uid::from(self.uid);
+        if uid != credentials.ruid()
+            && uid != credentials.euid()
+            && uid != credentials.suid()
+            && !capset.contains(CapSet::SETUID)
+        {
+            return_errno_with_message!(Errno::EPERM, "the UID in the credentials is not ours");
+        }
+
+        let gid = Gid::from(self.gid);
+        if gid != credentials.rgid()
+            && gid != credentials.egid()
+            && gid != credentials.sgid()
+            && !capset.contains(CapSet::SETGID)
+        {
+            return_errno_with_message!(Errno::EPERM, "the GID in the credentials is not ours");
+        }
+
+        Ok(())
+    }
+}
+
+/// The header of a control message (`struct cmsghdr` in Linux).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CControlMessageHeader {
+    len: usize,
+    level: i32,
+    type_: i32,
+}
+
+const HEADER_LEN: usize = size_of::<CControlMessageHeader>();
+
+/// Aligns the length of a control message (`CMSG_ALIGN` in Linux).
+const fn align_len(len: usize) -> usize {
+    len.next_multiple_of(size_of::<usize>())
+}
+
+/// The maximum length of the control messages that can be sent at once.
+///
+/// This is the default value of `net.core.optmem_max` in Linux.
+const MAX_CONTROL_LEN: usize = 20480;
+
+/// The maximum number of files that can be passed with `SCM_RIGHTS` at once (`SCM_MAX_FD` in
+/// Linux).
+const MAX_RIGHTS_FILES: usize = 253;
+
+// Control message types at the `SOL_SOCKET` level.
+const SCM_RIGHTS: i32 = 1;
+const SCM_CREDENTIALS: i32 = 2;
+
+/// The result of writing control messages to the user space.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WrittenControlMessages {
+    /// The number of bytes written.
+    pub len: usize,
+    /// Whether some control messages are discarded due to insufficient space.
+    pub is_truncated: bool,
+}
+
+impl ControlMessage {
+    /// Reads all control messages from the user buffer.
+    ///
+    /// The file descriptors in `SCM_RIGHTS` messages are resolved with `file_table`. Control
+    /// messages at levels other than `SOL_SOCKET` are ignored.
+    pub fn read_all_from_user(
+        user_space: &CurrentUserSpace,
+        addr: Vaddr,
+        len: usize,
+        file_table: &RwArc<FileTable>,
+    ) -> Result<Vec<Self>> {
+        if len > MAX_CONTROL_LEN {
+            return_errno_with_message!(Errno::ENOBUFS, "the control messages are too long");
+        }
+
+        let mut buffer = vec![0u8; len];
+        user_space.read_bytes(addr, &mut VmWriter::from(buffer.as_mut_slice()))?;
+
+        let mut messages = Vec::new();
+        let mut num_files = 0;
+
+        let mut offset = 0;
+        while len - offset >= HEADER_LEN {
+            let header = CControlMessageHeader::from_bytes(&buffer[offset..offset + HEADER_LEN]);
+            if header.len < HEADER_LEN || header.len > len - offset {
+                return_errno_with_message!(Errno::EINVAL, "the control message length is invalid");
+            }
+            let data = &buffer[offset + HEADER_LEN..offset + header.len];
+            offset = (offset + align_len(header.len)).min(len);
+
+            if header.level != CSocketOptionLevel::SOL_SOCKET as i32 {
+                warn!(
+                    "control messages at level {} are not supported",
+                    header.level
+                );
+                continue;
+            }
+
+            match header.type_ {
+                SCM_RIGHTS => {
+                    let files = data
+                        .chunks_exact(size_of::<FileDesc>())
+                        .map(|bytes| {
+                            let fd = FileDesc::from_bytes(bytes);
+                            file_table.read().get_file(fd).cloned()
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+
+                    num_files += files.len();
+                    if files.is_empty() || num_files > MAX_RIGHTS_FILES {
+                        return_errno_with_message!(
+                            Errno::EINVAL,
+                            "the number of files to pass is invalid"
+                        );
+                    }
+
+                    messages.push(Self::Rights(files));
+                }
+                SCM_CREDENTIALS => {
+                    if data.len() != size_of::<CUserCred>() {
+                        return_errno_with_message!(
+                            Errno::EINVAL,
+                            "the credentials have an invalid length"
+                        );
+                    }
+
+                    let cred = CUserCred::from_bytes(data);
+                    cred.check_current()?;
+
+                    messages.push(Self::Credentials(cred));
+                }
+                _ => return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the control message type is not supported"
+                ),
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Writes all control messages to the user buffer.
+    ///
+    /// The files in `SCM_RIGHTS` messages are installed to `file_table` with `fd_flags`. Files
+    /// that do not fit in the buffer are closed.
+    pub fn write_all_to_user(
+        messages: Vec<Self>,
+        user_space: &CurrentUserSpace,
+        addr: Vaddr,
+        len: usize,
+        file_table: &RwArc<FileTable>,
+        fd_flags: FdFlags,
+    ) -> Result<WrittenControlMessages> {
+        let mut buffer = Vec::new();
+        let mut is_truncated = false;
+
+        for message in messages {
+            let Some(space) = len.checked_sub(buffer.len() + HEADER_LEN) else {
+                is_truncated = true;
+                break;
+            };
+
+            let (type_, data) = match message {
+                Self::Rights(files) => {
+                    let max_files = space / size_of::<FileDesc>();
+                    if files.len() > max_files {
+                        is_truncated = true;
+                    }
+
+                    let data = files
+                        .into_iter()
+                        .take(max_files)
+                        .flat_map(|file| file_table.write().insert(file, fd_flags).to_ne_bytes())
+                        .collect::<Vec<_>>();
+                    (SCM_RIGHTS, data)
+                }
+                Self::Credentials(cred) => {
+                    let mut data = cred.as_bytes().to_vec();
+                    if data.len() > space {
+                        data.truncate(space);
+                        is_truncated = true;
+                    }
+                    (SCM_CREDENTIALS, data)
+                }
+            };
+
+            let header = CControlMessageHeader {
+                len: HEADER_LEN + data.len(),
+                level: CSocketOptionLevel::SOL_SOCKET as i32,
+                type_,
+            };
+            buffer.extend_from_slice(header.as_bytes());
+            buffer.extend_from_slice(&data);
+
+            // Like Linux, the padding of the last message is omitted if there is no space.
+            buffer.resize(align_len(buffer.len()).min(len), 0);
+        }
+
+        if !buffer.is_empty() {
+            user_space.write_bytes(addr, &mut VmReader::from(buffer.as_slice()))?;
+        }
+
+        Ok(WrittenControlMessages {
+            len: buffer.len(),
+            is_truncated,
+        })
+    }
+}
@@ -1,21 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::socket_addr::SocketAddr;
+use super::{control_message::ControlMessage, socket_addr::SocketAddr};
 use crate::prelude::*;
 
 /// Message header used for sendmsg/recvmsg.
 #[derive(Debug)]
 pub struct MessageHeader {
     pub(in crate::net) addr: Option<SocketAddr>,
-    pub(in crate::net) control_message: Option<ControlMessage>,
+    pub(in crate::net) control_messages: Vec<ControlMessage>,
 }
 
 impl MessageHeader {
     /// Creates a new `MessageHeader`.
-    pub const fn new(addr: Option<SocketAddr>, control_message: Option<ControlMessage>) -> Self {
+    pub const fn new(addr: Option<SocketAddr>, control_messages: Vec<ControlMessage>) -> Self {
         Self {
             addr,
-            control_message,
+            control_messages,
         }
     }
 
@@ -23,10 +23,14 @@ impl MessageHeader {
     pub fn addr(&self) -> Option<&SocketAddr> {
         self.addr.as_ref()
     }
-}
 
-/// Control message carried by MessageHeader.
-///
-/// TODO: Implement the struct. The struct is empty now.
-#[derive(Debug)]
-pub struct ControlMessage;
+    /// Returns the control messages.
+    pub fn control_messages(&self) -> &[ControlMessage] {
+        &self.control_messages
+    }
+
+    /// Takes the control messages out of the header.
+    pub fn take_control_messages(&mut self) -> Vec<ControlMessage> {
+        core::mem::take(&mut self.control_messages)
+    }
+}
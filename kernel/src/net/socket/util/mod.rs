@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+mod control_message;
 pub mod datagram_common;
 mod message_header;
 pub mod options;
@@ -7,4 +8,5 @@ pub mod send_recv_flags;
 pub mod shutdown_cmd;
 pub mod socket_addr;
 
+pub use control_message::{CUserCred, ControlMessage};
 pub use message_header::MessageHeader;
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The memory controller.
+//!
+//! The frames of the user memory are charged to the cgroup of the process
+//! that commits them, until the frames are freed. Each charged frame holds a
+//! reference to the cgroup in its metadata, so the frame is uncharged from
+//! the same cgroup even if the process moves to another cgroup or exits.
+
+use ostd::{
+    impl_untyped_frame_meta_for,
+    mm::{FrameAllocOptions, UFrame},
+};
+
+use super::Cgroup;
+use crate::{prelude::*, process::Process};
+
+/// The metadata of a frame that is charged to a cgroup.
+#[derive(Debug)]
+pub struct ChargedFrameMeta {
+    cgroup: Arc<Cgroup>,
+}
+
+impl_untyped_frame_meta_for!(ChargedFrameMeta);
+
+impl ChargedFrameMeta {
+    /// Returns the cgroup that the frame is charged to.
+    pub fn cgroup(&self) -> &Arc<Cgroup> {
+        &self.cgroup
+    }
+}
+
+impl Drop for ChargedFrameMeta {
+    fn drop(&mut self) {
+        self.cgroup.uncharge_memory(1);
+    }
+}
+
+/// Allocates a frame of the user memory, which is charged to the cgroup of
+/// the current process.
+///
+/// This method fails with `ENOMEM` if the memory limit of the cgroup (or of
+/// its ancestors) is reached. If there is no current process, e.g., in kernel
+/// threads, the frame is not charged.
+pub fn alloc_charged_frame(zeroed: bool) -> Result<UFrame> {
+    let options = {
+        let mut options = FrameAllocOptions::new();
+        options.zeroed(zeroed);
+        options
+    };
+
+    let Some(process) = Process::current() else {
+        return Ok(options.alloc_frame()?.into());
+    };
+
+    let cgroup = process.cgroup();
+    cgroup.try_charge_memory(1)?;
+    let meta = ChargedFrameMeta { cgroup };
+    // If the allocation fails, the metadata is dropped and the frame is
+    // uncharged.
+    Ok(options.alloc_frame_with(meta)?.into())
+}
@@ -0,0 +1,532 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Control groups (version 2).
+//!
+//! A control group (cgroup) is a group of processes whose resource usage is
+//! limited by controllers. The cgroups form a hierarchy, where the limits of a
+//! cgroup also apply to its descendants. Every process belongs to exactly one
+//! cgroup, which is inherited by its children.
+//!
+//! The following controllers are supported:
+//!  - The CPU controller weighs and limits the CPU time of the threads in the
+//!    FAIR scheduling class (see [`FairGroup`]);
+//!  - The memory controller limits the frames committed to the user memory;
+//!  - The PID controller limits the number of tasks.
+//!
+//! Like Linux, a controller is enabled in a cgroup if it is enabled in the
+//! `cgroup.subtree_control` file of its parent. The root cgroup has all the
+//! controllers enabled, but it cannot be limited.
+//!
+//! The hierarchy is managed by the users via the cgroup file system.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/cgroup-v2.html>
+
+use alloc::format;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use spin::Once;
+
+use super::{Pid, Process};
+use crate::{prelude::*, sched::FairGroup, thread::AsThread};
+
+mod memory;
+
+pub use memory::{alloc_charged_frame, ChargedFrameMeta};
+
+bitflags! {
+    /// A set of cgroup controllers.
+    pub struct Controllers: u8 {
+        const CPU = 1 << 0;
+        const MEMORY = 1 << 1;
+        const PIDS = 1 << 2;
+    }
+}
+
+impl Controllers {
+    const NAMES: [(Controllers, &'static str); 3] = [
+        (Controllers::CPU, "cpu"),
+        (Controllers::MEMORY, "memory"),
+        (Controllers::PIDS, "pids"),
+    ];
+
+    /// Parses the name of a controller.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(_, controller_name)| *controller_name == name)
+            .map(|(controller, _)| *controller)
+    }
+
+    /// Returns the space-separated names of the controllers.
+    pub fn to_names(self) -> String {
+        let names: Vec<_> = Self::NAMES
+            .iter()
+            .filter(|(controller, _)| self.contains(*controller))
+            .map(|(_, name)| *name)
+            .collect();
+        names.join(" ")
+    }
+}
+
+/// Returns the root cgroup.
+pub fn root() -> &'static Arc<Cgroup> {
+    static ROOT: Once<Arc<Cgroup>> = Once::new();
+    ROOT.call_once(|| Arc::new(Cgroup::new(String::new(), None)))
+}
+
+/// The lock that serializes the changes to the hierarchy and the migrations
+/// of processes.
+static HIERARCHY_LOCK: Mutex<()> = Mutex::new(());
+
+/// A control group.
+pub struct Cgroup {
+    name: String,
+    parent: Option<Arc<Cgroup>>,
+    inner: Mutex<CgroupInner>,
+    /// The state of the CPU controller, which is `None` for the root cgroup.
+    fair_group: Option<Arc<FairGroup>>,
+    /// The state of the memory controller, counted in pages.
+    memory: ResourceCounter,
+    /// The state of the PID controller, counted in tasks.
+    pids: ResourceCounter,
+}
+
+struct CgroupInner {
+    children: BTreeMap<String, Arc<Cgroup>>,
+    /// The processes in the cgroup (but not in its descendants).
+    processes: BTreeMap<Pid, Weak<Process>>,
+    /// The controllers that are enabled in the children.
+    subtree_control: Controllers,
+    is_removed: bool,
+}
+
+impl Cgroup {
+    fn new(name: String, parent: Option<Arc<Cgroup>>) -> Self {
+        let fair_group = parent
+            .as_ref()
+            .map(|parent| FairGroup::new(parent.fair_group.clone()));
+
+        Self {
+            name,
+            parent,
+            inner: Mutex::new(CgroupInner {
+                children: BTreeMap::new(),
+                processes: BTreeMap::new(),
+                subtree_control: Controllers::empty(),
+                is_removed: false,
+            }),
+            fair_group,
+            memory: ResourceCounter::new(),
+            pids: ResourceCounter::new(),
+        }
+    }
+
+    /// Returns the name of the cgroup, which is empty for the root cgroup.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parent(&self) -> Option<&Arc<Cgroup>> {
+        self.parent.as_ref()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.parent.is_none()
+    }
+
+    /// Returns the path of the cgroup relative to the root cgroup.
+    pub fn path(&self) -> String {
+        let mut names: Vec<_> = self
+            .ancestors()
+            .filter(|cgroup| !cgroup.is_root())
+            .map(|cgroup| cgroup.name())
+            .collect();
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// Returns the cgroup and its ancestors, from the cgroup to the root.
+    fn ancestors(&self) -> impl Iterator<Item = &Cgroup> {
+        core::iter::successors(Some(self), |cgroup| cgroup.parent.as_deref())
+    }
+
+    /// Returns whether the cgroup is `ancestor` or one of its descendants.
+    pub fn is_descendant_of(&self, ancestor: &Cgroup) -> bool {
+        self.ancestors()
+            .any(|cgroup| core::ptr::eq(cgroup, ancestor))
+    }
+
+    /// Creates a child cgroup.
+    pub fn create_child(self: &Arc<Self>, name: &str) -> Result<Arc<Cgroup>> {
+        let _guard = HIERARCHY_LOCK.lock();
+
+        let mut inner = self.inner.lock();
+        if inner.is_removed {
+            return_errno_with_message!(Errno::ENOENT, "the cgroup has been removed");
+        }
+        if inner.children.contains_key(name) {
+            return_errno_with_message!(Errno::EEXIST, "the cgroup already exists");
+        }
+
+        let child = Arc::new(Cgroup::new(name.to_string(), Some(self.clone())));
+        inner.children.insert(name.to_string(), child.clone());
+        Ok(child)
+    }
+
+    /// Removes a child cgroup, which must have no children or processes.
+    pub fn remove_child(&self, name: &str) -> Result<()> {
+        let _guard = HIERARCHY_LOCK.lock();
+
+        let mut inner = self.inner.lock();
+        let Some(child) = inner.children.get(name) else {
+            return_errno_with_message!(Errno::ENOENT, "the cgroup does not exist");
+        };
+
+        let mut child_inner = child.inner.lock();
+        if !child_inner.children.is_empty() || child_inner.has_processes() {
+            return_errno_with_message!(Errno::EBUSY, "the cgroup is not empty");
+        }
+        child_inner.is_removed = true;
+        drop(child_inner);
+
+        inner.children.remove(name);
+        Ok(())
+    }
+
+    /// Returns the processes in the cgroup (but not in its descendants).
+    pub fn processes(&self) -> Vec<Arc<Process>> {
+        self.inner
+            .lock()
+            .processes
+            .values()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    /// Returns whether the cgroup or its descendants have processes.
+    pub fn is_populated(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.has_processes() || inner.children.values().any(|child| child.is_populated())
+    }
+
+    /// Returns the controllers that are enabled in the cgroup.
+    pub fn controllers(&self) -> Controllers {
+        match &self.parent {
+            Some(parent) => parent.subtree_control(),
+            None => Controllers::all(),
+        }
+    }
+
+    /// Returns the controllers that are enabled in the children.
+    pub fn subtree_control(&self) -> Controllers {
+        self.inner.lock().subtree_control
+    }
+
+    /// Enables and disables the controllers in the children.
+    pub fn update_subtree_control(&self, enable: Controllers, disable: Controllers) -> Result<()> {
+        let _guard = HIERARCHY_LOCK.lock();
+
+        if !self.controllers().contains(enable | disable) {
+            return_errno_with_message!(Errno::ENOENT, "the controller is not enabled");
+        }
+
+        let mut inner = self.inner.lock();
+        // This is the "no internal process" rule: the controllers cannot be
+        // enabled in the children of a cgroup with processes, except for the
+        // root cgroup.
+        let newly_enabled = enable - inner.subtree_control;
+        if !newly_enabled.is_empty() && !self.is_root() && inner.has_processes() {
+            return_errno_with_message!(Errno::EBUSY, "the cgroup has processes");
+        }
+
+        let newly_disabled = disable & inner.subtree_control;
+        for child in inner.children.values() {
+            if child.subtree_control().intersects(newly_disabled) {
+                return_errno_with_message!(
+                    Errno::EBUSY,
+                    "the controller is enabled in the grandchildren"
+                );
+            }
+        }
+        for child in inner.children.values() {
+            child.reset_controllers(newly_disabled);
+        }
+
+        inner.subtree_control = (inner.subtree_control | enable) - disable;
+        Ok(())
+    }
+
+    /// Resets the limits of the disabled controllers.
+    fn reset_controllers(&self, controllers: Controllers) {
+        if controllers.contains(Controllers::CPU) {
+            let fair_group = self.fair_group.as_ref().unwrap();
+            fair_group.set_weight(FairGroup::DEFAULT_WEIGHT);
+            fair_group.set_bandwidth(None, FairGroup::DEFAULT_PERIOD_US);
+        }
+        if controllers.contains(Controllers::MEMORY) {
+            self.memory.set_limit(None);
+        }
+        if controllers.contains(Controllers::PIDS) {
+            self.pids.set_limit(None);
+        }
+    }
+
+    /// Moves the process with all its threads to the cgroup.
+    pub fn attach(self: &Arc<Self>, process: &Arc<Process>) -> Result<()> {
+        let _guard = HIERARCHY_LOCK.lock();
+
+        // The states checked here can only be changed with the hierarchy lock.
+        self.check_attachable()?;
+
+        // The task set is locked so that no threads are created or exit
+        // during the migration.
+        let tasks = process.tasks().lock();
+        let old_cgroup = process.cgroup();
+        if Arc::ptr_eq(&old_cgroup, self) {
+            return Ok(());
+        }
+
+        let nr_tasks = tasks
+            .as_slice()
+            .iter()
+            .filter(|task| !task.as_thread().unwrap().is_exited())
+            .count();
+        if nr_tasks == 0 {
+            return_errno_with_message!(Errno::ESRCH, "the process has exited");
+        }
+
+        // Like Linux, the migration is not restricted by the limits, and the
+        // memory that is already charged stays in the old cgroup.
+        old_cgroup.uncharge_tasks(nr_tasks);
+        self.force_charge_tasks(nr_tasks);
+
+        old_cgroup.remove_process(process);
+        self.add_process(process);
+        process.set_cgroup(self.clone());
+
+        for task in tasks.as_slice() {
+            task.as_thread()
+                .unwrap()
+                .sched_attr()
+                .set_fair_group(self.fair_group.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether processes can be moved to the cgroup.
+    pub(super) fn check_attachable(&self) -> Result<()> {
+        let inner = self.inner.lock();
+        if inner.is_removed {
+            return_errno_with_message!(Errno::ENOENT, "the cgroup has been removed");
+        }
+        // This is the "no internal process" rule.
+        if !self.is_root() && !inner.subtree_control.is_empty() {
+            return_errno_with_message!(
+                Errno::EBUSY,
+                "the cgroup has controllers enabled in its children"
+            );
+        }
+        Ok(())
+    }
+
+    /// Adds a newly created process to the cgroup.
+    pub(super) fn add_process(&self, process: &Arc<Process>) {
+        self.inner
+            .lock()
+            .processes
+            .insert(process.pid(), Arc::downgrade(process));
+    }
+
+    /// Removes an exited process from the cgroup.
+    pub(super) fn remove_process(&self, process: &Process) {
+        self.inner.lock().processes.remove(&process.pid());
+    }
+
+    // *********** CPU controller ***********
+
+    /// Returns the state of the CPU controller.
+    ///
+    /// This method returns `None` for the root cgroup.
+    pub fn fair_group(&self) -> Option<&Arc<FairGroup>> {
+        self.fair_group.as_ref()
+    }
+
+    // *********** Memory controller ***********
+
+    /// Returns the size (in bytes) of the memory charged to the cgroup and
+    /// its descendants.
+    pub fn memory_current(&self) -> usize {
+        self.memory.usage() * PAGE_SIZE
+    }
+
+    /// Returns the memory limit (in bytes), or `None` if it is unlimited.
+    pub fn memory_max(&self) -> Option<usize> {
+        self.memory.limit().map(|nr_pages| nr_pages * PAGE_SIZE)
+    }
+
+    /// Sets the memory limit (in bytes), which is rounded down to pages.
+    pub fn set_memory_max(&self, max: Option<usize>) {
+        self.memory.set_limit(max.map(|max| max / PAGE_SIZE));
+    }
+
+    /// Finds the cgroup among the cgroup and its ancestors whose memory
+    /// limit is reached.
+    pub fn find_memory_limited(self: &Arc<Self>) -> Option<Arc<Cgroup>> {
+        let mut cgroup = self;
+        loop {
+            if cgroup.memory.is_at_limit() {
+                return Some(cgroup.clone());
+            }
+            cgroup = cgroup.parent.as_ref()?;
+        }
+    }
+
+    fn try_charge_memory(&self, nr_pages: usize) -> Result<()> {
+        self.try_charge(|cgroup| &cgroup.memory, nr_pages)
+            .map_err(|_| Error::with_message(Errno::ENOMEM, "the memory limit is reached"))
+    }
+
+    fn uncharge_memory(&self, nr_pages: usize) {
+        self.uncharge(|cgroup| &cgroup.memory, nr_pages);
+    }
+
+    // *********** PID controller ***********
+
+    /// Returns the number of tasks in the cgroup and its descendants.
+    pub fn pids_current(&self) -> usize {
+        self.pids.usage()
+    }
+
+    /// Returns the limit of tasks, or `None` if it is unlimited.
+    pub fn pids_max(&self) -> Option<usize> {
+        self.pids.limit()
+    }
+
+    /// Sets the limit of tasks.
+    pub fn set_pids_max(&self, max: Option<usize>) {
+        self.pids.set_limit(max);
+    }
+
+    /// Charges a new task to the cgroup.
+    ///
+    /// This method fails with `EAGAIN` if the limit of tasks is reached.
+    pub(super) fn try_charge_task(&self) -> Result<()> {
+        self.try_charge(|cgroup| &cgroup.pids, 1)
+            .map_err(|_| Error::with_message(Errno::EAGAIN, "the limit of tasks is reached"))
+    }
+
+    /// Uncharges an exited task from the cgroup.
+    pub(super) fn uncharge_task(&self) {
+        self.uncharge_tasks(1);
+    }
+
+    fn force_charge_tasks(&self, nr_tasks: usize) {
+        for cgroup in self.ancestors() {
+            cgroup.pids.force_charge(nr_tasks);
+        }
+    }
+
+    fn uncharge_tasks(&self, nr_tasks: usize) {
+        self.uncharge(|cgroup| &cgroup.pids, nr_tasks);
+    }
+
+    // *********** Helpers ***********
+
+    /// Charges the resource to the cgroup and its ancestors.
+    ///
+    /// If any of their limits is exceeded, nothing is charged.
+    fn try_charge<F>(&self, counter_of: F, amount: usize) -> core::result::Result<(), ()>
+    where
+        F: Fn(&Cgroup) -> &ResourceCounter,
+    {
+        for (nr_charged, cgroup) in self.ancestors().enumerate() {
+            if counter_of(cgroup).try_charge(amount) {
+                continue;
+            }
+
+            for charged in self.ancestors().take(nr_charged) {
+                counter_of(charged).uncharge(amount);
+            }
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    fn uncharge<F>(&self, counter_of: F, amount: usize)
+    where
+        F: Fn(&Cgroup) -> &ResourceCounter,
+    {
+        for cgroup in self.ancestors() {
+            counter_of(cgroup).uncharge(amount);
+        }
+    }
+}
+
+impl Debug for Cgroup {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Cgroup")
+            .field("path", &self.path())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CgroupInner {
+    fn has_processes(&self) -> bool {
+        self.processes
+            .values()
+            .any(|process| process.strong_count() > 0)
+    }
+}
+
+/// A counter of the usage of a resource, with an optional limit.
+struct ResourceCounter {
+    usage: AtomicUsize,
+    /// The limit, which is `usize::MAX` if the usage is unlimited.
+    limit: AtomicUsize,
+}
+
+impl ResourceCounter {
+    const fn new() -> Self {
+        Self {
+            usage: AtomicUsize::new(0),
+            limit: AtomicUsize::new(usize::MAX),
+        }
+    }
+
+    fn usage(&self) -> usize {
+        self.usage.load(Ordering::Relaxed)
+    }
+
+    fn limit(&self) -> Option<usize> {
+        let limit = self.limit.load(Ordering::Relaxed);
+        (limit != usize::MAX).then_some(limit)
+    }
+
+    fn set_limit(&self, limit: Option<usize>) {
+        self.limit
+            .store(limit.unwrap_or(usize::MAX), Ordering::Relaxed);
+    }
+
+    fn is_at_limit(&self) -> bool {
+        self.usage() >= self.limit.load(Ordering::Relaxed)
+    }
+
+    fn try_charge(&self, amount: usize) -> bool {
+        let limit = self.limit.load(Ordering::Relaxed);
+        self.usage
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |usage| {
+                usage.checked_add(amount).filter(|&new| new <= limit)
+            })
+            .is_ok()
+    }
+
+    fn force_charge(&self, amount: usize) {
+        self.usage.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    fn uncharge(&self, amount: usize) {
+        self.usage.fetch_sub(amount, Ordering::Relaxed);
+    }
+}
@@ -5,18 +5,25 @@ use core::{num::NonZeroU64, sync::atomic::Ordering};
 use ostd::{cpu::context::UserContext, sync::RwArc, task::Task, user::UserContextApi};
 
 use super::{
+    cgroup::Cgroup,
+    namespace::{check_sys_admin, NsProxy, CLONE_NEW_NS_FLAGS},
+    pid_file::PidFile,
     posix_thread::{AsPosixThread, PosixThreadBuilder, ThreadName},
     process_table,
     process_vm::ProcessVm,
+    ptrace::ptrace_clone,
     signal::{constants::SIGCHLD, sig_disposition::SigDispositions, sig_num::SigNum},
     Credentials, Process, ProcessBuilder,
 };
 use crate::{
     cpu::LinuxAbi,
     current_userspace,
-    fs::{file_table::FileTable, thread_info::ThreadFsInfo},
+    fs::{
+        cgroupfs,
+        file_table::{get_file_fast, FdFlags, FileDesc, FileTable},
+        thread_info::ThreadFsInfo,
+    },
     prelude::*,
-    process::posix_thread::allocate_posix_tid,
     thread::{AsThread, Tid},
 };
 
@@ -77,7 +84,7 @@ bitflags! {
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CloneArgs {
     pub flags: CloneFlags,
-    pub _pidfd: Option<u64>,
+    pub pidfd: Option<Vaddr>,
     pub child_tid: Vaddr,
     pub parent_tid: Option<Vaddr>,
     pub exit_signal: Option<SigNum>,
@@ -86,7 +93,9 @@ pub struct CloneArgs {
     pub tls: u64,
     pub _set_tid: Option<u64>,
     pub _set_tid_size: Option<u64>,
-    pub _cgroup: Option<u64>,
+    /// The file descriptor of the cgroup directory that the child is put
+    /// in, if `CLONE_INTO_CGROUP` is specified.
+    pub cgroup: Option<FileDesc>,
 }
 
 impl CloneArgs {
@@ -109,7 +118,7 @@ impl CloneArgs {
             flags.contains(CloneFlags::CLONE_PARENT_SETTID),
         ) {
             (false, false) => (None, None),
-            (true, false) => (Some(parent_tid as u64), None),
+            (true, false) => (Some(parent_tid), None),
             (false, true) => (None, Some(parent_tid)),
             (true, true) => {
                 return_errno_with_message!(
@@ -121,7 +130,7 @@ impl CloneArgs {
 
         Ok(Self {
             flags,
-            _pidfd: pidfd,
+            pidfd,
             child_tid,
             parent_tid,
             exit_signal: (exit_signal != 0).then(|| SigNum::from_u8(exit_signal as u8)),
@@ -167,13 +176,44 @@ impl CloneFlags {
             | CloneFlags::CLONE_PARENT_SETTID
             | CloneFlags::CLONE_CHILD_SETTID
             | CloneFlags::CLONE_CHILD_CLEARTID
-            | CloneFlags::CLONE_VFORK;
+            | CloneFlags::CLONE_VFORK
+            | CloneFlags::CLONE_PIDFD
+            | CLONE_NEW_NS_FLAGS;
         let unsupported_flags = *self - supported_flags;
         if !unsupported_flags.is_empty() {
             warn!("contains unsupported clone flags: {:?}", unsupported_flags);
         }
         Ok(())
     }
+
+    fn check_invalid_ns_flags(&self) -> Result<()> {
+        // These combinations are not valid, according to the Linux man pages. See
+        // <https://www.man7.org/linux/man-pages/man2/clone.2.html>.
+        if self.contains(CloneFlags::CLONE_NEWNS | CloneFlags::CLONE_FS) {
+            return_errno_with_message!(Errno::EINVAL, "`CLONE_NEWNS` with `CLONE_FS` is not valid");
+        }
+        if self.contains(CloneFlags::CLONE_NEWPID)
+            && self.intersects(CloneFlags::CLONE_THREAD | CloneFlags::CLONE_PARENT)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "`CLONE_NEWPID` with `CLONE_THREAD` or `CLONE_PARENT` is not valid"
+            );
+        }
+        Ok(())
+    }
+
+    fn check_invalid_pidfd_flags(&self) -> Result<()> {
+        if self.contains(CloneFlags::CLONE_PIDFD)
+            && self.intersects(CloneFlags::CLONE_THREAD | CloneFlags::CLONE_DETACHED)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "`CLONE_PIDFD` with `CLONE_THREAD` or `CLONE_DETACHED` is not valid"
+            );
+        }
+        Ok(())
+    }
 }
 
 /// Clone a child thread or child process.
@@ -186,19 +226,37 @@ pub fn clone_child(
     clone_args: CloneArgs,
 ) -> Result<Tid> {
     clone_args.flags.check_unsupported_flags()?;
+    clone_args.flags.check_invalid_ns_flags()?;
+    clone_args.flags.check_invalid_pidfd_flags()?;
     if clone_args.flags.contains(CloneFlags::CLONE_THREAD) {
         let child_task = clone_child_task(ctx, parent_context, clone_args)?;
         let child_thread = child_task.as_thread().unwrap();
+        ptrace_clone(ctx, child_thread, &clone_args);
         child_thread.run();
 
         let child_tid = child_thread.as_posix_thread().unwrap().tid();
-        Ok(child_tid)
+        Ok(ctx.process.pid_ns().tid_in_ns(child_tid).unwrap())
     } else {
+        let pidfd_addr = clone_args
+            .pidfd
+            .filter(|_| clone_args.flags.contains(CloneFlags::CLONE_PIDFD));
+        // Check the address before creating the child process, so that no child process is
+        // left behind if the address is invalid.
+        if let Some(addr) = pidfd_addr {
+            let invalid_fd: FileDesc = -1;
+            ctx.user_space().write_val(addr, &invalid_fd)?;
+        }
+
         let child_process = clone_child_process(ctx, parent_context, clone_args)?;
         if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
             child_process.status().set_vfork_child(true);
         }
 
+        if let Some(addr) = pidfd_addr {
+            clone_pidfd(ctx, &child_process, addr);
+        }
+
+        ptrace_clone(ctx, &child_process.main_thread(), &clone_args);
         child_process.run();
 
         if child_process.status().is_vfork_child() {
@@ -208,7 +266,7 @@ pub fn clone_child(
         }
 
         let child_pid = child_process.pid();
-        Ok(child_pid)
+        Ok(ctx.process.pid_ns().tid_in_ns(child_pid).unwrap())
     }
 }
 
@@ -244,6 +302,9 @@ fn clone_child_task(
     // clone fs
     let child_fs = clone_fs(posix_thread.fs(), clone_flags);
 
+    // clone namespaces
+    let child_ns_proxy = clone_ns_proxy(ctx, &child_fs, clone_flags)?;
+
     let child_user_ctx = Arc::new(clone_user_ctx(
         parent_context,
         clone_args.stack,
@@ -255,7 +316,8 @@ fn clone_child_task(
     // Inherit sigmask from current thread
     let sig_mask = posix_thread.sig_mask().load(Ordering::Relaxed).into();
 
-    let child_tid = allocate_posix_tid();
+    let pid_ns = process.pid_ns();
+    let child_tid = pid_ns.alloc_tid()?;
     let child_task = {
         let credentials = {
             let credentials = ctx.posix_thread.credentials();
@@ -266,21 +328,40 @@ fn clone_child_task(
             .process(posix_thread.weak_process())
             .sig_mask(sig_mask)
             .file_table(child_file_table)
-            .fs(child_fs);
+            .fs(child_fs)
+            .ns_proxy(child_ns_proxy)
+            .seccomp(posix_thread.seccomp().inherit())
+            .no_new_privs(posix_thread.no_new_privs());
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        let child_tid_in_ns = pid_ns.tid_in_ns(child_tid).unwrap();
+        clone_parent_settid(child_tid_in_ns, clone_args.parent_tid, clone_flags)
+            .inspect_err(|_| pid_ns.free_tid(child_tid))?;
         thread_builder = clone_child_cleartid(thread_builder, clone_args.child_tid, clone_flags);
         thread_builder = clone_child_settid(thread_builder, clone_args.child_tid, clone_flags);
 
         thread_builder.build()
     };
 
-    process
-        .tasks()
-        .lock()
-        .insert(child_task.clone())
-        .map_err(|_| Error::with_message(Errno::EINTR, "the process has exited"))?;
+    {
+        // The process cannot move to another cgroup while its task set is
+        // locked.
+        let mut tasks = process.tasks().lock();
+        let cgroup = process.cgroup();
+        cgroup
+            .try_charge_task()
+            .inspect_err(|_| pid_ns.free_tid(child_tid))?;
+        tasks.insert(child_task.clone()).map_err(|_| {
+            cgroup.uncharge_task();
+            pid_ns.free_tid(child_tid);
+            Error::with_message(Errno::EINTR, "the process has exited")
+        })?;
+        child_task
+            .as_thread()
+            .unwrap()
+            .sched_attr()
+            .set_fair_group(cgroup.fair_group().cloned());
+    }
 
     Ok(child_task)
 }
@@ -320,6 +401,9 @@ fn clone_child_process(
     // clone fs
     let child_fs = clone_fs(posix_thread.fs(), clone_flags);
 
+    // clone namespaces
+    let child_ns_proxy = clone_ns_proxy(ctx, &child_fs, clone_flags)?;
+
     // clone sig dispositions
     let child_sig_dispositions = clone_sighand(process.sig_dispositions(), clone_flags);
 
@@ -332,7 +416,14 @@ fn clone_child_process(
     // inherit parent's nice value
     let child_nice = process.nice().load(Ordering::Relaxed);
 
-    let child_tid = allocate_posix_tid();
+    // inherit parent's cgroup, unless `CLONE_INTO_CGROUP` is specified
+    let child_cgroup = match clone_args.cgroup {
+        Some(cgroup_fd) => cgroup_of_fd(ctx, cgroup_fd)?,
+        None => process.cgroup(),
+    };
+
+    let child_pid_ns = child_ns_proxy.pid_ns_for_children().clone();
+    let child_tid = child_pid_ns.alloc_tid()?;
 
     let child = {
         let child_elf_path = process.executable_path();
@@ -349,10 +440,15 @@ fn clone_child_process(
                 .sig_mask(child_sig_mask)
                 .file_table(child_file_table)
                 .fs(child_fs)
+                .ns_proxy(child_ns_proxy)
+                .seccomp(posix_thread.seccomp().inherit())
+                .no_new_privs(posix_thread.no_new_privs())
         };
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        let child_tid_in_ns = process.pid_ns().tid_in_ns(child_tid).unwrap();
+        clone_parent_settid(child_tid_in_ns, clone_args.parent_tid, clone_flags)
+            .inspect_err(|_| child_pid_ns.free_tid(child_tid))?;
         child_thread_builder =
             clone_child_cleartid(child_thread_builder, clone_args.child_tid, clone_flags);
         child_thread_builder =
@@ -365,15 +461,23 @@ fn clone_child_process(
             .main_thread_builder(child_thread_builder)
             .process_vm(child_process_vm)
             .sig_dispositions(child_sig_dispositions)
-            .nice(child_nice);
+            .nice(child_nice)
+            .pid_ns(child_pid_ns.clone())
+            .cgroup(child_cgroup);
 
-        process_builder.build()?
+        process_builder
+            .build()
+            .inspect_err(|_| child_pid_ns.free_tid(child_tid))?
     };
 
     if let Some(sig) = clone_args.exit_signal {
         child.set_exit_signal(sig);
     };
 
+    // Inherit the OOM score adjustment and the dumpable attribute.
+    child.set_oom_score_adj(process.oom_score_adj());
+    child.set_dumpable(process.dumpable());
+
     // Sets parent process and group for child process.
     set_parent_and_group(process, &child);
 
@@ -387,6 +491,38 @@ fn clone_child_process(
     Ok(child)
 }
 
+/// Returns the cgroup of the directory that `cgroup_fd` refers to, which the
+/// child process is put in.
+fn cgroup_of_fd(ctx: &Context, cgroup_fd: FileDesc) -> Result<Arc<Cgroup>> {
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, cgroup_fd);
+    let cgroup = file
+        .as_inode_or_err()
+        .ok()
+        .and_then(|inode_handle| cgroupfs::cgroup_of_inode(inode_handle.dentry().inode().as_ref()))
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a cgroup directory"))?;
+
+    cgroup.check_attachable()?;
+    Ok(cgroup)
+}
+
+/// Creates a PID file of the child process for the parent and stores the file descriptor at
+/// `pidfd_addr`.
+fn clone_pidfd(ctx: &Context, child_process: &Arc<Process>, pidfd_addr: Vaddr) {
+    let pid_file = Arc::new(PidFile::new(child_process.clone(), false));
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        // Like Linux, the PID file is always closed on `execve`.
+        file_table_locked.insert(pid_file, FdFlags::CLOEXEC)
+    };
+
+    // The address has been checked before creating the child process. If the write fails
+    // anyway (e.g., the memory is unmapped concurrently), the file descriptor is still valid
+    // and can be found through `/proc/self/fd`.
+    let _ = ctx.user_space().write_val(pidfd_addr, &fd);
+}
+
 fn clone_child_cleartid(
     child_builder: PosixThreadBuilder,
     child_tidptr: Vaddr,
@@ -480,6 +616,35 @@ fn clone_fs(parent_fs: &Arc<ThreadFsInfo>, clone_flags: CloneFlags) -> Arc<Threa
     }
 }
 
+fn clone_ns_proxy(
+    ctx: &Context,
+    child_fs: &ThreadFsInfo,
+    clone_flags: CloneFlags,
+) -> Result<Arc<NsProxy>> {
+    let parent_ns_proxy = ctx.posix_thread.ns_proxy();
+
+    let ns_flags = clone_flags & CLONE_NEW_NS_FLAGS;
+    if ns_flags.is_empty() {
+        return Ok(parent_ns_proxy);
+    }
+    check_sys_admin(ctx.posix_thread)?;
+
+    let child_ns_proxy = parent_ns_proxy.new_copy(ns_flags, ctx.process.pid_ns())?;
+
+    // The child's root and working directories should be in the new mount namespace.
+    if ns_flags.contains(CloneFlags::CLONE_NEWNS) {
+        let mut resolver = child_fs.resolver().write();
+        let parent_mnt_ns = parent_ns_proxy.mnt_ns();
+        let child_mnt_ns = child_ns_proxy.mnt_ns();
+        let root = parent_mnt_ns.translate(resolver.root(), child_mnt_ns);
+        let cwd = parent_mnt_ns.translate(resolver.cwd(), child_mnt_ns);
+        resolver.set_root(root);
+        resolver.set_cwd(cwd);
+    }
+
+    Ok(child_ns_proxy)
+}
+
 fn clone_files(parent_file_table: &RwArc<FileTable>, clone_flags: CloneFlags) -> RwArc<FileTable> {
     // if CLONE_FILES is set, the child and parent shares the same file table
     // Otherwise, the child will deep copy a new file table.
@@ -0,0 +1,480 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The ELF layout of core files.
+//!
+//! A core file consists of the ELF header, the program headers, the notes,
+//! and the dumped memory, in this order. The dumped memory of each mapping
+//! starts at a page boundary.
+
+use core::{mem::size_of, sync::atomic::Ordering};
+
+use align_ext::AlignExt;
+use aster_rights::Full;
+use ostd::cpu::context::UserContext;
+
+use super::CoreFileWriter;
+use crate::{
+    arch::ptrace::{save_fp_regs, PtraceRegs},
+    prelude::*,
+    process::{posix_thread::PosixThread, signal::c_types::siginfo_t, Pid, Process},
+    time::timeval_t,
+    vm::{
+        perms::VmPerms,
+        vmar::{VmMappingInfo, Vmar},
+    },
+};
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EV_CURRENT: u8 = 1;
+const ET_CORE: u16 = 4;
+
+#[cfg(target_arch = "x86_64")]
+const ELF_MACHINE: u16 = 62; // EM_X86_64
+#[cfg(target_arch = "riscv64")]
+const ELF_MACHINE: u16 = 243; // EM_RISCV
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+/// The maximum number of program headers that can be recorded in `e_phnum`.
+const PN_XNUM: usize = 0xffff;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRFPREG: u32 = 2;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_SIGINFO: u32 = 0x5349_4749;
+const NT_FILE: u32 = 0x4649_4c45;
+
+/// The ELF header (`Elf64_Ehdr`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfHeader {
+    ident: [u8; 16],
+    type_: u16,
+    machine: u16,
+    version: u32,
+    entry: u64,
+    phoff: u64,
+    shoff: u64,
+    flags: u32,
+    ehsize: u16,
+    phentsize: u16,
+    phnum: u16,
+    shentsize: u16,
+    shnum: u16,
+    shstrndx: u16,
+}
+
+/// The program header (`Elf64_Phdr`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct ProgramHeader {
+    type_: u32,
+    flags: u32,
+    offset: u64,
+    vaddr: u64,
+    paddr: u64,
+    filesz: u64,
+    memsz: u64,
+    align: u64,
+}
+
+/// The header of a note (`Elf64_Nhdr`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct NoteHeader {
+    namesz: u32,
+    descsz: u32,
+    type_: u32,
+}
+
+/// The signal information in [`ElfPrStatus`] (`struct elf_siginfo`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfSigInfo {
+    signo: i32,
+    code: i32,
+    errno: i32,
+}
+
+/// The status of a thread (`struct elf_prstatus`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfPrStatus {
+    info: ElfSigInfo,
+    cursig: u16,
+    _pad0: u16,
+    sigpend: u64,
+    sighold: u64,
+    pid: u32,
+    ppid: u32,
+    pgrp: u32,
+    sid: u32,
+    utime: timeval_t,
+    stime: timeval_t,
+    cutime: timeval_t,
+    cstime: timeval_t,
+    reg: PtraceRegs,
+    fpvalid: i32,
+    _pad1: u32,
+}
+
+/// The information about a process (`struct elf_prpsinfo`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct ElfPrPsInfo {
+    state: u8,
+    sname: u8,
+    zomb: u8,
+    nice: i8,
+    _pad0: u32,
+    flag: u64,
+    uid: u32,
+    gid: u32,
+    pid: u32,
+    ppid: u32,
+    pgrp: u32,
+    sid: u32,
+    fname: [u8; 16],
+    psargs: [u8; 80],
+}
+
+/// Collects the notes in the `PT_NOTE` segment.
+///
+/// Only the status of the current thread is recorded.
+pub(super) fn collect_notes(
+    ctx: &Context,
+    user_ctx: &UserContext,
+    siginfo: &siginfo_t,
+    mappings: &[VmMappingInfo],
+) -> Vec<u8> {
+    let mut notes = Notes::default();
+
+    let fp_regs = save_fp_regs(user_ctx).ok();
+    let prstatus = prstatus(ctx, user_ctx, siginfo, fp_regs.is_some());
+    notes.push(NT_PRSTATUS, prstatus.as_bytes());
+    notes.push(NT_PRPSINFO, prpsinfo(ctx).as_bytes());
+    notes.push(NT_SIGINFO, siginfo.as_bytes());
+
+    match ctx.process.init_stack_reader().auxv() {
+        Ok(auxv) => {
+            let desc: Vec<u8> = auxv
+                .iter()
+                .flat_map(|(key, val)| key.to_ne_bytes().into_iter().chain(val.to_ne_bytes()))
+                .collect();
+            notes.push(NT_AUXV, &desc);
+        }
+        Err(err) => debug!("failed to read the auxiliary vector: {:?}", err),
+    }
+
+    notes.push(NT_FILE, &mapped_files(mappings));
+
+    if let Some(fp_regs) = fp_regs {
+        notes.push(NT_PRFPREG, &fp_regs);
+    }
+
+    notes.buf
+}
+
+/// Writes the core file with the collected notes.
+pub(super) fn write_core_file(
+    writer: &mut CoreFileWriter,
+    vmar: &Vmar<Full>,
+    mappings: &[VmMappingInfo],
+    notes: &[u8],
+) -> Result<()> {
+    let phnum = mappings.len() + 1;
+    if phnum >= PN_XNUM {
+        return_errno_with_message!(Errno::EFBIG, "there are too many mappings to dump");
+    }
+
+    let notes_offset = size_of::<ElfHeader>() + size_of::<ProgramHeader>() * phnum;
+    let data_offset = (notes_offset + notes.len()).align_up(PAGE_SIZE);
+    let dump_sizes: Vec<usize> = mappings
+        .iter()
+        .map(|mapping| dump_size(vmar, mapping))
+        .collect();
+
+    let mut ident = [0u8; 16];
+    ident[..4].copy_from_slice(&ELF_MAGIC);
+    ident[4] = ELFCLASS64;
+    ident[5] = ELFDATA2LSB;
+    ident[6] = EV_CURRENT;
+    let header = ElfHeader {
+        ident,
+        type_: ET_CORE,
+        machine: ELF_MACHINE,
+        version: EV_CURRENT as u32,
+        entry: 0,
+        phoff: size_of::<ElfHeader>() as u64,
+        shoff: 0,
+        flags: 0,
+        ehsize: size_of::<ElfHeader>() as u16,
+        phentsize: size_of::<ProgramHeader>() as u16,
+        phnum: phnum as u16,
+        shentsize: 0,
+        shnum: 0,
+        shstrndx: 0,
+    };
+    writer.write(header.as_bytes())?;
+
+    let note_header = ProgramHeader {
+        type_: PT_NOTE,
+        flags: 0,
+        offset: notes_offset as u64,
+        vaddr: 0,
+        paddr: 0,
+        filesz: notes.len() as u64,
+        memsz: 0,
+        align: 4,
+    };
+    writer.write(note_header.as_bytes())?;
+
+    let mut offset = data_offset;
+    for (mapping, dump_size) in mappings.iter().zip(dump_sizes.iter()) {
+        let load_header = ProgramHeader {
+            type_: PT_LOAD,
+            flags: segment_flags(mapping.perms),
+            offset: offset as u64,
+            vaddr: mapping.range.start as u64,
+            paddr: 0,
+            filesz: *dump_size as u64,
+            memsz: mapping.range.len() as u64,
+            align: PAGE_SIZE as u64,
+        };
+        writer.write(load_header.as_bytes())?;
+        offset += dump_size;
+    }
+
+    writer.write(notes)?;
+    writer.skip_to(data_offset)?;
+
+    let mut page = vec![0u8; PAGE_SIZE];
+    for (mapping, dump_size) in mappings.iter().zip(dump_sizes.iter()) {
+        let start = mapping.range.start;
+        for addr in (start..start + dump_size).step_by(PAGE_SIZE) {
+            // The pages that cannot be read are dumped as zeros, like Linux.
+            match vmar.read_page_for_dump(addr, &mut page) {
+                Ok(true) => writer.write(&page)?,
+                Ok(false) | Err(_) => writer.skip(PAGE_SIZE)?,
+            }
+        }
+    }
+    debug_assert_eq!(writer.offset(), offset);
+
+    Ok(())
+}
+
+/// Returns the number of bytes to dump for the mapping.
+///
+/// Like the default `coredump_filter` of Linux, anonymous mappings and
+/// private writable file mappings are dumped entirely, since their contents
+/// cannot be found elsewhere. For other file mappings, only the first page is
+/// dumped if it is an ELF header, so that debuggers can identify the file.
+fn dump_size(vmar: &Vmar<Full>, mapping: &VmMappingInfo) -> usize {
+    if !mapping.perms.contains(VmPerms::READ) {
+        return 0;
+    }
+
+    let Some((_, file_offset)) = &mapping.file else {
+        return mapping.range.len();
+    };
+    if !mapping.is_shared && mapping.perms.contains(VmPerms::WRITE) {
+        return mapping.range.len();
+    }
+
+    let mut magic = [0u8; 4];
+    if *file_offset == 0
+        && vmar.read_remote(mapping.range.start, &mut magic).is_ok()
+        && magic == ELF_MAGIC
+    {
+        return PAGE_SIZE;
+    }
+    0
+}
+
+fn segment_flags(perms: VmPerms) -> u32 {
+    let mut flags = 0;
+    if perms.contains(VmPerms::READ) {
+        flags |= PF_R;
+    }
+    if perms.contains(VmPerms::WRITE) {
+        flags |= PF_W;
+    }
+    if perms.contains(VmPerms::EXEC) {
+        flags |= PF_X;
+    }
+    flags
+}
+
+fn prstatus(
+    ctx: &Context,
+    user_ctx: &UserContext,
+    siginfo: &siginfo_t,
+    fpvalid: bool,
+) -> ElfPrStatus {
+    let posix_thread = ctx.posix_thread;
+    let process = ctx.process;
+    let ids = ProcessIds::new(process, posix_thread);
+
+    let prof_clock = posix_thread.prof_clock();
+    let utime = timeval_t::from(prof_clock.user_clock().read_time());
+    let stime = timeval_t::from(prof_clock.kernel_clock().read_time());
+
+    ElfPrStatus {
+        info: ElfSigInfo {
+            signo: siginfo.si_signo,
+            code: 0,
+            errno: 0,
+        },
+        cursig: siginfo.si_signo as u16,
+        _pad0: 0,
+        sigpend: u64::from(posix_thread.sig_pending()),
+        sighold: u64::from(posix_thread.sig_mask().load(Ordering::Relaxed)),
+        pid: ids.tid,
+        ppid: ids.ppid,
+        pgrp: ids.pgrp,
+        sid: ids.sid,
+        utime,
+        stime,
+        cutime: timeval_t::default(),
+        cstime: timeval_t::default(),
+        reg: PtraceRegs::from_user_ctx(user_ctx, None),
+        fpvalid: fpvalid as i32,
+        _pad1: 0,
+    }
+}
+
+fn prpsinfo(ctx: &Context) -> ElfPrPsInfo {
+    let posix_thread = ctx.posix_thread;
+    let process = ctx.process;
+    let ids = ProcessIds::new(process, posix_thread);
+    let credentials = posix_thread.credentials();
+
+    let mut fname = [0u8; 16];
+    if let Some(thread_name) = posix_thread.thread_name().lock().as_ref()
+        && let Ok(Some(name)) = thread_name.name()
+    {
+        let name = name.to_bytes();
+        let len = name.len().min(fname.len() - 1);
+        fname[..len].copy_from_slice(&name[..len]);
+    }
+
+    let mut psargs = [0u8; 80];
+    if let Ok(argv) = process.init_stack_reader().argv() {
+        let args = argv
+            .iter()
+            .map(|arg| arg.to_bytes())
+            .collect::<Vec<_>>()
+            .join(&b' ');
+        let len = args.len().min(psargs.len() - 1);
+        psargs[..len].copy_from_slice(&args[..len]);
+    }
+
+    ElfPrPsInfo {
+        state: 0,
+        sname: b'R',
+        zomb: 0,
+        nice: 0,
+        _pad0: 0,
+        flag: 0,
+        uid: u32::from(credentials.ruid()),
+        gid: u32::from(credentials.rgid()),
+        pid: ids.pid,
+        ppid: ids.ppid,
+        pgrp: ids.pgrp,
+        sid: ids.sid,
+        fname,
+        psargs,
+    }
+}
+
+/// The IDs of the current thread, as seen in the PID namespace of the process.
+struct ProcessIds {
+    pid: Pid,
+    tid: Pid,
+    ppid: Pid,
+    pgrp: Pid,
+    sid: Pid,
+}
+
+impl ProcessIds {
+    fn new(process: &Process, posix_thread: &PosixThread) -> Self {
+        let pid_ns = process.pid_ns();
+        let id_in_ns = |id| pid_ns.tid_in_ns(id).unwrap_or(0);
+
+        Self {
+            pid: id_in_ns(process.pid()),
+            tid: id_in_ns(posix_thread.tid()),
+            ppid: id_in_ns(process.parent().pid()),
+            pgrp: id_in_ns(process.pgid()),
+            sid: process
+                .session()
+                .map_or(0, |session| id_in_ns(session.sid())),
+        }
+    }
+}
+
+/// Builds the description of the `NT_FILE` note.
+///
+/// The description consists of the number of file mappings, the page size,
+/// an array of `(start, end, file_offset_in_pages)` tuples, and the
+/// NUL-terminated paths of the files.
+fn mapped_files(mappings: &[VmMappingInfo]) -> Vec<u8> {
+    let files: Vec<_> = mappings
+        .iter()
+        .filter_map(|mapping| {
+            let (file, offset) = mapping.file.as_ref()?;
+            Some((mapping, file, *offset))
+        })
+        .collect();
+
+    let mut desc = Vec::new();
+    desc.extend_from_slice(&(files.len() as u64).to_ne_bytes());
+    desc.extend_from_slice(&(PAGE_SIZE as u64).to_ne_bytes());
+    for (mapping, _, offset) in files.iter() {
+        desc.extend_from_slice(&(mapping.range.start as u64).to_ne_bytes());
+        desc.extend_from_slice(&(mapping.range.end as u64).to_ne_bytes());
+        desc.extend_from_slice(&((offset / PAGE_SIZE) as u64).to_ne_bytes());
+    }
+    for (_, file, _) in files.iter() {
+        desc.extend_from_slice(file.abs_path().as_bytes());
+        desc.push(0);
+    }
+    desc
+}
+
+const NOTE_NAME: &[u8] = b"CORE\0";
+
+/// The notes, each of which is named `CORE`.
+#[derive(Default)]
+struct Notes {
+    buf: Vec<u8>,
+}
+
+impl Notes {
+    fn push(&mut self, type_: u32, desc: &[u8]) {
+        let header = NoteHeader {
+            namesz: NOTE_NAME.len() as u32,
+            descsz: desc.len() as u32,
+            type_,
+        };
+        self.buf.extend_from_slice(header.as_bytes());
+        self.buf.extend_from_slice(NOTE_NAME);
+        self.pad();
+        self.buf.extend_from_slice(desc);
+        self.pad();
+    }
+
+    /// Pads the notes to 4 bytes, which is the alignment of notes.
+    fn pad(&mut self) {
+        let len = self.buf.len().align_up(4);
+        self.buf.resize(len, 0);
+    }
+}
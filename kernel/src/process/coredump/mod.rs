@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Core dumps, which are generated when processes are terminated by signals
+//! whose default action is to dump core (e.g., `SIGSEGV` and `SIGABRT`).
+//!
+//! A core file is an ELF file of type `ET_CORE`. It has a `PT_NOTE` segment,
+//! which describes the state of the process (e.g., the registers, the
+//! auxiliary vector, and the mapped files), and a `PT_LOAD` segment for each
+//! mapping in the address space of the process.
+//!
+//! The core file is named after `/proc/sys/kernel/core_pattern`, and is
+//! created relative to the working directory of the process. No core file is
+//! generated if the process is not dumpable (see [`Dumpable`]), or if the
+//! `RLIMIT_CORE` resource limit is too small.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/core.5.html>
+
+use core::fmt::Write;
+
+use ostd::cpu::context::UserContext;
+
+use self::elf::{collect_notes, write_core_file};
+use super::{signal::c_types::siginfo_t, ResourceType};
+use crate::{
+    fs::{
+        fs_resolver::{FsPath, AT_FDCWD},
+        utils::{AccessMode, CreationFlags, Inode, InodeType},
+    },
+    prelude::*,
+    time::clocks::RealTimeClock,
+};
+
+mod elf;
+
+/// Whether core dumps can be generated for a process.
+///
+/// This attribute also controls whether the process can be accessed by
+/// threads without `CAP_SYS_PTRACE` (e.g., via `ptrace` or `pidfd_getfd`).
+/// The ownership of the files in `/proc/[pid]` is not affected yet.
+///
+/// Reference: <https://man7.org/linux/man-pages/man2/pr_set_dumpable.2const.html>
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+pub enum Dumpable {
+    /// Core dumps are not generated.
+    Disable = 0,
+    /// Core dumps are generated.
+    User = 1,
+    /// Core dumps are generated and are only readable by root.
+    ///
+    /// This value can only be set via `/proc/sys/fs/suid_dumpable`.
+    Root = 2,
+}
+
+/// The default core pattern.
+const DEFAULT_CORE_PATTERN: &str = "core";
+
+/// The maximum length of the core pattern.
+const CORE_PATTERN_MAX_LEN: usize = 127;
+
+static CORE_PATTERN: RwLock<Option<String>> = RwLock::new(None);
+
+/// Returns the core pattern, which specifies the names of core files.
+pub fn core_pattern() -> String {
+    CORE_PATTERN
+        .read()
+        .clone()
+        .unwrap_or_else(|| DEFAULT_CORE_PATTERN.to_string())
+}
+
+/// Sets the core pattern.
+///
+/// A trailing newline in `pattern` is ignored.
+pub fn set_core_pattern(pattern: &str) -> Result<()> {
+    let pattern = pattern.strip_suffix('\n').unwrap_or(pattern);
+    if pattern.len() > CORE_PATTERN_MAX_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the core pattern is too long");
+    }
+
+    *CORE_PATTERN.write() = Some(pattern.to_string());
+    Ok(())
+}
+
+/// Generates a core dump for the current process, which is being terminated
+/// by the signal described in `siginfo`.
+///
+/// This method returns whether a core file is generated.
+//
+// FIXME: The other threads in the process keep running while the core file
+// is being written, so the dumped memory may be inconsistent. Their
+// registers are not dumped either.
+pub(super) fn do_coredump(ctx: &Context, user_ctx: &UserContext, siginfo: &siginfo_t) -> bool {
+    match dump_core(ctx, user_ctx, siginfo) {
+        Ok(()) => true,
+        Err(err) => {
+            debug!(
+                "failed to dump core for process {}: {:?}",
+                ctx.process.pid(),
+                err
+            );
+            false
+        }
+    }
+}
+
+fn dump_core(ctx: &Context, user_ctx: &UserContext, siginfo: &siginfo_t) -> Result<()> {
+    let process = ctx.process;
+
+    if process.dumpable() == Dumpable::Disable {
+        return_errno_with_message!(Errno::EPERM, "the process is not dumpable");
+    }
+
+    let limit = process
+        .resource_limits()
+        .get_rlimit(ResourceType::RLIMIT_CORE)
+        .get_cur();
+    // Like Linux, a core file is not generated if it cannot hold a page.
+    if limit < PAGE_SIZE as u64 {
+        return_errno_with_message!(Errno::EFBIG, "the core file size limit is too small");
+    }
+
+    let pattern = core_pattern();
+    if pattern.starts_with('|') {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "piping core dumps to programs is not supported"
+        );
+    }
+    let path = expand_core_pattern(&pattern, ctx, siginfo.si_signo);
+    let inode = create_core_file(ctx, &path)?;
+
+    let mappings = process.lock_root_vmar().unwrap().mappings_info();
+    // The notes must be collected without the VMAR locked, since reading the
+    // auxiliary vector locks it.
+    let notes = collect_notes(ctx, user_ctx, siginfo, &mappings);
+
+    let mut writer = CoreFileWriter {
+        inode,
+        offset: 0,
+        limit,
+    };
+    let vmar_guard = process.lock_root_vmar();
+    write_core_file(&mut writer, vmar_guard.unwrap(), &mappings, &notes)?;
+    writer.finish()
+}
+
+/// Expands the specifiers (e.g., `%p` for the PID) in the core pattern.
+fn expand_core_pattern(pattern: &str, ctx: &Context, sig_num: i32) -> String {
+    let process = ctx.process;
+    let posix_thread = ctx.posix_thread;
+    let pid_ns = process.pid_ns();
+
+    let mut path = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            path.push(c);
+            continue;
+        }
+
+        // Like Linux, unknown specifiers and a trailing `%` are dropped.
+        let _ = match chars.next() {
+            Some('%') => write!(path, "%"),
+            Some('p') => write!(path, "{}", pid_ns.tid_in_ns(process.pid()).unwrap_or(0)),
+            Some('P') => write!(path, "{}", process.pid()),
+            Some('i') => write!(
+                path,
+                "{}",
+                pid_ns.tid_in_ns(posix_thread.tid()).unwrap_or(0)
+            ),
+            Some('I') => write!(path, "{}", posix_thread.tid()),
+            Some('u') => write!(path, "{}", u32::from(posix_thread.credentials().ruid())),
+            Some('g') => write!(path, "{}", u32::from(posix_thread.credentials().rgid())),
+            Some('s') => write!(path, "{}", sig_num),
+            Some('t') => write!(path, "{}", RealTimeClock::get().read_time().as_secs()),
+            Some('h') => {
+                let uts_name = posix_thread.ns_proxy().uts_ns().uts_name();
+                write!(path, "{}", cstr_bytes_to_string(&uts_name.nodename))
+            }
+            Some('e') => {
+                let thread_name = posix_thread.thread_name().lock();
+                let name = thread_name
+                    .as_ref()
+                    .and_then(|thread_name| thread_name.name().ok().flatten())
+                    .map(|name| name.to_string_lossy().replace('/', "!"))
+                    .unwrap_or_default();
+                write!(path, "{}", name)
+            }
+            _ => Ok(()),
+        };
+    }
+
+    // TODO: Support `/proc/sys/kernel/core_uses_pid`, which appends the PID
+    // if the pattern does not contain `%p`.
+    path
+}
+
+/// Converts the bytes that end with a NUL byte to a string.
+fn cstr_bytes_to_string(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Creates the core file at `path`, truncating the existing one.
+///
+/// Like Linux, an existing core file is only reused if it is a regular file
+/// with a single hard link and is owned by the process, so that the process
+/// cannot be tricked into overwriting other files.
+fn create_core_file(ctx: &Context, path: &str) -> Result<Arc<dyn Inode>> {
+    let fs = ctx.posix_thread.fs();
+    let flags =
+        (CreationFlags::O_CREAT | CreationFlags::O_NOFOLLOW).bits() | AccessMode::O_WRONLY as u32;
+    let mode = 0o600 & !fs.umask().read().get();
+
+    let fs_path = FsPath::new(AT_FDCWD, path)?;
+    let file = fs.resolver().read().open(&fs_path, flags, mode)?;
+    let dentry = file.dentry();
+
+    let metadata = dentry.metadata();
+    if metadata.type_ != InodeType::File {
+        return_errno_with_message!(Errno::EISDIR, "the core file is not a regular file");
+    }
+    if metadata.nlinks != 1 {
+        return_errno_with_message!(Errno::EPERM, "the core file has multiple hard links");
+    }
+    if dentry.owner()? != ctx.posix_thread.credentials().fsuid() {
+        return_errno_with_message!(Errno::EPERM, "the core file is owned by another user");
+    }
+
+    dentry.resize(0)?;
+    Ok(dentry.inode().clone())
+}
+
+/// A writer that writes a core file sequentially.
+struct CoreFileWriter {
+    inode: Arc<dyn Inode>,
+    offset: usize,
+    /// The maximum size of the core file (`RLIMIT_CORE`).
+    limit: u64,
+}
+
+impl CoreFileWriter {
+    /// Writes the bytes at the current offset.
+    fn write(&mut self, buf: &[u8]) -> Result<()> {
+        self.check_limit(buf.len())?;
+
+        let mut written = 0;
+        while written < buf.len() {
+            let len = self
+                .inode
+                .write_bytes_at(self.offset + written, &buf[written..])?;
+            if len == 0 {
+                return_errno_with_message!(Errno::EIO, "the core file cannot be written");
+            }
+            written += len;
+        }
+        self.offset += buf.len();
+        Ok(())
+    }
+
+    /// Skips `len` bytes, which are left as a hole full of zeros.
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.check_limit(len)?;
+        self.offset += len;
+        Ok(())
+    }
+
+    /// Skips the bytes up to `offset`.
+    fn skip_to(&mut self, offset: usize) -> Result<()> {
+        debug_assert!(offset >= self.offset);
+        self.skip(offset - self.offset)
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Finishes writing, extending the file if it ends with a hole.
+    fn finish(self) -> Result<()> {
+        if self.inode.size() < self.offset {
+            self.inode.resize(self.offset)?;
+        }
+        Ok(())
+    }
+
+    fn check_limit(&self, len: usize) -> Result<()> {
+        if (self.offset + len) as u64 > self.limit {
+            return_errno_with_message!(Errno::EFBIG, "the core file size limit is exceeded");
+        }
+        Ok(())
+    }
+}
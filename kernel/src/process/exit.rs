@@ -2,8 +2,11 @@
 
 use core::sync::atomic::Ordering;
 
-use super::{process_table, Pid, Process};
-use crate::{prelude::*, process::signal::signals::kernel::KernelSignal};
+use super::{
+    namespace::PidNamespace, process_table, ptrace::detach_all, signal::constants::SIGKILL, Pid,
+    Process,
+};
+use crate::{events::IoEvents, prelude::*, process::signal::signals::kernel::KernelSignal};
 
 /// Exits the current POSIX process.
 ///
@@ -19,11 +22,20 @@ pub(super) fn exit_process(current_process: &Process) {
     // Drop fields in `Process`.
     current_process.lock_root_vmar().set_vmar(None);
 
+    // Like Linux, zombie processes do not belong to any cgroups.
+    current_process.cgroup().remove_process(current_process);
+
+    detach_all(current_process);
+
+    kill_pid_ns_processes(current_process);
+
     send_parent_death_signal(current_process);
 
     move_children_to_reaper_process(current_process);
 
     send_child_death_signal(current_process);
+
+    current_process.pidfd_pollee().notify(IoEvents::IN);
 }
 
 /// Sends parent-death signals to the children.
@@ -43,6 +55,25 @@ fn send_parent_death_signal(current_process: &Process) {
     }
 }
 
+/// Kills all the processes in the PID namespace if `current_process` is its init process.
+///
+/// Like Linux, the PID namespace cannot be used anymore after its init process exits.
+fn kill_pid_ns_processes(current_process: &Process) {
+    let pid_ns = current_process.pid_ns();
+    if pid_ns.is_init() || pid_ns.global_tid(INIT_PROCESS_PID) != Some(current_process.pid()) {
+        return;
+    }
+
+    for tid in pid_ns.set_dead() {
+        if tid == current_process.pid() {
+            continue;
+        }
+        if let Some(process) = process_table::get_process(tid) {
+            process.enqueue_signal(KernelSignal::new(SIGKILL));
+        }
+    }
+}
+
 /// Finds a reaper process for `current_process`.
 ///
 /// If there is no reaper process for `current_process`, returns `None`.
@@ -106,7 +137,7 @@ fn move_children_to_reaper_process(current_process: &Process) {
         }
     }
 
-    let Some(init_process) = get_init_process() else {
+    let Some(init_process) = get_init_process(current_process.pid_ns()) else {
         return;
     };
 
@@ -127,9 +158,12 @@ fn send_child_death_signal(current_process: &Process) {
 
 const INIT_PROCESS_PID: Pid = 1;
 
-/// Gets the init process
-fn get_init_process() -> Option<Arc<Process>> {
-    process_table::get_process(INIT_PROCESS_PID)
+/// Gets the init process of the PID namespace.
+///
+/// If the init process of the PID namespace has exited, the global init process is returned.
+fn get_init_process(pid_ns: &PidNamespace) -> Option<Arc<Process>> {
+    let init_pid = pid_ns.init_pid().unwrap_or(INIT_PROCESS_PID);
+    process_table::get_process(init_pid)
 }
 
 fn is_init_process(process: &Process) -> bool {
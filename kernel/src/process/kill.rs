@@ -1,12 +1,13 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::{
+    pid_file::PidFile,
     posix_thread::{thread_table, AsPosixThread},
     process_table,
     signal::{
         constants::SIGCONT,
         sig_num::SigNum,
-        signals::{user::UserSignal, Signal},
+        signals::{kernel::KernelSignal, user::UserSignal, Signal},
     },
     Pgid, Pid, Process, Sid, Uid,
 };
@@ -42,6 +43,23 @@ pub fn kill(pid: Pid, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
     kill_process(&process, signal, ctx)
 }
 
+/// Sends a signal to the process that a PID file refers to, using the current process as the
+/// sender.
+///
+/// Unlike [`kill`], the target process is found by the PID file instead of the PID, so the
+/// signal will never be sent to another process that reuses the PID.
+///
+/// If `signal` is `None`, this method will only check permission without sending
+/// any signal.
+pub fn kill_pid_file(pid_file: &PidFile, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
+    let process = pid_file.process();
+    if process.status().is_zombie() {
+        return_errno_with_message!(Errno::ESRCH, "the target process has exited");
+    }
+
+    kill_process(process, signal, ctx)
+}
+
 /// Sends a signal to all processes in a group, using the current process
 /// as the sender.
 ///
@@ -116,6 +134,15 @@ pub fn kill_all(signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
     Ok(())
 }
 
+/// Sends a signal to a process, using the kernel as the sender.
+///
+/// No permission is checked, since the signal is not sent on behalf of any
+/// process. This is used when the kernel itself decides to signal a process,
+/// e.g., to kill a process when the system runs out of memory.
+pub(super) fn kill_by_kernel(process: &Process, signum: SigNum) {
+    process.enqueue_signal(KernelSignal::new(signum));
+}
+
 fn kill_process(process: &Process, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
     let tasks = process.tasks().lock();
 
@@ -1,9 +1,14 @@
 // SPDX-License-Identifier: MPL-2.0
 
+pub mod cgroup;
 mod clone;
+pub mod coredump;
 pub mod credentials;
 mod exit;
 mod kill;
+pub mod namespace;
+mod oom;
+mod pid_file;
 pub mod posix_thread;
 #[expect(clippy::module_inception)]
 mod process;
@@ -11,7 +16,9 @@ mod process_filter;
 pub mod process_table;
 mod process_vm;
 mod program_loader;
+pub mod ptrace;
 pub mod rlimit;
+pub mod seccomp;
 pub mod signal;
 mod status;
 pub mod sync;
@@ -21,7 +28,12 @@ mod wait;
 
 pub use clone::{clone_child, CloneArgs, CloneFlags};
 pub use credentials::{Credentials, Gid, Uid};
-pub use kill::{kill, kill_all, kill_group, tgkill};
+pub use kill::{kill, kill_all, kill_group, kill_pid_file, tgkill};
+pub use oom::{
+    nr_oom_kills, oom_score, out_of_memory, out_of_memory_in_cgroup, OOM_SCORE_ADJ_MAX,
+    OOM_SCORE_ADJ_MIN,
+};
+pub use pid_file::PidFile;
 pub use process::{
     ExitCode, JobControl, Pgid, Pid, Process, ProcessBuilder, ProcessGroup, Session, Sid, Terminal,
 };
@@ -32,7 +44,7 @@ pub use process_vm::{
 pub use program_loader::{check_executable_file, ProgramToLoad};
 pub use rlimit::ResourceType;
 pub use term_status::TermStatus;
-pub use wait::{wait_child_exit, WaitOptions};
+pub use wait::{do_wait, WaitOptions, WaitStatus};
 
 pub(super) fn init() {
     process::init();
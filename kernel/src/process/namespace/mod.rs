@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Namespaces.
+//!
+//! A namespace wraps a global system resource so that the processes in the
+//! namespace have their own isolated instance of the resource. The
+//! namespaces of a thread are grouped in an [`NsProxy`], which is shared by
+//! the threads until one of them switches to other namespaces with
+//! `unshare` or `setns`.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man7/namespaces.7.html>
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::Once;
+
+use super::{credentials::capabilities::CapSet, posix_thread::PosixThread, CloneFlags};
+use crate::{fs::path::MountNamespace, ipc::IpcNamespace, net::NetNamespace, prelude::*};
+
+mod pid;
+mod uts;
+
+pub use pid::PidNamespace;
+pub use uts::{UtsName, UtsNamespace, UTS_FIELD_LEN};
+
+/// The namespaces of a thread.
+#[derive(Clone)]
+pub struct NsProxy {
+    uts_ns: Arc<UtsNamespace>,
+    mnt_ns: Arc<MountNamespace>,
+    pid_ns_for_children: Arc<PidNamespace>,
+    ipc_ns: Arc<IpcNamespace>,
+    net_ns: Arc<NetNamespace>,
+}
+
+impl NsProxy {
+    /// Returns the `NsProxy` with the initial namespaces.
+    pub fn get_init_singleton() -> &'static Arc<NsProxy> {
+        static INIT: Once<Arc<NsProxy>> = Once::new();
+
+        INIT.call_once(|| {
+            Arc::new(Self {
+                uts_ns: UtsNamespace::get_init_singleton().clone(),
+                mnt_ns: MountNamespace::get_init_singleton().clone(),
+                pid_ns_for_children: PidNamespace::get_init_singleton().clone(),
+                ipc_ns: IpcNamespace::get_init_singleton().clone(),
+                net_ns: NetNamespace::get_init_singleton().clone(),
+            })
+        })
+    }
+
+    /// Returns the UTS namespace.
+    pub fn uts_ns(&self) -> &Arc<UtsNamespace> {
+        &self.uts_ns
+    }
+
+    /// Returns the mount namespace.
+    pub fn mnt_ns(&self) -> &Arc<MountNamespace> {
+        &self.mnt_ns
+    }
+
+    /// Returns the PID namespace of the children.
+    ///
+    /// Note that the PID namespace of a process is fixed at its creation, so it is not always the
+    /// same as this one. See [`Process::pid_ns`].
+    ///
+    /// [`Process::pid_ns`]: crate::process::Process::pid_ns
+    pub fn pid_ns_for_children(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns_for_children
+    }
+
+    /// Returns the IPC namespace.
+    pub fn ipc_ns(&self) -> &Arc<IpcNamespace> {
+        &self.ipc_ns
+    }
+
+    /// Returns the network namespace.
+    pub fn net_ns(&self) -> &Arc<NetNamespace> {
+        &self.net_ns
+    }
+
+    /// Creates a new `NsProxy` with the new namespaces specified by the clone flags.
+    ///
+    /// The namespaces that are not specified by the flags are shared with this `NsProxy`.
+    /// `active_pid_ns` is the PID namespace of the calling process.
+    pub(super) fn new_copy(
+        &self,
+        clone_flags: CloneFlags,
+        active_pid_ns: &PidNamespace,
+    ) -> Result<Arc<NsProxy>> {
+        let mut new_proxy = self.clone();
+
+        if clone_flags.contains(CloneFlags::CLONE_NEWPID) {
+            // Like Linux, a thread can create at most one new PID namespace for its children.
+            if !core::ptr::eq(self.pid_ns_for_children.as_ref(), active_pid_ns) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "a new PID namespace has already been created for the children"
+                );
+            }
+            new_proxy.pid_ns_for_children = self.pid_ns_for_children.new_child()?;
+        }
+        if clone_flags.contains(CloneFlags::CLONE_NEWUTS) {
+            new_proxy.uts_ns = self.uts_ns.new_copy();
+        }
+        if clone_flags.contains(CloneFlags::CLONE_NEWIPC) {
+            new_proxy.ipc_ns = IpcNamespace::new();
+        }
+        if clone_flags.contains(CloneFlags::CLONE_NEWNET) {
+            new_proxy.net_ns = NetNamespace::new();
+        }
+        if clone_flags.contains(CloneFlags::CLONE_NEWNS) {
+            new_proxy.mnt_ns = self.mnt_ns.new_copy();
+        }
+
+        Ok(Arc::new(new_proxy))
+    }
+
+    /// Returns the namespace of the given type.
+    pub fn get(&self, ns_type: NsType) -> Namespace {
+        match ns_type {
+            NsType::Ipc => Namespace::Ipc(self.ipc_ns.clone()),
+            NsType::Mnt => Namespace::Mnt(self.mnt_ns.clone()),
+            NsType::Net => Namespace::Net(self.net_ns.clone()),
+            NsType::Pid => Namespace::Pid(self.pid_ns_for_children.clone()),
+            NsType::Uts => Namespace::Uts(self.uts_ns.clone()),
+        }
+    }
+}
+
+/// The type of a namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NsType {
+    Ipc,
+    Mnt,
+    Net,
+    Pid,
+    Uts,
+}
+
+impl NsType {
+    /// Returns the clone flag that creates a new namespace of the type.
+    pub fn clone_flag(&self) -> CloneFlags {
+        match self {
+            Self::Ipc => CloneFlags::CLONE_NEWIPC,
+            Self::Mnt => CloneFlags::CLONE_NEWNS,
+            Self::Net => CloneFlags::CLONE_NEWNET,
+            Self::Pid => CloneFlags::CLONE_NEWPID,
+            Self::Uts => CloneFlags::CLONE_NEWUTS,
+        }
+    }
+
+    /// Returns the name of the type, which is used in `/proc/[pid]/ns`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ipc => "ipc",
+            Self::Mnt => "mnt",
+            Self::Net => "net",
+            Self::Pid => "pid",
+            Self::Uts => "uts",
+        }
+    }
+}
+
+/// A namespace of any type.
+#[derive(Clone)]
+pub enum Namespace {
+    Ipc(Arc<IpcNamespace>),
+    Mnt(Arc<MountNamespace>),
+    Net(Arc<NetNamespace>),
+    Pid(Arc<PidNamespace>),
+    Uts(Arc<UtsNamespace>),
+}
+
+impl Namespace {
+    /// Returns the type of the namespace.
+    pub fn ns_type(&self) -> NsType {
+        match self {
+            Self::Ipc(_) => NsType::Ipc,
+            Self::Mnt(_) => NsType::Mnt,
+            Self::Net(_) => NsType::Net,
+            Self::Pid(_) => NsType::Pid,
+            Self::Uts(_) => NsType::Uts,
+        }
+    }
+
+    /// Returns the ID of the namespace.
+    ///
+    /// The ID is shown as the inode number of the namespace files in `/proc/[pid]/ns`.
+    pub fn id(&self) -> u64 {
+        match self {
+            Self::Ipc(ns) => ns.id(),
+            Self::Mnt(ns) => ns.id(),
+            Self::Net(ns) => ns.id(),
+            Self::Pid(ns) => ns.id(),
+            Self::Uts(ns) => ns.id(),
+        }
+    }
+}
+
+/// The clone flags that create new namespaces.
+pub const CLONE_NEW_NS_FLAGS: CloneFlags = CloneFlags::from_bits_truncate(
+    CloneFlags::CLONE_NEWNS.bits()
+        | CloneFlags::CLONE_NEWUTS.bits()
+        | CloneFlags::CLONE_NEWIPC.bits()
+        | CloneFlags::CLONE_NEWPID.bits()
+        | CloneFlags::CLONE_NEWNET.bits(),
+);
+
+/// Moves the current thread to the new namespaces specified by the clone flags.
+///
+/// The namespace flags in `clone_flags` other than [`CLONE_NEW_NS_FLAGS`] are ignored.
+pub fn unshare_namespaces(clone_flags: CloneFlags, ctx: &Context) -> Result<()> {
+    let clone_flags = clone_flags & CLONE_NEW_NS_FLAGS;
+    if clone_flags.is_empty() {
+        return Ok(());
+    }
+    check_sys_admin(ctx.posix_thread)?;
+
+    let old_proxy = ctx.posix_thread.ns_proxy();
+    let new_proxy = old_proxy.new_copy(clone_flags, ctx.process.pid_ns())?;
+
+    if clone_flags.contains(CloneFlags::CLONE_NEWNS) {
+        // FIXME: Unshare the file system information if it is shared with other threads.
+        if Arc::strong_count(ctx.posix_thread.fs()) > 1 {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "unsharing the file system information is not supported"
+            );
+        }
+
+        let mut resolver = ctx.posix_thread.fs().resolver().write();
+        let old_mnt_ns = old_proxy.mnt_ns();
+        let new_mnt_ns = new_proxy.mnt_ns();
+        let root = old_mnt_ns.translate(resolver.root(), new_mnt_ns);
+        let cwd = old_mnt_ns.translate(resolver.cwd(), new_mnt_ns);
+        resolver.set_root(root);
+        resolver.set_cwd(cwd);
+    }
+
+    ctx.posix_thread.set_ns_proxy(new_proxy);
+    Ok(())
+}
+
+/// Moves the current thread to the namespace.
+///
+/// For a PID namespace, only the PID namespace of the children is changed.
+pub fn switch_to_namespace(ns: Namespace, ctx: &Context) -> Result<()> {
+    check_sys_admin(ctx.posix_thread)?;
+
+    let mut new_proxy = NsProxy::clone(&ctx.posix_thread.ns_proxy());
+    match ns {
+        Namespace::Ipc(ipc_ns) => new_proxy.ipc_ns = ipc_ns,
+        Namespace::Mnt(mnt_ns) => {
+            // FIXME: Unshare the file system information if it is shared with other threads.
+            if Arc::strong_count(ctx.posix_thread.fs()) > 1 {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the file system information is shared with other threads"
+                );
+            }
+
+            let mut resolver = ctx.posix_thread.fs().resolver().write();
+            resolver.set_root(mnt_ns.root_dentry());
+            resolver.set_cwd(mnt_ns.root_dentry());
+            new_proxy.mnt_ns = mnt_ns;
+        }
+        Namespace::Net(net_ns) => new_proxy.net_ns = net_ns,
+        Namespace::Pid(pid_ns) => {
+            // Like Linux, a thread can only enter its own PID namespace or its descendants.
+            if !ctx.process.pid_ns().is_ancestor_of(&pid_ns) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the PID namespace is not a descendant of the current one"
+                );
+            }
+            new_proxy.pid_ns_for_children = pid_ns;
+        }
+        Namespace::Uts(uts_ns) => new_proxy.uts_ns = uts_ns,
+    }
+
+    ctx.posix_thread.set_ns_proxy(Arc::new(new_proxy));
+    Ok(())
+}
+
+/// Checks whether the thread is allowed to create or enter namespaces.
+pub(super) fn check_sys_admin(posix_thread: &PosixThread) -> Result<()> {
+    let credentials = posix_thread.credentials();
+    if !credentials.effective_capset().contains(CapSet::SYS_ADMIN) {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "CAP_SYS_ADMIN is required to create or enter namespaces"
+        );
+    }
+    Ok(())
+}
+
+/// Allocates a unique ID for a namespace.
+pub fn alloc_ns_id() -> u64 {
+    // Like Linux, the IDs of the namespaces (i.e., the inode numbers of the namespace
+    // files) start from `0xF000_0000`.
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0xF000_0000);
+
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::alloc_ns_id;
+use crate::{
+    prelude::*,
+    process::{posix_thread::allocate_posix_tid, Pid},
+    thread::Tid,
+};
+
+/// The maximum nesting depth of PID namespaces.
+const MAX_PID_NS_LEVEL: u32 = 32;
+
+/// A PID namespace.
+///
+/// A PID namespace isolates the process ID number space. Each thread has a
+/// global ID, which is the ID in the initial PID namespace, and an ID in each
+/// of the other PID namespaces that it is visible in (i.e., the PID namespace
+/// of its process and all the ancestors).
+///
+/// The threads that are not in a PID namespace or its descendants are
+/// invisible in the PID namespace.
+pub struct PidNamespace {
+    id: u64,
+    level: u32,
+    parent: Option<Arc<PidNamespace>>,
+    inner: Mutex<PidNamespaceInner>,
+}
+
+struct PidNamespaceInner {
+    next_id: Pid,
+    /// The global IDs of the visible threads, indexed by their IDs in this namespace.
+    global_ids: BTreeMap<Tid, Tid>,
+    /// The IDs in this namespace of the visible threads, indexed by their global IDs.
+    local_ids: BTreeMap<Tid, Tid>,
+    /// Whether the init process of the namespace has exited.
+    is_dead: bool,
+}
+
+impl PidNamespace {
+    /// Returns the initial PID namespace.
+    pub fn get_init_singleton() -> &'static Arc<PidNamespace> {
+        static INIT: Once<Arc<PidNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            Arc::new(Self {
+                id: alloc_ns_id(),
+                level: 0,
+                parent: None,
+                inner: Mutex::new(PidNamespaceInner::new()),
+            })
+        })
+    }
+
+    /// Creates a new child PID namespace.
+    pub fn new_child(self: &Arc<Self>) -> Result<Arc<PidNamespace>> {
+        if self.level >= MAX_PID_NS_LEVEL {
+            return_errno_with_message!(Errno::ENOSPC, "too many nested PID namespaces");
+        }
+
+        Ok(Arc::new(Self {
+            id: alloc_ns_id(),
+            level: self.level + 1,
+            parent: Some(self.clone()),
+            inner: Mutex::new(PidNamespaceInner::new()),
+        }))
+    }
+
+    /// Returns the ID of the namespace.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns whether this is the initial PID namespace.
+    pub fn is_init(&self) -> bool {
+        self.parent.is_none()
+    }
+
+    /// Returns whether this namespace is `other` or one of its ancestors.
+    pub fn is_ancestor_of(&self, other: &PidNamespace) -> bool {
+        if other.level < self.level {
+            return false;
+        }
+
+        let mut ns = other;
+        while ns.level > self.level {
+            ns = ns.parent.as_ref().unwrap();
+        }
+        core::ptr::eq(ns, self)
+    }
+
+    /// Allocates a new thread ID in this namespace and all its ancestors.
+    ///
+    /// The global ID is returned.
+    pub(in crate::process) fn alloc_tid(&self) -> Result<Tid> {
+        let tid = allocate_posix_tid();
+
+        let mut ns = Some(self);
+        while let Some(current_ns) = ns
+            && !current_ns.is_init()
+        {
+            let mut inner = current_ns.inner.lock();
+            if inner.is_dead {
+                drop(inner);
+                self.free_tid(tid);
+                return_errno_with_message!(
+                    Errno::ENOMEM,
+                    "the init process of the PID namespace has exited"
+                );
+            }
+
+            let local_id = inner.next_id;
+            inner.next_id += 1;
+            inner.global_ids.insert(local_id, tid);
+            inner.local_ids.insert(tid, local_id);
+
+            ns = current_ns.parent.as_deref();
+        }
+
+        Ok(tid)
+    }
+
+    /// Frees a thread ID allocated by [`Self::alloc_tid`].
+    pub(in crate::process) fn free_tid(&self, tid: Tid) {
+        let mut ns = Some(self);
+        while let Some(current_ns) = ns
+            && !current_ns.is_init()
+        {
+            let mut inner = current_ns.inner.lock();
+            if let Some(local_id) = inner.local_ids.remove(&tid) {
+                inner.global_ids.remove(&local_id);
+            }
+
+            ns = current_ns.parent.as_deref();
+        }
+    }
+
+    /// Translates a global thread ID to the ID in this namespace.
+    ///
+    /// Returns `None` if the thread is invisible in this namespace.
+    pub fn tid_in_ns(&self, global_tid: Tid) -> Option<Tid> {
+        if self.is_init() {
+            return Some(global_tid);
+        }
+
+        self.inner.lock().local_ids.get(&global_tid).copied()
+    }
+
+    /// Translates a thread ID in this namespace to the global thread ID.
+    ///
+    /// Returns `None` if there is no such thread in this namespace.
+    pub fn global_tid(&self, tid: Tid) -> Option<Tid> {
+        if self.is_init() {
+            return Some(tid);
+        }
+
+        self.inner.lock().global_ids.get(&tid).copied()
+    }
+
+    /// Returns the global ID of the init process of the namespace.
+    ///
+    /// Returns `None` if the init process has not been created yet or has exited.
+    pub fn init_pid(&self) -> Option<Pid> {
+        const INIT_PID: Pid = 1;
+
+        if self.is_init() {
+            return Some(INIT_PID);
+        }
+
+        let inner = self.inner.lock();
+        if inner.is_dead {
+            return None;
+        }
+        inner.global_ids.get(&INIT_PID).copied()
+    }
+
+    /// Marks the namespace as dead after its init process exits.
+    ///
+    /// No new processes can be created in a dead namespace. The global IDs of the threads in
+    /// the namespace are returned, so the caller can kill them.
+    pub(in crate::process) fn set_dead(&self) -> Vec<Tid> {
+        let mut inner = self.inner.lock();
+        inner.is_dead = true;
+        inner.global_ids.values().copied().collect()
+    }
+}
+
+impl PidNamespaceInner {
+    const fn new() -> Self {
+        Self {
+            next_id: 1,
+            global_ids: BTreeMap::new(),
+            local_ids: BTreeMap::new(),
+            is_dead: false,
+        }
+    }
+}
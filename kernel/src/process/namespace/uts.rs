@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use spin::Once;
+
+use super::alloc_ns_id;
+use crate::prelude::*;
+
+/// The length of each field in [`UtsName`], including the trailing null byte.
+pub const UTS_FIELD_LEN: usize = 65;
+
+/// The system identification returned by `uname`.
+///
+/// This is the same as `struct new_utsname` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+pub struct UtsName {
+    pub sysname: [u8; UTS_FIELD_LEN],
+    pub nodename: [u8; UTS_FIELD_LEN],
+    pub release: [u8; UTS_FIELD_LEN],
+    pub version: [u8; UTS_FIELD_LEN],
+    pub machine: [u8; UTS_FIELD_LEN],
+    pub domainname: [u8; UTS_FIELD_LEN],
+}
+
+/// A UTS namespace.
+///
+/// A UTS namespace isolates the host name and the NIS domain name.
+pub struct UtsNamespace {
+    id: u64,
+    uts_name: SpinLock<UtsName>,
+}
+
+impl UtsNamespace {
+    /// Returns the initial UTS namespace.
+    pub fn get_init_singleton() -> &'static Arc<UtsNamespace> {
+        static INIT: Once<Arc<UtsNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            let copy_slice = |src: &[u8], dst: &mut [u8]| {
+                let len = src.len().min(dst.len());
+                dst[..len].copy_from_slice(&src[..len]);
+            };
+
+            // We don't use the real name and version of our os here. Instead, we pick up fake
+            // values witch is the same as the ones of linux. The values are used to fool glibc
+            // since glibc will check the version and os name.
+            let mut uts_name = UtsName::new_zeroed();
+            copy_slice(b"Linux", &mut uts_name.sysname);
+            copy_slice(b"WHITLEY", &mut uts_name.nodename);
+            copy_slice(b"5.13.0", &mut uts_name.release);
+            copy_slice(b"5.13.0", &mut uts_name.version);
+            copy_slice(b"x86_64", &mut uts_name.machine);
+            copy_slice(b"", &mut uts_name.domainname);
+
+            Arc::new(Self {
+                id: alloc_ns_id(),
+                uts_name: SpinLock::new(uts_name),
+            })
+        })
+    }
+
+    /// Creates a new UTS namespace with a copy of the names in this namespace.
+    pub fn new_copy(&self) -> Arc<UtsNamespace> {
+        Arc::new(Self {
+            id: alloc_ns_id(),
+            uts_name: SpinLock::new(*self.uts_name.lock()),
+        })
+    }
+
+    /// Returns the ID of the namespace.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the system identification.
+    pub fn uts_name(&self) -> UtsName {
+        *self.uts_name.lock()
+    }
+
+    /// Sets the host name.
+    ///
+    /// The name should be shorter than [`UTS_FIELD_LEN`].
+    pub fn set_hostname(&self, name: &[u8]) -> Result<()> {
+        let mut uts_name = self.uts_name.lock();
+        set_field(&mut uts_name.nodename, name)
+    }
+
+    /// Sets the NIS domain name.
+    ///
+    /// The name should be shorter than [`UTS_FIELD_LEN`].
+    pub fn set_domainname(&self, name: &[u8]) -> Result<()> {
+        let mut uts_name = self.uts_name.lock();
+        set_field(&mut uts_name.domainname, name)
+    }
+}
+
+fn set_field(field: &mut [u8; UTS_FIELD_LEN], name: &[u8]) -> Result<()> {
+    if name.len() >= UTS_FIELD_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the name is too long");
+    }
+
+    field[..name.len()].copy_from_slice(name);
+    field[name.len()..].fill(0);
+    Ok(())
+}
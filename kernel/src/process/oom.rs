@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The out-of-memory (OOM) killer.
+//!
+//! When memory runs out and no pages can be reclaimed, the OOM killer kills
+//! the process that is the most "bad" to free its memory. Like Linux, the
+//! badness of a process is mostly its resident set size (RSS), adjusted by
+//! its OOM score adjustment (see [`Process::oom_score_adj`]).
+//!
+//! If the memory limit of a cgroup is reached instead, only the processes in
+//! the cgroup and its descendants are considered.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/mm/oom_kill.c>
+
+use alloc::format;
+use core::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use super::{
+    cgroup::Cgroup, kill::kill_by_kernel, process_table, signal::constants::SIGKILL, Process,
+};
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::signal::Poller,
+    vm::{mem_total, swap},
+};
+
+/// The minimum OOM score adjustment, which disables OOM killing.
+pub const OOM_SCORE_ADJ_MIN: i16 = -1000;
+
+/// The maximum OOM score adjustment.
+pub const OOM_SCORE_ADJ_MAX: i16 = 1000;
+
+/// The maximum time to wait for a victim to exit.
+const OOM_VICTIM_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The number of processes that are killed by the OOM killer.
+static NR_OOM_KILLS: AtomicUsize = AtomicUsize::new(0);
+
+/// The lock that serializes OOM killing.
+///
+/// The lock is held until the victim exits, so that the threads that run out
+/// of memory at the same time do not kill more processes than necessary.
+static OOM_LOCK: Mutex<()> = Mutex::new(());
+
+/// Returns the number of processes that are killed by the OOM killer.
+pub fn nr_oom_kills() -> usize {
+    NR_OOM_KILLS.load(Ordering::Relaxed)
+}
+
+/// Kills the process with the highest badness score to free memory.
+///
+/// This method waits for the victim to exit before returning, unless the
+/// victim is the current process.
+///
+/// Returns whether memory may have been freed, i.e., whether the failed
+/// allocation is worth retrying.
+pub fn out_of_memory() -> bool {
+    oom_kill(None)
+}
+
+/// Kills the process with the highest badness score in the cgroup (or in its
+/// descendants) to free memory charged to the cgroup.
+///
+/// This method behaves like [`out_of_memory`], except that the victim is
+/// selected from the cgroup subtree only.
+pub fn out_of_memory_in_cgroup(cgroup: &Cgroup) -> bool {
+    oom_kill(Some(cgroup))
+}
+
+fn oom_kill(cgroup: Option<&Cgroup>) -> bool {
+    let nr_kills = nr_oom_kills();
+    let _guard = OOM_LOCK.lock();
+    if nr_oom_kills() != nr_kills {
+        // Another thread has killed a process while we are waiting for the
+        // lock, so memory has been freed.
+        return true;
+    }
+
+    let Some((victim, points)) = select_victim(cgroup) else {
+        warn!("out of memory: no process can be killed");
+        return false;
+    };
+
+    let scope = cgroup.map_or_else(String::new, |cgroup| {
+        format!(" in cgroup {}", cgroup.path())
+    });
+    warn!(
+        "out of memory{}: killing process {} ({}) with score {}",
+        scope,
+        victim.pid(),
+        victim.executable_path(),
+        oom_score_of(points)
+    );
+    kill_by_kernel(&victim, SIGKILL);
+    NR_OOM_KILLS.fetch_add(1, Ordering::Relaxed);
+
+    if Process::current().is_some_and(|current| Arc::ptr_eq(&current, &victim)) {
+        // The current process will exit once it returns to the user space.
+        return false;
+    }
+
+    wait_for_exit(&victim);
+    true
+}
+
+/// Returns the OOM score of the process.
+///
+/// The score ranges from 0 to 2000, where 0 means that the process is never
+/// killed by the OOM killer. This is the value shown in
+/// `/proc/[pid]/oom_score`.
+pub fn oom_score(process: &Process) -> usize {
+    badness(process, total_pages()).map_or(0, oom_score_of)
+}
+
+/// Finds the process with the highest badness score.
+///
+/// If `cgroup` is `Some`, only the processes in the cgroup subtree are
+/// considered.
+fn select_victim(cgroup: Option<&Cgroup>) -> Option<(Arc<Process>, isize)> {
+    let total_pages = total_pages();
+
+    let processes: Vec<_> = process_table::process_table_mut().iter().cloned().collect();
+    processes
+        .into_iter()
+        .filter(|process| cgroup.is_none_or(|cgroup| process.cgroup().is_descendant_of(cgroup)))
+        .filter_map(|process| {
+            let points = badness(&process, total_pages)?;
+            Some((process, points))
+        })
+        .max_by_key(|(_, points)| *points)
+}
+
+/// Returns the badness score of the process.
+///
+/// Returns `None` if the process cannot be killed by the OOM killer.
+fn badness(process: &Process, total_pages: usize) -> Option<isize> {
+    let oom_score_adj = process.oom_score_adj();
+    if oom_score_adj == OOM_SCORE_ADJ_MIN
+        || process.is_init_process()
+        || process.status().is_zombie()
+    {
+        return None;
+    }
+
+    let rss_pages = process.lock_root_vmar().get()?.rss_pages();
+
+    // Like Linux, the adjustment is proportional to the total memory, so an
+    // adjustment of 1000 makes the process use all the memory, and an
+    // adjustment of -1000 makes the process use none of the memory.
+    Some(rss_pages as isize + oom_score_adj as isize * (total_pages / 1000) as isize)
+}
+
+/// Normalizes the badness score to the OOM score.
+fn oom_score_of(points: isize) -> usize {
+    let total_pages = total_pages() as isize;
+    (1000 + points * 1000 / total_pages).clamp(0, 2000) as usize
+}
+
+/// Returns the number of pages in memory and swap areas.
+fn total_pages() -> usize {
+    ((mem_total() + swap::total_swap_size()) / PAGE_SIZE).max(1)
+}
+
+/// Waits for the process to exit, or until the timeout expires.
+fn wait_for_exit(process: &Process) {
+    let mut poller = Poller::new(Some(&OOM_VICTIM_TIMEOUT));
+    let check_exit = || {
+        if process.status().is_zombie() {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    };
+
+    if !process
+        .pidfd_pollee()
+        .poll_with(IoEvents::IN, Some(poller.as_handle_mut()), check_exit)
+        .is_empty()
+    {
+        return;
+    }
+
+    // The waiting fails on timeout or if the current thread is interrupted by
+    // signals, e.g., if it is killed.
+    while poller.wait().is_ok() {
+        if process.status().is_zombie() {
+            return;
+        }
+    }
+}
@@ -2,6 +2,8 @@
 
 #![expect(dead_code)]
 
+use core::sync::atomic::AtomicBool;
+
 use ostd::{
     cpu::{context::UserContext, CpuSet},
     sync::RwArc,
@@ -13,7 +15,10 @@ use crate::{
     fs::{file_table::FileTable, thread_info::ThreadFsInfo},
     prelude::*,
     process::{
+        namespace::NsProxy,
         posix_thread::name::ThreadName,
+        ptrace::PtraceState,
+        seccomp::SeccompState,
         signal::{sig_mask::AtomicSigMask, sig_queues::SigQueues},
         Credentials, Process,
     },
@@ -36,9 +41,12 @@ pub struct PosixThreadBuilder {
     clear_child_tid: Vaddr,
     file_table: Option<RwArc<FileTable>>,
     fs: Option<Arc<ThreadFsInfo>>,
+    ns_proxy: Option<Arc<NsProxy>>,
     sig_mask: AtomicSigMask,
     sig_queues: SigQueues,
     sched_policy: SchedPolicy,
+    seccomp: SeccompState,
+    no_new_privs: bool,
 }
 
 impl PosixThreadBuilder {
@@ -53,9 +61,12 @@ impl PosixThreadBuilder {
             clear_child_tid: 0,
             file_table: None,
             fs: None,
+            ns_proxy: None,
             sig_mask: AtomicSigMask::new_empty(),
             sig_queues: SigQueues::new(),
             sched_policy: SchedPolicy::Fair(Nice::default()),
+            seccomp: SeccompState::new(),
+            no_new_privs: false,
         }
     }
 
@@ -89,6 +100,11 @@ impl PosixThreadBuilder {
         self
     }
 
+    pub fn ns_proxy(mut self, ns_proxy: Arc<NsProxy>) -> Self {
+        self.ns_proxy = Some(ns_proxy);
+        self
+    }
+
     pub fn sig_mask(mut self, sig_mask: AtomicSigMask) -> Self {
         self.sig_mask = sig_mask;
         self
@@ -99,6 +115,16 @@ impl PosixThreadBuilder {
         self
     }
 
+    pub fn seccomp(mut self, seccomp: SeccompState) -> Self {
+        self.seccomp = seccomp;
+        self
+    }
+
+    pub fn no_new_privs(mut self, no_new_privs: bool) -> Self {
+        self.no_new_privs = no_new_privs;
+        self
+    }
+
     pub fn build(self) -> Arc<Task> {
         let Self {
             tid,
@@ -110,15 +136,20 @@ impl PosixThreadBuilder {
             clear_child_tid,
             file_table,
             fs,
+            ns_proxy,
             sig_mask,
             sig_queues,
             sched_policy,
+            seccomp,
+            no_new_privs,
         } = self;
 
         let file_table = file_table.unwrap_or_else(|| RwArc::new(FileTable::new_with_stdio()));
 
         let fs = fs.unwrap_or_else(|| Arc::new(ThreadFsInfo::default()));
 
+        let ns_proxy = ns_proxy.unwrap_or_else(|| NsProxy::get_init_singleton().clone());
+
         let root_vmar = process
             .upgrade()
             .unwrap()
@@ -140,9 +171,13 @@ impl PosixThreadBuilder {
                     credentials,
                     file_table: Mutex::new(Some(file_table.clone_ro())),
                     fs,
+                    ns_proxy: Mutex::new(ns_proxy),
                     sig_mask,
                     sig_queues,
                     signalled_waker: SpinLock::new(None),
+                    ptrace: PtraceState::new(),
+                    seccomp,
+                    no_new_privs: AtomicBool::new(no_new_privs),
                     prof_clock,
                     virtual_timer_manager,
                     prof_timer_manager,
@@ -11,6 +11,7 @@ use crate::{
     prelude::*,
     process::{
         exit::exit_process,
+        ptrace::ptrace_exit,
         signal::{constants::SIGKILL, signals::kernel::KernelSignal},
         task_set::TaskSet,
         TermStatus,
@@ -65,10 +66,15 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
             return;
         }
         current_thread.exit();
+        // The process cannot move to another cgroup while its task set is
+        // locked, so the task is uncharged from the cgroup it is charged to.
+        posix_process.cgroup().uncharge_task();
 
         tasks.remove_exited(&current_task)
     };
 
+    ptrace_exit(posix_thread, term_status.as_u32());
+
     wake_clear_ctid(thread_local);
 
     wake_robust_list(thread_local, posix_thread.tid());
@@ -77,8 +83,15 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
     // process is reaped by its parent.
     if posix_thread.tid() != posix_process.pid() {
         thread_table::remove_thread(posix_thread.tid());
+        posix_process.pid_ns().free_tid(posix_thread.tid());
     }
 
+    // The io_uring requests of the thread hold its address space and its file table.
+    thread_local
+        .io_urings()
+        .borrow_mut()
+        .cancel_all(posix_thread.tid());
+
     // Drop fields in `PosixThread`.
     *posix_thread.file_table().lock() = None;
 
@@ -1,12 +1,15 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 use aster_rights::{ReadOp, WriteOp};
 use ostd::sync::{RoArc, Waker};
 
 use super::{
     kill::SignalSenderIds,
+    namespace::NsProxy,
+    ptrace::PtraceState,
+    seccomp::SeccompState,
     signal::{
         sig_action::SigAction,
         sig_mask::{AtomicSigMask, SigMask, SigSet},
@@ -21,7 +24,7 @@ use crate::{
     events::Observer,
     fs::{file_table::FileTable, thread_info::ThreadFsInfo},
     prelude::*,
-    process::signal::constants::SIGCONT,
+    process::signal::constants::{SIGCONT, SIGKILL},
     thread::{Thread, Tid},
     time::{clocks::ProfClock, Timer, TimerManager},
 };
@@ -59,6 +62,9 @@ pub struct PosixThread {
     /// File system
     fs: Arc<ThreadFsInfo>,
 
+    /// Namespaces
+    ns_proxy: Mutex<Arc<NsProxy>>,
+
     // Signal
     /// Blocked signals
     sig_mask: AtomicSigMask,
@@ -68,6 +74,15 @@ pub struct PosixThread {
     /// when enqueuing a signal.
     signalled_waker: SpinLock<Option<Arc<Waker>>>,
 
+    /// The ptrace state of the thread.
+    ptrace: PtraceState,
+
+    // Security
+    /// The seccomp state of the thread.
+    seccomp: SeccompState,
+    /// Whether the thread and its children cannot gain privileges via `execve`.
+    no_new_privs: AtomicBool,
+
     /// A profiling clock measures the user CPU time and kernel CPU time in the thread.
     prof_clock: Arc<ProfClock>,
 
@@ -104,6 +119,18 @@ impl PosixThread {
         &self.fs
     }
 
+    /// Returns the namespaces of the thread.
+    pub fn ns_proxy(&self) -> Arc<NsProxy> {
+        self.ns_proxy.lock().clone()
+    }
+
+    /// Moves the thread to other namespaces.
+    ///
+    /// This should only be called by the thread itself.
+    pub(in crate::process) fn set_ns_proxy(&self, ns_proxy: Arc<NsProxy>) {
+        *self.ns_proxy.lock() = ns_proxy;
+    }
+
     /// Get the reference to the signal mask of the thread.
     ///
     /// Note that while this function offers mutable access to the signal mask,
@@ -199,6 +226,29 @@ impl PosixThread {
         *self.signalled_waker.lock() = None;
     }
 
+    /// Returns the ptrace state of the thread.
+    pub fn ptrace(&self) -> &PtraceState {
+        &self.ptrace
+    }
+
+    /// Returns the seccomp state of the thread.
+    pub fn seccomp(&self) -> &SeccompState {
+        &self.seccomp
+    }
+
+    /// Returns whether the `no_new_privs` attribute is set.
+    ///
+    /// If it is set, `execve` does not grant privileges, e.g., via the
+    /// set-user-ID bit of the executable file.
+    pub fn no_new_privs(&self) -> bool {
+        self.no_new_privs.load(Ordering::Relaxed)
+    }
+
+    /// Sets the `no_new_privs` attribute, which cannot be unset.
+    pub fn set_no_new_privs(&self) {
+        self.no_new_privs.store(true, Ordering::Relaxed);
+    }
+
     /// Enqueues a thread-directed signal. This method should only be used for enqueue kernel
     /// signal and fault signal.
     pub fn enqueue_signal(&self, signal: Box<dyn Signal>) {
@@ -209,6 +259,10 @@ impl PosixThread {
         {
             waker.wake_up();
         }
+        // `SIGKILL` should wake up the thread even if it is in a ptrace-stop.
+        if signal_number == SIGKILL {
+            self.ptrace.wake_up();
+        }
     }
 
     /// Returns a reference to the profiling clock of the current thread.
@@ -6,7 +6,11 @@ use aster_rights::Full;
 use ostd::{mm::Vaddr, sync::RwArc, task::CurrentTask};
 
 use super::RobustListHead;
-use crate::{fs::file_table::FileTable, process::signal::SigStack, vm::vmar::Vmar};
+use crate::{
+    fs::{file_table::FileTable, io_uring::SubmittedIoUrings},
+    process::signal::SigStack,
+    vm::vmar::Vmar,
+};
 
 /// Local data for a POSIX thread.
 pub struct ThreadLocal {
@@ -24,6 +28,8 @@ pub struct ThreadLocal {
 
     // Files.
     file_table: RefCell<Option<RwArc<FileTable>>>,
+    /// The io_uring instances that the thread has submitted requests to.
+    io_urings: RefCell<SubmittedIoUrings>,
 
     // Signal.
     /// `ucontext` address for the signal handler.
@@ -32,6 +38,10 @@ pub struct ThreadLocal {
     sig_context: Cell<Option<Vaddr>>,
     /// Stack address, size, and flags for the signal handler.
     sig_stack: RefCell<Option<SigStack>>,
+
+    // Memory.
+    /// Whether a page fault in the kernel mode has run out of memory.
+    has_deferred_oom: Cell<bool>,
 }
 
 impl ThreadLocal {
@@ -47,8 +57,10 @@ impl ThreadLocal {
             root_vmar: RefCell::new(Some(root_vmar)),
             robust_list: RefCell::new(None),
             file_table: RefCell::new(Some(file_table)),
+            io_urings: RefCell::new(SubmittedIoUrings::default()),
             sig_context: Cell::new(None),
             sig_stack: RefCell::new(None),
+            has_deferred_oom: Cell::new(false),
         }
     }
 
@@ -76,6 +88,10 @@ impl ThreadLocal {
         FileTableRefMut(self.file_table.borrow_mut())
     }
 
+    pub fn io_urings(&self) -> &RefCell<SubmittedIoUrings> {
+        &self.io_urings
+    }
+
     pub fn sig_context(&self) -> &Cell<Option<Vaddr>> {
         &self.sig_context
     }
@@ -83,6 +99,10 @@ impl ThreadLocal {
     pub fn sig_stack(&self) -> &RefCell<Option<SigStack>> {
         &self.sig_stack
     }
+
+    pub fn has_deferred_oom(&self) -> &Cell<bool> {
+        &self.has_deferred_oom
+    }
 }
 
 /// An immutable, shared reference to the file table in [`ThreadLocal`].
@@ -6,6 +6,8 @@ use super::{Pid, Process};
 use crate::{
     prelude::*,
     process::{
+        cgroup::{self, Cgroup},
+        namespace::PidNamespace,
         posix_thread::{create_posix_task_from_executable, PosixThreadBuilder},
         process_vm::ProcessVm,
         rlimit::ResourceLimits,
@@ -13,6 +15,7 @@ use crate::{
         Credentials,
     },
     sched::Nice,
+    thread::AsThread,
 };
 
 pub struct ProcessBuilder<'a> {
@@ -30,6 +33,8 @@ pub struct ProcessBuilder<'a> {
     sig_dispositions: Option<Arc<Mutex<SigDispositions>>>,
     credentials: Option<Credentials>,
     nice: Option<Nice>,
+    pid_ns: Option<Arc<PidNamespace>>,
+    cgroup: Option<Arc<Cgroup>>,
 }
 
 impl<'a> ProcessBuilder<'a> {
@@ -46,6 +51,8 @@ impl<'a> ProcessBuilder<'a> {
             sig_dispositions: None,
             credentials: None,
             nice: None,
+            pid_ns: None,
+            cgroup: None,
         }
     }
 
@@ -89,6 +96,16 @@ impl<'a> ProcessBuilder<'a> {
         self
     }
 
+    pub fn pid_ns(&mut self, pid_ns: Arc<PidNamespace>) -> &mut Self {
+        self.pid_ns = Some(pid_ns);
+        self
+    }
+
+    pub fn cgroup(&mut self, cgroup: Arc<Cgroup>) -> &mut Self {
+        self.cgroup = Some(cgroup);
+        self
+    }
+
     fn check_build(&self) -> Result<()> {
         if self.main_thread_builder.is_some() {
             debug_assert!(self.parent.upgrade().is_some());
@@ -121,6 +138,8 @@ impl<'a> ProcessBuilder<'a> {
             sig_dispositions,
             credentials,
             nice,
+            pid_ns,
+            cgroup,
         } = self;
 
         let process_vm = process_vm.or_else(|| Some(ProcessVm::alloc())).unwrap();
@@ -135,14 +154,22 @@ impl<'a> ProcessBuilder<'a> {
 
         let nice = nice.or_else(|| Some(Nice::default())).unwrap();
 
+        let pid_ns = pid_ns.unwrap_or_else(|| PidNamespace::get_init_singleton().clone());
+
+        let cgroup = cgroup.unwrap_or_else(|| cgroup::root().clone());
+        // Charge the main thread to the cgroup.
+        cgroup.try_charge_task()?;
+
         let process = Process::new(
             pid,
+            pid_ns,
             parent,
             executable_path.to_string(),
             process_vm,
             resource_limits,
             nice,
             sig_dispositions,
+            cgroup.clone(),
         );
 
         let task = if let Some(thread_builder) = main_thread_builder {
@@ -157,10 +184,16 @@ impl<'a> ProcessBuilder<'a> {
                 Arc::downgrade(&process),
                 argv.unwrap(),
                 envp.unwrap(),
-            )?
+            )
+            .inspect_err(|_| cgroup.uncharge_task())?
         };
 
+        task.as_thread()
+            .unwrap()
+            .sched_attr()
+            .set_fair_group(cgroup.fair_group().cloned());
         process.tasks().lock().insert(task).unwrap();
+        cgroup.add_process(&process);
 
         Ok(process)
     }
@@ -11,7 +11,7 @@ use crate::{
             constants::{SIGCONT, SIGHUP},
             signals::kernel::KernelSignal,
         },
-        ProcessGroup, Session,
+        Process, ProcessGroup, Session,
     },
 };
 
@@ -137,9 +137,7 @@ impl JobControl {
     /// Wait until the current process is the foreground process group. If
     /// the foreground process group is None, returns true.
     ///
-    /// # Panics
-    ///
-    /// This function should only be called in process context.
+    /// Kernel threads never wait because they do not belong to any process.
     pub fn wait_until_in_foreground(&self) -> Result<()> {
         // Fast path
         if self.current_belongs_to_foreground() {
@@ -161,7 +159,13 @@ impl JobControl {
             return true;
         };
 
-        foreground.contains_process(current!().pid())
+        // Kernel threads (e.g., the workers that perform io_uring requests) do not belong to any
+        // process group and are never stopped by the job control.
+        let Some(current) = Process::current() else {
+            return true;
+        };
+
+        foreground.contains_process(current.pid())
     }
 }
 
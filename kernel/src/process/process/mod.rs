@@ -1,9 +1,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicI16, AtomicU32, AtomicU8, Ordering};
 
 use self::timer_manager::PosixTimerManager;
 use super::{
+    cgroup::{self, Cgroup},
+    coredump::Dumpable,
+    namespace::PidNamespace,
     posix_thread::{allocate_posix_tid, AsPosixThread},
     process_table,
     process_vm::{Heap, InitStackReader, ProcessVm, ProcessVmarGuard},
@@ -12,6 +15,7 @@ use super::{
         sig_disposition::SigDispositions,
         sig_num::{AtomicSigNum, SigNum},
         signals::Signal,
+        Pollee,
     },
     status::ProcessStatus,
     task_set::TaskSet,
@@ -21,7 +25,7 @@ use crate::{
     device::tty::open_ntty_as_controlling_terminal,
     prelude::*,
     sched::{AtomicNice, Nice},
-    thread::{AsThread, Thread},
+    thread::{AsThread, Thread, Tid},
     time::clocks::ProfClock,
 };
 
@@ -61,6 +65,8 @@ pub(super) fn init() {
 pub struct Process {
     // Immutable Part
     pid: Pid,
+    /// The PID namespace
+    pid_ns: Arc<PidNamespace>,
 
     process_vm: ProcessVm,
     /// Wait for child status changed
@@ -77,6 +83,8 @@ pub struct Process {
     pub(super) parent: ParentProcess,
     /// Children processes
     children: Mutex<BTreeMap<Pid, Arc<Process>>>,
+    /// The threads that are traced by the process
+    tracees: Mutex<BTreeMap<Tid, Arc<Thread>>>,
     /// Process group
     pub(super) process_group: Mutex<Weak<ProcessGroup>>,
     /// resource limits
@@ -85,6 +93,12 @@ pub struct Process {
     /// According to POSIX.1, the nice value is a per-process attribute,
     /// the threads in a process should share a nice value.
     nice: AtomicNice,
+    /// The adjustment to the badness score used by the OOM killer.
+    oom_score_adj: AtomicI16,
+    /// The cgroup that the process belongs to.
+    cgroup: RwLock<Arc<Cgroup>>,
+    /// Whether the process can generate core dumps, which is a [`Dumpable`].
+    dumpable: AtomicU8,
 
     // Child reaper attribute
     /// Whether the process is a child subreaper.
@@ -110,6 +124,10 @@ pub struct Process {
     /// The signal that should be sent to the parent when this process exits.
     exit_signal: AtomicSigNum,
 
+    /// The pollee of the PID files that refer to the process, which is notified when the
+    /// process exits.
+    pidfd_pollee: Pollee,
+
     /// A profiling clock measures the user CPU time and kernel CPU time of the current process.
     prof_clock: Arc<ProfClock>,
 
@@ -186,6 +204,7 @@ impl Process {
 
     fn new(
         pid: Pid,
+        pid_ns: Arc<PidNamespace>,
         parent: Weak<Process>,
         executable_path: String,
         process_vm: ProcessVm,
@@ -193,6 +212,7 @@ impl Process {
         resource_limits: ResourceLimits,
         nice: Nice,
         sig_dispositions: Arc<Mutex<SigDispositions>>,
+        cgroup: Arc<Cgroup>,
     ) -> Arc<Self> {
         // SIGCHID does not interrupt pauser. Child process will
         // resume paused parent when doing exit.
@@ -202,6 +222,7 @@ impl Process {
 
         Arc::new_cyclic(|process_ref: &Weak<Process>| Self {
             pid,
+            pid_ns,
             tasks: Mutex::new(TaskSet::new()),
             executable_path: RwLock::new(executable_path),
             process_vm,
@@ -209,14 +230,19 @@ impl Process {
             status: ProcessStatus::default(),
             parent: ParentProcess::new(parent),
             children: Mutex::new(BTreeMap::new()),
+            tracees: Mutex::new(BTreeMap::new()),
             process_group: Mutex::new(Weak::new()),
             is_child_subreaper: AtomicBool::new(false),
             has_child_subreaper: AtomicBool::new(false),
             sig_dispositions,
             parent_death_signal: AtomicSigNum::new_empty(),
             exit_signal: AtomicSigNum::new_empty(),
+            pidfd_pollee: Pollee::new(),
             resource_limits,
             nice: AtomicNice::new(nice),
+            oom_score_adj: AtomicI16::new(0),
+            cgroup: RwLock::new(cgroup),
+            dumpable: AtomicU8::new(Dumpable::User as u8),
             timer_manager: PosixTimerManager::new(&prof_clock, process_ref),
             prof_clock,
         })
@@ -296,6 +322,14 @@ impl Process {
         self.pid
     }
 
+    /// Returns the PID namespace of the process.
+    ///
+    /// Note that [`Self::pid`] returns the global PID, i.e., the PID in the initial PID
+    /// namespace.
+    pub fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
     /// Gets the profiling clock of the process.
     pub fn prof_clock(&self) -> &Arc<ProfClock> {
         &self.prof_clock
@@ -326,6 +360,45 @@ impl Process {
         &self.nice
     }
 
+    /// Returns the adjustment to the badness score used by the OOM killer.
+    ///
+    /// The value ranges from [`OOM_SCORE_ADJ_MIN`] to [`OOM_SCORE_ADJ_MAX`].
+    /// A process whose value is [`OOM_SCORE_ADJ_MIN`] is never killed by the
+    /// OOM killer.
+    ///
+    /// [`OOM_SCORE_ADJ_MIN`]: crate::process::OOM_SCORE_ADJ_MIN
+    /// [`OOM_SCORE_ADJ_MAX`]: crate::process::OOM_SCORE_ADJ_MAX
+    pub fn oom_score_adj(&self) -> i16 {
+        self.oom_score_adj.load(Ordering::Relaxed)
+    }
+
+    /// Sets the adjustment to the badness score used by the OOM killer.
+    pub fn set_oom_score_adj(&self, oom_score_adj: i16) {
+        self.oom_score_adj.store(oom_score_adj, Ordering::Relaxed);
+    }
+
+    /// Returns whether the process can generate core dumps.
+    pub fn dumpable(&self) -> Dumpable {
+        Dumpable::try_from(self.dumpable.load(Ordering::Relaxed) as u64).unwrap()
+    }
+
+    /// Sets whether the process can generate core dumps.
+    pub fn set_dumpable(&self, dumpable: Dumpable) {
+        self.dumpable.store(dumpable as u8, Ordering::Relaxed);
+    }
+
+    /// Returns the cgroup that the process belongs to.
+    pub fn cgroup(&self) -> Arc<Cgroup> {
+        self.cgroup.read().clone()
+    }
+
+    /// Sets the cgroup that the process belongs to.
+    ///
+    /// The process must be moved with [`Cgroup::attach`].
+    pub(in crate::process) fn set_cgroup(&self, cgroup: Arc<Cgroup>) {
+        *self.cgroup.write() = cgroup;
+    }
+
     pub fn main_thread(&self) -> Arc<Thread> {
         self.tasks.lock().main().as_thread().unwrap().clone()
     }
@@ -351,6 +424,11 @@ impl Process {
         &self.children_wait_queue
     }
 
+    /// Returns the threads that are traced by the process.
+    pub(in crate::process) fn tracees(&self) -> &Mutex<BTreeMap<Tid, Arc<Thread>>> {
+        &self.tracees
+    }
+
     // *********** Process group & Session***********
 
     /// Returns the process group ID of the process.
@@ -676,6 +754,11 @@ impl Process {
         self.exit_signal.as_sig_num()
     }
 
+    /// Returns the pollee of the PID files that refer to the process.
+    pub(in crate::process) fn pidfd_pollee(&self) -> &Pollee {
+        &self.pidfd_pollee
+    }
+
     // ******************* Status ********************
 
     /// Returns a reference to the process status.
@@ -726,6 +809,14 @@ impl Process {
     }
 }
 
+impl Drop for Process {
+    fn drop(&mut self) {
+        // The PID is kept in the PID namespace until the process is dropped, so that the PID of
+        // a reaped process can still be translated (e.g., for the return value of `wait4`).
+        self.pid_ns.free_tid(self.pid);
+    }
+}
+
 #[cfg(ktest)]
 mod test {
 
@@ -744,12 +835,14 @@ mod test {
         };
         Process::new(
             pid,
+            PidNamespace::get_init_singleton().clone(),
             parent,
             String::new(),
             ProcessVm::alloc(),
             ResourceLimits::default(),
             Nice::default(),
             Arc::new(Mutex::new(SigDispositions::default())),
+            cgroup::root().clone(),
         )
     }
 
@@ -2,8 +2,11 @@
 
 #![expect(dead_code)]
 
-use super::{Pgid, Pid};
-use crate::prelude::*;
+use super::{namespace::PidNamespace, Pgid, Pid, PidFile};
+use crate::{
+    fs::file_table::{get_file_fast, FileDesc},
+    prelude::*,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProcessFilter {
@@ -14,35 +17,52 @@ pub enum ProcessFilter {
 
 impl ProcessFilter {
     // used for waitid
-    pub fn from_which_and_id(which: u64, id: u64) -> Result<Self> {
-        // Does not support PID_FD now(which = 3)
+    //
+    // The IDs are in the PID namespace of the current process. Returns `None` if there is no
+    // such process or process group in the PID namespace.
+    pub fn from_which_and_id(which: u64, id: u64, ctx: &Context) -> Result<Option<Self>> {
         // https://elixir.bootlin.com/linux/latest/source/include/uapi/linux/wait.h#L20
+        let pid_ns = ctx.process.pid_ns();
         match which {
-            0 => Ok(ProcessFilter::Any),
-            1 => Ok(ProcessFilter::WithPid(id as Pid)),
-            2 => Ok(ProcessFilter::WithPgid(id as Pgid)),
-            3 => todo!(),
+            0 => Ok(Some(ProcessFilter::Any)),
+            1 => Ok(pid_ns.global_tid(id as Pid).map(ProcessFilter::WithPid)),
+            2 => Ok(pid_ns.global_tid(id as Pgid).map(ProcessFilter::WithPgid)),
+            3 => {
+                let mut file_table = ctx.thread_local.borrow_file_table_mut();
+                let file = get_file_fast!(&mut file_table, id as FileDesc);
+                let pid_file = file.downcast_ref::<PidFile>().ok_or_else(|| {
+                    Error::with_message(Errno::EBADF, "the file is not a PID file")
+                })?;
+                Ok(Some(ProcessFilter::WithPid(pid_file.process().pid())))
+            }
             _ => return_errno_with_message!(Errno::EINVAL, "invalid which"),
         }
     }
 
     // used for wait4 and kill
-    pub fn from_id(wait_pid: i32) -> Self {
+    //
+    // The IDs are in `pid_ns`. Returns `None` if there is no such process or process group in
+    // `pid_ns`.
+    pub fn from_id(wait_pid: i32, pid_ns: &PidNamespace) -> Option<Self> {
         // https://man7.org/linux/man-pages/man2/waitpid.2.html
         // https://man7.org/linux/man-pages/man2/kill.2.html
         if wait_pid < -1 {
             // process group ID is equal to the absolute value of pid.
-            ProcessFilter::WithPgid((-wait_pid) as Pgid)
+            pid_ns
+                .global_tid(wait_pid.unsigned_abs() as Pgid)
+                .map(ProcessFilter::WithPgid)
         } else if wait_pid == -1 {
             // wait for any child process
-            ProcessFilter::Any
+            Some(ProcessFilter::Any)
         } else if wait_pid == 0 {
             // wait for any child process with same process group ID
             let pgid = current!().pgid();
-            ProcessFilter::WithPgid(pgid)
+            Some(ProcessFilter::WithPgid(pgid))
         } else {
             // pid > 0. wait for the child whose process ID is equal to the value of pid.
-            ProcessFilter::WithPid(wait_pid as Pid)
+            pid_ns
+                .global_tid(wait_pid as Pid)
+                .map(ProcessFilter::WithPid)
         }
     }
 
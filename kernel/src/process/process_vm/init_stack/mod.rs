@@ -478,6 +478,47 @@ impl InitStackReader<'_> {
         Ok(envp)
     }
 
+    /// Reads the auxiliary vector from the process init stack.
+    ///
+    /// The entries are returned as key-value pairs in the stack, including
+    /// the trailing `AT_NULL` entry.
+    pub fn auxv(&self) -> Result<Vec<(u64, u64)>> {
+        const MAX_AUXV_NUMBER: usize = 64;
+
+        let vmar = self.vmar.unwrap();
+        let read_word = |addr: Vaddr| -> Result<u64> {
+            let mut word = 0u64;
+            vmar.read_remote(addr, word.as_bytes_mut())?;
+            Ok(word)
+        };
+
+        // Skip argc, the argument pointers, and the null pointer after them.
+        let argc = self.argc()? as usize;
+        let mut read_addr = self.init_stack_bottom() + size_of::<usize>() * (argc + 2);
+
+        // Skip the environment pointers and the null pointer after them.
+        for _ in 0..MAX_ENVP_NUMBER {
+            if read_word(read_addr)? == 0 {
+                break;
+            }
+            read_addr += size_of::<usize>();
+        }
+        read_addr += size_of::<usize>();
+
+        let mut auxv = Vec::new();
+        for _ in 0..MAX_AUXV_NUMBER {
+            let key = read_word(read_addr)?;
+            let val = read_word(read_addr + size_of::<u64>())?;
+            auxv.push((key, val));
+            if key == AuxKey::AT_NULL.as_u64() {
+                return Ok(auxv);
+            }
+            read_addr += size_of::<u64>() * 2;
+        }
+
+        return_errno_with_message!(Errno::EINVAL, "the auxiliary vector is corrupted");
+    }
+
     /// Returns the bottom address of the init stack (lowest address).
     pub const fn init_stack_bottom(&self) -> Vaddr {
         self.base
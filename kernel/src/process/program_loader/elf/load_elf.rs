@@ -299,6 +299,7 @@ fn map_segment_vmo(
         let mut vm_map_options = root_vmar
             .new_map(segment_size, perms)?
             .vmo(segment_vmo.dup()?)
+            .file(elf_file.clone())
             .vmo_offset(segment_offset)
             .vmo_limit(segment_offset + segment_size)
             .can_overwrite(true);
@@ -329,7 +330,7 @@ fn map_segment_vmo(
                 new_frame
             };
             cursor.map(
-                new_frame.into(),
+                new_frame,
                 PageProperty::new(page_flags, CachePolicy::Writeback),
             );
         }
@@ -354,7 +355,7 @@ fn map_segment_vmo(
             let tail_page_addr = map_addr + tail_padding_offset.align_down(PAGE_SIZE);
             cursor.jump(tail_page_addr)?;
             cursor.map(
-                new_frame.into(),
+                new_frame,
                 PageProperty::new(page_flags, CachePolicy::Writeback),
             );
         }
@@ -0,0 +1,776 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Process tracing.
+//!
+//! A traced thread (i.e., a tracee) enters a _ptrace-stop_ at certain points,
+//! e.g., before a signal is delivered, or when a system call is entered or
+//! exited. In a ptrace-stop, the tracee saves its registers, notifies the
+//! tracer, and sleeps until the tracer resumes it. Meanwhile, the tracer
+//! observes the stop via `wait4` or `waitid`, and inspects or modifies the
+//! tracee via `ptrace`.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use ostd::{cpu::context::UserContext, sync::WaitQueue, user::UserContextApi};
+
+use super::{
+    coredump::Dumpable,
+    credentials::capabilities::CapSet,
+    posix_thread::{AsPosixThread, PosixThread},
+    signal::{
+        c_types::siginfo_t,
+        constants::{SIGCHLD, SIGKILL, SIGSTOP, SIGTRAP},
+        sig_num::SigNum,
+        signals::{kernel::KernelSignal, Signal},
+    },
+    CloneArgs, CloneFlags, Process,
+};
+use crate::{
+    arch::ptrace::{self as arch_ptrace, PtraceRegs},
+    cpu::LinuxAbi,
+    prelude::*,
+    thread::Thread,
+};
+
+bitflags! {
+    /// The options of tracing, which are set by `PTRACE_SETOPTIONS` or `PTRACE_SEIZE`.
+    pub struct PtraceOptions: u32 {
+        const PTRACE_O_TRACESYSGOOD = 1;
+        const PTRACE_O_TRACEFORK = 1 << 1;
+        const PTRACE_O_TRACEVFORK = 1 << 2;
+        const PTRACE_O_TRACECLONE = 1 << 3;
+        const PTRACE_O_TRACEEXEC = 1 << 4;
+        const PTRACE_O_TRACEVFORKDONE = 1 << 5;
+        const PTRACE_O_TRACEEXIT = 1 << 6;
+        const PTRACE_O_TRACESECCOMP = 1 << 7;
+        const PTRACE_O_EXITKILL = 1 << 20;
+        const PTRACE_O_SUSPEND_SECCOMP = 1 << 21;
+    }
+}
+
+/// The events that are reported in ptrace-stops (i.e., `PTRACE_EVENT_*`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PtraceEvent {
+    Fork = 1,
+    Vfork = 2,
+    Clone = 3,
+    Exec = 4,
+    VforkDone = 5,
+    Exit = 6,
+    Seccomp = 7,
+    Stop = 128,
+}
+
+/// How a tracee runs after it is resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeMode {
+    /// Runs until the next signal (`PTRACE_CONT`).
+    Continue,
+    /// Runs until the next entry to or exit from a system call (`PTRACE_SYSCALL`).
+    Syscall,
+    /// Runs a single instruction (`PTRACE_SINGLESTEP`).
+    SingleStep,
+}
+
+/// The ptrace state of a POSIX thread.
+pub struct PtraceState {
+    /// Whether the thread is traced.
+    ///
+    /// This allows the thread to skip the ptrace-stops without locking `inner`.
+    is_traced: AtomicBool,
+    /// Whether the thread should enter a `PTRACE_EVENT_STOP` as soon as possible.
+    has_pending_interrupt: AtomicBool,
+    inner: SpinLock<PtraceInner>,
+    /// The wait queue where the thread sleeps in ptrace-stops.
+    wait_queue: WaitQueue,
+}
+
+struct PtraceInner {
+    tracer: Weak<Process>,
+    is_seized: bool,
+    options: PtraceOptions,
+    resume_mode: ResumeMode,
+    stop: Option<PtraceStop>,
+    /// The event that will be reported when the current system call returns.
+    pending_event: Option<PtraceEvent>,
+    /// The message of the last event, which can be retrieved by `PTRACE_GETEVENTMSG`.
+    event_msg: usize,
+    /// The wait status of the thread if it has exited.
+    exit_status: Option<u32>,
+}
+
+/// A ptrace-stop of a tracee.
+pub struct PtraceStop {
+    /// The code that is reported to the tracer.
+    ///
+    /// The code consists of the signal number (`SIGTRAP | 0x80` for syscall-stops if
+    /// `PTRACE_O_TRACESYSGOOD` is set) and the event number shifted by 8 bits.
+    code: u32,
+    siginfo: siginfo_t,
+    regs: PtraceRegs,
+    fp_regs: Option<Vec<u8>>,
+    is_reported: bool,
+    resume: Option<Resume>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Resume {
+    mode: ResumeMode,
+    signal: Option<SigNum>,
+}
+
+/// The status of a tracee that can be waited for by the tracer.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum TraceeStatus {
+    /// The tracee is in a ptrace-stop with the code.
+    Stopped(u32),
+    /// The tracee has exited with the wait status.
+    Exited(u32),
+}
+
+impl PtraceState {
+    pub(super) fn new() -> Self {
+        Self {
+            is_traced: AtomicBool::new(false),
+            has_pending_interrupt: AtomicBool::new(false),
+            inner: SpinLock::new(PtraceInner {
+                tracer: Weak::new(),
+                is_seized: false,
+                options: PtraceOptions::empty(),
+                resume_mode: ResumeMode::Continue,
+                stop: None,
+                pending_event: None,
+                event_msg: 0,
+                exit_status: None,
+            }),
+            wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Returns whether the thread is traced.
+    pub fn is_traced(&self) -> bool {
+        self.is_traced.load(Ordering::Acquire)
+    }
+
+    /// Returns whether the thread should enter a `PTRACE_EVENT_STOP` as soon as possible.
+    pub fn has_pending_interrupt(&self) -> bool {
+        self.has_pending_interrupt.load(Ordering::Relaxed)
+    }
+
+    /// Wakes up the thread if it is in a ptrace-stop.
+    ///
+    /// This should be called when `SIGKILL` is sent to the thread.
+    pub(super) fn wake_up(&self) {
+        self.wait_queue.wake_all();
+    }
+
+    fn options(&self) -> PtraceOptions {
+        self.inner.lock().options
+    }
+
+    fn resume_mode(&self) -> ResumeMode {
+        self.inner.lock().resume_mode
+    }
+
+    /// Enters a ptrace-stop and sleeps until the tracer resumes the thread.
+    ///
+    /// If the tracer modifies the registers, they will be written back to `user_ctx` and
+    /// `orig_syscall_num`.
+    ///
+    /// This method returns the signal injected by the tracer, or `None` if the thread is killed
+    /// during the stop.
+    fn stop(
+        &self,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+        code: u32,
+        siginfo: siginfo_t,
+        orig_syscall_num: &mut Option<usize>,
+    ) -> Option<Option<SigNum>> {
+        let is_killed = || ctx.posix_thread.sig_pending().contains(SIGKILL);
+        if is_killed() {
+            return None;
+        }
+
+        let fp_regs = arch_ptrace::save_fp_regs(user_ctx).ok();
+        let tracer = {
+            let mut inner = self.inner.lock();
+            let Some(tracer) = inner.tracer.upgrade() else {
+                return Some(None);
+            };
+            inner.stop = Some(PtraceStop {
+                code,
+                siginfo,
+                regs: PtraceRegs::from_user_ctx(user_ctx, *orig_syscall_num),
+                fp_regs,
+                is_reported: false,
+                resume: None,
+            });
+            tracer
+        };
+        notify_tracer(&tracer);
+
+        let stop = self.wait_queue.wait_until(|| {
+            let mut inner = self.inner.lock();
+            let is_resumed = inner.stop.as_ref().is_none_or(|stop| stop.resume.is_some());
+            (is_resumed || is_killed()).then(|| inner.stop.take())
+        });
+
+        if is_killed() {
+            return None;
+        }
+        let Some(PtraceStop {
+            regs,
+            resume: Some(resume),
+            ..
+        }) = stop
+        else {
+            return Some(None);
+        };
+
+        regs.write_to(ctx, user_ctx, orig_syscall_num);
+        // The tracer has checked whether single-stepping is supported before resuming us.
+        let _ = arch_ptrace::set_single_step(user_ctx, resume.mode == ResumeMode::SingleStep);
+
+        Some(resume.signal)
+    }
+
+    /// Polls the status that the tracer can wait for.
+    ///
+    /// If `should_consume` is true, the stop will not be reported again.
+    pub(super) fn poll_status(&self, should_consume: bool) -> Option<TraceeStatus> {
+        let mut inner = self.inner.lock();
+
+        if let Some(exit_status) = inner.exit_status {
+            return Some(TraceeStatus::Exited(exit_status));
+        }
+
+        let stop = inner.stop.as_mut()?;
+        if stop.is_reported || stop.resume.is_some() {
+            return None;
+        }
+        stop.is_reported |= should_consume;
+        Some(TraceeStatus::Stopped(stop.code))
+    }
+
+    /// Checks that the thread is traced by `tracer` and accesses the inner state with `op`.
+    fn with_inner<F, R>(&self, tracer: &Process, op: F) -> Result<R>
+    where
+        F: FnOnce(&mut PtraceInner) -> Result<R>,
+    {
+        let mut inner = self.inner.lock();
+        if !inner
+            .tracer
+            .upgrade()
+            .is_some_and(|process| core::ptr::eq(process.as_ref(), tracer))
+        {
+            return_errno_with_message!(Errno::ESRCH, "the thread is not traced by the process");
+        }
+        op(&mut inner)
+    }
+
+    /// Checks that the thread is traced by `tracer` and is in a ptrace-stop, and accesses the
+    /// inner state with `op`.
+    fn with_stopped_inner<F, R>(&self, tracer: &Process, op: F) -> Result<R>
+    where
+        F: FnOnce(&mut PtraceInner) -> Result<R>,
+    {
+        self.with_inner(tracer, |inner| {
+            if !inner
+                .stop
+                .as_ref()
+                .is_some_and(|stop| stop.resume.is_none())
+            {
+                return_errno_with_message!(Errno::ESRCH, "the thread is not stopped");
+            }
+            op(inner)
+        })
+    }
+
+    /// Accesses the ptrace-stop of the thread with `op`.
+    ///
+    /// This method fails with `ESRCH` if the thread is not traced by `tracer`, or if it is not
+    /// in a ptrace-stop.
+    pub fn with_stop<F, R>(&self, tracer: &Process, op: F) -> Result<R>
+    where
+        F: FnOnce(&mut PtraceStop) -> Result<R>,
+    {
+        self.with_stopped_inner(tracer, |inner| op(inner.stop.as_mut().unwrap()))
+    }
+
+    /// Sets the options of tracing.
+    pub fn set_options(&self, tracer: &Process, options: PtraceOptions) -> Result<()> {
+        self.with_stopped_inner(tracer, |inner| {
+            inner.options = options;
+            Ok(())
+        })
+    }
+
+    /// Returns the message of the last event.
+    pub fn event_msg(&self, tracer: &Process) -> Result<usize> {
+        self.with_stopped_inner(tracer, |inner| Ok(inner.event_msg))
+    }
+
+    /// Resumes the thread from the ptrace-stop.
+    pub fn resume(&self, tracer: &Process, mode: ResumeMode, signal: Option<SigNum>) -> Result<()> {
+        self.with_stopped_inner(tracer, |inner| {
+            inner.stop.as_mut().unwrap().resume = Some(Resume { mode, signal });
+            inner.resume_mode = mode;
+            Ok(())
+        })?;
+
+        self.wait_queue.wake_all();
+        Ok(())
+    }
+
+    /// Requests the thread to enter a `PTRACE_EVENT_STOP` (`PTRACE_INTERRUPT`).
+    pub fn interrupt(&self, tracer: &Process) -> Result<()> {
+        self.with_inner(tracer, |inner| {
+            if !inner.is_seized {
+                return_errno_with_message!(
+                    Errno::EIO,
+                    "the thread is not attached by PTRACE_SEIZE"
+                );
+            }
+            if inner.stop.is_none() {
+                self.has_pending_interrupt.store(true, Ordering::Relaxed);
+            }
+            Ok(())
+        })
+    }
+}
+
+impl PtraceStop {
+    /// Returns the registers of the tracee.
+    pub fn regs(&self) -> &PtraceRegs {
+        &self.regs
+    }
+
+    /// Returns the registers of the tracee, which will take effect after the tracee resumes.
+    pub fn regs_mut(&mut self) -> &mut PtraceRegs {
+        &mut self.regs
+    }
+
+    /// Returns the floating-point registers of the tracee.
+    pub fn fp_regs(&self) -> Result<&[u8]> {
+        self.fp_regs.as_deref().ok_or_else(|| {
+            Error::with_message(Errno::EIO, "the floating-point registers are not available")
+        })
+    }
+
+    /// Returns the information about the signal that causes the stop.
+    pub fn siginfo(&self) -> &siginfo_t {
+        &self.siginfo
+    }
+}
+
+/// Checks whether the current thread may access `target`, e.g., attach to it
+/// with `ptrace` or get its files with `pidfd_getfd`.
+///
+/// Other than threads in the same process, a thread can access `target` only
+/// if it has the same user and group IDs as `target` or has `CAP_SYS_PTRACE`.
+/// In addition, a thread without `CAP_SYS_PTRACE` cannot access `target` if
+/// the process of `target` is not dumpable (see [`Dumpable`]).
+///
+/// Reference: <https://man7.org/linux/man-pages/man2/ptrace.2.html> (section
+/// "Ptrace access mode checking").
+pub fn check_may_access(ctx: &Context, target: &Thread) -> Result<()> {
+    let target_posix_thread = target.as_posix_thread().unwrap();
+    let target_process = target_posix_thread.process();
+    if core::ptr::eq(target_process.as_ref(), ctx.process) {
+        return Ok(());
+    }
+
+    let credentials = ctx.posix_thread.credentials();
+    let has_cap_sys_ptrace = credentials.effective_capset().contains(CapSet::SYS_PTRACE);
+
+    let target_credentials = target_posix_thread.credentials();
+    let ruid = credentials.ruid();
+    let rgid = credentials.rgid();
+    let has_same_ids = target_credentials.ruid() == ruid
+        && target_credentials.euid() == ruid
+        && target_credentials.suid() == ruid
+        && target_credentials.rgid() == rgid
+        && target_credentials.egid() == rgid
+        && target_credentials.sgid() == rgid;
+    if !has_same_ids && !has_cap_sys_ptrace {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "the thread cannot be accessed by the current thread"
+        );
+    }
+
+    if target_process.dumpable() != Dumpable::User && !has_cap_sys_ptrace {
+        return_errno_with_message!(Errno::EPERM, "the process is not dumpable");
+    }
+
+    Ok(())
+}
+
+/// Attaches `tracee` to `tracer`.
+///
+/// `is_seized` is true if the tracee is attached with `PTRACE_SEIZE`.
+pub fn attach(
+    tracer: &Arc<Process>,
+    tracee: &Arc<Thread>,
+    is_seized: bool,
+    options: PtraceOptions,
+) -> Result<()> {
+    let tracee_posix_thread = tracee.as_posix_thread().unwrap();
+    let ptrace = tracee_posix_thread.ptrace();
+
+    // Lock order: tracees of the tracer -> inner state of the tracee
+    let mut tracees = tracer.tracees().lock();
+    let mut inner = ptrace.inner.lock();
+
+    if tracee.is_exited() {
+        return_errno_with_message!(Errno::ESRCH, "the thread has exited");
+    }
+    if inner.tracer.upgrade().is_some() {
+        return_errno_with_message!(Errno::EPERM, "the thread is already traced");
+    }
+
+    inner.tracer = Arc::downgrade(tracer);
+    inner.is_seized = is_seized;
+    inner.options = options;
+    inner.resume_mode = ResumeMode::Continue;
+    inner.stop = None;
+    inner.pending_event = None;
+    inner.event_msg = 0;
+    ptrace.is_traced.store(true, Ordering::Release);
+
+    tracees.insert(tracee_posix_thread.tid(), tracee.clone());
+    Ok(())
+}
+
+/// Detaches `tracee` from `tracer`.
+///
+/// If `tracee` is in a ptrace-stop, it will be resumed with `signal`.
+pub fn detach(tracer: &Process, tracee: &Thread, signal: Option<SigNum>) -> Result<()> {
+    let tracee_posix_thread = tracee.as_posix_thread().unwrap();
+    let ptrace = tracee_posix_thread.ptrace();
+
+    let mut tracees = tracer.tracees().lock();
+    ptrace.with_inner(tracer, |inner| {
+        detach_locked(ptrace, inner, signal);
+        Ok(())
+    })?;
+    tracees.remove(&tracee_posix_thread.tid());
+
+    ptrace.wait_queue.wake_all();
+    Ok(())
+}
+
+fn detach_locked(ptrace: &PtraceState, inner: &mut PtraceInner, signal: Option<SigNum>) {
+    inner.tracer = Weak::new();
+    inner.resume_mode = ResumeMode::Continue;
+    inner.pending_event = None;
+    if let Some(stop) = inner.stop.as_mut()
+        && stop.resume.is_none()
+    {
+        stop.resume = Some(Resume {
+            mode: ResumeMode::Continue,
+            signal,
+        });
+    }
+
+    ptrace.is_traced.store(false, Ordering::Release);
+    ptrace.has_pending_interrupt.store(false, Ordering::Relaxed);
+}
+
+/// Detaches all the tracees of `tracer`.
+///
+/// This should be called when `tracer` exits. The tracees with `PTRACE_O_EXITKILL` set are
+/// killed.
+pub(super) fn detach_all(tracer: &Process) {
+    let tracees = core::mem::take(&mut *tracer.tracees().lock());
+
+    for tracee in tracees.values() {
+        let tracee_posix_thread = tracee.as_posix_thread().unwrap();
+        let ptrace = tracee_posix_thread.ptrace();
+
+        let should_kill = {
+            let mut inner = ptrace.inner.lock();
+            if !inner
+                .tracer
+                .upgrade()
+                .is_none_or(|process| core::ptr::eq(process.as_ref(), tracer))
+            {
+                continue;
+            }
+            let should_kill = inner.options.contains(PtraceOptions::PTRACE_O_EXITKILL);
+            detach_locked(ptrace, &mut inner, None);
+            should_kill
+        };
+
+        if should_kill {
+            tracee_posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+        }
+        ptrace.wait_queue.wake_all();
+    }
+}
+
+/// Notifies the tracer that the state of a tracee has changed.
+fn notify_tracer(tracer: &Process) {
+    tracer.enqueue_signal(KernelSignal::new(SIGCHLD));
+    tracer.children_wait_queue().wake_all();
+}
+
+/// Returns the code of a ptrace-stop that reports `event`.
+fn event_code(event: PtraceEvent) -> u32 {
+    SIGTRAP.as_u8() as u32 | ((event as u32) << 8)
+}
+
+/// Injects the signal given by the tracer when resuming the current thread from a ptrace-stop
+/// that is not a signal-delivery-stop.
+fn inject_signal(ctx: &Context, signal: Option<SigNum>) {
+    if let Some(signal) = signal {
+        ctx.posix_thread
+            .enqueue_signal(Box::new(KernelSignal::new(signal)));
+    }
+}
+
+/// Reports the entry to a system call to the tracer if the current thread is traced with
+/// `PTRACE_SYSCALL`.
+///
+/// This method returns the number of the system call to execute, which may be changed by the
+/// tracer, or `None` if the system call should be skipped.
+pub fn ptrace_syscall_enter(ctx: &Context, user_ctx: &mut UserContext) -> Option<usize> {
+    let syscall_num = user_ctx.syscall_num();
+
+    let ptrace = ctx.posix_thread.ptrace();
+    if !ptrace.is_traced() || ptrace.resume_mode() != ResumeMode::Syscall {
+        return Some(syscall_num);
+    }
+
+    let mut orig_syscall_num = Some(syscall_num);
+    arch_ptrace::prepare_syscall_enter_stop(user_ctx);
+    let signal = ptrace.stop(
+        ctx,
+        user_ctx,
+        syscall_stop_code(ptrace),
+        siginfo_t::new(SIGTRAP, SIGTRAP.as_u8() as i32),
+        &mut orig_syscall_num,
+    )?;
+    inject_signal(ctx, signal);
+
+    // Like Linux, a system call number of -1 means that the tracer wants to skip it.
+    let syscall_num = orig_syscall_num.unwrap();
+    if syscall_num == usize::MAX {
+        return None;
+    }
+    user_ctx.set_syscall_num(syscall_num);
+    Some(syscall_num)
+}
+
+/// Reports the exit from a system call to the tracer if the current thread is traced.
+///
+/// This also reports the events (e.g., `PTRACE_EVENT_FORK`) that happen during the system call.
+pub fn ptrace_syscall_exit(ctx: &Context, user_ctx: &mut UserContext, syscall_num: Option<usize>) {
+    let ptrace = ctx.posix_thread.ptrace();
+    if !ptrace.is_traced() || ctx.thread.is_exited() {
+        return;
+    }
+
+    let pending_event = ptrace.inner.lock().pending_event.take();
+    if let Some(event) = pending_event {
+        let Some(signal) = ptrace.stop(
+            ctx,
+            user_ctx,
+            event_code(event),
+            siginfo_t::new(SIGTRAP, SIGTRAP.as_u8() as i32 | ((event as i32) << 8)),
+            &mut None,
+        ) else {
+            return;
+        };
+        inject_signal(ctx, signal);
+    }
+
+    if ptrace.resume_mode() != ResumeMode::Syscall {
+        return;
+    }
+    let mut orig_syscall_num = Some(syscall_num.unwrap_or(usize::MAX));
+    let Some(signal) = ptrace.stop(
+        ctx,
+        user_ctx,
+        syscall_stop_code(ptrace),
+        siginfo_t::new(SIGTRAP, SIGTRAP.as_u8() as i32),
+        &mut orig_syscall_num,
+    ) else {
+        return;
+    };
+    inject_signal(ctx, signal);
+}
+
+fn syscall_stop_code(ptrace: &PtraceState) -> u32 {
+    if ptrace
+        .options()
+        .contains(PtraceOptions::PTRACE_O_TRACESYSGOOD)
+    {
+        SIGTRAP.as_u8() as u32 | 0x80
+    } else {
+        SIGTRAP.as_u8() as u32
+    }
+}
+
+/// Reports the signal to the tracer before it is delivered to the current thread.
+///
+/// This method returns the signal that should be delivered, which may be replaced or
+/// suppressed by the tracer.
+pub fn ptrace_signal_stop(
+    ctx: &Context,
+    user_ctx: &mut UserContext,
+    signal: Box<dyn Signal>,
+) -> Option<Box<dyn Signal>> {
+    let ptrace = ctx.posix_thread.ptrace();
+    let sig_num = signal.num();
+    if !ptrace.is_traced() || sig_num == SIGKILL {
+        return Some(signal);
+    }
+
+    let new_sig_num = ptrace.stop(
+        ctx,
+        user_ctx,
+        sig_num.as_u8() as u32,
+        signal.to_info(),
+        &mut None,
+    )??;
+    if new_sig_num == sig_num {
+        Some(signal)
+    } else {
+        Some(Box::new(KernelSignal::new(new_sig_num)))
+    }
+}
+
+/// Reports the group-stop caused by `sig_num` to the tracer if the current thread is traced.
+///
+/// This method returns whether the group-stop is handled as a ptrace-stop.
+pub fn ptrace_group_stop(ctx: &Context, user_ctx: &mut UserContext, sig_num: SigNum) -> bool {
+    let ptrace = ctx.posix_thread.ptrace();
+    if !ptrace.is_traced() {
+        return false;
+    }
+
+    // For tracees attached by `PTRACE_SEIZE`, group-stops are reported as `PTRACE_EVENT_STOP`.
+    let code = if ptrace.inner.lock().is_seized {
+        sig_num.as_u8() as u32 | ((PtraceEvent::Stop as u32) << 8)
+    } else {
+        sig_num.as_u8() as u32
+    };
+    // The signal given by the tracer is ignored when resuming from a group-stop.
+    let _ = ptrace.stop(ctx, user_ctx, code, siginfo_t::new(sig_num, 0), &mut None);
+    true
+}
+
+/// Enters a `PTRACE_EVENT_STOP` if the tracer has requested it (e.g., by `PTRACE_INTERRUPT`).
+pub fn ptrace_interrupt_stop(ctx: &Context, user_ctx: &mut UserContext) {
+    let ptrace = ctx.posix_thread.ptrace();
+    if !ptrace.has_pending_interrupt.swap(false, Ordering::Relaxed) || !ptrace.is_traced() {
+        return;
+    }
+
+    let Some(signal) = ptrace.stop(
+        ctx,
+        user_ctx,
+        event_code(PtraceEvent::Stop),
+        siginfo_t::new(
+            SIGTRAP,
+            SIGTRAP.as_u8() as i32 | ((PtraceEvent::Stop as i32) << 8),
+        ),
+        &mut None,
+    ) else {
+        return;
+    };
+    inject_signal(ctx, signal);
+}
+
+/// Attaches the new child to the tracer of the current thread if needed.
+///
+/// This should be called before the child starts to run.
+pub(super) fn ptrace_clone(ctx: &Context, child: &Arc<Thread>, clone_args: &CloneArgs) {
+    let ptrace = ctx.posix_thread.ptrace();
+    if !ptrace.is_traced() || clone_args.flags.contains(CloneFlags::CLONE_UNTRACED) {
+        return;
+    }
+
+    let (event, option) = if clone_args.flags.contains(CloneFlags::CLONE_VFORK) {
+        (PtraceEvent::Vfork, PtraceOptions::PTRACE_O_TRACEVFORK)
+    } else if clone_args.exit_signal != Some(SIGCHLD) {
+        (PtraceEvent::Clone, PtraceOptions::PTRACE_O_TRACECLONE)
+    } else {
+        (PtraceEvent::Fork, PtraceOptions::PTRACE_O_TRACEFORK)
+    };
+
+    let (tracer, options, is_seized) = {
+        let inner = ptrace.inner.lock();
+        let Some(tracer) = inner.tracer.upgrade() else {
+            return;
+        };
+        (tracer, inner.options, inner.is_seized)
+    };
+    if !options.contains(option) && !clone_args.flags.contains(CloneFlags::CLONE_PTRACE) {
+        return;
+    }
+
+    let child_posix_thread = child.as_posix_thread().unwrap();
+    if attach(&tracer, child, is_seized, options).is_err() {
+        return;
+    }
+
+    // Like Linux, the new tracee starts with a `SIGSTOP`, or a `PTRACE_EVENT_STOP` if the tracer
+    // attached with `PTRACE_SEIZE`.
+    if is_seized {
+        child_posix_thread
+            .ptrace()
+            .has_pending_interrupt
+            .store(true, Ordering::Relaxed);
+    } else {
+        child_posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+    }
+
+    if options.contains(option) {
+        let mut inner = ptrace.inner.lock();
+        inner.pending_event = Some(event);
+        inner.event_msg = child_posix_thread.tid() as usize;
+    }
+}
+
+/// Reports the successful `execve` to the tracer if the current thread is traced.
+pub fn ptrace_exec(ctx: &Context) {
+    let ptrace = ctx.posix_thread.ptrace();
+    if !ptrace.is_traced() {
+        return;
+    }
+
+    let mut inner = ptrace.inner.lock();
+    if inner.options.contains(PtraceOptions::PTRACE_O_TRACEEXEC) {
+        inner.pending_event = Some(PtraceEvent::Exec);
+        inner.event_msg = ctx.posix_thread.tid() as usize;
+    } else if !inner.is_seized {
+        // Like Linux, a legacy `SIGTRAP` is sent to the tracee after `execve` succeeds.
+        drop(inner);
+        ctx.posix_thread
+            .enqueue_signal(Box::new(KernelSignal::new(SIGTRAP)));
+    }
+}
+
+/// Records the exit of the current thread and notifies the tracer if the thread is traced.
+//
+// TODO: Support `PTRACE_O_TRACEEXIT`, which requires a ptrace-stop before the thread exits.
+pub(super) fn ptrace_exit(posix_thread: &PosixThread, exit_status: u32) {
+    let ptrace = posix_thread.ptrace();
+    if !ptrace.is_traced() {
+        return;
+    }
+
+    let tracer = {
+        let mut inner = ptrace.inner.lock();
+        inner.exit_status = Some(exit_status);
+        inner.stop = None;
+        inner.tracer.upgrade()
+    };
+    if let Some(tracer) = tracer {
+        notify_tracer(&tracer);
+    }
+}
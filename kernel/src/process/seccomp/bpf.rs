@@ -0,0 +1,401 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The classic BPF (cBPF) programs used by seccomp filters.
+//!
+//! A program is verified and decoded once when it is loaded, so that the
+//! interpreter runs the decoded instructions without checking them again.
+//! Like Linux, only the instructions that make sense for seccomp are
+//! accepted. In particular, the data can only be loaded by aligned 32-bit
+//! absolute loads, which use the native byte order instead of the network
+//! byte order.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/net/core/filter.c>
+
+use crate::prelude::*;
+
+/// The maximum number of instructions in a program.
+pub const BPF_MAXINSNS: usize = 4096;
+
+/// The number of words in the scratch memory.
+const BPF_MEMWORDS: usize = 16;
+
+// Instruction classes.
+const BPF_LD: u16 = 0x00;
+const BPF_LDX: u16 = 0x01;
+const BPF_ST: u16 = 0x02;
+const BPF_STX: u16 = 0x03;
+const BPF_ALU: u16 = 0x04;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+const BPF_MISC: u16 = 0x07;
+
+// Load sizes.
+const BPF_W: u16 = 0x00;
+
+// Load modes.
+const BPF_IMM: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_MEM: u16 = 0x60;
+const BPF_LEN: u16 = 0x80;
+
+// ALU operations.
+const BPF_ADD: u16 = 0x00;
+const BPF_SUB: u16 = 0x10;
+const BPF_MUL: u16 = 0x20;
+const BPF_DIV: u16 = 0x30;
+const BPF_OR: u16 = 0x40;
+const BPF_AND: u16 = 0x50;
+const BPF_LSH: u16 = 0x60;
+const BPF_RSH: u16 = 0x70;
+const BPF_NEG: u16 = 0x80;
+const BPF_MOD: u16 = 0x90;
+const BPF_XOR: u16 = 0xa0;
+
+// Jump operations.
+const BPF_JA: u16 = 0x00;
+const BPF_JEQ: u16 = 0x10;
+const BPF_JGT: u16 = 0x20;
+const BPF_JGE: u16 = 0x30;
+const BPF_JSET: u16 = 0x40;
+
+// Operand sources.
+const BPF_K: u16 = 0x00;
+const BPF_X: u16 = 0x08;
+const BPF_A: u16 = 0x10;
+
+// Miscellaneous operations.
+const BPF_TAX: u16 = 0x00;
+const BPF_TXA: u16 = 0x80;
+
+/// A raw BPF instruction (`struct sock_filter`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+pub struct SockFilter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+/// A raw BPF program (`struct sock_fprog`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+pub struct SockFprog {
+    pub len: u16,
+    _padding: [u8; 6],
+    pub filter: Vaddr,
+}
+
+/// A verified BPF program.
+#[derive(Debug)]
+pub(super) struct BpfProgram {
+    insns: Vec<Insn>,
+    data_len: usize,
+}
+
+/// A decoded BPF instruction.
+#[derive(Debug, Clone, Copy)]
+enum Insn {
+    /// `A = data[k..k + 4]`
+    LdAbs(usize),
+    /// `A = k`
+    LdImm(u32),
+    /// `X = k`
+    LdxImm(u32),
+    /// `A = M[k]`
+    LdMem(usize),
+    /// `X = M[k]`
+    LdxMem(usize),
+    /// `M[k] = A`
+    St(usize),
+    /// `M[k] = X`
+    Stx(usize),
+    /// `A = A <op> src`
+    Alu(AluOp, Src),
+    /// `A = -A`
+    Neg,
+    /// `pc += k`
+    Ja(usize),
+    /// `pc += (A <op> src) ? jt : jf`
+    Jmp {
+        op: JmpOp,
+        src: Src,
+        jt: usize,
+        jf: usize,
+    },
+    /// `return k`
+    RetK(u32),
+    /// `return A`
+    RetA,
+    /// `X = A`
+    Tax,
+    /// `A = X`
+    Txa,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AluOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Or,
+    And,
+    Xor,
+    Lsh,
+    Rsh,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JmpOp {
+    Jeq,
+    Jgt,
+    Jge,
+    Jset,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Src {
+    K(u32),
+    X,
+}
+
+impl Src {
+    fn new(code: u16, k: u32) -> Self {
+        if code & BPF_X != 0 {
+            Src::X
+        } else {
+            Src::K(k)
+        }
+    }
+
+    fn value(self, x: u32) -> u32 {
+        match self {
+            Src::K(k) => k,
+            Src::X => x,
+        }
+    }
+}
+
+impl BpfProgram {
+    /// Verifies and decodes a program, which examines `data_len` bytes of
+    /// data.
+    pub(super) fn new(filter: &[SockFilter], data_len: usize) -> Result<Self> {
+        if filter.is_empty() || filter.len() > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the program length is invalid");
+        }
+
+        let insns = filter
+            .iter()
+            .enumerate()
+            .map(|(pc, insn)| decode(insn, pc, filter.len(), data_len))
+            .collect::<Result<Vec<_>>>()?;
+
+        if !matches!(insns.last(), Some(Insn::RetK(_) | Insn::RetA)) {
+            return_errno_with_message!(Errno::EINVAL, "the program does not end with a return");
+        }
+        check_loads_and_stores(&insns)?;
+
+        Ok(Self { insns, data_len })
+    }
+
+    /// Returns the number of instructions in the program.
+    pub(super) fn len(&self) -> usize {
+        self.insns.len()
+    }
+
+    /// Runs the program on the data and returns its return value.
+    pub(super) fn run(&self, data: &[u8]) -> u32 {
+        debug_assert_eq!(data.len(), self.data_len);
+
+        let mut a: u32 = 0;
+        let mut x: u32 = 0;
+        let mut mem = [0u32; BPF_MEMWORDS];
+        let mut pc = 0;
+
+        // The verifier ensures that all jumps are forward and in range, and
+        // that the last instruction returns, so the loop always terminates.
+        loop {
+            let insn = self.insns[pc];
+            pc += 1;
+
+            match insn {
+                Insn::LdAbs(offset) => {
+                    a = u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap());
+                }
+                Insn::LdImm(k) => a = k,
+                Insn::LdxImm(k) => x = k,
+                Insn::LdMem(idx) => a = mem[idx],
+                Insn::LdxMem(idx) => x = mem[idx],
+                Insn::St(idx) => mem[idx] = a,
+                Insn::Stx(idx) => mem[idx] = x,
+                Insn::Alu(op, src) => {
+                    let value = src.value(x);
+                    a = match op {
+                        AluOp::Add => a.wrapping_add(value),
+                        AluOp::Sub => a.wrapping_sub(value),
+                        AluOp::Mul => a.wrapping_mul(value),
+                        // Like Linux, dividing by zero aborts the program,
+                        // which then returns zero.
+                        AluOp::Div => match a.checked_div(value) {
+                            Some(result) => result,
+                            None => return 0,
+                        },
+                        AluOp::Mod => match a.checked_rem(value) {
+                            Some(result) => result,
+                            None => return 0,
+                        },
+                        AluOp::Or => a | value,
+                        AluOp::And => a & value,
+                        AluOp::Xor => a ^ value,
+                        AluOp::Lsh => a.checked_shl(value).unwrap_or(0),
+                        AluOp::Rsh => a.checked_shr(value).unwrap_or(0),
+                    };
+                }
+                Insn::Neg => a = a.wrapping_neg(),
+                Insn::Ja(offset) => pc += offset,
+                Insn::Jmp { op, src, jt, jf } => {
+                    let value = src.value(x);
+                    let cond = match op {
+                        JmpOp::Jeq => a == value,
+                        JmpOp::Jgt => a > value,
+                        JmpOp::Jge => a >= value,
+                        JmpOp::Jset => a & value != 0,
+                    };
+                    pc += if cond { jt } else { jf };
+                }
+                Insn::RetK(k) => return k,
+                Insn::RetA => return a,
+                Insn::Tax => x = a,
+                Insn::Txa => a = x,
+            }
+        }
+    }
+}
+
+/// Decodes the instruction at `pc`, checking that it is valid.
+fn decode(insn: &SockFilter, pc: usize, len: usize, data_len: usize) -> Result<Insn> {
+    let SockFilter { code, jt, jf, k } = *insn;
+
+    let mem_idx = || -> Result<usize> {
+        if k as usize >= BPF_MEMWORDS {
+            return_errno_with_message!(Errno::EINVAL, "the memory index is out of range");
+        }
+        Ok(k as usize)
+    };
+    let jump_offset = |offset: usize| -> Result<usize> {
+        // The target is `pc + 1 + offset`, which must be in the program.
+        if offset >= len - pc - 1 {
+            return_errno_with_message!(Errno::EINVAL, "the jump target is out of range");
+        }
+        Ok(offset)
+    };
+
+    let insn = match code {
+        _ if code == BPF_LD | BPF_W | BPF_ABS => {
+            let offset = k as usize;
+            if offset % 4 != 0 || offset + 4 > data_len {
+                return_errno_with_message!(Errno::EINVAL, "the load offset is invalid");
+            }
+            Insn::LdAbs(offset)
+        }
+        // The length of the data is fixed, so loading it is loading an
+        // immediate value.
+        _ if code == BPF_LD | BPF_W | BPF_LEN => Insn::LdImm(data_len as u32),
+        _ if code == BPF_LDX | BPF_W | BPF_LEN => Insn::LdxImm(data_len as u32),
+        _ if code == BPF_LD | BPF_IMM => Insn::LdImm(k),
+        _ if code == BPF_LDX | BPF_IMM => Insn::LdxImm(k),
+        _ if code == BPF_LD | BPF_MEM => Insn::LdMem(mem_idx()?),
+        _ if code == BPF_LDX | BPF_MEM => Insn::LdxMem(mem_idx()?),
+        BPF_ST => Insn::St(mem_idx()?),
+        BPF_STX => Insn::Stx(mem_idx()?),
+        _ if code == BPF_ALU | BPF_NEG => Insn::Neg,
+        _ if code & 0x07 == BPF_ALU && code <= 0xff => {
+            let op = match code & 0xf0 {
+                BPF_ADD => AluOp::Add,
+                BPF_SUB => AluOp::Sub,
+                BPF_MUL => AluOp::Mul,
+                BPF_DIV => AluOp::Div,
+                BPF_MOD => AluOp::Mod,
+                BPF_OR => AluOp::Or,
+                BPF_AND => AluOp::And,
+                BPF_XOR => AluOp::Xor,
+                BPF_LSH => AluOp::Lsh,
+                BPF_RSH => AluOp::Rsh,
+                _ => return_errno_with_message!(Errno::EINVAL, "the ALU operation is invalid"),
+            };
+            let src = Src::new(code, k);
+            match (op, src) {
+                (AluOp::Div | AluOp::Mod, Src::K(0)) => {
+                    return_errno_with_message!(Errno::EINVAL, "the divisor is zero")
+                }
+                (AluOp::Lsh | AluOp::Rsh, Src::K(32..)) => {
+                    return_errno_with_message!(Errno::EINVAL, "the shift is too large")
+                }
+                _ => Insn::Alu(op, src),
+            }
+        }
+        _ if code == BPF_JMP | BPF_JA => Insn::Ja(jump_offset(k as usize)?),
+        _ if code & 0x07 == BPF_JMP && code <= 0xff => {
+            let op = match code & 0xf0 {
+                BPF_JEQ => JmpOp::Jeq,
+                BPF_JGT => JmpOp::Jgt,
+                BPF_JGE => JmpOp::Jge,
+                BPF_JSET => JmpOp::Jset,
+                _ => return_errno_with_message!(Errno::EINVAL, "the jump operation is invalid"),
+            };
+            Insn::Jmp {
+                op,
+                src: Src::new(code, k),
+                jt: jump_offset(jt as usize)?,
+                jf: jump_offset(jf as usize)?,
+            }
+        }
+        _ if code == BPF_RET | BPF_K => Insn::RetK(k),
+        _ if code == BPF_RET | BPF_A => Insn::RetA,
+        _ if code == BPF_MISC | BPF_TAX => Insn::Tax,
+        _ if code == BPF_MISC | BPF_TXA => Insn::Txa,
+        _ => return_errno_with_message!(Errno::EINVAL, "the instruction is invalid"),
+    };
+
+    Ok(insn)
+}
+
+/// Checks that the scratch memory is always stored before it is loaded.
+///
+/// All the jumps are forward, so a single pass suffices. `masks[pc]` tracks
+/// the memory words that are stored on all the jumps to `pc`.
+fn check_loads_and_stores(insns: &[Insn]) -> Result<()> {
+    let mut masks = vec![u16::MAX; insns.len()];
+    let mut valid: u16 = 0;
+
+    for (pc, insn) in insns.iter().enumerate() {
+        valid &= masks[pc];
+        match *insn {
+            Insn::St(idx) | Insn::Stx(idx) => valid |= 1 << idx,
+            Insn::LdMem(idx) | Insn::LdxMem(idx) => {
+                if valid & (1 << idx) == 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the memory is loaded before it is stored"
+                    );
+                }
+            }
+            Insn::Ja(offset) => {
+                masks[pc + 1 + offset] &= valid;
+                valid = u16::MAX;
+            }
+            Insn::Jmp { jt, jf, .. } => {
+                masks[pc + 1 + jt] &= valid;
+                masks[pc + 1 + jf] &= valid;
+                valid = u16::MAX;
+            }
+            // The next instruction can only be reached by jumps.
+            Insn::RetK(_) | Insn::RetA => valid = u16::MAX,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Secure computing (seccomp), which restricts the system calls of threads.
+//!
+//! A thread in the strict mode can only make a few system calls, such as
+//! `read` and `write`. A thread in the filter mode has a stack of BPF
+//! filters, which examine every system call (see [`SeccompData`]) and decide
+//! what to do with it (see [`SeccompAction`]).
+//!
+//! The seccomp state of a thread is inherited by the threads that it
+//! creates, and is preserved across `execve`. Filters can only be added,
+//! never removed, so the filter stack of a thread is shared with its parent
+//! as a linked list.
+//!
+//! Reference: <https://docs.kernel.org/userspace-api/seccomp_filter.html>
+
+use core::{
+    mem::size_of,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use self::bpf::BpfProgram;
+pub use self::bpf::{SockFilter, SockFprog, BPF_MAXINSNS};
+use crate::prelude::*;
+
+mod bpf;
+
+/// The maximum number of instructions on the path of a system call, which
+/// runs all the filters in the stack.
+///
+/// Like Linux, each filter is counted as 4 instructions more than its length
+/// to penalize small filters.
+const MAX_INSNS_PER_PATH: usize = 32768;
+
+/// The seccomp mode of a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+#[repr(u8)]
+pub enum SeccompMode {
+    Disabled = 0,
+    Strict = 1,
+    Filter = 2,
+}
+
+/// The data that seccomp filters examine (`struct seccomp_data`).
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+pub struct SeccompData {
+    /// The system call number.
+    pub nr: i32,
+    /// The architecture (`AUDIT_ARCH_*`) of the system call convention.
+    pub arch: u32,
+    /// The address of the system call instruction.
+    pub instruction_pointer: u64,
+    /// The arguments of the system call.
+    pub args: [u64; 6],
+}
+
+/// The action to take on a system call, which is decided by seccomp filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeccompAction {
+    /// Kills the process with `SIGSYS`.
+    KillProcess,
+    /// Kills the thread with `SIGSYS`.
+    KillThread,
+    /// Sends `SIGSYS` to the thread without executing the system call. The
+    /// value is reported in `si_errno`.
+    Trap(u16),
+    /// Returns the error number without executing the system call.
+    Errno(u16),
+    /// Notifies a user-space supervisor.
+    UserNotif,
+    /// Notifies a ptrace tracer. The value is reported as the event message.
+    Trace(u16),
+    /// Logs and executes the system call.
+    Log,
+    /// Executes the system call.
+    Allow,
+}
+
+impl SeccompAction {
+    pub const RET_KILL_PROCESS: u32 = 0x8000_0000;
+    pub const RET_KILL_THREAD: u32 = 0x0000_0000;
+    pub const RET_TRAP: u32 = 0x0003_0000;
+    pub const RET_ERRNO: u32 = 0x0005_0000;
+    pub const RET_USER_NOTIF: u32 = 0x7fc0_0000;
+    pub const RET_TRACE: u32 = 0x7ff0_0000;
+    pub const RET_LOG: u32 = 0x7ffc_0000;
+    pub const RET_ALLOW: u32 = 0x7fff_0000;
+
+    const RET_ACTION_FULL: u32 = 0xffff_0000;
+    const RET_DATA: u32 = 0x0000_ffff;
+
+    /// Decodes the return value of a filter.
+    ///
+    /// Like Linux, an unknown action is treated as `KillProcess`.
+    pub fn from_ret(ret: u32) -> Self {
+        let data = (ret & Self::RET_DATA) as u16;
+        match ret & Self::RET_ACTION_FULL {
+            Self::RET_KILL_PROCESS => Self::KillProcess,
+            Self::RET_KILL_THREAD => Self::KillThread,
+            Self::RET_TRAP => Self::Trap(data),
+            Self::RET_ERRNO => Self::Errno(data),
+            Self::RET_USER_NOTIF => Self::UserNotif,
+            Self::RET_TRACE => Self::Trace(data),
+            Self::RET_LOG => Self::Log,
+            Self::RET_ALLOW => Self::Allow,
+            _ => Self::KillProcess,
+        }
+    }
+
+    /// Returns the precedence of the return value of a filter, where a lower
+    /// value takes precedence over a higher value.
+    fn precedence(ret: u32) -> i32 {
+        (ret & Self::RET_ACTION_FULL) as i32
+    }
+}
+
+/// A seccomp filter in the filter stack of threads.
+#[derive(Debug)]
+pub struct SeccompFilter {
+    program: BpfProgram,
+    /// Whether the actions other than `Allow` should be logged.
+    should_log: bool,
+    /// The previous filter in the stack.
+    prev: Option<Arc<SeccompFilter>>,
+}
+
+impl SeccompFilter {
+    /// Verifies the BPF program and creates a filter on top of the stack
+    /// whose top is `prev`.
+    pub fn new(
+        filter: &[SockFilter],
+        should_log: bool,
+        prev: Option<Arc<SeccompFilter>>,
+    ) -> Result<Arc<Self>> {
+        let program = BpfProgram::new(filter, size_of::<SeccompData>())?;
+
+        let path_len = core::iter::successors(prev.as_deref(), |filter| filter.prev.as_deref())
+            .map(|filter| filter.program.len() + 4)
+            .sum::<usize>()
+            + program.len()
+            + 4;
+        if path_len > MAX_INSNS_PER_PATH {
+            return_errno_with_message!(Errno::ENOMEM, "the filter stack is too large");
+        }
+
+        Ok(Arc::new(Self {
+            program,
+            should_log,
+            prev,
+        }))
+    }
+
+    /// Runs all the filters in the stack, and returns the action that takes
+    /// precedence and whether it should be logged.
+    pub fn run(&self, data: &SeccompData) -> (SeccompAction, bool) {
+        let bytes = data.as_bytes();
+
+        let mut ret = SeccompAction::RET_ALLOW;
+        let mut should_log = false;
+        for filter in self.iter() {
+            let filter_ret = filter.program.run(bytes);
+            if SeccompAction::precedence(filter_ret) < SeccompAction::precedence(ret) {
+                ret = filter_ret;
+                should_log = filter.should_log;
+            }
+        }
+
+        let action = SeccompAction::from_ret(ret);
+        let should_log = match action {
+            SeccompAction::KillProcess | SeccompAction::KillThread | SeccompAction::Log => true,
+            SeccompAction::Allow => false,
+            _ => should_log,
+        };
+        (action, should_log)
+    }
+
+    /// Returns whether `self` is in the stack whose top is `filter`.
+    pub fn is_ancestor_of(self: &Arc<Self>, filter: &Arc<SeccompFilter>) -> bool {
+        filter
+            .iter()
+            .any(|filter| core::ptr::eq(filter, Arc::as_ptr(self)))
+    }
+
+    /// Iterates over the filters in the stack, from the top to the bottom.
+    fn iter(&self) -> impl Iterator<Item = &SeccompFilter> {
+        core::iter::successors(Some(self), |filter| filter.prev.as_deref())
+    }
+}
+
+/// The seccomp state of a thread.
+pub struct SeccompState {
+    mode: AtomicU8,
+    /// The top of the filter stack, which is `Some` in the filter mode.
+    filter: SpinLock<Option<Arc<SeccompFilter>>>,
+}
+
+impl SeccompState {
+    /// Creates a state where seccomp is disabled.
+    pub fn new() -> Self {
+        Self {
+            mode: AtomicU8::new(SeccompMode::Disabled as u8),
+            filter: SpinLock::new(None),
+        }
+    }
+
+    /// Creates a copy of the state for a new thread.
+    pub fn inherit(&self) -> Self {
+        Self {
+            mode: AtomicU8::new(self.mode.load(Ordering::Acquire)),
+            filter: SpinLock::new(self.filter.lock().clone()),
+        }
+    }
+
+    /// Returns the seccomp mode.
+    pub fn mode(&self) -> SeccompMode {
+        SeccompMode::try_from(self.mode.load(Ordering::Acquire)).unwrap()
+    }
+
+    /// Returns the top of the filter stack.
+    pub fn filter(&self) -> Option<Arc<SeccompFilter>> {
+        self.filter.lock().clone()
+    }
+
+    /// Enters the strict mode.
+    ///
+    /// This method fails with `EINVAL` if the thread is in the filter mode.
+    pub fn set_strict(&self) -> Result<()> {
+        if self.mode() == SeccompMode::Filter {
+            return_errno_with_message!(Errno::EINVAL, "the thread is in the filter mode");
+        }
+        self.mode
+            .store(SeccompMode::Strict as u8, Ordering::Release);
+        Ok(())
+    }
+
+    /// Enters the filter mode with `filter` as the top of the filter stack.
+    ///
+    /// The callers should ensure that `filter` is built on top of the current
+    /// filter stack. This method fails with `EINVAL` if the thread is in the
+    /// strict mode.
+    pub fn set_filter(&self, filter: Arc<SeccompFilter>) -> Result<()> {
+        if self.mode() == SeccompMode::Strict {
+            return_errno_with_message!(Errno::EINVAL, "the thread is in the strict mode");
+        }
+        *self.filter.lock() = Some(filter);
+        self.mode
+            .store(SeccompMode::Filter as u8, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl Default for SeccompState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
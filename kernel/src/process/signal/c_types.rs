@@ -57,6 +57,22 @@ impl siginfo_t {
     pub fn si_addr(&self) -> Vaddr {
         read_union_field!(self, Self, siginfo_fields.sigfault.addr)
     }
+
+    /// Sets the fields of a `SIGCHLD` signal, which are also used by `waitid`.
+    pub fn set_sigchld_fields(&mut self, pid: Pid, uid: Uid, status: i32) {
+        self.siginfo_fields.common.first.piduid = siginfo_piduid_t { pid, uid };
+        self.siginfo_fields.common.second.sigchild.status = status;
+    }
+
+    /// Sets the fields of a `SIGSYS` signal, which describe the system call
+    /// that causes it.
+    pub fn set_sigsys_fields(&mut self, call_addr: Vaddr, syscall: i32, arch: u32) {
+        self.siginfo_fields.sigsys = siginfo_sigsys_t {
+            call_addr,
+            syscall,
+            arch,
+        };
+    }
 }
 
 #[derive(Clone, Copy, Pod)]
@@ -65,6 +81,7 @@ union siginfo_fields_t {
     bytes: [u8; 128 - mem::size_of::<i32>() * 4],
     common: siginfo_common_t,
     sigfault: siginfo_sigfault_t,
+    sigsys: siginfo_sigsys_t,
 }
 
 impl siginfo_fields_t {
@@ -77,7 +94,7 @@ impl siginfo_fields_t {
 
 #[derive(Clone, Copy, Pod)]
 #[repr(C)]
-union siginfo_common_t {
+struct siginfo_common_t {
     first: siginfo_common_first_t,
     second: siginfo_common_second_t,
 }
@@ -129,8 +146,9 @@ impl sigval_t {
 
 #[derive(Clone, Copy, Pod)]
 #[repr(C)]
-union siginfo_sigchild_t {
+struct siginfo_sigchild_t {
     status: i32,
+    _padding: i32,
     utime: clock_t,
     stime: clock_t,
 }
@@ -143,6 +161,14 @@ struct siginfo_sigfault_t {
     first: siginfo_sigfault_first_t,
 }
 
+#[derive(Clone, Copy, Pod)]
+#[repr(C)]
+struct siginfo_sigsys_t {
+    call_addr: Vaddr, //*const c_void
+    syscall: i32,
+    arch: u32,
+}
+
 #[derive(Clone, Copy, Pod)]
 #[repr(C)]
 union siginfo_sigfault_first_t {
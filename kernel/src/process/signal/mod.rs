@@ -17,7 +17,7 @@ use core::{mem, sync::atomic::Ordering};
 
 use align_ext::AlignExt;
 use c_types::{siginfo_t, ucontext_t};
-use constants::SIGSEGV;
+use constants::{SIGCHLD, SIGSEGV};
 pub use events::{SigEvents, SigEventsFilter};
 use ostd::{cpu::context::UserContext, user::UserContextApi};
 pub use pause::{with_sigmask_changed, Pause};
@@ -26,13 +26,19 @@ use sig_action::{SigAction, SigActionFlags, SigDefaultAction};
 use sig_mask::SigMask;
 use sig_num::SigNum;
 pub use sig_stack::{SigStack, SigStackFlags};
+use signals::kernel::KernelSignal;
 
 use super::posix_thread::ThreadLocal;
 use crate::{
     cpu::LinuxAbi,
     current_userspace,
     prelude::*,
-    process::{posix_thread::do_exit_group, TermStatus},
+    process::{
+        coredump::do_coredump,
+        posix_thread::do_exit_group,
+        ptrace::{ptrace_group_stop, ptrace_signal_stop},
+        Process, TermStatus,
+    },
 };
 
 pub trait SignalContext {
@@ -69,6 +75,10 @@ pub fn handle_pending_signal(
             return;
         }
     };
+    // The tracer may replace or suppress the signal.
+    let Some(signal) = ptrace_signal_stop(ctx, user_ctx, signal) else {
+        return;
+    };
     let sig_num = signal.num();
     trace!("sig_num = {:?}, sig_name = {}", sig_num, sig_num.sig_name());
 
@@ -136,21 +146,50 @@ pub fn handle_pending_signal(
                         current.executable_path(),
                         sig_num.sig_name()
                     );
+                    let term_status = if sig_default_action == SigDefaultAction::Core
+                        && do_coredump(ctx, user_ctx, &signal.to_info())
+                    {
+                        TermStatus::CoreDumped(sig_num)
+                    } else {
+                        TermStatus::Killed(sig_num)
+                    };
                     // We should exit current here, since we cannot restore a valid status from trap now.
-                    do_exit_group(TermStatus::Killed(sig_num));
+                    do_exit_group(term_status);
                 }
                 SigDefaultAction::Ign => {}
                 SigDefaultAction::Stop => {
-                    let _ = ctx.thread.stop();
+                    if !ptrace_group_stop(ctx, user_ctx, sig_num) && ctx.thread.stop().is_ok() {
+                        current.status().set_stopped(sig_num);
+                        notify_parent_job_control(current);
+                    }
                 }
                 SigDefaultAction::Cont => {
-                    let _ = ctx.thread.resume();
+                    if ctx.thread.resume().is_some() {
+                        current.status().set_continued();
+                        notify_parent_job_control(current);
+                    }
                 }
             }
         }
     }
 }
 
+/// Notifies the parent that the current process is stopped or continued.
+fn notify_parent_job_control(current: &Process) {
+    let Some(parent) = current.parent().lock().process().upgrade() else {
+        return;
+    };
+
+    let is_sigchld_suppressed = matches!(
+        parent.sig_dispositions().lock().get(SIGCHLD),
+        SigAction::User { flags, .. } if flags.contains(SigActionFlags::SA_NOCLDSTOP)
+    );
+    if !is_sigchld_suppressed {
+        parent.enqueue_signal(KernelSignal::new(SIGCHLD));
+    }
+    parent.children_wait_queue().wake_all();
+}
+
 #[expect(clippy::too_many_arguments)]
 pub fn handle_user_signal(
     ctx: &Context,
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::Signal;
+use crate::{
+    prelude::*,
+    process::signal::{
+        c_types::siginfo_t,
+        constants::{SIGSYS, SYS_SECCOMP},
+        sig_num::SigNum,
+    },
+};
+
+/// A `SIGSYS` signal that is sent because a seccomp filter traps a system
+/// call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeccompSignal {
+    call_addr: Vaddr,
+    syscall: i32,
+    arch: u32,
+    data: u16,
+}
+
+impl SeccompSignal {
+    pub fn new(call_addr: Vaddr, syscall: i32, arch: u32, data: u16) -> Self {
+        Self {
+            call_addr,
+            syscall,
+            arch,
+            data,
+        }
+    }
+}
+
+impl Signal for SeccompSignal {
+    fn num(&self) -> SigNum {
+        SIGSYS
+    }
+
+    fn to_info(&self) -> siginfo_t {
+        let mut info = siginfo_t::new(SIGSYS, SYS_SECCOMP);
+        // Like Linux, the data returned by the filter is reported in `si_errno`.
+        info.si_errno = self.data as i32;
+        info.set_sigsys_fields(self.call_addr, self.syscall, self.arch);
+        info
+    }
+}
@@ -4,7 +4,7 @@
 
 use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
-use super::ExitCode;
+use super::{signal::sig_num::SigNum, ExitCode};
 
 /// The status of a process.
 ///
@@ -12,12 +12,15 @@ use super::ExitCode;
 /// 1. Whether the process is a zombie (i.e., all its threads have exited);
 /// 2. Whether the process is the vfork child, which shares the user-space virtual memory
 ///    with its parent process;
-/// 3. The exit code of the process.
+/// 3. The exit code of the process;
+/// 4. The job-control status change (i.e., being stopped or continued) that has not been
+///    reported to the parent yet.
 #[derive(Debug)]
 pub struct ProcessStatus {
     is_zombie: AtomicBool,
     is_vfork_child: AtomicBool,
     exit_code: AtomicU32,
+    job_control_status: AtomicU32,
 }
 
 impl Default for ProcessStatus {
@@ -26,6 +29,7 @@ impl Default for ProcessStatus {
             is_zombie: AtomicBool::new(false),
             is_vfork_child: AtomicBool::new(false),
             exit_code: AtomicU32::new(0),
+            job_control_status: AtomicU32::new(0),
         }
     }
 }
@@ -71,3 +75,45 @@ impl ProcessStatus {
         self.exit_code.store(exit_code, Ordering::Relaxed);
     }
 }
+
+/// A job-control status change of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobControlStatus {
+    /// The process is stopped by the signal.
+    Stopped(SigNum),
+    /// The process is continued by `SIGCONT`.
+    Continued,
+}
+
+const JOB_CONTROL_CONTINUED: u32 = u32::MAX;
+
+impl ProcessStatus {
+    /// Records that the process is stopped by the signal.
+    pub(super) fn set_stopped(&self, sig_num: SigNum) {
+        self.job_control_status
+            .store(sig_num.as_u8() as u32, Ordering::Relaxed);
+    }
+
+    /// Records that the process is continued.
+    pub(super) fn set_continued(&self) {
+        self.job_control_status
+            .store(JOB_CONTROL_CONTINUED, Ordering::Relaxed);
+    }
+
+    /// Returns the job-control status change that has not been reported.
+    ///
+    /// If `should_clear` is true, the status change will not be returned again.
+    pub(super) fn job_control_status(&self, should_clear: bool) -> Option<JobControlStatus> {
+        let status = if should_clear {
+            self.job_control_status.swap(0, Ordering::Relaxed)
+        } else {
+            self.job_control_status.load(Ordering::Relaxed)
+        };
+
+        match status {
+            0 => None,
+            JOB_CONTROL_CONTINUED => Some(JobControlStatus::Continued),
+            sig_num => Some(JobControlStatus::Stopped(SigNum::from_u8(sig_num as u8))),
+        }
+    }
+}
@@ -3,27 +3,37 @@
 #![expect(dead_code)]
 
 use super::{
+    namespace::PidNamespace,
+    posix_thread::AsPosixThread,
     process_filter::ProcessFilter,
-    signal::{constants::SIGCHLD, with_sigmask_changed},
+    ptrace::TraceeStatus,
+    signal::{
+        c_types::siginfo_t,
+        constants::{
+            CLD_CONTINUED, CLD_DUMPED, CLD_EXITED, CLD_KILLED, CLD_STOPPED, CLD_TRAPPED, SIGCHLD,
+            SIGCONT,
+        },
+        sig_num::SigNum,
+        with_sigmask_changed,
+    },
+    status::JobControlStatus,
     ExitCode, Pid, Process,
 };
 use crate::{
     prelude::*,
-    process::{
-        posix_thread::{thread_table, AsPosixThread},
-        process_table,
-    },
+    process::{posix_thread::thread_table, process_table},
+    thread::Thread,
 };
 
 // The definition of WaitOptions is from Occlum
 bitflags! {
     pub struct WaitOptions: u32 {
         const WNOHANG = 0x1;
-        //Note: Below flags are not supported yet
         const WSTOPPED = 0x2; // Same as WUNTRACED
         const WEXITED = 0x4;
         const WCONTINUED = 0x8;
         const WNOWAIT = 0x01000000;
+        //Note: Below flags are not supported yet
         const WNOTHREAD = 0x20000000;
         const WALL = 0x40000000;
         const WCLONE = 0x80000000;
@@ -32,18 +42,116 @@ bitflags! {
 
 impl WaitOptions {
     pub fn supported(&self) -> bool {
-        let unsupported_flags = WaitOptions::all() - WaitOptions::WNOHANG;
+        let unsupported_flags = WaitOptions::WNOTHREAD | WaitOptions::WALL | WaitOptions::WCLONE;
         !self.intersects(unsupported_flags)
     }
 }
 
-pub fn wait_child_exit(
+/// The status change of a child process or a tracee, which is reported by `wait4` or `waitid`.
+pub enum WaitStatus {
+    /// The child process has exited and been a zombie.
+    Exited(Arc<Process>),
+    /// The child process has been stopped by the signal.
+    Stopped(Arc<Process>, SigNum),
+    /// The child process has been continued by `SIGCONT`.
+    Continued(Arc<Process>),
+    /// The tracee is in a ptrace-stop with the code.
+    PtraceStopped(Arc<Thread>, u32),
+    /// The tracee has exited with the wait status.
+    PtraceExited(Arc<Thread>, u32),
+}
+
+impl WaitStatus {
+    /// Returns the ID of the child process or the tracee.
+    pub fn pid(&self) -> Pid {
+        match self {
+            Self::Exited(process) | Self::Stopped(process, _) | Self::Continued(process) => {
+                process.pid()
+            }
+            Self::PtraceStopped(thread, _) | Self::PtraceExited(thread, _) => {
+                thread.as_posix_thread().unwrap().tid()
+            }
+        }
+    }
+
+    /// Returns the ID of the child process or the tracee in the PID namespace.
+    ///
+    /// Returns zero if the child process or the tracee is invisible in the PID namespace.
+    pub fn pid_in_ns(&self, pid_ns: &PidNamespace) -> Pid {
+        pid_ns.tid_in_ns(self.pid()).unwrap_or(0)
+    }
+
+    /// Returns the process of the child process or the tracee.
+    pub fn process(&self) -> Option<Arc<Process>> {
+        match self {
+            Self::Exited(process) | Self::Stopped(process, _) | Self::Continued(process) => {
+                Some(process.clone())
+            }
+            Self::PtraceStopped(thread, _) | Self::PtraceExited(thread, _) => {
+                thread.as_posix_thread().unwrap().weak_process().upgrade()
+            }
+        }
+    }
+
+    /// Returns the status in the format of `wait4`.
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            Self::Exited(process) => process.status().exit_code(),
+            Self::Stopped(_, sig_num) => ((sig_num.as_u8() as u32) << 8) | 0x7f,
+            Self::Continued(_) => 0xffff,
+            Self::PtraceStopped(_, code) => (code << 8) | 0x7f,
+            Self::PtraceExited(_, status) => *status,
+        }
+    }
+
+    /// Returns the signal information in the format of `waitid`.
+    ///
+    /// The ID of the child process or the tracee is in the PID namespace.
+    pub fn to_siginfo(&self, pid_ns: &PidNamespace) -> siginfo_t {
+        let (code, status) = match self {
+            Self::Exited(_) | Self::PtraceExited(..) => {
+                let status = self.as_u32();
+                if status & 0x7f == 0 {
+                    (CLD_EXITED, (status >> 8) & 0xff)
+                } else if status & 0x80 != 0 {
+                    (CLD_DUMPED, status & 0x7f)
+                } else {
+                    (CLD_KILLED, status & 0x7f)
+                }
+            }
+            Self::Stopped(_, sig_num) => (CLD_STOPPED, sig_num.as_u8() as u32),
+            Self::Continued(_) => (CLD_CONTINUED, SIGCONT.as_u8() as u32),
+            Self::PtraceStopped(_, code) => (CLD_TRAPPED, *code),
+        };
+
+        let uid = match self {
+            Self::Exited(process) | Self::Stopped(process, _) | Self::Continued(process) => process
+                .main_thread()
+                .as_posix_thread()
+                .unwrap()
+                .credentials()
+                .ruid(),
+            Self::PtraceStopped(thread, _) | Self::PtraceExited(thread, _) => {
+                thread.as_posix_thread().unwrap().credentials().ruid()
+            }
+        };
+
+        let mut siginfo = siginfo_t::new(SIGCHLD, code);
+        siginfo.set_sigchld_fields(self.pid_in_ns(pid_ns), uid, status as i32);
+        siginfo
+    }
+}
+
+/// Waits for a child process or a tracee to change its status.
+///
+/// If `WNOHANG` is specified and no status change is available, this method returns `None`.
+pub fn do_wait(
     child_filter: ProcessFilter,
     wait_options: WaitOptions,
     ctx: &Context,
-) -> Result<Option<Arc<Process>>> {
+) -> Result<Option<WaitStatus>> {
     let current = ctx.process;
-    let zombie_child = with_sigmask_changed(
+    let wait_status = with_sigmask_changed(
         ctx,
         |sigmask| sigmask + SIGCHLD,
         || {
@@ -60,29 +168,23 @@ pub fn wait_child_exit(
                     .cloned()
                     .collect::<Vec<_>>();
 
-                if unwaited_children.is_empty() {
+                if let Some(wait_status) = poll_children(current, &unwaited_children, wait_options)
+                {
+                    return Some(Ok(Some(wait_status)));
+                }
+
+                let (has_tracees, wait_status) = poll_tracees(current, child_filter, wait_options);
+                if let Some(wait_status) = wait_status {
+                    return Some(Ok(Some(wait_status)));
+                }
+
+                if unwaited_children.is_empty() && !has_tracees {
                     return Some(Err(Error::with_message(
                         Errno::ECHILD,
                         "the process has no child to wait",
                     )));
                 }
 
-                // return immediately if we find a zombie child
-                let zombie_child = unwaited_children
-                    .iter()
-                    .find(|child| child.status().is_zombie());
-
-                if let Some(zombie_child) = zombie_child {
-                    let zombie_pid = zombie_child.pid();
-                    if wait_options.contains(WaitOptions::WNOWAIT) {
-                        // does not reap child, directly return
-                        return Some(Ok(Some(zombie_child.clone())));
-                    } else {
-                        reap_zombie_child(current, zombie_pid);
-                        return Some(Ok(Some(zombie_child.clone())));
-                    }
-                }
-
                 if wait_options.contains(WaitOptions::WNOHANG) {
                     return Some(Ok(None));
                 }
@@ -93,7 +195,113 @@ pub fn wait_child_exit(
         },
     )??;
 
-    Ok(zombie_child)
+    Ok(wait_status)
+}
+
+/// Polls the status changes of the children.
+fn poll_children(
+    current: &Process,
+    children: &[Arc<Process>],
+    wait_options: WaitOptions,
+) -> Option<WaitStatus> {
+    let should_consume = !wait_options.contains(WaitOptions::WNOWAIT);
+
+    for child in children {
+        // return immediately if we find a zombie child
+        if child.status().is_zombie() {
+            if !wait_options.contains(WaitOptions::WEXITED) {
+                continue;
+            }
+            if should_consume {
+                reap_zombie_child(current, child.pid());
+            }
+            return Some(WaitStatus::Exited(child.clone()));
+        }
+
+        let wait_status = match child.status().job_control_status(false) {
+            Some(JobControlStatus::Stopped(sig_num))
+                if wait_options.contains(WaitOptions::WSTOPPED) =>
+            {
+                WaitStatus::Stopped(child.clone(), sig_num)
+            }
+            Some(JobControlStatus::Continued) if wait_options.contains(WaitOptions::WCONTINUED) => {
+                WaitStatus::Continued(child.clone())
+            }
+            _ => continue,
+        };
+        child.status().job_control_status(should_consume);
+        return Some(wait_status);
+    }
+
+    None
+}
+
+/// Polls the status changes of the tracees.
+///
+/// This method returns whether there are tracees that match the filter, and the status change
+/// of one of them, if any.
+fn poll_tracees(
+    current: &Process,
+    child_filter: ProcessFilter,
+    wait_options: WaitOptions,
+) -> (bool, Option<WaitStatus>) {
+    let should_consume = !wait_options.contains(WaitOptions::WNOWAIT);
+
+    let mut tracees = current.tracees().lock();
+    let mut has_tracees = false;
+    let mut wait_status = None;
+    let mut exited_tids = Vec::new();
+
+    for (tid, tracee) in tracees.iter() {
+        let posix_thread = tracee.as_posix_thread().unwrap();
+        let Some(process) = posix_thread.weak_process().upgrade() else {
+            exited_tids.push(*tid);
+            continue;
+        };
+        let is_matched = match child_filter {
+            ProcessFilter::Any => true,
+            ProcessFilter::WithPid(pid) => *tid == pid,
+            ProcessFilter::WithPgid(pgid) => process.pgid() == pgid,
+        };
+        if !is_matched {
+            continue;
+        }
+
+        let is_main_thread = *tid == process.pid();
+        match posix_thread.ptrace().poll_status(should_consume) {
+            Some(TraceeStatus::Stopped(code)) => {
+                wait_status = Some(WaitStatus::PtraceStopped(tracee.clone(), code));
+                break;
+            }
+            // The exit of a child process is reported by the child process itself.
+            Some(TraceeStatus::Exited(_))
+                if is_main_thread && current.has_child(&process.pid()) =>
+            {
+                exited_tids.push(*tid);
+            }
+            // Like Linux, the exit of the main thread is reported after all threads have exited.
+            Some(TraceeStatus::Exited(_))
+                if (is_main_thread && !process.status().is_zombie())
+                    || !wait_options.contains(WaitOptions::WEXITED) =>
+            {
+                has_tracees = true;
+            }
+            Some(TraceeStatus::Exited(status)) => {
+                wait_status = Some(WaitStatus::PtraceExited(tracee.clone(), status));
+                if should_consume {
+                    exited_tids.push(*tid);
+                }
+                break;
+            }
+            None => has_tracees = true,
+        }
+    }
+
+    for tid in exited_tids {
+        tracees.remove(&tid);
+    }
+
+    (has_tracees, wait_status)
 }
 
 /// Free zombie child with pid, returns the exit code of child process.
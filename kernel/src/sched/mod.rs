@@ -6,6 +6,6 @@ mod stats;
 
 pub use self::{
     nice::{AtomicNice, Nice},
-    sched_class::{init, RealTimePolicy, RealTimePriority, SchedAttr, SchedPolicy},
+    sched_class::{init, FairGroup, RealTimePolicy, RealTimePriority, SchedAttr, SchedPolicy},
     stats::{loadavg, nr_queued_and_running},
 };
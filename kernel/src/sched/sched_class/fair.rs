@@ -1,13 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{collections::BinaryHeap, sync::Arc};
+use alloc::{collections::BinaryHeap, sync::Arc, vec::Vec};
 use core::{
     cmp::{self, Reverse},
+    mem,
     sync::atomic::{AtomicU64, Ordering::Relaxed},
 };
 
 use ostd::{
+    arch::read_tsc as sched_clock,
     cpu::{num_cpus, CpuId},
+    sync::{LocalIrqDisabled, RcuOption, SpinLock},
     task::{
         scheduler::{EnqueueFlags, UpdateFlags},
         Task,
@@ -15,7 +18,7 @@ use ostd::{
 };
 
 use super::{
-    time::{base_slice_clocks, min_period_clocks},
+    time::{base_slice_clocks, min_period_clocks, us_to_clocks},
     CurrentRuntime, SchedAttr, SchedClassRq,
 };
 use crate::{
@@ -93,10 +96,24 @@ pub const fn nice_to_weight(nice: Nice) -> u64 {
 ///
 ///     period_delta > time_slice
 ///         || vruntime > rq_min_vruntime + normalized_time_slice
-#[derive(Debug)]
+///
+/// # Groups
+///
+/// A thread may belong to a [`FairGroup`], whose weight scales the weight of
+/// the thread, and whose bandwidth limit throttles the thread.
 pub struct FairAttr {
     weight: AtomicU64,
     vruntime: AtomicU64,
+    group: RcuOption<Arc<FairGroup>>,
+}
+
+impl core::fmt::Debug for FairAttr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FairAttr")
+            .field("weight", &self.weight)
+            .field("vruntime", &self.vruntime)
+            .finish_non_exhaustive()
+    }
 }
 
 impl FairAttr {
@@ -104,6 +121,7 @@ impl FairAttr {
         FairAttr {
             weight: nice_to_weight(nice).into(),
             vruntime: Default::default(),
+            group: RcuOption::new_none(),
         }
     }
 
@@ -111,19 +129,203 @@ impl FairAttr {
         self.weight.store(nice_to_weight(nice), Relaxed);
     }
 
-    fn update_vruntime(&self, delta: u64) -> (u64, u64) {
+    pub fn set_group(&self, group: Option<Arc<FairGroup>>) {
+        self.group.update(group);
+    }
+
+    /// Returns the weight of the thread, scaled by the weights of its groups.
+    fn weight(&self) -> u64 {
         let weight = self.weight.load(Relaxed);
+        match self.group.read().get() {
+            Some(group) => group.scale_weight(weight),
+            None => weight,
+        }
+    }
+
+    fn update_vruntime(&self, delta: u64) -> (u64, u64) {
+        let weight = self.weight();
         let delta = delta * WEIGHT_0 / weight;
         let vruntime = self.vruntime.fetch_add(delta, Relaxed) + delta;
         (vruntime, weight)
     }
+
+    /// Charges the runtime to the groups of the thread.
+    ///
+    /// Returns whether the thread is throttled afterwards.
+    fn charge_groups(&self, delta: u64) -> bool {
+        self.group
+            .read()
+            .get()
+            .is_some_and(|group| group.charge(delta, sched_clock()))
+    }
+
+    fn is_throttled(&self) -> bool {
+        self.group
+            .read()
+            .get()
+            .is_some_and(|group| group.is_throttled(sched_clock()))
+    }
+}
+
+/// A group of threads in the FAIR scheduling class, i.e., the state of the
+/// CPU controller of a cgroup.
+///
+/// The weight of a group is relative to [`FairGroup::DEFAULT_WEIGHT`]. Since
+/// the run queue does not schedule groups as entities, the weight of a group
+/// is applied by scaling the weight of each thread in the group (and in its
+/// descendants). This approximates the group scheduling of Linux without
+/// hierarchical run queues: the CPU share of a group grows with its weight,
+/// but also with the number of its runnable threads.
+///
+/// The bandwidth limit of a group restricts the total runtime of the threads
+/// in the group (and in its descendants) in each period. Once the quota is
+/// exhausted, the threads are throttled until the next period starts.
+pub struct FairGroup {
+    parent: Option<Arc<FairGroup>>,
+    weight: AtomicU64,
+    bandwidth: SpinLock<Bandwidth, LocalIrqDisabled>,
+}
+
+/// The CPU bandwidth limit of a [`FairGroup`].
+struct Bandwidth {
+    /// The maximum runtime in each period in microseconds, or `None` if the
+    /// runtime is unlimited.
+    quota_us: Option<u64>,
+    period_us: u64,
+    /// The quota in TSC clock units.
+    quota: Option<u64>,
+    /// The period in TSC clock units.
+    period: u64,
+    /// The start of the current period.
+    period_start: u64,
+    /// The runtime consumed in the current period.
+    runtime: u64,
+}
+
+impl FairGroup {
+    /// The minimum weight of a group.
+    pub const MIN_WEIGHT: u64 = 1;
+    /// The maximum weight of a group.
+    pub const MAX_WEIGHT: u64 = 10000;
+    /// The default weight of a group.
+    pub const DEFAULT_WEIGHT: u64 = 100;
+
+    /// The default bandwidth period in microseconds.
+    pub const DEFAULT_PERIOD_US: u64 = 100_000;
+    /// The minimum bandwidth period or quota in microseconds.
+    pub const MIN_BANDWIDTH_US: u64 = 1_000;
+    /// The maximum bandwidth period in microseconds.
+    pub const MAX_PERIOD_US: u64 = 1_000_000;
+
+    /// Creates a new group with the default weight and no bandwidth limit.
+    pub fn new(parent: Option<Arc<FairGroup>>) -> Arc<Self> {
+        Arc::new(Self {
+            parent,
+            weight: AtomicU64::new(Self::DEFAULT_WEIGHT),
+            bandwidth: SpinLock::new(Bandwidth {
+                quota_us: None,
+                period_us: Self::DEFAULT_PERIOD_US,
+                quota: None,
+                period: us_to_clocks(Self::DEFAULT_PERIOD_US),
+                period_start: 0,
+                runtime: 0,
+            }),
+        })
+    }
+
+    /// Returns the weight of the group.
+    pub fn weight(&self) -> u64 {
+        self.weight.load(Relaxed)
+    }
+
+    /// Sets the weight of the group.
+    ///
+    /// The weight must be in the range of [`Self::MIN_WEIGHT`] and
+    /// [`Self::MAX_WEIGHT`].
+    pub fn set_weight(&self, weight: u64) {
+        debug_assert!((Self::MIN_WEIGHT..=Self::MAX_WEIGHT).contains(&weight));
+        self.weight.store(weight, Relaxed);
+    }
+
+    /// Returns the bandwidth limit of the group, i.e., the quota (`None` if
+    /// unlimited) and the period in microseconds.
+    pub fn bandwidth(&self) -> (Option<u64>, u64) {
+        let bandwidth = self.bandwidth.lock();
+        (bandwidth.quota_us, bandwidth.period_us)
+    }
+
+    /// Sets the bandwidth limit of the group.
+    ///
+    /// The quota and the period must be no less than
+    /// [`Self::MIN_BANDWIDTH_US`], and the period must be no greater than
+    /// [`Self::MAX_PERIOD_US`].
+    pub fn set_bandwidth(&self, quota_us: Option<u64>, period_us: u64) {
+        let mut bandwidth = self.bandwidth.lock();
+        bandwidth.quota_us = quota_us;
+        bandwidth.period_us = period_us;
+        bandwidth.quota = quota_us.map(us_to_clocks);
+        bandwidth.period = us_to_clocks(period_us);
+        bandwidth.period_start = sched_clock();
+        bandwidth.runtime = 0;
+    }
+
+    fn ancestors(&self) -> impl Iterator<Item = &FairGroup> {
+        core::iter::successors(Some(self), |group| group.parent.as_deref())
+    }
+
+    fn scale_weight(&self, weight: u64) -> u64 {
+        self.ancestors().fold(weight, |weight, group| {
+            (weight.saturating_mul(group.weight()) / Self::DEFAULT_WEIGHT).max(1)
+        })
+    }
+
+    /// Charges the runtime to the group and its ancestors.
+    ///
+    /// Returns whether the group is throttled afterwards.
+    fn charge(&self, delta: u64, now: u64) -> bool {
+        let mut is_throttled = false;
+        for group in self.ancestors() {
+            let mut bandwidth = group.bandwidth.lock();
+            if bandwidth.quota.is_none() {
+                continue;
+            }
+            bandwidth.renew_if_expired(now);
+            bandwidth.runtime += delta;
+            is_throttled |= bandwidth.is_exhausted();
+        }
+        is_throttled
+    }
+
+    fn is_throttled(&self, now: u64) -> bool {
+        self.ancestors().any(|group| {
+            let mut bandwidth = group.bandwidth.lock();
+            bandwidth.renew_if_expired(now);
+            bandwidth.is_exhausted()
+        })
+    }
+}
+
+impl Bandwidth {
+    fn renew_if_expired(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.period_start);
+        if elapsed >= self.period {
+            self.period_start = now - elapsed % self.period;
+            self.runtime = 0;
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.quota.is_some_and(|quota| self.runtime >= quota)
+    }
 }
 
 /// The wrapper for threads in the FAIR run queue.
 ///
 /// This structure is used to provide the capability for keying in the
 /// run queue implemented by `BTreeSet` in the `FairClassRq`.
-struct FairQueueItem(Arc<Task>, u64);
+///
+/// The fields are the thread, its vruntime, and its weight when enqueued.
+struct FairQueueItem(Arc<Task>, u64, u64);
 
 impl core::fmt::Debug for FairQueueItem {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -169,6 +371,8 @@ pub(super) struct FairClassRq {
     cpu: CpuId,
     /// The ready-to-run threads.
     entities: BinaryHeap<Reverse<FairQueueItem>>,
+    /// The threads that are throttled by the bandwidth limits of their groups.
+    throttled: Vec<Arc<Task>>,
     /// The minimum of vruntime in the run queue. Serves as the initial
     /// value of newly-enqueued threads.
     min_vruntime: u64,
@@ -180,6 +384,7 @@ impl FairClassRq {
         Self {
             cpu,
             entities: BinaryHeap::new(),
+            throttled: Vec::new(),
             min_vruntime: 0,
             total_weight: 0,
         }
@@ -217,6 +422,22 @@ impl FairClassRq {
     fn time_slice(&self, cur_weight: u64) -> u64 {
         self.period() * cur_weight / (self.total_weight + cur_weight)
     }
+
+    /// Puts the throttled threads whose groups have been given new runtime
+    /// back into the run queue.
+    fn unthrottle(&mut self) {
+        if self.throttled.is_empty() {
+            return;
+        }
+
+        for entity in mem::take(&mut self.throttled) {
+            if entity.as_thread().unwrap().sched_attr().fair.is_throttled() {
+                self.throttled.push(entity);
+            } else {
+                self.enqueue(entity, None);
+            }
+        }
+    }
 }
 
 impl SchedClassRq for FairClassRq {
@@ -231,25 +452,36 @@ impl SchedClassRq for FairClassRq {
             .fetch_max(vruntime, Relaxed)
             .max(vruntime);
 
-        self.total_weight += fair_attr.weight.load(Relaxed);
-        self.entities.push(Reverse(FairQueueItem(entity, vruntime)));
+        let weight = fair_attr.weight();
+        self.total_weight += weight;
+        self.entities
+            .push(Reverse(FairQueueItem(entity, vruntime, weight)));
     }
 
+    // The throttled threads are counted, so that the run queue is polled to
+    // unthrottle them even if the CPU is idle.
     fn len(&self) -> usize {
-        self.entities.len()
+        self.entities.len() + self.throttled.len()
     }
 
     fn is_empty(&self) -> bool {
-        self.entities.is_empty()
+        self.entities.is_empty() && self.throttled.is_empty()
     }
 
     fn pick_next(&mut self) -> Option<Arc<Task>> {
-        let Reverse(FairQueueItem(entity, _)) = self.entities.pop()?;
+        self.unthrottle();
+
+        loop {
+            let Reverse(FairQueueItem(entity, _, weight)) = self.entities.pop()?;
+            self.total_weight -= weight;
 
-        let sched_attr = entity.as_thread().unwrap().sched_attr();
-        self.total_weight -= sched_attr.fair.weight.load(Relaxed);
+            if entity.as_thread().unwrap().sched_attr().fair.is_throttled() {
+                self.throttled.push(entity);
+                continue;
+            }
 
-        Some(entity)
+            return Some(entity);
+        }
     }
 
     fn update_current(
@@ -262,12 +494,14 @@ impl SchedClassRq for FairClassRq {
             UpdateFlags::Yield => true,
             UpdateFlags::Tick | UpdateFlags::Wait => {
                 let (vruntime, weight) = attr.fair.update_vruntime(rt.delta);
+                let is_throttled = attr.fair.charge_groups(rt.delta);
                 self.min_vruntime = match self.entities.peek() {
                     Some(Reverse(leftmost)) => vruntime.min(leftmost.key()),
                     None => vruntime,
                 };
 
-                rt.period_delta > self.time_slice(weight)
+                is_throttled
+                    || rt.period_delta > self.time_slice(weight)
                     || vruntime > self.min_vruntime + self.vtime_slice()
             }
         }
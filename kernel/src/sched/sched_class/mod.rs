@@ -35,6 +35,7 @@ mod stop;
 
 use self::policy::{SchedPolicyKind, SchedPolicyState};
 pub use self::{
+    fair::FairGroup,
     policy::SchedPolicy,
     real_time::{RealTimePolicy, RealTimePriority},
 };
@@ -178,6 +179,14 @@ impl SchedAttr {
         });
     }
 
+    /// Sets the group of the thread in the FAIR scheduling class.
+    ///
+    /// The group takes effect the next time the thread is enqueued or
+    /// charged for its runtime.
+    pub fn set_fair_group(&self, group: Option<Arc<FairGroup>>) {
+        self.fair.set_group(group);
+    }
+
     pub fn update_policy<T>(&self, f: impl FnOnce(&mut SchedPolicy) -> T) -> T {
         self.policy.update(f)
     }
@@ -51,3 +51,9 @@ pub fn base_slice_clocks() -> u64 {
 pub fn min_period_clocks() -> u64 {
     consts().1
 }
+
+/// Converts a duration in microseconds to TSC clock units.
+pub fn us_to_clocks(us: u64) -> u64 {
+    let (a, b) = tsc_factors();
+    (us as u128 * 1000 * b as u128 / a as u128) as u64
+}
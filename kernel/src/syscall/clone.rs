@@ -10,6 +10,12 @@ use crate::{
     process::{clone_child, signal::sig_num::SigNum, CloneArgs, CloneFlags},
 };
 
+/// The flag of `clone3` to put the child in the cgroup specified by the
+/// `cgroup` field.
+///
+/// The flag does not fit in [`CloneFlags`], which only has the lower 32 bits.
+const CLONE_INTO_CGROUP: u64 = 0x2_0000_0000;
+
 // The order of arguments for clone differs in different architecture.
 // This order we use here is the order for x86_64. See https://man7.org/linux/man-pages/man2/clone.2.html.
 pub fn sys_clone(
@@ -23,7 +29,7 @@ pub fn sys_clone(
 ) -> Result<SyscallReturn> {
     let args = CloneArgs::for_clone(clone_flags, parent_tidptr, child_tidptr, tls, new_sp)?;
     debug!("flags = {:?}, child_stack_ptr = 0x{:x}, parent_tid_ptr = 0x{:x?}, child tid ptr = 0x{:x}, tls = 0x{:x}", args.flags, args.stack, args.parent_tid, args.child_tid, args.tls);
-    let child_pid = clone_child(ctx, parent_context, args).unwrap();
+    let child_pid = clone_child(ctx, parent_context, args)?;
     Ok(SyscallReturn::Return(child_pid as _))
 }
 
@@ -83,22 +89,14 @@ struct Clone3Args {
 
 impl From<Clone3Args> for CloneArgs {
     fn from(value: Clone3Args) -> Self {
-        // TODO: deal with pidfd, set_tid, set_tid_size, cgroup
-        if value.pidfd != 0 {
-            warn!("pidfd is not supported");
-        }
-
+        // TODO: deal with set_tid, set_tid_size
         if value.set_tid != 0 || value.set_tid_size != 0 {
             warn!("set_tid is not supported");
         }
 
-        if value.cgroup != 0 {
-            warn!("cgroup is not supported");
-        }
-
         Self {
             flags: CloneFlags::from_bits_truncate(value.flags as u32),
-            _pidfd: Some(value.pidfd),
+            pidfd: Some(value.pidfd as _),
             child_tid: value.child_tid as _,
             parent_tid: Some(value.parent_tid as _),
             exit_signal: (value.exit_signal != 0).then(|| SigNum::from_u8(value.exit_signal as u8)),
@@ -107,7 +105,7 @@ impl From<Clone3Args> for CloneArgs {
             tls: value.tls,
             _set_tid: Some(value.set_tid),
             _set_tid_size: Some(value.set_tid_size),
-            _cgroup: Some(value.cgroup),
+            cgroup: (value.flags & CLONE_INTO_CGROUP != 0).then_some(value.cgroup as _),
         }
     }
 }
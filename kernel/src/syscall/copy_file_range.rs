@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_handle::FileLike,
+        file_table::{FileDesc, WithFileTable},
+        inode_handle::InodeHandle,
+        notify::FsEvents,
+        utils::{InodeType, SeekFrom, StatusFlags},
+    },
+    prelude::*,
+};
+
+pub fn sys_copy_file_range(
+    fd_in: FileDesc,
+    offset_in_ptr: Vaddr,
+    fd_out: FileDesc,
+    offset_out_ptr: Vaddr,
+    len: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd_in = {}, offset_in_ptr = 0x{:x}, fd_out = {}, offset_out_ptr = 0x{:x}, len = 0x{:x}, flags = 0x{:x}",
+        fd_in, offset_in_ptr, fd_out, offset_out_ptr, len, flags
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+
+    let (file_in, file_out) = ctx
+        .thread_local
+        .borrow_file_table_mut()
+        .read_with(|inner| {
+            let file_in = inner.get_file(fd_in)?.clone();
+            let file_out = inner.get_file(fd_out)?.clone();
+            Ok::<_, Error>((file_in, file_out))
+        })?;
+    let handle_in = check_file(file_in.as_ref())?;
+    let handle_out = check_file(file_out.as_ref())?;
+
+    if !file_in.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "the input file is not readable");
+    }
+    if !file_out.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "the output file is not writable");
+    }
+    if file_out.status_flags().contains(StatusFlags::O_APPEND) {
+        return_errno_with_message!(Errno::EBADF, "the output file is opened in append mode");
+    }
+
+    let offset_in = match read_offset(offset_in_ptr, ctx)? {
+        Some(offset) => offset,
+        None => handle_in.offset(),
+    };
+    let offset_out = match read_offset(offset_out_ptr, ctx)? {
+        Some(offset) => offset,
+        None => handle_out.offset(),
+    };
+
+    let inode_in = handle_in.dentry().inode();
+    let inode_out = handle_out.dentry().inode();
+    if Arc::ptr_eq(inode_in, inode_out)
+        && offset_in < offset_out.saturating_add(len)
+        && offset_out < offset_in.saturating_add(len)
+    {
+        return_errno_with_message!(Errno::EINVAL, "the ranges overlap in the same file");
+    }
+
+    // Copy in the file system if it supports doing so, or fall back to a generic copy.
+    let copied_len = match inode_out.copy_range_from(inode_in, offset_in, offset_out, len) {
+        Err(err) if err.error() == Errno::EOPNOTSUPP => copy_range(
+            file_in.as_ref(),
+            offset_in,
+            file_out.as_ref(),
+            offset_out,
+            len,
+        )?,
+        result => {
+            let copied_len = result?;
+            if copied_len > 0 {
+                handle_in.dentry().publish_event(FsEvents::ACCESS);
+                handle_out.dentry().publish_event(FsEvents::MODIFY);
+            }
+            copied_len
+        }
+    };
+
+    if offset_in_ptr != 0 {
+        write_offset(offset_in_ptr, offset_in + copied_len, ctx)?;
+    } else {
+        handle_in.seek(SeekFrom::Start(offset_in + copied_len))?;
+    }
+    if offset_out_ptr != 0 {
+        write_offset(offset_out_ptr, offset_out + copied_len, ctx)?;
+    } else {
+        handle_out.seek(SeekFrom::Start(offset_out + copied_len))?;
+    }
+
+    Ok(SyscallReturn::Return(copied_len as _))
+}
+
+/// Checks that the file is a regular file.
+fn check_file(file: &dyn FileLike) -> Result<&InodeHandle> {
+    let Ok(inode_handle) = file.as_inode_or_err() else {
+        return_errno_with_message!(Errno::EINVAL, "the file is not a regular file");
+    };
+
+    match inode_handle.dentry().type_() {
+        InodeType::File => Ok(inode_handle),
+        InodeType::Dir => return_errno_with_message!(Errno::EISDIR, "the file is a directory"),
+        _ => return_errno_with_message!(Errno::EINVAL, "the file is not a regular file"),
+    }
+}
+
+/// Copies the bytes by reading them to a kernel buffer and then writing them.
+fn copy_range(
+    file_in: &dyn FileLike,
+    mut offset_in: usize,
+    file_out: &dyn FileLike,
+    mut offset_out: usize,
+    len: usize,
+) -> Result<usize> {
+    let mut buffer = vec![0u8; PAGE_SIZE].into_boxed_slice();
+    let mut copied_len = 0;
+
+    while copied_len < len {
+        let max_len = buffer.len().min(len - copied_len);
+        let result = file_in
+            .read_bytes_at(offset_in, &mut buffer[..max_len])
+            .and_then(|read_len| {
+                let written_len = file_out.write_bytes_at(offset_out, &buffer[..read_len])?;
+                Ok((read_len, written_len))
+            });
+
+        let (read_len, written_len) = match result {
+            Ok(lens) => lens,
+            Err(err) if copied_len == 0 => return Err(err),
+            Err(err) => {
+                warn!("error occurs when trying to copy file: {:?}", err);
+                break;
+            }
+        };
+
+        copied_len += written_len;
+        offset_in += written_len;
+        offset_out += written_len;
+        if read_len < max_len || written_len < read_len {
+            break;
+        }
+    }
+
+    Ok(copied_len)
+}
+
+fn read_offset(offset_ptr: Vaddr, ctx: &Context) -> Result<Option<usize>> {
+    if offset_ptr == 0 {
+        return Ok(None);
+    }
+
+    let offset: i64 = ctx.user_space().read_val(offset_ptr)?;
+    if offset < 0 {
+        return_errno_with_message!(Errno::EINVAL, "offset cannot be negative");
+    }
+    Ok(Some(offset as usize))
+}
+
+fn write_offset(offset_ptr: Vaddr, offset: usize, ctx: &Context) -> Result<()> {
+    ctx.user_space().write_val(offset_ptr, &(offset as i64))?;
+    Ok(())
+}
@@ -15,8 +15,9 @@ use crate::{
     },
     prelude::*,
     process::{
-        check_executable_file, posix_thread::ThreadName, renew_vm_and_map, Credentials, Process,
-        ProgramToLoad, MAX_ARGV_NUMBER, MAX_ARG_LEN, MAX_ENVP_NUMBER, MAX_ENV_LEN,
+        check_executable_file, coredump::Dumpable, posix_thread::ThreadName, ptrace::ptrace_exec,
+        renew_vm_and_map, Credentials, Process, ProgramToLoad, MAX_ARGV_NUMBER, MAX_ARG_LEN,
+        MAX_ENVP_NUMBER, MAX_ENV_LEN,
     },
 };
 
@@ -145,11 +146,25 @@ fn do_execve(
     *thread_local.robust_list().borrow_mut() = None;
     debug!("load elf in execve succeeds");
 
+    // The set-user-ID and set-group-ID bits are ignored if `no_new_privs` is set.
+    let no_new_privs = posix_thread.no_new_privs();
     let credentials = posix_thread.credentials_mut();
-    set_uid_from_elf(process, &credentials, &elf_file)?;
-    set_gid_from_elf(process, &credentials, &elf_file)?;
+    set_uid_from_elf(process, &credentials, &elf_file, no_new_privs)?;
+    set_gid_from_elf(process, &credentials, &elf_file, no_new_privs)?;
     credentials.set_keep_capabilities(false);
 
+    // Like Linux, the processes whose user or group IDs are changed by set-user-ID or
+    // set-group-ID programs cannot generate core dumps.
+    let dumpable = {
+        let credentials = posix_thread.credentials();
+        if credentials.euid() != credentials.ruid() || credentials.egid() != credentials.rgid() {
+            Dumpable::Disable
+        } else {
+            Dumpable::User
+        }
+    };
+    process.set_dumpable(dumpable);
+
     // set executable path
     process.set_executable_path(new_executable_path);
     // set signal disposition to default
@@ -169,6 +184,8 @@ fn do_execve(
     // set new user stack top
     user_context.set_stack_pointer(elf_load_info.user_stack_top() as _);
     debug!("user stack top: 0x{:x}", elf_load_info.user_stack_top());
+
+    ptrace_exec(ctx);
     Ok(())
 }
 
@@ -222,8 +239,9 @@ fn set_uid_from_elf(
     current: &Process,
     credentials: &Credentials<WriteOp>,
     elf_file: &Dentry,
+    no_new_privs: bool,
 ) -> Result<()> {
-    if elf_file.mode()?.has_set_uid() {
+    if !no_new_privs && elf_file.mode()?.has_set_uid() {
         let uid = elf_file.owner()?;
         credentials.set_euid(uid);
 
@@ -240,8 +258,9 @@ fn set_gid_from_elf(
     current: &Process,
     credentials: &Credentials<WriteOp>,
     elf_file: &Dentry,
+    no_new_privs: bool,
 ) -> Result<()> {
-    if elf_file.mode()?.has_set_gid() {
+    if !no_new_privs && elf_file.mode()?.has_set_gid() {
         let gid = elf_file.group()?;
         credentials.set_egid(gid);
 
@@ -5,6 +5,7 @@ use crate::{
     fs::{
         file_handle::FileLike,
         file_table::{get_file_fast, FdFlags, FileDesc, WithFileTable},
+        ramfs::memfd::{FileSeals, MemfdSeals},
         utils::{
             FileRange, RangeLockItem, RangeLockItemBuilder, RangeLockType, StatusFlags, OFFSET_MAX,
         },
@@ -31,6 +32,8 @@ pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64, ctx: &Context) -> Result<Sysc
         }),
         FcntlCmd::F_GETOWN => handle_getown(fd, ctx),
         FcntlCmd::F_SETOWN => handle_setown(fd, arg, ctx),
+        FcntlCmd::F_ADD_SEALS => handle_addseals(fd, arg, ctx),
+        FcntlCmd::F_GET_SEALS => handle_getseals(fd, ctx),
     }
 }
 
@@ -162,6 +165,34 @@ fn handle_setown(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
     Ok(SyscallReturn::Return(0))
 }
 
+fn handle_addseals(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn> {
+    let new_seals = FileSeals::from_bits(arg as u32)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid seals"))?;
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd);
+    if !file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EPERM, "the file is not opened for writing");
+    }
+
+    let inode = file.as_inode_or_err()?.dentry().inode();
+    let seals = MemfdSeals::of(inode.as_ref())
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the file does not support seals"))?;
+
+    seals.add(inode.as_ref(), new_seals)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+fn handle_getseals(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd);
+    let inode = file.as_inode_or_err()?.dentry().inode();
+    let seals = MemfdSeals::of(inode.as_ref())
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the file does not support seals"))?;
+
+    Ok(SyscallReturn::Return(seals.get().bits() as _))
+}
+
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, TryFromInt)]
 #[expect(non_camel_case_types)]
@@ -177,6 +208,8 @@ enum FcntlCmd {
     F_SETOWN = 8,
     F_GETOWN = 9,
     F_DUPFD_CLOEXEC = 1030,
+    F_ADD_SEALS = 1033,
+    F_GET_SEALS = 1034,
 }
 
 #[expect(non_camel_case_types)]
@@ -4,5 +4,11 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_getppid(ctx: &Context) -> Result<SyscallReturn> {
-    Ok(SyscallReturn::Return(ctx.process.parent().pid() as _))
+    // Like Linux, the parent process is reported as zero if it is in an ancestor PID namespace.
+    let ppid = ctx
+        .process
+        .pid_ns()
+        .tid_in_ns(ctx.process.parent().pid())
+        .unwrap_or(0);
+    Ok(SyscallReturn::Return(ppid as _))
 }
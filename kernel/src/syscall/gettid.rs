@@ -4,6 +4,10 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_gettid(ctx: &Context) -> Result<SyscallReturn> {
-    let tid = ctx.posix_thread.tid();
+    let tid = ctx
+        .process
+        .pid_ns()
+        .tid_in_ns(ctx.posix_thread.tid())
+        .unwrap();
     Ok(SyscallReturn::Return(tid as _))
 }
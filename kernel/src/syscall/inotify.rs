@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::{get_file_fast, FdFlags, FileDesc},
+        fs_resolver::{FsPath, AT_FDCWD},
+        notify::{
+            inotify::{InotifyControls, InotifyFile},
+            FsEvents,
+        },
+        utils::{CreationFlags, Permission, StatusFlags, PATH_MAX},
+    },
+    prelude::*,
+};
+
+pub fn sys_inotify_init(ctx: &Context) -> Result<SyscallReturn> {
+    sys_inotify_init1(0, ctx)
+}
+
+pub fn sys_inotify_init1(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = InotifyFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!("flags = {:?}", flags);
+
+    let inotify_file = InotifyFile::new(flags.contains(InotifyFlags::IN_NONBLOCK));
+    let fd_flags = if flags.contains(InotifyFlags::IN_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+
+    let file_table = ctx.thread_local.borrow_file_table();
+    let fd = file_table.unwrap().write().insert(inotify_file, fd_flags);
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_inotify_add_watch(
+    fd: FileDesc,
+    path_addr: Vaddr,
+    mask: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let path = ctx.user_space().read_cstring(path_addr, PATH_MAX)?;
+    let events = FsEvents::from_bits_truncate(mask);
+    let controls = InotifyControls::from_bits_truncate(mask);
+    debug!(
+        "fd = {}, path = {:?}, events = {:?}, controls = {:?}",
+        fd, path, events, controls
+    );
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd).into_owned();
+    drop(file_table);
+
+    let inotify_file = file
+        .downcast_ref::<InotifyFile>()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the file is not an inotify file"))?;
+
+    let dentry = {
+        let path = path.to_string_lossy();
+        if path.is_empty() {
+            return_errno_with_message!(Errno::ENOENT, "path is empty");
+        }
+        let fs_path = FsPath::new(AT_FDCWD, path.as_ref())?;
+        let fs = ctx.posix_thread.fs().resolver().read();
+        if controls.contains(InotifyControls::IN_DONT_FOLLOW) {
+            fs.lookup_no_follow(&fs_path)?
+        } else {
+            fs.lookup(&fs_path)?
+        }
+    };
+    dentry.inode().check_permission(Permission::MAY_READ)?;
+
+    let wd = inotify_file.add_watch(dentry.inode(), events, controls)?;
+    Ok(SyscallReturn::Return(wd as _))
+}
+
+pub fn sys_inotify_rm_watch(fd: FileDesc, wd: i32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("fd = {}, wd = {}", fd, wd);
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd);
+    let inotify_file = file
+        .downcast_ref::<InotifyFile>()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the file is not an inotify file"))?;
+
+    let wd = u32::try_from(wd)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "invalid watch descriptor"))?;
+    inotify_file.remove_watch(wd)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+bitflags! {
+    struct InotifyFlags: u32 {
+        const IN_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+        const IN_CLOEXEC = CreationFlags::O_CLOEXEC.bits();
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::{get_file_fast, FileDesc},
+        io_uring::{IoUringEnterFlags, IoUringFile},
+    },
+    prelude::*,
+    process::signal::{sig_mask::SigMask, with_sigmask_changed},
+};
+
+pub fn sys_io_uring_enter(
+    fd: FileDesc,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    sigmask_addr: Vaddr,
+    sigmask_size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = IoUringEnterFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+    debug!(
+        "fd = {}, to_submit = {}, min_complete = {}, flags = {:?}",
+        fd, to_submit, min_complete, flags
+    );
+
+    // TODO: Support extended arguments (e.g., timeouts for waiting completions).
+    if flags.contains(IoUringEnterFlags::EXT_ARG) {
+        return_errno_with_message!(Errno::EINVAL, "extended arguments are not supported");
+    }
+
+    // The file table cannot stay borrowed because the requests may refer to other files.
+    let file = {
+        let mut file_table = ctx.thread_local.borrow_file_table_mut();
+        get_file_fast!(&mut file_table, fd).into_owned()
+    };
+    let io_uring_file = file
+        .downcast_ref::<IoUringFile>()
+        .ok_or_else(|| Error::with_message(Errno::EOPNOTSUPP, "the file is not an io_uring"))?;
+
+    // Since there is no kernel thread polling the SQ, `IORING_ENTER_SQ_WAKEUP` and
+    // `IORING_ENTER_SQ_WAIT` are no-ops.
+
+    let submitted = if sigmask_addr != 0 && flags.contains(IoUringEnterFlags::GETEVENTS) {
+        if sigmask_size != size_of::<SigMask>() {
+            return_errno_with_message!(Errno::EINVAL, "invalid sigmask size");
+        }
+
+        let sigmask = ctx.user_space().read_val::<SigMask>(sigmask_addr)?;
+        with_sigmask_changed(
+            ctx,
+            |_| sigmask,
+            || io_uring_file.enter(to_submit, min_complete, flags, ctx),
+        )?
+    } else {
+        io_uring_file.enter(to_submit, min_complete, flags, ctx)?
+    };
+
+    Ok(SyscallReturn::Return(submitted as _))
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::{get_file_fast, FileDesc},
+        io_uring::{IoUringFile, IoUringRegisterOp},
+    },
+    prelude::*,
+};
+
+pub fn sys_io_uring_register(
+    fd: FileDesc,
+    opcode: u32,
+    arg: Vaddr,
+    nr_args: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let op = IoUringRegisterOp::try_from(opcode)?;
+    debug!(
+        "fd = {}, op = {:?}, arg = 0x{:x}, nr_args = {}",
+        fd, op, arg, nr_args
+    );
+
+    // The file table cannot stay borrowed because the requests may refer to other files.
+    let file = {
+        let mut file_table = ctx.thread_local.borrow_file_table_mut();
+        get_file_fast!(&mut file_table, fd).into_owned()
+    };
+    let io_uring_file = file
+        .downcast_ref::<IoUringFile>()
+        .ok_or_else(|| Error::with_message(Errno::EOPNOTSUPP, "the file is not an io_uring"))?;
+
+    let res = io_uring_file.register(op, arg, nr_args, ctx)?;
+
+    Ok(SyscallReturn::Return(res as _))
+}
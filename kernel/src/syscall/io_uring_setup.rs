@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::FdFlags,
+        io_uring::{IoUringFile, IoUringParams},
+    },
+    prelude::*,
+};
+
+pub fn sys_io_uring_setup(
+    entries: u32,
+    params_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let user_space = ctx.user_space();
+
+    let mut params = user_space.read_val::<IoUringParams>(params_addr)?;
+    debug!("entries = {}, params = {:?}", entries, params);
+
+    if params.resv.iter().any(|resv| *resv != 0) {
+        return_errno_with_message!(Errno::EINVAL, "the reserved fields are not zero");
+    }
+
+    let io_uring_file = IoUringFile::new(entries, &mut params)?;
+    user_space.write_val(params_addr, &params)?;
+
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        // Like Linux, the io_uring file is always closed on `execve`.
+        file_table_locked.insert(Arc::new(io_uring_file), FdFlags::CLOEXEC)
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
@@ -4,13 +4,15 @@ use super::SyscallReturn;
 use crate::{
     fs::{
         file_table::{get_file_fast, FdFlags, FileDesc, WithFileTable},
-        utils::{IoctlCmd, StatusFlags},
+        utils::{IoctlCmd, RawIoctlCmd, StatusFlags},
     },
     prelude::*,
 };
 
 pub fn sys_ioctl(fd: FileDesc, cmd: u32, arg: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
-    let ioctl_cmd = IoctlCmd::try_from(cmd)?;
+    let Ok(ioctl_cmd) = IoctlCmd::try_from(cmd) else {
+        return sys_ioctl_raw(fd, RawIoctlCmd::new(cmd), arg, ctx);
+    };
     debug!(
         "fd = {}, ioctl_cmd = {:?}, arg = 0x{:x}",
         fd, ioctl_cmd, arg
@@ -69,3 +71,20 @@ pub fn sys_ioctl(fd: FileDesc, cmd: u32, arg: Vaddr, ctx: &Context) -> Result<Sy
     };
     Ok(SyscallReturn::Return(res as _))
 }
+
+/// Performs an ioctl command that is not listed in [`IoctlCmd`] (e.g., `EVIOCGNAME(len)`).
+fn sys_ioctl_raw(
+    fd: FileDesc,
+    cmd: RawIoctlCmd,
+    arg: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("fd = {}, raw_cmd = {:?}, arg = 0x{:x}", fd, cmd, arg);
+
+    let file = {
+        let mut file_table = ctx.thread_local.borrow_file_table_mut();
+        get_file_fast!(&mut file_table, fd).into_owned()
+    };
+    let res = file.ioctl_raw(cmd, arg)?;
+    Ok(SyscallReturn::Return(res as _))
+}
@@ -14,7 +14,8 @@ use crate::{
 };
 
 pub fn sys_kill(process_filter: u64, sig_num: u64, ctx: &Context) -> Result<SyscallReturn> {
-    let process_filter = ProcessFilter::from_id(process_filter as _);
+    let process_filter = ProcessFilter::from_id(process_filter as _, ctx.process.pid_ns())
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process does not exist"))?;
     let sig_num = if sig_num == 0 {
         None
     } else {
@@ -45,6 +45,7 @@ pub fn sys_madvise(
             warn!("MADV_DONTNEED isn't implemented, do nothing for now.");
         }
         MadviseBehavior::MADV_FREE => madv_free(start, end, ctx)?,
+        MadviseBehavior::MADV_PAGEOUT => madv_pageout(start, end, ctx),
         _ => todo!(),
     }
     Ok(SyscallReturn::Return(0))
@@ -59,6 +60,13 @@ fn madv_free(start: Vaddr, end: Vaddr, ctx: &Context) -> Result<()> {
     Ok(())
 }
 
+fn madv_pageout(start: Vaddr, end: Vaddr, ctx: &Context) {
+    let user_space = ctx.user_space();
+    let root_vmar = user_space.root_vmar();
+    let nr_pages = root_vmar.page_out(start..end);
+    debug!("{} pages are reclaimed", nr_pages);
+}
+
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, TryFromInt)]
 #[expect(non_camel_case_types)]
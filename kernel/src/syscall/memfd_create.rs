@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::FdFlags,
+        ramfs::memfd::{create_memfd, MAX_MEMFD_NAME_LEN},
+    },
+    prelude::*,
+};
+
+pub fn sys_memfd_create(name_addr: Vaddr, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = MemfdFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+    if flags.contains(MemfdFlags::MFD_HUGETLB) {
+        return_errno_with_message!(Errno::EINVAL, "huge pages are not supported");
+    }
+
+    let name = ctx
+        .user_space()
+        .read_cstring(name_addr, MAX_MEMFD_NAME_LEN + 1)?;
+    let name = name
+        .to_str()
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the name is not valid UTF-8"))?;
+    debug!("name = {}, flags = {:?}", name, flags);
+
+    let file = create_memfd(name, flags.contains(MemfdFlags::MFD_ALLOW_SEALING))?;
+
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        let fd_flags = if flags.contains(MemfdFlags::MFD_CLOEXEC) {
+            FdFlags::CLOEXEC
+        } else {
+            FdFlags::empty()
+        };
+        file_table_locked.insert(Arc::new(file), fd_flags)
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+bitflags! {
+    struct MemfdFlags: u32 {
+        const MFD_CLOEXEC = 0x0001;
+        const MFD_ALLOW_SEALING = 0x0002;
+        const MFD_HUGETLB = 0x0004;
+    }
+}
@@ -124,36 +124,45 @@ fn do_sys_mmap(
                 options = options.vmo(shared_vmo);
             }
         } else {
-            let vmo = {
+            let (vmo, vmo_offset, mapped_file) = {
                 let mut file_table = ctx.thread_local.borrow_file_table_mut();
                 let file = get_file_fast!(&mut file_table, fd);
-                let inode_handle = file.as_inode_or_err()?;
-
-                let access_mode = inode_handle.access_mode();
-                if vm_perms.contains(VmPerms::READ) && !access_mode.is_readable() {
-                    return_errno!(Errno::EACCES);
-                }
-                if option.typ() == MMapType::Shared
-                    && vm_perms.contains(VmPerms::WRITE)
-                    && !access_mode.is_writable()
-                {
-                    return_errno!(Errno::EACCES);
+                if let Ok(inode_handle) = file.as_inode_or_err() {
+                    let access_mode = inode_handle.access_mode();
+                    if vm_perms.contains(VmPerms::READ) && !access_mode.is_readable() {
+                        return_errno!(Errno::EACCES);
+                    }
+                    if option.typ() == MMapType::Shared
+                        && vm_perms.contains(VmPerms::WRITE)
+                        && !access_mode.is_writable()
+                    {
+                        return_errno!(Errno::EACCES);
+                    }
+
+                    let dentry = inode_handle.dentry();
+                    if let Some(page_cache) = dentry.inode().page_cache() {
+                        (page_cache.to_dyn(), offset, Some(dentry.clone()))
+                    } else {
+                        // Devices without page caches (e.g., framebuffers) may provide their own
+                        // VMOs.
+                        let (vmo, vmo_offset) = inode_handle.mmap_vmo(offset)?;
+                        (vmo, vmo_offset, None)
+                    }
+                } else {
+                    // Files that are not backed by inodes (e.g., io_uring files) may provide
+                    // their own VMOs.
+                    let (vmo, vmo_offset) = file.mmap_vmo(offset)?;
+                    (vmo, vmo_offset, None)
                 }
-
-                let inode = inode_handle.dentry().inode();
-                inode
-                    .page_cache()
-                    .ok_or(Error::with_message(
-                        Errno::EBADF,
-                        "File does not have page cache",
-                    ))?
-                    .to_dyn()
             };
 
             options = options
                 .vmo(vmo)
-                .vmo_offset(offset)
+                .vmo_offset(vmo_offset)
                 .handle_page_faults_around();
+            if let Some(mapped_file) = mapped_file {
+                options = options.file(mapped_file);
+            }
         }
 
         options
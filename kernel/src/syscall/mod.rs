@@ -26,6 +26,7 @@ mod clone;
 mod close;
 mod connect;
 mod constants;
+mod copy_file_range;
 mod dup;
 mod epoll;
 mod eventfd;
@@ -62,6 +63,10 @@ mod gettid;
 mod gettimeofday;
 mod getuid;
 mod getxattr;
+mod inotify;
+mod io_uring_enter;
+mod io_uring_register;
+mod io_uring_setup;
 mod ioctl;
 mod kill;
 mod link;
@@ -69,16 +74,26 @@ mod listen;
 mod listxattr;
 mod lseek;
 mod madvise;
+mod memfd_create;
 mod mkdir;
 mod mknod;
 mod mmap;
 mod mount;
 mod mprotect;
+mod mqueue;
+mod mremap;
+mod msgctl;
+mod msgget;
+mod msgrcv;
+mod msgsnd;
 mod msync;
 mod munmap;
 mod nanosleep;
 mod open;
 mod pause;
+mod pidfd_getfd;
+mod pidfd_open;
+mod pidfd_send_signal;
 mod pipe;
 mod poll;
 mod ppoll;
@@ -87,6 +102,7 @@ mod pread64;
 mod preadv;
 mod prlimit64;
 mod pselect6;
+mod ptrace;
 mod pwrite64;
 mod pwritev;
 mod read;
@@ -111,6 +127,7 @@ mod sched_setattr;
 mod sched_setparam;
 mod sched_setscheduler;
 mod sched_yield;
+mod seccomp;
 mod select;
 mod semctl;
 mod semget;
@@ -121,11 +138,14 @@ mod sendto;
 mod set_priority;
 mod set_robust_list;
 mod set_tid_address;
+mod setdomainname;
 mod setfsgid;
 mod setfsuid;
 mod setgid;
 mod setgroups;
+mod sethostname;
 mod setitimer;
+mod setns;
 mod setpgid;
 mod setregid;
 mod setresgid;
@@ -135,14 +155,21 @@ mod setsid;
 mod setsockopt;
 mod setuid;
 mod setxattr;
+mod shmat;
+mod shmctl;
+mod shmdt;
+mod shmget;
 mod shutdown;
 mod sigaltstack;
 mod signalfd;
 mod socket;
 mod socketpair;
+mod splice;
 mod stat;
 mod statfs;
 mod statx;
+mod swapoff;
+mod swapon;
 mod symlink;
 mod sync;
 mod sysinfo;
@@ -158,6 +185,7 @@ mod umask;
 mod umount;
 mod uname;
 mod unlink;
+mod unshare;
 mod utimens;
 mod wait4;
 mod waitid;
@@ -341,12 +369,21 @@ impl SyscallArgument {
 
 pub fn handle_syscall(ctx: &Context, user_ctx: &mut UserContext) {
     let syscall_frame = SyscallArgument::new_from_context(user_ctx);
-    let syscall_return = arch::syscall_dispatch(
+    let syscall_return = match seccomp::check_syscall(
         syscall_frame.syscall_number,
         syscall_frame.args,
         ctx,
         user_ctx,
-    );
+    ) {
+        // The system call is skipped according to the seccomp state.
+        Some(return_value) => Ok(return_value),
+        None => arch::syscall_dispatch(
+            syscall_frame.syscall_number,
+            syscall_frame.args,
+            ctx,
+            user_ctx,
+        ),
+    };
 
     match syscall_return {
         Ok(return_value) => {
@@ -382,7 +419,3 @@ macro_rules! log_syscall_entry {
         }
     };
 }
-
-pub(super) fn init() {
-    uname::init();
-}
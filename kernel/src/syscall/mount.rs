@@ -1,11 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_block::BlockDevice;
+
 use super::SyscallReturn;
 use crate::{
+    device::get_block_device,
     fs::{
+        cgroupfs,
+        device::DeviceId,
         exfat::{ExfatFS, ExfatMountOptions},
         ext2::Ext2,
         fs_resolver::{FsPath, AT_FDCWD},
+        mqueue,
         overlayfs::OverlayFS,
         path::Dentry,
         utils::{FileSystem, InodeType},
@@ -166,17 +172,14 @@ fn get_fs(
 
     let fs_type = fs_type.to_str().unwrap();
     match fs_type {
-        "ext2" => {
-            let device = aster_block::get_device(devname.to_str().unwrap()).ok_or(
-                Error::with_message(Errno::ENOENT, "device for ext2 does not exist"),
-            )?;
+        // Ext3 is Ext2 with a journal, which is handled by the same driver.
+        "ext2" | "ext3" => {
+            let device = lookup_block_device(devname, ctx)?;
             let ext2_fs = Ext2::open(device)?;
             Ok(ext2_fs)
         }
         "exfat" => {
-            let device = aster_block::get_device(devname.to_str().unwrap()).ok_or(
-                Error::with_message(Errno::ENOENT, "device for exfat does not exist"),
-            )?;
+            let device = lookup_block_device(devname, ctx)?;
             let exfat_fs = ExfatFS::open(device, ExfatMountOptions::default())?;
             Ok(exfat_fs)
         }
@@ -184,10 +187,34 @@ fn get_fs(
             let overlay_fs = create_overlayfs(data.as_ref(), ctx)?;
             Ok(overlay_fs)
         }
+        // There is a single instance of mqueue, so all the mounts share the queues.
+        "mqueue" => Ok(mqueue::singleton().clone()),
+        // There is a single cgroup hierarchy, so all the mounts share the cgroups.
+        "cgroup2" => Ok(cgroupfs::singleton().clone()),
         _ => return_errno_with_message!(Errno::EINVAL, "Invalid fs type"),
     }
 }
 
+/// Looks up the block device by the path of its device node.
+pub(super) fn lookup_block_device(devname: CString, ctx: &Context) -> Result<Arc<dyn BlockDevice>> {
+    let devname = devname.to_string_lossy();
+    if devname.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "devname is empty");
+    }
+
+    let dentry = {
+        let fs_path = FsPath::new(AT_FDCWD, devname.as_ref())?;
+        ctx.posix_thread.fs().resolver().read().lookup(&fs_path)?
+    };
+    if dentry.type_() != InodeType::BlockDevice {
+        return_errno_with_message!(Errno::ENOTBLK, "devname is not a block device");
+    }
+
+    let device_id = DeviceId::from(dentry.inode().metadata().rdev);
+    get_block_device(device_id)
+        .ok_or_else(|| Error::with_message(Errno::ENXIO, "the block device does not exist"))
+}
+
 // TODO: Support read-only mount (no upper) and customized features
 fn create_overlayfs(data: &str, ctx: &Context) -> Result<Arc<OverlayFS>> {
     let mut lower = Vec::new();
@@ -0,0 +1,361 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_handle::FileLike,
+        file_table::{get_file_fast, FdFlags, FileDesc},
+        inode_handle::InodeHandle,
+        mqueue::{
+            self, MqueueInode, Notification, DFLT_MSGMAX, DFLT_MSGSIZEMAX, HARD_MSGMAX,
+            HARD_MSGSIZEMAX,
+        },
+        utils::{AccessMode, CreationFlags, InodeMode, Permission, StatusFlags},
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        signal::{
+            c_types::{sigevent_t, SigNotify},
+            sig_num::SigNum,
+        },
+    },
+    syscall::constants::MAX_FILENAME_LEN,
+    time::{clocks::RealTimeClock, timespec_t},
+};
+
+pub fn sys_mq_open(
+    name_addr: Vaddr,
+    flags: u32,
+    mode: u16,
+    attr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!(
+        "name = {:?}, flags = {}, mode = {:o}, attr_addr = 0x{:x}",
+        name, flags, mode, attr_addr
+    );
+
+    let name = name.to_string_lossy();
+    check_queue_name(&name)?;
+
+    let access_mode = AccessMode::from_u32(flags)?;
+    let creation_flags = CreationFlags::from_bits_truncate(flags);
+    let status_flags = StatusFlags::from_bits_truncate(flags) & StatusFlags::O_NONBLOCK;
+
+    let root = mqueue::root_dentry();
+    let inode_handle = match root.lookup(&name) {
+        Ok(dentry) => {
+            if creation_flags.contains(CreationFlags::O_CREAT | CreationFlags::O_EXCL) {
+                return_errno_with_message!(Errno::EEXIST, "the message queue already exists");
+            }
+            InodeHandle::new(dentry, access_mode, status_flags)?
+        }
+        Err(err) if err.error() == Errno::ENOENT => {
+            if !creation_flags.contains(CreationFlags::O_CREAT) {
+                return Err(err);
+            }
+
+            let (max_messages, max_msg_size) = if attr_addr == 0 {
+                (DFLT_MSGMAX, DFLT_MSGSIZEMAX)
+            } else {
+                let attr = ctx.user_space().read_val::<c_mq_attr>(attr_addr)?;
+                check_queue_limits(&attr, ctx)?
+            };
+
+            root.inode().check_permission(Permission::MAY_WRITE)?;
+            let credentials = ctx.posix_thread.credentials();
+            let mode = mode & !ctx.posix_thread.fs().umask().read().get();
+            mqueue::singleton().create_queue(
+                &name,
+                InodeMode::from_bits_truncate(mode),
+                credentials.fsuid(),
+                credentials.fsgid(),
+                max_messages,
+                max_msg_size,
+            )?;
+
+            // The permission is not checked for the queue created by ourselves.
+            InodeHandle::new_unchecked_access(root.lookup(&name)?, access_mode, status_flags)?
+        }
+        Err(err) => return Err(err),
+    };
+
+    let fd_flags = if creation_flags.contains(CreationFlags::O_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    let file_table = ctx.thread_local.borrow_file_table();
+    let fd = file_table
+        .unwrap()
+        .write()
+        .insert(Arc::new(inode_handle), fd_flags);
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_mq_unlink(name_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!("name = {:?}", name);
+
+    let name = name.to_string_lossy();
+    check_queue_name(&name)?;
+
+    let root = mqueue::root_dentry();
+    root.inode().check_permission(Permission::MAY_WRITE)?;
+    root.unlink(&name)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_timedsend(
+    mqdes: FileDesc,
+    msg_addr: Vaddr,
+    msg_len: usize,
+    msg_prio: u32,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, msg_addr = 0x{:x}, msg_len = {}, msg_prio = {}, abs_timeout_addr = 0x{:x}",
+        mqdes, msg_addr, msg_len, msg_prio, abs_timeout_addr
+    );
+
+    let timeout = read_timeout(abs_timeout_addr, ctx)?;
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes).into_owned();
+    drop(file_table);
+
+    let (inode_handle, mqueue_inode) = downcast_queue(&file)?;
+    if !inode_handle.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "the message queue is not open for writing");
+    }
+
+    let queue = mqueue_inode.queue();
+    if msg_len > queue.max_msg_size() {
+        return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+    }
+    let mut data = vec![0u8; msg_len];
+    ctx.user_space()
+        .read_bytes(msg_addr, &mut VmWriter::from(data.as_mut_slice()))?;
+
+    let is_nonblocking = inode_handle
+        .status_flags()
+        .contains(StatusFlags::O_NONBLOCK);
+    queue.send(data, msg_prio, is_nonblocking, timeout.as_ref())?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_timedreceive(
+    mqdes: FileDesc,
+    msg_addr: Vaddr,
+    msg_len: usize,
+    msg_prio_addr: Vaddr,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, msg_addr = 0x{:x}, msg_len = {}, msg_prio_addr = 0x{:x}, abs_timeout_addr = 0x{:x}",
+        mqdes, msg_addr, msg_len, msg_prio_addr, abs_timeout_addr
+    );
+
+    let timeout = read_timeout(abs_timeout_addr, ctx)?;
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes).into_owned();
+    drop(file_table);
+
+    let (inode_handle, mqueue_inode) = downcast_queue(&file)?;
+    if !inode_handle.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "the message queue is not open for reading");
+    }
+
+    let queue = mqueue_inode.queue();
+    if msg_len < queue.max_msg_size() {
+        return_errno_with_message!(
+            Errno::EMSGSIZE,
+            "the buffer is smaller than the message size"
+        );
+    }
+
+    let is_nonblocking = inode_handle
+        .status_flags()
+        .contains(StatusFlags::O_NONBLOCK);
+    let (data, priority) = queue.receive(is_nonblocking, timeout.as_ref())?;
+
+    let user_space = ctx.user_space();
+    user_space.write_bytes(msg_addr, &mut VmReader::from(data.as_slice()))?;
+    if msg_prio_addr != 0 {
+        user_space.write_val(msg_prio_addr, &priority)?;
+    }
+
+    Ok(SyscallReturn::Return(data.len() as _))
+}
+
+pub fn sys_mq_notify(
+    mqdes: FileDesc,
+    sigevent_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("mqdes = {}, sigevent_addr = 0x{:x}", mqdes, sigevent_addr);
+
+    let notification = if sigevent_addr == 0 {
+        None
+    } else {
+        let sig_event = ctx.user_space().read_val::<sigevent_t>(sigevent_addr)?;
+        let signal = match SigNotify::try_from(sig_event.sigev_notify)? {
+            SigNotify::SIGEV_NONE => None,
+            SigNotify::SIGEV_SIGNAL => Some(SigNum::try_from(sig_event.sigev_signo as u8)?),
+            // TODO: Support `SIGEV_THREAD`, which C libraries implement with
+            // netlink sockets.
+            SigNotify::SIGEV_THREAD | SigNotify::SIGEV_THREAD_ID => {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the notification method is not supported"
+                )
+            }
+        };
+        Some(Notification {
+            pid: ctx.process.pid(),
+            signal,
+        })
+    };
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes);
+    let (_, mqueue_inode) = downcast_queue(&file)?;
+    mqueue_inode
+        .queue()
+        .set_notification(ctx.process.pid(), notification)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_getsetattr(
+    mqdes: FileDesc,
+    new_attr_addr: Vaddr,
+    old_attr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, new_attr_addr = 0x{:x}, old_attr_addr = 0x{:x}",
+        mqdes, new_attr_addr, old_attr_addr
+    );
+
+    let user_space = ctx.user_space();
+    let new_attr = if new_attr_addr != 0 {
+        Some(user_space.read_val::<c_mq_attr>(new_attr_addr)?)
+    } else {
+        None
+    };
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes);
+    let (inode_handle, mqueue_inode) = downcast_queue(&file)?;
+    let queue = mqueue_inode.queue();
+
+    let status_flags = inode_handle.status_flags();
+    if old_attr_addr != 0 {
+        let old_attr = c_mq_attr {
+            mq_flags: (status_flags & StatusFlags::O_NONBLOCK).bits() as i64,
+            mq_maxmsg: queue.max_messages() as i64,
+            mq_msgsize: queue.max_msg_size() as i64,
+            mq_curmsgs: queue.num_messages() as i64,
+            __reserved: [0; 4],
+        };
+        user_space.write_val(old_attr_addr, &old_attr)?;
+    }
+
+    // Only `O_NONBLOCK` can be changed.
+    if let Some(new_attr) = new_attr {
+        let new_flags = StatusFlags::from_bits_truncate(new_attr.mq_flags as u32);
+        let status_flags =
+            (status_flags - StatusFlags::O_NONBLOCK) | (new_flags & StatusFlags::O_NONBLOCK);
+        inode_handle.set_status_flags(status_flags)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Checks the name of a message queue.
+///
+/// C libraries remove the leading slash of the name before passing it to the
+/// kernel, so the name must not contain any slashes.
+fn check_queue_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "the queue name is empty");
+    }
+    if name.contains('/') || name == "." || name == ".." {
+        return_errno_with_message!(Errno::EACCES, "the queue name is invalid");
+    }
+    Ok(())
+}
+
+/// Checks the limits of a message queue to be created.
+///
+/// Returns the maximum number of messages and the maximum size of a message.
+fn check_queue_limits(attr: &c_mq_attr, ctx: &Context) -> Result<(usize, usize)> {
+    if attr.mq_maxmsg <= 0 || attr.mq_msgsize <= 0 {
+        return_errno_with_message!(Errno::EINVAL, "the queue limits must be positive");
+    }
+
+    let max_messages = attr.mq_maxmsg as usize;
+    let max_msg_size = attr.mq_msgsize as usize;
+    let (msgmax, msgsizemax) = if ctx
+        .posix_thread
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::SYS_RESOURCE)
+    {
+        (HARD_MSGMAX, HARD_MSGSIZEMAX)
+    } else {
+        (DFLT_MSGMAX, DFLT_MSGSIZEMAX)
+    };
+    if max_messages > msgmax || max_msg_size > msgsizemax {
+        return_errno_with_message!(Errno::EINVAL, "the queue limits are too large");
+    }
+
+    Ok((max_messages, max_msg_size))
+}
+
+/// Reads the absolute timeout and converts it to the time remaining.
+fn read_timeout(abs_timeout_addr: Vaddr, ctx: &Context) -> Result<Option<Duration>> {
+    if abs_timeout_addr == 0 {
+        return Ok(None);
+    }
+
+    let timespec = ctx.user_space().read_val::<timespec_t>(abs_timeout_addr)?;
+    let abs_timeout = Duration::try_from(timespec)?;
+    let now = RealTimeClock::get().read_time();
+    Ok(Some(abs_timeout.saturating_sub(now)))
+}
+
+fn downcast_queue(file: &Arc<dyn FileLike>) -> Result<(&InodeHandle, &MqueueInode)> {
+    let not_queue_error = || Error::with_message(Errno::EBADF, "the file is not a message queue");
+
+    let inode_handle = file
+        .downcast_ref::<InodeHandle>()
+        .ok_or_else(not_queue_error)?;
+    let mqueue_inode = inode_handle
+        .dentry()
+        .inode()
+        .downcast_ref::<MqueueInode>()
+        .ok_or_else(not_queue_error)?;
+    Ok((inode_handle, mqueue_inode))
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct c_mq_attr {
+    mq_flags: i64,
+    mq_maxmsg: i64,
+    mq_msgsize: i64,
+    mq_curmsgs: i64,
+    __reserved: [i64; 4],
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::{prelude::*, vm::vmar::RemapTarget};
+
+pub fn sys_mremap(
+    old_addr: Vaddr,
+    old_size: usize,
+    new_size: usize,
+    flags: i32,
+    new_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = MremapFlags::from_bits(flags).ok_or(Errno::EINVAL)?;
+    let new_addr = do_sys_mremap(old_addr, old_size, new_size, flags, new_addr, ctx)?;
+    Ok(SyscallReturn::Return(new_addr as _))
+}
+
+fn do_sys_mremap(
+    old_addr: Vaddr,
+    old_size: usize,
+    new_size: usize,
+    flags: MremapFlags,
+    new_addr: Vaddr,
+    ctx: &Context,
+) -> Result<Vaddr> {
+    debug!(
+        "old_addr = 0x{:x}, old_size = 0x{:x}, new_size = 0x{:x}, flags = {:?}, new_addr = 0x{:x}",
+        old_addr, old_size, new_size, flags, new_addr,
+    );
+
+    if old_addr % PAGE_SIZE != 0 {
+        return_errno_with_message!(Errno::EINVAL, "`old_addr` must be page-aligned");
+    }
+    if flags.contains(MremapFlags::MREMAP_FIXED) && !flags.contains(MremapFlags::MREMAP_MAYMOVE) {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "`MREMAP_FIXED` specified without also specifying `MREMAP_MAYMOVE`"
+        );
+    }
+    if flags.contains(MremapFlags::MREMAP_DONTUNMAP)
+        && (!flags.contains(MremapFlags::MREMAP_MAYMOVE) || old_size != new_size)
+    {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "`MREMAP_DONTUNMAP` requires `MREMAP_MAYMOVE` and an unchanged size"
+        );
+    }
+    if new_size == 0 {
+        return_errno_with_message!(Errno::EINVAL, "`new_size` cannot be zero");
+    }
+    if old_size == 0 {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "duplicating a mapping with zero `old_size` is not supported"
+        );
+    }
+    if old_size > isize::MAX as usize || new_size > isize::MAX as usize {
+        return_errno_with_message!(Errno::ENOMEM, "the size is too large");
+    }
+
+    let old_size = old_size.align_up(PAGE_SIZE);
+    let new_size = new_size.align_up(PAGE_SIZE);
+
+    let target = if flags.contains(MremapFlags::MREMAP_FIXED) {
+        if new_addr % PAGE_SIZE != 0 {
+            return_errno_with_message!(Errno::EINVAL, "`new_addr` must be page-aligned");
+        }
+        RemapTarget::Fixed(new_addr)
+    } else if flags.contains(MremapFlags::MREMAP_MAYMOVE) {
+        RemapTarget::MayMove
+    } else {
+        RemapTarget::InPlace
+    };
+    let keep_old = flags.contains(MremapFlags::MREMAP_DONTUNMAP);
+
+    let user_space = ctx.user_space();
+    let root_vmar = user_space.root_vmar();
+    root_vmar.remap(old_addr, old_size, new_size, target, keep_old)
+}
+
+bitflags! {
+    struct MremapFlags: i32 {
+        const MREMAP_MAYMOVE = 1 << 0;
+        const MREMAP_FIXED = 1 << 1;
+        const MREMAP_DONTUNMAP = 1 << 2;
+    }
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        c_ipc64_perm, key_t,
+        msg::system_v::msg_queue::{
+            get_queue, msg_queues, remove_queue, MsgQueueStat, MSGMAX, MSGMNB, MSGMNI,
+        },
+        IpcNamespace, PermissionMode,
+    },
+    prelude::*,
+};
+
+pub fn sys_msgctl(msqid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    // Some C libraries pass `IPC_64` to request the new data structures,
+    // which are the only ones supported.
+    const IPC_64: i32 = 0x100;
+    let cmd = MsgCtlCmd::try_from(cmd & !IPC_64)?;
+    debug!("msqid = {}, cmd = {:?}, buf = 0x{:x}", msqid, cmd, buf);
+
+    let credentials = ctx.posix_thread.credentials();
+    let user_space = ctx.user_space();
+    let ns_proxy = ctx.posix_thread.ns_proxy();
+    let ipc_ns = ns_proxy.ipc_ns();
+
+    match cmd {
+        MsgCtlCmd::IPC_RMID => {
+            remove_queue(ipc_ns, msqid, &credentials)?;
+        }
+        MsgCtlCmd::IPC_SET => {
+            let msqid_ds: c_msqid_ds = user_space.read_val(buf)?;
+            let perm = &msqid_ds.msg_perm;
+            get_queue(ipc_ns, msqid)?.set_owner_and_mode(
+                &credentials,
+                perm.uid(),
+                perm.gid(),
+                perm.mode(),
+                msqid_ds.msg_qbytes as usize,
+            )?;
+        }
+        MsgCtlCmd::IPC_STAT | MsgCtlCmd::MSG_STAT | MsgCtlCmd::MSG_STAT_ANY => {
+            // The IDs of queues are their indices, so `MSG_STAT` and
+            // `MSG_STAT_ANY` work the same as `IPC_STAT`.
+            let queue = get_queue(ipc_ns, msqid)?;
+            if !matches!(cmd, MsgCtlCmd::MSG_STAT_ANY) {
+                queue.check_permission(&credentials, PermissionMode::READ)?;
+            }
+            user_space.write_val(buf, &c_msqid_ds::from(queue.stat()))?;
+
+            if !matches!(cmd, MsgCtlCmd::IPC_STAT) {
+                return Ok(SyscallReturn::Return(queue.id() as _));
+            }
+        }
+        MsgCtlCmd::IPC_INFO | MsgCtlCmd::MSG_INFO => {
+            let mut msginfo = c_msginfo {
+                msgpool: (MSGMNI * MSGMNB / 1024) as i32,
+                msgmap: MSGMNB as i32,
+                msgmax: MSGMAX as i32,
+                msgmnb: MSGMNB as i32,
+                msgmni: MSGMNI as i32,
+                msgssz: MSGSSZ,
+                msgtql: MSGMNB as i32,
+                msgseg: u16::MAX,
+                __pad: 0,
+            };
+            // Like Linux, `MSG_INFO` reuses some fields to report the
+            // resources that are in use.
+            if matches!(cmd, MsgCtlCmd::MSG_INFO) {
+                let stats: Vec<_> = msg_queues(ipc_ns)
+                    .iter()
+                    .map(|queue| queue.stat())
+                    .collect();
+                msginfo.msgpool = stats.len() as i32;
+                msginfo.msgmap = stats.iter().map(|stat| stat.num_messages).sum::<usize>() as i32;
+                msginfo.msgtql = stats.iter().map(|stat| stat.num_bytes).sum::<usize>() as i32;
+            }
+            user_space.write_val(buf, &msginfo)?;
+
+            return Ok(SyscallReturn::Return(max_id(ipc_ns) as _));
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The size of message segments, which is only reported to the user space.
+const MSGSSZ: i32 = 16;
+
+/// Returns the largest ID in use, or zero if there are no queues.
+fn max_id(ipc_ns: &IpcNamespace) -> key_t {
+    msg_queues(ipc_ns).last().map_or(0, |queue| queue.id())
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, TryFromInt)]
+#[expect(non_camel_case_types)]
+enum MsgCtlCmd {
+    IPC_RMID = 0,
+    IPC_SET = 1,
+    IPC_STAT = 2,
+    IPC_INFO = 3,
+
+    MSG_STAT = 11,
+    MSG_INFO = 12,
+    MSG_STAT_ANY = 13,
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct c_msqid_ds {
+    msg_perm: c_ipc64_perm,
+    msg_stime: i64,
+    msg_rtime: i64,
+    msg_ctime: i64,
+    msg_cbytes: u64,
+    msg_qnum: u64,
+    msg_qbytes: u64,
+    msg_lspid: i32,
+    msg_lrpid: i32,
+    __unused4: u64,
+    __unused5: u64,
+}
+
+impl From<MsgQueueStat> for c_msqid_ds {
+    fn from(stat: MsgQueueStat) -> Self {
+        Self {
+            msg_perm: c_ipc64_perm::new(
+                stat.key, stat.uid, stat.gid, stat.cuid, stat.cgid, stat.mode,
+            ),
+            msg_stime: stat.stime as i64,
+            msg_rtime: stat.rtime as i64,
+            msg_ctime: stat.ctime as i64,
+            msg_cbytes: stat.num_bytes as u64,
+            msg_qnum: stat.num_messages as u64,
+            msg_qbytes: stat.max_bytes as u64,
+            msg_lspid: stat.lspid as i32,
+            msg_lrpid: stat.lrpid as i32,
+            __unused4: 0,
+            __unused5: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct c_msginfo {
+    msgpool: i32,
+    msgmap: i32,
+    msgmax: i32,
+    msgmnb: i32,
+    msgmni: i32,
+    msgssz: i32,
+    msgtql: i32,
+    msgseg: u16,
+    __pad: u16,
+}
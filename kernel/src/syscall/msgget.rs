@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{msg::system_v::msg_queue::get_or_create_queue, IpcFlags},
+    prelude::*,
+};
+
+pub fn sys_msgget(key: i32, msgflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = IpcFlags::from_bits_truncate(msgflg as u32);
+    let mode = (msgflg as u32 & 0o777) as u16;
+    debug!("key = {}, flags = {:?}, mode = {:o}", key, flags, mode);
+
+    let credentials = ctx.posix_thread.credentials();
+    let id = get_or_create_queue(
+        ctx.posix_thread.ns_proxy().ipc_ns(),
+        key,
+        flags,
+        mode,
+        &credentials,
+    )?;
+
+    Ok(SyscallReturn::Return(id as _))
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        msg::system_v::{msg_queue::get_queue, MsgRcvFlags},
+        PermissionMode,
+    },
+    prelude::*,
+};
+
+pub fn sys_msgrcv(
+    msqid: i32,
+    msgp: Vaddr,
+    msgsz: isize,
+    msgtyp: i64,
+    msgflg: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = MsgRcvFlags::from_bits_truncate(msgflg as u32);
+    debug!(
+        "msqid = {}, msgp = 0x{:x}, msgsz = {}, msgtyp = {}, flags = {:?}",
+        msqid, msgp, msgsz, msgtyp, flags
+    );
+
+    let msgsz = usize::try_from(msgsz)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the message size is negative"))?;
+    let queue = get_queue(ctx.posix_thread.ns_proxy().ipc_ns(), msqid)?;
+    queue.check_permission(&ctx.posix_thread.credentials(), PermissionMode::READ)?;
+
+    let message = queue.receive(msgtyp, msgsz, flags, ctx.process.pid())?;
+
+    // The message starts with its type, followed by its data.
+    let user_space = ctx.user_space();
+    user_space.write_val(msgp, &message.type_())?;
+    user_space.write_bytes(msgp + size_of::<i64>(), &mut VmReader::from(message.data()))?;
+
+    Ok(SyscallReturn::Return(message.data().len() as _))
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        msg::system_v::msg_queue::{get_queue, Message, MSGMAX},
+        IpcFlags, PermissionMode,
+    },
+    prelude::*,
+};
+
+pub fn sys_msgsnd(
+    msqid: i32,
+    msgp: Vaddr,
+    msgsz: isize,
+    msgflg: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = IpcFlags::from_bits_truncate(msgflg as u32);
+    debug!(
+        "msqid = {}, msgp = 0x{:x}, msgsz = {}, flags = {:?}",
+        msqid, msgp, msgsz, flags
+    );
+
+    let msgsz = usize::try_from(msgsz)
+        .ok()
+        .filter(|msgsz| *msgsz <= MSGMAX)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the message size is invalid"))?;
+    let queue = get_queue(ctx.posix_thread.ns_proxy().ipc_ns(), msqid)?;
+    queue.check_permission(&ctx.posix_thread.credentials(), PermissionMode::WRITE)?;
+
+    // The message starts with its type, followed by its data.
+    let user_space = ctx.user_space();
+    let type_ = user_space.read_val::<i64>(msgp)?;
+    let mut data = vec![0u8; msgsz];
+    user_space.read_bytes(
+        msgp + size_of::<i64>(),
+        &mut VmWriter::from(data.as_mut_slice()),
+    )?;
+    let message = Message::new(type_, data)?;
+
+    queue.send(
+        message,
+        flags.contains(IpcFlags::IPC_NOWAIT),
+        ctx.process.pid(),
+    )?;
+
+    Ok(SyscallReturn::Return(0))
+}
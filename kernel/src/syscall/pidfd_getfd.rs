@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file_table::{get_file_fast, FdFlags, FileDesc},
+    prelude::*,
+    process::{posix_thread::AsPosixThread, ptrace::check_may_access, PidFile},
+};
+
+pub fn sys_pidfd_getfd(
+    pidfd: FileDesc,
+    target_fd: FileDesc,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "pidfd = {}, target_fd = {}, flags = 0x{:x}",
+        pidfd, target_fd, flags
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "unknown flags");
+    }
+
+    let process = {
+        let mut file_table = ctx.thread_local.borrow_file_table_mut();
+        let file = get_file_fast!(&mut file_table, pidfd);
+        let pid_file = file
+            .downcast_ref::<PidFile>()
+            .ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a PID file"))?;
+        pid_file.process().clone()
+    };
+
+    check_may_access(ctx, &process.main_thread())?;
+
+    let file = {
+        let main_thread = process.main_thread();
+        let file_table = main_thread.as_posix_thread().unwrap().file_table().lock();
+        let file_table = file_table
+            .as_ref()
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process has exited"))?;
+        file_table.read().get_file(target_fd)?.clone()
+    };
+
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        // Like Linux, the duplicated file descriptor is always closed on `execve`.
+        file_table_locked.insert(file, FdFlags::CLOEXEC)
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file_table::FdFlags,
+    prelude::*,
+    process::{posix_thread::thread_table, process_table, Pid, PidFile},
+};
+
+pub fn sys_pidfd_open(pid: Pid, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = PidfdFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+    debug!("pid = {}, flags = {:?}", pid, flags);
+
+    if pid as i32 <= 0 {
+        return_errno_with_message!(Errno::EINVAL, "the PID is not positive");
+    }
+
+    let global_pid = ctx
+        .process
+        .pid_ns()
+        .global_tid(pid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?;
+    let Some(process) = process_table::get_process(global_pid) else {
+        if thread_table::get_thread(global_pid).is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the thread is not a thread group leader");
+        }
+        return_errno_with_message!(Errno::ESRCH, "the process does not exist");
+    };
+
+    let pid_file = PidFile::new(process, flags.contains(PidfdFlags::PIDFD_NONBLOCK));
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        // Like Linux, the PID file is always closed on `execve`.
+        file_table_locked.insert(Arc::new(pid_file), FdFlags::CLOEXEC)
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+bitflags! {
+    struct PidfdFlags: u32 {
+        const PIDFD_NONBLOCK = 0o4000;
+    }
+}
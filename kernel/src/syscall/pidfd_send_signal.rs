@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::file_table::{get_file_fast, FileDesc},
+    prelude::*,
+    process::{
+        kill_pid_file,
+        signal::{
+            c_types::siginfo_t,
+            constants::SI_TKILL,
+            sig_num::SigNum,
+            signals::user::{UserSignal, UserSignalKind},
+        },
+        PidFile,
+    },
+};
+
+pub fn sys_pidfd_send_signal(
+    pidfd: FileDesc,
+    sig_num: u8,
+    siginfo_addr: Vaddr,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "pidfd = {}, sig_num = {}, siginfo_addr = 0x{:x}, flags = 0x{:x}",
+        pidfd, sig_num, siginfo_addr, flags
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "unknown flags");
+    }
+
+    let sig_num = if sig_num == 0 {
+        None
+    } else {
+        Some(SigNum::try_from(sig_num)?)
+    };
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, pidfd);
+    let pid_file = file
+        .downcast_ref::<PidFile>()
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a PID file"))?;
+
+    let kind = if siginfo_addr == 0 {
+        UserSignalKind::Kill
+    } else {
+        let siginfo = ctx.user_space().read_val::<siginfo_t>(siginfo_addr)?;
+        if sig_num.map_or(0, |sig_num| sig_num.as_u8() as i32) != siginfo.si_signo {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the signal number does not match the one in the signal information"
+            );
+        }
+        // Like `rt_sigqueueinfo`, a process cannot pretend to be the kernel or `kill` when
+        // sending signals to other processes.
+        if !core::ptr::eq(pid_file.process().as_ref(), ctx.process)
+            && (siginfo.si_code >= 0 || siginfo.si_code == SI_TKILL)
+        {
+            return_errno_with_message!(Errno::EPERM, "the signal code is not permitted");
+        }
+        // FIXME: Deliver the other fields in the signal information to the target process.
+        UserSignalKind::Sigqueue
+    };
+
+    let signal = sig_num.map(|sig_num| {
+        let pid = ctx.process.pid();
+        let uid = ctx.posix_thread.credentials().ruid();
+        UserSignal::new(sig_num, kind, pid, uid)
+    });
+    kill_pid_file(pid_file, signal, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::mem::size_of;
+
+use ostd::cpu::context::UserContext;
+
+use super::SyscallReturn;
+use crate::{
+    arch::ptrace::{set_single_step, PtraceRegs},
+    prelude::*,
+    process::{
+        posix_thread::{thread_table, AsPosixThread},
+        ptrace::{attach, check_may_access, detach, PtraceOptions, PtraceStop, ResumeMode},
+        signal::{
+            constants::{SIGKILL, SIGSTOP},
+            sig_num::SigNum,
+            signals::kernel::KernelSignal,
+        },
+    },
+    thread::{Thread, Tid},
+};
+
+pub fn sys_ptrace(
+    request: u32,
+    pid: Tid,
+    addr: Vaddr,
+    data: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let request = PtraceRequest::try_from(request)
+        .map_err(|_| Error::with_message(Errno::EIO, "invalid ptrace request"))?;
+    debug!(
+        "request = {:?}, pid = {}, addr = 0x{:x}, data = 0x{:x}",
+        request, pid, addr, data
+    );
+
+    if request == PtraceRequest::PTRACE_TRACEME {
+        let Some(parent) = ctx.process.parent().lock().process().upgrade() else {
+            return_errno_with_message!(Errno::EPERM, "the process has no parent");
+        };
+        attach(&parent, &current_thread!(), false, PtraceOptions::empty())?;
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    let tracee = thread_table::get_thread(pid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the thread does not exist"))?;
+    let tracee_posix_thread = tracee.as_posix_thread().unwrap();
+    let ptrace = tracee_posix_thread.ptrace();
+    let tracer = ctx.process;
+
+    match request {
+        PtraceRequest::PTRACE_TRACEME => unreachable!(),
+        PtraceRequest::PTRACE_ATTACH => {
+            check_attach_permission(&tracee, ctx)?;
+            attach(
+                &ctx.posix_thread.process(),
+                &tracee,
+                false,
+                PtraceOptions::empty(),
+            )?;
+            tracee_posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGSTOP)));
+        }
+        PtraceRequest::PTRACE_SEIZE => {
+            if addr != 0 {
+                return_errno_with_message!(Errno::EIO, "the address must be zero");
+            }
+            let options = PtraceOptions::from_bits(data as u32)
+                .ok_or_else(|| Error::with_message(Errno::EIO, "invalid ptrace options"))?;
+            check_attach_permission(&tracee, ctx)?;
+            attach(&ctx.posix_thread.process(), &tracee, true, options)?;
+        }
+        PtraceRequest::PTRACE_DETACH => {
+            detach(tracer, &tracee, parse_signal(data)?)?;
+        }
+        PtraceRequest::PTRACE_PEEKTEXT | PtraceRequest::PTRACE_PEEKDATA => {
+            ptrace.with_stop(tracer, |_| Ok(()))?;
+            let mut word = [0u8; size_of::<usize>()];
+            read_tracee_memory(&tracee, addr, &mut word)?;
+            ctx.user_space()
+                .write_val(data, &usize::from_ne_bytes(word))?;
+        }
+        PtraceRequest::PTRACE_POKETEXT | PtraceRequest::PTRACE_POKEDATA => {
+            ptrace.with_stop(tracer, |_| Ok(()))?;
+            write_tracee_memory(&tracee, addr, &data.to_ne_bytes())?;
+        }
+        PtraceRequest::PTRACE_PEEKUSER => {
+            let word = ptrace.with_stop(tracer, |stop| read_user_word(stop.regs(), addr))?;
+            ctx.user_space().write_val(data, &word)?;
+        }
+        PtraceRequest::PTRACE_POKEUSER => {
+            ptrace.with_stop(tracer, |stop| {
+                let mut regs = *stop.regs();
+                write_user_word(&mut regs, addr, data)?;
+                set_regs(stop, regs)
+            })?;
+        }
+        PtraceRequest::PTRACE_GETREGS => {
+            let regs = ptrace.with_stop(tracer, |stop| Ok(*stop.regs()))?;
+            ctx.user_space().write_val(data, &regs)?;
+        }
+        PtraceRequest::PTRACE_SETREGS => {
+            let regs = ctx.user_space().read_val::<PtraceRegs>(data)?;
+            ptrace.with_stop(tracer, |stop| set_regs(stop, regs))?;
+        }
+        PtraceRequest::PTRACE_GETFPREGS => {
+            let fp_regs = ptrace.with_stop(tracer, |stop| Ok(stop.fp_regs()?.to_vec()))?;
+            ctx.user_space()
+                .write_bytes(data, &mut VmReader::from(fp_regs.as_slice()))?;
+        }
+        PtraceRequest::PTRACE_GETREGSET => {
+            let mut iov = ctx.user_space().read_val::<iovec_t>(data)?;
+            let bytes = ptrace.with_stop(tracer, |stop| match addr {
+                NT_PRSTATUS => Ok(stop.regs().as_bytes().to_vec()),
+                NT_PRFPREG => Ok(stop.fp_regs()?.to_vec()),
+                _ => return_errno_with_message!(Errno::EINVAL, "the register set is not supported"),
+            })?;
+            iov.len = iov.len.min(bytes.len());
+            ctx.user_space()
+                .write_bytes(iov.base, &mut VmReader::from(&bytes[..iov.len]))?;
+            ctx.user_space().write_val(data, &iov)?;
+        }
+        PtraceRequest::PTRACE_SETREGSET => {
+            if addr != NT_PRSTATUS {
+                return_errno_with_message!(Errno::EINVAL, "the register set is not supported");
+            }
+            let iov = ctx.user_space().read_val::<iovec_t>(data)?;
+            if iov.len < size_of::<PtraceRegs>() {
+                return_errno_with_message!(Errno::EINVAL, "the register set is incomplete");
+            }
+            let regs = ctx.user_space().read_val::<PtraceRegs>(iov.base)?;
+            ptrace.with_stop(tracer, |stop| set_regs(stop, regs))?;
+        }
+        PtraceRequest::PTRACE_GETSIGINFO => {
+            let siginfo = ptrace.with_stop(tracer, |stop| Ok(*stop.siginfo()))?;
+            ctx.user_space().write_val(data, &siginfo)?;
+        }
+        PtraceRequest::PTRACE_SETOPTIONS => {
+            ptrace.set_options(tracer, parse_options(data)?)?;
+        }
+        PtraceRequest::PTRACE_GETEVENTMSG => {
+            let event_msg = ptrace.event_msg(tracer)?;
+            ctx.user_space().write_val(data, &event_msg)?;
+        }
+        PtraceRequest::PTRACE_CONT => {
+            ptrace.resume(tracer, ResumeMode::Continue, parse_signal(data)?)?;
+        }
+        PtraceRequest::PTRACE_SYSCALL => {
+            ptrace.resume(tracer, ResumeMode::Syscall, parse_signal(data)?)?;
+        }
+        PtraceRequest::PTRACE_SINGLESTEP => {
+            // Check whether single-stepping is supported by the architecture.
+            set_single_step(&mut UserContext::default(), true)?;
+            ptrace.resume(tracer, ResumeMode::SingleStep, parse_signal(data)?)?;
+        }
+        PtraceRequest::PTRACE_KILL => {
+            ptrace.with_stop(tracer, |_| Ok(()))?;
+            tracee_posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+        }
+        PtraceRequest::PTRACE_INTERRUPT => {
+            ptrace.interrupt(tracer)?;
+        }
+        PtraceRequest::PTRACE_SETFPREGS
+        | PtraceRequest::PTRACE_SETSIGINFO
+        | PtraceRequest::PTRACE_LISTEN => {
+            return_errno_with_message!(Errno::EIO, "the ptrace request is not supported");
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Checks whether the current thread can attach to `tracee`.
+fn check_attach_permission(tracee: &Thread, ctx: &Context) -> Result<()> {
+    let tracee_posix_thread = tracee.as_posix_thread().unwrap();
+    if core::ptr::eq(tracee_posix_thread.process().as_ref(), ctx.process) {
+        return_errno_with_message!(Errno::EPERM, "the thread belongs to the current process");
+    }
+
+    check_may_access(ctx, tracee)
+}
+
+fn read_tracee_memory(tracee: &Thread, addr: Vaddr, buf: &mut [u8]) -> Result<()> {
+    let process = tracee.as_posix_thread().unwrap().process();
+    let root_vmar = process.lock_root_vmar();
+    root_vmar.unwrap().read_remote(addr, buf)
+}
+
+fn write_tracee_memory(tracee: &Thread, addr: Vaddr, buf: &[u8]) -> Result<()> {
+    let process = tracee.as_posix_thread().unwrap().process();
+    let root_vmar = process.lock_root_vmar();
+    root_vmar.unwrap().write_remote(addr, buf)
+}
+
+/// Reads a word at `offset` in the `struct user` of the tracee.
+///
+/// Only the registers at the start of `struct user` are supported. The other fields (e.g., the
+/// debug registers) are read as zeros.
+fn read_user_word(regs: &PtraceRegs, offset: usize) -> Result<usize> {
+    if offset % size_of::<usize>() != 0 {
+        return_errno_with_message!(Errno::EIO, "the offset is not aligned");
+    }
+
+    let bytes = regs.as_bytes();
+    let Some(word) = bytes.get(offset..offset + size_of::<usize>()) else {
+        return Ok(0);
+    };
+    Ok(usize::from_ne_bytes(word.try_into().unwrap()))
+}
+
+/// Writes a word at `offset` in the `struct user` of the tracee.
+fn write_user_word(regs: &mut PtraceRegs, offset: usize, word: usize) -> Result<()> {
+    if offset % size_of::<usize>() != 0 {
+        return_errno_with_message!(Errno::EIO, "the offset is not aligned");
+    }
+
+    let bytes = regs.as_bytes_mut();
+    let Some(dst) = bytes.get_mut(offset..offset + size_of::<usize>()) else {
+        return_errno_with_message!(Errno::EIO, "the offset is not supported");
+    };
+    dst.copy_from_slice(&word.to_ne_bytes());
+    Ok(())
+}
+
+/// Sets the registers of the stopped tracee after checking them.
+fn set_regs(stop: &mut PtraceStop, regs: PtraceRegs) -> Result<()> {
+    regs.check_writable(stop.regs())?;
+    *stop.regs_mut() = regs;
+    Ok(())
+}
+
+fn parse_options(data: usize) -> Result<PtraceOptions> {
+    PtraceOptions::from_bits(data as u32)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid ptrace options"))
+}
+
+fn parse_signal(data: usize) -> Result<Option<SigNum>> {
+    if data == 0 {
+        return Ok(None);
+    }
+
+    let sig_num =
+        u8::try_from(data).map_err(|_| Error::with_message(Errno::EIO, "invalid signal number"))?;
+    Ok(Some(SigNum::try_from(sig_num).map_err(|_| {
+        Error::with_message(Errno::EIO, "invalid signal number")
+    })?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+#[repr(u32)]
+#[expect(non_camel_case_types)]
+enum PtraceRequest {
+    PTRACE_TRACEME = 0,
+    PTRACE_PEEKTEXT = 1,
+    PTRACE_PEEKDATA = 2,
+    PTRACE_PEEKUSER = 3,
+    PTRACE_POKETEXT = 4,
+    PTRACE_POKEDATA = 5,
+    PTRACE_POKEUSER = 6,
+    PTRACE_CONT = 7,
+    PTRACE_KILL = 8,
+    PTRACE_SINGLESTEP = 9,
+    PTRACE_GETREGS = 12,
+    PTRACE_SETREGS = 13,
+    PTRACE_GETFPREGS = 14,
+    PTRACE_SETFPREGS = 15,
+    PTRACE_ATTACH = 16,
+    PTRACE_DETACH = 17,
+    PTRACE_SYSCALL = 24,
+    PTRACE_SETOPTIONS = 0x4200,
+    PTRACE_GETEVENTMSG = 0x4201,
+    PTRACE_GETSIGINFO = 0x4202,
+    PTRACE_SETSIGINFO = 0x4203,
+    PTRACE_GETREGSET = 0x4204,
+    PTRACE_SETREGSET = 0x4205,
+    PTRACE_SEIZE = 0x4206,
+    PTRACE_INTERRUPT = 0x4207,
+    PTRACE_LISTEN = 0x4208,
+}
+
+/// The general-purpose registers (i.e., `struct user_regs_struct`).
+const NT_PRSTATUS: usize = 1;
+/// The floating-point registers (i.e., `struct user_fpregs_struct`).
+const NT_PRFPREG: usize = 2;
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct iovec_t {
+    base: Vaddr,
+    len: usize,
+}
@@ -2,7 +2,7 @@
 
 use super::SyscallReturn;
 use crate::{
-    fs::file_table::{get_file_fast, FileDesc},
+    fs::file_table::{get_file_fast, FdFlags, FileDesc},
     net::socket::SendRecvFlags,
     prelude::*,
     util::net::CUserMsgHdr,
@@ -14,7 +14,7 @@ pub fn sys_recvmsg(
     flags: i32,
     ctx: &Context,
 ) -> Result<SyscallReturn> {
-    let c_user_msghdr: CUserMsgHdr = ctx.user_space().read_val(user_msghdr_ptr)?;
+    let mut c_user_msghdr: CUserMsgHdr = ctx.user_space().read_val(user_msghdr_ptr)?;
     let flags = SendRecvFlags::from_bits_truncate(flags);
 
     debug!(
@@ -26,8 +26,8 @@ pub fn sys_recvmsg(
     let file = get_file_fast!(&mut file_table, sockfd);
     let socket = file.as_socket_or_err()?;
 
-    let (total_bytes, message_header) = {
-        let user_space = ctx.user_space();
+    let user_space = ctx.user_space();
+    let (total_bytes, mut message_header) = {
         let mut io_vec_writer = c_user_msghdr.copy_writer_array_from_user(&user_space)?;
         socket
             .recvmsg(&mut io_vec_writer, flags)
@@ -37,14 +37,24 @@ pub fn sys_recvmsg(
                 _ => err,
             })?
     };
+    drop(file);
 
     if let Some(addr) = message_header.addr() {
         c_user_msghdr.write_socket_addr_to_user(addr)?;
     }
 
-    if c_user_msghdr.msg_control != 0 {
-        warn!("receiving control message is not supported");
-    }
+    let fd_flags = if flags.contains(SendRecvFlags::MSG_CMSG_CLOEXEC) {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+    c_user_msghdr.write_control_messages_to_user(
+        message_header.take_control_messages(),
+        &user_space,
+        file_table.unwrap(),
+        fd_flags,
+    )?;
+    user_space.write_val(user_msghdr_ptr, &c_user_msghdr)?;
 
     Ok(SyscallReturn::Return(total_bytes as _))
 }
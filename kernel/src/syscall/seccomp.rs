@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{mem::size_of, sync::atomic::Ordering};
+
+use ostd::cpu::context::UserContext;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        posix_thread::{do_exit, do_exit_group, AsPosixThread},
+        seccomp::{
+            SeccompAction, SeccompData, SeccompFilter, SeccompMode, SockFilter, SockFprog,
+            BPF_MAXINSNS,
+        },
+        signal::{
+            constants::{SIGKILL, SIGSYS},
+            sig_action::SigAction,
+            signals::seccomp::SeccompSignal,
+        },
+        TermStatus,
+    },
+};
+
+pub fn sys_seccomp(op: u32, flags: u32, args: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let op = SeccompOp::try_from(op)?;
+    debug!("op = {:?}, flags = {:#x}, args = {:#x}", op, flags, args);
+
+    match op {
+        SeccompOp::SetModeStrict => {
+            if flags != 0 || args != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the flags or arguments are not zero");
+            }
+            set_mode_strict(ctx)
+        }
+        SeccompOp::SetModeFilter => {
+            let flags = SeccompFilterFlags::from_bits(flags)
+                .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+            set_mode_filter(flags, args, ctx)
+        }
+        SeccompOp::GetActionAvail => {
+            if flags != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the flags are not zero");
+            }
+            let action = ctx.user_space().read_val::<u32>(args)?;
+            match action {
+                SeccompAction::RET_KILL_PROCESS
+                | SeccompAction::RET_KILL_THREAD
+                | SeccompAction::RET_TRAP
+                | SeccompAction::RET_ERRNO
+                | SeccompAction::RET_LOG
+                | SeccompAction::RET_ALLOW => Ok(SyscallReturn::Return(0)),
+                _ => return_errno_with_message!(Errno::EOPNOTSUPP, "the action is not supported"),
+            }
+        }
+        SeccompOp::GetNotifSizes => {
+            return_errno_with_message!(Errno::EINVAL, "user-space notification is not supported")
+        }
+    }
+}
+
+/// Puts the current thread into the strict mode.
+pub(super) fn set_mode_strict(ctx: &Context) -> Result<SyscallReturn> {
+    ctx.posix_thread.seccomp().set_strict()?;
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Adds a filter to the filter stack of the current thread, and of the other
+/// threads in the process if `SECCOMP_FILTER_FLAG_TSYNC` is specified.
+pub(super) fn set_mode_filter(
+    flags: SeccompFilterFlags,
+    fprog_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let user_space = ctx.user_space();
+    let fprog = user_space.read_val::<SockFprog>(fprog_addr)?;
+    let len = fprog.len as usize;
+    if len == 0 || len > BPF_MAXINSNS {
+        return_errno_with_message!(Errno::EINVAL, "the filter length is invalid");
+    }
+    let insns = (0..len)
+        .map(|idx| user_space.read_val::<SockFilter>(fprog.filter + idx * size_of::<SockFilter>()))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Like Linux, unprivileged threads must set `no_new_privs` first, so that
+    // they cannot mislead set-user-ID programs with filters.
+    let posix_thread = ctx.posix_thread;
+    if !posix_thread.no_new_privs()
+        && !posix_thread
+            .credentials()
+            .effective_capset()
+            .contains(CapSet::SYS_ADMIN)
+    {
+        return_errno_with_message!(
+            Errno::EACCES,
+            "either no_new_privs or CAP_SYS_ADMIN is required"
+        );
+    }
+
+    // Filters are only added with the task set locked, so that the threads
+    // in the process see consistent filter stacks during synchronization.
+    let tasks = ctx.process.tasks().lock();
+
+    let seccomp = posix_thread.seccomp();
+    let prev = seccomp.filter();
+    let filter = SeccompFilter::new(
+        &insns,
+        flags.contains(SeccompFilterFlags::LOG),
+        prev.clone(),
+    )?;
+
+    let other_threads = || {
+        tasks
+            .as_slice()
+            .iter()
+            .map(|task| task.as_posix_thread().unwrap())
+            .filter(|thread| !core::ptr::eq(*thread, posix_thread))
+    };
+
+    if flags.contains(SeccompFilterFlags::TSYNC) {
+        // The other threads can be synchronized only if their filter stacks
+        // are part of ours.
+        for thread in other_threads() {
+            let can_sync = match thread.seccomp().mode() {
+                SeccompMode::Disabled => true,
+                SeccompMode::Strict => false,
+                SeccompMode::Filter => thread
+                    .seccomp()
+                    .filter()
+                    .zip(prev.as_ref())
+                    .is_some_and(|(theirs, ours)| theirs.is_ancestor_of(ours)),
+            };
+            if can_sync {
+                continue;
+            }
+
+            if flags.contains(SeccompFilterFlags::TSYNC_ESRCH) {
+                return_errno_with_message!(Errno::ESRCH, "a thread cannot be synchronized");
+            }
+            // Like Linux, the TID of the thread is returned on failure.
+            let tid = ctx.process.pid_ns().tid_in_ns(thread.tid()).unwrap_or(0);
+            return Ok(SyscallReturn::Return(tid as _));
+        }
+    }
+
+    seccomp.set_filter(filter.clone())?;
+
+    if flags.contains(SeccompFilterFlags::TSYNC) {
+        for thread in other_threads() {
+            thread.seccomp().set_filter(filter.clone()).unwrap();
+            if posix_thread.no_new_privs() {
+                thread.set_no_new_privs();
+            }
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The maximum error number that a filter can return.
+const MAX_ERRNO: u16 = 4095;
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e; // AUDIT_ARCH_X86_64
+#[cfg(target_arch = "riscv64")]
+const AUDIT_ARCH: u32 = 0xc000_00f3; // AUDIT_ARCH_RISCV64
+
+/// The system calls that are allowed in the strict mode, which are `read`,
+/// `write`, `exit`, and `rt_sigreturn`.
+#[cfg(target_arch = "x86_64")]
+const STRICT_MODE_SYSCALLS: [u64; 4] = [0, 1, 60, 15];
+#[cfg(target_arch = "riscv64")]
+const STRICT_MODE_SYSCALLS: [u64; 4] = [63, 64, 93, 139];
+
+/// Checks the system call against the seccomp state of the current thread.
+///
+/// This method returns `None` if the system call should be executed.
+/// Otherwise, the system call is skipped and its return value is returned.
+pub(super) fn check_syscall(
+    syscall_number: u64,
+    args: [u64; 6],
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> Option<SyscallReturn> {
+    let seccomp = ctx.posix_thread.seccomp();
+
+    let filter = match seccomp.mode() {
+        SeccompMode::Disabled => return None,
+        SeccompMode::Strict => {
+            if STRICT_MODE_SYSCALLS.contains(&syscall_number) {
+                return None;
+            }
+            info!(
+                "seccomp: thread {} is killed for system call {} in the strict mode",
+                ctx.posix_thread.tid(),
+                syscall_number
+            );
+            do_exit(TermStatus::Killed(SIGKILL));
+            return Some(SyscallReturn::NoReturn);
+        }
+        SeccompMode::Filter => seccomp.filter().unwrap(),
+    };
+
+    let data = SeccompData {
+        nr: syscall_number as i32,
+        arch: AUDIT_ARCH,
+        instruction_pointer: user_ctx.instruction_pointer() as u64,
+        args,
+    };
+    let (action, should_log) = filter.run(&data);
+    if should_log {
+        info!(
+            "seccomp: action {:?} for system call {} of thread {}",
+            action,
+            syscall_number,
+            ctx.posix_thread.tid()
+        );
+    }
+
+    match action {
+        SeccompAction::Allow | SeccompAction::Log => None,
+        SeccompAction::Errno(errno) => {
+            Some(SyscallReturn::Return(-(errno.min(MAX_ERRNO) as isize)))
+        }
+        SeccompAction::Trap(value) => {
+            let signal =
+                SeccompSignal::new(data.instruction_pointer as _, data.nr, data.arch, value);
+            force_sigsys(ctx, signal);
+            Some(SyscallReturn::NoReturn)
+        }
+        // Like Linux, the system call fails with `ENOSYS` if there is no
+        // tracer or supervisor to notify.
+        //
+        // TODO: Support `PTRACE_O_TRACESECCOMP` and `SECCOMP_FILTER_FLAG_NEW_LISTENER`.
+        SeccompAction::Trace(_) | SeccompAction::UserNotif => {
+            Some(SyscallReturn::Return(-(Errno::ENOSYS as isize)))
+        }
+        SeccompAction::KillThread if ctx.process.tasks().lock().as_slice().len() > 1 => {
+            do_exit(TermStatus::Killed(SIGSYS));
+            Some(SyscallReturn::NoReturn)
+        }
+        SeccompAction::KillThread | SeccompAction::KillProcess => {
+            do_exit_group(TermStatus::Killed(SIGSYS));
+            Some(SyscallReturn::NoReturn)
+        }
+    }
+}
+
+/// Sends `SIGSYS` to the current thread.
+///
+/// Like Linux, the signal cannot be blocked or ignored. If it is, the default
+/// action (i.e., terminating the process) is taken instead.
+fn force_sigsys(ctx: &Context, signal: SeccompSignal) {
+    let sig_mask = ctx.posix_thread.sig_mask();
+    let is_blocked = sig_mask.contains(SIGSYS, Ordering::Relaxed);
+
+    let mut sig_dispositions = ctx.process.sig_dispositions().lock();
+    if is_blocked || sig_dispositions.get(SIGSYS) == SigAction::Ign {
+        sig_dispositions.set_default(SIGSYS);
+        if is_blocked {
+            sig_mask.store(sig_mask.load(Ordering::Relaxed) - SIGSYS, Ordering::Relaxed);
+        }
+    }
+    drop(sig_dispositions);
+
+    ctx.posix_thread.enqueue_signal(Box::new(signal));
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, TryFromInt)]
+enum SeccompOp {
+    SetModeStrict = 0,
+    SetModeFilter = 1,
+    GetActionAvail = 2,
+    GetNotifSizes = 3,
+}
+
+bitflags! {
+    pub(super) struct SeccompFilterFlags: u32 {
+        const TSYNC = 1 << 0;
+        const LOG = 1 << 1;
+        /// Disables speculative store bypass mitigation, which is a no-op.
+        const SPEC_ALLOW = 1 << 2;
+        const TSYNC_ESRCH = 1 << 4;
+    }
+}
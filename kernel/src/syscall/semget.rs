@@ -3,11 +3,8 @@
 use super::SyscallReturn;
 use crate::{
     ipc::{
-        semaphore::system_v::{
-            sem_set::{check_sem, create_sem_set, create_sem_set_with_id, SEMMSL},
-            PermissionMode,
-        },
-        IpcFlags,
+        semaphore::system_v::sem_set::{check_sem, create_sem_set, create_sem_set_with_id, SEMMSL},
+        IpcFlags, PermissionMode,
     },
     prelude::*,
 };
@@ -24,6 +21,8 @@ pub fn sys_semget(key: i32, nsems: i32, semflags: i32, ctx: &Context) -> Result<
     let mode: u16 = (semflags as u32 & 0x1FF) as u16;
     let nsems = nsems as usize;
     let credentials = ctx.posix_thread.credentials();
+    let ns_proxy = ctx.posix_thread.ns_proxy();
+    let ipc_ns = ns_proxy.ipc_ns();
 
     debug!(
         "[sys_semget] key = {}, nsems = {}, flags = {:?}",
@@ -37,12 +36,13 @@ pub fn sys_semget(key: i32, nsems: i32, semflags: i32, ctx: &Context) -> Result<
             return_errno!(Errno::EINVAL);
         }
         return Ok(SyscallReturn::Return(
-            create_sem_set(nsems, mode, credentials)? as isize,
+            create_sem_set(ipc_ns, nsems, mode, credentials)? as isize,
         ));
     }
 
     // Get a semaphore set, and create if necessary
     match check_sem(
+        ipc_ns,
         key,
         Some(nsems),
         PermissionMode::ALTER | PermissionMode::READ,
@@ -61,7 +61,7 @@ pub fn sys_semget(key: i32, nsems: i32, semflags: i32, ctx: &Context) -> Result<
                 return_errno!(Errno::EINVAL);
             }
 
-            create_sem_set_with_id(key, nsems, mode, credentials)?
+            create_sem_set_with_id(ipc_ns, key, nsems, mode, credentials)?
         }
     };
 
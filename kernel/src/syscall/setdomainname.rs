@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{credentials::capabilities::CapSet, namespace::UTS_FIELD_LEN},
+};
+
+pub fn sys_setdomainname(name_addr: Vaddr, len: usize, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("name_addr = 0x{:x}, len = {}", name_addr, len);
+
+    let credentials = ctx.posix_thread.credentials();
+    if !credentials.effective_capset().contains(CapSet::SYS_ADMIN) {
+        return_errno_with_message!(Errno::EPERM, "CAP_SYS_ADMIN is required");
+    }
+
+    if len >= UTS_FIELD_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the name is too long");
+    }
+
+    let mut name = vec![0u8; len];
+    ctx.user_space()
+        .read_bytes(name_addr, &mut VmWriter::from(name.as_mut_slice()))?;
+
+    ctx.posix_thread.ns_proxy().uts_ns().set_domainname(&name)?;
+
+    Ok(SyscallReturn::Return(0))
+}
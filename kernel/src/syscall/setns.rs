@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::{get_file_fast, FileDesc},
+        procfs::namespace_of_inode,
+    },
+    prelude::*,
+    process::namespace::switch_to_namespace,
+};
+
+pub fn sys_setns(fd: FileDesc, nstype: i32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("fd = {}, nstype = 0x{:x}", fd, nstype);
+
+    let namespace = {
+        let mut file_table = ctx.thread_local.borrow_file_table_mut();
+        let file = get_file_fast!(&mut file_table, fd);
+        let inode = file.as_inode_or_err()?.dentry().inode();
+        namespace_of_inode(inode.as_ref()).ok_or_else(|| {
+            Error::with_message(Errno::EINVAL, "the file does not refer to a namespace")
+        })?
+    };
+
+    // FIXME: Support PID file descriptors, which can be used to enter multiple namespaces
+    // of a process at once.
+    if nstype != 0 && nstype as u32 != namespace.ns_type().clone_flag().bits() {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the namespace does not match the specified type"
+        );
+    }
+
+    switch_to_namespace(namespace, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
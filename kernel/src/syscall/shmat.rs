@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::shm::system_v::{
+        shm_segment::{get_segment, SHMLBA},
+        ShmAtFlags,
+    },
+    prelude::*,
+    vm::vmar::is_userspace_vaddr,
+};
+
+pub fn sys_shmat(shmid: i32, shmaddr: Vaddr, shmflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = ShmAtFlags::from_bits_truncate(shmflg as u32);
+    debug!(
+        "shmid = {}, shmaddr = 0x{:x}, flags = {:?}",
+        shmid, shmaddr, flags
+    );
+
+    let addr = if flags.contains(ShmAtFlags::SHM_RND) {
+        shmaddr.align_down(SHMLBA)
+    } else if shmaddr % SHMLBA != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the address is not aligned");
+    } else {
+        shmaddr
+    };
+    let addr = if addr == 0 {
+        if flags.contains(ShmAtFlags::SHM_REMAP) {
+            return_errno_with_message!(Errno::EINVAL, "SHM_REMAP requires an address");
+        }
+        None
+    } else {
+        if !is_userspace_vaddr(addr) {
+            return_errno_with_message!(Errno::EINVAL, "the address is not in user space");
+        }
+        Some(addr)
+    };
+
+    let segment = get_segment(ctx.posix_thread.ns_proxy().ipc_ns(), shmid)?;
+    let credentials = ctx.posix_thread.credentials();
+    let map_addr = segment.attach(
+        ctx.user_space().root_vmar(),
+        addr,
+        flags,
+        &credentials,
+        ctx.process.pid(),
+    )?;
+
+    Ok(SyscallReturn::Return(map_addr as _))
+}
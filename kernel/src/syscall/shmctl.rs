@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        c_ipc64_perm, key_t,
+        shm::system_v::shm_segment::{
+            get_segment, remove_segment, shm_segments, ShmSegmentStat, SHMALL, SHMMAX, SHMMIN,
+            SHMMNI, SHMSEG,
+        },
+        IpcNamespace, PermissionMode,
+    },
+    prelude::*,
+};
+
+pub fn sys_shmctl(shmid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    // Some C libraries pass `IPC_64` to request the new data structures,
+    // which are the only ones supported.
+    const IPC_64: i32 = 0x100;
+    let cmd = ShmCtlCmd::try_from(cmd & !IPC_64)?;
+    debug!("shmid = {}, cmd = {:?}, buf = 0x{:x}", shmid, cmd, buf);
+
+    let credentials = ctx.posix_thread.credentials();
+    let user_space = ctx.user_space();
+    let ns_proxy = ctx.posix_thread.ns_proxy();
+    let ipc_ns = ns_proxy.ipc_ns();
+
+    match cmd {
+        ShmCtlCmd::IPC_RMID => {
+            remove_segment(ipc_ns, shmid, &credentials)?;
+        }
+        ShmCtlCmd::IPC_SET => {
+            let shmid_ds: c_shmid_ds = user_space.read_val(buf)?;
+            let perm = &shmid_ds.shm_perm;
+            get_segment(ipc_ns, shmid)?.set_owner_and_mode(
+                &credentials,
+                perm.uid(),
+                perm.gid(),
+                perm.mode(),
+            )?;
+        }
+        ShmCtlCmd::IPC_STAT | ShmCtlCmd::SHM_STAT | ShmCtlCmd::SHM_STAT_ANY => {
+            // The IDs of segments are their indices, so `SHM_STAT` and
+            // `SHM_STAT_ANY` work the same as `IPC_STAT`.
+            let segment = get_segment(ipc_ns, shmid)?;
+            if !matches!(cmd, ShmCtlCmd::SHM_STAT_ANY) {
+                segment.check_permission(&credentials, PermissionMode::READ)?;
+            }
+            user_space.write_val(buf, &c_shmid_ds::from(segment.stat()))?;
+
+            if !matches!(cmd, ShmCtlCmd::IPC_STAT) {
+                return Ok(SyscallReturn::Return(segment.id() as _));
+            }
+        }
+        ShmCtlCmd::IPC_INFO => {
+            let shminfo = c_shminfo64 {
+                shmmax: SHMMAX as u64,
+                shmmin: SHMMIN as u64,
+                shmmni: SHMMNI as u64,
+                shmseg: SHMSEG as u64,
+                shmall: SHMALL as u64,
+                ..Default::default()
+            };
+            user_space.write_val(buf, &shminfo)?;
+
+            return Ok(SyscallReturn::Return(max_id(ipc_ns) as _));
+        }
+        ShmCtlCmd::SHM_INFO => {
+            let segments = shm_segments(ipc_ns);
+            let total_pages: usize = segments
+                .iter()
+                .map(|segment| segment.size().div_ceil(PAGE_SIZE))
+                .sum();
+            let shm_info = c_shm_info {
+                used_ids: segments.len() as i32,
+                shm_tot: total_pages as u64,
+                shm_rss: total_pages as u64,
+                ..Default::default()
+            };
+            user_space.write_val(buf, &shm_info)?;
+
+            return Ok(SyscallReturn::Return(max_id(ipc_ns) as _));
+        }
+        ShmCtlCmd::SHM_LOCK | ShmCtlCmd::SHM_UNLOCK => {
+            get_segment(ipc_ns, shmid)?
+                .set_locked(&credentials, matches!(cmd, ShmCtlCmd::SHM_LOCK))?;
+        }
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Returns the largest ID in use, or zero if there are no segments.
+fn max_id(ipc_ns: &IpcNamespace) -> key_t {
+    shm_segments(ipc_ns)
+        .last()
+        .map_or(0, |segment| segment.id())
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, TryFromInt)]
+#[expect(non_camel_case_types)]
+enum ShmCtlCmd {
+    IPC_RMID = 0,
+    IPC_SET = 1,
+    IPC_STAT = 2,
+    IPC_INFO = 3,
+
+    SHM_LOCK = 11,
+    SHM_UNLOCK = 12,
+    SHM_STAT = 13,
+    SHM_INFO = 14,
+    SHM_STAT_ANY = 15,
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct c_shmid_ds {
+    shm_perm: c_ipc64_perm,
+    shm_segsz: u64,
+    shm_atime: i64,
+    shm_dtime: i64,
+    shm_ctime: i64,
+    shm_cpid: i32,
+    shm_lpid: i32,
+    shm_nattch: u64,
+    __unused4: u64,
+    __unused5: u64,
+}
+
+impl From<ShmSegmentStat> for c_shmid_ds {
+    fn from(stat: ShmSegmentStat) -> Self {
+        Self {
+            shm_perm: c_ipc64_perm::new(
+                stat.key, stat.uid, stat.gid, stat.cuid, stat.cgid, stat.mode,
+            ),
+            shm_segsz: stat.size as u64,
+            shm_atime: stat.atime as i64,
+            shm_dtime: stat.dtime as i64,
+            shm_ctime: stat.ctime as i64,
+            shm_cpid: stat.cpid as i32,
+            shm_lpid: stat.lpid as i32,
+            shm_nattch: stat.nattch as u64,
+            __unused4: 0,
+            __unused5: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct c_shminfo64 {
+    shmmax: u64,
+    shmmin: u64,
+    shmmni: u64,
+    shmseg: u64,
+    shmall: u64,
+    __unused1: u64,
+    __unused2: u64,
+    __unused3: u64,
+    __unused4: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, Pod)]
+#[repr(C)]
+#[expect(non_camel_case_types)]
+struct c_shm_info {
+    used_ids: i32,
+    __pad: u32,
+    shm_tot: u64,
+    shm_rss: u64,
+    shm_swp: u64,
+    swap_attempts: u64,
+    swap_successes: u64,
+}
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{ipc::shm::system_v::shm_segment::detach_segment, prelude::*};
+
+pub fn sys_shmdt(shmaddr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("shmaddr = 0x{:x}", shmaddr);
+
+    if shmaddr % PAGE_SIZE != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the address is not page-aligned");
+    }
+
+    detach_segment(
+        ctx.posix_thread.ns_proxy().ipc_ns(),
+        ctx.user_space().root_vmar(),
+        shmaddr,
+        ctx.process.pid(),
+    )?;
+    Ok(SyscallReturn::Return(0))
+}
@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{shm::system_v::shm_segment::get_or_create_segment, IpcFlags},
+    prelude::*,
+};
+
+pub fn sys_shmget(key: i32, size: usize, shmflg: i32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = IpcFlags::from_bits_truncate(shmflg as u32);
+    let mode = (shmflg as u32 & 0o777) as u16;
+    debug!(
+        "key = {}, size = {}, flags = {:?}, mode = {:o}",
+        key, size, flags, mode
+    );
+
+    let credentials = ctx.posix_thread.credentials();
+    let id = get_or_create_segment(
+        ctx.posix_thread.ns_proxy().ipc_ns(),
+        key,
+        size,
+        flags,
+        mode,
+        &credentials,
+        ctx.process.pid(),
+    )?;
+
+    Ok(SyscallReturn::Return(id as _))
+}
@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_handle::FileLike,
+        file_table::{FileDesc, WithFileTable},
+        pipe::{PipeBuffer, PipeReader, PipeWriter},
+        utils::{InodeType, SeekFrom, StatusFlags},
+    },
+    prelude::*,
+    util::{VmReaderArray, VmWriterArray},
+    vm::vmo::CommitFlags,
+};
+
+pub fn sys_splice(
+    fd_in: FileDesc,
+    offset_in_ptr: Vaddr,
+    fd_out: FileDesc,
+    offset_out_ptr: Vaddr,
+    len: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd_in = {}, offset_in_ptr = 0x{:x}, fd_out = {}, offset_out_ptr = 0x{:x}, len = 0x{:x}, flags = 0x{:x}",
+        fd_in, offset_in_ptr, fd_out, offset_out_ptr, len, flags
+    );
+
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    let (file_in, file_out) = get_files(fd_in, fd_out, ctx)?;
+    if len == 0 {
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    let pipe_in = file_in.downcast_ref::<PipeReader>();
+    let pipe_out = file_out.downcast_ref::<PipeWriter>();
+
+    let spliced_len = match (pipe_in, pipe_out) {
+        (Some(pipe_in), Some(pipe_out)) => {
+            if offset_in_ptr != 0 || offset_out_ptr != 0 {
+                return_errno_with_message!(Errno::ESPIPE, "pipes cannot have offsets");
+            }
+            let is_nonblocking = flags.contains(SpliceFlags::SPLICE_F_NONBLOCK)
+                || is_nonblocking_file(file_in.as_ref())
+                || is_nonblocking_file(file_out.as_ref());
+            pipe_in.splice_to(pipe_out, len, is_nonblocking)?
+        }
+        (Some(pipe_in), None) => {
+            if offset_in_ptr != 0 {
+                return_errno_with_message!(Errno::ESPIPE, "pipes cannot have offsets");
+            }
+            let is_nonblocking = flags.contains(SpliceFlags::SPLICE_F_NONBLOCK)
+                || is_nonblocking_file(file_out.as_ref());
+            let mut offset = read_offset(offset_out_ptr, ctx)?;
+            let spliced_len =
+                splice_from_pipe(pipe_in, &file_out, offset.as_mut(), len, is_nonblocking)?;
+            write_offset(offset_out_ptr, offset, ctx)?;
+            spliced_len
+        }
+        (None, Some(pipe_out)) => {
+            if offset_out_ptr != 0 {
+                return_errno_with_message!(Errno::ESPIPE, "pipes cannot have offsets");
+            }
+            let is_nonblocking = flags.contains(SpliceFlags::SPLICE_F_NONBLOCK)
+                || is_nonblocking_file(file_in.as_ref());
+            let mut offset = read_offset(offset_in_ptr, ctx)?;
+            let spliced_len =
+                splice_to_pipe(&file_in, offset.as_mut(), pipe_out, len, is_nonblocking)?;
+            write_offset(offset_in_ptr, offset, ctx)?;
+            spliced_len
+        }
+        (None, None) => {
+            return_errno_with_message!(Errno::EINVAL, "neither of the files is a pipe")
+        }
+    };
+
+    Ok(SyscallReturn::Return(spliced_len as _))
+}
+
+pub fn sys_tee(
+    fd_in: FileDesc,
+    fd_out: FileDesc,
+    len: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd_in = {}, fd_out = {}, len = 0x{:x}, flags = 0x{:x}",
+        fd_in, fd_out, len, flags
+    );
+
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    let (file_in, file_out) = get_files(fd_in, fd_out, ctx)?;
+
+    let (Some(pipe_in), Some(pipe_out)) = (
+        file_in.downcast_ref::<PipeReader>(),
+        file_out.downcast_ref::<PipeWriter>(),
+    ) else {
+        return_errno_with_message!(Errno::EINVAL, "the files are not pipes");
+    };
+
+    let is_nonblocking = flags.contains(SpliceFlags::SPLICE_F_NONBLOCK)
+        || is_nonblocking_file(file_in.as_ref())
+        || is_nonblocking_file(file_out.as_ref());
+    let copied_len = pipe_in.tee_to(pipe_out, len, is_nonblocking)?;
+
+    Ok(SyscallReturn::Return(copied_len as _))
+}
+
+pub fn sys_vmsplice(
+    fd: FileDesc,
+    io_vec_ptr: Vaddr,
+    io_vec_count: usize,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd = {}, io_vec_ptr = 0x{:x}, io_vec_count = {}, flags = 0x{:x}",
+        fd, io_vec_ptr, io_vec_count, flags
+    );
+
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    if io_vec_count > IOV_MAX {
+        return_errno_with_message!(Errno::EINVAL, "too many I/O vectors");
+    }
+
+    let file = ctx
+        .thread_local
+        .borrow_file_table_mut()
+        .read_with(|inner| inner.get_file(fd).cloned())?;
+
+    let is_nonblocking =
+        flags.contains(SpliceFlags::SPLICE_F_NONBLOCK) || is_nonblocking_file(file.as_ref());
+    let user_space = ctx.user_space();
+
+    // TODO: Map the user pages into the pipe instead of copying them, which is what
+    // `SPLICE_F_GIFT` is for.
+    let len = if let Some(pipe_out) = file.downcast_ref::<PipeWriter>() {
+        let mut reader_array =
+            VmReaderArray::from_user_io_vecs(&user_space, io_vec_ptr, io_vec_count)?;
+        pipe_out.write_vectored(&mut reader_array, is_nonblocking)?
+    } else if let Some(pipe_in) = file.downcast_ref::<PipeReader>() {
+        let mut writer_array =
+            VmWriterArray::from_user_io_vecs(&user_space, io_vec_ptr, io_vec_count)?;
+        pipe_in.read_vectored(&mut writer_array, is_nonblocking)?
+    } else {
+        return_errno_with_message!(Errno::EBADF, "the file is not a pipe");
+    };
+
+    Ok(SyscallReturn::Return(len as _))
+}
+
+/// Splices at most `len` bytes from `file` to `pipe_out`.
+///
+/// If `file` is backed by a page cache, the pages are referenced by the pipe
+/// instead of being copied.
+fn splice_to_pipe(
+    file: &Arc<dyn FileLike>,
+    offset: Option<&mut usize>,
+    pipe_out: &PipeWriter,
+    len: usize,
+    is_nonblocking: bool,
+) -> Result<usize> {
+    if let Ok(inode_handle) = file.as_inode_or_err() {
+        let inode = inode_handle.dentry().inode();
+        if inode.type_() == InodeType::File
+            && let Some(pages) = inode.page_cache()
+        {
+            let start = match offset.as_deref() {
+                Some(offset) => *offset,
+                None => inode_handle.offset(),
+            };
+
+            let mut pos = start;
+            let spliced_len = pipe_out.push_with(len, is_nonblocking, |max_len| {
+                let file_size = inode.size();
+                if pos >= file_size {
+                    return Ok(None);
+                }
+
+                let page_offset = pos % PAGE_SIZE;
+                let buffer_len = max_len.min(PAGE_SIZE - page_offset).min(file_size - pos);
+                let frame = pages.commit_on(pos / PAGE_SIZE, CommitFlags::empty())?;
+
+                pos += buffer_len;
+                Ok(Some(PipeBuffer::from_frame(
+                    frame,
+                    page_offset..page_offset + buffer_len,
+                )))
+            })?;
+
+            match offset {
+                Some(offset) => *offset += spliced_len,
+                None => {
+                    inode_handle.seek(SeekFrom::Start(start + spliced_len))?;
+                }
+            }
+            return Ok(spliced_len);
+        }
+    }
+
+    // Otherwise, read the file into the buffers of the pipe. Like Linux, only a single read is
+    // performed, so that a file that may block (e.g., a socket) is not read again once some data
+    // has arrived.
+    let mut offset = offset;
+    let mut has_read = false;
+    pipe_out.push_with(len, is_nonblocking, |max_len| {
+        if has_read {
+            return Ok(None);
+        }
+
+        let buffer = PipeBuffer::alloc_with(max_len.min(PAGE_SIZE), |writer| {
+            let mut writer = writer.to_fallible();
+            match offset.as_deref_mut() {
+                Some(offset) => {
+                    let read_len = file.read_at(*offset, &mut writer)?;
+                    *offset += read_len;
+                    Ok(read_len)
+                }
+                None => file.read(&mut writer),
+            }
+        })?;
+        has_read = true;
+
+        Ok((!buffer.is_empty()).then_some(buffer))
+    })
+}
+
+/// Splices at most `len` bytes from `pipe_in` to `file`.
+///
+/// The bytes are written to `file` directly from the buffers of the pipe.
+fn splice_from_pipe(
+    pipe_in: &PipeReader,
+    file: &Arc<dyn FileLike>,
+    mut offset: Option<&mut usize>,
+    len: usize,
+    is_nonblocking: bool,
+) -> Result<usize> {
+    if file.status_flags().contains(StatusFlags::O_APPEND) {
+        return_errno_with_message!(Errno::EINVAL, "the file is opened in append mode");
+    }
+
+    pipe_in.consume_with(len, is_nonblocking, |reader| {
+        let mut reader = reader.clone().to_fallible();
+        match offset.as_deref_mut() {
+            Some(offset) => {
+                let written_len = file.write_at(*offset, &mut reader)?;
+                *offset += written_len;
+                Ok(written_len)
+            }
+            None => file.write(&mut reader),
+        }
+    })
+}
+
+/// Gets the input and output files and checks their access modes.
+fn get_files(
+    fd_in: FileDesc,
+    fd_out: FileDesc,
+    ctx: &Context,
+) -> Result<(Arc<dyn FileLike>, Arc<dyn FileLike>)> {
+    let (file_in, file_out) = ctx
+        .thread_local
+        .borrow_file_table_mut()
+        .read_with(|inner| {
+            let file_in = inner.get_file(fd_in)?.clone();
+            let file_out = inner.get_file(fd_out)?.clone();
+            Ok::<_, Error>((file_in, file_out))
+        })?;
+
+    if !file_in.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "the input file is not readable");
+    }
+    if !file_out.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "the output file is not writable");
+    }
+
+    Ok((file_in, file_out))
+}
+
+fn is_nonblocking_file(file: &dyn FileLike) -> bool {
+    file.status_flags().contains(StatusFlags::O_NONBLOCK)
+}
+
+/// Reads the offset at `offset_ptr`, or returns `None` if `offset_ptr` is null.
+fn read_offset(offset_ptr: Vaddr, ctx: &Context) -> Result<Option<usize>> {
+    if offset_ptr == 0 {
+        return Ok(None);
+    }
+
+    let offset: i64 = ctx.user_space().read_val(offset_ptr)?;
+    if offset < 0 {
+        return_errno_with_message!(Errno::EINVAL, "offset cannot be negative");
+    }
+    Ok(Some(offset as usize))
+}
+
+fn write_offset(offset_ptr: Vaddr, offset: Option<usize>, ctx: &Context) -> Result<()> {
+    if let Some(offset) = offset {
+        ctx.user_space().write_val(offset_ptr, &(offset as i64))?;
+    }
+    Ok(())
+}
+
+/// The maximum number of I/O vectors.
+const IOV_MAX: usize = 1024;
+
+bitflags! {
+    struct SpliceFlags: u32 {
+        const SPLICE_F_MOVE = 1;
+        const SPLICE_F_NONBLOCK = 2;
+        const SPLICE_F_MORE = 4;
+        const SPLICE_F_GIFT = 8;
+    }
+}
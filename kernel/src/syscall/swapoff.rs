@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{mount::lookup_block_device, SyscallReturn};
+use crate::{
+    prelude::*, process::credentials::capabilities::CapSet, syscall::constants::MAX_FILENAME_LEN,
+    vm::swap,
+};
+
+pub fn sys_swapoff(path_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let path = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    debug!("path = {:?}", path);
+
+    let credentials = ctx.posix_thread.credentials();
+    if !credentials.effective_capset().contains(CapSet::SYS_ADMIN) {
+        return_errno_with_message!(Errno::EPERM, "CAP_SYS_ADMIN is required");
+    }
+
+    let device = lookup_block_device(path, ctx)?;
+    swap::swap_off(&device)?;
+    Ok(SyscallReturn::Return(0))
+}
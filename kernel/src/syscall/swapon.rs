@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{mount::lookup_block_device, SyscallReturn};
+use crate::{
+    prelude::*, process::credentials::capabilities::CapSet, syscall::constants::MAX_FILENAME_LEN,
+    vm::swap,
+};
+
+pub fn sys_swapon(path_addr: Vaddr, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let path = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    let swap_flags = SwapFlags::from_bits(flags & !SWAP_FLAG_PRIO_MASK)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid swap flags"))?;
+    debug!("path = {:?}, flags = {:?}", path, swap_flags);
+
+    let credentials = ctx.posix_thread.credentials();
+    if !credentials.effective_capset().contains(CapSet::SYS_ADMIN) {
+        return_errno_with_message!(Errno::EPERM, "CAP_SYS_ADMIN is required");
+    }
+
+    // Discarding freed swap slots is an optimization for SSDs, which is
+    // unnecessary for correctness.
+    if swap_flags.intersects(SwapFlags::SWAP_FLAG_DISCARD_ONCE | SwapFlags::SWAP_FLAG_DISCARD_PAGES)
+    {
+        warn!("discarding swap slots is not supported");
+    }
+
+    let priority = swap_flags
+        .contains(SwapFlags::SWAP_FLAG_PREFER)
+        .then_some((flags & SWAP_FLAG_PRIO_MASK) as i32);
+
+    let name = path.to_string_lossy().into_owned();
+    let device = lookup_block_device(path, ctx)?;
+
+    swap::swap_on(&name, device, priority)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The bits in the flags of `swapon` that specify the priority.
+const SWAP_FLAG_PRIO_MASK: u32 = 0x7fff;
+
+bitflags! {
+    /// The flags of `swapon`, except for the priority.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/linux/swap.h#L22>
+    struct SwapFlags: u32 {
+        /// Sets the priority of the swap area.
+        const SWAP_FLAG_PREFER = 0x8000;
+        /// Discards the swap area.
+        const SWAP_FLAG_DISCARD = 0x10000;
+        /// Discards the swap area once when it is enabled.
+        const SWAP_FLAG_DISCARD_ONCE = 0x20000;
+        /// Discards the freed swap slots.
+        const SWAP_FLAG_DISCARD_PAGES = 0x40000;
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::sync::RwArc;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{
+        namespace::{unshare_namespaces, CLONE_NEW_NS_FLAGS},
+        CloneFlags,
+    },
+};
+
+pub fn sys_unshare(flags: u64, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("flags = 0x{:x}", flags);
+
+    let mut flags = CloneFlags::from(flags);
+
+    // A new mount namespace requires the file system information to be unshared.
+    if flags.contains(CloneFlags::CLONE_NEWNS) {
+        flags |= CloneFlags::CLONE_FS;
+    }
+
+    let supported_flags = CloneFlags::CLONE_FILES
+        | CloneFlags::CLONE_FS
+        | CloneFlags::CLONE_SYSVSEM
+        | CloneFlags::CLONE_THREAD
+        | CloneFlags::CLONE_SIGHAND
+        | CloneFlags::CLONE_VM
+        | CLONE_NEW_NS_FLAGS;
+    if !supported_flags.contains(flags) {
+        return_errno_with_message!(Errno::EINVAL, "the flags cannot be unshared");
+    }
+
+    // The thread-related attributes can only be unshared if the process has no other threads.
+    if flags.intersects(CloneFlags::CLONE_THREAD | CloneFlags::CLONE_SIGHAND | CloneFlags::CLONE_VM)
+        && ctx.process.tasks().lock().as_slice().len() > 1
+    {
+        return_errno_with_message!(Errno::EINVAL, "the process has more than one thread");
+    }
+
+    // FIXME: Unshare the file system information if it is shared with other threads.
+    if flags.contains(CloneFlags::CLONE_FS) && Arc::strong_count(ctx.posix_thread.fs()) > 1 {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "unsharing the file system information is not supported"
+        );
+    }
+
+    unshare_namespaces(flags, ctx)?;
+
+    if flags.contains(CloneFlags::CLONE_FILES) {
+        let mut file_table_ref = ctx.thread_local.borrow_file_table_mut();
+        let file_table = file_table_ref.unwrap();
+        let new_file_table = RwArc::new(file_table.read().clone());
+        *ctx.posix_thread.file_table().lock() = Some(new_file_table.clone_ro());
+        *file_table = new_file_table;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
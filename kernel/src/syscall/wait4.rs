@@ -3,7 +3,7 @@
 use super::{getrusage::rusage_t, SyscallReturn};
 use crate::{
     prelude::*,
-    process::{wait_child_exit, ProcessFilter, WaitOptions},
+    process::{do_wait, ProcessFilter, WaitOptions},
 };
 
 pub fn sys_wait4(
@@ -20,24 +20,30 @@ pub fn sys_wait4(
         wait_pid as i32, exit_status_ptr, wait_options
     );
     debug!("wait4 current pid = {}", ctx.process.pid());
-    let process_filter = ProcessFilter::from_id(wait_pid as _);
+    if wait_options.intersects(WaitOptions::WEXITED | WaitOptions::WNOWAIT) {
+        return_errno_with_message!(Errno::EINVAL, "the wait options are only valid for waitid");
+    }
+    let process_filter = ProcessFilter::from_id(wait_pid as _, ctx.process.pid_ns())
+        .ok_or_else(|| Error::with_message(Errno::ECHILD, "the child process does not exist"))?;
 
-    let waited_process =
-        wait_child_exit(process_filter, wait_options, ctx).map_err(|err| match err.error() {
+    let wait_status = do_wait(process_filter, wait_options | WaitOptions::WEXITED, ctx).map_err(
+        |err| match err.error() {
             Errno::EINTR => Error::new(Errno::ERESTARTSYS),
             _ => err,
-        })?;
-    let Some(process) = waited_process else {
+        },
+    )?;
+    let Some(wait_status) = wait_status else {
         return Ok(SyscallReturn::Return(0 as _));
     };
 
-    let (return_pid, exit_code) = (process.pid(), process.status().exit_code());
     if exit_status_ptr != 0 {
         ctx.user_space()
-            .write_val(exit_status_ptr as _, &exit_code)?;
+            .write_val(exit_status_ptr as _, &wait_status.as_u32())?;
     }
 
-    if rusage_addr != 0 {
+    if rusage_addr != 0
+        && let Some(process) = wait_status.process()
+    {
         let rusage = rusage_t {
             ru_utime: process.prof_clock().user_clock().read_time().into(),
             ru_stime: process.prof_clock().kernel_clock().read_time().into(),
@@ -47,5 +53,7 @@ pub fn sys_wait4(
         ctx.user_space().write_val(rusage_addr, &rusage)?;
     }
 
-    Ok(SyscallReturn::Return(return_pid as _))
+    Ok(SyscallReturn::Return(
+        wait_status.pid_in_ns(ctx.process.pid_ns()) as _,
+    ))
 }
@@ -1,28 +1,55 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::SyscallReturn;
+use super::{getrusage::rusage_t, SyscallReturn};
 use crate::{
     prelude::*,
-    process::{wait_child_exit, ProcessFilter, WaitOptions},
+    process::{do_wait, signal::c_types::siginfo_t, ProcessFilter, WaitOptions},
 };
 
 pub fn sys_waitid(
     which: u64,
     upid: u64,
-    _infoq_addr: u64,
+    infop_addr: Vaddr,
     options: u64,
-    _rusage_addr: u64,
+    rusage_addr: Vaddr,
     ctx: &Context,
 ) -> Result<SyscallReturn> {
-    // FIXME: what does infoq and rusage use for?
-    let process_filter = ProcessFilter::from_which_and_id(which, upid)?;
+    let process_filter = ProcessFilter::from_which_and_id(which, upid, ctx)?
+        .ok_or_else(|| Error::with_message(Errno::ECHILD, "the child process does not exist"))?;
     let wait_options = WaitOptions::from_bits(options as u32)
         .ok_or(Error::with_message(Errno::EINVAL, "invalid options"))?;
-    let waited_process =
-        wait_child_exit(process_filter, wait_options, ctx).map_err(|err| match err.error() {
+    if !wait_options
+        .intersects(WaitOptions::WEXITED | WaitOptions::WSTOPPED | WaitOptions::WCONTINUED)
+    {
+        return_errno_with_message!(Errno::EINVAL, "no status change to wait for");
+    }
+
+    let wait_status =
+        do_wait(process_filter, wait_options, ctx).map_err(|err| match err.error() {
             Errno::EINTR => Error::new(Errno::ERESTARTSYS),
             _ => err,
         })?;
-    let pid = waited_process.map_or(0, |process| process.pid());
-    Ok(SyscallReturn::Return(pid as _))
+
+    if infop_addr != 0 {
+        // Like Linux, the `siginfo_t` is zeroed if there is no status change with `WNOHANG`.
+        let siginfo = wait_status
+            .as_ref()
+            .map(|wait_status| wait_status.to_siginfo(ctx.process.pid_ns()))
+            .unwrap_or_else(siginfo_t::new_zeroed);
+        ctx.user_space().write_val(infop_addr, &siginfo)?;
+    }
+
+    if rusage_addr != 0
+        && let Some(process) = wait_status.as_ref().and_then(|status| status.process())
+    {
+        let rusage = rusage_t {
+            ru_utime: process.prof_clock().user_clock().read_time().into(),
+            ru_stime: process.prof_clock().kernel_clock().read_time().into(),
+            ..Default::default()
+        };
+
+        ctx.user_space().write_val(rusage_addr, &rusage)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
 }
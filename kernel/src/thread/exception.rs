@@ -3,13 +3,16 @@
 #![expect(unused_variables)]
 
 use aster_rights::Full;
-use ostd::cpu::context::{CpuExceptionInfo, UserContext};
+use ostd::{
+    cpu::context::{CpuExceptionInfo, UserContext},
+    task::Task,
+};
 
 use crate::{
     current_userspace,
     prelude::*,
-    process::signal::signals::fault::FaultSignal,
-    vm::{page_fault_handler::PageFaultHandler, perms::VmPerms, vmar::Vmar},
+    process::{self, signal::signals::fault::FaultSignal, Process},
+    vm::{page_fault_handler::PageFaultHandler, perms::VmPerms, swap, vmar::Vmar},
 };
 
 /// Page fault information converted from [`CpuExceptionInfo`].
@@ -34,7 +37,7 @@ pub fn handle_exception(ctx: &Context, context: &UserContext) {
     if let Ok(page_fault_info) = PageFaultInfo::try_from(trap_info) {
         let user_space = ctx.user_space();
         let root_vmar = user_space.root_vmar();
-        if handle_page_fault_from_vmar(root_vmar, &page_fault_info).is_ok() {
+        if handle_user_page_fault(root_vmar, &page_fault_info).is_ok() {
             return;
         }
     }
@@ -42,12 +45,81 @@ pub fn handle_exception(ctx: &Context, context: &UserContext) {
     generate_fault_signal(trap_info, ctx);
 }
 
+/// The number of pages to reclaim when a page fault runs out of memory.
+const NR_DIRECT_RECLAIM_PAGES: usize = 32;
+
+/// Handles the page fault that occurs in the user mode.
+///
+/// If the page fault cannot be handled due to memory shortage, memory is
+/// freed with [`free_memory_on_oom`] before handling it again. Unlike page
+/// faults in the kernel mode, no locks are held here, so it is safe to do so.
+fn handle_user_page_fault(
+    root_vmar: &Vmar<Full>,
+    page_fault_info: &PageFaultInfo,
+) -> core::result::Result<(), ()> {
+    let e = loop {
+        let Err(e) = root_vmar.handle_page_fault(page_fault_info) else {
+            return Ok(());
+        };
+
+        if e.error() != Errno::ENOMEM || !free_memory_on_oom() {
+            break e;
+        }
+    };
+
+    warn!(
+        "page fault handler failed: addr: 0x{:x}, err: {:?}",
+        page_fault_info.address, e
+    );
+    Err(())
+}
+
+/// Handles the memory shortage that a page fault in the kernel mode has run
+/// into, if any.
+///
+/// Like Linux, such a page fault fails the memory access (e.g., the system
+/// call returns `EFAULT`), since memory cannot be freed with locks held. This
+/// method should be called after the system call returns, where no locks are
+/// held, to free memory with [`free_memory_on_oom`]. So the memory access may
+/// succeed if it is retried.
+pub fn handle_deferred_oom(ctx: &Context) {
+    if ctx.thread_local.has_deferred_oom().replace(false) {
+        free_memory_on_oom();
+    }
+}
+
+/// Frees memory when a page fault runs out of memory.
+///
+/// Some pages are reclaimed, or some process is killed by the OOM killer if
+/// no pages can be reclaimed. If the memory limit of a cgroup is reached, a
+/// process in the cgroup is killed instead, since reclaiming pages from the
+/// whole system does not help.
+///
+/// Returns whether some memory may have been freed.
+fn free_memory_on_oom() -> bool {
+    let limited_cgroup =
+        Process::current().and_then(|process| process.cgroup().find_memory_limited());
+    if let Some(cgroup) = limited_cgroup {
+        return process::out_of_memory_in_cgroup(&cgroup);
+    }
+
+    swap::reclaim_pages(NR_DIRECT_RECLAIM_PAGES) > 0 || process::out_of_memory()
+}
+
 /// Handles the page fault occurs in the input `Vmar`.
+///
+/// If the page fault runs out of memory, freeing memory is deferred to
+/// [`handle_deferred_oom`].
 fn handle_page_fault_from_vmar(
     root_vmar: &Vmar<Full>,
     page_fault_info: &PageFaultInfo,
 ) -> core::result::Result<(), ()> {
     if let Err(e) = root_vmar.handle_page_fault(page_fault_info) {
+        if e.error() == Errno::ENOMEM {
+            let current_task = Task::current().unwrap();
+            let thread_local = current_task.as_thread_local().unwrap();
+            thread_local.has_deferred_oom().set(true);
+        }
         warn!(
             "page fault handler failed: addr: 0x{:x}, err: {:?}",
             page_fault_info.address, e
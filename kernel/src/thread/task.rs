@@ -13,10 +13,14 @@ use crate::{
     prelude::*,
     process::{
         posix_thread::{AsPosixThread, AsThreadLocal, ThreadLocal},
+        ptrace::{ptrace_interrupt_stop, ptrace_syscall_enter, ptrace_syscall_exit},
         signal::handle_pending_signal,
     },
     syscall::handle_syscall,
-    thread::{exception::handle_exception, AsThread},
+    thread::{
+        exception::{handle_deferred_oom, handle_exception},
+        AsThread,
+    },
     vm::vmar::is_userspace_vaddr,
 };
 
@@ -56,12 +60,20 @@ pub fn create_new_user_task(
         // Make sure the store operation completes before the clone call returns control to user space
         // in the child process.
         if is_userspace_vaddr(child_tid_ptr) {
+            let child_tid = current_posix_thread
+                .process()
+                .pid_ns()
+                .tid_in_ns(current_posix_thread.tid())
+                .unwrap();
             current_userspace!()
-                .write_val(child_tid_ptr, &current_posix_thread.tid())
+                .write_val(child_tid_ptr, &child_tid)
                 .unwrap();
         }
 
-        let has_kernel_event_fn = || current_posix_thread.has_pending();
+        let has_kernel_event_fn = || {
+            current_posix_thread.has_pending()
+                || current_posix_thread.ptrace().has_pending_interrupt()
+        };
 
         let ctx = Context {
             process: current_process.as_ref(),
@@ -79,16 +91,22 @@ pub fn create_new_user_task(
             match return_reason {
                 ReturnReason::UserException => handle_exception(&ctx, user_ctx),
                 ReturnReason::UserSyscall => {
-                    syscall_number = Some(user_ctx.syscall_num());
-                    handle_syscall(&ctx, user_ctx);
+                    let traced_syscall_number = ptrace_syscall_enter(&ctx, user_ctx);
+                    if traced_syscall_number.is_some() {
+                        syscall_number = traced_syscall_number;
+                        handle_syscall(&ctx, user_ctx);
+                    }
+                    ptrace_syscall_exit(&ctx, user_ctx, traced_syscall_number);
                 }
                 ReturnReason::KernelEvent => {}
             };
+            handle_deferred_oom(&ctx);
 
             if current_thread.is_exited() {
                 break;
             }
             handle_pending_signal(user_ctx, &ctx, syscall_number);
+            ptrace_interrupt_stop(&ctx, user_ctx);
             // If current is suspended, wait for a signal to wake up self
             while current_thread.is_stopped() {
                 Thread::yield_now();
@@ -4,7 +4,12 @@ use core::cmp::min;
 
 use ostd::task::Task;
 
-use super::{ip::CSocketAddrInet, netlink::CSocketAddrNetlink, unix, vsock::CSocketAddrVm};
+use super::{
+    ip::{CSocketAddrInet, CSocketAddrInet6, SIN6_LEN_RFC2133},
+    netlink::CSocketAddrNetlink,
+    unix,
+    vsock::CSocketAddrVm,
+};
 use crate::{current_userspace, net::socket::SocketAddr, prelude::*};
 
 /// Address family.
@@ -158,6 +163,13 @@ pub fn read_socket_addr_from_user(addr: Vaddr, addr_len: usize) -> Result<Socket
             let (addr, port) = CSocketAddrInet::from_bytes(storage.as_bytes()).into();
             SocketAddr::IPv4(addr, port)
         }
+        Ok(CSocketAddrFamily::AF_INET6) => {
+            if addr_len < SIN6_LEN_RFC2133 {
+                return_errno_with_message!(Errno::EINVAL, "the socket address length is too small");
+            }
+            let (addr, port) = CSocketAddrInet6::from_bytes(storage.as_bytes()).into();
+            SocketAddr::IPv6(addr, port)
+        }
         Ok(CSocketAddrFamily::AF_UNIX) => {
             let addr = unix::from_c_bytes(&storage.as_bytes()[..addr_len])?;
             SocketAddr::Unix(addr)
@@ -251,6 +263,11 @@ pub fn write_socket_addr_with_max_len(
             dest,
             max_len as usize,
         )?,
+        SocketAddr::IPv6(addr, port) => write_c_socket_address_util::<CSocketAddrInet6, _>(
+            (*addr, *port),
+            dest,
+            max_len as usize,
+        )?,
         SocketAddr::Unix(addr) => unix::into_c_bytes_and(addr, |bytes| {
             let written_len = min(bytes.len(), max_len as _);
             current_userspace!().write_bytes(dest, &mut VmReader::from(&bytes[..written_len]))?;
@@ -267,6 +284,25 @@ pub fn write_socket_addr_with_max_len(
     Ok(actual_len as i32)
 }
 
+/// Converts a socket address to the bytes of the corresponding Linux C structure.
+///
+/// This is useful if the socket address should be written to the user space of a process that is
+/// not the current one.
+///
+/// # Panics
+///
+/// Like [`write_socket_addr_with_max_len`], this method will panic if the socket address cannot be
+/// validly mapped to the corresponding Linux C structures.
+pub fn socket_addr_to_c_bytes(socket_addr: &SocketAddr) -> Vec<u8> {
+    match socket_addr {
+        SocketAddr::IPv4(addr, port) => CSocketAddrInet::from((*addr, *port)).as_bytes().to_vec(),
+        SocketAddr::IPv6(addr, port) => CSocketAddrInet6::from((*addr, *port)).as_bytes().to_vec(),
+        SocketAddr::Unix(addr) => unix::into_c_bytes_and(addr, |bytes| bytes.to_vec()),
+        SocketAddr::Netlink(addr) => CSocketAddrNetlink::from(*addr).as_bytes().to_vec(),
+        SocketAddr::Vsock(addr) => CSocketAddrVm::from(*addr).as_bytes().to_vec(),
+    }
+}
+
 // Utility function to write a C socket address to user space.
 fn write_c_socket_address_util<TCSockAddr: Pod, TSockAddr>(
     addr: TSockAddr,
@@ -6,7 +6,7 @@ use crate::{
     current_userspace,
     net::socket::{
         ip::{options::IpTtl, stream::CongestionControl},
-        LingerOption,
+        CUserCred, LingerOption,
     },
     prelude::*,
 };
@@ -136,6 +136,17 @@ impl WriteToUser for Option<Error> {
     }
 }
 
+impl WriteToUser for CUserCred {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        // Like Linux, the credentials are truncated if the buffer is too small.
+        let write_len = core::mem::size_of::<CUserCred>().min(max_len as usize);
+
+        current_userspace!()
+            .write_bytes(addr, &mut VmReader::from(&self.as_bytes()[..write_len]))?;
+        Ok(write_len)
+    }
+}
+
 impl ReadFromUser for LingerOption {
     fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
         if (max_len as usize) < core::mem::size_of::<CLinger>() {
@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use ostd::sync::RwArc;
+
 use super::read_socket_addr_from_user;
 use crate::{
-    net::socket::SocketAddr,
+    fs::file_table::{FdFlags, FileTable},
+    net::socket::{ControlMessage, SendRecvFlags, SocketAddr},
     prelude::*,
     util::{net::write_socket_addr_with_max_len, VmReaderArray, VmWriterArray},
 };
@@ -84,13 +87,13 @@ pub struct CUserMsgHdr {
     /// Scatter/Gather iov array
     pub msg_iov: Vaddr,
     /// The # of elements in msg_iov
-    pub msg_iovlen: u32,
+    pub msg_iovlen: usize,
     /// Ancillary data
     pub msg_control: Vaddr,
     /// Ancillary data buffer length
-    pub msg_controllen: u32,
+    pub msg_controllen: usize,
     /// Flags on received message
-    pub msg_flags: u32,
+    pub msg_flags: i32,
 }
 
 impl CUserMsgHdr {
@@ -116,13 +119,61 @@ impl CUserMsgHdr {
         &self,
         user_space: &'a CurrentUserSpace<'a>,
     ) -> Result<VmReaderArray<'a>> {
-        VmReaderArray::from_user_io_vecs(user_space, self.msg_iov, self.msg_iovlen as usize)
+        VmReaderArray::from_user_io_vecs(user_space, self.msg_iov, self.msg_iovlen)
     }
 
     pub fn copy_writer_array_from_user<'a>(
         &self,
         user_space: &'a CurrentUserSpace<'a>,
     ) -> Result<VmWriterArray<'a>> {
-        VmWriterArray::from_user_io_vecs(user_space, self.msg_iov, self.msg_iovlen as usize)
+        VmWriterArray::from_user_io_vecs(user_space, self.msg_iov, self.msg_iovlen)
+    }
+
+    pub fn read_control_messages_from_user(
+        &self,
+        user_space: &CurrentUserSpace,
+        file_table: &RwArc<FileTable>,
+    ) -> Result<Vec<ControlMessage>> {
+        if self.msg_controllen == 0 {
+            return Ok(Vec::new());
+        }
+
+        ControlMessage::read_all_from_user(
+            user_space,
+            self.msg_control,
+            self.msg_controllen,
+            file_table,
+        )
+    }
+
+    /// Writes the control messages to the user space.
+    ///
+    /// This method also updates `msg_controllen` and `msg_flags` accordingly, but it is the
+    /// caller's responsibility to write the updated header back to the user space.
+    pub fn write_control_messages_to_user(
+        &mut self,
+        control_messages: Vec<ControlMessage>,
+        user_space: &CurrentUserSpace,
+        file_table: &RwArc<FileTable>,
+        fd_flags: FdFlags,
+    ) -> Result<()> {
+        let written = ControlMessage::write_all_to_user(
+            control_messages,
+            user_space,
+            self.msg_control,
+            self.msg_controllen,
+            file_table,
+            fd_flags,
+        )?;
+        let flags = if written.is_truncated {
+            SendRecvFlags::MSG_CTRUNC
+        } else {
+            SendRecvFlags::empty()
+        };
+
+        self.msg_controllen = written.len;
+        self.msg_flags = flags.bits();
+
+        Ok(())
     }
 }
@@ -21,6 +21,7 @@ use osdk_heap_allocator::{type_from_layout, HeapAllocator};
 
 pub mod page_fault_handler;
 pub mod perms;
+pub mod swap;
 pub mod util;
 pub mod vmar;
 pub mod vmo;
@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Swap areas on block devices.
+
+use core::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+
+use aster_block::{
+    bio::{BioDirection, BioSegment, BioStatus},
+    id::Bid,
+    BlockDevice, BLOCK_SIZE, SECTOR_SIZE,
+};
+use id_alloc::IdAlloc;
+use ostd::{
+    const_assert,
+    mm::{UFrame, VmIo},
+};
+
+use crate::{prelude::*, process::cgroup::alloc_charged_frame};
+
+// Each slot of a swap area is a block, which holds exactly one page.
+const_assert!(BLOCK_SIZE == PAGE_SIZE);
+
+/// The signature at the end of the first page of a swap area.
+///
+/// This is the signature written by `mkswap`.
+const SWAP_SIGNATURE: &[u8; 10] = b"SWAPSPACE2";
+
+/// The version of the swap header written by `mkswap`.
+const SWAP_HEADER_VERSION: u32 = 1;
+
+/// The offset of [`SwapHeaderInfo`] in the first page.
+const SWAP_HEADER_INFO_OFFSET: usize = 1024;
+
+/// The offset of the list of bad pages in the first page.
+///
+/// The list follows the 512-byte header information.
+const SWAP_BAD_PAGES_OFFSET: usize = SWAP_HEADER_INFO_OFFSET + 512;
+
+/// The maximum number of bad pages in a swap area.
+const MAX_SWAP_BAD_PAGES: usize =
+    (PAGE_SIZE - SWAP_SIGNATURE.len() - SWAP_BAD_PAGES_OFFSET) / size_of::<u32>();
+
+/// The header information of a swap area.
+///
+/// Only the fields used by the kernel are included.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.0.9/source/include/linux/swap.h#L166>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct SwapHeaderInfo {
+    version: u32,
+    /// The index of the last usable page.
+    last_page: u32,
+    nr_bad_pages: u32,
+}
+
+/// The active swap areas, in descending order of priority.
+static SWAP_AREAS: RwLock<Vec<Arc<SwapArea>>> = RwLock::new(Vec::new());
+
+/// The priority of the next swap area that is enabled without a priority.
+///
+/// Like Linux, such swap areas have decreasing negative priorities, so that
+/// they are used in the order of being enabled.
+static NEXT_DEFAULT_PRIORITY: AtomicI32 = AtomicI32::new(-1);
+
+/// A swap area on a block device.
+///
+/// The area is divided into page-sized slots. The first slot holds the swap
+/// header, and the other slots hold the pages that are swapped out.
+#[derive(Debug)]
+pub(in crate::vm) struct SwapArea {
+    name: String,
+    device: Arc<dyn BlockDevice>,
+    priority: i32,
+    slots: SpinLock<IdAlloc>,
+    /// The number of slots that can hold pages.
+    nr_slots: usize,
+    /// The number of slots that do not hold pages.
+    nr_free_slots: AtomicUsize,
+}
+
+impl SwapArea {
+    /// Creates a swap area on the device, whose first page must contain a
+    /// valid swap header.
+    fn open(name: String, device: Arc<dyn BlockDevice>, priority: i32) -> Result<Self> {
+        let mut signature = [0u8; SWAP_SIGNATURE.len()];
+        device.read_bytes(PAGE_SIZE - SWAP_SIGNATURE.len(), &mut signature)?;
+        if &signature != SWAP_SIGNATURE {
+            return_errno_with_message!(Errno::EINVAL, "the swap signature is not found");
+        }
+
+        let info: SwapHeaderInfo = device.read_val(SWAP_HEADER_INFO_OFFSET)?;
+        if info.version != SWAP_HEADER_VERSION {
+            return_errno_with_message!(Errno::EINVAL, "the swap header version is unsupported");
+        }
+        let nr_bad_pages = info.nr_bad_pages as usize;
+        if nr_bad_pages > MAX_SWAP_BAD_PAGES {
+            return_errno_with_message!(Errno::EINVAL, "the swap area has too many bad pages");
+        }
+
+        let device_pages = device.metadata().nr_sectors * SECTOR_SIZE / PAGE_SIZE;
+        let nr_pages = (info.last_page as usize + 1).min(device_pages);
+        if nr_pages <= 1 {
+            return_errno_with_message!(Errno::EINVAL, "the swap area is empty");
+        }
+
+        let mut slots = IdAlloc::with_capacity(nr_pages);
+        // The header is never used to hold pages.
+        slots.alloc_specific(0);
+        for i in 0..nr_bad_pages {
+            let bad_page: u32 = device.read_val(SWAP_BAD_PAGES_OFFSET + i * size_of::<u32>())?;
+            if (bad_page as usize) < nr_pages {
+                slots.alloc_specific(bad_page as usize);
+            }
+        }
+        let nr_slots = (0..nr_pages).filter(|&i| !slots.is_allocated(i)).count();
+
+        Ok(Self {
+            name,
+            device,
+            priority,
+            slots: SpinLock::new(slots),
+            nr_slots,
+            nr_free_slots: AtomicUsize::new(nr_slots),
+        })
+    }
+
+    fn alloc_slot(&self) -> Option<usize> {
+        let slot = self.slots.lock().alloc()?;
+        self.nr_free_slots.fetch_sub(1, Ordering::Relaxed);
+        Some(slot)
+    }
+
+    fn free_slot(&self, slot: usize) {
+        self.slots.lock().free(slot);
+        self.nr_free_slots.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn read_page(&self, slot: usize, frame: &UFrame) -> Result<()> {
+        let bio_segment =
+            BioSegment::new_from_segment(frame.clone().into(), BioDirection::FromDevice);
+        match self
+            .device
+            .read_blocks(Bid::new(slot as u64), bio_segment)?
+        {
+            BioStatus::Complete => Ok(()),
+            err_status => Err(Error::from(err_status)),
+        }
+    }
+
+    fn write_page(&self, slot: usize, frame: &UFrame) -> Result<()> {
+        let bio_segment =
+            BioSegment::new_from_segment(frame.clone().into(), BioDirection::ToDevice);
+        match self
+            .device
+            .write_blocks(Bid::new(slot as u64), bio_segment)?
+        {
+            BioStatus::Complete => Ok(()),
+            err_status => Err(Error::from(err_status)),
+        }
+    }
+}
+
+/// A page that is swapped out to a swap area.
+///
+/// The slot in the swap area is freed when the entry is dropped.
+pub(in crate::vm) struct SwapEntry {
+    area: Arc<SwapArea>,
+    slot: usize,
+    /// The frame of the page, if the page is still in memory.
+    ///
+    /// The frame is kept until the page is written to the swap area, so that
+    /// the page can be swapped in again without waiting for the I/O.
+    cached_frame: SpinLock<Option<UFrame>>,
+}
+
+impl Debug for SwapEntry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SwapEntry")
+            .field("area", &self.area.name)
+            .field("slot", &self.slot)
+            .finish()
+    }
+}
+
+impl SwapEntry {
+    /// Allocates a slot for the page in `frame` from the swap area with the
+    /// highest priority.
+    ///
+    /// Returns `None` if there are no free slots.
+    pub(in crate::vm) fn alloc(frame: UFrame) -> Option<Arc<Self>> {
+        let areas = SWAP_AREAS.read();
+        areas.iter().find_map(|area| {
+            let slot = area.alloc_slot()?;
+            Some(Arc::new(Self {
+                area: area.clone(),
+                slot,
+                cached_frame: SpinLock::new(Some(frame.clone())),
+            }))
+        })
+    }
+
+    /// Reads the page.
+    ///
+    /// This method may involve I/O operations if the page is no longer in
+    /// memory.
+    pub(in crate::vm) fn read_page(&self) -> Result<UFrame> {
+        if let Some(frame) = self.cached_frame.lock().as_ref() {
+            return Ok(frame.clone());
+        }
+
+        let frame = alloc_charged_frame(false)?;
+        self.area.read_page(self.slot, &frame)?;
+        Ok(frame)
+    }
+
+    /// Writes the page in memory to the swap area.
+    pub(super) fn write_back(&self) -> Result<()> {
+        let Some(frame) = self.cached_frame.lock().clone() else {
+            return Ok(());
+        };
+        self.area.write_page(self.slot, &frame)
+    }
+
+    /// Takes the frame of the page out of the entry.
+    ///
+    /// After that, the page can only be read from the swap area.
+    pub(in crate::vm) fn take_cached_frame(&self) -> Option<UFrame> {
+        self.cached_frame.lock().take()
+    }
+
+    /// Returns whether the frame of the page is `frame`.
+    pub(in crate::vm) fn caches(&self, frame: &UFrame) -> bool {
+        self.cached_frame
+            .lock()
+            .as_ref()
+            .is_some_and(|cached| cached.start_paddr() == frame.start_paddr())
+    }
+
+    /// Returns whether the page is swapped out to `area`.
+    pub(in crate::vm) fn is_in(&self, area: &Arc<SwapArea>) -> bool {
+        Arc::ptr_eq(&self.area, area)
+    }
+}
+
+impl Drop for SwapEntry {
+    fn drop(&mut self) {
+        self.area.free_slot(self.slot);
+    }
+}
+
+/// Enables swapping to the block device.
+///
+/// `name` is the path of the device, which is only used for debugging.
+/// If `priority` is `None`, the swap area is given a priority lower than
+/// those of all swap areas that are enabled before.
+pub fn swap_on(name: &str, device: Arc<dyn BlockDevice>, priority: Option<i32>) -> Result<()> {
+    let priority =
+        priority.unwrap_or_else(|| NEXT_DEFAULT_PRIORITY.fetch_sub(1, Ordering::Relaxed));
+    let area = Arc::new(SwapArea::open(name.to_string(), device, priority)?);
+
+    let mut areas = SWAP_AREAS.write();
+    if areas
+        .iter()
+        .any(|other| Arc::ptr_eq(&other.device, &area.device))
+    {
+        return_errno_with_message!(Errno::EBUSY, "the device is already used for swapping");
+    }
+    insert_area(&mut areas, area);
+    drop(areas);
+
+    super::reclaim::start_reclaimer();
+    Ok(())
+}
+
+/// Disables swapping to the block device.
+///
+/// All the pages in the swap area are swapped in before this method returns.
+pub fn swap_off(device: &Arc<dyn BlockDevice>) -> Result<()> {
+    let area = {
+        let mut areas = SWAP_AREAS.write();
+        let Some(pos) = areas
+            .iter()
+            .position(|area| Arc::ptr_eq(&area.device, device))
+        else {
+            return_errno_with_message!(Errno::EINVAL, "the device is not used for swapping");
+        };
+        areas.remove(pos)
+    };
+
+    if let Err(err) = super::reclaim::swap_in_all(&area) {
+        // Put the area back since some pages are still in it.
+        insert_area(&mut SWAP_AREAS.write(), area);
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Inserts the swap area after the areas with higher or equal priorities.
+fn insert_area(areas: &mut Vec<Arc<SwapArea>>, area: Arc<SwapArea>) {
+    let pos = areas.partition_point(|other| other.priority >= area.priority);
+    areas.insert(pos, area);
+}
+
+/// Returns whether there are free slots in any swap area.
+pub(in crate::vm) fn has_free_slots() -> bool {
+    SWAP_AREAS
+        .read()
+        .iter()
+        .any(|area| area.nr_free_slots.load(Ordering::Relaxed) > 0)
+}
+
+/// Returns the total size (in bytes) of all swap areas.
+pub fn total_swap_size() -> usize {
+    SWAP_AREAS
+        .read()
+        .iter()
+        .map(|area| area.nr_slots * PAGE_SIZE)
+        .sum()
+}
+
+/// Returns the total size (in bytes) of free space in all swap areas.
+pub fn free_swap_size() -> usize {
+    SWAP_AREAS
+        .read()
+        .iter()
+        .map(|area| area.nr_free_slots.load(Ordering::Relaxed) * PAGE_SIZE)
+        .sum()
+}
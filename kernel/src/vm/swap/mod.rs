@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Swapping.
+//!
+//! When memory runs low, the pages of anonymous VMOs can be written to swap
+//! areas on block devices, so that their frames can be freed. A page that is
+//! swapped out is read back when it is committed again, e.g., on page faults.
+//!
+//! Only anonymous pages are swapped out, including the pages owned by VMOs
+//! without pagers and the private anonymous pages of mappings (e.g., heaps
+//! and stacks). The latter are recorded in their mappings once swapped out.
+//! The pages of file-backed VMOs can be written back to their files instead.
+
+mod area;
+mod reclaim;
+
+pub use area::{free_swap_size, swap_off, swap_on, total_swap_size};
+pub(in crate::vm) use area::{has_free_slots, SwapArea, SwapEntry};
+pub use reclaim::reclaim_pages;
+pub(in crate::vm) use reclaim::{
+    page_out, page_out_anon, register_vmo, wake_up_reclaimer_if_needed, ReclaimCandidate,
+};
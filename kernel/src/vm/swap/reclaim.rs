@@ -0,0 +1,454 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Page reclaim.
+//!
+//! Anonymous pages are reclaimed by swapping them out. The pages to reclaim
+//! are chosen with the clock algorithm, which approximates LRU with the
+//! accessed bits in the page tables. There are two clock hands. One sweeps
+//! over the committed pages of all anonymous VMOs, and the other sweeps over
+//! the private anonymous pages mapped in the address spaces of all processes
+//! (e.g., heaps and stacks). If a page has been accessed since the hand last
+//! passed it, the accessed bits are cleared and the page is given a second
+//! chance. Otherwise, the page is unmapped from all VM spaces and written to
+//! a swap area.
+//!
+//! Pages are reclaimed by a kernel thread when free memory runs low, by the
+//! page fault handler when it runs out of memory, and on `MADV_PAGEOUT`.
+
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use aster_rights::Full;
+use ostd::{mm::UFrame, sync::WaitQueue};
+use spin::Once;
+
+use super::area::{self, SwapArea, SwapEntry};
+use crate::{
+    prelude::*,
+    process::{process_table, Pid, Process},
+    sched::{Nice, SchedPolicy},
+    thread::kernel_thread::ThreadOptions,
+    vm::{vmar::Vmar, vmo::Vmo_},
+};
+
+/// The maximum number of pages that are reclaimed in a batch.
+const RECLAIM_BATCH_SIZE: usize = 32;
+
+/// The time to wait before reclaiming again if no pages can be reclaimed.
+const RECLAIM_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The anonymous VMOs, whose pages can be reclaimed.
+static ANON_VMOS: SpinLock<AnonVmos> = SpinLock::new(AnonVmos::new());
+
+/// The clock hand, whose lock also serializes reclaiming.
+static CLOCK_HAND: Mutex<ClockHand> = Mutex::new(ClockHand::new());
+
+/// The wait queue of the reclaimer thread.
+static RECLAIMER_WAIT_QUEUE: WaitQueue = WaitQueue::new();
+
+/// The reclaimer thread is started when swapping is first enabled.
+static RECLAIMER: Once<()> = Once::new();
+
+/// The amount of free memory (in bytes) below which the reclaimer thread
+/// starts to reclaim pages.
+static LOW_WATERMARK: AtomicUsize = AtomicUsize::new(0);
+
+struct AnonVmos {
+    vmos: Vec<Weak<Vmo_>>,
+    /// The number of VMOs at which dropped VMOs are removed from the list.
+    prune_threshold: usize,
+}
+
+impl AnonVmos {
+    const MIN_PRUNE_THRESHOLD: usize = 64;
+
+    const fn new() -> Self {
+        Self {
+            vmos: Vec::new(),
+            prune_threshold: Self::MIN_PRUNE_THRESHOLD,
+        }
+    }
+
+    fn push(&mut self, vmo: Weak<Vmo_>) {
+        if self.vmos.len() >= self.prune_threshold {
+            self.vmos.retain(|vmo| vmo.strong_count() > 0);
+            self.prune_threshold = (self.vmos.len() * 2).max(Self::MIN_PRUNE_THRESHOLD);
+        }
+        self.vmos.push(vmo);
+    }
+}
+
+/// A page that is chosen to be reclaimed.
+pub(in crate::vm) struct ReclaimCandidate {
+    pub(in crate::vm) vmo: Arc<Vmo_>,
+    pub(in crate::vm) page_idx: usize,
+    pub(in crate::vm) frame: UFrame,
+    /// Whether the page has been accessed since the last scan.
+    pub(in crate::vm) is_referenced: bool,
+}
+
+impl ReclaimCandidate {
+    pub(in crate::vm) fn new(vmo: Arc<Vmo_>, page_idx: usize, frame: UFrame) -> Self {
+        Self {
+            vmo,
+            page_idx,
+            frame,
+            is_referenced: false,
+        }
+    }
+}
+
+/// The positions of the clock hands.
+struct ClockHand {
+    /// The position of the VMO in [`ANON_VMOS`].
+    vmo_pos: usize,
+    /// The index of the page in the VMO.
+    page_idx: usize,
+    /// The PID of the process whose private anonymous pages are scanned.
+    pid: Pid,
+    /// The address in the process to continue scanning from.
+    addr: Vaddr,
+}
+
+impl ClockHand {
+    const fn new() -> Self {
+        Self {
+            vmo_pos: 0,
+            page_idx: 0,
+            pid: 0,
+            addr: 0,
+        }
+    }
+
+    fn reset_vmo_hand(&mut self) {
+        self.vmo_pos = 0;
+        self.page_idx = 0;
+    }
+
+    fn reset_anon_hand(&mut self) {
+        self.pid = 0;
+        self.addr = 0;
+    }
+
+    /// Advances the clock hand over at most `max_pages` committed pages.
+    ///
+    /// Returns the passed pages, and whether the hand has gone back to the
+    /// first VMO.
+    fn advance(&mut self, max_pages: usize) -> (Vec<ReclaimCandidate>, bool) {
+        let mut candidates = Vec::new();
+
+        while candidates.len() < max_pages {
+            let Some(vmo) = ANON_VMOS.lock().vmos.get(self.vmo_pos).cloned() else {
+                self.reset_vmo_hand();
+                return (candidates, true);
+            };
+
+            let next_page_idx = vmo.upgrade().and_then(|vmo| {
+                // The pages of the VMO may be locked in memory.
+                if !vmo.is_swappable() {
+                    return None;
+                }
+
+                let (pages, next_page_idx) =
+                    vmo.committed_pages_in(self.page_idx..usize::MAX, max_pages - candidates.len());
+                candidates.extend(
+                    pages.into_iter().map(|(page_idx, frame)| {
+                        ReclaimCandidate::new(vmo.clone(), page_idx, frame)
+                    }),
+                );
+                next_page_idx
+            });
+
+            match next_page_idx {
+                Some(page_idx) => self.page_idx = page_idx,
+                None => {
+                    self.vmo_pos += 1;
+                    self.page_idx = 0;
+                }
+            }
+        }
+
+        (candidates, false)
+    }
+
+    /// Advances the clock hand over at most `max_pages` private anonymous
+    /// pages mapped in the processes, and unmaps the pages that are not
+    /// accessed since the hand last passed them.
+    ///
+    /// Returns the swap entries of the unmapped pages, and whether the hand
+    /// has gone back to the first process.
+    fn advance_anon(&mut self, max_pages: usize) -> (Vec<Arc<SwapEntry>>, bool) {
+        let Some(process) = next_process(self.pid) else {
+            self.reset_anon_hand();
+            return (Vec::new(), true);
+        };
+        if process.pid() != self.pid {
+            self.pid = process.pid();
+            self.addr = 0;
+        }
+
+        let root_vmar = process.lock_root_vmar();
+        let Some(root_vmar) = root_vmar.get() else {
+            self.pid += 1;
+            self.addr = 0;
+            return (Vec::new(), false);
+        };
+        let (swap_entries, next_addr) =
+            root_vmar.unmap_anon_pages(self.addr..usize::MAX, max_pages, false);
+        match next_addr {
+            Some(addr) => self.addr = addr,
+            None => {
+                self.pid += 1;
+                self.addr = 0;
+            }
+        }
+
+        (swap_entries, false)
+    }
+}
+
+/// Returns the process with the smallest PID that is no less than `pid`.
+fn next_process(pid: Pid) -> Option<Arc<Process>> {
+    process_table::process_table_mut()
+        .iter()
+        .find(|process| process.pid() >= pid)
+        .cloned()
+}
+
+/// Registers an anonymous VMO, whose pages can be reclaimed.
+pub(in crate::vm) fn register_vmo(vmo: &Arc<Vmo_>) {
+    ANON_VMOS.lock().push(Arc::downgrade(vmo));
+}
+
+/// Reclaims at most `nr_pages` pages.
+///
+/// Returns the number of reclaimed pages.
+pub fn reclaim_pages(nr_pages: usize) -> usize {
+    let mut clock_hand = CLOCK_HAND.lock();
+
+    let mut nr_reclaimed = 0;
+    let mut nr_vmo_sweeps = 0;
+    let mut nr_anon_sweeps = 0;
+    // A page is reclaimed after the clock hand passes it twice without it
+    // being accessed, so two sweeps are enough to find all reclaimable pages.
+    while nr_reclaimed < nr_pages
+        && (nr_vmo_sweeps < 2 || nr_anon_sweeps < 2)
+        && area::has_free_slots()
+    {
+        if nr_vmo_sweeps < 2 {
+            let max_pages = (nr_pages - nr_reclaimed).min(RECLAIM_BATCH_SIZE);
+            let (candidates, has_swept) = clock_hand.advance(max_pages);
+            if has_swept {
+                nr_vmo_sweeps += 1;
+            }
+            nr_reclaimed += swap_out(candidates, false);
+        }
+
+        if nr_anon_sweeps < 2 && nr_reclaimed < nr_pages {
+            let max_pages = (nr_pages - nr_reclaimed).min(RECLAIM_BATCH_SIZE);
+            let (swap_entries, has_swept) = clock_hand.advance_anon(max_pages);
+            if has_swept {
+                nr_anon_sweeps += 1;
+            }
+            nr_reclaimed += write_back_anon(swap_entries);
+        }
+    }
+
+    nr_reclaimed
+}
+
+/// Reclaims the private anonymous pages mapped in `range` of the VMAR, no
+/// matter whether they are accessed recently.
+///
+/// Returns the number of reclaimed pages.
+pub(in crate::vm) fn page_out_anon<R>(vmar: &Vmar<R>, range: Range<Vaddr>) -> usize {
+    let _clock_hand = CLOCK_HAND.lock();
+
+    let mut nr_reclaimed = 0;
+    let mut start = range.start;
+    while start < range.end && area::has_free_slots() {
+        let (swap_entries, next_addr) =
+            vmar.unmap_anon_pages(start..range.end, RECLAIM_BATCH_SIZE, true);
+        nr_reclaimed += write_back_anon(swap_entries);
+        match next_addr {
+            Some(addr) => start = addr,
+            None => break,
+        }
+    }
+
+    nr_reclaimed
+}
+
+/// Reclaims the pages no matter whether they are accessed recently.
+///
+/// Returns the number of reclaimed pages.
+pub(in crate::vm) fn page_out(candidates: Vec<ReclaimCandidate>) -> usize {
+    let _clock_hand = CLOCK_HAND.lock();
+
+    let mut candidates = candidates;
+    let mut nr_reclaimed = 0;
+    while !candidates.is_empty() && area::has_free_slots() {
+        let batch_size = candidates.len().min(RECLAIM_BATCH_SIZE);
+        let rest = candidates.split_off(batch_size);
+        nr_reclaimed += swap_out(candidates, true);
+        candidates = rest;
+    }
+
+    nr_reclaimed
+}
+
+/// Swaps out the pages to the swap areas.
+///
+/// The pages that are accessed since the last scan are skipped, unless
+/// `ignore_referenced` is true.
+///
+/// Returns the number of pages that are swapped out.
+fn swap_out(mut candidates: Vec<ReclaimCandidate>, ignore_referenced: bool) -> usize {
+    if candidates.is_empty() {
+        return 0;
+    }
+
+    for_each_root_vmar(|root_vmar| {
+        root_vmar.unmap_reclaimed_pages(&mut candidates, ignore_referenced);
+    });
+
+    let mut nr_swapped = 0;
+    for candidate in candidates {
+        if candidate.is_referenced {
+            continue;
+        }
+
+        let ReclaimCandidate {
+            vmo,
+            page_idx,
+            frame,
+            ..
+        } = candidate;
+        let Some(swap_entry) = SwapEntry::alloc(frame) else {
+            break;
+        };
+        if !vmo.start_swap_out(page_idx, &swap_entry) {
+            continue;
+        }
+
+        let res = swap_entry.write_back();
+        if let Err(err) = &res {
+            warn!("failed to write {:?}: {:?}", swap_entry, err);
+        }
+        if vmo.finish_swap_out(page_idx, &swap_entry, res.is_ok()) {
+            nr_swapped += 1;
+        }
+    }
+
+    nr_swapped
+}
+
+/// Writes the private anonymous pages that have been unmapped to the swap
+/// areas.
+///
+/// If a page fails to be written, it stays in memory with its swap entry.
+///
+/// Returns the number of pages that are swapped out.
+fn write_back_anon(swap_entries: Vec<Arc<SwapEntry>>) -> usize {
+    let mut nr_swapped = 0;
+    for swap_entry in swap_entries {
+        if let Err(err) = swap_entry.write_back() {
+            warn!("failed to write {:?}: {:?}", swap_entry, err);
+            continue;
+        }
+
+        // The page may have been swapped in during the write, in which case
+        // the frame is still used after being taken out of the entry.
+        if swap_entry
+            .take_cached_frame()
+            .is_some_and(|frame| frame.reference_count() == 1)
+        {
+            nr_swapped += 1;
+        }
+    }
+
+    nr_swapped
+}
+
+fn for_each_root_vmar<F>(mut f: F)
+where
+    F: FnMut(&Vmar<Full>),
+{
+    let processes: Vec<_> = process_table::process_table_mut().iter().cloned().collect();
+    for process in processes {
+        let root_vmar = process.lock_root_vmar();
+        if let Some(root_vmar) = root_vmar.get() {
+            f(root_vmar);
+        }
+    }
+}
+
+/// Swaps in all the pages in the swap area.
+pub(super) fn swap_in_all(area: &Arc<SwapArea>) -> Result<()> {
+    let vmos: Vec<_> = ANON_VMOS
+        .lock()
+        .vmos
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect();
+
+    for vmo in vmos {
+        vmo.swap_in_from(area)?;
+    }
+
+    let mut res = Ok(());
+    for_each_root_vmar(|root_vmar| {
+        if res.is_ok() {
+            res = root_vmar.swap_in_anon_pages(area);
+        }
+    });
+    res
+}
+
+/// Wakes up the reclaimer thread if free memory runs low.
+pub(in crate::vm) fn wake_up_reclaimer_if_needed() {
+    if RECLAIMER.get().is_none() {
+        return;
+    }
+
+    if osdk_frame_allocator::load_total_free_size() < LOW_WATERMARK.load(Ordering::Relaxed) {
+        RECLAIMER_WAIT_QUEUE.wake_one();
+    }
+}
+
+/// Starts the reclaimer thread if it is not started yet.
+pub(super) fn start_reclaimer() {
+    RECLAIMER.call_once(|| {
+        // Like Linux, the reclaimer thread reclaims pages until the free
+        // memory is above the high watermark once it drops below the low
+        // watermark.
+        let low_watermark = crate::vm::mem_total() / 64;
+        let high_watermark = low_watermark * 2;
+        LOW_WATERMARK.store(low_watermark, Ordering::Relaxed);
+
+        let task_fn = move || loop {
+            RECLAIMER_WAIT_QUEUE.wait_until(|| {
+                (osdk_frame_allocator::load_total_free_size() < low_watermark).then_some(())
+            });
+
+            let mut is_progressing = true;
+            while is_progressing && osdk_frame_allocator::load_total_free_size() < high_watermark {
+                let nr_pages =
+                    (high_watermark - osdk_frame_allocator::load_total_free_size()) / PAGE_SIZE;
+                is_progressing = reclaim_pages(nr_pages.max(1)) > 0;
+            }
+
+            if !is_progressing {
+                // Nothing can be reclaimed for now, so back off for a while.
+                let _ = RECLAIMER_WAIT_QUEUE.wait_until_or_timeout(|| None::<()>, &RECLAIM_BACKOFF);
+            }
+        };
+
+        ThreadOptions::new(task_fn)
+            .sched_policy(SchedPolicy::Fair(Nice::MIN))
+            .spawn();
+    });
+}
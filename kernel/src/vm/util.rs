@@ -1,14 +1,15 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use ostd::mm::{Frame, FrameAllocOptions, UFrame, UntypedMem};
+use ostd::mm::{UFrame, UntypedMem};
 
-use crate::prelude::*;
+use crate::{prelude::*, process::cgroup::alloc_charged_frame};
 
-/// Creates a new `Frame<()>` and initializes it with the contents of the `src`.
+/// Creates a new `UFrame` and initializes it with the contents of the `src`.
 ///
-/// Note that it only duplicates the contents not the metadata.
-pub fn duplicate_frame(src: &UFrame) -> Result<Frame<()>> {
-    let new_frame = FrameAllocOptions::new().zeroed(false).alloc_frame()?;
+/// Note that it only duplicates the contents not the metadata. The new frame
+/// is charged to the cgroup of the current process.
+pub fn duplicate_frame(src: &UFrame) -> Result<UFrame> {
+    let new_frame = alloc_charged_frame(false)?;
     new_frame.writer().write(&mut src.reader());
     Ok(new_frame)
 }
@@ -11,19 +11,24 @@ use core::{num::NonZeroUsize, ops::Range};
 
 use align_ext::AlignExt;
 use aster_rights::Rights;
-use ostd::mm::{tlb::TlbFlushOp, PageFlags, PageProperty, VmSpace, MAX_USERSPACE_VADDR};
+use ostd::mm::{
+    tlb::TlbFlushOp, vm_space::VmItem, PageFlags, PageProperty, UFrame, VmIo, VmSpace,
+    MAX_USERSPACE_VADDR,
+};
 
 use self::{
     interval_set::{Interval, IntervalSet},
     vm_mapping::{MappedVmo, VmMapping},
 };
 use crate::{
+    fs::path::Dentry,
     prelude::*,
     process::{Process, ResourceType},
     thread::exception::PageFaultInfo,
     vm::{
         perms::VmPerms,
-        vmo::{Vmo, VmoRightsOp},
+        swap::{self, ReclaimCandidate, SwapArea, SwapEntry},
+        vmo::{get_page_idx_range, Vmo, VmoRightsOp},
     },
 };
 
@@ -88,6 +93,175 @@ impl<R> Vmar<R> {
     pub fn resize_mapping(&self, map_addr: Vaddr, old_size: usize, new_size: usize) -> Result<()> {
         self.0.resize_mapping(map_addr, old_size, new_size)
     }
+
+    /// Remaps a part of an existing mapping, possibly moving it elsewhere.
+    ///
+    /// The range `old_addr..old_addr + old_size` must be covered by a single
+    /// [`VmMapping`]. Otherwise, this method will return `Err`.
+    ///
+    /// If the new size is smaller than the old size, the mapping is shrunk.
+    /// If it is larger, the mapping is first grown in place when the
+    /// following range is free. When the mapping has to move, as decided by
+    /// `target`, the mapped pages are relocated to the new range without
+    /// being copied.
+    ///
+    /// If `keep_old` is true, the old range stays mapped after moving, but
+    /// with all its pages moved away.
+    ///
+    /// On success, the start address of the remapped range is returned.
+    pub fn remap(
+        &self,
+        old_addr: Vaddr,
+        old_size: usize,
+        new_size: usize,
+        target: RemapTarget,
+        keep_old: bool,
+    ) -> Result<Vaddr> {
+        self.0.remap(old_addr, old_size, new_size, target, keep_old)
+    }
+
+    /// Returns the offset in `vmo` that `addr` is mapped to.
+    ///
+    /// Returns `None` if `addr` is not in a mapping of `vmo`.
+    pub fn vmo_offset_at(&self, addr: Vaddr, vmo: &Vmo) -> Option<usize> {
+        self.0.vmo_offset_at(addr, vmo)
+    }
+
+    /// Removes the mappings of `vmo` that were created by mapping the whole
+    /// VMO at `map_addr`.
+    ///
+    /// Only the mappings within `map_addr..map_addr + vmo.size()` that map
+    /// each address to the same offset as the original mapping are removed.
+    /// Other mappings in the range are left untouched.
+    pub fn remove_vmo_mappings(&self, map_addr: Vaddr, vmo: &Vmo) -> Result<()> {
+        self.0.remove_vmo_mappings(map_addr, vmo)
+    }
+
+    /// Reclaims the anonymous pages that are mapped in `range`.
+    ///
+    /// The pages of anonymous VMOs and the private anonymous pages of the
+    /// mappings are swapped out even if they are accessed recently. Other
+    /// pages are left untouched.
+    ///
+    /// Returns the number of reclaimed pages.
+    pub fn page_out(&self, range: Range<Vaddr>) -> usize {
+        let candidates = self.0.collect_reclaim_candidates(range.clone());
+        swap::page_out(candidates) + swap::page_out_anon(self, range)
+    }
+
+    /// Unmaps the private anonymous pages in `range` so that they can be
+    /// swapped out.
+    ///
+    /// At most `max_pages` mapped pages are scanned. If a page has been
+    /// accessed since the last scan, the accessed bit is cleared instead,
+    /// unless `ignore_referenced` is true.
+    ///
+    /// Returns the swap entries of the unmapped pages, which still need to be
+    /// written to the swap areas, and the address to continue scanning from,
+    /// which is `None` if the end of the range is reached.
+    pub(in crate::vm) fn unmap_anon_pages(
+        &self,
+        range: Range<Vaddr>,
+        max_pages: usize,
+        ignore_referenced: bool,
+    ) -> (Vec<Arc<SwapEntry>>, Option<Vaddr>) {
+        self.0.unmap_anon_pages(range, max_pages, ignore_referenced)
+    }
+
+    /// Swaps in all the private anonymous pages that are swapped out to the
+    /// swap area.
+    pub(in crate::vm) fn swap_in_anon_pages(&self, area: &Arc<SwapArea>) -> Result<()> {
+        self.0.swap_in_anon_pages(area)
+    }
+
+    /// Unmaps the pages to be reclaimed from the mappings in the VMAR.
+    ///
+    /// If a page has been accessed since the last scan, the page stays mapped
+    /// and is marked as referenced, unless `ignore_referenced` is true.
+    pub(in crate::vm) fn unmap_reclaimed_pages(
+        &self,
+        candidates: &mut [ReclaimCandidate],
+        ignore_referenced: bool,
+    ) {
+        self.0.unmap_reclaimed_pages(candidates, ignore_referenced)
+    }
+
+    /// Returns the resident set size (RSS) of the VMAR in pages.
+    ///
+    /// This is the number of pages that are mapped in the page table, so the
+    /// pages that are shared with other VMARs are counted in each of them.
+    pub fn rss_pages(&self) -> usize {
+        self.0.rss_pages()
+    }
+
+    /// Returns the information about the mappings in the VMAR, sorted by
+    /// their addresses.
+    pub fn mappings_info(&self) -> Vec<VmMappingInfo> {
+        self.0.mappings_info()
+    }
+
+    /// Reads the page at `addr` for a core dump.
+    ///
+    /// Unlike [`Self::read_remote`], this method does not allocate the pages
+    /// that have never been touched in anonymous mappings. Instead, it
+    /// returns `false` without reading the page, since the page is full of
+    /// zeros.
+    pub fn read_page_for_dump(&self, addr: Vaddr, buf: &mut [u8]) -> Result<bool> {
+        self.0.read_page_for_dump(addr, buf)
+    }
+
+    /// Reads the bytes at `addr` on behalf of another process (e.g., a tracer).
+    ///
+    /// The pages are faulted in if they are not present yet.
+    pub fn read_remote(&self, addr: Vaddr, buf: &mut [u8]) -> Result<()> {
+        self.0
+            .access_remote(addr, buf.len(), false, |frame, offset, range| {
+                frame.read_bytes(offset, &mut buf[range])?;
+                Ok(())
+            })
+    }
+
+    /// Writes the bytes to `addr` on behalf of another process (e.g., a tracer).
+    ///
+    /// Unlike writes from the user space, writes to private mappings are
+    /// allowed even if the mappings are not writable, in which case the pages
+    /// are copied on write. This allows debuggers to insert breakpoints into
+    /// the code of the tracees.
+    pub fn write_remote(&self, addr: Vaddr, buf: &[u8]) -> Result<()> {
+        self.0
+            .access_remote(addr, buf.len(), true, |frame, offset, range| {
+                frame.write_bytes(offset, &buf[range])?;
+                Ok(())
+            })
+    }
+}
+
+/// The information about a mapping, which is returned by
+/// [`Vmar::mappings_info`].
+#[derive(Debug, Clone)]
+pub struct VmMappingInfo {
+    /// The address range of the mapping.
+    pub range: Range<Vaddr>,
+    /// The permissions of the mapping.
+    pub perms: VmPerms,
+    /// Whether the mapping is shared.
+    pub is_shared: bool,
+    /// The mapped file and the offset in the file, if the mapping is
+    /// file-backed.
+    pub file: Option<(Dentry, usize)>,
+}
+
+/// Specifies where [`Vmar::remap`] may place the remapped range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemapTarget {
+    /// The range must stay at its original address.
+    InPlace,
+    /// The range may be moved to an address chosen by the VMAR.
+    MayMove,
+    /// The range must be moved to the given address.
+    ///
+    /// Any existing mappings in the target range will be unmapped.
+    Fixed(Vaddr),
 }
 
 pub(super) struct Vmar_ {
@@ -218,6 +392,60 @@ impl VmarInner {
         Ok(offset..(offset + size))
     }
 
+    /// Creates a mapping without any mapped pages for `range`.
+    ///
+    /// The range must be covered by a single mapping, whose properties are
+    /// inherited by the new mapping. The new mapping is not inserted.
+    fn new_empty_mapping(&self, range: &Range<Vaddr>) -> Result<VmMapping> {
+        let vm_mapping = self.vm_mappings.find_one(&range.start).unwrap();
+        let (_, empty_mapping, _) = vm_mapping.new_empty()?.split_range(range)?;
+        Ok(empty_mapping)
+    }
+
+    /// Moves the `old_range` of a mapping to `new_addr` and resizes it to
+    /// `new_size`.
+    ///
+    /// The old range must be covered by a single mapping, and the new range
+    /// must be free. If `placeholder` is provided, it occupies the old range
+    /// after the move.
+    fn move_mapping(
+        &mut self,
+        vm_space: &VmSpace,
+        old_range: Range<Vaddr>,
+        new_addr: Vaddr,
+        new_size: usize,
+        placeholder: Option<VmMapping>,
+    ) -> Result<()> {
+        let vm_mapping_addr = self
+            .vm_mappings
+            .find_one(&old_range.start)
+            .unwrap()
+            .map_to_addr();
+        let vm_mapping = self.remove(&vm_mapping_addr).unwrap();
+
+        let (left, taken, right) = vm_mapping.split_range(&old_range)?;
+        if let Some(left) = left {
+            self.insert(left);
+        }
+        if let Some(right) = right {
+            self.insert(right);
+        }
+        if let Some(placeholder) = placeholder {
+            self.insert(placeholder);
+        }
+
+        let old_size = taken.map_size();
+        let moved = taken.remap(vm_space, new_addr);
+        let moved = if new_size > old_size {
+            moved.enlarge(new_size - old_size)
+        } else {
+            moved
+        };
+        self.insert(moved);
+
+        Ok(())
+    }
+
     /// Allocates a free region for mapping.
     ///
     /// If no such region is found, return an error.
@@ -319,18 +547,25 @@ impl Vmar_ {
             let intersected_range = get_intersected_range(&range, &vm_mapping_range);
 
             // Protects part of the taken `VmMapping`.
-            let (left, taken, right) = vm_mapping.split_range(&intersected_range)?;
-
-            let taken = taken.protect(vm_space.as_ref(), perms);
-            inner.insert(taken);
+            let (left, mut taken, right) = vm_mapping.split_range(&intersected_range)?;
 
-            // And put the rest back.
+            // Put the rest back.
             if let Some(left) = left {
                 inner.insert(left);
             }
             if let Some(right) = right {
                 inner.insert(right);
             }
+
+            if perms.contains(VmPerms::WRITE)
+                && let Err(err) = taken.map_writable()
+            {
+                inner.insert(taken);
+                return Err(err);
+            }
+
+            let taken = taken.protect(vm_space.as_ref(), perms);
+            inner.insert(taken);
         }
 
         Ok(())
@@ -343,6 +578,8 @@ impl Vmar_ {
             return_errno_with_message!(Errno::EACCES, "page fault addr is not in current vmar");
         }
 
+        swap::wake_up_reclaimer_if_needed();
+
         let inner = self.inner.read();
 
         if let Some(vm_mapping) = inner.vm_mappings.find_one(&address) {
@@ -367,6 +604,235 @@ impl Vmar_ {
         Ok(())
     }
 
+    /// Accesses the `len` bytes at `addr` page by page with `op`.
+    ///
+    /// `op` is called with the frame, the offset in the frame, and the range
+    /// of the accessed bytes relative to `addr`.
+    fn access_remote<F>(&self, addr: Vaddr, len: usize, is_write: bool, mut op: F) -> Result<()>
+    where
+        F: FnMut(&UFrame, usize, Range<usize>) -> Result<()>,
+    {
+        let inner = self.inner.read();
+
+        let mut accessed_len = 0;
+        while accessed_len < len {
+            let cur_addr = addr
+                .checked_add(accessed_len)
+                .ok_or_else(|| Error::with_message(Errno::EIO, "the address overflows"))?;
+            let Some(vm_mapping) = inner.vm_mappings.find_one(&cur_addr) else {
+                return_errno_with_message!(Errno::EIO, "the address is not mapped");
+            };
+
+            let offset = cur_addr % PAGE_SIZE;
+            let page_len = (PAGE_SIZE - offset).min(len - accessed_len);
+            vm_mapping.access_remote(&self.vm_space, cur_addr, is_write, |frame| {
+                op(frame, offset, accessed_len..accessed_len + page_len)
+            })?;
+
+            accessed_len += page_len;
+        }
+
+        Ok(())
+    }
+
+    fn mappings_info(&self) -> Vec<VmMappingInfo> {
+        let inner = self.inner.read();
+        inner
+            .vm_mappings
+            .iter()
+            .map(|vm_mapping| VmMappingInfo {
+                range: vm_mapping.range(),
+                perms: vm_mapping.perms(),
+                is_shared: vm_mapping.is_shared(),
+                file: vm_mapping
+                    .file_and_offset()
+                    .map(|(file, offset)| (file.clone(), offset)),
+            })
+            .collect()
+    }
+
+    fn read_page_for_dump(&self, addr: Vaddr, buf: &mut [u8]) -> Result<bool> {
+        debug_assert!(addr % PAGE_SIZE == 0 && buf.len() == PAGE_SIZE);
+
+        let inner = self.inner.read();
+        let Some(vm_mapping) = inner.vm_mappings.find_one(&addr) else {
+            return_errno_with_message!(Errno::EIO, "the address is not mapped");
+        };
+
+        // Read the page directly if it is mapped.
+        let mut cursor = self.vm_space.cursor(&(addr..addr + PAGE_SIZE))?;
+        if let VmItem::Mapped { frame, .. } = cursor.query()? {
+            frame.read_bytes(0, buf)?;
+            return Ok(true);
+        }
+        drop(cursor);
+
+        // Otherwise, the page is faulted in only if it may contain data.
+        if vm_mapping.is_swapped_out(addr) {
+            vm_mapping.access_remote(&self.vm_space, addr, false, |frame| {
+                frame.read_bytes(0, buf)?;
+                Ok(())
+            })?;
+            return Ok(true);
+        }
+        let Some((vmo, vmo_offset)) = vm_mapping.vmo_and_offset() else {
+            return Ok(false);
+        };
+        let page_idx = (vmo_offset + (addr - vm_mapping.map_to_addr())) / PAGE_SIZE;
+        if !vmo.0.may_have_data(page_idx) {
+            return Ok(false);
+        }
+        vm_mapping.access_remote(&self.vm_space, addr, false, |frame| {
+            frame.read_bytes(0, buf)?;
+            Ok(())
+        })?;
+        Ok(true)
+    }
+
+    fn vmo_offset_at(&self, addr: Vaddr, vmo: &Vmo) -> Option<usize> {
+        let inner = self.inner.read();
+        let vm_mapping = inner.vm_mappings.find_one(&addr)?;
+        let (mapped_vmo, offset) = vm_mapping.vmo_and_offset()?;
+        (mapped_vmo == vmo).then(|| offset + (addr - vm_mapping.map_to_addr()))
+    }
+
+    fn remove_vmo_mappings(&self, map_addr: Vaddr, vmo: &Vmo) -> Result<()> {
+        let range = map_addr..map_addr + vmo.size();
+        let mut inner = self.inner.write();
+
+        let mut ranges_to_remove = Vec::new();
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let Some((mapped_vmo, offset)) = vm_mapping.vmo_and_offset() else {
+                continue;
+            };
+            let mapping_range = vm_mapping.range();
+            if mapped_vmo == vmo
+                && mapping_range.start >= map_addr
+                && offset == mapping_range.start - map_addr
+            {
+                ranges_to_remove.push(get_intersected_range(&range, &mapping_range));
+            }
+        }
+
+        for range in ranges_to_remove {
+            inner.alloc_free_region_exact_truncate(&self.vm_space, range.start, range.len())?;
+        }
+        Ok(())
+    }
+
+    fn collect_reclaim_candidates(&self, range: Range<Vaddr>) -> Vec<ReclaimCandidate> {
+        let inner = self.inner.read();
+
+        let mut candidates = Vec::new();
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let Some((vmo, vmo_offset)) = vm_mapping.vmo_and_offset() else {
+                continue;
+            };
+            if !vmo.0.is_swappable() {
+                continue;
+            }
+
+            let mapping_range = get_intersected_range(&range, &vm_mapping.range());
+            let offset_range = vmo_offset + (mapping_range.start - vm_mapping.map_to_addr())
+                ..vmo_offset + (mapping_range.end - vm_mapping.map_to_addr());
+            let page_idx_range = get_page_idx_range(&offset_range);
+            let (pages, _) = vmo
+                .0
+                .committed_pages_in(page_idx_range.clone(), page_idx_range.len());
+            candidates.extend(
+                pages
+                    .into_iter()
+                    .map(|(page_idx, frame)| ReclaimCandidate::new(vmo.0.clone(), page_idx, frame)),
+            );
+        }
+
+        candidates
+    }
+
+    fn unmap_anon_pages(
+        &self,
+        range: Range<Vaddr>,
+        mut max_pages: usize,
+        ignore_referenced: bool,
+    ) -> (Vec<Arc<SwapEntry>>, Option<Vaddr>) {
+        let inner = self.inner.read();
+
+        let mut swap_entries = Vec::new();
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let mapping_range = get_intersected_range(&range, &vm_mapping.range());
+            let next_addr = vm_mapping.unmap_anon_pages(
+                &self.vm_space,
+                mapping_range,
+                &mut max_pages,
+                ignore_referenced,
+                &mut swap_entries,
+            );
+            if next_addr.is_some() {
+                return (swap_entries, next_addr);
+            }
+            if max_pages == 0 || !swap::has_free_slots() {
+                return (swap_entries, Some(vm_mapping.map_end()));
+            }
+        }
+
+        (swap_entries, None)
+    }
+
+    fn swap_in_anon_pages(&self, area: &Arc<SwapArea>) -> Result<()> {
+        let inner = self.inner.read();
+
+        for vm_mapping in inner.vm_mappings.iter() {
+            vm_mapping.swap_in_from(&self.vm_space, area)?;
+        }
+        Ok(())
+    }
+
+    fn rss_pages(&self) -> usize {
+        let inner = self.inner.read();
+
+        let mut nr_pages = 0;
+        for vm_mapping in inner.vm_mappings.iter() {
+            let Ok(cursor) = self.vm_space.cursor(&vm_mapping.range()) else {
+                continue;
+            };
+            nr_pages += cursor
+                .filter(|item| matches!(item, VmItem::Mapped { .. }))
+                .count();
+        }
+
+        nr_pages
+    }
+
+    fn unmap_reclaimed_pages(&self, candidates: &mut [ReclaimCandidate], ignore_referenced: bool) {
+        let inner = self.inner.read();
+
+        for vm_mapping in inner.vm_mappings.iter() {
+            let Some((vmo, vmo_offset)) = vm_mapping.vmo_and_offset() else {
+                continue;
+            };
+
+            for candidate in candidates.iter_mut() {
+                if !Arc::ptr_eq(&vmo.0, &candidate.vmo) {
+                    continue;
+                }
+                let Some(page_offset) = (candidate.page_idx * PAGE_SIZE).checked_sub(vmo_offset)
+                else {
+                    continue;
+                };
+                if page_offset >= vm_mapping.map_size() {
+                    continue;
+                }
+
+                vm_mapping.unmap_reclaimed_page(
+                    &self.vm_space,
+                    vm_mapping.map_to_addr() + page_offset,
+                    candidate,
+                    ignore_referenced,
+                );
+            }
+        }
+    }
+
     // Split and unmap the found mapping if resize smaller.
     // Enlarge the last mapping if resize larger.
     fn resize_mapping(&self, map_addr: Vaddr, old_size: usize, new_size: usize) -> Result<()> {
@@ -404,6 +870,136 @@ impl Vmar_ {
         Ok(())
     }
 
+    fn remap(
+        &self,
+        old_addr: Vaddr,
+        old_size: usize,
+        new_size: usize,
+        target: RemapTarget,
+        keep_old: bool,
+    ) -> Result<Vaddr> {
+        debug_assert!(old_addr % PAGE_SIZE == 0);
+        debug_assert!(old_size % PAGE_SIZE == 0);
+        debug_assert!(new_size % PAGE_SIZE == 0);
+        debug_assert!(!keep_old || target != RemapTarget::InPlace);
+
+        if old_size == 0 || new_size == 0 {
+            return_errno_with_message!(Errno::EINVAL, "can not remap a mapping of 0 size");
+        }
+
+        let old_end = old_addr.checked_add(old_size).ok_or(Error::with_message(
+            Errno::EFAULT,
+            "the old range overflows",
+        ))?;
+        let old_range = old_addr..old_end;
+
+        let mut inner = self.inner.write();
+
+        let Some(vm_mapping) = inner.vm_mappings.find_one(&old_addr) else {
+            return_errno_with_message!(Errno::EFAULT, "the old range is not mapped");
+        };
+        let vm_mapping_end = vm_mapping.map_end();
+        if vm_mapping_end < old_end {
+            return_errno_with_message!(Errno::EFAULT, "the old range spans multiple mappings");
+        }
+
+        if let RemapTarget::Fixed(new_addr) = target {
+            let new_end = new_addr
+                .checked_add(new_size)
+                .filter(|new_end| *new_end <= ROOT_VMAR_CAP_ADDR)
+                .ok_or(Error::with_message(
+                    Errno::EINVAL,
+                    "the new range overflows",
+                ))?;
+            let new_range = new_addr..new_end;
+            if !is_userspace_vaddr(new_addr) {
+                return_errno_with_message!(Errno::EINVAL, "the new address is invalid");
+            }
+            if is_intersected(&old_range, &new_range) {
+                return_errno_with_message!(Errno::EINVAL, "the old and new ranges overlap");
+            }
+
+            // The pages in the new range will be replaced, and the old range
+            // will be released unless it is kept.
+            let mut released_size = inner.count_overlap_size(new_range.clone());
+            if !keep_old {
+                released_size += old_size;
+            }
+            if new_size > released_size {
+                inner.check_expand_size(new_size - released_size)?;
+            }
+
+            // Shrink the old range before moving, just as Linux does.
+            let moved_range = old_addr..old_addr + new_size.min(old_size);
+
+            // Prepare everything that may fail before touching any mappings, so
+            // that neither range is lost if the remapping fails.
+            let placeholder = if keep_old {
+                Some(inner.new_empty_mapping(&moved_range)?)
+            } else {
+                None
+            };
+
+            if new_size < old_size {
+                inner.alloc_free_region_exact_truncate(
+                    &self.vm_space,
+                    old_addr + new_size,
+                    old_size - new_size,
+                )?;
+            }
+            inner.alloc_free_region_exact_truncate(&self.vm_space, new_addr, new_size)?;
+            inner.move_mapping(&self.vm_space, moved_range, new_addr, new_size, placeholder)?;
+            return Ok(new_addr);
+        }
+
+        if !keep_old {
+            if new_size <= old_size {
+                if new_size < old_size {
+                    inner.alloc_free_region_exact_truncate(
+                        &self.vm_space,
+                        old_addr + new_size,
+                        old_size - new_size,
+                    )?;
+                }
+                return Ok(old_addr);
+            }
+
+            // Try to grow the mapping in place.
+            let new_end = old_addr.checked_add(new_size);
+            if let Some(new_end) = new_end
+                && old_end == vm_mapping_end
+                && new_end <= ROOT_VMAR_CAP_ADDR
+                && inner.vm_mappings.find(&(old_end..new_end)).next().is_none()
+            {
+                inner.check_expand_size(new_size - old_size)?;
+
+                let vm_mapping_addr = inner.vm_mappings.find_one(&old_addr).unwrap().map_to_addr();
+                let vm_mapping = inner.remove(&vm_mapping_addr).unwrap();
+                inner.insert(vm_mapping.enlarge(new_size - old_size));
+                return Ok(old_addr);
+            }
+        }
+
+        if target != RemapTarget::MayMove {
+            return_errno_with_message!(Errno::ENOMEM, "the mapping cannot be grown in place");
+        }
+
+        if keep_old {
+            inner.check_expand_size(new_size)?;
+        } else {
+            inner.check_expand_size(new_size - old_size)?;
+        }
+        let placeholder = if keep_old {
+            Some(inner.new_empty_mapping(&old_range)?)
+        } else {
+            None
+        };
+        let new_addr = inner.alloc_free_region(new_size, PAGE_SIZE)?.start;
+        inner.move_mapping(&self.vm_space, old_range, new_addr, new_size, placeholder)?;
+
+        Ok(new_addr)
+    }
+
     /// Returns the attached `VmSpace`.
     fn vm_space(&self) -> &Arc<VmSpace> {
         &self.vm_space
@@ -470,6 +1066,7 @@ impl<R> Vmar<R> {
 pub struct VmarMapOptions<'a, R1, R2> {
     parent: &'a Vmar<R1>,
     vmo: Option<Vmo<R2>>,
+    file: Option<Dentry>,
     perms: VmPerms,
     vmo_offset: usize,
     vmo_limit: usize,
@@ -494,6 +1091,7 @@ impl<'a, R1, R2> VmarMapOptions<'a, R1, R2> {
         Self {
             parent,
             vmo: None,
+            file: None,
             perms,
             vmo_offset: 0,
             vmo_limit: usize::MAX,
@@ -527,6 +1125,15 @@ impl<'a, R1, R2> VmarMapOptions<'a, R1, R2> {
         self
     }
 
+    /// Records the file that is mapped.
+    ///
+    /// The bound VMO should be the page cache of the file. The file is only
+    /// used to describe the mapping (e.g., in core dumps).
+    pub fn file(mut self, file: Dentry) -> Self {
+        self.file = Some(file);
+        self
+    }
+
     /// Sets the offset of the first memory page in the VMO that is to be
     /// mapped into the VMAR.
     ///
@@ -610,6 +1217,7 @@ where
         let Self {
             parent,
             vmo,
+            file,
             perms,
             vmo_offset,
             vmo_limit,
@@ -621,6 +1229,18 @@ where
             handle_page_faults_around,
         } = self;
 
+        // Build the mapped VMO before allocating the region, so that a failure
+        // will not remove the existing mappings in the region.
+        let vmo = vmo
+            .map(|vmo| {
+                let mut mapped_vmo = MappedVmo::new(vmo.to_dyn(), vmo_offset..vmo_limit);
+                if is_shared && perms.contains(VmPerms::WRITE) {
+                    mapped_vmo.map_writable()?;
+                }
+                Ok::<_, Error>(mapped_vmo)
+            })
+            .transpose()?;
+
         let mut inner = parent.0.inner.write();
 
         inner.check_expand_size(map_size).or_else(|e| {
@@ -656,11 +1276,11 @@ where
         };
 
         // Build the mapping.
-        let vmo = vmo.map(|vmo| MappedVmo::new(vmo.to_dyn(), vmo_offset..vmo_limit));
         let vm_mapping = VmMapping::new(
             NonZeroUsize::new(map_size).unwrap(),
             map_to_addr,
             vmo,
+            file,
             is_shared,
             handle_page_faults_around,
             perms,
@@ -8,16 +8,18 @@ use core::{
 
 use align_ext::AlignExt;
 use ostd::mm::{
-    tlb::TlbFlushOp, vm_space::VmItem, CachePolicy, FrameAllocOptions, PageFlags, PageProperty,
-    UFrame, VmSpace,
+    tlb::TlbFlushOp, vm_space::VmItem, CachePolicy, PageFlags, PageProperty, UFrame, VmSpace,
 };
 
 use super::interval_set::Interval;
 use crate::{
+    fs::path::Dentry,
     prelude::*,
+    process::cgroup::alloc_charged_frame,
     thread::exception::PageFaultInfo,
     vm::{
         perms::VmPerms,
+        swap::{ReclaimCandidate, SwapArea, SwapEntry},
         util::duplicate_frame,
         vmo::{CommitFlags, Vmo, VmoCommitError},
     },
@@ -54,6 +56,11 @@ pub(super) struct VmMapping {
     /// The start of the virtual address maps to the start of the range
     /// specified in [`MappedVmo`].
     vmo: Option<MappedVmo>,
+    /// The mapped file, if the mapping is file-backed.
+    ///
+    /// The start of the virtual address maps to the start of the range
+    /// specified in [`MappedVmo`], which is the page cache of the file.
+    file: Option<Dentry>,
     /// Whether the mapping is shared.
     ///
     /// The updates to a shared mapping are visible among processes, or carried
@@ -66,6 +73,12 @@ pub(super) struct VmMapping {
     ///
     /// All pages within the same `VmMapping` have the same permissions.
     perms: VmPerms,
+    /// The private anonymous pages that are swapped out, indexed by their
+    /// offsets in the mapping.
+    ///
+    /// A page is moved between the page table and `swapped` only with the
+    /// page table locked. Therefore, a page is never in both of them.
+    swapped: SpinLock<BTreeMap<usize, Arc<SwapEntry>>>,
 }
 
 impl Interval<Vaddr> for VmMapping {
@@ -81,6 +94,7 @@ impl VmMapping {
         map_size: NonZeroUsize,
         map_to_addr: Vaddr,
         vmo: Option<MappedVmo>,
+        file: Option<Dentry>,
         is_shared: bool,
         handle_page_faults_around: bool,
         perms: VmPerms,
@@ -89,15 +103,30 @@ impl VmMapping {
             map_size,
             map_to_addr,
             vmo,
+            file,
             is_shared,
             handle_page_faults_around,
             perms,
+            swapped: SpinLock::new(BTreeMap::new()),
         }
     }
 
     pub(super) fn new_fork(&self) -> Result<VmMapping> {
         Ok(VmMapping {
             vmo: self.vmo.as_ref().map(|vmo| vmo.dup()).transpose()?,
+            file: self.file.clone(),
+            // The swapped-out pages are shared until they are swapped in.
+            swapped: SpinLock::new(self.swapped.lock().clone()),
+            ..*self
+        })
+    }
+
+    /// Creates a mapping with the same properties but without any pages.
+    pub(super) fn new_empty(&self) -> Result<VmMapping> {
+        Ok(VmMapping {
+            vmo: self.vmo.as_ref().map(|vmo| vmo.dup()).transpose()?,
+            file: self.file.clone(),
+            swapped: SpinLock::new(BTreeMap::new()),
             ..*self
         })
     }
@@ -121,6 +150,33 @@ impl VmMapping {
     pub fn perms(&self) -> VmPerms {
         self.perms
     }
+
+    /// Returns whether the mapping is shared.
+    pub fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+
+    /// Returns the mapped file and the offset in the file where the mapping
+    /// starts, if the mapping is file-backed.
+    pub fn file_and_offset(&self) -> Option<(&Dentry, usize)> {
+        let file = self.file.as_ref()?;
+        let (_, offset) = self.vmo_and_offset()?;
+        Some((file, offset))
+    }
+
+    /// Returns the mapped VMO and the offset in the VMO where the mapping
+    /// starts, if the mapping is VMO-backed.
+    pub(super) fn vmo_and_offset(&self) -> Option<(&Vmo, usize)> {
+        self.vmo
+            .as_ref()
+            .map(|mapped_vmo| (&mapped_vmo.vmo, mapped_vmo.range.start))
+    }
+
+    /// Returns whether the page at `addr` is swapped out.
+    pub(super) fn is_swapped_out(&self, addr: Vaddr) -> bool {
+        let page_offset = addr.align_down(PAGE_SIZE) - self.map_to_addr;
+        self.swapped.lock().contains_key(&page_offset)
+    }
 }
 
 /****************************** Page faults **********************************/
@@ -146,7 +202,13 @@ impl VmMapping {
         let page_aligned_addr = address.align_down(PAGE_SIZE);
         let is_write = page_fault_info.required_perms.contains(VmPerms::WRITE);
 
-        if !is_write && self.vmo.is_some() && self.handle_page_faults_around {
+        // The surrounding pages are not handled if some pages are swapped
+        // out, since they should not be taken from the VMO.
+        if !is_write
+            && self.vmo.is_some()
+            && self.handle_page_faults_around
+            && self.swapped.lock().is_empty()
+        {
             let res = self.handle_page_faults_around(vm_space, address);
 
             // Errors caused by the "around" pages should be ignored, so here we
@@ -202,11 +264,27 @@ impl VmMapping {
                     } else {
                         let new_frame = duplicate_frame(&frame)?;
                         prop.flags |= new_flags;
-                        cursor.map(new_frame.into(), prop);
+                        cursor.map(new_frame, prop);
                     }
                     cursor.flusher().sync_tlb_flush();
                 }
                 VmItem::NotMapped { .. } => {
+                    let page_offset = page_aligned_addr - self.map_to_addr;
+                    let swap_entry = self.swapped.lock().get(&page_offset).cloned();
+                    if let Some(swap_entry) = swap_entry {
+                        // Swapping in the page may need I/O.
+                        drop(cursor);
+                        let frame = swap_entry.read_page()?;
+                        self.map_swapped_in_page(
+                            vm_space,
+                            page_aligned_addr,
+                            &swap_entry,
+                            frame,
+                            is_write,
+                        )?;
+                        continue 'retry;
+                    }
+
                     // Map a new frame to the page fault address.
                     let (frame, is_readonly) = match self.prepare_page(address, is_write) {
                         Ok((frame, is_readonly)) => (frame, is_readonly),
@@ -245,6 +323,109 @@ impl VmMapping {
         Ok(())
     }
 
+    /// Accesses the frame mapped at `address` with `op`, on behalf of another
+    /// process (e.g., a tracer).
+    ///
+    /// Like Linux's `FOLL_FORCE`, write accesses to private mappings are
+    /// allowed even if the mappings are not writable. In this case, the page
+    /// is copied before being written, and the copy is mapped with the
+    /// original permissions.
+    pub(super) fn access_remote<F>(
+        &self,
+        vm_space: &VmSpace,
+        address: Vaddr,
+        is_write: bool,
+        op: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&UFrame) -> Result<()>,
+    {
+        if is_write && self.is_shared && !self.perms.contains(VmPerms::WRITE) {
+            return_errno_with_message!(Errno::EIO, "the shared mapping is not writable");
+        }
+
+        // Make sure that the page is present.
+        let required_perms = if is_write && self.perms.contains(VmPerms::WRITE) {
+            VmPerms::WRITE
+        } else {
+            VmPerms::READ
+        };
+        self.handle_page_fault(
+            vm_space,
+            &PageFaultInfo {
+                address,
+                required_perms,
+            },
+        )
+        .map_err(|_| Error::with_message(Errno::EIO, "the page cannot be accessed"))?;
+
+        let page_aligned_addr = address.align_down(PAGE_SIZE);
+        let mut cursor =
+            vm_space.cursor_mut(&(page_aligned_addr..page_aligned_addr + PAGE_SIZE))?;
+        let VmItem::Mapped { frame, prop, .. } = cursor.query().unwrap() else {
+            return_errno_with_message!(Errno::EIO, "the page is not mapped");
+        };
+
+        // See `handle_page_fault` for why a reference count of 2 means that the frame is not
+        // shared with others.
+        if !is_write || prop.flags.contains(PageFlags::W) || frame.reference_count() == 2 {
+            return op(&frame);
+        }
+
+        let new_frame = duplicate_frame(&frame)?;
+        op(&new_frame)?;
+        cursor.map(new_frame, prop);
+        cursor.flusher().sync_tlb_flush();
+
+        Ok(())
+    }
+
+    /// Maps the page that is swapped in from `swap_entry` at `addr`.
+    ///
+    /// Nothing is done if the page has been swapped in by others.
+    fn map_swapped_in_page(
+        &self,
+        vm_space: &VmSpace,
+        addr: Vaddr,
+        swap_entry: &Arc<SwapEntry>,
+        frame: UFrame,
+        is_write: bool,
+    ) -> Result<()> {
+        let mut cursor = vm_space.cursor_mut(&(addr..addr + PAGE_SIZE))?;
+        if let VmItem::Mapped { .. } = cursor.query().unwrap() {
+            return Ok(());
+        }
+
+        let page_offset = addr - self.map_to_addr;
+        let mut swapped = self.swapped.lock();
+        if !swapped
+            .get(&page_offset)
+            .is_some_and(|current| Arc::ptr_eq(current, swap_entry))
+        {
+            return Ok(());
+        }
+        swapped.remove(&page_offset);
+        drop(swapped);
+
+        // The frame may still be shared with the swap entry, or with other
+        // processes that share the swap entry after forking. The shared
+        // frame is mapped read-only so that it is copied on write.
+        let (frame, vm_perms) = if !is_write {
+            (frame, self.perms - VmPerms::WRITE)
+        } else if frame.reference_count() == 1 {
+            (frame, self.perms)
+        } else {
+            (duplicate_frame(&frame)?, self.perms)
+        };
+
+        let mut page_flags = PageFlags::from(vm_perms) | PageFlags::ACCESSED;
+        if is_write {
+            page_flags |= PageFlags::DIRTY;
+        }
+        cursor.map(frame, PageProperty::new(page_flags, CachePolicy::Writeback));
+        Ok(())
+    }
+
     fn prepare_page(
         &self,
         page_fault_addr: Vaddr,
@@ -252,19 +433,19 @@ impl VmMapping {
     ) -> core::result::Result<(UFrame, bool), VmoCommitError> {
         let mut is_readonly = false;
         let Some(vmo) = &self.vmo else {
-            return Ok((FrameAllocOptions::new().alloc_frame()?.into(), is_readonly));
+            return Ok((alloc_charged_frame(true)?, is_readonly));
         };
 
         let page_offset = page_fault_addr.align_down(PAGE_SIZE) - self.map_to_addr;
         if !self.is_shared && page_offset >= vmo.size() {
             // The page index is outside the VMO. This is only allowed in private mapping.
-            return Ok((FrameAllocOptions::new().alloc_frame()?.into(), is_readonly));
+            return Ok((alloc_charged_frame(true)?, is_readonly));
         }
 
         let page = vmo.get_committed_frame(page_offset)?;
         if !self.is_shared && write {
             // Write access to private VMO-backed mapping. Performs COW directly.
-            Ok((duplicate_frame(&page)?.into(), is_readonly))
+            Ok((duplicate_frame(&page)?, is_readonly))
         } else {
             // Operations to shared mapping or read access to private VMO-backed mapping.
             // If read access to private VMO-backed mapping triggers a page fault,
@@ -342,7 +523,7 @@ impl VmMapping {
     ///
     /// The address must be within the mapping and page-aligned. The address
     /// must not be either the start or the end of the mapping.
-    fn split(self, at: Vaddr) -> Result<(Self, Self)> {
+    fn split(mut self, at: Vaddr) -> Result<(Self, Self)> {
         debug_assert!(self.map_to_addr < at && at < self.map_end());
         debug_assert!(at % PAGE_SIZE == 0);
 
@@ -354,22 +535,33 @@ impl VmMapping {
             let l_range = vmo.range.start..at_offset;
             let r_range = at_offset..vmo.range.end;
 
-            l_vmo = Some(MappedVmo::new(vmo.vmo.dup()?, l_range));
-            r_vmo = Some(MappedVmo::new(vmo.vmo.dup()?, r_range));
+            l_vmo = Some(vmo.dup_with_range(l_range)?);
+            r_vmo = Some(vmo.dup_with_range(r_range)?);
         }
 
         let left_size = at - self.map_to_addr;
         let right_size = self.map_size.get() - left_size;
+
+        let mut l_swapped = core::mem::take(self.swapped.get_mut());
+        let r_swapped = l_swapped
+            .split_off(&left_size)
+            .into_iter()
+            .map(|(page_offset, swap_entry)| (page_offset - left_size, swap_entry))
+            .collect();
+
         let left = Self {
             map_to_addr: self.map_to_addr,
             map_size: NonZeroUsize::new(left_size).unwrap(),
             vmo: l_vmo,
+            file: self.file.clone(),
+            swapped: SpinLock::new(l_swapped),
             ..self
         };
         let right = Self {
             map_to_addr: at,
             map_size: NonZeroUsize::new(right_size).unwrap(),
             vmo: r_vmo,
+            swapped: SpinLock::new(r_swapped),
             ..self
         };
 
@@ -428,6 +620,197 @@ impl VmMapping {
         Ok(())
     }
 
+    /// Unmaps the page at `addr` if the page is to be reclaimed.
+    ///
+    /// If the page has been accessed since the last scan, the accessed bit
+    /// is cleared instead, and the page is marked as referenced, unless
+    /// `ignore_referenced` is true.
+    pub(super) fn unmap_reclaimed_page(
+        &self,
+        vm_space: &VmSpace,
+        addr: Vaddr,
+        candidate: &mut ReclaimCandidate,
+        ignore_referenced: bool,
+    ) {
+        let Ok(mut cursor) = vm_space.cursor_mut(&(addr..addr + PAGE_SIZE)) else {
+            return;
+        };
+        let Ok(VmItem::Mapped { frame, prop, .. }) = cursor.query() else {
+            return;
+        };
+        // The page may have been copied on write, in which case the mapped
+        // frame is not the one to be reclaimed.
+        if frame.start_paddr() != candidate.frame.start_paddr() {
+            return;
+        }
+
+        if !ignore_referenced && prop.flags.contains(PageFlags::ACCESSED) {
+            candidate.is_referenced = true;
+            if let Some(va) = cursor.protect_next(PAGE_SIZE, |p| p.flags -= PageFlags::ACCESSED) {
+                cursor.flusher().issue_tlb_flush(TlbFlushOp::Range(va));
+            }
+        } else {
+            cursor.unmap(PAGE_SIZE);
+        }
+        cursor.flusher().dispatch_tlb_flush();
+        cursor.flusher().sync_tlb_flush();
+    }
+
+    /// Unmaps the private anonymous pages in `range` so that they can be
+    /// swapped out.
+    ///
+    /// A mapped page is private and anonymous if the mapping is private and
+    /// the page is mapped nowhere else, e.g., not shared with a VMO or with
+    /// other processes after forking. If such a page has been accessed since
+    /// the last scan, the accessed bit is cleared instead, unless
+    /// `ignore_referenced` is true.
+    ///
+    /// At most `max_pages` mapped pages are scanned, which is decreased by the
+    /// number of scanned pages. The swap entries of the unmapped pages are
+    /// appended to `swap_entries`, and the pages still need to be written to
+    /// the swap areas.
+    ///
+    /// Returns the address to continue scanning from, which is `None` if the
+    /// end of the range is reached.
+    pub(super) fn unmap_anon_pages(
+        &self,
+        vm_space: &VmSpace,
+        range: Range<Vaddr>,
+        max_pages: &mut usize,
+        ignore_referenced: bool,
+        swap_entries: &mut Vec<Arc<SwapEntry>>,
+    ) -> Option<Vaddr> {
+        if self.is_shared {
+            return None;
+        }
+
+        let mapped_addrs: Vec<Vaddr> = vm_space
+            .cursor(&range)
+            .ok()?
+            .filter_map(|item| match item {
+                VmItem::Mapped { va, .. } => Some(va),
+                VmItem::NotMapped { .. } => None,
+            })
+            .take(*max_pages)
+            .collect();
+        *max_pages -= mapped_addrs.len();
+        let next_addr = (*max_pages == 0)
+            .then(|| mapped_addrs.last().map(|addr| addr + PAGE_SIZE))
+            .flatten();
+
+        for addr in mapped_addrs {
+            let Ok(mut cursor) = vm_space.cursor_mut(&(addr..addr + PAGE_SIZE)) else {
+                continue;
+            };
+            let Ok(VmItem::Mapped { frame, prop, .. }) = cursor.query() else {
+                continue;
+            };
+            // See `handle_page_fault` for why a reference count of 2 means
+            // that the frame is not shared with others.
+            if frame.reference_count() != 2 {
+                continue;
+            }
+
+            if !ignore_referenced && prop.flags.contains(PageFlags::ACCESSED) {
+                if let Some(va) = cursor.protect_next(PAGE_SIZE, |p| p.flags -= PageFlags::ACCESSED)
+                {
+                    cursor.flusher().issue_tlb_flush(TlbFlushOp::Range(va));
+                }
+            } else {
+                let Some(swap_entry) = SwapEntry::alloc(frame) else {
+                    return None;
+                };
+                cursor.unmap(PAGE_SIZE);
+                self.swapped
+                    .lock()
+                    .insert(addr - self.map_to_addr, swap_entry.clone());
+                swap_entries.push(swap_entry);
+            }
+            cursor.flusher().dispatch_tlb_flush();
+            cursor.flusher().sync_tlb_flush();
+        }
+
+        next_addr
+    }
+
+    /// Swaps in all the pages that are swapped out to the swap area.
+    pub(super) fn swap_in_from(&self, vm_space: &VmSpace, area: &Arc<SwapArea>) -> Result<()> {
+        let page_offsets: Vec<usize> = self
+            .swapped
+            .lock()
+            .iter()
+            .filter(|(_, swap_entry)| swap_entry.is_in(area))
+            .map(|(page_offset, _)| *page_offset)
+            .collect();
+
+        for page_offset in page_offsets {
+            self.handle_page_fault(
+                vm_space,
+                &PageFaultInfo {
+                    address: self.map_to_addr + page_offset,
+                    required_perms: VmPerms::empty(),
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Moves the mapping to `new_addr` together with its mapped pages.
+    ///
+    /// The mapped frames are collected from the old range, which is then
+    /// unmapped, and are mapped again at the same offsets in the new range.
+    /// So the frames are neither copied nor released, but the cost grows with
+    /// the number of mapped pages. The new range must not contain any mapped
+    /// pages.
+    ///
+    /// Both ranges must be valid user space ranges.
+    pub(super) fn remap(self, vm_space: &VmSpace, new_addr: Vaddr) -> Self {
+        let old_range = self.range();
+        let new_range = new_addr..new_addr + old_range.len();
+
+        let mapped_pages: Vec<_> = vm_space
+            .cursor(&old_range)
+            .unwrap()
+            .filter_map(|item| match item {
+                VmItem::Mapped { va, frame, prop } => Some((va - old_range.start, frame, prop)),
+                VmItem::NotMapped { .. } => None,
+            })
+            .collect();
+
+        let mut cursor = vm_space.cursor_mut(&old_range).unwrap();
+        cursor.unmap(old_range.len());
+        cursor.flusher().dispatch_tlb_flush();
+        cursor.flusher().sync_tlb_flush();
+        drop(cursor);
+
+        let mut cursor = vm_space.cursor_mut(&new_range).unwrap();
+        for (offset, frame, prop) in mapped_pages {
+            cursor.jump(new_range.start + offset).unwrap();
+            cursor.map(frame, prop);
+        }
+
+        Self {
+            map_to_addr: new_addr,
+            ..self
+        }
+    }
+
+    /// Records the mapping as a writable shared mapping of its VMO.
+    ///
+    /// This must be done before granting the write permission to a shared
+    /// VMO-backed mapping, since the VMO may deny writable shared mappings.
+    pub(super) fn map_writable(&mut self) -> Result<()> {
+        if !self.is_shared {
+            return Ok(());
+        }
+        let Some(vmo) = self.vmo.as_mut() else {
+            return Ok(());
+        };
+
+        vmo.map_writable()
+            .map_err(|_| Error::with_message(Errno::EACCES, "the VMO cannot be mapped writable"))
+    }
+
     /// Change the perms of the mapping.
     pub(super) fn protect(self, vm_space: &VmSpace, perms: VmPerms) -> Self {
         let range = self.range();
@@ -456,12 +839,30 @@ pub(super) struct MappedVmo {
     vmo: Vmo,
     /// Represents the accessible range in the VMO for mappings.
     range: Range<usize>,
+    /// Whether the mapping is counted as a writable shared mapping of the VMO.
+    is_writable_mapped: bool,
 }
 
 impl MappedVmo {
     /// Creates a `MappedVmo` used for mapping.
     pub(super) fn new(vmo: Vmo, range: Range<usize>) -> Self {
-        Self { vmo, range }
+        vmo.inc_num_mappings();
+        Self {
+            vmo,
+            range,
+            is_writable_mapped: false,
+        }
+    }
+
+    /// Counts the mapping as a writable shared mapping of the VMO.
+    ///
+    /// Returns `Err` if the VMO denies writable shared mappings.
+    pub(super) fn map_writable(&mut self) -> Result<()> {
+        if !self.is_writable_mapped {
+            self.vmo.writable_mapping_status().map()?;
+            self.is_writable_mapped = true;
+        }
+        Ok(())
     }
 
     fn size(&self) -> usize {
@@ -517,9 +918,24 @@ impl MappedVmo {
 
     /// Duplicates the capability.
     pub fn dup(&self) -> Result<Self> {
-        Ok(Self {
-            vmo: self.vmo.dup()?,
-            range: self.range.clone(),
-        })
+        self.dup_with_range(self.range.clone())
+    }
+
+    /// Duplicates the capability with a different accessible range.
+    fn dup_with_range(&self, range: Range<usize>) -> Result<Self> {
+        let mut new_vmo = Self::new(self.vmo.dup()?, range);
+        if self.is_writable_mapped {
+            new_vmo.map_writable()?;
+        }
+        Ok(new_vmo)
+    }
+}
+
+impl Drop for MappedVmo {
+    fn drop(&mut self) {
+        self.vmo.dec_num_mappings();
+        if self.is_writable_mapped {
+            self.vmo.writable_mapping_status().unmap();
+        }
     }
 }
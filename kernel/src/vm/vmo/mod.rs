@@ -7,18 +7,23 @@
 
 use core::{
     ops::Range,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering},
 };
 
 use align_ext::AlignExt;
 use aster_rights::Rights;
 use ostd::{
-    mm::{FrameAllocOptions, UFrame, UntypedMem, VmReader, VmWriter},
+    mm::{UFrame, UntypedMem, VmReader, VmWriter},
     task::disable_preempt,
 };
+use spin::Once;
 use xarray::{Cursor, LockedXArray, XArray};
 
-use crate::prelude::*;
+use crate::{
+    prelude::*,
+    process::cgroup::alloc_charged_frame,
+    vm::swap::{SwapArea, SwapEntry},
+};
 
 mod dyn_cap;
 mod options;
@@ -77,6 +82,12 @@ pub use pager::Pager;
 #[derive(Debug)]
 pub struct Vmo<R = Rights>(pub(super) Arc<Vmo_>, R);
 
+impl<R> PartialEq for Vmo<R> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
 /// Functions exist both for static capbility and dynamic capability
 pub trait VmoRightsOp {
     /// Returns the access rights.
@@ -148,6 +159,11 @@ pub(super) struct Vmo_ {
     flags: VmoFlags,
     /// The virtual pages where the VMO resides.
     pages: XArray<UFrame>,
+    /// The pages that are swapped out.
+    ///
+    /// A page is moved between `pages` and `swapped` only with the lock of
+    /// `pages` held. Therefore, a page is never in both of them.
+    swapped: SpinLock<BTreeMap<usize, Arc<SwapEntry>>>,
     /// The size of the VMO.
     ///
     /// Note: This size may not necessarily match the size of the `pages`, but it is
@@ -155,6 +171,14 @@ pub(super) struct Vmo_ {
     /// the [`XArray`] in the `pages` field. Therefore, the size read after locking the
     /// `pages` will be the latest size.
     size: AtomicUsize,
+    /// The status of writable shared mappings of the VMO.
+    writable_mapping_status: WritableMappingStatus,
+    /// The number of mappings of the VMO.
+    num_mappings: AtomicUsize,
+    /// The hook that is called when the VMO becomes mapped or unmapped.
+    mapping_hook: Once<Box<dyn Fn() + Send + Sync>>,
+    /// Whether the pages of the VMO are locked in memory.
+    is_unevictable: AtomicBool,
 }
 
 impl Debug for Vmo_ {
@@ -187,7 +211,7 @@ impl Vmo_ {
     /// This operation may involve I/O operations if the VMO is backed by a pager.
     fn prepare_page(&self, page_idx: usize, commit_flags: CommitFlags) -> Result<UFrame> {
         match &self.pager {
-            None => Ok(FrameAllocOptions::new().alloc_frame()?.into()),
+            None => alloc_charged_frame(true),
             Some(pager) => {
                 if commit_flags.will_overwrite() {
                     pager.commit_overwrite(page_idx)
@@ -201,10 +225,34 @@ impl Vmo_ {
     /// Commits a page at a specific page index.
     ///
     /// This method may involve I/O operations if the VMO needs to fetch a page from
-    /// the underlying page cache.
+    /// the underlying page cache or the swap area.
     pub fn commit_on(&self, page_idx: usize, commit_flags: CommitFlags) -> Result<UFrame> {
-        let new_page = self.prepare_page(page_idx, commit_flags)?;
+        loop {
+            let swap_entry = self.swapped.lock().get(&page_idx).cloned();
+            let new_page = match &swap_entry {
+                Some(swap_entry) => swap_entry.read_page()?,
+                None => self.prepare_page(page_idx, commit_flags)?,
+            };
 
+            if let Some(page) = self.install_page(page_idx, new_page, swap_entry.as_ref())? {
+                return Ok(page);
+            }
+        }
+    }
+
+    /// Stores the prepared page at the target index, unless a page has
+    /// already been committed there.
+    ///
+    /// The page must have been prepared from `swap_entry`, or prepared as a
+    /// new page if `swap_entry` is `None`. Returns `None` if the page has
+    /// been swapped in or out since then, in which case the page should be
+    /// prepared again.
+    fn install_page(
+        &self,
+        page_idx: usize,
+        new_page: UFrame,
+        swap_entry: Option<&Arc<SwapEntry>>,
+    ) -> Result<Option<UFrame>> {
         let mut locked_pages = self.pages.lock();
         if page_idx * PAGE_SIZE > self.size() {
             return_errno_with_message!(Errno::EINVAL, "the offset is outside the VMO");
@@ -212,11 +260,22 @@ impl Vmo_ {
 
         let mut cursor = locked_pages.cursor_mut(page_idx as u64);
         if let Some(page) = cursor.load() {
-            return Ok(page.clone());
+            return Ok(Some(page.clone()));
         }
 
+        let mut swapped = self.swapped.lock();
+        let is_prepared_from_current = match (swapped.get(&page_idx), swap_entry) {
+            (None, None) => true,
+            (Some(current), Some(prepared)) => Arc::ptr_eq(current, prepared),
+            _ => false,
+        };
+        if !is_prepared_from_current {
+            return Ok(None);
+        }
+        swapped.remove(&page_idx);
+
         cursor.store(new_page.clone());
-        Ok(new_page)
+        Ok(Some(new_page))
     }
 
     fn try_commit_with_cursor(
@@ -236,8 +295,18 @@ impl Vmo_ {
             return Err(VmoCommitError::NeedIo(cursor.index() as usize));
         }
 
-        let frame = self.commit_on(cursor.index() as usize, CommitFlags::empty())?;
-        Ok(frame)
+        let page_idx = cursor.index() as usize;
+        if self.swapped.lock().contains_key(&page_idx) {
+            // Swapping in the page may need I/O.
+            return Err(VmoCommitError::NeedIo(page_idx));
+        }
+
+        let new_page = self.prepare_page(page_idx, CommitFlags::empty())?;
+        match self.install_page(page_idx, new_page, None)? {
+            Some(frame) => Ok(frame),
+            // The page has been swapped out concurrently.
+            None => Err(VmoCommitError::NeedIo(page_idx)),
+        }
     }
 
     /// Commits the page corresponding to the target offset in the VMO.
@@ -444,10 +513,13 @@ impl Vmo_ {
         let mut cursor = locked_pages.cursor_mut(page_idx_range.start as u64);
 
         let Some(pager) = &self.pager else {
-            for _ in page_idx_range {
+            for _ in page_idx_range.clone() {
                 cursor.remove();
                 cursor.next();
             }
+            self.swapped
+                .lock()
+                .retain(|page_idx, _| !page_idx_range.contains(page_idx));
             return Ok(());
         };
 
@@ -480,9 +552,143 @@ impl Vmo_ {
             return_errno_with_message!(Errno::EINVAL, "the page index is outside of the vmo");
         }
 
+        self.swapped.lock().remove(&page_idx);
         locked_pages.store(page_idx as u64, page);
         Ok(())
     }
+
+    /// Returns whether the pages of the VMO can be swapped out.
+    pub(in crate::vm) fn is_swappable(&self) -> bool {
+        self.pager.is_none()
+            && !self.flags.intersects(VmoFlags::CONTIGUOUS | VmoFlags::DMA)
+            && !self.is_unevictable.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the page at the target index may contain data.
+    ///
+    /// This is false only if the VMO is anonymous and the page is neither
+    /// committed nor swapped out, in which case the page is full of zeros.
+    pub(in crate::vm) fn may_have_data(&self, page_idx: usize) -> bool {
+        if self.pager.is_some() {
+            return true;
+        }
+
+        let guard = disable_preempt();
+        let mut cursor = self.pages.cursor(&guard, page_idx as u64);
+        cursor.load().is_some() || self.swapped.lock().contains_key(&page_idx)
+    }
+
+    /// Collects at most `max_pages` committed pages in the page index range.
+    ///
+    /// Returns the pages with their indices, and the index to continue
+    /// collecting from, which is `None` if the end of the range or the VMO
+    /// is reached.
+    pub(in crate::vm) fn committed_pages_in(
+        &self,
+        page_idx_range: Range<usize>,
+        max_pages: usize,
+    ) -> (Vec<(usize, UFrame)>, Option<usize>) {
+        let end_idx = page_idx_range.end.min(self.size().div_ceil(PAGE_SIZE));
+        let mut pages = Vec::new();
+
+        let guard = disable_preempt();
+        let mut cursor = self.pages.cursor(&guard, page_idx_range.start as u64);
+        while (cursor.index() as usize) < end_idx {
+            if pages.len() == max_pages {
+                return (pages, Some(cursor.index() as usize));
+            }
+            if let Some(page) = cursor.load() {
+                pages.push((cursor.index() as usize, page.clone()));
+            }
+            cursor.next();
+        }
+
+        (pages, None)
+    }
+
+    /// Starts swapping out the page at the target index to `swap_entry`.
+    ///
+    /// The page is moved from the VMO to `swap_entry`, where it stays in
+    /// memory until it is written to the swap area. This fails if the
+    /// committed page is not the one in `swap_entry`, or if the page is still
+    /// used elsewhere (e.g., mapped).
+    ///
+    /// Returns whether the page is moved.
+    pub(in crate::vm) fn start_swap_out(
+        &self,
+        page_idx: usize,
+        swap_entry: &Arc<SwapEntry>,
+    ) -> bool {
+        let mut locked_pages = self.pages.lock();
+        if self.is_unevictable.load(Ordering::Relaxed) {
+            return false;
+        }
+        let mut cursor = locked_pages.cursor_mut(page_idx as u64);
+        let Some(page) = cursor.load() else {
+            return false;
+        };
+
+        // The page is referenced by the VMO and the swap entry.
+        if !swap_entry.caches(&page) || page.reference_count() != 2 {
+            return false;
+        }
+
+        cursor.remove();
+        self.swapped.lock().insert(page_idx, swap_entry.clone());
+        true
+    }
+
+    /// Finishes swapping out the page at the target index to `swap_entry`.
+    ///
+    /// If the page is not written to the swap area, or if the page is used
+    /// again during the write, the page is moved back to the VMO.
+    ///
+    /// Returns whether the page has been swapped out.
+    pub(in crate::vm) fn finish_swap_out(
+        &self,
+        page_idx: usize,
+        swap_entry: &Arc<SwapEntry>,
+        is_written: bool,
+    ) -> bool {
+        let mut locked_pages = self.pages.lock();
+        let mut swapped = self.swapped.lock();
+        let Some(page) = swap_entry.take_cached_frame() else {
+            return false;
+        };
+
+        // If the page has been swapped in, decommitted, or swapped out
+        // again, there is nothing left to do.
+        if !swapped
+            .get(&page_idx)
+            .is_some_and(|current| Arc::ptr_eq(current, swap_entry))
+        {
+            return false;
+        }
+
+        if is_written && page.reference_count() == 1 {
+            return true;
+        }
+
+        swapped.remove(&page_idx);
+        locked_pages.store(page_idx as u64, page);
+        false
+    }
+
+    /// Swaps in all the pages that are swapped out to the swap area.
+    pub(in crate::vm) fn swap_in_from(&self, area: &Arc<SwapArea>) -> Result<()> {
+        let page_idxs: Vec<usize> = self
+            .swapped
+            .lock()
+            .iter()
+            .filter(|(_, swap_entry)| swap_entry.is_in(area))
+            .map(|(page_idx, _)| *page_idx)
+            .collect();
+
+        for page_idx in page_idxs {
+            self.commit_on(page_idx, CommitFlags::empty())?;
+        }
+        Ok(())
+    }
 }
 
 impl<R> Vmo<R> {
@@ -495,6 +701,111 @@ impl<R> Vmo<R> {
     pub fn flags(&self) -> VmoFlags {
         self.0.flags()
     }
+
+    /// Returns the status of writable shared mappings of a VMO.
+    pub fn writable_mapping_status(&self) -> &WritableMappingStatus {
+        &self.0.writable_mapping_status
+    }
+
+    /// Returns the number of mappings of a VMO.
+    ///
+    /// A mapping that is split into pieces (e.g., by `mprotect`) is counted
+    /// once for each piece.
+    pub fn num_mappings(&self) -> usize {
+        self.0.num_mappings.load(Ordering::Relaxed)
+    }
+
+    /// Records a new mapping of a VMO.
+    ///
+    /// The hook set by [`Self::set_mapping_hook`] is called if this is the
+    /// first mapping of the VMO.
+    pub(in crate::vm) fn inc_num_mappings(&self) {
+        let old_num = self.0.num_mappings.fetch_add(1, Ordering::Relaxed);
+
+        if old_num == 0
+            && let Some(hook) = self.0.mapping_hook.get()
+        {
+            hook();
+        }
+    }
+
+    /// Removes a mapping recorded by [`Self::inc_num_mappings`].
+    ///
+    /// The hook set by [`Self::set_mapping_hook`] is called if this is the
+    /// last mapping of the VMO.
+    pub(in crate::vm) fn dec_num_mappings(&self) {
+        let old_num = self.0.num_mappings.fetch_sub(1, Ordering::Relaxed);
+        debug_assert!(old_num > 0);
+
+        if old_num == 1
+            && let Some(hook) = self.0.mapping_hook.get()
+        {
+            hook();
+        }
+    }
+
+    /// Sets the hook that is called whenever a VMO becomes mapped or unmapped,
+    /// i.e., when its first mapping is added or its last mapping is removed.
+    ///
+    /// The hooks for concurrent changes may be called in any order, so the
+    /// hook should check [`Self::num_mappings`] with its own lock held to find
+    /// out whether the VMO is mapped.
+    ///
+    /// The hook may be called with the locks of VMARs held, so it must not
+    /// operate on VMARs. The hook can only be set once.
+    pub fn set_mapping_hook(&self, hook: Box<dyn Fn() + Send + Sync>) {
+        self.0.mapping_hook.call_once(|| hook);
+    }
+
+    /// Sets whether the pages of a VMO are locked in memory.
+    ///
+    /// The pages of a locked VMO are never swapped out. The pages that have
+    /// already been swapped out are still swapped in on demand.
+    pub fn set_unevictable(&self, is_unevictable: bool) {
+        self.0
+            .is_unevictable
+            .store(is_unevictable, Ordering::Relaxed);
+    }
+}
+
+/// The status of writable shared mappings of a VMO.
+///
+/// A VMO either has a number of writable shared mappings, or denies the
+/// creation of such mappings (e.g., after a memfd is sealed with
+/// `F_SEAL_WRITE`). The two states are mutually exclusive.
+#[derive(Debug, Default)]
+pub struct WritableMappingStatus(AtomicIsize);
+
+impl WritableMappingStatus {
+    /// Records a new writable shared mapping.
+    ///
+    /// Returns `Err` if writable shared mappings are denied.
+    pub fn map(&self) -> Result<()> {
+        self.0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count >= 0).then_some(count + 1)
+            })
+            .map_err(|_| Error::with_message(Errno::EPERM, "writable mappings are denied"))?;
+        Ok(())
+    }
+
+    /// Removes a writable shared mapping recorded by [`Self::map`].
+    pub fn unmap(&self) {
+        let old_count = self.0.fetch_sub(1, Ordering::AcqRel);
+        debug_assert!(old_count > 0);
+    }
+
+    /// Denies the creation of writable shared mappings from now on.
+    ///
+    /// Returns `Err` if there are writable shared mappings.
+    pub fn deny(&self) -> Result<()> {
+        self.0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count <= 0).then_some(-1)
+            })
+            .map_err(|_| Error::with_message(Errno::EBUSY, "writable mappings exist"))?;
+        Ok(())
+    }
 }
 
 /// Gets the page index range that contains the offset range of VMO.
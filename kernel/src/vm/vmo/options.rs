@@ -4,15 +4,19 @@
 
 //! Options for allocating root and child VMOs.
 
-use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::{AtomicBool, AtomicUsize};
 
 use align_ext::AlignExt;
 use aster_rights::{Rights, TRightSet, TRights};
 use ostd::mm::{FrameAllocOptions, UFrame, USegment};
+use spin::Once;
 use xarray::XArray;
 
-use super::{Pager, Vmo, VmoFlags};
-use crate::{prelude::*, vm::vmo::Vmo_};
+use super::{Pager, Vmo, VmoFlags, WritableMappingStatus};
+use crate::{
+    prelude::*,
+    vm::{swap, vmo::Vmo_},
+};
 
 /// Options for allocating a root VMO.
 ///
@@ -97,7 +101,7 @@ impl VmoOptions<Rights> {
             size, flags, pager, ..
         } = self;
         let vmo_ = alloc_vmo_(size, flags, pager)?;
-        Ok(Vmo(Arc::new(vmo_), Rights::all()))
+        Ok(Vmo(vmo_, Rights::all()))
     }
 }
 
@@ -116,19 +120,28 @@ impl<R: TRights> VmoOptions<TRightSet<R>> {
             pager,
         } = self;
         let vmo_ = alloc_vmo_(size, flags, pager)?;
-        Ok(Vmo(Arc::new(vmo_), TRightSet(R::new())))
+        Ok(Vmo(vmo_, TRightSet(R::new())))
     }
 }
 
-fn alloc_vmo_(size: usize, flags: VmoFlags, pager: Option<Arc<dyn Pager>>) -> Result<Vmo_> {
+fn alloc_vmo_(size: usize, flags: VmoFlags, pager: Option<Arc<dyn Pager>>) -> Result<Arc<Vmo_>> {
     let size = size.align_up(PAGE_SIZE);
     let pages = committed_pages_if_continuous(flags, size)?;
-    Ok(Vmo_ {
+    let vmo_ = Arc::new(Vmo_ {
         pager,
         flags,
         pages,
+        swapped: SpinLock::new(BTreeMap::new()),
         size: AtomicUsize::new(size),
-    })
+        writable_mapping_status: WritableMappingStatus::default(),
+        num_mappings: AtomicUsize::new(0),
+        mapping_hook: Once::new(),
+        is_unevictable: AtomicBool::new(false),
+    });
+    if vmo_.is_swappable() {
+        swap::register_vmo(&vmo_);
+    }
+    Ok(vmo_)
 }
 
 fn committed_pages_if_continuous(flags: VmoFlags, size: usize) -> Result<XArray<UFrame>> {
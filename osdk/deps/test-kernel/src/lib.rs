@@ -33,6 +33,7 @@ pub enum KtestResult {
 /// The entry point of the test runner.
 #[ostd::ktest::main]
 fn main() {
+    #[cfg(not(miri))]
     use ostd::task::TaskOptions;
 
     let test_task = move || {
@@ -49,6 +50,10 @@ fn main() {
         };
     };
 
+    // Miri cannot switch contexts, so the tests run in the boot context.
+    #[cfg(miri)]
+    test_task();
+    #[cfg(not(miri))]
     TaskOptions::new(test_task).data(()).spawn().unwrap();
 }
 
@@ -50,6 +50,8 @@ pub enum BaseCrateType {
     Run,
     /// The base crate is for testing the target crate.
     Test,
+    /// The base crate is for testing the target crate on the host with Miri.
+    Miri,
     /// The base crate is for other actions using Cargo.
     Other,
 }
@@ -73,6 +75,7 @@ pub fn new_base_crate(
             + match base_type {
                 BaseCrateType::Run => "-run-base",
                 BaseCrateType::Test => "-test-base",
+                BaseCrateType::Miri => "-miri-base",
                 BaseCrateType::Other => "-base",
             })
         .to_string(),
@@ -82,6 +85,7 @@ pub fn new_base_crate(
         // Reuse the existing base crate if it is identical to the new one.
         let base_crate_tmp_path = base_crate_path.join("tmp");
         do_new_base_crate(
+            base_type,
             &base_crate_tmp_path,
             dep_crate_name,
             &dep_crate_path,
@@ -102,6 +106,7 @@ pub fn new_base_crate(
         }
     }
     do_new_base_crate(
+        base_type,
         &base_crate_path,
         dep_crate_name,
         dep_crate_path,
@@ -112,6 +117,7 @@ pub fn new_base_crate(
 }
 
 fn do_new_base_crate(
+    base_type: BaseCrateType,
     base_crate_path: impl AsRef<Path>,
     dep_crate_name: &str,
     dep_crate_path: impl AsRef<Path>,
@@ -176,7 +182,10 @@ fn do_new_base_crate(
     include_linker_script!(["x86_64.ld", "riscv64.ld"]);
 
     // Overwrite the main.rs file
-    let main_rs = include_str!("main.rs.template");
+    let main_rs = match base_type {
+        BaseCrateType::Miri => include_str!("miri_main.rs.template"),
+        _ => include_str!("main.rs.template"),
+    };
     // Replace all occurrence of `#TARGET_NAME#` with the `dep_crate_name`
     let main_rs = main_rs.replace("#TARGET_NAME#", &dep_crate_name.replace('-', "_"));
     fs::write("src/main.rs", main_rs).unwrap();
@@ -8,8 +8,8 @@ use crate::{
     arch::Arch,
     commands::{
         execute_build_command, execute_debug_command, execute_forwarded_command,
-        execute_forwarded_command_on_each_crate, execute_new_command, execute_profile_command,
-        execute_run_command, execute_test_command,
+        execute_forwarded_command_on_each_crate, execute_miri_command, execute_new_command,
+        execute_profile_command, execute_run_command, execute_test_command,
     },
     config::{
         manifest::{ProjectType, TomlManifest},
@@ -56,6 +56,7 @@ pub fn main() {
         OsdkSubcommand::Test(test_args) => {
             execute_test_command(&load_config(&test_args.common_args), test_args);
         }
+        OsdkSubcommand::Miri(miri_args) => execute_miri_command(miri_args),
         OsdkSubcommand::Check(args) => {
             execute_forwarded_command_on_each_crate("check", &args.args, true)
         }
@@ -94,6 +95,8 @@ pub enum OsdkSubcommand {
     Profile(ProfileArgs),
     #[command(about = "Execute kernel mode unit test by starting a VMM")]
     Test(TestArgs),
+    #[command(about = "Execute kernel mode unit test on the host with Miri")]
+    Miri(MiriArgs),
     #[command(about = "Check a local package and all of its dependencies for errors")]
     Check(ForwardedArguments),
     #[command(about = "Checks a package to catch common mistakes and improve your Rust code")]
@@ -330,6 +333,17 @@ pub struct TestArgs {
     pub common_args: CommonArgs,
 }
 
+#[derive(Debug, Parser)]
+pub struct MiriArgs {
+    #[arg(
+        name = "TESTNAME",
+        help = "Only run tests containing this string in their names"
+    )]
+    pub test_name: Option<String>,
+    #[command(flatten)]
+    pub cargo_args: CargoArgs,
+}
+
 #[derive(Debug, Args, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CargoArgs {
     #[arg(
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{fs, process};
+
+use super::util::{cargo, DEFAULT_TARGET_RELPATH};
+use crate::{
+    base_crate::{new_base_crate, BaseCrateType},
+    cli::MiriArgs,
+    error::Errno,
+    error_msg,
+    util::{get_current_crates, get_target_directory, DirGuard},
+};
+
+/// The Miri flags that are always needed to run OSTD.
+///
+/// The kernel never frees some of its memory, e.g., the emulated physical
+/// memory, so leaks are not reported. OSTD casts integers to pointers when
+/// accessing physical memory or CPU-local objects, which is only allowed with
+/// permissive provenance.
+const DEFAULT_MIRI_FLAGS: &[&str] = &["-Zmiri-ignore-leaks", "-Zmiri-permissive-provenance"];
+
+pub fn execute_miri_command(args: &MiriArgs) {
+    let crates = get_current_crates();
+    for crate_info in crates {
+        std::env::set_current_dir(crate_info.path).unwrap();
+        miri_current_crate(args);
+    }
+}
+
+fn miri_current_crate(args: &MiriArgs) {
+    let current_crates = get_current_crates();
+    if current_crates.len() != 1 {
+        error_msg!("The current directory contains more than one crate");
+        process::exit(Errno::TooManyCrates as _);
+    }
+    let current_crate = get_current_crates().remove(0);
+
+    if current_crate.name == "osdk-test-kernel" {
+        error_msg!("The tested crate name collides with the OSDK test runner crate");
+        process::exit(Errno::BadCrateName as _);
+    }
+
+    let cargo_target_directory = get_target_directory();
+    let osdk_output_directory = cargo_target_directory.join(DEFAULT_TARGET_RELPATH);
+
+    let target_crate_dir = new_base_crate(
+        BaseCrateType::Miri,
+        osdk_output_directory.join(&current_crate.name),
+        &current_crate.name,
+        &current_crate.path,
+        true,
+    );
+
+    let main_rs_path = target_crate_dir.join("src").join("main.rs");
+
+    let ktest_test_whitelist = match &args.test_name {
+        Some(name) => format!(r#"Some(&["{}"])"#, name),
+        None => r#"None"#.to_string(),
+    };
+
+    let mut ktest_crate_whitelist = vec![current_crate.name.clone()];
+    if let Some(name) = &args.test_name {
+        ktest_crate_whitelist.push(name.clone());
+    }
+
+    // Append the ktest whitelist getters and the runner reference to the
+    // `main.rs` file. Miri cannot resolve extern statics, so getters are
+    // generated instead of the statics that `cargo osdk test` generates.
+    let ktest_main_rs = format!(
+        r#"
+
+extern crate osdk_test_kernel;
+
+#[no_mangle]
+fn __ktest_test_whitelist() -> Option<&'static [&'static str]> {{
+    {}
+}}
+#[no_mangle]
+fn __ktest_crate_whitelist() -> Option<&'static [&'static str]> {{
+    Some(&{:?})
+}}
+
+"#,
+        ktest_test_whitelist, ktest_crate_whitelist,
+    );
+    let mut main_rs_content = fs::read_to_string(&main_rs_path).unwrap();
+    main_rs_content.push_str(&ktest_main_rs);
+    fs::write(&main_rs_path, main_rs_content).unwrap();
+
+    let _dir_guard = DirGuard::change_dir(&target_crate_dir);
+
+    // Unlike other commands, the base crate is built for the host, since Miri
+    // interprets the kernel on the host instead of running it on a machine.
+    let env_rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+    let rustflags = [
+        env_rustflags.as_str(),
+        "--check-cfg cfg(ktest)",
+        "--cfg ktest",
+        "-C panic=unwind",
+    ];
+    let env_miriflags = std::env::var("MIRIFLAGS").unwrap_or_default();
+    let mut miriflags = Vec::from(DEFAULT_MIRI_FLAGS);
+    miriflags.push(&env_miriflags);
+
+    let cargo_args = &args.cargo_args;
+    let mut command = cargo();
+    command.env_remove("RUSTUP_TOOLCHAIN");
+    command.env("RUSTFLAGS", rustflags.join(" "));
+    command.env("MIRIFLAGS", miriflags.join(" "));
+    command.arg("miri").arg("run");
+    command.arg("--features").arg(cargo_args.features.join(" "));
+    if cargo_args.no_default_features {
+        command.arg("--no-default-features");
+    }
+    command
+        .arg("--target-dir")
+        .arg(cargo_target_directory.join("miri"));
+    if let Some(profile) = cargo_args.profile() {
+        command.arg("--profile=".to_string() + &profile);
+    }
+    for override_config in &cargo_args.override_configs {
+        command.arg("--config").arg(override_config);
+    }
+
+    info!(
+        "Running kernel mode unit tests using command: {:#?}",
+        command
+    );
+
+    let status = command.status().unwrap();
+    if !status.success() {
+        error_msg!("Miri run failed");
+        process::exit(status.code().unwrap_or(Errno::ExecuteCommand as _));
+    }
+}
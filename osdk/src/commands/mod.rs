@@ -4,6 +4,7 @@
 
 mod build;
 mod debug;
+mod miri;
 mod new;
 mod profile;
 mod run;
@@ -13,8 +14,9 @@ mod util;
 use util::DEFAULT_TARGET_RELPATH;
 
 pub use self::{
-    build::execute_build_command, debug::execute_debug_command, new::execute_new_command,
-    profile::execute_profile_command, run::execute_run_command, test::execute_test_command,
+    build::execute_build_command, debug::execute_debug_command, miri::execute_miri_command,
+    new::execute_new_command, profile::execute_profile_command, run::execute_run_command,
+    test::execute_test_command,
 };
 
 use crate::{
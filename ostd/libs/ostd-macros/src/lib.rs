@@ -276,6 +276,10 @@ pub fn ktest(_attr: TokenStream, item: TokenStream) -> TokenStream {
         &format!("{}_ktest_item_{}", &input.sig.ident, &fn_id),
         proc_macro2::Span::call_site(),
     );
+    let fn_ktest_ctor_name = Ident::new(
+        &format!("{}_ktest_ctor_{}", &input.sig.ident, &fn_id),
+        proc_macro2::Span::call_site(),
+    );
 
     let is_should_panic_attr = |attr: &&syn::Attribute| {
         attr.path()
@@ -364,10 +368,33 @@ pub fn ktest(_attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     };
 
+    // Miri knows nothing about the `.ktest_array` section. So the test item is
+    // also registered by a global constructor, which Miri runs before entering
+    // the kernel.
+    let ktest_mod = if package_name.as_str() == "ostd" {
+        quote! { ostd_test }
+    } else {
+        quote! { ostd::ktest }
+    };
+    let register_ktest_node = quote! {
+        #[cfg(all(ktest, miri))]
+        #[used]
+        #[link_section = ".init_array"]
+        static #fn_ktest_ctor_name: extern "C" fn() = {
+            extern "C" fn register() {
+                static NODE: #ktest_mod::KtestNode = #ktest_mod::KtestNode::new(&#fn_ktest_item_name);
+                NODE.register();
+            }
+            register
+        };
+    };
+
     let output = quote! {
         #input
 
         #register_ktest_item
+
+        #register_ktest_node
     };
 
     TokenStream::from(output)
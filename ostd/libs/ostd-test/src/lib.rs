@@ -68,6 +68,8 @@
 
 extern crate alloc;
 use alloc::{boxed::Box, string::String};
+#[cfg(miri)]
+use core::sync::atomic::{AtomicPtr, Ordering};
 
 #[derive(Clone, Debug)]
 pub struct PanicInfo {
@@ -179,6 +181,7 @@ impl KtestItem {
     }
 }
 
+#[cfg(not(miri))]
 macro_rules! ktest_array {
     () => {{
         extern "C" {
@@ -192,6 +195,67 @@ macro_rules! ktest_array {
     }};
 }
 
+/// A node in the list of the tests registered under Miri.
+///
+/// Miri does not support custom linker script sections, so each test item is
+/// pushed into a global linked list by a constructor generated by `#[ktest]`.
+#[cfg(miri)]
+#[doc(hidden)]
+pub struct KtestNode {
+    item: &'static KtestItem,
+    next: AtomicPtr<KtestNode>,
+}
+
+#[cfg(miri)]
+static KTEST_LIST: AtomicPtr<KtestNode> = AtomicPtr::new(core::ptr::null_mut());
+
+#[cfg(miri)]
+impl KtestNode {
+    /// Create a new [`KtestNode`].
+    ///
+    /// Do not use this function directly. Instead, use the `#[ktest]`
+    /// attribute to mark the test function.
+    pub const fn new(item: &'static KtestItem) -> Self {
+        Self {
+            item,
+            next: AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+
+    /// Register the test item into the global list.
+    pub fn register(&'static self) {
+        let mut head = KTEST_LIST.load(Ordering::Acquire);
+        loop {
+            self.next.store(head, Ordering::Relaxed);
+            match KTEST_LIST.compare_exchange_weak(
+                head,
+                self as *const _ as *mut _,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+}
+
+#[cfg(not(miri))]
+fn get_ktest_item(index: usize) -> Option<&'static KtestItem> {
+    ktest_array!().get(index)
+}
+
+#[cfg(miri)]
+fn get_ktest_item(index: usize) -> Option<&'static KtestItem> {
+    let mut node = KTEST_LIST.load(Ordering::Acquire);
+    for _ in 0..index {
+        // SAFETY: All the nodes in the list are registered statics.
+        node = unsafe { node.as_ref() }?.next.load(Ordering::Acquire);
+    }
+    // SAFETY: All the nodes in the list are registered statics.
+    unsafe { node.as_ref() }.map(|node| node.item)
+}
+
 /// The iterator of the ktest array.
 pub struct KtestIter {
     index: usize,
@@ -216,7 +280,7 @@ impl core::iter::Iterator for KtestIter {
     type Item = KtestItem;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let ktest_item = ktest_array!().get(self.index)?;
+        let ktest_item = get_ktest_item(self.index)?;
         self.index += 1;
         Some(ktest_item.clone())
     }
@@ -224,18 +288,31 @@ impl core::iter::Iterator for KtestIter {
 
 // The whitelists that will be generated by the OSDK as static consts.
 // They deliver the target tests that the user wants to run.
+#[cfg(not(miri))]
 extern "Rust" {
     static KTEST_TEST_WHITELIST: Option<&'static [&'static str]>;
     static KTEST_CRATE_WHITELIST: Option<&'static [&'static str]>;
 }
 
+// Miri cannot resolve extern statics, so the OSDK generates getter functions
+// for the whitelists instead.
+#[cfg(miri)]
+extern "Rust" {
+    fn __ktest_test_whitelist() -> Option<&'static [&'static str]>;
+    fn __ktest_crate_whitelist() -> Option<&'static [&'static str]>;
+}
+
 /// Get the whitelist of the tests.
 ///
 /// The whitelist is generated by the OSDK runner, indicating name of the
 /// target tests that the user wants to run.
 pub fn get_ktest_test_whitelist() -> Option<&'static [&'static str]> {
     // SAFETY: The two extern statics in the base crate are generated by OSDK.
-    unsafe { KTEST_TEST_WHITELIST }
+    #[cfg(not(miri))]
+    return unsafe { KTEST_TEST_WHITELIST };
+    // SAFETY: The getter in the base crate is generated by OSDK.
+    #[cfg(miri)]
+    return unsafe { __ktest_test_whitelist() };
 }
 
 /// Get the whitelist of the crates.
@@ -244,5 +321,9 @@ pub fn get_ktest_test_whitelist() -> Option<&'static [&'static str]> {
 /// that the user wants to test.
 pub fn get_ktest_crate_whitelist() -> Option<&'static [&'static str]> {
     // SAFETY: The two extern statics in the base crate are generated by OSDK.
-    unsafe { KTEST_CRATE_WHITELIST }
+    #[cfg(not(miri))]
+    return unsafe { KTEST_CRATE_WHITELIST };
+    // SAFETY: The getter in the base crate is generated by OSDK.
+    #[cfg(miri)]
+    return unsafe { __ktest_crate_whitelist() };
 }
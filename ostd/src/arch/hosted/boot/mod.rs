@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The hosted boot module defines the entrypoint of OSTD in Miri.
+
+pub mod smp;
+
+use crate::{
+    arch::mm::{set_current_page_table_paddr, set_phys_mem_base},
+    boot::{
+        call_ostd_main,
+        memory_region::{MemoryRegion, MemoryRegionArray, MemoryRegionType},
+        BootloaderAcpiArg, EarlyBootInfo, EARLY_INFO,
+    },
+    mm::{Paddr, PAGE_SIZE},
+};
+
+/// The size of the emulated physical memory.
+const PHYS_MEM_SIZE: usize = 64 * 1024 * 1024;
+
+/// The physical address of the page table that the "firmware" sets up.
+const BOOT_PAGE_TABLE_PADDR: Paddr = PAGE_SIZE;
+
+extern "Rust" {
+    /// Allocates memory from the interpreter.
+    ///
+    /// This function is provided by Miri.
+    fn miri_alloc(size: usize, align: usize) -> *mut u8;
+}
+
+fn parse_memory_regions() -> MemoryRegionArray {
+    let mut regions = MemoryRegionArray::new();
+
+    // The first page pretends to be where the kernel is loaded, so that the
+    // kernel code mapping has something to map.
+    regions
+        .push(MemoryRegion::new(0, PAGE_SIZE, MemoryRegionType::Kernel))
+        .unwrap();
+    regions
+        .push(MemoryRegion::new(
+            BOOT_PAGE_TABLE_PADDR,
+            PAGE_SIZE,
+            MemoryRegionType::Reserved,
+        ))
+        .unwrap();
+    regions
+        .push(MemoryRegion::new(
+            BOOT_PAGE_TABLE_PADDR + PAGE_SIZE,
+            PHYS_MEM_SIZE - BOOT_PAGE_TABLE_PADDR - PAGE_SIZE,
+            MemoryRegionType::Usable,
+        ))
+        .unwrap();
+
+    regions.into_non_overlapping()
+}
+
+/// The entry point of OSTD on the hosted platform.
+///
+/// It is called by the `miri_start` function of the base crate generated by
+/// `cargo osdk miri`.
+pub fn hosted_boot() -> ! {
+    // SAFETY: The size is non-zero and the alignment is a power of two.
+    let phys_mem = unsafe { miri_alloc(PHYS_MEM_SIZE, PAGE_SIZE) };
+    assert!(!phys_mem.is_null());
+    // SAFETY: The memory is valid for writes of `PHYS_MEM_SIZE` bytes, and is
+    // never deallocated.
+    unsafe {
+        core::ptr::write_bytes(phys_mem, 0, PHYS_MEM_SIZE);
+        set_phys_mem_base(phys_mem);
+    }
+
+    // An empty root page table stands for the one set up by the firmware.
+    set_current_page_table_paddr(BOOT_PAGE_TABLE_PADDR);
+
+    EARLY_INFO.call_once(|| EarlyBootInfo {
+        bootloader_name: "Miri",
+        kernel_cmdline: "",
+        initramfs: None,
+        acpi_arg: BootloaderAcpiArg::NotProvided,
+        framebuffer_arg: None,
+        memory_regions: parse_memory_regions(),
+    });
+
+    call_ostd_main();
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! CPU execution context control.
+//!
+//! There is no user space on the hosted platform. The types are defined so
+//! that the architecture-independent code compiles, but executing a user
+//! context always fails with [`CpuException::NO_USER_SPACE`].
+
+use core::fmt::Debug;
+
+pub use crate::arch::trap::GeneralRegs as RawGeneralRegs;
+use crate::{
+    arch::trap::TrapFrame,
+    user::{ReturnReason, UserContextApi, UserContextApiInternal},
+};
+
+/// Cpu context, including both general-purpose registers and FPU state.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct UserContext {
+    general: RawGeneralRegs,
+    fpu_state: FpuState,
+    cpu_exception_info: CpuExceptionInfo,
+}
+
+/// CPU exception information.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct CpuExceptionInfo {
+    /// The type of the exception.
+    pub code: CpuException,
+    /// The error code associated with the exception.
+    pub error_code: usize,
+    /// The virtual address where a page fault occurred.
+    pub page_fault_addr: usize,
+}
+
+impl CpuExceptionInfo {
+    /// Get corresponding CPU exception
+    pub fn cpu_exception(&self) -> CpuException {
+        self.code
+    }
+}
+
+impl UserContext {
+    /// Returns a reference to the general registers.
+    pub fn general_regs(&self) -> &RawGeneralRegs {
+        &self.general
+    }
+
+    /// Returns a mutable reference to the general registers
+    pub fn general_regs_mut(&mut self) -> &mut RawGeneralRegs {
+        &mut self.general
+    }
+
+    /// Returns the trap information.
+    pub fn trap_information(&self) -> &CpuExceptionInfo {
+        &self.cpu_exception_info
+    }
+
+    /// Returns a reference to the FPU state.
+    pub fn fpu_state(&self) -> &FpuState {
+        &self.fpu_state
+    }
+
+    /// Returns a mutable reference to the FPU state.
+    pub fn fpu_state_mut(&mut self) -> &mut FpuState {
+        &mut self.fpu_state
+    }
+
+    /// Sets thread-local storage pointer.
+    pub fn set_tls_pointer(&mut self, tls: usize) {
+        self.general.tls = tls;
+    }
+
+    /// Gets thread-local storage pointer.
+    pub fn tls_pointer(&self) -> usize {
+        self.general.tls
+    }
+
+    /// Activates thread-local storage pointer on the current CPU.
+    pub fn activate_tls_pointer(&self) {
+        // No-op
+    }
+}
+
+impl UserContextApiInternal for UserContext {
+    fn execute<F>(&mut self, _has_kernel_event: F) -> ReturnReason
+    where
+        F: FnMut() -> bool,
+    {
+        // There is no user space to return to, so report an exception that
+        // the caller can recognize instead.
+        self.cpu_exception_info = CpuExceptionInfo {
+            code: CpuException::NO_USER_SPACE,
+            ..Default::default()
+        };
+        ReturnReason::UserException
+    }
+
+    fn as_trap_frame(&self) -> TrapFrame {
+        TrapFrame {
+            general: self.general,
+        }
+    }
+}
+
+impl UserContextApi for UserContext {
+    fn trap_number(&self) -> usize {
+        self.cpu_exception_info.code.0
+    }
+
+    fn trap_error_code(&self) -> usize {
+        self.cpu_exception_info.error_code
+    }
+
+    fn instruction_pointer(&self) -> usize {
+        self.general.ip
+    }
+
+    fn set_instruction_pointer(&mut self, ip: usize) {
+        self.general.ip = ip;
+    }
+
+    fn stack_pointer(&self) -> usize {
+        self.general.sp
+    }
+
+    fn set_stack_pointer(&mut self, sp: usize) {
+        self.general.sp = sp;
+    }
+}
+
+/// CPU exception.
+///
+/// No exceptions are raised on the hosted platform, so it only records the
+/// trap number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuException(pub usize);
+
+impl CpuException {
+    /// The exception that is reported whenever a user context is executed.
+    pub const NO_USER_SPACE: Self = Self(usize::MAX);
+}
+
+/// The FPU state of user task.
+///
+/// The hosted platform has no user tasks, so there is no state to save.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FpuState;
+
+impl FpuState {
+    /// Saves CPU's current FPU state into this instance.
+    pub fn save(&self) {}
+
+    /// Restores CPU's FPU state from this instance.
+    pub fn restore(&self) {}
+}
@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Architecture dependent CPU-local information utilities.
+
+/// Gets the base address for the CPU local storage.
+///
+/// There is only one CPU on the hosted platform, whose CPU-local objects are
+/// the statics themselves. The base is zero so that the offset of a
+/// CPU-local object is its address.
+pub(crate) fn get_base() -> u64 {
+    0
+}
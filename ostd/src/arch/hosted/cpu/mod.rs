@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! CPU context & state control and CPU local memory.
+
+pub mod context;
+pub mod local;
+
+/// Halts the CPU.
+///
+/// There are no interrupts on the hosted platform, so this function only
+/// hints the interpreter that it is spinning.
+///
+/// Since the function sleeps the CPU, it should not be used within an atomic
+/// mode ([`crate::task::atomic_mode`]).
+#[track_caller]
+pub fn sleep_for_interrupt() {
+    crate::task::atomic_mode::might_sleep();
+    core::hint::spin_loop();
+}
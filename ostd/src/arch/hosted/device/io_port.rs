@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! I/O port access.
+
+use core::marker::PhantomData;
+
+pub struct WriteOnlyAccess;
+pub struct ReadWriteAccess;
+
+pub trait IoPortWriteAccess {}
+pub trait IoPortReadAccess {}
+
+impl IoPortWriteAccess for WriteOnlyAccess {}
+impl IoPortWriteAccess for ReadWriteAccess {}
+impl IoPortReadAccess for ReadWriteAccess {}
+
+/// Reads from I/O ports.
+///
+/// There are no I/O ports on the hosted platform. Like reading an I/O port
+/// that no device responds to, a read returns all ones.
+pub trait PortRead: Sized {
+    unsafe fn read_from_port(port: u16) -> Self;
+}
+
+/// Writes to I/O ports.
+///
+/// There are no I/O ports on the hosted platform, so writes are discarded.
+pub trait PortWrite: Sized {
+    unsafe fn write_to_port(_port: u16, _value: Self) {}
+}
+
+macro_rules! impl_port_read {
+    ($($ty:ty)*) => {
+        $(
+            impl PortRead for $ty {
+                unsafe fn read_from_port(_port: u16) -> Self {
+                    <$ty>::MAX
+                }
+            }
+        )*
+    };
+}
+
+impl_port_read!(u8 u16 u32);
+
+impl PortWrite for u8 {}
+impl PortWrite for u16 {}
+impl PortWrite for u32 {}
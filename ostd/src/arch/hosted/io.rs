@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Device I/O on the hosted platform.
+//!
+//! There are no devices, so the allocators are never initialized.
+
+/// The maximum I/O port number.
+///
+/// It is kept the same as x86-64, since the port I/O allocator is compiled
+/// in whenever the host is an x86-64 machine.
+pub const MAX_IO_PORT: u16 = u16::MAX;
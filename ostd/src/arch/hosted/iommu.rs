@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The IOMMU support.
+//!
+//! The hosted platform has no IOMMU.
+
+use crate::mm::{dma::Daddr, Paddr};
+
+/// An enumeration representing possible errors related to IOMMU.
+#[derive(Debug)]
+pub enum IommuError {
+    /// No IOMMU is available.
+    NoIommu,
+}
+
+///
+/// # Safety
+///
+/// Mapping an incorrect address may lead to a kernel data leak.
+pub(crate) unsafe fn map(_daddr: Daddr, _paddr: Paddr) -> Result<(), IommuError> {
+    Err(IommuError::NoIommu)
+}
+
+pub(crate) fn unmap(_daddr: Daddr) -> Result<(), IommuError> {
+    Err(IommuError::NoIommu)
+}
+
+pub(crate) fn has_dma_remapping() -> bool {
+    false
+}
+
+pub(crate) fn has_interrupt_remapping() -> bool {
+    false
+}
@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Interrupts.
+//!
+//! No interrupts are delivered on the hosted platform. The IRQ lines can
+//! still be allocated and have callbacks registered, but the callbacks are
+//! never invoked. The local interrupt flag is emulated so that the IRQ
+//! guards behave the same as on other platforms.
+
+#![expect(dead_code)]
+
+use alloc::{boxed::Box, fmt::Debug, sync::Arc, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use id_alloc::IdAlloc;
+use spin::Once;
+
+use crate::{
+    cpu::CpuId,
+    sync::{Mutex, PreemptDisabled, RwLock, RwLockReadGuard, SpinLock},
+    trap::TrapFrame,
+};
+
+/// The global allocator for software defined IRQ lines.
+pub(crate) static IRQ_ALLOCATOR: Once<SpinLock<IdAlloc>> = Once::new();
+
+pub(crate) static IRQ_LIST: Once<Vec<IrqLine>> = Once::new();
+
+pub(crate) fn init() {
+    let mut list: Vec<IrqLine> = Vec::new();
+    for i in 0..256 {
+        list.push(IrqLine {
+            irq_num: i as u8,
+            callback_list: RwLock::new(Vec::new()),
+        });
+    }
+    IRQ_LIST.call_once(|| list);
+    CALLBACK_ID_ALLOCATOR.call_once(|| Mutex::new(IdAlloc::with_capacity(256)));
+    IRQ_ALLOCATOR.call_once(|| SpinLock::new(IdAlloc::with_capacity(256)));
+}
+
+/// The emulated local interrupt flag of the only CPU.
+static IS_LOCAL_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn enable_local() {
+    IS_LOCAL_ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn disable_local() {
+    IS_LOCAL_ENABLED.store(false, Ordering::Relaxed);
+}
+
+pub(crate) fn is_local_enabled() -> bool {
+    IS_LOCAL_ENABLED.load(Ordering::Relaxed)
+}
+
+static CALLBACK_ID_ALLOCATOR: Once<Mutex<IdAlloc>> = Once::new();
+
+pub struct CallbackElement {
+    function: Box<dyn Fn(&TrapFrame) + Send + Sync + 'static>,
+    id: usize,
+}
+
+impl CallbackElement {
+    pub fn call(&self, element: &TrapFrame) {
+        (self.function)(element);
+    }
+}
+
+impl Debug for CallbackElement {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CallbackElement")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// An interrupt request (IRQ) line.
+#[derive(Debug)]
+pub(crate) struct IrqLine {
+    pub(crate) irq_num: u8,
+    pub(crate) callback_list: RwLock<Vec<CallbackElement>>,
+}
+
+impl IrqLine {
+    /// Acquires an interrupt request line.
+    ///
+    /// # Safety
+    ///
+    /// This function is marked unsafe as manipulating interrupt lines is
+    /// considered a dangerous operation.
+    #[expect(clippy::redundant_allocation)]
+    pub unsafe fn acquire(irq_num: u8) -> Arc<&'static Self> {
+        Arc::new(IRQ_LIST.get().unwrap().get(irq_num as usize).unwrap())
+    }
+
+    /// Gets the IRQ number.
+    pub fn num(&self) -> u8 {
+        self.irq_num
+    }
+
+    pub fn callback_list(
+        &self,
+    ) -> RwLockReadGuard<alloc::vec::Vec<CallbackElement>, PreemptDisabled> {
+        self.callback_list.read()
+    }
+
+    /// Registers a callback that will be invoked when the IRQ is active.
+    ///
+    /// A handle to the callback is returned. Dropping the handle
+    /// automatically unregisters the callback.
+    ///
+    /// For each IRQ line, multiple callbacks may be registered.
+    pub fn on_active<F>(&self, callback: F) -> IrqCallbackHandle
+    where
+        F: Fn(&TrapFrame) + Sync + Send + 'static,
+    {
+        let allocated_id = CALLBACK_ID_ALLOCATOR.get().unwrap().lock().alloc().unwrap();
+        self.callback_list.write().push(CallbackElement {
+            function: Box::new(callback),
+            id: allocated_id,
+        });
+        IrqCallbackHandle {
+            irq_num: self.irq_num,
+            id: allocated_id,
+        }
+    }
+}
+
+/// The handle to a registered callback for a IRQ line.
+///
+/// When the handle is dropped, the callback will be unregistered automatically.
+#[must_use]
+#[derive(Debug)]
+pub struct IrqCallbackHandle {
+    irq_num: u8,
+    id: usize,
+}
+
+impl Drop for IrqCallbackHandle {
+    fn drop(&mut self) {
+        let mut a = IRQ_LIST
+            .get()
+            .unwrap()
+            .get(self.irq_num as usize)
+            .unwrap()
+            .callback_list
+            .write();
+        a.retain(|item| item.id != self.id);
+        CALLBACK_ID_ALLOCATOR.get().unwrap().lock().free(self.id);
+    }
+}
+
+/// Sends a general inter-processor interrupt (IPI) to the specified CPU.
+///
+/// # Safety
+///
+/// The caller must ensure that the CPU ID and the interrupt number corresponds
+/// to a safe function to call.
+pub(crate) unsafe fn send_ipi(_cpu_id: CpuId, _irq_num: u8) {
+    unreachable!("the hosted platform has only one processor");
+}
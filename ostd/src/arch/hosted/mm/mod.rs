@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Memory management on the hosted platform.
+//!
+//! The page table format is the same as x86-64, but the page tables are never
+//! walked by any MMU. Miri cannot access memory through page tables, so the
+//! physical memory is instead accessed through its linear mapping in the
+//! interpreter's address space (see [`phys_mem_base`]).
+
+#![expect(dead_code)]
+
+use alloc::fmt;
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ops::Range,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+use crate::{
+    mm::{
+        page_prop::{CachePolicy, PageFlags, PageProperty, PrivilegedPageFlags as PrivFlags},
+        page_table::PageTableEntryTrait,
+        Paddr, PagingConstsTrait, PagingLevel, PodOnce, Vaddr,
+    },
+    util::marker::SameSizeAs,
+    Pod,
+};
+
+pub(crate) const NR_ENTRIES_PER_PAGE: usize = 512;
+
+#[derive(Clone, Debug, Default)]
+pub struct PagingConsts {}
+
+impl PagingConstsTrait for PagingConsts {
+    const BASE_PAGE_SIZE: usize = 4096;
+    const NR_LEVELS: PagingLevel = 4;
+    const ADDRESS_WIDTH: usize = 48;
+    const HIGHEST_TRANSLATION_LEVEL: PagingLevel = 2;
+    const PTE_SIZE: usize = core::mem::size_of::<PageTableEntry>();
+}
+
+bitflags::bitflags! {
+    #[derive(Pod)]
+    #[repr(C)]
+    /// Possible flags for a page table entry.
+    pub struct PageTableFlags: usize {
+        /// Specifies whether the mapped frame or page table is loaded in memory.
+        const PRESENT =         1 << 0;
+        /// Controls whether writes to the mapped frames are allowed.
+        const WRITABLE =        1 << 1;
+        /// Controls whether accesses from userspace (i.e. ring 3) are permitted.
+        const USER =            1 << 2;
+        /// If this bit is set, a “write-through” policy is used for the cache, else a “write-back”
+        /// policy is used.
+        const WRITE_THROUGH =   1 << 3;
+        /// Disables caching for the pointed entry is cacheable.
+        const NO_CACHE =        1 << 4;
+        /// Whether this entry has been used for linear-address translation.
+        const ACCESSED =        1 << 5;
+        /// Whether the memory area represented by this entry is modified.
+        const DIRTY =           1 << 6;
+        /// In level 2 or 3 it indicates that it map to a huge page.
+        /// In level 1, it is the PAT (page attribute table) bit.
+        /// We use this bit in level 1, 2 and 3 to indicate that this entry is
+        /// "valid". For levels above 3, `PRESENT` is used for "valid".
+        const HUGE =            1 << 7;
+        /// Indicates that the mapping is present in all address spaces, so it isn't flushed from
+        /// the TLB on an address space switch.
+        const GLOBAL =          1 << 8;
+        /// Ignored by the hardware. Free to use.
+        const HIGH_IGN1 =       1 << 52;
+        /// Ignored by the hardware. Free to use.
+        const HIGH_IGN2 =       1 << 53;
+
+        /// Forbid execute codes on the page. The NXE bits in EFER msr must be set.
+        const NO_EXECUTE =      1 << 63;
+    }
+}
+
+/// Flush any TLB entry that contains the map of the given virtual address.
+///
+/// There is no TLB on the hosted platform.
+pub(crate) fn tlb_flush_addr(_vaddr: Vaddr) {}
+
+/// Flush any TLB entry that intersects with the given address range.
+pub(crate) fn tlb_flush_addr_range(_range: &Range<Vaddr>) {}
+
+/// Flush all TLB entries except for the global-page entries.
+pub(crate) fn tlb_flush_all_excluding_global() {}
+
+/// Flush all TLB entries, including global-page entries.
+pub(crate) fn tlb_flush_all_including_global() {}
+
+#[derive(Clone, Copy, Pod, Default)]
+#[repr(C)]
+pub struct PageTableEntry(usize);
+
+/// The physical address of the activated root page table.
+static CURRENT_PAGE_TABLE_PADDR: AtomicUsize = AtomicUsize::new(0);
+
+/// Activates the given level 4 page table.
+///
+/// # Safety
+///
+/// Changing the level 4 page table is unsafe, because it's possible to violate memory safety by
+/// changing the page mapping.
+pub unsafe fn activate_page_table(root_paddr: Paddr, _root_pt_cache: CachePolicy) {
+    set_current_page_table_paddr(root_paddr);
+}
+
+pub fn current_page_table_paddr() -> Paddr {
+    CURRENT_PAGE_TABLE_PADDR.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_current_page_table_paddr(root_paddr: Paddr) {
+    CURRENT_PAGE_TABLE_PADDR.store(root_paddr, Ordering::Relaxed);
+}
+
+/// The address of the emulated physical memory in the interpreter.
+static PHYS_MEM_BASE: AtomicPtr<u8> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Returns the address where the physical address zero is placed.
+pub(crate) fn phys_mem_base() -> usize {
+    PHYS_MEM_BASE.load(Ordering::Relaxed) as usize
+}
+
+/// Sets the address of the emulated physical memory.
+///
+/// # Safety
+///
+/// The memory must be valid for reads and writes for the lifetime of the
+/// kernel, and it must be large enough to cover all the memory regions
+/// reported in the boot information.
+pub(crate) unsafe fn set_phys_mem_base(base: *mut u8) {
+    PHYS_MEM_BASE.store(base, Ordering::Relaxed);
+}
+
+impl PageTableEntry {
+    const PHYS_ADDR_MASK: usize = 0xF_FFFF_FFFF_F000;
+    const PROP_MASK: usize = !Self::PHYS_ADDR_MASK & !PageTableFlags::HUGE.bits();
+}
+
+/// Parse a bit-flag bits `val` in the representation of `from` to `to` in bits.
+macro_rules! parse_flags {
+    ($val:expr, $from:expr, $to:expr) => {
+        ($val as usize & $from.bits() as usize) >> $from.bits().ilog2() << $to.bits().ilog2()
+    };
+}
+
+// SAFETY: `PageTableEntry` has the same size as `usize`
+unsafe impl SameSizeAs<usize> for PageTableEntry {}
+
+impl PodOnce for PageTableEntry {}
+
+impl PageTableEntryTrait for PageTableEntry {
+    fn is_present(&self) -> bool {
+        // For PT child, `PRESENT` should be set; for huge page, `HUGE` should
+        // be set; for the leaf child page, `PAT`, which is the same bit as
+        // the `HUGE` bit in upper levels, should be set.
+        self.0 & PageTableFlags::PRESENT.bits() != 0 || self.0 & PageTableFlags::HUGE.bits() != 0
+    }
+
+    fn new_page(paddr: Paddr, _level: PagingLevel, prop: PageProperty) -> Self {
+        let flags = PageTableFlags::HUGE.bits();
+        let mut pte = Self(paddr & Self::PHYS_ADDR_MASK | flags);
+        pte.set_prop(prop);
+        pte
+    }
+
+    fn new_pt(paddr: Paddr) -> Self {
+        // In x86 if it's an intermediate PTE, it's better to have the same permissions
+        // as the most permissive child (to reduce hardware page walk accesses). But we
+        // don't have a mechanism to keep it generic across architectures, thus just
+        // setting it to be the most permissive.
+        let flags = PageTableFlags::PRESENT.bits()
+            | PageTableFlags::WRITABLE.bits()
+            | PageTableFlags::USER.bits();
+        Self(paddr & Self::PHYS_ADDR_MASK | flags)
+    }
+
+    fn paddr(&self) -> Paddr {
+        self.0 & Self::PHYS_ADDR_MASK
+    }
+
+    fn prop(&self) -> PageProperty {
+        let flags = (parse_flags!(self.0, PageTableFlags::PRESENT, PageFlags::R))
+            | (parse_flags!(self.0, PageTableFlags::WRITABLE, PageFlags::W))
+            | (parse_flags!(!self.0, PageTableFlags::NO_EXECUTE, PageFlags::X))
+            | (parse_flags!(self.0, PageTableFlags::ACCESSED, PageFlags::ACCESSED))
+            | (parse_flags!(self.0, PageTableFlags::DIRTY, PageFlags::DIRTY))
+            | (parse_flags!(self.0, PageTableFlags::HIGH_IGN1, PageFlags::AVAIL1))
+            | (parse_flags!(self.0, PageTableFlags::HIGH_IGN2, PageFlags::AVAIL2));
+        let priv_flags = (parse_flags!(self.0, PageTableFlags::USER, PrivFlags::USER))
+            | (parse_flags!(self.0, PageTableFlags::GLOBAL, PrivFlags::GLOBAL));
+        let cache = if self.0 & PageTableFlags::NO_CACHE.bits() != 0 {
+            CachePolicy::Uncacheable
+        } else if self.0 & PageTableFlags::WRITE_THROUGH.bits() != 0 {
+            CachePolicy::Writethrough
+        } else {
+            CachePolicy::Writeback
+        };
+        PageProperty {
+            flags: PageFlags::from_bits(flags as u8).unwrap(),
+            cache,
+            priv_flags: PrivFlags::from_bits(priv_flags as u8).unwrap(),
+        }
+    }
+
+    fn set_prop(&mut self, prop: PageProperty) {
+        if !self.is_present() {
+            return;
+        }
+        let mut flags = PageTableFlags::empty().bits();
+        flags |= (parse_flags!(prop.flags.bits(), PageFlags::R, PageTableFlags::PRESENT))
+            | (parse_flags!(prop.flags.bits(), PageFlags::W, PageTableFlags::WRITABLE))
+            | (parse_flags!(!prop.flags.bits(), PageFlags::X, PageTableFlags::NO_EXECUTE))
+            | (parse_flags!(
+                prop.flags.bits(),
+                PageFlags::ACCESSED,
+                PageTableFlags::ACCESSED
+            ))
+            | (parse_flags!(prop.flags.bits(), PageFlags::DIRTY, PageTableFlags::DIRTY))
+            | (parse_flags!(
+                prop.flags.bits(),
+                PageFlags::AVAIL1,
+                PageTableFlags::HIGH_IGN1
+            ))
+            | (parse_flags!(
+                prop.flags.bits(),
+                PageFlags::AVAIL2,
+                PageTableFlags::HIGH_IGN2
+            ))
+            | (parse_flags!(
+                prop.priv_flags.bits(),
+                PrivFlags::USER,
+                PageTableFlags::USER
+            ))
+            | (parse_flags!(
+                prop.priv_flags.bits(),
+                PrivFlags::GLOBAL,
+                PageTableFlags::GLOBAL
+            ));
+        match prop.cache {
+            CachePolicy::Writeback => {}
+            CachePolicy::Writethrough => {
+                flags |= PageTableFlags::WRITE_THROUGH.bits();
+            }
+            CachePolicy::Uncacheable => {
+                flags |= PageTableFlags::NO_CACHE.bits();
+            }
+            _ => panic!("unsupported cache policy"),
+        }
+        self.0 = self.0 & !Self::PROP_MASK | flags;
+    }
+
+    fn is_last(&self, _level: PagingLevel) -> bool {
+        self.0 & PageTableFlags::HUGE.bits() != 0
+    }
+}
+
+impl fmt::Debug for PageTableEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut f = f.debug_struct("PageTableEntry");
+        f.field("raw", &format_args!("{:#x}", self.0))
+            .field("paddr", &format_args!("{:#x}", self.paddr()))
+            .field("present", &self.is_present())
+            .field(
+                "flags",
+                &PageTableFlags::from_bits_truncate(self.0 & !Self::PHYS_ADDR_MASK),
+            )
+            .field("prop", &self.prop())
+            .finish()
+    }
+}
+
+/// Copies `size` bytes from `src` to `dst`, returning the number of bytes
+/// that failed to be copied.
+///
+/// Page faults cannot be recovered on the hosted platform, so the copy either
+/// succeeds or triggers an error in the interpreter.
+///
+/// # Safety
+///
+/// The source and destination must be valid for the accesses.
+pub(crate) unsafe fn __memcpy_fallible(dst: *mut u8, src: *const u8, size: usize) -> usize {
+    // SAFETY: The safety is upheld by the caller.
+    unsafe { core::ptr::copy(src, dst, size) };
+    0
+}
+
+/// Fills `size` bytes in the memory pointed to by `dst` with the value
+/// `value`, returning the number of bytes that failed to be set.
+///
+/// # Safety
+///
+/// The destination must be valid for the accesses.
+pub(crate) unsafe fn __memset_fallible(dst: *mut u8, value: u8, size: usize) -> usize {
+    // SAFETY: The safety is upheld by the caller.
+    unsafe { core::ptr::write_bytes(dst, value, size) };
+    0
+}
+
+extern "Rust" {
+    fn miri_alloc(size: usize, align: usize) -> *mut u8;
+    fn miri_dealloc(ptr: *mut u8, size: usize, align: usize);
+}
+
+/// The heap allocator on the hosted platform.
+///
+/// It forwards the requests to the interpreter, so that Miri can check heap
+/// objects precisely, e.g., for use-after-free and out-of-bound accesses.
+struct HostedHeapAllocator;
+
+#[global_allocator]
+static HEAP_ALLOCATOR: HostedHeapAllocator = HostedHeapAllocator;
+
+// SAFETY: The allocations are managed by the interpreter.
+unsafe impl GlobalAlloc for HostedHeapAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: The layout has a non-zero size as required by `GlobalAlloc`.
+        unsafe { miri_alloc(layout.size(), layout.align()) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: The pointer is allocated by `alloc` with the same layout.
+        unsafe { miri_dealloc(ptr, layout.size(), layout.align()) }
+    }
+}
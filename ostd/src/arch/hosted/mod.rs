@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Platform-specific code for the hosted platform.
+//!
+//! The hosted platform is used to run OSTD in the Miri interpreter, so that
+//! the kernel mode unit tests can be checked for undefined behavior. It
+//! emulates a machine with a single CPU, whose physical memory is a buffer
+//! allocated from the interpreter. There are no devices, no interrupts, and no
+//! user space on the platform. Context switching is not supported either, so
+//! tasks other than the bootstrap context never run.
+//!
+//! The platform is selected automatically when OSTD is compiled with
+//! `cfg(miri)`, which is what `cargo osdk miri` does.
+
+pub mod boot;
+pub(crate) mod cpu;
+pub mod device;
+pub(crate) mod io;
+pub mod iommu;
+pub(crate) mod irq;
+pub(crate) mod mm;
+pub(crate) mod pci;
+pub mod qemu;
+pub mod serial;
+pub mod task;
+pub mod timer;
+pub mod trap;
+pub mod unwind;
+
+/// Architecture-specific initialization on the bootstrapping processor.
+///
+/// # Safety
+///
+/// This function must be called only once in the boot context of the
+/// bootstrapping processor.
+pub(crate) unsafe fn late_init_on_bsp() {
+    // SAFETY: This function is only called once on BSP.
+    unsafe { trap::init() };
+    irq::init();
+}
+
+/// Architecture-specific initialization on the application processor.
+///
+/// # Safety
+///
+/// This function must be called only once on each application processor.
+pub(crate) unsafe fn init_on_ap() {
+    unreachable!("the hosted platform has no application processors");
+}
+
+pub(crate) fn interrupts_ack(_irq_number: usize) {}
+
+pub(crate) fn enable_cpu_features() {}
+
+/// Inserts a TDX-specific code block.
+///
+/// The hosted platform is never a TDX guest, so only the `else_block` (if
+/// any) is inserted.
+#[macro_export]
+macro_rules! if_tdx_enabled {
+    ($if_block:block else $else_block:block) => {{
+        $else_block
+    }};
+    ($if_block:block) => {{}};
+}
+
+pub use if_tdx_enabled;
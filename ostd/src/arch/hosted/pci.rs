@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! PCI bus access.
+//!
+//! The hosted platform has no PCI bus.
+
+use crate::{bus::pci::PciDeviceLocation, prelude::*, trap::IrqLine, Error};
+
+pub(crate) fn write32(_location: &PciDeviceLocation, _offset: u32, _value: u32) -> Result<()> {
+    Err(Error::IoError)
+}
+
+pub(crate) fn read32(_location: &PciDeviceLocation, _offset: u32) -> Result<u32> {
+    Err(Error::IoError)
+}
+
+pub(crate) fn has_pci_bus() -> bool {
+    false
+}
+
+pub(crate) const MSIX_DEFAULT_MSG_ADDR: u32 = 0;
+
+pub(crate) fn construct_remappable_msix_address(_irq: &IrqLine) -> u32 {
+    unreachable!("there is no PCI device on the hosted platform")
+}
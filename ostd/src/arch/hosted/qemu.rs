@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Provides the ability to exit the interpreter and return a value as the
+//! debug result.
+//!
+//! The names are kept the same as other platforms so that the test runners
+//! need not to be aware of the hosted platform.
+
+/// The exit code of the interpreted program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum QemuExitCode {
+    /// The code that indicates a successful exit.
+    Success = 0,
+    /// The code that indicates a failed exit.
+    Failed = 1,
+}
+
+extern "C" {
+    /// Terminates the interpreted program.
+    ///
+    /// Miri implements this function like the C standard library does.
+    fn exit(status: i32) -> !;
+}
+
+/// Exits the interpreter with the given exit code.
+pub fn exit_qemu(exit_code: QemuExitCode) -> ! {
+    log::debug!("exit qemu with exit code {exit_code:?}");
+    // SAFETY: The kernel is about to exit, so no one can observe the
+    // skipped destructors.
+    unsafe { exit(exit_code as i32) }
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The architecture support of context switch.
+//!
+//! Miri cannot switch between machine stacks, so only the bootstrap context
+//! ever runs on the hosted platform.
+
+use crate::task::TaskContextApi;
+
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+pub(crate) struct TaskContext {
+    pub ip: usize,
+    pub sp: usize,
+    pub tls: usize,
+}
+
+impl TaskContext {
+    pub const fn new() -> Self {
+        Self {
+            ip: 0,
+            sp: 0,
+            tls: 0,
+        }
+    }
+
+    /// Sets thread-local storage pointer.
+    pub fn set_tls_pointer(&mut self, tls: usize) {
+        self.tls = tls;
+    }
+
+    /// Gets thread-local storage pointer.
+    pub fn tls_pointer(&self) -> usize {
+        self.tls
+    }
+}
+
+impl TaskContextApi for TaskContext {
+    fn set_instruction_pointer(&mut self, ip: usize) {
+        self.ip = ip;
+    }
+
+    fn instruction_pointer(&self) -> usize {
+        self.ip
+    }
+
+    fn set_stack_pointer(&mut self, sp: usize) {
+        self.sp = sp;
+    }
+
+    fn stack_pointer(&self) -> usize {
+        self.sp
+    }
+}
+
+/// Switches from the current task context to the next one.
+///
+/// # Safety
+///
+/// See the x86-64 counterpart. This function never returns on the hosted
+/// platform.
+pub(crate) unsafe fn context_switch(_cur: *mut TaskContext, _nxt: *const TaskContext) {
+    panic!("context switching is not supported on the hosted platform");
+}
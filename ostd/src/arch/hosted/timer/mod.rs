@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The timer support.
+//!
+//! The hosted platform has no timer interrupts, so the jiffies never advance.
+
+/// The timer frequency (Hz).
+///
+/// It is kept the same as other platforms so that time conversions behave
+/// alike.
+pub const TIMER_FREQ: u64 = 1000;
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handles trap.
+//!
+//! No traps occur on the hosted platform, since there are neither interrupts
+//! nor user space.
+
+use crate::Pod;
+
+/// The saved context when the kernel is trapped.
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+pub struct TrapFrame {
+    /// General registers
+    pub general: GeneralRegs,
+}
+
+/// General registers
+#[derive(Debug, Default, Clone, Copy, Pod)]
+#[repr(C)]
+pub struct GeneralRegs {
+    /// The instruction pointer.
+    pub ip: usize,
+    /// The stack pointer.
+    pub sp: usize,
+    /// The thread-local storage pointer.
+    pub tls: usize,
+}
+
+/// Initializes interrupt handling.
+///
+/// # Safety
+///
+/// This function must be called only once on each CPU.
+pub unsafe fn init() {}
+
+/// Returns true if this function is called within the context of an IRQ handler
+/// and the IRQ occurs while the CPU is executing in the kernel mode.
+/// Otherwise, it returns false.
+pub fn is_kernel_interrupted() -> bool {
+    false
+}
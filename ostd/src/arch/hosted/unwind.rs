@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Stack unwinding on the hosted platform.
+//!
+//! The DWARF unwinder cannot run in the interpreter, so the panics are
+//! unwound by Miri instead. The functions have the same signatures as their
+//! counterparts in the `unwinding` crate.
+
+use alloc::boxed::Box;
+use core::{any::Any, mem::ManuallyDrop};
+
+use unwinding::abi::UnwindReasonCode;
+
+extern "Rust" {
+    /// Starts unwinding the stack with the given payload.
+    ///
+    /// This function is provided by Miri.
+    fn miri_start_unwind(payload: *mut u8) -> !;
+}
+
+/// The payload passed through the interpreter.
+///
+/// A `Box<dyn Any + Send>` is a fat pointer, so it is boxed again to be
+/// passed as a thin pointer.
+type Payload = Box<dyn Any + Send>;
+
+/// Begins unwinding the stack with the given payload.
+pub fn begin_panic(payload: Box<dyn Any + Send>) -> UnwindReasonCode {
+    let payload = Box::into_raw(Box::new(payload));
+    // SAFETY: The payload is a valid pointer that is reclaimed by
+    // `catch_unwind`.
+    unsafe { miri_start_unwind(payload as *mut u8) }
+}
+
+/// Invokes a closure, capturing the cause of an unwinding panic if one
+/// occurs.
+pub fn catch_unwind<R, F: FnOnce() -> R>(f: F) -> Result<R, Box<dyn Any + Send>> {
+    union Data<F, R> {
+        f: ManuallyDrop<F>,
+        r: ManuallyDrop<R>,
+        p: ManuallyDrop<Payload>,
+    }
+
+    fn do_call<F: FnOnce() -> R, R>(data: *mut u8) {
+        // SAFETY: `data` points to the `Data` union in `catch_unwind`, which
+        // contains the closure at this moment.
+        unsafe {
+            let data = &mut *(data as *mut Data<F, R>);
+            let f = ManuallyDrop::take(&mut data.f);
+            data.r = ManuallyDrop::new(f());
+        }
+    }
+
+    fn do_catch<F: FnOnce() -> R, R>(data: *mut u8, payload: *mut u8) {
+        // SAFETY: `payload` is the pointer passed to `miri_start_unwind` by
+        // `begin_panic`, and `data` points to the `Data` union in
+        // `catch_unwind`, whose closure has been consumed.
+        unsafe {
+            let data = &mut *(data as *mut Data<F, R>);
+            let payload = Box::from_raw(payload as *mut Payload);
+            data.p = ManuallyDrop::new(*payload);
+        }
+    }
+
+    let mut data = Data {
+        f: ManuallyDrop::new(f),
+    };
+    let data_ptr = &mut data as *mut _ as *mut u8;
+
+    // SAFETY: The two functions access the data union as described above.
+    let unwound =
+        unsafe { core::intrinsics::catch_unwind(do_call::<F, R>, data_ptr, do_catch::<F, R>) };
+
+    // SAFETY: The union contains the result if the closure returns normally,
+    // or the payload otherwise.
+    unsafe {
+        if unwound == 0 {
+            Ok(ManuallyDrop::into_inner(data.r))
+        } else {
+            Err(ManuallyDrop::into_inner(data.p))
+        }
+    }
+}
@@ -545,6 +545,21 @@ impl FpuState {
     pub fn clear(&self) {
         self.is_valid.store(false, Relaxed);
     }
+
+    /// Returns the bytes of the legacy FPU state (i.e., the area saved by `FXSAVE`).
+    ///
+    /// The bytes reflect the FPU state that is saved most recently.
+    pub fn as_fxsave_bytes(&self) -> &[u8] {
+        let fxsave_area = &self.state_area.fxsave_area;
+        // SAFETY: `FxSaveArea` is a `repr(C)` structure consisting of integers without
+        // padding bytes, so it is valid to view it as bytes.
+        unsafe {
+            core::slice::from_raw_parts(
+                fxsave_area as *const FxSaveArea as *const u8,
+                size_of::<FxSaveArea>(),
+            )
+        }
+    }
 }
 
 impl Clone for FpuState {
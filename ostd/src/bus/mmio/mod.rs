@@ -24,7 +24,7 @@ pub static MMIO_BUS: SpinLock<MmioBus> = SpinLock::new(MmioBus::new());
 static IRQS: SpinLock<Vec<IrqLine>> = SpinLock::new(Vec::new());
 
 pub(crate) fn init() {
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
     {
         crate::arch::if_tdx_enabled!({
             // SAFETY:
@@ -41,7 +41,7 @@ pub(crate) fn init() {
     }
 }
 
-#[cfg(target_arch = "x86_64")]
+#[cfg(all(target_arch = "x86_64", not(miri)))]
 fn iter_range(range: Range<usize>) {
     debug!("[Virtio]: Iter MMIO range:{:x?}", range);
     let mut current = range.end;
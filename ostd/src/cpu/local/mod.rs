@@ -45,11 +45,22 @@ use super::CpuId;
 use crate::mm::{frame::allocator, paddr_to_vaddr, Paddr, PAGE_SIZE};
 
 // These symbols are provided by the linker script.
+#[cfg(not(miri))]
 extern "C" {
     fn __cpu_local_start();
     fn __cpu_local_end();
 }
 
+// There is no `.cpu_local` section under Miri. The CPU-local objects of the
+// only CPU are the statics themselves, so the section spans the whole address
+// space (see `crate::arch::cpu::local::get_base`).
+#[cfg(miri)]
+#[expect(non_upper_case_globals)]
+const __cpu_local_start: usize = 0;
+#[cfg(miri)]
+#[expect(non_upper_case_globals)]
+const __cpu_local_end: usize = usize::MAX;
+
 /// The CPU-local areas for APs.
 static CPU_LOCAL_STORAGES: Once<&'static [Paddr]> = Once::new();
 
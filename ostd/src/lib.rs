@@ -25,12 +25,15 @@
 
 extern crate alloc;
 
-#[cfg(target_arch = "x86_64")]
+#[cfg(all(target_arch = "x86_64", not(miri)))]
 #[path = "arch/x86/mod.rs"]
 pub mod arch;
-#[cfg(target_arch = "riscv64")]
+#[cfg(all(target_arch = "riscv64", not(miri)))]
 #[path = "arch/riscv/mod.rs"]
 pub mod arch;
+#[cfg(miri)]
+#[path = "arch/hosted/mod.rs"]
+pub mod arch;
 pub mod boot;
 pub mod bus;
 pub mod console;
@@ -128,6 +131,8 @@ unsafe fn init() {
 
     arch::irq::enable_local();
 
+    // Miri knows nothing about the linker-defined `.init_array` boundaries.
+    #[cfg(not(miri))]
     invoke_ffi_init_funcs();
 
     IN_BOOTSTRAP_CONTEXT.store(false, Ordering::Relaxed);
@@ -139,6 +144,7 @@ pub(crate) static IN_BOOTSTRAP_CONTEXT: AtomicBool = AtomicBool::new(true);
 /// Invoke the initialization functions defined in the FFI.
 /// The component system uses this function to call the initialization functions of
 /// the components.
+#[cfg(not(miri))]
 fn invoke_ffi_init_funcs() {
     extern "C" {
         fn __sinit_array();
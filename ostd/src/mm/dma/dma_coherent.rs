@@ -20,7 +20,7 @@ use crate::{
 };
 
 cfg_if! {
-    if #[cfg(all(target_arch = "x86_64", feature = "cvm_guest"))] {
+    if #[cfg(all(target_arch = "x86_64", feature = "cvm_guest", not(miri)))] {
         use crate::arch::tdx_guest;
     }
 }
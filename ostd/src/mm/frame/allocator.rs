@@ -175,12 +175,21 @@ pub trait GlobalFrameAllocator: Sync {
     fn add_free_memory(&self, addr: Paddr, size: usize);
 }
 
+#[cfg(not(miri))]
 extern "Rust" {
     /// The global frame allocator's reference exported by
     /// [`crate::global_frame_allocator`].
     static __GLOBAL_FRAME_ALLOCATOR_REF: &'static dyn GlobalFrameAllocator;
 }
 
+// Miri cannot resolve extern statics, so the base crate generated by
+// `cargo osdk miri` exports a getter function instead.
+#[cfg(miri)]
+extern "Rust" {
+    fn __global_frame_allocator_ref() -> &'static dyn GlobalFrameAllocator;
+}
+
+#[cfg(not(miri))]
 pub(super) fn get_global_frame_allocator() -> &'static dyn GlobalFrameAllocator {
     // SAFETY: The global frame allocator is set up correctly with the
     // `global_frame_allocator` attribute. If they use safe code only, the
@@ -188,6 +197,13 @@ pub(super) fn get_global_frame_allocator() -> &'static dyn GlobalFrameAllocator
     unsafe { __GLOBAL_FRAME_ALLOCATOR_REF }
 }
 
+#[cfg(miri)]
+pub(super) fn get_global_frame_allocator() -> &'static dyn GlobalFrameAllocator {
+    // SAFETY: The getter is defined by the base crate generated by OSDK,
+    // which only returns a reference to a static allocator.
+    unsafe { __global_frame_allocator_ref() }
+}
+
 /// Initializes the global frame allocator.
 ///
 /// It just does adds the frames to the global frame allocator. Calling it
@@ -19,20 +19,37 @@ pub(crate) mod mapping {
     //! in [`FRAME_METADATA_RANGE`].
 
     use core::mem::size_of;
+    #[cfg(miri)]
+    use core::sync::atomic::{AtomicUsize, Ordering};
 
     use super::MetaSlot;
     use crate::mm::{kspace::FRAME_METADATA_RANGE, Paddr, PagingConstsTrait, Vaddr, PAGE_SIZE};
 
-    /// Converts a physical address of a base frame to the virtual address of the metadata slot.
-    pub(crate) const fn frame_to_meta<C: PagingConstsTrait>(paddr: Paddr) -> Vaddr {
+    /// The address where the metadata slots are accessed under Miri.
+    ///
+    /// Miri cannot access memory through page tables, so the metadata slots
+    /// are accessed through the linear mapping of the metadata frames instead.
+    #[cfg(miri)]
+    pub(super) static META_SLOTS_BASE: AtomicUsize = AtomicUsize::new(0);
+
+    fn meta_slots_base() -> Vaddr {
+        #[cfg(miri)]
+        let base = META_SLOTS_BASE.load(Ordering::Relaxed);
+        #[cfg(not(miri))]
         let base = FRAME_METADATA_RANGE.start;
+        base
+    }
+
+    /// Converts a physical address of a base frame to the virtual address of the metadata slot.
+    pub(crate) fn frame_to_meta<C: PagingConstsTrait>(paddr: Paddr) -> Vaddr {
+        let base = meta_slots_base();
         let offset = paddr / PAGE_SIZE;
         base + offset * size_of::<MetaSlot>()
     }
 
     /// Converts a virtual address of the metadata slot to the physical address of the frame.
-    pub(crate) const fn meta_to_frame<C: PagingConstsTrait>(vaddr: Vaddr) -> Paddr {
-        let base = FRAME_METADATA_RANGE.start;
+    pub(crate) fn meta_to_frame<C: PagingConstsTrait>(vaddr: Vaddr) -> Paddr {
+        let base = meta_slots_base();
         let offset = (vaddr - base) / size_of::<MetaSlot>();
         offset * PAGE_SIZE
     }
@@ -57,7 +74,7 @@ use crate::{
     const_assert,
     mm::{
         frame::allocator::{self, EarlyAllocatedFrameMeta},
-        kspace::LINEAR_MAPPING_BASE_VADDR,
+        kspace::{FRAME_METADATA_RANGE, LINEAR_MAPPING_BASE_VADDR},
         paddr_to_vaddr, page_size,
         page_table::boot_pt,
         CachePolicy, Infallible, Paddr, PageFlags, PageProperty, PrivilegedPageFlags, Segment,
@@ -469,12 +486,14 @@ pub(crate) unsafe fn init() -> Segment<MetaPageMeta> {
 
     let tot_nr_frames = max_paddr / page_size::<PagingConsts>(1);
     let (nr_meta_pages, meta_pages) = alloc_meta_frames(tot_nr_frames);
+    #[cfg(miri)]
+    mapping::META_SLOTS_BASE.store(paddr_to_vaddr(meta_pages), Ordering::Relaxed);
 
     // Map the metadata frames.
     boot_pt::with_borrow(|boot_pt| {
         for i in 0..nr_meta_pages {
             let frame_paddr = meta_pages + i * PAGE_SIZE;
-            let vaddr = mapping::frame_to_meta::<PagingConsts>(0) + i * PAGE_SIZE;
+            let vaddr = FRAME_METADATA_RANGE.start + i * PAGE_SIZE;
             let prop = PageProperty {
                 flags: PageFlags::RW,
                 cache: CachePolicy::Writeback,
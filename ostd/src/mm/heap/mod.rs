@@ -90,9 +90,13 @@ fn handle_alloc_error(layout: core::alloc::Layout) -> ! {
     abort_with_message!("Heap allocation error, layout = {:#x?}", layout);
 }
 
+// Under Miri, the heap is served by the interpreter instead, so that each
+// heap object can be tracked by Miri. See `crate::arch::mm`.
+#[cfg(not(miri))]
 #[global_allocator]
 static HEAP_ALLOCATOR: AllocDispatch = AllocDispatch;
 
+#[cfg_attr(miri, expect(dead_code))]
 struct AllocDispatch;
 
 // TODO: Somehow restrict unwinding in the user-provided global allocator.
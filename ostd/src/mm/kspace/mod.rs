@@ -106,7 +106,12 @@ pub const LINEAR_MAPPING_VADDR_RANGE: Range<Vaddr> = LINEAR_MAPPING_BASE_VADDR..
 /// Convert physical address to virtual address using offset, only available inside `ostd`
 pub fn paddr_to_vaddr(pa: Paddr) -> usize {
     debug_assert!(pa < VMALLOC_BASE_VADDR - LINEAR_MAPPING_BASE_VADDR);
-    pa + LINEAR_MAPPING_BASE_VADDR
+    // Miri cannot access memory through page tables, so the emulated physical
+    // memory is accessed directly instead.
+    #[cfg(miri)]
+    return pa + crate::arch::mm::phys_mem_base();
+    #[cfg(not(miri))]
+    return pa + LINEAR_MAPPING_BASE_VADDR;
 }
 
 /// Returns whether the given address should be mapped as tracked.
@@ -163,7 +168,7 @@ pub fn init_kernel_page_table(meta_pages: Segment<MetaPageMeta>) {
 
     // Map the metadata pages.
     {
-        let start_va = mapping::frame_to_meta::<PagingConsts>(0);
+        let start_va = FRAME_METADATA_RANGE.start;
         let from = start_va..start_va + meta_pages.size();
         let prop = PageProperty {
             flags: PageFlags::RW,
@@ -101,7 +101,10 @@ fn manual_paddr_to_vaddr() {
     let pa = 0x1000;
     let va = paddr_to_vaddr(pa);
 
+    #[cfg(not(miri))]
     assert_eq!(va, LINEAR_MAPPING_BASE_VADDR + pa);
+    #[cfg(miri)]
+    assert_eq!(va, crate::arch::mm::phys_mem_base() + pa);
 }
 
 #[ktest]
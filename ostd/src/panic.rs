@@ -2,20 +2,27 @@
 
 //! Panic support.
 
+#[cfg(not(miri))]
 use core::ffi::c_void;
 
+#[cfg(not(miri))]
 pub use unwinding::panic::{begin_panic, catch_unwind};
 
+#[cfg(miri)]
+pub use crate::arch::unwind::{begin_panic, catch_unwind};
 use crate::{
     arch::qemu::{exit_qemu, QemuExitCode},
-    early_print, early_println,
-    sync::SpinLock,
+    early_println,
 };
+#[cfg(not(miri))]
+use crate::{early_print, sync::SpinLock};
 
 extern crate cfg_if;
 extern crate gimli;
 
+#[cfg(not(miri))]
 use gimli::Register;
+#[cfg(not(miri))]
 use unwinding::abi::{
     UnwindContext, UnwindReasonCode, _Unwind_Backtrace, _Unwind_FindEnclosingFunction,
     _Unwind_GetGR, _Unwind_GetIP,
@@ -55,6 +62,7 @@ pub fn abort() -> ! {
 /// Prints the stack trace of the current thread to the console.
 ///
 /// The printing procedure is protected by a spin lock to prevent interleaving.
+#[cfg(not(miri))]
 pub fn print_stack_trace() {
     /// We acquire a global lock to prevent the frames in the stack trace from
     /// interleaving. The spin lock is used merely for its simplicity.
@@ -106,3 +114,12 @@ pub fn print_stack_trace() {
     let mut data = CallbackData { counter: 0 };
     _Unwind_Backtrace(callback, &mut data as *mut _ as _);
 }
+
+/// Prints the stack trace of the current thread to the console.
+///
+/// The DWARF unwinder cannot run in Miri, so the stack trace is printed by
+/// Miri itself when the interpreted program aborts.
+#[cfg(miri)]
+pub fn print_stack_trace() {
+    early_println!("Printing stack trace: not available in Miri");
+}
@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../common/test.h"
+#include <fcntl.h>
+#include <linux/fs.h>
+#include <stdint.h>
+#include <sys/ioctl.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <termios.h>
+#include <unistd.h>
+
+// The ext2 disk is attached by QEMU with `serial=vext2`.
+#define EXT2_DISK "/dev/disk/by-id/virtio-vext2"
+
+// The ext2 superblock starts at offset 1024, and its magic number is at offset 56.
+#define EXT2_MAGIC_OFFSET (1024 + 56)
+#define EXT2_MAGIC 0xEF53
+
+// The second sector is in the ext2 boot block, which is not used by the filesystem.
+#define UNUSED_OFFSET (512 + 100)
+
+static int fd_ro;
+static int fd_rw;
+
+FN_SETUP(open)
+{
+	fd_ro = CHECK(open(EXT2_DISK, O_RDONLY));
+	fd_rw = CHECK(open(EXT2_DISK, O_RDWR));
+}
+END_SETUP()
+
+FN_TEST(stat)
+{
+	struct stat st;
+
+	TEST_RES(stat(EXT2_DISK, &st), S_ISBLK(st.st_mode));
+	TEST_RES(lstat(EXT2_DISK, &st), S_ISLNK(st.st_mode));
+}
+END_TEST()
+
+FN_TEST(ioctl)
+{
+	uint64_t size;
+	unsigned long nr_sectors;
+	int sector_size;
+
+	TEST_RES(ioctl(fd_ro, BLKGETSIZE64, &size), size > 0);
+	TEST_RES(ioctl(fd_ro, BLKGETSIZE, &nr_sectors),
+		 nr_sectors * 512 == size);
+	TEST_RES(ioctl(fd_ro, BLKSSZGET, &sector_size), sector_size == 512);
+	TEST_SUCC(ioctl(fd_rw, BLKFLSBUF, 0));
+
+	TEST_ERRNO(ioctl(fd_ro, TCGETS, &(struct termios){}), ENOTTY);
+
+	TEST_RES(lseek(fd_ro, 0, SEEK_END), (uint64_t)_ret == size);
+	TEST_RES(lseek(fd_ro, 0, SEEK_SET), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(read)
+{
+	uint16_t magic;
+	char buf[1024];
+	char buf2[1024];
+	uint64_t size;
+
+	TEST_RES(pread(fd_ro, &magic, sizeof(magic), EXT2_MAGIC_OFFSET),
+		 _ret == sizeof(magic) && magic == EXT2_MAGIC);
+
+	TEST_RES(lseek(fd_ro, 1000, SEEK_SET), _ret == 1000);
+	TEST_RES(read(fd_ro, buf, sizeof(buf)), _ret == sizeof(buf));
+	TEST_RES(lseek(fd_ro, 0, SEEK_CUR), _ret == 1000 + (long)sizeof(buf));
+	TEST_RES(pread(fd_ro, buf2, sizeof(buf2), 1000),
+		 _ret == sizeof(buf2) && memcmp(buf, buf2, sizeof(buf)) == 0);
+	TEST_RES(memcmp(&buf[EXT2_MAGIC_OFFSET - 1000], &magic, sizeof(magic)),
+		 _ret == 0);
+
+	TEST_SUCC(ioctl(fd_ro, BLKGETSIZE64, &size));
+	TEST_RES(pread(fd_ro, buf, sizeof(buf), size - 10), _ret == 10);
+	TEST_RES(pread(fd_ro, buf, sizeof(buf), size), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(write)
+{
+	char old[16];
+	char buf[16];
+	uint64_t size;
+
+	TEST_ERRNO(pwrite(fd_ro, "hello", 5, UNUSED_OFFSET), EBADF);
+
+	TEST_RES(pread(fd_rw, old, sizeof(old), UNUSED_OFFSET),
+		 _ret == sizeof(old));
+
+	TEST_RES(pwrite(fd_rw, "hello", 5, UNUSED_OFFSET), _ret == 5);
+	TEST_RES(pread(fd_ro, buf, sizeof(buf), UNUSED_OFFSET),
+		 _ret == sizeof(buf) && memcmp(buf, "hello", 5) == 0 &&
+			 memcmp(buf + 5, old + 5, sizeof(buf) - 5) == 0);
+
+	TEST_RES(pwrite(fd_rw, old, sizeof(old), UNUSED_OFFSET),
+		 _ret == sizeof(old));
+	TEST_RES(pread(fd_ro, buf, sizeof(buf), UNUSED_OFFSET),
+		 _ret == sizeof(buf) && memcmp(buf, old, sizeof(buf)) == 0);
+
+	TEST_SUCC(ioctl(fd_ro, BLKGETSIZE64, &size));
+	TEST_ERRNO(pwrite(fd_rw, "hello", 5, size), ENOSPC);
+}
+END_TEST()
+
+FN_TEST(mount)
+{
+	TEST_ERRNO(mount("/dev/null", "/tmp", "ext2", 0, ""), ENOTBLK);
+	TEST_ERRNO(mount("/dev/nonexistent", "/tmp", "ext2", 0, ""), ENOENT);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fd_ro));
+	CHECK(close(fd_rw));
+}
+END_SETUP()
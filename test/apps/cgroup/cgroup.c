@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define CGROUP_ROOT "/tmp/cgroup"
+#define CGROUP_TEST CGROUP_ROOT "/test"
+
+static int read_string(const char *path, char *buf, size_t len)
+{
+	int fd, ret;
+
+	memset(buf, 0, len);
+	fd = CHECK(open(path, O_RDONLY));
+	ret = CHECK(read(fd, buf, len - 1));
+	CHECK(close(fd));
+
+	return ret;
+}
+
+static ssize_t write_string(const char *path, const char *str)
+{
+	ssize_t ret;
+	int fd, saved_errno;
+
+	fd = CHECK(open(path, O_WRONLY | O_TRUNC));
+	ret = write(fd, str, strlen(str));
+	saved_errno = errno;
+	CHECK(close(fd));
+	errno = saved_errno;
+
+	return ret;
+}
+
+static ssize_t write_pid(const char *path, pid_t pid)
+{
+	char buf[16];
+
+	snprintf(buf, sizeof(buf), "%d\n", pid);
+	return write_string(path, buf);
+}
+
+static char buf[256];
+
+FN_SETUP(mount)
+{
+	CHECK(mkdir(CGROUP_ROOT, 0755));
+	CHECK(mount("none", CGROUP_ROOT, "cgroup2", 0, NULL));
+}
+END_SETUP()
+
+FN_TEST(root_cgroup)
+{
+	TEST_RES(read_string("/proc/self/cgroup", buf, sizeof(buf)),
+		 strcmp(buf, "0::/\n") == 0);
+	TEST_RES(read_string(CGROUP_ROOT "/cgroup.controllers", buf,
+			     sizeof(buf)),
+		 strcmp(buf, "cpu memory pids\n") == 0);
+	TEST_ERRNO(access(CGROUP_ROOT "/cgroup.events", F_OK), ENOENT);
+	TEST_ERRNO(access(CGROUP_ROOT "/pids.max", F_OK), ENOENT);
+}
+END_TEST()
+
+FN_TEST(create_cgroup)
+{
+	TEST_SUCC(mkdir(CGROUP_TEST, 0755));
+	TEST_ERRNO(mkdir(CGROUP_TEST, 0755), EEXIST);
+	TEST_ERRNO(access(CGROUP_TEST "/pids.max", F_OK), ENOENT);
+
+	TEST_RES(write_string(CGROUP_ROOT "/cgroup.subtree_control",
+			      "+cpu +memory +pids"),
+		 _ret == 18);
+	TEST_RES(read_string(CGROUP_TEST "/cgroup.controllers", buf,
+			     sizeof(buf)),
+		 strcmp(buf, "cpu memory pids\n") == 0);
+	TEST_RES(read_string(CGROUP_TEST "/pids.max", buf, sizeof(buf)),
+		 strcmp(buf, "max\n") == 0);
+	TEST_RES(read_string(CGROUP_TEST "/cpu.weight", buf, sizeof(buf)),
+		 strcmp(buf, "100\n") == 0);
+	TEST_RES(read_string(CGROUP_TEST "/cgroup.events", buf, sizeof(buf)),
+		 strcmp(buf, "populated 0\n") == 0);
+
+	TEST_ERRNO(write_string(CGROUP_TEST "/cgroup.subtree_control",
+				"+foo"),
+		   EINVAL);
+	TEST_ERRNO(write_string(CGROUP_TEST "/cpu.weight", "0"), ERANGE);
+	TEST_RES(write_string(CGROUP_TEST "/cpu.max", "50000 100000"),
+		 _ret == 12);
+	TEST_RES(read_string(CGROUP_TEST "/cpu.max", buf, sizeof(buf)),
+		 strcmp(buf, "50000 100000\n") == 0);
+	TEST_RES(write_string(CGROUP_TEST "/memory.max", "64M"), _ret == 3);
+	TEST_RES(read_string(CGROUP_TEST "/memory.max", buf, sizeof(buf)),
+		 strcmp(buf, "67108864\n") == 0);
+}
+END_TEST()
+
+FN_TEST(move_process)
+{
+	TEST_RES(write_pid(CGROUP_TEST "/cgroup.procs", getpid()), _ret > 0);
+	TEST_RES(read_string("/proc/self/cgroup", buf, sizeof(buf)),
+		 strcmp(buf, "0::/test\n") == 0);
+	TEST_RES(read_string(CGROUP_TEST "/cgroup.events", buf, sizeof(buf)),
+		 strcmp(buf, "populated 1\n") == 0);
+	TEST_RES(read_string(CGROUP_TEST "/pids.current", buf, sizeof(buf)),
+		 strcmp(buf, "1\n") == 0);
+
+	// A cgroup with processes cannot be removed.
+	TEST_ERRNO(rmdir(CGROUP_TEST), EBUSY);
+}
+END_TEST()
+
+FN_TEST(pids_limit)
+{
+	int status;
+	pid_t pid;
+
+	TEST_RES(write_string(CGROUP_TEST "/pids.max", "1"), _ret == 1);
+	TEST_ERRNO(fork(), EAGAIN);
+
+	TEST_RES(write_string(CGROUP_TEST "/pids.max", "max"), _ret == 3);
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		char child_buf[64];
+
+		read_string("/proc/self/cgroup", child_buf, sizeof(child_buf));
+		_exit(strcmp(child_buf, "0::/test\n") == 0 ? 0 : 1);
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	TEST_RES(read_string(CGROUP_TEST "/pids.current", buf, sizeof(buf)),
+		 strcmp(buf, "1\n") == 0);
+}
+END_TEST()
+
+#define OOM_BUF_SIZE (4 << 20)
+
+FN_TEST(memory_limit_in_syscall)
+{
+	int status;
+	pid_t pid;
+
+	TEST_RES(write_string(CGROUP_TEST "/memory.max", "1M"), _ret == 2);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		char *oom_buf;
+		int fd;
+
+		// Make sure that the child is chosen by the OOM killer.
+		if (write_string("/proc/self/oom_score_adj", "1000") != 4)
+			_exit(1);
+
+		oom_buf = mmap(NULL, OOM_BUF_SIZE, PROT_READ | PROT_WRITE,
+			       MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+		fd = open("/dev/zero", O_RDONLY);
+		if (oom_buf == MAP_FAILED || fd < 0)
+			_exit(1);
+
+		// The buffer is faulted in by the kernel, which runs out of
+		// memory. The OOM killer runs after the system call returns.
+		read(fd, oom_buf, OOM_BUF_SIZE);
+		_exit(0);
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+
+	TEST_RES(write_string(CGROUP_TEST "/memory.max", "max"), _ret == 3);
+}
+END_TEST()
+
+FN_TEST(remove_cgroup)
+{
+	TEST_RES(write_string(CGROUP_ROOT "/cgroup.procs", "0"), _ret == 1);
+	TEST_RES(read_string("/proc/self/cgroup", buf, sizeof(buf)),
+		 strcmp(buf, "0::/\n") == 0);
+	TEST_RES(read_string(CGROUP_TEST "/pids.current", buf, sizeof(buf)),
+		 strcmp(buf, "0\n") == 0);
+
+	TEST_ERRNO(rmdir(CGROUP_TEST "/cgroup.procs"), ENOTDIR);
+	TEST_SUCC(rmdir(CGROUP_TEST));
+	TEST_ERRNO(access(CGROUP_TEST, F_OK), ENOENT);
+	TEST_RES(write_string(CGROUP_ROOT "/cgroup.subtree_control",
+			      "-cpu -memory -pids"),
+		 _ret == 18);
+}
+END_TEST()
+
+FN_SETUP(umount)
+{
+	CHECK(umount(CGROUP_ROOT));
+	CHECK(rmdir(CGROUP_ROOT));
+}
+END_SETUP()
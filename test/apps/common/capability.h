@@ -0,0 +1,29 @@
+/* SPDX-License-Identifier: MPL-2.0 */
+
+/*
+ * Utilities for the tests that deal with capabilities.
+ *
+ * This header uses CHECK(), so it must be included after "test.h".
+ */
+
+#include <linux/capability.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+/**
+ * Removes a capability from the effective set of the current thread.
+ *
+ * The capability stays in the permitted set, so it can be raised again.
+ */
+static inline void drop_effective_cap(int cap)
+{
+	struct __user_cap_header_struct header = {
+		.version = _LINUX_CAPABILITY_VERSION_3,
+		.pid = 0,
+	};
+	struct __user_cap_data_struct data[2];
+
+	CHECK(syscall(SYS_capget, &header, data));
+	data[CAP_TO_INDEX(cap)].effective &= ~CAP_TO_MASK(cap);
+	CHECK(syscall(SYS_capset, &header, data));
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <elf.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/prctl.h>
+#include <sys/resource.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define CORE_PATTERN_PATH "/proc/sys/kernel/core_pattern"
+#define CORE_DIR "/tmp/coredump"
+#define MARKER_LEN 64
+
+// The pattern is not a macro, since `%` cannot appear in the arguments of the
+// test macros, which are used as format strings.
+static const char *core_pattern = CORE_DIR "/core.%p\n";
+
+static char old_pattern[256];
+static char core_path[64];
+
+static void fill_marker(char *buf)
+{
+	int i;
+
+	// The marker is built at runtime, so that it can only be found in the
+	// dumped memory instead of the program file.
+	for (i = 0; i < MARKER_LEN; ++i)
+		buf[i] = 'a' + (i * 7) % 26;
+}
+
+static int write_core_pattern(const char *pattern)
+{
+	int fd, ret;
+
+	fd = open(CORE_PATTERN_PATH, O_WRONLY | O_TRUNC);
+	if (fd < 0)
+		return -1;
+	ret = write(fd, pattern, strlen(pattern));
+	close(fd);
+
+	return ret;
+}
+
+static int read_core_pattern(char *buf, size_t len)
+{
+	int fd, ret;
+
+	memset(buf, 0, len);
+	fd = open(CORE_PATTERN_PATH, O_RDONLY);
+	if (fd < 0)
+		return -1;
+	ret = read(fd, buf, len - 1);
+	close(fd);
+
+	return ret;
+}
+
+// Forks a child that is killed by `SIGQUIT` with the core file size limit
+// set to `core_limit`, and returns the wait status of the child.
+static int crash_child(rlim_t core_limit, int dumpable)
+{
+	int status;
+	pid_t pid;
+
+	pid = fork();
+	if (pid < 0)
+		return -1;
+
+	if (pid == 0) {
+		struct rlimit rlimit = { core_limit, core_limit };
+		char *marker;
+
+		if (setrlimit(RLIMIT_CORE, &rlimit) < 0)
+			_exit(1);
+		if (prctl(PR_SET_DUMPABLE, dumpable, 0, 0, 0) < 0)
+			_exit(2);
+
+		marker = malloc(MARKER_LEN);
+		if (marker == NULL)
+			_exit(3);
+		fill_marker(marker);
+
+		kill(getpid(), SIGQUIT);
+		_exit(4);
+	}
+
+	snprintf(core_path, sizeof(core_path), CORE_DIR "/core.%d", pid);
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	return status;
+}
+
+// Checks that the core file is a valid ELF core file with the marker in one
+// of its `PT_LOAD` segments.
+static int check_core_file(const char *path)
+{
+	char marker[MARKER_LEN];
+	Elf64_Ehdr ehdr;
+	Elf64_Phdr phdr;
+	int fd, i, found = 0;
+	char *buf;
+
+	fill_marker(marker);
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	if (pread(fd, &ehdr, sizeof(ehdr), 0) != sizeof(ehdr) ||
+	    memcmp(ehdr.e_ident, ELFMAG, SELFMAG) != 0 ||
+	    ehdr.e_type != ET_CORE || ehdr.e_phnum < 2)
+		goto err;
+
+	for (i = 0; i < ehdr.e_phnum; ++i) {
+		if (pread(fd, &phdr, sizeof(phdr),
+			  ehdr.e_phoff + i * sizeof(phdr)) != sizeof(phdr))
+			goto err;
+		// The notes come first.
+		if ((i == 0) != (phdr.p_type == PT_NOTE))
+			goto err;
+		if (phdr.p_type != PT_LOAD || phdr.p_filesz == 0)
+			continue;
+
+		buf = malloc(phdr.p_filesz);
+		if (buf == NULL)
+			goto err;
+		if (pread(fd, buf, phdr.p_filesz, phdr.p_offset) ==
+			    (ssize_t)phdr.p_filesz &&
+		    memmem(buf, phdr.p_filesz, marker, MARKER_LEN) != NULL)
+			found = 1;
+		free(buf);
+	}
+
+	close(fd);
+	return found ? 0 : -1;
+
+err:
+	close(fd);
+	return -1;
+}
+
+FN_SETUP(core_pattern)
+{
+	CHECK(mkdir(CORE_DIR, 0755));
+	CHECK(read_core_pattern(old_pattern, sizeof(old_pattern)));
+	CHECK(write_core_pattern(core_pattern));
+}
+END_SETUP()
+
+FN_TEST(dumpable)
+{
+	TEST_RES(prctl(PR_GET_DUMPABLE, 0, 0, 0, 0), _ret == 1);
+	TEST_ERRNO(prctl(PR_SET_DUMPABLE, 2, 0, 0, 0), EINVAL);
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 0, 0, 0, 0));
+	TEST_RES(prctl(PR_GET_DUMPABLE, 0, 0, 0, 0), _ret == 0);
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 1, 0, 0, 0));
+	TEST_RES(prctl(PR_GET_DUMPABLE, 0, 0, 0, 0), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(read_core_pattern)
+{
+	char buf[256];
+
+	TEST_RES(read_core_pattern(buf, sizeof(buf)),
+		 strcmp(buf, core_pattern) == 0);
+}
+END_TEST()
+
+FN_TEST(dump_core)
+{
+	TEST_RES(crash_child(RLIM_INFINITY, 1),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGQUIT &&
+			 WCOREDUMP(_ret));
+	TEST_SUCC(check_core_file(core_path));
+	TEST_SUCC(unlink(core_path));
+}
+END_TEST()
+
+FN_TEST(core_limit_too_small)
+{
+	TEST_RES(crash_child(0, 1), WIFSIGNALED(_ret) &&
+					    WTERMSIG(_ret) == SIGQUIT &&
+					    !WCOREDUMP(_ret));
+	TEST_ERRNO(access(core_path, F_OK), ENOENT);
+}
+END_TEST()
+
+FN_TEST(not_dumpable)
+{
+	TEST_RES(crash_child(RLIM_INFINITY, 0), WIFSIGNALED(_ret) &&
+							WTERMSIG(_ret) ==
+								SIGQUIT &&
+							!WCOREDUMP(_ret));
+	TEST_ERRNO(access(core_path, F_OK), ENOENT);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(write_core_pattern(old_pattern));
+	CHECK(rmdir(CORE_DIR));
+}
+END_SETUP()
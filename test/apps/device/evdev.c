@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../common/test.h"
+#include <fcntl.h>
+#include <linux/input.h>
+#include <poll.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <time.h>
+#include <unistd.h>
+
+#define BITS_TO_BYTES(nr_bits) (((nr_bits) + 63) / 64 * 8)
+#define TEST_BIT(bits, nr) (((bits)[(nr) / 8] >> ((nr) & 7)) & 1)
+
+static int fd;
+
+FN_SETUP(open)
+{
+	fd = open("/dev/input/event0", O_RDONLY | O_NONBLOCK);
+	if (fd < 0 && errno == ENOENT) {
+		fprintf(stderr, "no input devices, skipping the tests\n");
+		exit(EXIT_SUCCESS);
+	}
+	CHECK(fd);
+}
+END_SETUP()
+
+FN_TEST(version_and_id)
+{
+	int version;
+	struct input_id id;
+
+	TEST_RES(ioctl(fd, EVIOCGVERSION, &version), version == EV_VERSION);
+	TEST_RES(ioctl(fd, EVIOCGID, &id), id.bustype == BUS_VIRTUAL);
+}
+END_TEST()
+
+FN_TEST(name)
+{
+	char name[256];
+	char short_name[4];
+
+	memset(name, 'x', sizeof(name));
+	TEST_RES(ioctl(fd, EVIOCGNAME(sizeof(name)), name),
+		 _ret > 1 && _ret == (long)strlen(name) + 1);
+
+	memset(short_name, 'x', sizeof(short_name));
+	TEST_RES(ioctl(fd, EVIOCGNAME(2), short_name),
+		 _ret == 2 && short_name[0] == name[0] &&
+			 short_name[1] == name[1] && short_name[2] == 'x');
+
+	TEST_ERRNO(ioctl(fd, EVIOCGPHYS(sizeof(name)), name), ENOENT);
+}
+END_TEST()
+
+FN_TEST(bits)
+{
+	unsigned char ev_bits[BITS_TO_BYTES(EV_CNT)];
+	unsigned char key_bits[BITS_TO_BYTES(KEY_CNT)];
+	unsigned char rel_bits[BITS_TO_BYTES(REL_CNT)];
+	unsigned char key_state[BITS_TO_BYTES(KEY_CNT)];
+
+	memset(ev_bits, 0, sizeof(ev_bits));
+	TEST_RES(ioctl(fd, EVIOCGBIT(0, sizeof(ev_bits)), ev_bits),
+		 _ret == sizeof(ev_bits) && TEST_BIT(ev_bits, EV_SYN) &&
+			 TEST_BIT(ev_bits, EV_KEY) &&
+			 !TEST_BIT(ev_bits, EV_REL));
+
+	memset(key_bits, 0, sizeof(key_bits));
+	TEST_RES(ioctl(fd, EVIOCGBIT(EV_KEY, sizeof(key_bits)), key_bits),
+		 _ret == sizeof(key_bits) && TEST_BIT(key_bits, KEY_A) &&
+			 TEST_BIT(key_bits, KEY_ESC) &&
+			 !TEST_BIT(key_bits, KEY_MAX));
+
+	memset(rel_bits, 0xff, sizeof(rel_bits));
+	TEST_RES(ioctl(fd, EVIOCGBIT(EV_REL, sizeof(rel_bits)), rel_bits),
+		 _ret == sizeof(rel_bits) && rel_bits[0] == 0);
+
+	TEST_ERRNO(ioctl(fd, EVIOCGBIT(EV_MAX, sizeof(rel_bits)), rel_bits),
+		   EINVAL);
+
+	// No keys are pressed during the tests.
+	memset(key_state, 0xff, sizeof(key_state));
+	TEST_RES(ioctl(fd, EVIOCGKEY(sizeof(key_state)), key_state),
+		 _ret == sizeof(key_state) && key_state[0] == 0 &&
+			 key_state[sizeof(key_state) - 1] == 0);
+}
+END_TEST()
+
+FN_TEST(grab)
+{
+	int fd2;
+
+	fd2 = TEST_SUCC(open("/dev/input/event0", O_RDONLY));
+
+	TEST_SUCC(ioctl(fd, EVIOCGRAB, 1));
+	TEST_ERRNO(ioctl(fd, EVIOCGRAB, 1), EBUSY);
+	TEST_ERRNO(ioctl(fd2, EVIOCGRAB, 1), EBUSY);
+	TEST_ERRNO(ioctl(fd2, EVIOCGRAB, 0), EINVAL);
+	TEST_SUCC(ioctl(fd, EVIOCGRAB, 0));
+	TEST_ERRNO(ioctl(fd, EVIOCGRAB, 0), EINVAL);
+
+	// Closing the file releases the grab.
+	TEST_SUCC(ioctl(fd2, EVIOCGRAB, 1));
+	TEST_ERRNO(ioctl(fd, EVIOCGRAB, 1), EBUSY);
+	TEST_SUCC(close(fd2));
+	TEST_SUCC(ioctl(fd, EVIOCGRAB, 1));
+	TEST_SUCC(ioctl(fd, EVIOCGRAB, 0));
+}
+END_TEST()
+
+FN_TEST(clock_id)
+{
+	int clock_id;
+
+	clock_id = CLOCK_MONOTONIC;
+	TEST_SUCC(ioctl(fd, EVIOCSCLOCKID, &clock_id));
+	clock_id = CLOCK_REALTIME;
+	TEST_SUCC(ioctl(fd, EVIOCSCLOCKID, &clock_id));
+	clock_id = CLOCK_PROCESS_CPUTIME_ID;
+	TEST_ERRNO(ioctl(fd, EVIOCSCLOCKID, &clock_id), EINVAL);
+}
+END_TEST()
+
+FN_TEST(read_and_poll)
+{
+	struct input_event events[4];
+	struct pollfd pfd = { .fd = fd, .events = POLLIN };
+
+	TEST_ERRNO(read(fd, events, sizeof(events[0]) - 1), EINVAL);
+	TEST_ERRNO(read(fd, events, sizeof(events)), EAGAIN);
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0 && pfd.revents == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fd));
+}
+END_SETUP()
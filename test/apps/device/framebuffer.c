@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../common/test.h"
+#include <fcntl.h>
+#include <linux/fb.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <sys/mman.h>
+#include <termios.h>
+#include <unistd.h>
+
+static int fd;
+static struct fb_var_screeninfo var;
+static struct fb_fix_screeninfo fix;
+
+FN_SETUP(open)
+{
+	fd = open("/dev/fb0", O_RDWR);
+	if (fd < 0 && errno == ENOENT) {
+		fprintf(stderr, "no framebuffer, skipping the tests\n");
+		exit(EXIT_SUCCESS);
+	}
+	CHECK(fd);
+
+	CHECK(ioctl(fd, FBIOGET_VSCREENINFO, &var));
+	CHECK(ioctl(fd, FBIOGET_FSCREENINFO, &fix));
+}
+END_SETUP()
+
+FN_TEST(screeninfo)
+{
+	struct fb_var_screeninfo new_var;
+
+	TEST_RES(0, var.xres > 0 && var.yres > 0 &&
+			    var.xres_virtual == var.xres &&
+			    var.yres_virtual == var.yres &&
+			    (var.bits_per_pixel & 7) == 0);
+	TEST_RES(0, fix.type == FB_TYPE_PACKED_PIXELS &&
+			    fix.line_length ==
+				    var.xres * var.bits_per_pixel / 8 &&
+			    fix.smem_len == fix.line_length * var.yres);
+	// The I/O memory is not mapped by `mmap`, so its address is hidden.
+	TEST_RES(0, fix.smem_start == 0);
+
+	new_var = var;
+	TEST_RES(ioctl(fd, FBIOPUT_VSCREENINFO, &new_var),
+		 new_var.xres == var.xres && new_var.yres == var.yres);
+
+	new_var = var;
+	new_var.yres_virtual = var.yres * 2;
+	TEST_ERRNO(ioctl(fd, FBIOPUT_VSCREENINFO, &new_var), EINVAL);
+}
+END_TEST()
+
+FN_TEST(pan_and_blank)
+{
+	struct fb_var_screeninfo new_var;
+
+	new_var = var;
+	TEST_SUCC(ioctl(fd, FBIOPAN_DISPLAY, &new_var));
+	new_var.yoffset = 1;
+	TEST_ERRNO(ioctl(fd, FBIOPAN_DISPLAY, &new_var), EINVAL);
+
+	TEST_SUCC(ioctl(fd, FBIOBLANK, FB_BLANK_POWERDOWN));
+	TEST_SUCC(ioctl(fd, FBIOBLANK, FB_BLANK_NORMAL));
+	TEST_SUCC(ioctl(fd, FBIOBLANK, FB_BLANK_UNBLANK));
+	TEST_ERRNO(ioctl(fd, FBIOBLANK, FB_BLANK_POWERDOWN + 1), EINVAL);
+
+	TEST_ERRNO(ioctl(fd, TCGETS, &(struct termios){}), ENOTTY);
+}
+END_TEST()
+
+FN_TEST(read_write)
+{
+	char buf[4] = { 1, 2, 3, 4 };
+	char rbuf[4];
+
+	TEST_RES(pwrite(fd, buf, sizeof(buf), 100), _ret == sizeof(buf));
+	TEST_RES(pread(fd, rbuf, sizeof(rbuf), 100),
+		 _ret == sizeof(rbuf) && memcmp(buf, rbuf, sizeof(buf)) == 0);
+
+	TEST_RES(pwrite(fd, buf, sizeof(buf), fix.smem_len - 2), _ret == 2);
+	TEST_ERRNO(pwrite(fd, buf, sizeof(buf), fix.smem_len), ENOSPC);
+	TEST_ERRNO(pwrite(fd, buf, sizeof(buf), fix.smem_len + 1), EFBIG);
+	TEST_RES(pread(fd, rbuf, sizeof(rbuf), fix.smem_len), _ret == 0);
+
+	TEST_RES(lseek(fd, 0, SEEK_END), _ret == fix.smem_len);
+}
+END_TEST()
+
+FN_TEST(mmap)
+{
+	unsigned char *addr;
+	unsigned char rbuf[4];
+
+	addr = (unsigned char *)TEST_SUCC((long)mmap(NULL, fix.smem_len,
+						     PROT_READ | PROT_WRITE,
+						     MAP_SHARED, fd, 0));
+	TEST_RES(addr[100], _ret == 1);
+
+	memset(addr, 0xab, 4);
+	TEST_RES(pread(fd, rbuf, sizeof(rbuf), 0),
+		 _ret == sizeof(rbuf) && rbuf[0] == 0xab && rbuf[3] == 0xab);
+	// Panning the display copies the mapped buffer to the framebuffer at once.
+	TEST_SUCC(ioctl(fd, FBIOPAN_DISPLAY, &var));
+
+	TEST_SUCC(munmap(addr, fix.smem_len));
+
+	// The buffer can be mapped again after all its mappings are removed.
+	addr = (unsigned char *)TEST_SUCC((long)mmap(NULL, fix.smem_len,
+						     PROT_READ | PROT_WRITE,
+						     MAP_SHARED, fd, 0));
+	TEST_RES(addr[0], _ret == 0xab);
+	memset(addr, 0xcd, 4);
+	TEST_RES(pread(fd, rbuf, sizeof(rbuf), 0),
+		 _ret == sizeof(rbuf) && rbuf[0] == 0xcd && rbuf[3] == 0xcd);
+	TEST_SUCC(munmap(addr, fix.smem_len));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fd));
+}
+END_SETUP()
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../common/test.h"
+#include <fcntl.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <sys/stat.h>
+#include <sys/sysmacros.h>
+#include <unistd.h>
+
+#define NODE "/tmp/mknod_test_node"
+
+static int master;
+static int slave_index;
+
+FN_SETUP(pty)
+{
+	master = CHECK(posix_openpt(O_RDWR | O_NOCTTY));
+	CHECK(grantpt(master));
+	CHECK(unlockpt(master));
+	CHECK(ioctl(master, TIOCGPTN, &slave_index));
+}
+END_SETUP()
+
+FN_TEST(mem_device)
+{
+	int fd;
+	char buf[4] = { 1, 2, 3, 4 };
+
+	TEST_SUCC(mknod(NODE, S_IFCHR | 0666, makedev(1, 5)));
+	fd = TEST_SUCC(open(NODE, O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == (long)sizeof(buf) && buf[0] == 0 && buf[3] == 0);
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(NODE));
+}
+END_TEST()
+
+FN_TEST(pty_slave)
+{
+	struct stat st;
+	int fd;
+	char buf[8];
+
+	TEST_RES(stat(ptsname(master), &st),
+		 major(st.st_rdev) == 136 &&
+			 minor(st.st_rdev) == (unsigned int)slave_index);
+
+	TEST_SUCC(mknod(NODE, S_IFCHR | 0620, st.st_rdev));
+	fd = TEST_SUCC(open(NODE, O_RDWR | O_NOCTTY));
+	TEST_RES(write(master, "hi\n", 3), _ret == 3);
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == 3 && memcmp(buf, "hi\n", 3) == 0);
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(NODE));
+
+	// The slave that does not exist cannot be found.
+	TEST_ERRNO(mknod(NODE, S_IFCHR | 0620, makedev(136, 4000)), EINVAL);
+}
+END_TEST()
+
+FN_TEST(block_device)
+{
+	struct stat st;
+	int fd;
+	char buf[512];
+
+	TEST_SUCC(stat("/dev/vda", &st));
+	TEST_SUCC(mknod(NODE, S_IFBLK | 0600, st.st_rdev));
+	fd = TEST_SUCC(open(NODE, O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)), _ret == (long)sizeof(buf));
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(NODE));
+
+	// Character devices and block devices have separate device numbers.
+	TEST_ERRNO(mknod(NODE, S_IFCHR | 0600, st.st_rdev), EINVAL);
+	TEST_ERRNO(mknod(NODE, S_IFBLK | 0600, makedev(1, 5)), EINVAL);
+}
+END_TEST()
+
+FN_TEST(proc_devices)
+{
+	int fd;
+	char buf[1024] = { 0 };
+
+	fd = TEST_SUCC(open("/proc/devices", O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf) - 1),
+		 _ret > 0 && strstr(buf, "Character devices:\n") == buf &&
+			 strstr(buf, "\n  1 mem\n") != NULL &&
+			 strstr(buf, "\n136 pts\n") != NULL &&
+			 strstr(buf, "\n\nBlock devices:\n") != NULL);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(master));
+}
+END_SETUP()
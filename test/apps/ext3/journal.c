@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../common/test.h"
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+// The Ext3 disk is attached by QEMU with `serial=vext3`. Its journal has a committed
+// transaction that rewrites `a.txt` and `b.txt` (see `test/Makefile`).
+#define EXT3_DISK "/dev/disk/by-id/virtio-vext3"
+#define MNT "/tmp/ext3"
+
+// Each file is created in a journal handle. There are enough of them to fill up the
+// running transaction several times.
+#define NR_FILES 512
+
+static int read_file(const char *path, char *buf, size_t len)
+{
+	int fd;
+	ssize_t nread;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+	nread = read(fd, buf, len - 1);
+	close(fd);
+	if (nread < 0)
+		return -1;
+	buf[nread] = '\0';
+	return 0;
+}
+
+static int write_file(const char *path, const char *content)
+{
+	int fd;
+	ssize_t nwritten;
+
+	fd = open(path, O_WRONLY | O_CREAT | O_TRUNC, 0644);
+	if (fd < 0)
+		return -1;
+	nwritten = write(fd, content, strlen(content));
+	close(fd);
+	return nwritten == (ssize_t)strlen(content) ? 0 : -1;
+}
+
+FN_SETUP(mount)
+{
+	CHECK_WITH(mkdir(MNT, 0755), _ret == 0 || errno == EEXIST);
+	CHECK(mount(EXT3_DISK, MNT, "ext3", 0, ""));
+}
+END_SETUP()
+
+FN_TEST(replayed)
+{
+	char buf[64];
+
+	TEST_RES(read_file(MNT "/a.txt", buf, sizeof(buf)),
+		 strcmp(buf, "new a\n") == 0);
+	TEST_RES(read_file(MNT "/b.txt", buf, sizeof(buf)),
+		 strcmp(buf, "new b\n") == 0);
+}
+END_TEST()
+
+FN_TEST(many_files)
+{
+	char path[64];
+	char content[64];
+	char buf[64];
+	int nr_bad = 0;
+
+	TEST_SUCC(mkdir(MNT "/dir", 0755));
+	for (int i = 0; i < NR_FILES; i++) {
+		snprintf(path, sizeof(path), MNT "/dir/%d", i);
+		snprintf(content, sizeof(content), "file %d\n", i);
+		if (write_file(path, content) < 0)
+			nr_bad++;
+	}
+	TEST_RES(nr_bad, _ret == 0);
+
+	// The metadata must be committed so that the files survive the remount.
+	sync();
+	TEST_SUCC(umount(MNT));
+	TEST_SUCC(mount(EXT3_DISK, MNT, "ext3", 0, ""));
+
+	for (int i = 0; i < NR_FILES; i++) {
+		snprintf(path, sizeof(path), MNT "/dir/%d", i);
+		snprintf(content, sizeof(content), "file %d\n", i);
+		if (read_file(path, buf, sizeof(buf)) < 0 ||
+		    strcmp(buf, content) != 0)
+			nr_bad++;
+		if (unlink(path) < 0)
+			nr_bad++;
+	}
+	TEST_RES(nr_bad, _ret == 0);
+	TEST_SUCC(rmdir(MNT "/dir"));
+
+	// The files written by the replayed transaction are still there.
+	TEST_RES(read_file(MNT "/a.txt", buf, sizeof(buf)),
+		 strcmp(buf, "new a\n") == 0);
+}
+END_TEST()
+
+FN_SETUP(umount)
+{
+	sync();
+	CHECK(umount(MNT));
+}
+END_SETUP()
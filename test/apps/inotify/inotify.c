@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <poll.h>
+#include <sys/inotify.h>
+#include <sys/ioctl.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define DIR_NAME "/tmp/inotify_test"
+#define FILE_NAME DIR_NAME "/file"
+#define NEW_FILE_NAME DIR_NAME "/new_file"
+#define SUB_DIR_NAME DIR_NAME "/sub_dir"
+#define COPY_FILE_NAME "/tmp/inotify_copy"
+
+#define EVENT_BUF_LEN 4096
+
+static int fd;
+static int dir_wd;
+static char buf[EVENT_BUF_LEN] __attribute__((aligned(8)));
+
+FN_SETUP(init)
+{
+	CHECK(mkdir(DIR_NAME, 0755));
+	fd = CHECK(inotify_init1(IN_NONBLOCK | IN_CLOEXEC));
+}
+END_SETUP()
+
+FN_TEST(invalid_args)
+{
+	TEST_ERRNO(inotify_init1(0x1), EINVAL);
+	TEST_ERRNO(inotify_add_watch(fd, "/non_existent", IN_ALL_EVENTS),
+		   ENOENT);
+	TEST_ERRNO(inotify_add_watch(STDIN_FILENO, DIR_NAME, IN_ALL_EVENTS),
+		   EINVAL);
+	TEST_ERRNO(inotify_rm_watch(fd, 1000), EINVAL);
+}
+END_TEST()
+
+FN_TEST(add_watch)
+{
+	int flags;
+
+	flags = TEST_SUCC(fcntl(fd, F_GETFD));
+	TEST_RES(flags, _ret == FD_CLOEXEC);
+
+	dir_wd = TEST_SUCC(inotify_add_watch(fd, DIR_NAME, IN_ALL_EVENTS));
+	TEST_RES(inotify_add_watch(fd, DIR_NAME, IN_ALL_EVENTS),
+		 _ret == dir_wd);
+	TEST_ERRNO(inotify_add_watch(fd, DIR_NAME,
+				     IN_ALL_EVENTS | IN_MASK_CREATE),
+		   EEXIST);
+	TEST_ERRNO(read(fd, buf, sizeof(buf)), EAGAIN);
+}
+END_TEST()
+
+static struct inotify_event *next_event(char **pos, char *end)
+{
+	struct inotify_event *event;
+
+	if (*pos >= end)
+		return NULL;
+
+	event = (struct inotify_event *)*pos;
+	*pos += sizeof(struct inotify_event) + event->len;
+	return event;
+}
+
+FN_TEST(create_write_close)
+{
+	int file_fd;
+	int nbytes;
+	char *pos, *end;
+	struct inotify_event *event;
+
+	file_fd = TEST_SUCC(open(FILE_NAME, O_CREAT | O_WRONLY, 0644));
+	TEST_RES(write(file_fd, "hello", 5), _ret == 5);
+	TEST_SUCC(close(file_fd));
+
+	TEST_RES(ioctl(fd, FIONREAD, &nbytes), nbytes > 0);
+	TEST_ERRNO(read(fd, buf, sizeof(struct inotify_event)), EINVAL);
+
+	nbytes = TEST_SUCC(read(fd, buf, sizeof(buf)));
+	pos = buf;
+	end = buf + nbytes;
+
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == IN_CREATE && event->wd == dir_wd &&
+			 strcmp(event->name, "file") == 0);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask, _ret == IN_OPEN);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask, _ret == IN_MODIFY);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == IN_CLOSE_WRITE && strcmp(event->name, "file") == 0);
+	TEST_RES((long)next_event(&pos, end), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(attrib_and_rename)
+{
+	int nbytes;
+	char *pos, *end;
+	struct inotify_event *event;
+	uint32_t cookie;
+
+	TEST_SUCC(chmod(FILE_NAME, 0600));
+	TEST_SUCC(rename(FILE_NAME, NEW_FILE_NAME));
+
+	nbytes = TEST_SUCC(read(fd, buf, sizeof(buf)));
+	pos = buf;
+	end = buf + nbytes;
+
+	event = next_event(&pos, end);
+	TEST_RES(event->mask, _ret == IN_ATTRIB);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == IN_MOVED_FROM && event->cookie != 0 &&
+			 strcmp(event->name, "file") == 0);
+	cookie = event->cookie;
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == IN_MOVED_TO && event->cookie == cookie &&
+			 strcmp(event->name, "new_file") == 0);
+	TEST_RES((long)next_event(&pos, end), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(access)
+{
+	int access_fd;
+	int file_fd, copy_fd;
+	int pipe_fds[2];
+	int wd;
+	int nbytes;
+	char *pos, *end;
+	loff_t offset;
+	char data[8];
+	struct inotify_event *event = (struct inotify_event *)buf;
+
+	access_fd = TEST_SUCC(inotify_init1(IN_NONBLOCK));
+	wd = TEST_SUCC(inotify_add_watch(access_fd, NEW_FILE_NAME, IN_ACCESS));
+	file_fd = TEST_SUCC(open(NEW_FILE_NAME, O_RDONLY));
+	copy_fd = TEST_SUCC(open(COPY_FILE_NAME, O_CREAT | O_WRONLY, 0644));
+	TEST_SUCC(pipe(pipe_fds));
+
+	TEST_RES(read(file_fd, data, sizeof(data)), _ret == 5);
+	TEST_RES(read(access_fd, buf, sizeof(buf)),
+		 _ret == sizeof(struct inotify_event) &&
+			 event->mask == IN_ACCESS && event->wd == wd);
+
+	// Reading nothing at the end of the file is not an access.
+	TEST_RES(read(file_fd, data, sizeof(data)), _ret == 0);
+	TEST_ERRNO(read(access_fd, buf, sizeof(buf)), EAGAIN);
+
+	offset = 0;
+	TEST_RES(copy_file_range(file_fd, &offset, copy_fd, NULL, 5, 0),
+		 _ret == 5);
+	TEST_RES(read(access_fd, buf, sizeof(buf)),
+		 _ret == sizeof(struct inotify_event) &&
+			 event->mask == IN_ACCESS && event->wd == wd);
+
+	offset = 0;
+	TEST_RES(splice(file_fd, &offset, pipe_fds[1], NULL, 5, 0), _ret == 5);
+	TEST_RES(read(access_fd, buf, sizeof(buf)),
+		 _ret == sizeof(struct inotify_event) &&
+			 event->mask == IN_ACCESS && event->wd == wd);
+
+	TEST_SUCC(close(pipe_fds[0]));
+	TEST_SUCC(close(pipe_fds[1]));
+	TEST_SUCC(close(copy_fd));
+	TEST_SUCC(close(file_fd));
+	TEST_SUCC(close(access_fd));
+	TEST_SUCC(unlink(COPY_FILE_NAME));
+
+	// The consecutive access events to the directory watch are coalesced.
+	nbytes = TEST_SUCC(read(fd, buf, sizeof(buf)));
+	pos = buf;
+	end = buf + nbytes;
+
+	event = next_event(&pos, end);
+	TEST_RES(event->mask, _ret == IN_OPEN);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == IN_ACCESS && strcmp(event->name, "new_file") == 0);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask, _ret == IN_CLOSE_NOWRITE);
+	TEST_RES((long)next_event(&pos, end), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(directory_events)
+{
+	int nbytes;
+	char *pos, *end;
+	struct inotify_event *event;
+
+	TEST_SUCC(mkdir(SUB_DIR_NAME, 0755));
+	TEST_SUCC(rmdir(SUB_DIR_NAME));
+
+	nbytes = TEST_SUCC(read(fd, buf, sizeof(buf)));
+	pos = buf;
+	end = buf + nbytes;
+
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == (IN_CREATE | IN_ISDIR) &&
+			 strcmp(event->name, "sub_dir") == 0);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == (IN_DELETE | IN_ISDIR) &&
+			 strcmp(event->name, "sub_dir") == 0);
+	TEST_RES((long)next_event(&pos, end), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(oneshot)
+{
+	int oneshot_fd;
+	int wd;
+	struct inotify_event *event = (struct inotify_event *)buf;
+
+	oneshot_fd = TEST_SUCC(inotify_init1(IN_NONBLOCK));
+	wd = TEST_SUCC(inotify_add_watch(oneshot_fd, NEW_FILE_NAME,
+					 IN_ATTRIB | IN_ONESHOT));
+	TEST_SUCC(chmod(NEW_FILE_NAME, 0644));
+	TEST_SUCC(chmod(NEW_FILE_NAME, 0600));
+
+	TEST_RES(read(oneshot_fd, buf, sizeof(struct inotify_event)),
+		 _ret == sizeof(struct inotify_event) &&
+			 event->mask == IN_ATTRIB && event->wd == wd &&
+			 event->len == 0);
+	TEST_RES(read(oneshot_fd, buf, sizeof(struct inotify_event)),
+		 _ret == sizeof(struct inotify_event) &&
+			 event->mask == IN_IGNORED && event->wd == wd);
+	TEST_ERRNO(read(oneshot_fd, buf, sizeof(buf)), EAGAIN);
+	TEST_ERRNO(inotify_rm_watch(oneshot_fd, wd), EINVAL);
+	TEST_SUCC(close(oneshot_fd));
+
+	// The two identical events to the directory watch are coalesced.
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == sizeof(struct inotify_event) + event->len &&
+			 event->mask == IN_ATTRIB && event->wd == dir_wd &&
+			 strcmp(event->name, "new_file") == 0);
+}
+END_TEST()
+
+FN_TEST(poll_and_delete)
+{
+	int wd;
+	int nbytes;
+	char *pos, *end;
+	struct inotify_event *event;
+	struct pollfd pfd = { .fd = fd, .events = POLLIN };
+
+	wd = TEST_SUCC(inotify_add_watch(fd, NEW_FILE_NAME, IN_DELETE_SELF));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	TEST_SUCC(unlink(NEW_FILE_NAME));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLIN);
+
+	nbytes = TEST_SUCC(read(fd, buf, sizeof(buf)));
+	pos = buf;
+	end = buf + nbytes;
+
+	event = next_event(&pos, end);
+	TEST_RES(event->mask, _ret == IN_DELETE_SELF && event->wd == wd);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask, _ret == IN_IGNORED && event->wd == wd);
+	event = next_event(&pos, end);
+	TEST_RES(event->mask,
+		 _ret == IN_DELETE && strcmp(event->name, "new_file") == 0);
+	TEST_RES((long)next_event(&pos, end), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(rm_watch)
+{
+	int nbytes;
+	struct inotify_event *event = (struct inotify_event *)buf;
+
+	TEST_SUCC(inotify_rm_watch(fd, dir_wd));
+	nbytes = TEST_SUCC(read(fd, buf, sizeof(buf)));
+	TEST_RES(event->mask, _ret == IN_IGNORED && event->wd == dir_wd &&
+				      nbytes == sizeof(struct inotify_event));
+	TEST_ERRNO(inotify_rm_watch(fd, dir_wd), EINVAL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fd));
+	CHECK(rmdir(DIR_NAME));
+}
+END_SETUP()
@@ -0,0 +1,683 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/io_uring.h>
+#include <netinet/in.h>
+#include <poll.h>
+#include <stdint.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/socket.h>
+#include <sys/syscall.h>
+#include <sys/uio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define IORING_MAX_ENTRIES 32768
+
+struct ring {
+	int fd;
+	unsigned int sq_entries;
+	unsigned int cq_entries;
+	void *ring_ptr;
+	size_t ring_size;
+	struct io_uring_sqe *sqes;
+	size_t sqes_size;
+	unsigned int *sq_head;
+	unsigned int *sq_tail;
+	unsigned int *sq_mask;
+	unsigned int *sq_array;
+	unsigned int *sq_dropped;
+	unsigned int *cq_head;
+	unsigned int *cq_tail;
+	unsigned int *cq_mask;
+	unsigned int *cq_overflow;
+	struct io_uring_cqe *cqes;
+};
+
+static int io_uring_setup(unsigned int entries, struct io_uring_params *p)
+{
+	return syscall(SYS_io_uring_setup, entries, p);
+}
+
+static int io_uring_enter(int fd, unsigned int to_submit,
+			  unsigned int min_complete, unsigned int flags)
+{
+	return syscall(SYS_io_uring_enter, fd, to_submit, min_complete, flags,
+		       NULL, 0);
+}
+
+static int io_uring_register(int fd, unsigned int opcode, void *arg,
+			     unsigned int nr_args)
+{
+	return syscall(SYS_io_uring_register, fd, opcode, arg, nr_args);
+}
+
+static int ring_init(struct ring *ring, unsigned int entries)
+{
+	struct io_uring_params p;
+	void *ptr;
+
+	memset(&p, 0, sizeof(p));
+	ring->fd = io_uring_setup(entries, &p);
+	if (ring->fd < 0)
+		return -1;
+
+	ring->sq_entries = p.sq_entries;
+	ring->cq_entries = p.cq_entries;
+
+	ring->ring_size = p.sq_off.array + p.sq_entries * sizeof(unsigned int);
+	if (ring->ring_size <
+	    p.cq_off.cqes + p.cq_entries * sizeof(struct io_uring_cqe))
+		ring->ring_size = p.cq_off.cqes +
+				  p.cq_entries * sizeof(struct io_uring_cqe);
+	ptr = mmap(NULL, ring->ring_size, PROT_READ | PROT_WRITE,
+		   MAP_SHARED | MAP_POPULATE, ring->fd, IORING_OFF_SQ_RING);
+	if (ptr == MAP_FAILED)
+		return -1;
+	ring->ring_ptr = ptr;
+
+	ring->sqes_size = p.sq_entries * sizeof(struct io_uring_sqe);
+	ring->sqes = mmap(NULL, ring->sqes_size, PROT_READ | PROT_WRITE,
+			  MAP_SHARED | MAP_POPULATE, ring->fd, IORING_OFF_SQES);
+	if (ring->sqes == MAP_FAILED)
+		return -1;
+
+	ring->sq_head = ptr + p.sq_off.head;
+	ring->sq_tail = ptr + p.sq_off.tail;
+	ring->sq_mask = ptr + p.sq_off.ring_mask;
+	ring->sq_array = ptr + p.sq_off.array;
+	ring->sq_dropped = ptr + p.sq_off.dropped;
+	ring->cq_head = ptr + p.cq_off.head;
+	ring->cq_tail = ptr + p.cq_off.tail;
+	ring->cq_mask = ptr + p.cq_off.ring_mask;
+	ring->cq_overflow = ptr + p.cq_off.overflow;
+	ring->cqes = ptr + p.cq_off.cqes;
+
+	return 0;
+}
+
+static int ring_exit(struct ring *ring)
+{
+	munmap(ring->sqes, ring->sqes_size);
+	munmap(ring->ring_ptr, ring->ring_size);
+	return close(ring->fd);
+}
+
+static struct io_uring_sqe *get_sqe(struct ring *ring, uint8_t opcode, int fd,
+				    uint64_t user_data)
+{
+	unsigned int tail = *ring->sq_tail;
+	unsigned int index = tail & *ring->sq_mask;
+	struct io_uring_sqe *sqe = &ring->sqes[index];
+
+	memset(sqe, 0, sizeof(*sqe));
+	sqe->opcode = opcode;
+	sqe->fd = fd;
+	sqe->user_data = user_data;
+
+	ring->sq_array[index] = index;
+	__atomic_store_n(ring->sq_tail, tail + 1, __ATOMIC_RELEASE);
+
+	return sqe;
+}
+
+static int submit_and_wait(struct ring *ring, unsigned int wait_nr)
+{
+	unsigned int tail = __atomic_load_n(ring->sq_tail, __ATOMIC_ACQUIRE);
+	unsigned int to_submit = tail - *ring->sq_head;
+
+	return io_uring_enter(ring->fd, to_submit, wait_nr,
+			      wait_nr ? IORING_ENTER_GETEVENTS : 0);
+}
+
+/*
+ * Pops the next CQE. Returns the `res` field and stores the user data to
+ * `user_data`, or returns `-EAGAIN` if the CQ is empty.
+ */
+static int pop_cqe(struct ring *ring, uint64_t *user_data)
+{
+	unsigned int head = *ring->cq_head;
+	struct io_uring_cqe *cqe;
+	int res;
+
+	if (head == __atomic_load_n(ring->cq_tail, __ATOMIC_ACQUIRE))
+		return -EAGAIN;
+
+	cqe = &ring->cqes[head & *ring->cq_mask];
+	res = cqe->res;
+	*user_data = cqe->user_data;
+	__atomic_store_n(ring->cq_head, head + 1, __ATOMIC_RELEASE);
+
+	return res;
+}
+
+/* Waits for one CQE and checks its user data. Returns the `res` field. */
+static int wait_cqe(struct ring *ring, uint64_t expected_user_data)
+{
+	uint64_t user_data = 0;
+	int res;
+
+	if (io_uring_enter(ring->fd, 0, 1, IORING_ENTER_GETEVENTS) < 0)
+		return -1000;
+
+	res = pop_cqe(ring, &user_data);
+	if (user_data != expected_user_data)
+		return -1000;
+
+	return res;
+}
+
+static struct ring ring;
+
+FN_SETUP(init)
+{
+	CHECK(ring_init(&ring, 8));
+}
+END_SETUP()
+
+FN_TEST(setup_params)
+{
+	struct io_uring_params p;
+	int fd;
+
+	TEST_RES(ring.sq_entries, _ret == 8);
+	TEST_RES(ring.cq_entries, _ret == 16);
+
+	memset(&p, 0, sizeof(p));
+	TEST_ERRNO(io_uring_setup(0, &p), EINVAL);
+	TEST_ERRNO(io_uring_setup(IORING_MAX_ENTRIES + 1, &p), EINVAL);
+
+	p.flags = IORING_SETUP_CLAMP;
+	fd = TEST_SUCC(io_uring_setup(IORING_MAX_ENTRIES + 1, &p));
+	TEST_RES(p.sq_entries, _ret == IORING_MAX_ENTRIES);
+	TEST_SUCC(close(fd));
+
+	memset(&p, 0, sizeof(p));
+	p.flags = IORING_SETUP_CQSIZE;
+	p.cq_entries = 100;
+	fd = TEST_SUCC(io_uring_setup(5, &p));
+	TEST_RES(p.sq_entries, _ret == 8);
+	TEST_RES(p.cq_entries, _ret == 128);
+	TEST_RES(fcntl(fd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_SUCC(close(fd));
+
+	memset(&p, 0, sizeof(p));
+	p.flags = IORING_SETUP_CQSIZE;
+	p.cq_entries = 4;
+	TEST_ERRNO(io_uring_setup(8, &p), EINVAL);
+
+	memset(&p, 0, sizeof(p));
+	p.resv[0] = 1;
+	TEST_ERRNO(io_uring_setup(8, &p), EINVAL);
+}
+END_TEST()
+
+FN_TEST(enter_invalid)
+{
+	int fd;
+
+	fd = TEST_SUCC(open("/dev/null", O_RDONLY));
+	TEST_ERRNO(io_uring_enter(fd, 0, 0, 0), EOPNOTSUPP);
+	TEST_ERRNO(io_uring_register(fd, IORING_UNREGISTER_FILES, NULL, 0),
+		   EOPNOTSUPP);
+	TEST_SUCC(close(fd));
+
+	TEST_RES(io_uring_enter(ring.fd, 0, 0, 0), _ret == 0);
+	TEST_ERRNO(io_uring_enter(ring.fd, 0, 0, 1U << 31), EINVAL);
+}
+END_TEST()
+
+FN_TEST(nop)
+{
+	uint64_t user_data;
+
+	get_sqe(&ring, IORING_OP_NOP, -1, 1);
+	get_sqe(&ring, IORING_OP_NOP, -1, 2);
+	TEST_RES(submit_and_wait(&ring, 2), _ret == 2);
+
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 1);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 2);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -EAGAIN);
+}
+END_TEST()
+
+FN_TEST(invalid_sqe)
+{
+	uint64_t user_data;
+	unsigned int dropped = *ring.sq_dropped;
+	unsigned int tail;
+
+	// Invalid SQEs are reported in CQEs, and the submission stops at them.
+	get_sqe(&ring, 0xff, -1, 3);
+	get_sqe(&ring, IORING_OP_NOP, -1, 4);
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -EINVAL && user_data == 3);
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 4);
+
+	get_sqe(&ring, IORING_OP_READ, 1000, 5);
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -EBADF && user_data == 5);
+
+	// Invalid SQ indexes are dropped and not counted as submitted.
+	tail = *ring.sq_tail;
+	ring.sq_array[tail & *ring.sq_mask] = 100;
+	__atomic_store_n(ring.sq_tail, tail + 1, __ATOMIC_RELEASE);
+	TEST_RES(submit_and_wait(&ring, 0), _ret == 0);
+	TEST_RES(*ring.sq_head, _ret == tail + 1);
+	TEST_RES(*ring.sq_dropped, _ret == dropped + 1);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -EAGAIN);
+}
+END_TEST()
+
+FN_TEST(pipe_read_write)
+{
+	int fds[2];
+	char buf[16];
+	struct io_uring_sqe *sqe;
+	uint64_t user_data;
+
+	TEST_SUCC(pipe(fds));
+
+	// The read request waits until the data arrives.
+	sqe = get_sqe(&ring, IORING_OP_READ, fds[0], 10);
+	sqe->addr = (uintptr_t)buf;
+	sqe->len = sizeof(buf);
+	sqe->off = -1;
+	TEST_RES(submit_and_wait(&ring, 0), _ret == 1);
+	usleep(10 * 1000);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -EAGAIN);
+
+	sqe = get_sqe(&ring, IORING_OP_WRITE, fds[1], 11);
+	sqe->addr = (uintptr_t) "hello";
+	sqe->len = 5;
+	sqe->off = -1;
+	TEST_RES(submit_and_wait(&ring, 2), _ret == 1);
+
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 5 && user_data == 11);
+	TEST_RES(wait_cqe(&ring, 10),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(file_readv_writev_fsync)
+{
+	char path[] = "/tmp/io_uring_XXXXXX";
+	char buf1[4], buf2[8];
+	struct iovec iov[2];
+	struct io_uring_sqe *sqe;
+	int fd;
+
+	fd = TEST_SUCC(mkstemp(path));
+	TEST_SUCC(unlink(path));
+
+	iov[0].iov_base = "abcd";
+	iov[0].iov_len = 4;
+	iov[1].iov_base = "efghijkl";
+	iov[1].iov_len = 8;
+	sqe = get_sqe(&ring, IORING_OP_WRITEV, fd, 20);
+	sqe->addr = (uintptr_t)iov;
+	sqe->len = 2;
+	sqe->off = 0;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 20), _ret == 12);
+
+	// An explicit offset does not change the file offset.
+	TEST_RES(lseek(fd, 0, SEEK_CUR), _ret == 0);
+
+	sqe = get_sqe(&ring, IORING_OP_FSYNC, fd, 21);
+	sqe->fsync_flags = IORING_FSYNC_DATASYNC;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 21), _ret == 0);
+
+	iov[0].iov_base = buf1;
+	iov[0].iov_len = sizeof(buf1);
+	iov[1].iov_base = buf2;
+	iov[1].iov_len = sizeof(buf2);
+	sqe = get_sqe(&ring, IORING_OP_READV, fd, 22);
+	sqe->addr = (uintptr_t)iov;
+	sqe->len = 2;
+	sqe->off = 2;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 22),
+		 _ret == 10 && memcmp(buf1, "cdef", 4) == 0 &&
+			 memcmp(buf2, "ghijkl", 6) == 0);
+
+	// An offset of -1 means the current file offset.
+	TEST_SUCC(lseek(fd, 8, SEEK_SET));
+	sqe = get_sqe(&ring, IORING_OP_READ, fd, 23);
+	sqe->addr = (uintptr_t)buf2;
+	sqe->len = sizeof(buf2);
+	sqe->off = -1;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 23),
+		 _ret == 4 && memcmp(buf2, "ijkl", 4) == 0);
+	TEST_RES(lseek(fd, 0, SEEK_CUR), _ret == 12);
+
+	sqe = get_sqe(&ring, IORING_OP_READ, fd, 24);
+	sqe->addr = 1;
+	sqe->len = 4;
+	sqe->off = 0;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 24), _ret == -EFAULT);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(poll_add_remove)
+{
+	int fds[2];
+	struct io_uring_sqe *sqe;
+	uint64_t user_data;
+
+	TEST_SUCC(pipe(fds));
+
+	sqe = get_sqe(&ring, IORING_OP_POLL_ADD, fds[0], 30);
+	sqe->poll32_events = POLLIN;
+	TEST_RES(submit_and_wait(&ring, 0), _ret == 1);
+	TEST_RES(write(fds[1], "x", 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 30), _ret == POLLIN);
+
+	// The pipe is still readable.
+	sqe = get_sqe(&ring, IORING_OP_POLL_ADD, fds[0], 31);
+	sqe->poll32_events = POLLIN;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 31), _ret == POLLIN);
+
+	// Remove a pending poll request.
+	sqe = get_sqe(&ring, IORING_OP_POLL_ADD, fds[1], 32);
+	sqe->poll32_events = POLLPRI;
+	TEST_RES(submit_and_wait(&ring, 0), _ret == 1);
+	sqe = get_sqe(&ring, IORING_OP_POLL_REMOVE, -1, 33);
+	sqe->addr = 32;
+	TEST_RES(submit_and_wait(&ring, 2), _ret == 1);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 33);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -ECANCELED &&
+						     user_data == 32);
+
+	sqe = get_sqe(&ring, IORING_OP_POLL_REMOVE, -1, 34);
+	sqe->addr = 32;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 34), _ret == -ENOENT);
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(timeout)
+{
+	struct __kernel_timespec ts = { .tv_sec = 0, .tv_nsec = 20000000 };
+	struct __kernel_timespec long_ts = { .tv_sec = 100, .tv_nsec = 0 };
+	struct io_uring_sqe *sqe;
+	uint64_t user_data;
+
+	sqe = get_sqe(&ring, IORING_OP_TIMEOUT, -1, 40);
+	sqe->addr = (uintptr_t)&ts;
+	sqe->len = 1;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 40), _ret == -ETIME);
+
+	// A timeout with a count completes after the other requests.
+	sqe = get_sqe(&ring, IORING_OP_TIMEOUT, -1, 41);
+	sqe->addr = (uintptr_t)&long_ts;
+	sqe->len = 1;
+	sqe->off = 1;
+	get_sqe(&ring, IORING_OP_NOP, -1, 42);
+	TEST_RES(submit_and_wait(&ring, 2), _ret == 2);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 42);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 41);
+
+	// Remove a pending timeout.
+	sqe = get_sqe(&ring, IORING_OP_TIMEOUT, -1, 43);
+	sqe->addr = (uintptr_t)&long_ts;
+	sqe->len = 1;
+	sqe = get_sqe(&ring, IORING_OP_TIMEOUT_REMOVE, -1, 44);
+	sqe->addr = 43;
+	TEST_RES(submit_and_wait(&ring, 2), _ret == 2);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 44);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -ECANCELED &&
+						     user_data == 43);
+
+	sqe = get_sqe(&ring, IORING_OP_TIMEOUT, -1, 45);
+	sqe->addr = (uintptr_t)&ts;
+	sqe->len = 2;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 45), _ret == -EINVAL);
+}
+END_TEST()
+
+FN_TEST(async_cancel)
+{
+	int fds[2];
+	char buf[4];
+	struct io_uring_sqe *sqe;
+	uint64_t user_data;
+
+	TEST_SUCC(pipe(fds));
+
+	sqe = get_sqe(&ring, IORING_OP_READ, fds[0], 50);
+	sqe->addr = (uintptr_t)buf;
+	sqe->len = sizeof(buf);
+	TEST_RES(submit_and_wait(&ring, 0), _ret == 1);
+
+	sqe = get_sqe(&ring, IORING_OP_ASYNC_CANCEL, -1, 51);
+	sqe->addr = 50;
+	TEST_RES(submit_and_wait(&ring, 2), _ret == 1);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 51);
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == -ECANCELED &&
+						     user_data == 50);
+
+	sqe = get_sqe(&ring, IORING_OP_ASYNC_CANCEL, -1, 52);
+	sqe->addr = 50;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 52), _ret == -ENOENT);
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(submitter_exit)
+{
+	int fds[2];
+	int status;
+	pid_t pid;
+	struct io_uring_sqe *sqe;
+
+	TEST_SUCC(pipe(fds));
+
+	// The requests are canceled when their submitter exits, even if the
+	// io_uring instance is still open.
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		sqe = get_sqe(&ring, IORING_OP_POLL_ADD, fds[0], 55);
+		sqe->poll32_events = POLLIN;
+		_exit(submit_and_wait(&ring, 0) == 1 ? EXIT_SUCCESS :
+							 EXIT_FAILURE);
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+	TEST_RES(wait_cqe(&ring, 55), _ret == -ECANCELED);
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(socket_ops)
+{
+	struct sockaddr_in addr = { .sin_family = AF_INET };
+	struct sockaddr_in peer_addr;
+	socklen_t addrlen = sizeof(addr);
+	socklen_t peer_addrlen = sizeof(peer_addr);
+	int listen_fd, client_fd, server_fd;
+	struct io_uring_sqe *sqe;
+	char buf[16];
+	uint64_t user_data;
+
+	addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+	listen_fd = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	TEST_SUCC(bind(listen_fd, (struct sockaddr *)&addr, sizeof(addr)));
+	TEST_SUCC(getsockname(listen_fd, (struct sockaddr *)&addr, &addrlen));
+	TEST_SUCC(listen(listen_fd, 2));
+
+	sqe = get_sqe(&ring, IORING_OP_ACCEPT, listen_fd, 60);
+	sqe->addr = (uintptr_t)&peer_addr;
+	sqe->addr2 = (uintptr_t)&peer_addrlen;
+	sqe->accept_flags = SOCK_CLOEXEC;
+	TEST_RES(submit_and_wait(&ring, 0), _ret == 1);
+
+	// Connecting blocking sockets is not supported.
+	client_fd = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	sqe = get_sqe(&ring, IORING_OP_CONNECT, client_fd, 61);
+	sqe->addr = (uintptr_t)&addr;
+	sqe->off = sizeof(addr);
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 61), _ret == -EAGAIN);
+	TEST_SUCC(close(client_fd));
+
+	client_fd = TEST_SUCC(socket(AF_INET, SOCK_STREAM | SOCK_NONBLOCK, 0));
+	sqe = get_sqe(&ring, IORING_OP_CONNECT, client_fd, 61);
+	sqe->addr = (uintptr_t)&addr;
+	sqe->off = sizeof(addr);
+	TEST_RES(submit_and_wait(&ring, 2), _ret == 1);
+
+	server_fd = -1;
+	for (int i = 0; i < 2; ++i) {
+		int res = pop_cqe(&ring, &user_data);
+
+		if (res == -EAGAIN) {
+			TEST_SUCC(io_uring_enter(ring.fd, 0, 1,
+						 IORING_ENTER_GETEVENTS));
+			--i;
+			continue;
+		}
+		if (user_data == 60)
+			server_fd = res;
+		else
+			TEST_RES(res, (_ret == 0 || _ret == -EINPROGRESS) &&
+					      user_data == 61);
+	}
+	TEST_RES(server_fd, _ret >= 0);
+	TEST_RES(fcntl(server_fd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(peer_addrlen, _ret == sizeof(peer_addr) &&
+				       peer_addr.sin_family == AF_INET);
+
+	sqe = get_sqe(&ring, IORING_OP_RECV, server_fd, 62);
+	sqe->addr = (uintptr_t)buf;
+	sqe->len = sizeof(buf);
+	TEST_RES(submit_and_wait(&ring, 0), _ret == 1);
+
+	sqe = get_sqe(&ring, IORING_OP_SEND, client_fd, 63);
+	sqe->addr = (uintptr_t) "ping";
+	sqe->len = 4;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 63), _ret == 4);
+	TEST_RES(wait_cqe(&ring, 62), _ret == 4 && memcmp(buf, "ping", 4) == 0);
+
+	TEST_SUCC(close(server_fd));
+	TEST_SUCC(close(client_fd));
+	TEST_SUCC(close(listen_fd));
+}
+END_TEST()
+
+FN_TEST(register_files_and_buffers)
+{
+	int fds[2], files[2];
+	char buf[8];
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+	struct io_uring_sqe *sqe;
+
+	TEST_SUCC(pipe(fds));
+	files[0] = -1;
+	files[1] = fds[0];
+
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_UNREGISTER_FILES, NULL, 0),
+		   ENXIO);
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_FILES, files, 2));
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_REGISTER_FILES, files, 2),
+		   EBUSY);
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_BUFFERS, &iov, 1));
+
+	TEST_RES(write(fds[1], "fixed", 5), _ret == 5);
+
+	sqe = get_sqe(&ring, IORING_OP_READ_FIXED, 1, 70);
+	sqe->flags = IOSQE_FIXED_FILE;
+	sqe->addr = (uintptr_t)buf;
+	sqe->len = sizeof(buf);
+	sqe->buf_index = 0;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 70),
+		 _ret == 5 && memcmp(buf, "fixed", 5) == 0);
+
+	sqe = get_sqe(&ring, IORING_OP_READ, 0, 71);
+	sqe->flags = IOSQE_FIXED_FILE;
+	sqe->addr = (uintptr_t)buf;
+	sqe->len = sizeof(buf);
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 71), _ret == -EBADF);
+
+	sqe = get_sqe(&ring, IORING_OP_READ_FIXED, 1, 72);
+	sqe->flags = IOSQE_FIXED_FILE;
+	sqe->addr = (uintptr_t)buf;
+	sqe->len = sizeof(buf) + 1;
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(wait_cqe(&ring, 72), _ret == -EFAULT);
+
+	TEST_SUCC(
+		io_uring_register(ring.fd, IORING_UNREGISTER_BUFFERS, NULL, 0));
+	TEST_SUCC(io_uring_register(ring.fd, IORING_UNREGISTER_FILES, NULL, 0));
+
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(probe)
+{
+	struct {
+		struct io_uring_probe probe;
+		struct io_uring_probe_op ops[64];
+	} p;
+
+	memset(&p, 0, sizeof(p));
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_PROBE, &p, 64));
+	TEST_RES(p.probe.last_op, _ret >= IORING_OP_RECV);
+	TEST_RES(p.ops[IORING_OP_READ].flags & IO_URING_OP_SUPPORTED,
+		 _ret != 0);
+	TEST_RES(p.ops[IORING_OP_ACCEPT].flags & IO_URING_OP_SUPPORTED,
+		 _ret != 0);
+}
+END_TEST()
+
+FN_TEST(poll_ring)
+{
+	struct pollfd pfd = { .fd = ring.fd, .events = POLLIN | POLLOUT };
+	uint64_t user_data;
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLOUT);
+
+	get_sqe(&ring, IORING_OP_NOP, -1, 80);
+	TEST_RES(submit_and_wait(&ring, 1), _ret == 1);
+	TEST_RES(poll(&pfd, 1, 0),
+		 _ret == 1 && pfd.revents == (POLLIN | POLLOUT));
+	TEST_RES(pop_cqe(&ring, &user_data), _ret == 0 && user_data == 80);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(ring_exit(&ring));
+}
+END_SETUP()
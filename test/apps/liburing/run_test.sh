@@ -0,0 +1,27 @@
+#!/bin/sh
+
+# SPDX-License-Identifier: MPL-2.0
+
+# Runs the upstream tests of liburing. Like the upstream test runner, a test that exits with 77
+# is skipped because the feature that it tests is not supported.
+
+set -e
+
+TEST_DIR=/test/liburing
+SKIP_EXIT_CODE=77
+
+# The tests create temporary files in the current directory.
+cd /tmp
+
+for testcase in $(ls ${TEST_DIR} | grep -v '\.sh$')
+do
+    echo "Running liburing test ${testcase}......"
+    ret=0
+    ${TEST_DIR}/${testcase} || ret=$?
+    if [ ${ret} -eq ${SKIP_EXIT_CODE} ]; then
+        echo "Skipped liburing test ${testcase}."
+    elif [ ${ret} -ne 0 ]; then
+        echo "Error: liburing test ${testcase} failed with exit code ${ret}."
+        exit 1
+    fi
+done
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <sys/mman.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PAGE_SIZE 4096
+
+#ifndef MREMAP_DONTUNMAP
+#define MREMAP_DONTUNMAP 4
+#endif
+
+static char *addr;
+
+FN_SETUP(mmap)
+{
+	// Leave a hole after the mapping so that it can grow in place.
+	addr = (char *)CHECK_WITH((long)mmap(NULL, 3 * PAGE_SIZE,
+					     PROT_READ | PROT_WRITE,
+					     MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				  _ret != (long)MAP_FAILED);
+	CHECK(munmap(addr + 2 * PAGE_SIZE, PAGE_SIZE));
+	addr[0] = 'a';
+	addr[PAGE_SIZE] = 'b';
+}
+END_SETUP()
+
+FN_TEST(invalid_args)
+{
+	TEST_ERRNO((long)mremap(addr + 1, PAGE_SIZE, PAGE_SIZE, 0), EINVAL);
+	TEST_ERRNO((long)mremap(addr, PAGE_SIZE, 0, 0), EINVAL);
+	TEST_ERRNO((long)mremap(addr, PAGE_SIZE, PAGE_SIZE, MREMAP_FIXED,
+				addr),
+		   EINVAL);
+	TEST_ERRNO((long)mremap(addr, PAGE_SIZE, 2 * PAGE_SIZE,
+				MREMAP_MAYMOVE | MREMAP_DONTUNMAP),
+		   EINVAL);
+	TEST_ERRNO((long)mremap(addr, 3 * PAGE_SIZE, 4 * PAGE_SIZE, 0),
+		   EFAULT);
+}
+END_TEST()
+
+FN_TEST(shrink)
+{
+	TEST_RES((long)mremap(addr, 2 * PAGE_SIZE, PAGE_SIZE, 0),
+		 _ret == (long)addr);
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_ERRNO(mprotect(addr + PAGE_SIZE, PAGE_SIZE, PROT_READ), ENOMEM);
+}
+END_TEST()
+
+FN_TEST(grow_in_place)
+{
+	TEST_RES((long)mremap(addr, PAGE_SIZE, 2 * PAGE_SIZE, 0),
+		 _ret == (long)addr);
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_RES(addr[PAGE_SIZE], _ret == 0);
+	addr[PAGE_SIZE] = 'b';
+}
+END_TEST()
+
+FN_TEST(grow_and_move)
+{
+	char *blocker;
+	char *new_addr;
+
+	blocker = mmap(addr + 2 * PAGE_SIZE, PAGE_SIZE, PROT_READ,
+		       MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED, -1, 0);
+	TEST_RES((long)blocker, _ret == (long)(addr + 2 * PAGE_SIZE));
+
+	TEST_ERRNO((long)mremap(addr, 2 * PAGE_SIZE, 3 * PAGE_SIZE, 0),
+		   ENOMEM);
+
+	new_addr = (char *)TEST_RES(
+		(long)mremap(addr, 2 * PAGE_SIZE, 3 * PAGE_SIZE,
+			     MREMAP_MAYMOVE),
+		_ret != (long)MAP_FAILED && _ret != (long)addr);
+	TEST_RES(new_addr[0], _ret == 'a');
+	TEST_RES(new_addr[PAGE_SIZE], _ret == 'b');
+	TEST_RES(new_addr[2 * PAGE_SIZE], _ret == 0);
+	TEST_ERRNO(mprotect(addr, PAGE_SIZE, PROT_READ), ENOMEM);
+
+	TEST_SUCC(munmap(blocker, PAGE_SIZE));
+	addr = new_addr;
+}
+END_TEST()
+
+FN_TEST(move_fixed)
+{
+	char *target;
+
+	target = mmap(NULL, 3 * PAGE_SIZE, PROT_READ,
+		      MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	TEST_RES((long)target, _ret != (long)MAP_FAILED);
+
+	TEST_ERRNO((long)mremap(addr, 3 * PAGE_SIZE, 3 * PAGE_SIZE,
+				MREMAP_MAYMOVE | MREMAP_FIXED,
+				addr + PAGE_SIZE),
+		   EINVAL);
+	TEST_RES((long)mremap(addr, 3 * PAGE_SIZE, 2 * PAGE_SIZE,
+			      MREMAP_MAYMOVE | MREMAP_FIXED, target),
+		 _ret == (long)target);
+	TEST_RES(target[0], _ret == 'a');
+	TEST_RES(target[PAGE_SIZE], _ret == 'b');
+	TEST_ERRNO(mprotect(addr, PAGE_SIZE, PROT_READ), ENOMEM);
+
+	// The rest of the target mapping is left untouched.
+	TEST_SUCC(munmap(target + 2 * PAGE_SIZE, PAGE_SIZE));
+
+	addr = target;
+}
+END_TEST()
+
+FN_TEST(dont_unmap)
+{
+	char *new_addr;
+
+	new_addr = (char *)TEST_RES(
+		(long)mremap(addr, 2 * PAGE_SIZE, 2 * PAGE_SIZE,
+			     MREMAP_MAYMOVE | MREMAP_DONTUNMAP),
+		_ret != (long)MAP_FAILED && _ret != (long)addr);
+	TEST_RES(new_addr[0], _ret == 'a');
+	TEST_RES(new_addr[PAGE_SIZE], _ret == 'b');
+	TEST_RES(addr[0], _ret == 0);
+	TEST_RES(addr[PAGE_SIZE], _ret == 0);
+
+	TEST_SUCC(munmap(addr, 2 * PAGE_SIZE));
+	TEST_SUCC(munmap(new_addr, 2 * PAGE_SIZE));
+}
+END_TEST()
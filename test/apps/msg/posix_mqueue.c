@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <mqueue.h>
+#include <poll.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define MQ_NAME "/test_mqueue"
+
+static mqd_t mqd;
+
+FN_SETUP(create)
+{
+	struct mq_attr attr = { .mq_maxmsg = 2, .mq_msgsize = 16 };
+
+	mqd = CHECK(mq_open(MQ_NAME, O_RDWR | O_CREAT | O_EXCL, 0600, &attr));
+}
+END_SETUP()
+
+FN_TEST(open)
+{
+	struct mq_attr attr = { .mq_maxmsg = 100000, .mq_msgsize = 16 };
+	mqd_t other;
+
+	TEST_ERRNO(mq_open(MQ_NAME, O_RDWR | O_CREAT | O_EXCL, 0600, NULL),
+		   EEXIST);
+	TEST_ERRNO(mq_open("/test_mqueue_missing", O_RDWR), ENOENT);
+	TEST_ERRNO(mq_open("/test_mqueue_large", O_RDWR | O_CREAT, 0600,
+			   &attr),
+		   EINVAL);
+
+	other = TEST_SUCC(mq_open(MQ_NAME, O_RDONLY));
+	TEST_SUCC(mq_close(other));
+}
+END_TEST()
+
+FN_TEST(send_and_receive)
+{
+	char buf[16];
+	unsigned int prio;
+	struct mq_attr attr;
+
+	TEST_SUCC(mq_send(mqd, "low", 4, 1));
+	TEST_SUCC(mq_send(mqd, "high", 5, 9));
+	TEST_RES(mq_getattr(mqd, &attr),
+		 attr.mq_curmsgs == 2 && attr.mq_maxmsg == 2 &&
+			 attr.mq_msgsize == 16 && attr.mq_flags == 0);
+
+	// The message with the highest priority is received first.
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 5 && prio == 9 && strcmp(buf, "high") == 0);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 4 && prio == 1 && strcmp(buf, "low") == 0);
+
+	TEST_ERRNO(mq_send(mqd, buf, 17, 0), EMSGSIZE);
+	TEST_ERRNO(mq_receive(mqd, buf, 8, NULL), EMSGSIZE);
+}
+END_TEST()
+
+FN_TEST(nonblocking_and_timeout)
+{
+	char buf[16];
+	struct mq_attr attr = { .mq_flags = O_NONBLOCK };
+	struct timespec timeout;
+
+	TEST_SUCC(mq_setattr(mqd, &attr, NULL));
+	TEST_ERRNO(mq_receive(mqd, buf, sizeof(buf), NULL), EAGAIN);
+	TEST_SUCC(mq_send(mqd, "a", 2, 0));
+	TEST_SUCC(mq_send(mqd, "b", 2, 0));
+	TEST_ERRNO(mq_send(mqd, "c", 2, 0), EAGAIN);
+
+	attr.mq_flags = 0;
+	TEST_SUCC(mq_setattr(mqd, &attr, NULL));
+	CHECK(clock_gettime(CLOCK_REALTIME, &timeout));
+	timeout.tv_nsec += 10 * 1000 * 1000;
+	if (timeout.tv_nsec >= 1000000000) {
+		timeout.tv_sec += 1;
+		timeout.tv_nsec -= 1000000000;
+	}
+	TEST_ERRNO(mq_timedsend(mqd, "c", 2, 0, &timeout), ETIMEDOUT);
+
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL),
+		 _ret == 2 && strcmp(buf, "a") == 0);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL),
+		 _ret == 2 && strcmp(buf, "b") == 0);
+}
+END_TEST()
+
+FN_TEST(poll)
+{
+	struct pollfd pfd = { .fd = mqd, .events = POLLIN | POLLOUT };
+	char buf[16];
+
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLOUT);
+
+	TEST_SUCC(mq_send(mqd, "a", 2, 0));
+	TEST_SUCC(mq_send(mqd, "b", 2, 0));
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLIN);
+
+	TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+	TEST_RES(poll(&pfd, 1, 0),
+		 _ret == 1 && pfd.revents == (POLLIN | POLLOUT));
+	TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+}
+END_TEST()
+
+static volatile sig_atomic_t received_signal;
+
+static void handle_signal(int signo)
+{
+	received_signal = signo;
+}
+
+FN_TEST(notify)
+{
+	struct sigevent sev = { .sigev_notify = SIGEV_SIGNAL,
+				.sigev_signo = SIGUSR1 };
+	char buf[16];
+
+	signal(SIGUSR1, handle_signal);
+
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_ERRNO(mq_notify(mqd, &sev), EBUSY);
+
+	TEST_RES(mq_send(mqd, "a", 2, 0), received_signal == SIGUSR1);
+	TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+
+	// The registration is removed after the notification.
+	received_signal = 0;
+	TEST_RES(mq_send(mqd, "a", 2, 0), received_signal == 0);
+	TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_SUCC(mq_notify(mqd, NULL));
+	TEST_RES(mq_send(mqd, "a", 2, 0), received_signal == 0);
+	TEST_SUCC(mq_receive(mqd, buf, sizeof(buf), NULL));
+
+	signal(SIGUSR1, SIG_DFL);
+}
+END_TEST()
+
+FN_TEST(blocking_receive)
+{
+	char buf[16];
+	int status;
+
+	if (CHECK(fork()) == 0) {
+		usleep(100 * 1000);
+		if (mq_send(mqd, "hello", 6, 0) < 0)
+			_exit(1);
+		_exit(0);
+	}
+
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL),
+		 _ret == 6 && strcmp(buf, "hello") == 0);
+	TEST_RES(wait(&status), WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_SETUP(unlink)
+{
+	CHECK(mq_unlink(MQ_NAME));
+	CHECK(mq_close(mqd));
+}
+END_SETUP()
+
+FN_TEST(unlinked)
+{
+	TEST_ERRNO(mq_open(MQ_NAME, O_RDWR), ENOENT);
+	TEST_ERRNO(mq_unlink(MQ_NAME), ENOENT);
+}
+END_TEST()
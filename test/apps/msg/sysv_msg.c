@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <stdio.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/msg.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define MSG_KEY 0x6b6b
+
+struct message {
+	long mtype;
+	char mtext[64];
+};
+
+static int msqid;
+
+FN_SETUP(create)
+{
+	msqid = CHECK(msgget(MSG_KEY, IPC_CREAT | IPC_EXCL | 0600));
+}
+END_SETUP()
+
+FN_TEST(get)
+{
+	TEST_RES(msgget(MSG_KEY, 0), _ret == msqid);
+	TEST_RES(msgget(MSG_KEY, IPC_CREAT), _ret == msqid);
+	TEST_ERRNO(msgget(MSG_KEY, IPC_CREAT | IPC_EXCL), EEXIST);
+	TEST_ERRNO(msgget(MSG_KEY + 1, 0), ENOENT);
+}
+END_TEST()
+
+FN_TEST(send_and_receive)
+{
+	struct message msg;
+	struct msqid_ds ds;
+
+	msg.mtype = 2;
+	strcpy(msg.mtext, "two");
+	TEST_SUCC(msgsnd(msqid, &msg, 4, 0));
+	msg.mtype = 1;
+	strcpy(msg.mtext, "one");
+	TEST_SUCC(msgsnd(msqid, &msg, 4, 0));
+	msg.mtype = 3;
+	strcpy(msg.mtext, "three");
+	TEST_SUCC(msgsnd(msqid, &msg, 6, 0));
+
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == 3 && ds.msg_cbytes == 14 &&
+			 ds.msg_lspid == getpid() &&
+			 (ds.msg_perm.mode & 0777) == 0600);
+
+	// Receive the first message of the type.
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 1, 0),
+		 _ret == 4 && msg.mtype == 1 && strcmp(msg.mtext, "one") == 0);
+
+	// Receive the first message of the lowest type not above 3.
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), -3, 0),
+		 _ret == 4 && msg.mtype == 2 && strcmp(msg.mtext, "two") == 0);
+
+	// The message is too long to be received.
+	TEST_ERRNO(msgrcv(msqid, &msg, 2, 0, 0), E2BIG);
+	TEST_RES(msgrcv(msqid, &msg, 2, 0, MSG_NOERROR),
+		 _ret == 2 && msg.mtype == 3 && memcmp(msg.mtext, "th", 2) == 0);
+
+	TEST_ERRNO(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, IPC_NOWAIT),
+		   ENOMSG);
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == 0 && ds.msg_cbytes == 0 &&
+			 ds.msg_lrpid == getpid());
+}
+END_TEST()
+
+FN_TEST(invalid_messages)
+{
+	struct message msg;
+
+	msg.mtype = 0;
+	TEST_ERRNO(msgsnd(msqid, &msg, 1, 0), EINVAL);
+	msg.mtype = 1;
+	TEST_ERRNO(msgsnd(msqid, &msg, -1, 0), EINVAL);
+	TEST_ERRNO(msgsnd(msqid + 1, &msg, 1, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(except)
+{
+	struct message msg;
+
+	msg.mtype = 5;
+	TEST_SUCC(msgsnd(msqid, &msg, 0, 0));
+	msg.mtype = 6;
+	TEST_SUCC(msgsnd(msqid, &msg, 0, 0));
+
+	TEST_RES(msgrcv(msqid, &msg, 0, 5, MSG_EXCEPT),
+		 _ret == 0 && msg.mtype == 6);
+	TEST_RES(msgrcv(msqid, &msg, 0, 0, 0), _ret == 0 && msg.mtype == 5);
+}
+END_TEST()
+
+FN_TEST(full_queue)
+{
+	struct message msg;
+	struct msqid_ds ds;
+
+	TEST_SUCC(msgctl(msqid, IPC_STAT, &ds));
+	ds.msg_qbytes = 8;
+	TEST_SUCC(msgctl(msqid, IPC_SET, &ds));
+
+	msg.mtype = 1;
+	TEST_SUCC(msgsnd(msqid, &msg, 8, 0));
+	TEST_ERRNO(msgsnd(msqid, &msg, 1, IPC_NOWAIT), EAGAIN);
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 0, 0), _ret == 8);
+
+	TEST_SUCC(msgctl(msqid, IPC_STAT, &ds));
+	ds.msg_qbytes = 16384;
+	TEST_SUCC(msgctl(msqid, IPC_SET, &ds));
+}
+END_TEST()
+
+FN_TEST(blocking_receive)
+{
+	struct message msg;
+	int status;
+
+	if (CHECK(fork()) == 0) {
+		usleep(100 * 1000);
+		msg.mtype = 7;
+		strcpy(msg.mtext, "hello");
+		if (msgsnd(msqid, &msg, 6, 0) < 0)
+			_exit(1);
+		_exit(0);
+	}
+
+	TEST_RES(msgrcv(msqid, &msg, sizeof(msg.mtext), 7, 0),
+		 _ret == 6 && strcmp(msg.mtext, "hello") == 0);
+	TEST_RES(wait(&status), WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(remove_wakes_receiver)
+{
+	struct message msg;
+	int status;
+	int id;
+
+	id = TEST_SUCC(msgget(IPC_PRIVATE, 0600));
+
+	if (CHECK(fork()) == 0) {
+		if (msgrcv(id, &msg, sizeof(msg.mtext), 0, 0) >= 0 ||
+		    errno != EIDRM)
+			_exit(1);
+		_exit(0);
+	}
+
+	usleep(100 * 1000);
+	TEST_SUCC(msgctl(id, IPC_RMID, NULL));
+	TEST_RES(wait(&status), WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	TEST_ERRNO(msgctl(id, IPC_STAT, NULL), EINVAL);
+}
+END_TEST()
+
+FN_SETUP(remove)
+{
+	CHECK(msgctl(msqid, IPC_RMID, NULL));
+}
+END_SETUP()
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sched.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/mount.h>
+#include <sys/shm.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/utsname.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define MOUNT_DIR "/tmp/namespace_mnt"
+#define MOUNT_FILE MOUNT_DIR "/file"
+#define SHM_KEY 0x4e53
+
+static int wait_child(pid_t pid)
+{
+	int status;
+
+	CHECK_WITH(waitpid(pid, &status, 0), _ret == pid);
+	if (!WIFEXITED(status))
+		return -1;
+	return WEXITSTATUS(status);
+}
+
+static int hostname_is(const char *name)
+{
+	struct utsname uts;
+
+	CHECK(uname(&uts));
+	return strcmp(uts.nodename, name) == 0;
+}
+
+FN_TEST(uts_namespace)
+{
+	struct utsname uts;
+	pid_t pid;
+
+	TEST_SUCC(uname(&uts));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWUTS));
+		CHECK(sethostname("namespace", strlen("namespace")));
+		CHECK_WITH(hostname_is("namespace"), _ret == 1);
+		_exit(0);
+	}
+	TEST_RES(wait_child(pid), _ret == 0);
+
+	// The host name of the parent must not be changed.
+	TEST_RES(hostname_is(uts.nodename), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(setns_uts)
+{
+	struct utsname uts;
+	pid_t pid;
+
+	TEST_SUCC(uname(&uts));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int fd = CHECK(open("/proc/self/ns/uts", O_RDONLY));
+
+		CHECK(unshare(CLONE_NEWUTS));
+		CHECK(sethostname("namespace", strlen("namespace")));
+
+		CHECK_WITH(setns(fd, CLONE_NEWNET), _ret < 0 && errno == EINVAL);
+		CHECK(setns(fd, CLONE_NEWUTS));
+		CHECK_WITH(hostname_is(uts.nodename), _ret == 1);
+		_exit(0);
+	}
+	TEST_RES(wait_child(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(mount_namespace)
+{
+	pid_t pid;
+
+	TEST_RES(mkdir(MOUNT_DIR, 0755), _ret == 0 || errno == EEXIST);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		int fd;
+
+		CHECK(unshare(CLONE_NEWNS));
+		CHECK(mount("none", MOUNT_DIR, "tmpfs", 0, NULL));
+		fd = CHECK(creat(MOUNT_FILE, 0644));
+		CHECK(close(fd));
+		CHECK(access(MOUNT_FILE, F_OK));
+		_exit(0);
+	}
+	TEST_RES(wait_child(pid), _ret == 0);
+
+	// The mount must be invisible to the parent.
+	TEST_ERRNO(access(MOUNT_FILE, F_OK), ENOENT);
+	TEST_SUCC(rmdir(MOUNT_DIR));
+}
+END_TEST()
+
+FN_TEST(pid_namespace)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		pid_t child;
+
+		CHECK(unshare(CLONE_NEWPID));
+		// The PID namespace of the calling process is not changed.
+		CHECK_WITH(getpid(), _ret > 1);
+		CHECK_WITH(unshare(CLONE_NEWPID), _ret < 0 && errno == EINVAL);
+
+		child = CHECK(fork());
+		if (child == 0) {
+			CHECK_WITH(getpid(), _ret == 1);
+			CHECK_WITH(getppid(), _ret == 0);
+			_exit(0);
+		}
+		CHECK_WITH(wait_child(child), _ret == 0);
+		_exit(0);
+	}
+	TEST_RES(wait_child(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(ipc_namespace)
+{
+	pid_t pid;
+	int shmid;
+
+	shmid = TEST_SUCC(shmget(SHM_KEY, 4096, IPC_CREAT | 0600));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWIPC));
+		CHECK_WITH(shmget(SHM_KEY, 4096, 0600),
+			   _ret < 0 && errno == ENOENT);
+		_exit(0);
+	}
+	TEST_RES(wait_child(pid), _ret == 0);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include <unistd.h>
+#include <sys/signal.h>
+#include <sys/socket.h>
+#include <sys/poll.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include <fcntl.h>
+
+#include "../common/test.h"
+
+static struct sockaddr_in6 sk_addr6;
+static struct sockaddr_in6 sk_mapped6;
+static struct sockaddr_in sk_addr4;
+
+#define UDP_PORT htons(0x2234)
+#define TCP_PORT htons(0x2235)
+#define MAPPED_UDP_PORT htons(0x2236)
+#define MAPPED_TCP_PORT htons(0x2237)
+#define V6ONLY_PORT htons(0x2238)
+
+FN_SETUP(general)
+{
+	sk_addr6.sin6_family = AF_INET6;
+	sk_addr6.sin6_addr = in6addr_loopback;
+
+	sk_mapped6.sin6_family = AF_INET6;
+	CHECK_WITH(inet_pton(AF_INET6, "::ffff:127.0.0.1",
+			     &sk_mapped6.sin6_addr),
+		   _ret == 1);
+
+	sk_addr4.sin_family = AF_INET;
+	CHECK(inet_aton("127.0.0.1", &sk_addr4.sin_addr));
+
+	signal(SIGPIPE, SIG_IGN);
+}
+END_SETUP()
+
+static int sk_udp_unbound;
+static int sk_udp_bound;
+static int sk_udp_client;
+
+FN_SETUP(udp)
+{
+	sk_udp_unbound = CHECK(socket(PF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+
+	sk_udp_bound = CHECK(socket(PF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+	sk_addr6.sin6_port = UDP_PORT;
+	CHECK(bind(sk_udp_bound, (struct sockaddr *)&sk_addr6,
+		   sizeof(sk_addr6)));
+
+	sk_udp_client = CHECK(socket(PF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+	CHECK(connect(sk_udp_client, (struct sockaddr *)&sk_addr6,
+		      sizeof(sk_addr6)));
+}
+END_SETUP()
+
+FN_TEST(udp_getsockname)
+{
+	struct sockaddr_in6 saddr;
+	struct sockaddr *psaddr = (struct sockaddr *)&saddr;
+	socklen_t addrlen = sizeof(saddr);
+
+	TEST_RES(getsockname(sk_udp_unbound, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == 0 &&
+			 IN6_IS_ADDR_UNSPECIFIED(&saddr.sin6_addr));
+
+	TEST_RES(getsockname(sk_udp_bound, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == UDP_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(getsockname(sk_udp_client, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_port != 0 &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(getpeername(sk_udp_client, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_port == UDP_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_ERRNO(getpeername(sk_udp_bound, psaddr, &addrlen), ENOTCONN);
+}
+END_TEST()
+
+FN_TEST(udp_send_recv)
+{
+	struct sockaddr_in6 saddr;
+	struct sockaddr *psaddr = (struct sockaddr *)&saddr;
+	socklen_t addrlen = sizeof(saddr);
+	char buf[8];
+
+	TEST_RES(send(sk_udp_client, "hello", 5, 0), _ret == 5);
+	TEST_RES(recvfrom(sk_udp_bound, buf, sizeof(buf), 0, psaddr, &addrlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 addrlen == sizeof(saddr) &&
+			 saddr.sin6_family == AF_INET6 &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(sendto(sk_udp_bound, "world", 5, 0, psaddr, addrlen),
+		 _ret == 5);
+	TEST_RES(recv(sk_udp_client, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(udp_mapped)
+{
+	struct sockaddr_in6 saddr;
+	struct sockaddr *psaddr = (struct sockaddr *)&saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk6, sk4;
+	char buf[8];
+
+	sk6 = TEST_SUCC(socket(PF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+	sk4 = TEST_SUCC(socket(PF_INET, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+
+	sk_mapped6.sin6_port = MAPPED_UDP_PORT;
+	TEST_SUCC(bind(sk6, (struct sockaddr *)&sk_mapped6,
+		       sizeof(sk_mapped6)));
+
+	sk_addr4.sin_port = MAPPED_UDP_PORT;
+	TEST_RES(sendto(sk4, "mapped", 6, 0, (struct sockaddr *)&sk_addr4,
+			sizeof(sk_addr4)),
+		 _ret == 6);
+	TEST_RES(recvfrom(sk6, buf, sizeof(buf), 0, psaddr, &addrlen),
+		 _ret == 6 && addrlen == sizeof(saddr) &&
+			 saddr.sin6_family == AF_INET6 &&
+			 IN6_IS_ADDR_V4MAPPED(&saddr.sin6_addr));
+
+	TEST_SUCC(close(sk6));
+	TEST_SUCC(close(sk4));
+}
+END_TEST()
+
+static int sk_listen;
+static int sk_connected;
+static int sk_accepted;
+
+FN_SETUP(tcp)
+{
+	struct pollfd pfd;
+
+	sk_listen = CHECK(socket(PF_INET6, SOCK_STREAM | SOCK_NONBLOCK, 0));
+	sk_addr6.sin6_port = TCP_PORT;
+	CHECK(bind(sk_listen, (struct sockaddr *)&sk_addr6, sizeof(sk_addr6)));
+	CHECK(listen(sk_listen, 2));
+
+	sk_connected = CHECK(socket(PF_INET6, SOCK_STREAM | SOCK_NONBLOCK, 0));
+	CHECK_WITH(connect(sk_connected, (struct sockaddr *)&sk_addr6,
+			   sizeof(sk_addr6)),
+		   _ret < 0 && errno == EINPROGRESS);
+
+	pfd.fd = sk_listen;
+	pfd.events = POLLIN;
+	CHECK_WITH(poll(&pfd, 1, 1000), _ret == 1 && (pfd.revents & POLLIN));
+	sk_accepted = CHECK(accept(sk_listen, NULL, NULL));
+
+	pfd.fd = sk_connected;
+	pfd.events = POLLOUT;
+	CHECK_WITH(poll(&pfd, 1, 1000), _ret == 1 && (pfd.revents & POLLOUT));
+}
+END_SETUP()
+
+FN_TEST(tcp_addr)
+{
+	struct sockaddr_in6 saddr;
+	struct sockaddr *psaddr = (struct sockaddr *)&saddr;
+	socklen_t addrlen = sizeof(saddr);
+
+	TEST_RES(getsockname(sk_listen, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == TCP_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(getpeername(sk_connected, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_port == TCP_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(getsockname(sk_accepted, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_port == TCP_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(getpeername(sk_accepted, psaddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_port != TCP_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+}
+END_TEST()
+
+FN_TEST(tcp_send_recv)
+{
+	struct pollfd pfd = { .fd = sk_accepted, .events = POLLIN };
+	char buf[8];
+
+	TEST_RES(send(sk_connected, "hello", 5, 0), _ret == 5);
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && (pfd.revents & POLLIN));
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(tcp_mapped)
+{
+	struct sockaddr_in6 saddr;
+	struct sockaddr *psaddr = (struct sockaddr *)&saddr;
+	socklen_t addrlen = sizeof(saddr);
+	struct pollfd pfd;
+	int sk_listen6, sk_connect4, sk_accept6;
+
+	sk_listen6 =
+		TEST_SUCC(socket(PF_INET6, SOCK_STREAM | SOCK_NONBLOCK, 0));
+	sk_mapped6.sin6_port = MAPPED_TCP_PORT;
+	TEST_SUCC(bind(sk_listen6, (struct sockaddr *)&sk_mapped6,
+		       sizeof(sk_mapped6)));
+	TEST_SUCC(listen(sk_listen6, 2));
+
+	sk_connect4 =
+		TEST_SUCC(socket(PF_INET, SOCK_STREAM | SOCK_NONBLOCK, 0));
+	sk_addr4.sin_port = MAPPED_TCP_PORT;
+	TEST_ERRNO(connect(sk_connect4, (struct sockaddr *)&sk_addr4,
+			   sizeof(sk_addr4)),
+		   EINPROGRESS);
+
+	pfd.fd = sk_listen6;
+	pfd.events = POLLIN;
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && (pfd.revents & POLLIN));
+	sk_accept6 = TEST_RES(accept(sk_listen6, psaddr, &addrlen),
+			      addrlen == sizeof(saddr) &&
+				      saddr.sin6_family == AF_INET6 &&
+				      IN6_IS_ADDR_V4MAPPED(&saddr.sin6_addr));
+
+	TEST_SUCC(close(sk_accept6));
+	TEST_SUCC(close(sk_connect4));
+	TEST_SUCC(close(sk_listen6));
+}
+END_TEST()
+
+FN_TEST(v6only)
+{
+	int sk, v6only;
+	socklen_t optlen = sizeof(v6only);
+
+	sk = TEST_SUCC(socket(PF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+
+	TEST_RES(getsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &v6only, &optlen),
+		 optlen == sizeof(v6only) && v6only == 0);
+
+	v6only = 1;
+	TEST_SUCC(setsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &v6only,
+			     sizeof(v6only)));
+	TEST_RES(getsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &v6only, &optlen),
+		 optlen == sizeof(v6only) && v6only == 1);
+
+	sk_mapped6.sin6_port = V6ONLY_PORT;
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&sk_mapped6,
+			sizeof(sk_mapped6)),
+		   EINVAL);
+	TEST_ERRNO(connect(sk, (struct sockaddr *)&sk_mapped6,
+			   sizeof(sk_mapped6)),
+		   ENETUNREACH);
+	TEST_ERRNO(sendto(sk, "", 0, 0, (struct sockaddr *)&sk_mapped6,
+			  sizeof(sk_mapped6)),
+		   ENETUNREACH);
+
+	sk_addr6.sin6_port = V6ONLY_PORT;
+	TEST_SUCC(bind(sk, (struct sockaddr *)&sk_addr6, sizeof(sk_addr6)));
+
+	v6only = 0;
+	TEST_ERRNO(setsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &v6only,
+			      sizeof(v6only)),
+		   EINVAL);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(family_mismatch)
+{
+	int sk;
+
+	sk = TEST_SUCC(socket(PF_INET, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+
+	sk_addr6.sin6_port = V6ONLY_PORT;
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&sk_addr6, sizeof(sk_addr6)),
+		   EAFNOSUPPORT);
+	TEST_ERRNO(getsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &(int){ 0 },
+			      &(socklen_t){ sizeof(int) }),
+		   ENOPROTOOPT);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(bad_addrlen)
+{
+	TEST_ERRNO(bind(sk_udp_unbound, (struct sockaddr *)&sk_addr6,
+			sizeof(struct sockaddr_in)),
+		   EINVAL);
+}
+END_TEST()
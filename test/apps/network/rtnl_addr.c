@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include <arpa/inet.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+#include <net/if.h>
+#include <netinet/in.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define ETHER_NAME "eth0"
+
+// The default configuration of `eth0`
+#define ETHER_ADDR "10.0.2.15"
+#define ETHER_GATEWAY "10.0.2.2"
+
+// Another configuration in a different subnet
+#define OTHER_ADDR "10.0.3.15"
+#define OTHER_GATEWAY "10.0.3.2"
+
+#define PREFIX_LEN 24
+
+struct rtnl_request {
+	struct nlmsghdr hdr;
+	union {
+		struct ifaddrmsg ifa;
+		struct rtmsg rtm;
+	};
+	char attrs[64];
+};
+
+static int sk_route;
+static int ether_index;
+
+FN_SETUP(socket)
+{
+	sk_route = CHECK(socket(PF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+}
+END_SETUP()
+
+static int find_ether_index(void)
+{
+	struct if_nameindex *if_ni, *i;
+	int index = 0;
+
+	if_ni = if_nameindex();
+	if (if_ni == NULL)
+		return -1;
+
+	for (i = if_ni; i->if_index != 0 || i->if_name != NULL; i++)
+		if (strcmp(i->if_name, ETHER_NAME) == 0)
+			index = i->if_index;
+
+	if_freenameindex(if_ni);
+	return index;
+}
+
+FN_SETUP(ether_index)
+{
+	// `if_nametoindex` is not used because it relies on `SIOCGIFINDEX`.
+	ether_index = CHECK_WITH(find_ether_index(), _ret > 0);
+}
+END_SETUP()
+
+static void add_attr(struct nlmsghdr *hdr, int type, const void *data,
+		     int len)
+{
+	struct rtattr *rta =
+		(struct rtattr *)((char *)hdr + NLMSG_ALIGN(hdr->nlmsg_len));
+
+	rta->rta_type = type;
+	rta->rta_len = RTA_LENGTH(len);
+	memcpy(RTA_DATA(rta), data, len);
+	hdr->nlmsg_len = NLMSG_ALIGN(hdr->nlmsg_len) + RTA_ALIGN(rta->rta_len);
+}
+
+static void add_addr_attr(struct nlmsghdr *hdr, int type, const char *addr)
+{
+	struct in_addr in_addr;
+
+	inet_pton(AF_INET, addr, &in_addr);
+	add_attr(hdr, type, &in_addr, sizeof(in_addr));
+}
+
+// Sends the request and waits for the acknowledgment.
+//
+// On failure, this function returns -1 and sets `errno` to the error code in
+// the acknowledgment.
+static int do_request(struct rtnl_request *req)
+{
+	char buf[256];
+	struct nlmsghdr *hdr = (struct nlmsghdr *)buf;
+	struct nlmsgerr *err = NLMSG_DATA(hdr);
+	ssize_t len;
+
+	req->hdr.nlmsg_flags |= NLM_F_REQUEST | NLM_F_ACK;
+
+	if (send(sk_route, req, req->hdr.nlmsg_len, 0) < 0)
+		return -1;
+
+	len = recv(sk_route, buf, sizeof(buf), 0);
+	if (len < 0)
+		return -1;
+
+	if (!NLMSG_OK(hdr, len) || hdr->nlmsg_type != NLMSG_ERROR ||
+	    hdr->nlmsg_seq != req->hdr.nlmsg_seq) {
+		errno = EBADMSG;
+		return -1;
+	}
+
+	if (err->error != 0) {
+		errno = -err->error;
+		return -1;
+	}
+
+	return 0;
+}
+
+static unsigned int seq;
+
+static int do_addr(int type, int flags, int family, int index,
+		   const char *addr)
+{
+	struct rtnl_request req;
+
+	memset(&req, 0, sizeof(req));
+	req.hdr.nlmsg_len = NLMSG_LENGTH(sizeof(struct ifaddrmsg));
+	req.hdr.nlmsg_type = type;
+	req.hdr.nlmsg_flags = flags;
+	req.hdr.nlmsg_seq = ++seq;
+	req.ifa.ifa_family = family;
+	req.ifa.ifa_prefixlen = PREFIX_LEN;
+	req.ifa.ifa_index = index;
+
+	if (addr != NULL)
+		add_addr_attr(&req.hdr, IFA_LOCAL, addr);
+
+	return do_request(&req);
+}
+
+static int do_route(int type, int flags, int index, const char *gateway)
+{
+	struct rtnl_request req;
+
+	memset(&req, 0, sizeof(req));
+	req.hdr.nlmsg_len = NLMSG_LENGTH(sizeof(struct rtmsg));
+	req.hdr.nlmsg_type = type;
+	req.hdr.nlmsg_flags = flags;
+	req.hdr.nlmsg_seq = ++seq;
+	req.rtm.rtm_family = AF_INET;
+	req.rtm.rtm_table = RT_TABLE_MAIN;
+	req.rtm.rtm_protocol = RTPROT_BOOT;
+	req.rtm.rtm_scope = RT_SCOPE_UNIVERSE;
+	req.rtm.rtm_type = RTN_UNICAST;
+
+	if (gateway != NULL)
+		add_addr_attr(&req.hdr, RTA_GATEWAY, gateway);
+	if (index != 0)
+		add_attr(&req.hdr, RTA_OIF, &index, sizeof(index));
+
+	return do_request(&req);
+}
+
+static int bind_udp(const char *addr)
+{
+	struct sockaddr_in saddr = { .sin_family = AF_INET };
+	int sk, ret;
+
+	inet_pton(AF_INET, addr, &saddr.sin_addr);
+
+	sk = socket(AF_INET, SOCK_DGRAM, 0);
+	if (sk < 0)
+		return -1;
+
+	ret = bind(sk, (struct sockaddr *)&saddr, sizeof(saddr));
+	close(sk);
+
+	return ret;
+}
+
+#define CREATE_EXCL (NLM_F_CREATE | NLM_F_EXCL)
+#define CREATE_REPLACE (NLM_F_CREATE | NLM_F_REPLACE)
+
+FN_TEST(new_addr_invalid)
+{
+	TEST_ERRNO(do_addr(RTM_NEWADDR, CREATE_EXCL, AF_INET6, ether_index,
+			   NULL),
+		   EAFNOSUPPORT);
+
+	TEST_ERRNO(do_addr(RTM_NEWADDR, CREATE_EXCL, AF_INET, 1000,
+			   OTHER_ADDR),
+		   ENODEV);
+
+	TEST_ERRNO(do_addr(RTM_NEWADDR, CREATE_EXCL, AF_INET, ether_index,
+			   NULL),
+		   EINVAL);
+}
+END_TEST()
+
+FN_TEST(new_addr_exists)
+{
+	TEST_ERRNO(do_addr(RTM_NEWADDR, CREATE_EXCL, AF_INET, ether_index,
+			   ETHER_ADDR),
+		   EEXIST);
+
+	TEST_ERRNO(do_addr(RTM_NEWADDR, CREATE_EXCL, AF_INET, ether_index,
+			   OTHER_ADDR),
+		   EEXIST);
+}
+END_TEST()
+
+FN_TEST(del_addr_nonexistent)
+{
+	TEST_ERRNO(do_addr(RTM_DELADDR, 0, AF_INET, ether_index, OTHER_ADDR),
+		   EADDRNOTAVAIL);
+}
+END_TEST()
+
+FN_TEST(new_route_invalid)
+{
+	TEST_ERRNO(do_route(RTM_NEWROUTE, CREATE_EXCL, ether_index,
+			    ETHER_GATEWAY),
+		   EEXIST);
+
+	TEST_ERRNO(do_route(RTM_NEWROUTE, CREATE_REPLACE, ether_index,
+			    OTHER_GATEWAY),
+		   ENETUNREACH);
+
+	TEST_ERRNO(do_route(RTM_NEWROUTE, CREATE_REPLACE, 1000,
+			    ETHER_GATEWAY),
+		   ENODEV);
+}
+END_TEST()
+
+FN_TEST(replace_addr)
+{
+	TEST_SUCC(do_addr(RTM_NEWADDR, CREATE_REPLACE, AF_INET, ether_index,
+			  OTHER_ADDR));
+
+	TEST_SUCC(bind_udp(OTHER_ADDR));
+	TEST_ERRNO(bind_udp(ETHER_ADDR), EADDRNOTAVAIL);
+
+	TEST_SUCC(do_route(RTM_NEWROUTE, CREATE_REPLACE, ether_index,
+			   OTHER_GATEWAY));
+
+	TEST_SUCC(do_addr(RTM_NEWADDR, CREATE_REPLACE, AF_INET, ether_index,
+			  ETHER_ADDR));
+
+	TEST_SUCC(bind_udp(ETHER_ADDR));
+	TEST_ERRNO(bind_udp(OTHER_ADDR), EADDRNOTAVAIL);
+
+	TEST_SUCC(do_route(RTM_NEWROUTE, CREATE_REPLACE, ether_index,
+			   ETHER_GATEWAY));
+}
+END_TEST()
+
+FN_TEST(del_and_new_route)
+{
+	TEST_SUCC(do_route(RTM_DELROUTE, 0, 0, ETHER_GATEWAY));
+
+	TEST_ERRNO(do_route(RTM_DELROUTE, 0, 0, ETHER_GATEWAY), ESRCH);
+
+	TEST_SUCC(do_route(RTM_NEWROUTE, CREATE_EXCL, 0, ETHER_GATEWAY));
+}
+END_TEST()
+
+FN_TEST(del_and_new_addr)
+{
+	TEST_SUCC(do_addr(RTM_DELADDR, 0, AF_INET, ether_index, ETHER_ADDR));
+
+	TEST_ERRNO(bind_udp(ETHER_ADDR), EADDRNOTAVAIL);
+
+	TEST_ERRNO(do_addr(RTM_DELADDR, 0, AF_INET, ether_index, ETHER_ADDR),
+		   EADDRNOTAVAIL);
+
+	TEST_SUCC(do_addr(RTM_NEWADDR, CREATE_EXCL, AF_INET, ether_index,
+			  ETHER_ADDR));
+
+	TEST_SUCC(bind_udp(ETHER_ADDR));
+
+	// Deleting the address also deletes the default route via the address.
+	TEST_SUCC(do_route(RTM_NEWROUTE, CREATE_EXCL, ether_index,
+			   ETHER_GATEWAY));
+}
+END_TEST()
@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <sys/socket.h>
+#include <sys/un.h>
+#include <sys/poll.h>
+#include <unistd.h>
+#include <stddef.h>
+
+#include "../common/test.h"
+
+#define PATH_OFFSET offsetof(struct sockaddr_un, sun_path)
+
+#define SERVER_PATH "/tmp/dgram_server"
+#define CLIENT_PATH "/tmp/dgram_client"
+#define SEQPACKET_PATH "/tmp/seqpacket_server"
+
+static struct sockaddr_un server_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = SERVER_PATH,
+};
+
+static struct sockaddr_un client_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = CLIENT_PATH,
+};
+
+static struct sockaddr_un seqpacket_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = SEQPACKET_PATH,
+};
+
+static struct sockaddr_un abstract_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = "\0nonexistent",
+};
+
+static int sk_pair[2];
+static int sk_server;
+static int sk_client;
+
+FN_SETUP(pair)
+{
+	CHECK(socketpair(PF_UNIX, SOCK_DGRAM | SOCK_NONBLOCK, 0, sk_pair));
+}
+END_SETUP()
+
+FN_SETUP(server)
+{
+	sk_server = CHECK(socket(PF_UNIX, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+	CHECK(bind(sk_server, (struct sockaddr *)&server_addr,
+		   sizeof(server_addr)));
+
+	sk_client = CHECK(socket(PF_UNIX, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+}
+END_SETUP()
+
+FN_TEST(pair_boundaries)
+{
+	char buf[16];
+
+	TEST_RES(send(sk_pair[0], "hello", 5, 0), _ret == 5);
+	TEST_RES(send(sk_pair[0], "world!", 6, 0), _ret == 6);
+	TEST_RES(send(sk_pair[0], "", 0, 0), _ret == 0);
+
+	TEST_RES(poll(&(struct pollfd){ .fd = sk_pair[1], .events = POLLIN },
+		      1, 0),
+		 _ret == 1);
+
+	TEST_RES(recv(sk_pair[1], buf, sizeof(buf), MSG_PEEK),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(recv(sk_pair[1], buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+
+	// The rest of a truncated datagram is discarded.
+	TEST_RES(recv(sk_pair[1], buf, 3, 0),
+		 _ret == 3 && memcmp(buf, "wor", 3) == 0);
+
+	TEST_RES(recv(sk_pair[1], buf, sizeof(buf), 0), _ret == 0);
+	TEST_ERRNO(recv(sk_pair[1], buf, sizeof(buf), 0), EAGAIN);
+
+	TEST_RES(send(sk_pair[1], "reply", 5, 0), _ret == 5);
+	TEST_RES(recv(sk_pair[0], buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "reply", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(unconnected)
+{
+	char buf[16];
+
+	TEST_ERRNO(send(sk_client, "hello", 5, 0), ENOTCONN);
+	TEST_ERRNO(recv(sk_client, buf, sizeof(buf), 0), EAGAIN);
+
+	TEST_ERRNO(sendto(sk_client, "hello", 5, 0,
+			  (struct sockaddr *)&abstract_addr,
+			  PATH_OFFSET + 12),
+		   ECONNREFUSED);
+	TEST_ERRNO(connect(sk_client, (struct sockaddr *)&abstract_addr,
+			   PATH_OFFSET + 12),
+		   ECONNREFUSED);
+}
+END_TEST()
+
+FN_TEST(sendto_unbound)
+{
+	char buf[16];
+	struct sockaddr_un addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_RES(sendto(sk_client, "hello", 5, 0,
+			(struct sockaddr *)&server_addr, sizeof(server_addr)),
+		 _ret == 5);
+
+	TEST_RES(recvfrom(sk_server, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&addr, &addrlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 addrlen == sizeof(sa_family_t) &&
+			 addr.sun_family == AF_UNIX);
+}
+END_TEST()
+
+FN_TEST(sendto_bound)
+{
+	char buf[16];
+	struct sockaddr_un addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_SUCC(bind(sk_client, (struct sockaddr *)&client_addr,
+		       sizeof(client_addr)));
+
+	TEST_RES(sendto(sk_client, "hello", 5, 0,
+			(struct sockaddr *)&server_addr, sizeof(server_addr)),
+		 _ret == 5);
+
+	TEST_RES(recvfrom(sk_server, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&addr, &addrlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 addrlen == PATH_OFFSET + sizeof(CLIENT_PATH) &&
+			 strcmp(addr.sun_path, CLIENT_PATH) == 0);
+
+	TEST_RES(sendto(sk_server, "world", 5, 0, (struct sockaddr *)&addr,
+			addrlen),
+		 _ret == 5);
+	TEST_RES(recv(sk_client, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(connected)
+{
+	char buf[16];
+	struct sockaddr_un addr;
+	socklen_t addrlen = sizeof(addr);
+
+	TEST_SUCC(connect(sk_client, (struct sockaddr *)&server_addr,
+			  sizeof(server_addr)));
+
+	TEST_RES(getpeername(sk_client, (struct sockaddr *)&addr, &addrlen),
+		 addrlen == PATH_OFFSET + sizeof(SERVER_PATH) &&
+			 strcmp(addr.sun_path, SERVER_PATH) == 0);
+
+	TEST_RES(send(sk_client, "hello", 5, 0), _ret == 5);
+	TEST_RES(write(sk_client, "world", 5), _ret == 5);
+
+	TEST_RES(read(sk_server, buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(read(sk_server, buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(peer_closed)
+{
+	TEST_SUCC(close(sk_server));
+	TEST_SUCC(unlink(SERVER_PATH));
+
+	TEST_ERRNO(send(sk_client, "hello", 5, 0), ECONNREFUSED);
+
+	TEST_SUCC(close(sk_client));
+	TEST_SUCC(unlink(CLIENT_PATH));
+}
+END_TEST()
+
+FN_TEST(seqpacket_pair)
+{
+	int sk[2];
+	char buf[16];
+
+	TEST_SUCC(socketpair(PF_UNIX, SOCK_SEQPACKET | SOCK_NONBLOCK, 0, sk));
+
+	TEST_RES(send(sk[0], "hello", 5, 0), _ret == 5);
+	TEST_RES(send(sk[0], "world!", 6, 0), _ret == 6);
+
+	TEST_RES(recv(sk[1], buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(recv(sk[1], buf, 3, 0),
+		 _ret == 3 && memcmp(buf, "wor", 3) == 0);
+	TEST_ERRNO(recv(sk[1], buf, sizeof(buf), 0), EAGAIN);
+
+	TEST_SUCC(close(sk[0]));
+	TEST_RES(recv(sk[1], buf, sizeof(buf), 0), _ret == 0);
+	TEST_SUCC(close(sk[1]));
+}
+END_TEST()
+
+FN_TEST(seqpacket_accept)
+{
+	int sk_listen, sk_connect, sk_accept, sk_stream;
+	char buf[16];
+
+	sk_listen = TEST_SUCC(socket(PF_UNIX, SOCK_SEQPACKET, 0));
+	TEST_SUCC(bind(sk_listen, (struct sockaddr *)&seqpacket_addr,
+		       sizeof(seqpacket_addr)));
+	TEST_SUCC(listen(sk_listen, 2));
+
+	sk_stream = TEST_SUCC(socket(PF_UNIX, SOCK_STREAM, 0));
+	TEST_ERRNO(connect(sk_stream, (struct sockaddr *)&seqpacket_addr,
+			   sizeof(seqpacket_addr)),
+		   EPROTOTYPE);
+
+	sk_connect = TEST_SUCC(socket(PF_UNIX, SOCK_SEQPACKET, 0));
+	TEST_SUCC(connect(sk_connect, (struct sockaddr *)&seqpacket_addr,
+			  sizeof(seqpacket_addr)));
+	sk_accept = TEST_SUCC(accept(sk_listen, NULL, NULL));
+
+	TEST_RES(send(sk_connect, "hello", 5, 0), _ret == 5);
+	TEST_RES(send(sk_connect, "world", 5, 0), _ret == 5);
+
+	TEST_RES(recv(sk_accept, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(recv(sk_accept, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+
+	TEST_SUCC(close(sk_stream));
+	TEST_SUCC(close(sk_connect));
+	TEST_SUCC(close(sk_accept));
+	TEST_SUCC(close(sk_listen));
+	TEST_SUCC(unlink(SEQPACKET_PATH));
+}
+END_TEST()
@@ -11,7 +11,7 @@
 #include <unistd.h>
 #include <stddef.h>
 
-#include "test.h"
+#include "../common/test.h"
 
 #define PATH_OFFSET offsetof(struct sockaddr_un, sun_path)
 
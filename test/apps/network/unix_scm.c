@@ -0,0 +1,429 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sys/poll.h>
+#include <sys/socket.h>
+#include <sys/un.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define MAX_FDS 4
+#define CONTROL_LEN \
+	(CMSG_SPACE(sizeof(int) * MAX_FDS) + CMSG_SPACE(sizeof(struct ucred)))
+
+#define LISTEN_PATH "/tmp/scm_listen"
+
+static struct sockaddr_un listen_addr = {
+	.sun_family = AF_UNIX,
+	.sun_path = LISTEN_PATH,
+};
+
+// Sends the data along with the file descriptors and/or the credentials.
+static int send_scm(int sk, const char *data, const int *fds, int nfds,
+		    const struct ucred *cred)
+{
+	char control[CONTROL_LEN];
+	struct iovec iov = { .iov_base = (void *)data, .iov_len = strlen(data) };
+	struct msghdr msg = { .msg_iov = &iov, .msg_iovlen = 1 };
+	struct cmsghdr *cmsg;
+	size_t len = 0;
+
+	memset(control, 0, sizeof(control));
+	msg.msg_control = control;
+	msg.msg_controllen = sizeof(control);
+	cmsg = CMSG_FIRSTHDR(&msg);
+
+	if (nfds > 0) {
+		cmsg->cmsg_level = SOL_SOCKET;
+		cmsg->cmsg_type = SCM_RIGHTS;
+		cmsg->cmsg_len = CMSG_LEN(sizeof(int) * nfds);
+		memcpy(CMSG_DATA(cmsg), fds, sizeof(int) * nfds);
+		len += CMSG_SPACE(sizeof(int) * nfds);
+		cmsg = CMSG_NXTHDR(&msg, cmsg);
+	}
+
+	if (cred != NULL) {
+		cmsg->cmsg_level = SOL_SOCKET;
+		cmsg->cmsg_type = SCM_CREDENTIALS;
+		cmsg->cmsg_len = CMSG_LEN(sizeof(struct ucred));
+		memcpy(CMSG_DATA(cmsg), cred, sizeof(struct ucred));
+		len += CMSG_SPACE(sizeof(struct ucred));
+	}
+
+	msg.msg_controllen = len;
+	if (len == 0)
+		msg.msg_control = NULL;
+
+	return sendmsg(sk, &msg, 0);
+}
+
+struct scm_result {
+	int fds[MAX_FDS];
+	int nfds;
+	struct ucred cred;
+	int has_cred;
+	int msg_flags;
+};
+
+// Receives the data along with the file descriptors and/or the credentials.
+static int recv_scm(int sk, char *buf, size_t buf_len, size_t control_len,
+		    int flags, struct scm_result *res)
+{
+	char control[CONTROL_LEN];
+	struct iovec iov = { .iov_base = buf, .iov_len = buf_len };
+	struct msghdr msg = { .msg_iov = &iov, .msg_iovlen = 1 };
+	struct cmsghdr *cmsg;
+	int ret;
+
+	memset(res, 0, sizeof(*res));
+	msg.msg_control = control;
+	msg.msg_controllen = control_len;
+
+	ret = recvmsg(sk, &msg, flags);
+	if (ret < 0)
+		return ret;
+
+	res->msg_flags = msg.msg_flags;
+
+	for (cmsg = CMSG_FIRSTHDR(&msg); cmsg != NULL;
+	     cmsg = CMSG_NXTHDR(&msg, cmsg)) {
+		if (cmsg->cmsg_level != SOL_SOCKET)
+			continue;
+
+		if (cmsg->cmsg_type == SCM_RIGHTS) {
+			res->nfds = (cmsg->cmsg_len - CMSG_LEN(0)) / sizeof(int);
+			memcpy(res->fds, CMSG_DATA(cmsg),
+			       sizeof(int) * res->nfds);
+		} else if (cmsg->cmsg_type == SCM_CREDENTIALS) {
+			memcpy(&res->cred, CMSG_DATA(cmsg),
+			       sizeof(struct ucred));
+			res->has_cred = 1;
+		}
+	}
+
+	return ret;
+}
+
+static int is_same_pipe(int write_fd, int read_fd)
+{
+	char c;
+
+	if (write(write_fd, "x", 1) != 1)
+		return 0;
+	if (read(read_fd, &c, 1) != 1)
+		return 0;
+	return c == 'x';
+}
+
+static int sk_stream[2];
+static int sk_dgram[2];
+static int pipe_fds[2];
+
+FN_SETUP(sockets)
+{
+	CHECK(socketpair(PF_UNIX, SOCK_STREAM | SOCK_NONBLOCK, 0, sk_stream));
+	CHECK(socketpair(PF_UNIX, SOCK_DGRAM | SOCK_NONBLOCK, 0, sk_dgram));
+	CHECK(pipe(pipe_fds));
+}
+END_SETUP()
+
+FN_TEST(rights_stream)
+{
+	char buf[16];
+	struct scm_result res;
+
+	TEST_RES(send_scm(sk_stream[0], "hello", &pipe_fds[1], 1, NULL),
+		 _ret == 5);
+	TEST_RES(recv_scm(sk_stream[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 5 && res.nfds == 1 && res.fds[0] != pipe_fds[1] &&
+			 res.msg_flags == 0);
+
+	TEST_RES(is_same_pipe(res.fds[0], pipe_fds[0]), _ret == 1);
+	TEST_RES(fcntl(res.fds[0], F_GETFD), _ret == 0);
+	TEST_SUCC(close(res.fds[0]));
+}
+END_TEST()
+
+FN_TEST(rights_cloexec)
+{
+	char buf[16];
+	struct scm_result res;
+
+	TEST_RES(send_scm(sk_stream[0], "hello", &pipe_fds[1], 1, NULL),
+		 _ret == 5);
+	TEST_RES(recv_scm(sk_stream[1], buf, sizeof(buf), CONTROL_LEN,
+			  MSG_CMSG_CLOEXEC, &res),
+		 _ret == 5 && res.nfds == 1);
+
+	TEST_RES(fcntl(res.fds[0], F_GETFD), _ret == FD_CLOEXEC);
+	TEST_SUCC(close(res.fds[0]));
+}
+END_TEST()
+
+FN_TEST(rights_truncated)
+{
+	char buf[16];
+	int fds[2] = { pipe_fds[0], pipe_fds[1] };
+	struct scm_result res;
+
+	// There is room for only one file descriptor.
+	TEST_RES(send_scm(sk_stream[0], "hello", fds, 2, NULL), _ret == 5);
+	TEST_RES(recv_scm(sk_stream[1], buf, sizeof(buf), CMSG_LEN(sizeof(int)),
+			  0, &res),
+		 _ret == 5 && res.nfds == 1 && res.msg_flags == MSG_CTRUNC);
+	TEST_SUCC(close(res.fds[0]));
+
+	// There is no room for any control messages.
+	TEST_RES(send_scm(sk_stream[0], "hello", fds, 2, NULL), _ret == 5);
+	TEST_RES(recv_scm(sk_stream[1], buf, sizeof(buf), 0, 0, &res),
+		 _ret == 5 && res.nfds == 0 && res.msg_flags == MSG_CTRUNC);
+}
+END_TEST()
+
+FN_TEST(rights_boundaries)
+{
+	char buf[16];
+	struct scm_result res;
+
+	// A read stops after the bytes that carry the file descriptors.
+	TEST_RES(send_scm(sk_stream[0], "ab", NULL, 0, NULL), _ret == 2);
+	TEST_RES(send_scm(sk_stream[0], "cd", &pipe_fds[1], 1, NULL),
+		 _ret == 2);
+	TEST_RES(send_scm(sk_stream[0], "ef", NULL, 0, NULL), _ret == 2);
+
+	TEST_RES(recv_scm(sk_stream[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 4 && memcmp(buf, "abcd", 4) == 0 && res.nfds == 1);
+	TEST_SUCC(close(res.fds[0]));
+
+	TEST_RES(recv_scm(sk_stream[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 2 && memcmp(buf, "ef", 2) == 0 && res.nfds == 0);
+
+	// The file descriptors are received with the first byte.
+	TEST_RES(send_scm(sk_stream[0], "gh", &pipe_fds[1], 1, NULL),
+		 _ret == 2);
+	TEST_RES(recv_scm(sk_stream[1], buf, 1, CONTROL_LEN, 0, &res),
+		 _ret == 1 && buf[0] == 'g' && res.nfds == 1);
+	TEST_SUCC(close(res.fds[0]));
+	TEST_RES(recv_scm(sk_stream[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 1 && buf[0] == 'h' && res.nfds == 0);
+}
+END_TEST()
+
+FN_TEST(rights_invalid)
+{
+	int bad_fd = 1000;
+
+	TEST_ERRNO(send_scm(sk_stream[0], "hello", &bad_fd, 1, NULL), EBADF);
+}
+END_TEST()
+
+FN_TEST(rights_dgram_peek)
+{
+	char buf[16];
+	struct scm_result res;
+
+	TEST_RES(send_scm(sk_dgram[0], "hello", &pipe_fds[1], 1, NULL),
+		 _ret == 5);
+
+	TEST_RES(recv_scm(sk_dgram[1], buf, sizeof(buf), CONTROL_LEN,
+			  MSG_PEEK, &res),
+		 _ret == 5 && res.nfds == 1);
+	TEST_RES(is_same_pipe(res.fds[0], pipe_fds[0]), _ret == 1);
+	TEST_SUCC(close(res.fds[0]));
+
+	TEST_RES(recv_scm(sk_dgram[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 5 && res.nfds == 1);
+	TEST_RES(is_same_pipe(res.fds[0], pipe_fds[0]), _ret == 1);
+	TEST_SUCC(close(res.fds[0]));
+
+	TEST_ERRNO(recv_scm(sk_dgram[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			    &res),
+		   EAGAIN);
+}
+END_TEST()
+
+FN_TEST(credentials)
+{
+	char buf[16];
+	int one = 1;
+	struct scm_result res;
+	struct ucred cred = { .pid = getpid(), .uid = getuid(), .gid = getgid() };
+
+	// The credentials are not received without `SO_PASSCRED`.
+	TEST_RES(send_scm(sk_dgram[0], "hello", NULL, 0, NULL), _ret == 5);
+	TEST_RES(recv_scm(sk_dgram[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 5 && res.has_cred == 0);
+
+	TEST_SUCC(setsockopt(sk_dgram[1], SOL_SOCKET, SO_PASSCRED, &one,
+			     sizeof(one)));
+
+	// The credentials are sent by default.
+	TEST_RES(send_scm(sk_dgram[0], "hello", NULL, 0, NULL), _ret == 5);
+	TEST_RES(recv_scm(sk_dgram[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 5 && res.has_cred == 1 &&
+			 res.cred.pid == cred.pid && res.cred.uid == cred.uid &&
+			 res.cred.gid == cred.gid);
+
+	// The credentials are sent explicitly.
+	TEST_RES(send_scm(sk_dgram[0], "hello", &pipe_fds[1], 1, &cred),
+		 _ret == 5);
+	TEST_RES(recv_scm(sk_dgram[1], buf, sizeof(buf), CONTROL_LEN, 0,
+			  &res),
+		 _ret == 5 && res.has_cred == 1 &&
+			 res.cred.pid == cred.pid && res.nfds == 1);
+	TEST_SUCC(close(res.fds[0]));
+}
+END_TEST()
+
+FN_TEST(credentials_invalid)
+{
+	char control[CMSG_SPACE(sizeof(int))];
+	struct iovec iov = { .iov_base = "hello", .iov_len = 5 };
+	struct msghdr msg = { .msg_iov = &iov,
+			      .msg_iovlen = 1,
+			      .msg_control = control,
+			      .msg_controllen = sizeof(control) };
+	struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+
+	// The credentials have an invalid length.
+	cmsg->cmsg_level = SOL_SOCKET;
+	cmsg->cmsg_type = SCM_CREDENTIALS;
+	cmsg->cmsg_len = CMSG_LEN(sizeof(int));
+	TEST_ERRNO(sendmsg(sk_dgram[0], &msg, 0), EINVAL);
+
+	// The control message type is unknown.
+	cmsg->cmsg_type = 100;
+	TEST_ERRNO(sendmsg(sk_dgram[0], &msg, 0), EINVAL);
+
+	// The control message length is invalid.
+	cmsg->cmsg_type = SCM_RIGHTS;
+	cmsg->cmsg_len = sizeof(control) + 1;
+	TEST_ERRNO(sendmsg(sk_dgram[0], &msg, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(passcred_option)
+{
+	int val;
+	socklen_t len = sizeof(val);
+
+	TEST_RES(getsockopt(sk_stream[0], SOL_SOCKET, SO_PASSCRED, &val, &len),
+		 len == sizeof(val) && val == 0);
+	TEST_RES(getsockopt(sk_dgram[1], SOL_SOCKET, SO_PASSCRED, &val, &len),
+		 len == sizeof(val) && val == 1);
+}
+END_TEST()
+
+FN_TEST(peercred_pair)
+{
+	struct ucred cred;
+	socklen_t len = sizeof(cred);
+
+	TEST_RES(getsockopt(sk_stream[0], SOL_SOCKET, SO_PEERCRED, &cred, &len),
+		 len == sizeof(cred) && cred.pid == getpid() &&
+			 cred.uid == geteuid() && cred.gid == getegid());
+	TEST_RES(getsockopt(sk_dgram[0], SOL_SOCKET, SO_PEERCRED, &cred, &len),
+		 len == sizeof(cred) && cred.pid == getpid());
+}
+END_TEST()
+
+FN_TEST(peercred_connect)
+{
+	int sk_listen, sk_connect, sk_accept, sk_unconnected;
+	struct ucred cred;
+	socklen_t len = sizeof(cred);
+	int val = 1;
+	socklen_t val_len = sizeof(val);
+
+	sk_unconnected = TEST_SUCC(socket(PF_UNIX, SOCK_STREAM, 0));
+	TEST_RES(getsockopt(sk_unconnected, SOL_SOCKET, SO_PEERCRED, &cred,
+			    &len),
+		 len == sizeof(cred) && cred.pid == 0 &&
+			 cred.uid == (uid_t)-1 && cred.gid == (gid_t)-1);
+
+	sk_listen = TEST_SUCC(socket(PF_UNIX, SOCK_STREAM, 0));
+	TEST_SUCC(bind(sk_listen, (struct sockaddr *)&listen_addr,
+		       sizeof(listen_addr)));
+	TEST_SUCC(setsockopt(sk_listen, SOL_SOCKET, SO_PASSCRED, &val,
+			     sizeof(val)));
+	TEST_SUCC(listen(sk_listen, 1));
+
+	sk_connect = TEST_SUCC(socket(PF_UNIX, SOCK_STREAM, 0));
+	TEST_SUCC(connect(sk_connect, (struct sockaddr *)&listen_addr,
+			  sizeof(listen_addr)));
+	sk_accept = TEST_SUCC(accept(sk_listen, NULL, NULL));
+
+	TEST_RES(getsockopt(sk_connect, SOL_SOCKET, SO_PEERCRED, &cred, &len),
+		 len == sizeof(cred) && cred.pid == getpid());
+	TEST_RES(getsockopt(sk_accept, SOL_SOCKET, SO_PEERCRED, &cred, &len),
+		 len == sizeof(cred) && cred.pid == getpid());
+
+	// The accepted socket inherits `SO_PASSCRED`.
+	val = 0;
+	TEST_RES(getsockopt(sk_accept, SOL_SOCKET, SO_PASSCRED, &val,
+			    &val_len),
+		 val_len == sizeof(val) && val == 1);
+
+	TEST_SUCC(close(sk_unconnected));
+	TEST_SUCC(close(sk_connect));
+	TEST_SUCC(close(sk_accept));
+	TEST_SUCC(close(sk_listen));
+	TEST_SUCC(unlink(LISTEN_PATH));
+}
+END_TEST()
+
+FN_TEST(garbage_collection)
+{
+	int sk_a[2], sk_b[2];
+	int fds[2];
+	char buf[16];
+	struct pollfd pfd;
+
+	TEST_SUCC(socketpair(PF_UNIX, SOCK_STREAM, 0, sk_a));
+	TEST_SUCC(socketpair(PF_UNIX, SOCK_STREAM | SOCK_NONBLOCK, 0, sk_b));
+
+	// Make a cycle that holds `sk_b[0]`.
+	fds[0] = sk_a[0];
+	fds[1] = sk_b[0];
+	TEST_RES(send_scm(sk_a[0], "x", fds, 2, NULL), _ret == 1);
+	TEST_RES(send_scm(sk_a[1], "x", &sk_a[1], 1, NULL), _ret == 1);
+
+	TEST_SUCC(close(sk_a[0]));
+	TEST_SUCC(close(sk_a[1]));
+	TEST_SUCC(close(sk_b[0]));
+
+	TEST_ERRNO(recv(sk_b[1], buf, sizeof(buf), 0), EAGAIN);
+
+	// Sending files may trigger the garbage collection.
+	TEST_RES(send_scm(sk_stream[0], "x", &pipe_fds[1], 1, NULL),
+		 _ret == 1);
+
+	pfd.fd = sk_b[1];
+	pfd.events = POLLIN;
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1);
+	TEST_RES(recv(sk_b[1], buf, sizeof(buf), 0), _ret == 0);
+
+	TEST_SUCC(close(sk_b[1]));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_stream[0]));
+	CHECK(close(sk_stream[1]));
+	CHECK(close(sk_dgram[0]));
+	CHECK(close(sk_dgram[1]));
+	CHECK(close(pipe_fds[0]));
+	CHECK(close(pipe_fds[1]));
+}
+END_SETUP()
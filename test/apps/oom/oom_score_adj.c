@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+static int read_number(const char *path)
+{
+	char buf[32] = {};
+	int fd;
+
+	fd = CHECK(open(path, O_RDONLY));
+	CHECK(read(fd, buf, sizeof(buf) - 1));
+	CHECK(close(fd));
+
+	return atoi(buf);
+}
+
+static ssize_t write_string(const char *path, const char *str)
+{
+	ssize_t ret;
+	int fd, saved_errno;
+
+	fd = CHECK(open(path, O_WRONLY | O_TRUNC));
+	ret = write(fd, str, strlen(str));
+	saved_errno = errno;
+	CHECK(close(fd));
+	errno = saved_errno;
+
+	return ret;
+}
+
+FN_TEST(oom_score_adj)
+{
+	TEST_RES(read_number("/proc/self/oom_score_adj"), _ret == 0);
+
+	TEST_RES(write_string("/proc/self/oom_score_adj", "500\n"), _ret == 4);
+	TEST_RES(read_number("/proc/self/oom_score_adj"), _ret == 500);
+
+	TEST_RES(write_string("/proc/self/oom_score_adj", "-1000"), _ret == 5);
+	TEST_RES(read_number("/proc/self/oom_score_adj"), _ret == -1000);
+
+	TEST_ERRNO(write_string("/proc/self/oom_score_adj", "1001"), EINVAL);
+	TEST_ERRNO(write_string("/proc/self/oom_score_adj", "-1001"), EINVAL);
+	TEST_ERRNO(write_string("/proc/self/oom_score_adj", "abc"), EINVAL);
+	TEST_RES(read_number("/proc/self/oom_score_adj"), _ret == -1000);
+
+	TEST_RES(write_string("/proc/self/oom_score_adj", "0"), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(oom_score)
+{
+	TEST_RES(write_string("/proc/self/oom_score_adj", "-1000"), _ret == 5);
+	TEST_RES(read_number("/proc/self/oom_score"), _ret == 0);
+
+	TEST_RES(write_string("/proc/self/oom_score_adj", "1000"), _ret == 4);
+	TEST_RES(read_number("/proc/self/oom_score"),
+		 _ret >= 1999 && _ret <= 2000);
+
+	TEST_RES(write_string("/proc/self/oom_score_adj", "0"), _ret == 1);
+	TEST_RES(read_number("/proc/self/oom_score"),
+		 _ret >= 1000 && _ret <= 2000);
+}
+END_TEST()
+
+FN_TEST(oom_score_adj_fork)
+{
+	int status;
+	pid_t pid;
+
+	TEST_RES(write_string("/proc/self/oom_score_adj", "300"), _ret == 3);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0)
+		_exit(read_number("/proc/self/oom_score_adj") == 300 ? 0 : 1);
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_RES(write_string("/proc/self/oom_score_adj", "0"), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(vmstat_oom_kill)
+{
+	char buf[256] = {};
+	int fd;
+
+	fd = TEST_SUCC(open("/proc/vmstat", O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf) - 1), _ret > 0);
+	TEST_RES(strstr(buf, "oom_kill ") != NULL, _ret);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
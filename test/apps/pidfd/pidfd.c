@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/sched.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/prctl.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+#include "../common/capability.h"
+
+#ifndef P_PIDFD
+#define P_PIDFD 3
+#endif
+
+static int pidfd_open(pid_t pid, unsigned int flags)
+{
+	return syscall(SYS_pidfd_open, pid, flags);
+}
+
+static int pidfd_send_signal(int pidfd, int sig, siginfo_t *info,
+			     unsigned int flags)
+{
+	return syscall(SYS_pidfd_send_signal, pidfd, sig, info, flags);
+}
+
+static int pidfd_getfd(int pidfd, int targetfd, unsigned int flags)
+{
+	return syscall(SYS_pidfd_getfd, pidfd, targetfd, flags);
+}
+
+static pid_t fork_pause(void)
+{
+	pid_t pid;
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		for (;;)
+			pause();
+	}
+
+	return pid;
+}
+
+static int poll_pidfd(int pidfd, int timeout)
+{
+	struct pollfd pfd = { .fd = pidfd, .events = POLLIN };
+
+	return poll(&pfd, 1, timeout);
+}
+
+FN_TEST(open_invalid)
+{
+	int fd;
+
+	TEST_ERRNO(pidfd_open(0, 0), EINVAL);
+	TEST_ERRNO(pidfd_open(getpid(), 1), EINVAL);
+	TEST_ERRNO(pidfd_open(0x3fffffff, 0), ESRCH);
+
+	fd = TEST_SUCC(open("/dev/null", O_RDONLY));
+	TEST_ERRNO(pidfd_send_signal(fd, 0, NULL, 0), EBADF);
+	TEST_ERRNO(pidfd_getfd(fd, 0, 0), EBADF);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(open_flags)
+{
+	int pidfd;
+
+	pidfd = TEST_SUCC(pidfd_open(getpid(), 0));
+	TEST_RES(fcntl(pidfd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(fcntl(pidfd, F_GETFL) & O_NONBLOCK, _ret == 0);
+	TEST_SUCC(close(pidfd));
+
+	pidfd = TEST_SUCC(pidfd_open(getpid(), O_NONBLOCK));
+	TEST_RES(fcntl(pidfd, F_GETFL) & O_NONBLOCK, _ret == O_NONBLOCK);
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
+
+FN_TEST(send_signal_and_wait)
+{
+	pid_t pid;
+	int pidfd;
+	siginfo_t info;
+
+	pid = TEST_SUCC(fork_pause());
+	pidfd = TEST_SUCC(pidfd_open(pid, 0));
+
+	TEST_RES(poll_pidfd(pidfd, 0), _ret == 0);
+	TEST_ERRNO(pidfd_send_signal(pidfd, SIGKILL, NULL, 0x100), EINVAL);
+	TEST_SUCC(pidfd_send_signal(pidfd, 0, NULL, 0));
+	TEST_SUCC(pidfd_send_signal(pidfd, SIGKILL, NULL, 0));
+
+	TEST_RES(poll_pidfd(pidfd, -1), _ret == 1);
+	TEST_RES(waitid(P_PIDFD, pidfd, &info, WEXITED),
+		 _ret == 0 && info.si_pid == pid &&
+			 info.si_code == CLD_KILLED &&
+			 info.si_status == SIGKILL);
+
+	// The process has been reaped, but the PID file still refers to it.
+	TEST_ERRNO(pidfd_send_signal(pidfd, SIGKILL, NULL, 0), ESRCH);
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
+
+FN_TEST(send_signal_siginfo)
+{
+	pid_t pid;
+	int pidfd, status;
+	siginfo_t info;
+
+	pid = TEST_SUCC(fork_pause());
+	pidfd = TEST_SUCC(pidfd_open(pid, 0));
+
+	memset(&info, 0, sizeof(info));
+	info.si_signo = SIGKILL;
+	info.si_code = SI_USER;
+	TEST_ERRNO(pidfd_send_signal(pidfd, SIGKILL, &info, 0), EPERM);
+
+	info.si_signo = SIGTERM;
+	info.si_code = SI_QUEUE;
+	TEST_ERRNO(pidfd_send_signal(pidfd, SIGKILL, &info, 0), EINVAL);
+
+	info.si_signo = SIGKILL;
+	TEST_SUCC(pidfd_send_signal(pidfd, SIGKILL, &info, 0));
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
+
+FN_TEST(getfd)
+{
+	int fds[2], sync_fds[2];
+	pid_t pid;
+	int pidfd, fd;
+	char buf[4];
+
+	TEST_SUCC(pipe(fds));
+	TEST_SUCC(pipe(sync_fds));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(dup2(fds[1], 100));
+		CHECK(close(fds[0]));
+		CHECK(close(fds[1]));
+		CHECK(write(sync_fds[1], "x", 1));
+		for (;;)
+			pause();
+	}
+	TEST_SUCC(close(fds[1]));
+	TEST_RES(read(sync_fds[0], buf, 1), _ret == 1);
+
+	pidfd = TEST_SUCC(pidfd_open(pid, 0));
+	TEST_ERRNO(pidfd_getfd(pidfd, 100, 1), EINVAL);
+	TEST_ERRNO(pidfd_getfd(pidfd, 101, 0), EBADF);
+
+	fd = TEST_SUCC(pidfd_getfd(pidfd, 100, 0));
+	TEST_RES(fcntl(fd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(write(fd, "abc", 3), _ret == 3);
+	TEST_RES(read(fds[0], buf, 3),
+		 _ret == 3 && memcmp(buf, "abc", 3) == 0);
+
+	TEST_SUCC(pidfd_send_signal(pidfd, SIGKILL, NULL, 0));
+	TEST_RES(waitpid(pid, NULL, 0), _ret == pid);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(close(pidfd));
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(sync_fds[0]));
+	TEST_SUCC(close(sync_fds[1]));
+}
+END_TEST()
+
+FN_TEST(getfd_not_dumpable)
+{
+	int sync_fds[2];
+	pid_t pid, child;
+	int pidfd, fd, status;
+	char buf[1];
+
+	TEST_SUCC(pipe(sync_fds));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_DUMPABLE, 0, 0, 0, 0));
+		CHECK(write(sync_fds[1], "x", 1));
+		for (;;)
+			pause();
+	}
+	TEST_RES(read(sync_fds[0], buf, 1), _ret == 1);
+	pidfd = TEST_SUCC(pidfd_open(pid, 0));
+
+	// Without `CAP_SYS_PTRACE`, the files of a non-dumpable process cannot
+	// be accessed, even if it has the same user IDs.
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		drop_effective_cap(CAP_SYS_PTRACE);
+		if (pidfd_getfd(pidfd, sync_fds[1], 0) >= 0 || errno != EPERM)
+			_exit(EXIT_FAILURE);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	// With `CAP_SYS_PTRACE`, the files can be accessed.
+	fd = TEST_SUCC(pidfd_getfd(pidfd, sync_fds[1], 0));
+
+	TEST_SUCC(pidfd_send_signal(pidfd, SIGKILL, NULL, 0));
+	TEST_RES(waitpid(pid, NULL, 0), _ret == pid);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(close(pidfd));
+	TEST_SUCC(close(sync_fds[0]));
+	TEST_SUCC(close(sync_fds[1]));
+}
+END_TEST()
+
+FN_TEST(clone_pidfd)
+{
+	int pidfd = -1, ptid;
+	pid_t pid;
+	siginfo_t info;
+
+	TEST_ERRNO(syscall(SYS_clone,
+			   CLONE_PIDFD | CLONE_PARENT_SETTID | SIGCHLD, 0,
+			   &ptid, NULL, 0),
+		   EINVAL);
+
+	pid = TEST_SUCC(syscall(SYS_clone, CLONE_PIDFD | SIGCHLD, 0, &pidfd,
+				NULL, 0));
+	if (pid == 0)
+		_exit(7);
+
+	TEST_RES(pidfd, _ret >= 0);
+	TEST_RES(fcntl(pidfd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(waitid(P_PIDFD, pidfd, &info, WEXITED),
+		 _ret == 0 && info.si_pid == pid &&
+			 info.si_code == CLD_EXITED && info.si_status == 7);
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
+
+FN_TEST(clone3_pidfd)
+{
+	int pidfd = -1;
+	struct clone_args args = {
+		.flags = CLONE_PIDFD,
+		.pidfd = (uintptr_t)&pidfd,
+		.exit_signal = SIGCHLD,
+	};
+	pid_t pid;
+
+	pid = TEST_SUCC(syscall(SYS_clone3, &args, sizeof(args)));
+	if (pid == 0)
+		_exit(0);
+
+	TEST_RES(pidfd, _ret >= 0);
+	TEST_RES(poll_pidfd(pidfd, -1), _ret == 1);
+	TEST_RES(waitpid(pid, NULL, 0), _ret == pid);
+	TEST_SUCC(close(pidfd));
+}
+END_TEST()
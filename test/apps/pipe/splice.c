@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <string.h>
+#include <sys/uio.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PAGE_SIZE 4096
+#define FILE_SIZE (3 * PAGE_SIZE + 100)
+
+#define SRC_FILE "/ext2/splice_src"
+#define DST_FILE "/ext2/splice_dst"
+
+static char data[FILE_SIZE];
+static char buf[FILE_SIZE];
+static int src_fd, dst_fd;
+static int rfd, wfd;
+
+FN_SETUP(files)
+{
+	int i;
+
+	for (i = 0; i < FILE_SIZE; ++i)
+		data[i] = i * 7 % 251;
+
+	src_fd = CHECK(open(SRC_FILE, O_RDWR | O_CREAT | O_TRUNC, 0644));
+	CHECK_WITH(write(src_fd, data, FILE_SIZE), _ret == FILE_SIZE);
+	CHECK(lseek(src_fd, 0, SEEK_SET));
+
+	dst_fd = CHECK(open(DST_FILE, O_RDWR | O_CREAT | O_TRUNC, 0644));
+}
+END_SETUP()
+
+FN_SETUP(pipe)
+{
+	int fildes[2];
+
+	CHECK(pipe(fildes));
+	rfd = fildes[0];
+	wfd = fildes[1];
+}
+END_SETUP()
+
+FN_TEST(splice_file_to_pipe)
+{
+	loff_t off = 100;
+
+	TEST_RES(splice(src_fd, &off, wfd, NULL, 5000, 0),
+		 _ret == 5000 && off == 5100);
+	TEST_RES(lseek(src_fd, 0, SEEK_CUR), _ret == 0);
+	TEST_RES(read(rfd, buf, sizeof(buf)),
+		 _ret == 5000 && memcmp(buf, data + 100, 5000) == 0);
+
+	TEST_SUCC(lseek(src_fd, 10, SEEK_SET));
+	TEST_RES(splice(src_fd, NULL, wfd, NULL, 20, 0), _ret == 20);
+	TEST_RES(lseek(src_fd, 0, SEEK_CUR), _ret == 30);
+	TEST_RES(read(rfd, buf, sizeof(buf)),
+		 _ret == 20 && memcmp(buf, data + 10, 20) == 0);
+
+	off = FILE_SIZE - 50;
+	TEST_RES(splice(src_fd, &off, wfd, NULL, 100, 0),
+		 _ret == 50 && off == FILE_SIZE);
+	TEST_RES(splice(src_fd, &off, wfd, NULL, 100, 0), _ret == 0);
+	TEST_RES(read(rfd, buf, sizeof(buf)),
+		 _ret == 50 && memcmp(buf, data + FILE_SIZE - 50, 50) == 0);
+}
+END_TEST()
+
+FN_TEST(splice_pipe_to_file)
+{
+	loff_t off = 10;
+
+	TEST_RES(write(wfd, data, PAGE_SIZE + 10), _ret == PAGE_SIZE + 10);
+	TEST_RES(splice(rfd, NULL, dst_fd, &off, PAGE_SIZE + 10, 0),
+		 _ret == PAGE_SIZE + 10 && off == PAGE_SIZE + 20);
+	TEST_RES(lseek(dst_fd, 0, SEEK_CUR), _ret == 0);
+	TEST_RES(pread(dst_fd, buf, sizeof(buf), 10),
+		 _ret == PAGE_SIZE + 10 &&
+			 memcmp(buf, data, PAGE_SIZE + 10) == 0);
+
+	TEST_RES(write(wfd, "hello", 5), _ret == 5);
+	TEST_RES(splice(rfd, NULL, dst_fd, NULL, 100, 0), _ret == 5);
+	TEST_RES(lseek(dst_fd, 0, SEEK_CUR), _ret == 5);
+	TEST_RES(pread(dst_fd, buf, 5, 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(splice_pipe_to_pipe)
+{
+	int fildes[2];
+
+	TEST_SUCC(pipe(fildes));
+
+	TEST_RES(write(wfd, "hello", 5), _ret == 5);
+	TEST_RES(splice(rfd, NULL, fildes[1], NULL, 3, 0), _ret == 3);
+	TEST_RES(read(fildes[0], buf, sizeof(buf)),
+		 _ret == 3 && memcmp(buf, "hel", 3) == 0);
+	TEST_RES(read(rfd, buf, sizeof(buf)),
+		 _ret == 2 && memcmp(buf, "lo", 2) == 0);
+
+	TEST_RES(write(wfd, "world", 5), _ret == 5);
+	TEST_RES(tee(rfd, fildes[1], 100, 0), _ret == 5);
+	TEST_RES(read(fildes[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+	TEST_RES(read(rfd, buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+
+	TEST_ERRNO(splice(rfd, NULL, fildes[1], NULL, 5, SPLICE_F_NONBLOCK),
+		   EAGAIN);
+	TEST_ERRNO(tee(rfd, fildes[1], 5, SPLICE_F_NONBLOCK), EAGAIN);
+
+	TEST_SUCC(close(fildes[1]));
+	TEST_RES(splice(fildes[0], NULL, wfd, NULL, 5, 0), _ret == 0);
+	TEST_SUCC(close(fildes[0]));
+}
+END_TEST()
+
+FN_TEST(vmsplice)
+{
+	struct iovec iov[2] = {
+		{ .iov_base = "hello ", .iov_len = 6 },
+		{ .iov_base = "world", .iov_len = 5 },
+	};
+	char out1[4], out2[8];
+	struct iovec out_iov[2] = {
+		{ .iov_base = out1, .iov_len = sizeof(out1) },
+		{ .iov_base = out2, .iov_len = sizeof(out2) },
+	};
+
+	TEST_RES(vmsplice(wfd, iov, 2, 0), _ret == 11);
+	TEST_RES(vmsplice(rfd, out_iov, 2, 0),
+		 _ret == 11 && memcmp(out1, "hell", 4) == 0 &&
+			 memcmp(out2, "o world", 7) == 0);
+
+	TEST_ERRNO(vmsplice(rfd, out_iov, 2, SPLICE_F_NONBLOCK), EAGAIN);
+	TEST_ERRNO(vmsplice(src_fd, iov, 2, 0), EBADF);
+}
+END_TEST()
+
+FN_TEST(splice_errors)
+{
+	loff_t off = 0;
+
+	TEST_ERRNO(splice(src_fd, NULL, dst_fd, NULL, 5, 0), EINVAL);
+	TEST_ERRNO(splice(src_fd, NULL, wfd, &off, 5, 0), ESPIPE);
+	TEST_ERRNO(splice(rfd, &off, dst_fd, NULL, 5, 0), ESPIPE);
+	TEST_ERRNO(splice(src_fd, NULL, wfd, NULL, 5, 0x100), EINVAL);
+	TEST_ERRNO(splice(wfd, NULL, dst_fd, NULL, 5, 0), EBADF);
+	TEST_ERRNO(splice(rfd, NULL, src_fd + 100, NULL, 5, 0), EBADF);
+	TEST_ERRNO(splice(rfd, NULL, dst_fd, NULL, 5, SPLICE_F_NONBLOCK),
+		   EAGAIN);
+
+	TEST_ERRNO(tee(rfd, wfd, 5, 0), EINVAL);
+	TEST_ERRNO(tee(src_fd, wfd, 5, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(copy_file_range)
+{
+	loff_t off_in = 100, off_out = 200;
+
+	TEST_SUCC(ftruncate(dst_fd, 0));
+
+	TEST_RES(copy_file_range(src_fd, &off_in, dst_fd, &off_out,
+				 FILE_SIZE, 0),
+		 _ret == FILE_SIZE - 100 && off_in == FILE_SIZE &&
+			 off_out == FILE_SIZE + 100);
+	TEST_RES(pread(dst_fd, buf, sizeof(buf), 200),
+		 _ret == FILE_SIZE - 100 &&
+			 memcmp(buf, data + 100, FILE_SIZE - 100) == 0);
+	TEST_RES(pread(dst_fd, buf, 200, 0),
+		 _ret == 200 && buf[0] == 0 && buf[199] == 0);
+
+	TEST_SUCC(lseek(src_fd, 0, SEEK_SET));
+	TEST_SUCC(lseek(dst_fd, 0, SEEK_SET));
+	TEST_RES(copy_file_range(src_fd, NULL, dst_fd, NULL, 10, 0),
+		 _ret == 10);
+	TEST_RES(lseek(src_fd, 0, SEEK_CUR), _ret == 10);
+	TEST_RES(lseek(dst_fd, 0, SEEK_CUR), _ret == 10);
+	TEST_RES(pread(dst_fd, buf, 10, 0),
+		 _ret == 10 && memcmp(buf, data, 10) == 0);
+
+	off_in = FILE_SIZE;
+	TEST_RES(copy_file_range(src_fd, &off_in, dst_fd, NULL, 10, 0),
+		 _ret == 0);
+}
+END_TEST()
+
+FN_TEST(copy_file_range_errors)
+{
+	loff_t off_in = 0, off_out = 10;
+	int fd;
+
+	TEST_ERRNO(copy_file_range(src_fd, NULL, dst_fd, NULL, 10, 1),
+		   EINVAL);
+	TEST_ERRNO(copy_file_range(src_fd, &off_in, src_fd, &off_out, 20, 0),
+		   EINVAL);
+	TEST_ERRNO(copy_file_range(rfd, NULL, dst_fd, NULL, 10, 0), EINVAL);
+
+	fd = TEST_SUCC(open(DST_FILE, O_RDONLY));
+	TEST_ERRNO(copy_file_range(src_fd, NULL, fd, NULL, 10, 0), EBADF);
+	TEST_SUCC(close(fd));
+
+	fd = TEST_SUCC(open(DST_FILE, O_WRONLY | O_APPEND));
+	TEST_ERRNO(copy_file_range(src_fd, NULL, fd, NULL, 10, 0), EBADF);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(rfd));
+	CHECK(close(wfd));
+	CHECK(close(src_fd));
+	CHECK(close(dst_fd));
+	CHECK(unlink(SRC_FILE));
+	CHECK(unlink(DST_FILE));
+}
+END_SETUP()
@@ -0,0 +1,409 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <asm/prctl.h>
+#include <signal.h>
+#include <stddef.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/prctl.h>
+#include <sys/ptrace.h>
+#include <sys/syscall.h>
+#include <sys/user.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+#include "../common/capability.h"
+
+static volatile long value = 1;
+
+static pid_t fork_traceme(void)
+{
+	pid_t pid;
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, NULL, NULL));
+		CHECK(raise(SIGSTOP));
+		// This is the syscall traced in `syscall_stops`.
+		CHECK(getpid());
+		_exit(value);
+	}
+
+	return pid;
+}
+
+static pid_t fork_pause(void)
+{
+	pid_t pid;
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		for (;;)
+			pause();
+	}
+
+	return pid;
+}
+
+FN_TEST(traceme_peek_poke)
+{
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork_traceme());
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_RES(ptrace(PTRACE_PEEKDATA, pid, &value, NULL), _ret == 1);
+	TEST_SUCC(ptrace(PTRACE_POKEDATA, pid, &value, (void *)42));
+	TEST_RES(ptrace(PTRACE_PEEKDATA, pid, &value, NULL), _ret == 42);
+	TEST_RES(value, _ret == 1);
+	TEST_ERRNO(ptrace(0x1234, pid, NULL, NULL), EIO);
+
+#ifdef __x86_64__
+	struct user_regs_struct regs;
+
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+	TEST_RES(ptrace(PTRACE_PEEKUSER, pid,
+			offsetof(struct user_regs_struct, rip), NULL),
+		 _ret == regs.rip);
+#endif
+
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 42);
+}
+END_TEST()
+
+#ifdef __x86_64__
+// The system calls are made without glibc, which cannot work after the
+// tracer changes the FS base.
+static long raw_syscall2(long num, long arg0, long arg1)
+{
+	long ret;
+
+	asm volatile("syscall"
+		     : "=a"(ret)
+		     : "a"(num), "D"(arg0), "S"(arg1)
+		     : "rcx", "r11", "memory");
+	return ret;
+}
+
+static void raw_stop(void)
+{
+	raw_syscall2(SYS_kill, raw_syscall2(SYS_getpid, 0, 0), SIGSTOP);
+}
+
+static char fake_tls[4096];
+static volatile unsigned long seen_fs_base;
+
+FN_TEST(setregs)
+{
+	struct user_regs_struct regs, bad_regs;
+	unsigned long orig_fs_base;
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, NULL, NULL));
+		raw_stop();
+		raw_syscall2(SYS_arch_prctl, ARCH_GET_FS,
+			     (long)&seen_fs_base);
+		raw_stop();
+		_exit(value);
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+	orig_fs_base = regs.fs_base;
+
+	bad_regs = regs;
+	bad_regs.fs_base = 0xffff800000000000UL;
+	TEST_ERRNO(ptrace(PTRACE_SETREGS, pid, NULL, &bad_regs), EIO);
+	bad_regs = regs;
+	bad_regs.gs_base = regs.gs_base + 0x1000;
+	TEST_ERRNO(ptrace(PTRACE_SETREGS, pid, NULL, &bad_regs), EIO);
+
+	// The new FS base must survive the context switches of the tracee.
+	regs.fs_base = (unsigned long)fake_tls;
+	TEST_SUCC(ptrace(PTRACE_SETREGS, pid, NULL, &regs));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+	TEST_RES(ptrace(PTRACE_PEEKDATA, pid, &seen_fs_base, NULL),
+		 _ret == (long)fake_tls);
+	TEST_RES(ptrace(PTRACE_PEEKUSER, pid,
+			offsetof(struct user_regs_struct, fs_base), NULL),
+		 _ret == (long)fake_tls);
+
+	// Restore the FS base so that the tracee can use glibc again.
+	TEST_SUCC(ptrace(PTRACE_POKEUSER, pid,
+			 offsetof(struct user_regs_struct, fs_base),
+			 orig_fs_base));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 1);
+}
+END_TEST()
+
+FN_TEST(singlestep)
+{
+	struct user_regs_struct regs;
+	unsigned long last_rip;
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, NULL, NULL));
+		raw_stop();
+		_exit(value);
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+	last_rip = regs.rip;
+	for (int i = 0; i < 3; i++) {
+		TEST_SUCC(ptrace(PTRACE_SINGLESTEP, pid, NULL, NULL));
+		TEST_RES(waitpid(pid, &status, 0),
+			 _ret == pid && WIFSTOPPED(status) &&
+				 WSTOPSIG(status) == SIGTRAP);
+		TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+		TEST_RES(regs.rip, _ret != last_rip);
+		last_rip = regs.rip;
+	}
+
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 1);
+}
+END_TEST()
+
+static __attribute__((noinline)) int breakpoint_target(void)
+{
+	return value + 1;
+}
+
+// This is how debuggers like GDB set a breakpoint and step over it.
+FN_TEST(breakpoint)
+{
+	struct user_regs_struct regs;
+	long orig_word;
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(ptrace(PTRACE_TRACEME, 0, NULL, NULL));
+		CHECK(raise(SIGSTOP));
+		_exit(breakpoint_target());
+	}
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	// Insert an `int3` instruction.
+	orig_word = TEST_SUCC(
+		ptrace(PTRACE_PEEKTEXT, pid, breakpoint_target, NULL));
+	TEST_SUCC(ptrace(PTRACE_POKETEXT, pid, breakpoint_target,
+			 (orig_word & ~0xffL) | 0xcc));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGTRAP);
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, NULL, &regs));
+	TEST_RES(regs.rip, _ret == (unsigned long)breakpoint_target + 1);
+
+	// Remove the breakpoint and rewind the instruction pointer.
+	TEST_SUCC(ptrace(PTRACE_POKETEXT, pid, breakpoint_target, orig_word));
+	regs.rip = (unsigned long)breakpoint_target;
+	TEST_SUCC(ptrace(PTRACE_SETREGS, pid, NULL, &regs));
+	TEST_SUCC(ptrace(PTRACE_SINGLESTEP, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGTRAP);
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 2);
+}
+END_TEST()
+#endif
+
+FN_TEST(syscall_stops)
+{
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork_traceme());
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_SUCC(ptrace(PTRACE_SETOPTIONS, pid, NULL,
+			 (void *)PTRACE_O_TRACESYSGOOD));
+
+	// Syscall-enter-stop
+	TEST_SUCC(ptrace(PTRACE_SYSCALL, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == (SIGTRAP | 0x80));
+#ifdef __x86_64__
+	TEST_RES(ptrace(PTRACE_PEEKUSER, pid,
+			offsetof(struct user_regs_struct, orig_rax), NULL),
+		 _ret == SYS_getpid);
+#endif
+
+	// Syscall-exit-stop
+	TEST_SUCC(ptrace(PTRACE_SYSCALL, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == (SIGTRAP | 0x80));
+
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 1);
+}
+END_TEST()
+
+FN_TEST(signal_injection)
+{
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork_traceme());
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, NULL, (void *)SIGTERM));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGTERM);
+}
+END_TEST()
+
+FN_TEST(attach_detach)
+{
+	pid_t pid;
+	int status;
+
+	pid = TEST_SUCC(fork_pause());
+
+	TEST_SUCC(ptrace(PTRACE_ATTACH, pid, NULL, NULL));
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, pid, NULL, NULL), EPERM);
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_SUCC(ptrace(PTRACE_DETACH, pid, NULL, NULL));
+	TEST_ERRNO(ptrace(PTRACE_CONT, pid, NULL, NULL), ESRCH);
+
+	TEST_SUCC(kill(pid, SIGKILL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
+
+FN_TEST(seize_interrupt)
+{
+	pid_t pid;
+	int status;
+	siginfo_t info;
+
+	pid = TEST_SUCC(fork_pause());
+
+	TEST_ERRNO(ptrace(PTRACE_SEIZE, pid, NULL, (void *)-1), EIO);
+	TEST_SUCC(ptrace(PTRACE_SEIZE, pid, NULL, NULL));
+	TEST_ERRNO(ptrace(PTRACE_CONT, pid, NULL, NULL), ESRCH);
+
+	TEST_SUCC(ptrace(PTRACE_INTERRUPT, pid, NULL, NULL));
+	TEST_RES(waitid(P_PID, pid, &info, WSTOPPED | WNOWAIT),
+		 info.si_pid == pid && info.si_code == CLD_TRAPPED);
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGTRAP &&
+			 (status >> 16) == PTRACE_EVENT_STOP);
+
+	TEST_SUCC(kill(pid, SIGKILL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
+
+static pid_t fork_not_dumpable(void)
+{
+	int sync_fds[2];
+	pid_t pid;
+	char buf[1];
+
+	CHECK(pipe(sync_fds));
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_DUMPABLE, 0, 0, 0, 0));
+		CHECK(write(sync_fds[1], "x", 1));
+		for (;;)
+			pause();
+	}
+
+	// Wait until the child has become non-dumpable.
+	CHECK_WITH(read(sync_fds[0], buf, 1), _ret == 1);
+	CHECK(close(sync_fds[0]));
+	CHECK(close(sync_fds[1]));
+
+	return pid;
+}
+
+FN_TEST(attach_not_dumpable)
+{
+	pid_t pid, child;
+	int status;
+
+	pid = TEST_SUCC(fork_not_dumpable());
+
+	// Without `CAP_SYS_PTRACE`, a non-dumpable process cannot be traced,
+	// even if it has the same user IDs.
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		drop_effective_cap(CAP_SYS_PTRACE);
+		if (ptrace(PTRACE_ATTACH, pid, NULL, NULL) == 0 ||
+		    errno != EPERM)
+			_exit(EXIT_FAILURE);
+		_exit(EXIT_SUCCESS);
+	}
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	// With `CAP_SYS_PTRACE`, the process can be traced.
+	TEST_SUCC(ptrace(PTRACE_ATTACH, pid, NULL, NULL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 WSTOPSIG(status) == SIGSTOP);
+
+	TEST_SUCC(kill(pid, SIGKILL));
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSIGNALED(status) &&
+			 WTERMSIG(status) == SIGKILL);
+}
+END_TEST()
+
+FN_TEST(errors)
+{
+	TEST_ERRNO(ptrace(PTRACE_ATTACH, getpid(), NULL, NULL), EPERM);
+	TEST_ERRNO(ptrace(PTRACE_CONT, getppid(), NULL, NULL), ESRCH);
+	TEST_ERRNO(ptrace(PTRACE_CONT, 0x7fffffff, NULL, NULL), ESRCH);
+}
+END_TEST()
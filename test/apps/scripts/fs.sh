@@ -63,5 +63,12 @@ echo "All fdatasync test passed."
 
 pipe/pipe_err
 pipe/short_rw
+pipe/splice
 epoll/epoll_err
 epoll/poll_err
+inotify/inotify
+block/block_dev
+ext3/journal
+device/evdev
+device/framebuffer
+device/mknod
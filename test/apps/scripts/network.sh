@@ -32,9 +32,13 @@ sleep 0.2
 ./tcp_err
 ./tcp_poll
 ./udp_err
+./ipv6
 ./unix_err
+./unix_dgram
+./unix_scm
 
 ./netlink_route
 ./rtnl_err
+./rtnl_addr
 
 echo "All network test passed"
@@ -10,10 +10,12 @@ cd ${SCRIPT_DIR}/..
 echo "Start process test......"
 # These test programs are sorted by name.
 tests="
+cgroup/cgroup
 clone3/clone_exit_signal
 clone3/clone_files
 clone3/clone_no_exit_signal
 clone3/clone_process
+coredump/coredump
 cpu_affinity/cpu_affinity
 execve/execve
 exit/exit_code
@@ -24,17 +26,30 @@ fork_c/fork
 getpid/getpid
 hello_pie/hello
 hello_world/hello_world
+io_uring/io_uring
 itimer/setitimer
 itimer/timer_create
+liburing/run_test.sh
 mmap/mmap_and_fork
 mmap/mmap_shared_filebacked
 mmap/mmap_readahead
+mmap/mremap
+msg/posix_mqueue
+msg/sysv_msg
+namespace/namespace
+oom/oom_score_adj
+pidfd/pidfd
+ptrace/ptrace
 pthread/pthread_test
 pty/open_pty
 sched/sched_attr
+seccomp/seccomp
+shm/memfd
 shm/posix_shm
+shm/sysv_shm
 signal_c/parent_death_signal
 signal_c/signal_test
+swap/swap
 "
 
 for testcase in ${tests}
@@ -0,0 +1,25 @@
+#!/bin/sh
+
+# SPDX-License-Identifier: MPL-2.0
+
+set -e
+
+STRACE_LOG=/tmp/strace.log
+
+echo "Start strace test......"
+
+strace -f -o ${STRACE_LOG} /bin/sh -c "echo hello > /dev/null"
+grep -q 'execve("/bin/sh"' ${STRACE_LOG}
+grep -q 'write(1, "hello\\n", 6) = 6' ${STRACE_LOG}
+grep -q '+++ exited with 0 +++' ${STRACE_LOG}
+
+# The system call is skipped if it is injected with an error.
+if strace -o ${STRACE_LOG} -e inject=openat:error=ENOENT:when=1+ -e trace=openat \
+    /bin/cat /proc/self/stat > /dev/null 2>&1; then
+    echo "Error: the injected error is ignored."
+    exit 1
+fi
+grep -q 'openat(.*= -1 ENOENT (No such file or directory) (INJECTED)' ${STRACE_LOG}
+
+rm -f ${STRACE_LOG}
+echo "All strace test passed."
@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <signal.h>
+#include <stddef.h>
+#include <string.h>
+#include <sys/prctl.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+#include <linux/audit.h>
+#include <linux/filter.h>
+#include <linux/seccomp.h>
+
+#include "../common/test.h"
+
+#if defined(__x86_64__)
+#define TEST_AUDIT_ARCH AUDIT_ARCH_X86_64
+#elif defined(__riscv) && __riscv_xlen == 64
+#define TEST_AUDIT_ARCH AUDIT_ARCH_RISCV64
+#else
+#error "unsupported architecture"
+#endif
+
+#ifndef SYS_SECCOMP
+#define SYS_SECCOMP 1
+#endif
+
+#define ARRAY_SIZE(a) (sizeof(a) / sizeof((a)[0]))
+
+// Returns `action` for the system call `sysno` and allows all the others.
+#define FILTER_SYSCALL(sysno, action)                                        \
+	{                                                                    \
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS,                           \
+			 offsetof(struct seccomp_data, arch)),               \
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, TEST_AUDIT_ARCH, 1, 0),  \
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_KILL_PROCESS),         \
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS,                           \
+			 offsetof(struct seccomp_data, nr)),                 \
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, (sysno), 0, 1),          \
+		BPF_STMT(BPF_RET | BPF_K, (action)),                         \
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),                \
+	}
+
+static int install_filter(struct sock_filter *filter, unsigned short len)
+{
+	struct sock_fprog prog = {
+		.len = len,
+		.filter = filter,
+	};
+
+	return syscall(SYS_seccomp, SECCOMP_SET_MODE_FILTER, 0, &prog);
+}
+
+static int wait_for_child(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	return status;
+}
+
+FN_TEST(no_new_privs)
+{
+	TEST_RES(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 0);
+	TEST_ERRNO(prctl(PR_SET_NO_NEW_PRIVS, 0, 0, 0, 0), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_NO_NEW_PRIVS, 1, 1, 0, 0), EINVAL);
+	TEST_SUCC(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+	TEST_RES(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(action_avail)
+{
+	__u32 action;
+
+	action = SECCOMP_RET_KILL_PROCESS;
+	TEST_SUCC(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action));
+	action = SECCOMP_RET_ERRNO;
+	TEST_SUCC(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action));
+	action = SECCOMP_RET_ALLOW;
+	TEST_SUCC(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action));
+	action = 0x12340000;
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action),
+		   EOPNOTSUPP);
+}
+END_TEST()
+
+FN_TEST(invalid_filter)
+{
+	struct sock_filter no_ret[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS, 0),
+	};
+	struct sock_filter bad_offset[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS, 1),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter bad_jump[] = {
+		BPF_JUMP(BPF_JMP | BPF_JA, 10, 0, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+
+	TEST_ERRNO(install_filter(no_ret, 0), EINVAL);
+	TEST_ERRNO(install_filter(no_ret, ARRAY_SIZE(no_ret)), EINVAL);
+	TEST_ERRNO(install_filter(bad_offset, ARRAY_SIZE(bad_offset)), EINVAL);
+	TEST_ERRNO(install_filter(bad_jump, ARRAY_SIZE(bad_jump)), EINVAL);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_SET_MODE_FILTER, 0x100, NULL),
+		   EINVAL);
+	TEST_RES(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(scratch_memory)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// `M[0]` is stored on the only path that reaches its load, which
+		// jumps over a `ret`.
+		struct sock_filter filter[] = {
+			BPF_STMT(BPF_LD | BPF_W | BPF_ABS,
+				 offsetof(struct seccomp_data, nr)),
+			BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, SYS_getpgid, 0, 2),
+			BPF_STMT(BPF_ST, 0),
+			BPF_JUMP(BPF_JMP | BPF_JA, 1, 0, 0),
+			BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+			BPF_STMT(BPF_LD | BPF_MEM, 0),
+			BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, SYS_getpgid, 0, 1),
+			BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ERRNO | EPERM),
+			BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+		};
+
+		if (install_filter(filter, ARRAY_SIZE(filter)) < 0)
+			_exit(1);
+		if (syscall(SYS_getpgid, 0) != -1 || errno != EPERM)
+			_exit(2);
+		_exit(0);
+	}
+	TEST_RES(wait_for_child(pid),
+		 WIFEXITED(_ret) && WEXITSTATUS(_ret) == 0);
+}
+END_TEST()
+
+FN_TEST(kill_process)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct sock_filter filter[] = FILTER_SYSCALL(
+			SYS_getppid, SECCOMP_RET_KILL_PROCESS);
+
+		if (install_filter(filter, ARRAY_SIZE(filter)) < 0)
+			_exit(1);
+		syscall(SYS_getppid);
+		_exit(2);
+	}
+	TEST_RES(wait_for_child(pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGSYS);
+}
+END_TEST()
+
+static volatile int sigsys_code;
+static volatile int sigsys_syscall;
+static volatile int sigsys_errno;
+
+static void handle_sigsys(int signum, siginfo_t *info, void *ucontext)
+{
+	sigsys_code = info->si_code;
+	sigsys_syscall = info->si_syscall;
+	sigsys_errno = info->si_errno;
+}
+
+FN_TEST(trap_and_errno)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct sock_filter trap_filter[] = FILTER_SYSCALL(
+			SYS_getppid, SECCOMP_RET_TRAP | 42);
+		struct sock_filter errno_filter[] = FILTER_SYSCALL(
+			SYS_getpgid, SECCOMP_RET_ERRNO | EPERM);
+		struct sigaction sa;
+
+		memset(&sa, 0, sizeof(sa));
+		sa.sa_sigaction = handle_sigsys;
+		sa.sa_flags = SA_SIGINFO;
+		if (sigaction(SIGSYS, &sa, NULL) < 0)
+			_exit(1);
+
+		if (install_filter(trap_filter, ARRAY_SIZE(trap_filter)) < 0 ||
+		    install_filter(errno_filter, ARRAY_SIZE(errno_filter)) < 0)
+			_exit(2);
+		if (prctl(PR_GET_SECCOMP, 0, 0, 0, 0) != SECCOMP_MODE_FILTER)
+			_exit(3);
+
+		syscall(SYS_getppid);
+		if (sigsys_code != SYS_SECCOMP ||
+		    sigsys_syscall != SYS_getppid || sigsys_errno != 42)
+			_exit(4);
+
+		if (syscall(SYS_getpgid, 0) != -1 || errno != EPERM)
+			_exit(5);
+
+		// The filters are inherited by the child processes.
+		pid = fork();
+		if (pid == 0) {
+			_exit(syscall(SYS_getpgid, 0) == -1 && errno == EPERM ?
+				      0 :
+				      1);
+		}
+		if (wait_for_child(pid) != 0)
+			_exit(6);
+
+		// Strict mode cannot be entered in the filter mode.
+		if (prctl(PR_SET_SECCOMP, SECCOMP_MODE_STRICT, 0, 0, 0) != -1 ||
+		    errno != EINVAL)
+			_exit(7);
+		_exit(0);
+	}
+	TEST_RES(wait_for_child(pid),
+		 WIFEXITED(_ret) && WEXITSTATUS(_ret) == 0);
+}
+END_TEST()
+
+FN_TEST(strict_mode)
+{
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		if (prctl(PR_SET_SECCOMP, SECCOMP_MODE_STRICT, 0, 0, 0) < 0)
+			_exit(1);
+		if (write(STDOUT_FILENO, "", 0) < 0)
+			syscall(SYS_exit, 2);
+		syscall(SYS_getpid);
+		syscall(SYS_exit, 3);
+	}
+	TEST_RES(wait_for_child(pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGKILL);
+}
+END_TEST()
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PAGE_SIZE 4096
+
+static int fd;
+static int unsealable_fd;
+
+FN_SETUP(create)
+{
+	fd = CHECK(memfd_create("test_memfd", MFD_ALLOW_SEALING | MFD_CLOEXEC));
+	unsealable_fd = CHECK(memfd_create("test_memfd", 0));
+}
+END_SETUP()
+
+FN_TEST(invalid_flags)
+{
+	TEST_ERRNO(memfd_create("test_memfd", 0x100), EINVAL);
+}
+END_TEST()
+
+FN_TEST(file_ops)
+{
+	char buf[5] = { 0 };
+
+	TEST_RES(fcntl(fd, F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(write(fd, "hello", 5), _ret == 5);
+	TEST_RES(pread(fd, buf, 5, 0), _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_SUCC(ftruncate(fd, 2 * PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(unsealable)
+{
+	TEST_RES(fcntl(unsealable_fd, F_GET_SEALS), _ret == F_SEAL_SEAL);
+	TEST_ERRNO(fcntl(unsealable_fd, F_ADD_SEALS, F_SEAL_WRITE), EPERM);
+}
+END_TEST()
+
+FN_TEST(not_memfd)
+{
+	int fds[2];
+
+	TEST_SUCC(pipe(fds));
+	TEST_ERRNO(fcntl(fds[0], F_GET_SEALS), EINVAL);
+	TEST_ERRNO(fcntl(fds[1], F_ADD_SEALS, F_SEAL_WRITE), EINVAL);
+	TEST_SUCC(close(fds[0]));
+	TEST_SUCC(close(fds[1]));
+}
+END_TEST()
+
+FN_TEST(seal_shrink_and_grow)
+{
+	TEST_RES(fcntl(fd, F_GET_SEALS), _ret == 0);
+
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_SHRINK));
+	TEST_ERRNO(ftruncate(fd, PAGE_SIZE), EPERM);
+	TEST_SUCC(ftruncate(fd, 3 * PAGE_SIZE));
+
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_GROW));
+	TEST_ERRNO(ftruncate(fd, 4 * PAGE_SIZE), EPERM);
+	TEST_ERRNO(pwrite(fd, "x", 1, 3 * PAGE_SIZE), EPERM);
+	TEST_RES(pwrite(fd, "x", 1, PAGE_SIZE), _ret == 1);
+
+	TEST_RES(fcntl(fd, F_GET_SEALS),
+		 _ret == (F_SEAL_SHRINK | F_SEAL_GROW));
+}
+END_TEST()
+
+FN_TEST(seal_write)
+{
+	char *addr;
+
+	addr = (char *)TEST_SUCC((long)mmap(NULL, PAGE_SIZE,
+					    PROT_READ | PROT_WRITE, MAP_SHARED,
+					    fd, 0));
+	TEST_ERRNO(fcntl(fd, F_ADD_SEALS, F_SEAL_WRITE), EBUSY);
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_WRITE));
+	TEST_ERRNO(write(fd, "x", 1), EPERM);
+	TEST_ERRNO((long)mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE,
+			      MAP_SHARED, fd, 0),
+		   EPERM);
+
+	addr = (char *)TEST_SUCC((long)mmap(NULL, PAGE_SIZE, PROT_READ,
+					    MAP_SHARED, fd, 0));
+	TEST_RES(addr[0], _ret == 'h');
+	TEST_ERRNO(mprotect(addr, PAGE_SIZE, PROT_READ | PROT_WRITE), EACCES);
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+
+	addr = (char *)TEST_SUCC((long)mmap(NULL, PAGE_SIZE,
+					    PROT_READ | PROT_WRITE, MAP_PRIVATE,
+					    fd, 0));
+	addr[0] = 'j';
+	TEST_RES(addr[0], _ret == 'j');
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+}
+END_TEST()
+
+FN_TEST(seal_seal)
+{
+	TEST_SUCC(fcntl(fd, F_ADD_SEALS, F_SEAL_SEAL));
+	TEST_ERRNO(fcntl(fd, F_ADD_SEALS, F_SEAL_SHRINK), EPERM);
+	TEST_RES(fcntl(fd, F_GET_SEALS),
+		 _ret == (F_SEAL_SEAL | F_SEAL_SHRINK | F_SEAL_GROW |
+			  F_SEAL_WRITE));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fd));
+	CHECK(close(unsealable_fd));
+}
+END_SETUP()
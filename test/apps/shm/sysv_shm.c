@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <stdio.h>
+#include <sys/ipc.h>
+#include <sys/mman.h>
+#include <sys/shm.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../common/test.h"
+
+#define PAGE_SIZE 4096
+#define SHM_KEY 0x5a5a
+#define SHM_SIZE (PAGE_SIZE + 100)
+
+static int shmid;
+
+FN_SETUP(create)
+{
+	shmid = CHECK(shmget(SHM_KEY, SHM_SIZE, IPC_CREAT | IPC_EXCL | 0600));
+}
+END_SETUP()
+
+FN_TEST(get)
+{
+	TEST_RES(shmget(SHM_KEY, SHM_SIZE, 0), _ret == shmid);
+	TEST_RES(shmget(SHM_KEY, 0, IPC_CREAT), _ret == shmid);
+	TEST_ERRNO(shmget(SHM_KEY, SHM_SIZE, IPC_CREAT | IPC_EXCL), EEXIST);
+	TEST_ERRNO(shmget(SHM_KEY, SHM_SIZE + PAGE_SIZE, 0), EINVAL);
+	TEST_ERRNO(shmget(SHM_KEY + 1, SHM_SIZE, 0), ENOENT);
+	TEST_ERRNO(shmget(IPC_PRIVATE, 0, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(attach_and_fork)
+{
+	char *addr;
+	struct shmid_ds ds;
+	int status;
+
+	addr = (char *)TEST_SUCC((long)shmat(shmid, NULL, 0));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_nattch == 1 && ds.shm_segsz == SHM_SIZE &&
+			 ds.shm_perm.__key == SHM_KEY &&
+			 (ds.shm_perm.mode & 0777) == 0600 &&
+			 ds.shm_lpid == getpid() && ds.shm_cpid == getpid());
+
+	addr[0] = 'a';
+	addr[SHM_SIZE - 1] = 'z';
+
+	if (CHECK(fork()) == 0) {
+		char *child_addr;
+
+		child_addr = shmat(shmid, NULL, SHM_RDONLY);
+		if (child_addr == (char *)-1 || child_addr[0] != 'a' ||
+		    child_addr[SHM_SIZE - 1] != 'z')
+			_exit(1);
+		// Write via the inherited attach.
+		addr[1] = 'b';
+		_exit(0);
+	}
+
+	TEST_RES(wait(&status), WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	TEST_RES(addr[1], _ret == 'b');
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds), ds.shm_nattch == 1);
+
+	TEST_ERRNO(shmdt(addr + 1), EINVAL);
+	TEST_ERRNO(shmdt(addr + PAGE_SIZE), EINVAL);
+	TEST_SUCC(shmdt(addr));
+	TEST_ERRNO(shmdt(addr), EINVAL);
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds), ds.shm_nattch == 0);
+}
+END_TEST()
+
+FN_TEST(fixed_address)
+{
+	char *addr;
+	char *fixed_addr;
+
+	addr = (char *)TEST_SUCC((long)shmat(shmid, NULL, 0));
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_SUCC(shmdt(addr));
+
+	fixed_addr = (char *)TEST_SUCC((long)shmat(shmid, addr + 1, SHM_RND));
+	TEST_RES(fixed_addr == addr, _ret && fixed_addr[1] == 'b');
+	TEST_SUCC(shmdt(fixed_addr));
+
+	TEST_ERRNO((long)shmat(shmid, addr + 1, 0), EINVAL);
+	TEST_ERRNO((long)shmat(shmid, NULL, SHM_REMAP), EINVAL);
+}
+END_TEST()
+
+FN_TEST(set)
+{
+	struct shmid_ds ds;
+
+	TEST_SUCC(shmctl(shmid, IPC_STAT, &ds));
+	ds.shm_perm.mode = 0640;
+	TEST_SUCC(shmctl(shmid, IPC_SET, &ds));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 (ds.shm_perm.mode & 0777) == 0640);
+}
+END_TEST()
+
+FN_TEST(proc_sysvipc)
+{
+	FILE *file;
+	char line[256];
+	int key, id;
+	int found = 0;
+
+	file = fopen("/proc/sysvipc/shm", "r");
+	TEST_RES(file != NULL, _ret);
+	// Skip the header.
+	TEST_RES(fgets(line, sizeof(line), file) != NULL, _ret);
+	while (fgets(line, sizeof(line), file) != NULL) {
+		if (sscanf(line, "%d %d", &key, &id) == 2 && key == SHM_KEY &&
+		    id == shmid)
+			found = 1;
+	}
+	TEST_RES(found, _ret);
+	TEST_SUCC(fclose(file));
+}
+END_TEST()
+
+FN_TEST(remove)
+{
+	char *addr;
+	struct shmid_ds ds;
+
+	addr = (char *)TEST_SUCC((long)shmat(shmid, NULL, 0));
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+
+	// The segment is alive until it is detached, but its key is gone.
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_nattch == 1 && ds.shm_perm.__key == IPC_PRIVATE &&
+			 (ds.shm_perm.mode & SHM_DEST));
+	TEST_ERRNO(shmget(SHM_KEY, SHM_SIZE, 0), ENOENT);
+	TEST_RES(addr[0], _ret == 'a');
+
+	TEST_SUCC(shmdt(addr));
+	TEST_ERRNO(shmctl(shmid, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(remove_unattached)
+{
+	int id;
+	struct shmid_ds ds;
+
+	id = TEST_SUCC(shmget(IPC_PRIVATE, PAGE_SIZE, 0600));
+	TEST_SUCC(shmctl(id, IPC_RMID, NULL));
+	TEST_ERRNO(shmctl(id, IPC_STAT, &ds), EINVAL);
+	TEST_ERRNO(shmctl(id, IPC_RMID, NULL), EINVAL);
+}
+END_TEST()
+
+FN_TEST(remove_and_munmap)
+{
+	int id;
+	char *addr;
+	struct shmid_ds ds;
+
+	id = TEST_SUCC(shmget(IPC_PRIVATE, PAGE_SIZE, 0600));
+	addr = (char *)TEST_SUCC((long)shmat(id, NULL, 0));
+	TEST_SUCC(shmctl(id, IPC_RMID, NULL));
+
+	// Unmapping the segment detaches it implicitly.
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+	TEST_ERRNO(shmctl(id, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(remove_and_exit)
+{
+	int id;
+	pid_t pid;
+	int status;
+	struct shmid_ds ds;
+
+	id = TEST_SUCC(shmget(IPC_PRIVATE, PAGE_SIZE, 0600));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		if (shmat(id, NULL, 0) == (void *)-1 ||
+		    shmctl(id, IPC_RMID, NULL) < 0)
+			_exit(EXIT_FAILURE);
+		// Exiting detaches the segment implicitly.
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(wait4(pid, &status, 0, NULL),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+	TEST_ERRNO(shmctl(id, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../common/test.h"
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/shm.h>
+#include <sys/swap.h>
+#include <sys/sysinfo.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+// The swap disk is attached by QEMU with `serial=vswap`.
+#define SWAP_DISK "/dev/disk/by-id/virtio-vswap"
+
+#ifndef MADV_PAGEOUT
+#define MADV_PAGEOUT 21
+#endif
+
+#define PAGE_SIZE 4096
+#define NR_PAGES 16
+
+static char *buf;
+
+static void fill_pages(char *addr, char seed)
+{
+	for (int i = 0; i < NR_PAGES; i++)
+		memset(addr + i * PAGE_SIZE, seed + i, PAGE_SIZE);
+}
+
+static int check_pages(const char *addr, char seed)
+{
+	for (int i = 0; i < NR_PAGES; i++)
+		for (int j = 0; j < PAGE_SIZE; j++)
+			if (addr[i * PAGE_SIZE + j] != (char)(seed + i))
+				return 0;
+	return 1;
+}
+
+static unsigned long used_swap_pages(void)
+{
+	struct sysinfo info;
+
+	CHECK(sysinfo(&info));
+	return (info.totalswap - info.freeswap) * info.mem_unit / PAGE_SIZE;
+}
+
+FN_SETUP(swapon)
+{
+	CHECK(swapon(SWAP_DISK, 0));
+
+	buf = (char *)CHECK_WITH((long)mmap(NULL, NR_PAGES * PAGE_SIZE,
+					    PROT_READ | PROT_WRITE,
+					    MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				 _ret != (long)MAP_FAILED);
+}
+END_SETUP()
+
+FN_TEST(swapon_errors)
+{
+	TEST_ERRNO(swapon(SWAP_DISK, 0), EBUSY);
+	TEST_ERRNO(swapon("/dev/null", 0), ENOTBLK);
+	TEST_ERRNO(swapoff("/dev/null"), ENOTBLK);
+}
+END_TEST()
+
+FN_TEST(pageout_and_fault)
+{
+	fill_pages(buf, 'a');
+
+	TEST_SUCC(madvise(buf, NR_PAGES * PAGE_SIZE, MADV_PAGEOUT));
+	TEST_RES(used_swap_pages(), _ret == NR_PAGES);
+
+	// Faulting the pages in frees the slots in the swap area.
+	TEST_RES(check_pages(buf, 'a'), _ret);
+	TEST_RES(used_swap_pages(), _ret == 0);
+
+	// The pages are writable after being swapped in.
+	fill_pages(buf, 'A');
+	TEST_RES(check_pages(buf, 'A'), _ret);
+}
+END_TEST()
+
+FN_TEST(pageout_and_fork)
+{
+	pid_t pid;
+	int status;
+
+	fill_pages(buf, 'a');
+	TEST_SUCC(madvise(buf, NR_PAGES * PAGE_SIZE, MADV_PAGEOUT));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// The child shares the swapped-out pages with the parent.
+		if (!check_pages(buf, 'a'))
+			_exit(EXIT_FAILURE);
+		fill_pages(buf, 'b');
+		_exit(check_pages(buf, 'b') ? EXIT_SUCCESS : EXIT_FAILURE);
+	}
+
+	TEST_RES(wait4(pid, &status, 0, NULL),
+		 _ret == pid && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == EXIT_SUCCESS);
+
+	// The writes of the child are not visible to the parent.
+	TEST_RES(check_pages(buf, 'a'), _ret);
+	TEST_RES(used_swap_pages(), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(shm_lock)
+{
+	int id;
+	char *addr;
+
+	id = TEST_SUCC(shmget(IPC_PRIVATE, NR_PAGES * PAGE_SIZE, 0600));
+	addr = (char *)TEST_SUCC((long)shmat(id, NULL, 0));
+	fill_pages(addr, 'a');
+
+	// The pages of a locked segment are not swapped out.
+	TEST_SUCC(shmctl(id, SHM_LOCK, NULL));
+	TEST_SUCC(madvise(addr, NR_PAGES * PAGE_SIZE, MADV_PAGEOUT));
+	TEST_RES(used_swap_pages(), _ret == 0);
+
+	TEST_SUCC(shmctl(id, SHM_UNLOCK, NULL));
+	TEST_SUCC(madvise(addr, NR_PAGES * PAGE_SIZE, MADV_PAGEOUT));
+	TEST_RES(used_swap_pages(), _ret == NR_PAGES);
+	TEST_RES(check_pages(addr, 'a'), _ret);
+	TEST_RES(used_swap_pages(), _ret == 0);
+
+	TEST_SUCC(shmdt(addr));
+	TEST_SUCC(shmctl(id, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(swapoff)
+{
+	fill_pages(buf, 'x');
+	TEST_SUCC(madvise(buf, NR_PAGES * PAGE_SIZE, MADV_PAGEOUT));
+	TEST_RES(used_swap_pages(), _ret == NR_PAGES);
+
+	// Disabling the swap area swaps all its pages in.
+	TEST_SUCC(swapoff(SWAP_DISK));
+	TEST_RES(check_pages(buf, 'x'), _ret);
+	TEST_ERRNO(swapoff(SWAP_DISK), EINVAL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(munmap(buf, NR_PAGES * PAGE_SIZE));
+}
+END_SETUP()